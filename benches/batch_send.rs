@@ -0,0 +1,114 @@
+use std::future;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tokio::runtime::Runtime;
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission},
+    mproc::{single, SinglePointInfo},
+    test_util::{duplex_connector, serve_in_memory_with_handle, ScriptedPeer},
+    Client, ClientHandler, ClientOption, DataStore, Error,
+};
+
+// 一次总召唤响应的 ASDU 个数
+const BATCH: u16 = 50;
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+fn points() -> Vec<Asdu> {
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+    (1..=BATCH)
+        .map(|ioa| single(false, cot, 1, vec![SinglePointInfo::new_single(ioa, true)]).unwrap())
+        .collect()
+}
+
+// 从发送到对端收齐整批的时间
+fn server_broadcast(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (mut master, handle) = rt.block_on(async {
+        let (mut master, handle) = serve_in_memory_with_handle(DataStore::new());
+        master.start_dt().await.unwrap();
+        (master, handle)
+    });
+
+    let mut group = c.benchmark_group("server_broadcast_50");
+    group.bench_function("broadcast_asdu", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for asdu in points() {
+                    handle.broadcast_asdu(asdu).unwrap();
+                }
+                for _ in 0..BATCH {
+                    master.expect_asdu().await;
+                }
+            })
+        })
+    });
+    group.bench_function("broadcast_batch", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                handle.broadcast_batch(points()).unwrap();
+                for _ in 0..BATCH {
+                    master.expect_asdu().await;
+                }
+            })
+        })
+    });
+    group.finish();
+}
+
+fn client_send(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (client, mut slave) = rt.block_on(async {
+        let (connector, mut streams) = duplex_connector();
+        let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+        client.start().await.unwrap();
+        let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+        slave.accept_start_dt().await.unwrap();
+        (client, slave)
+    });
+
+    let mut group = c.benchmark_group("client_send_50");
+    group.bench_function("send_asdu", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let send = async {
+                    for asdu in points() {
+                        client.send_asdu(asdu).await.unwrap();
+                    }
+                };
+                let recv = async {
+                    for _ in 0..BATCH {
+                        slave.expect_asdu().await;
+                    }
+                };
+                tokio::join!(send, recv);
+            })
+        })
+    });
+    group.bench_function("send_asdus", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let recv = async {
+                    for _ in 0..BATCH {
+                        slave.expect_asdu().await;
+                    }
+                };
+                let (sent, _) = tokio::join!(client.send_asdus(points()), recv);
+                sent.unwrap();
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, server_broadcast, client_send);
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+use bytes::{Bytes, BytesMut};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use futures::{SinkExt, StreamExt};
+use tokio::runtime::Runtime;
+use tokio_iecp5::{
+    apci::new_iframe,
+    asdu::{Asdu, Cause, CauseOfTransmission},
+    mproc::{single, SinglePointInfo},
+    test_util::duplex_pair,
+    Codec,
+};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+// 可变结构限定词允许的最多信息元素个数
+const MAX_POINTS: u16 = 127;
+// 一次解码或回环发送的帧数
+const FRAMES: usize = 1000;
+
+// 顺序排列的最长单点信息 ASDU
+fn max_sequence() -> Asdu {
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+    let infos = (1..=MAX_POINTS)
+        .map(|ioa| SinglePointInfo::new_single(ioa, ioa % 2 == 0))
+        .collect();
+    single(true, cot, 1, infos).unwrap()
+}
+
+fn encoded_frames(n: usize) -> BytesMut {
+    let mut codec = Codec::default();
+    let mut buf = BytesMut::new();
+    for sn in 0..n {
+        let apdu = new_iframe(max_sequence(), sn as u16 % 0x8000, 0);
+        codec.encode(apdu, &mut buf).unwrap();
+    }
+    buf
+}
+
+fn apdu_decode(c: &mut Criterion) {
+    let frames = encoded_frames(FRAMES);
+    let mut group = c.benchmark_group("apdu_decode");
+    group.throughput(Throughput::Bytes(frames.len() as u64));
+    group.bench_function("max_sequence", |b| {
+        b.iter_batched(
+            || frames.clone(),
+            |mut buf| {
+                let mut codec = Codec::default();
+                while let Some(apdu) = codec.decode(&mut buf).unwrap() {
+                    criterion::black_box(apdu);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn asdu_try_from(c: &mut Criterion) {
+    let raw: Bytes = max_sequence().try_into().unwrap();
+    c.bench_function("asdu_try_from", |b| {
+        b.iter(|| Asdu::try_from(criterion::black_box(raw.clone())).unwrap())
+    });
+}
+
+fn get_single_point(c: &mut Criterion) {
+    let asdu = max_sequence();
+    let mut group = c.benchmark_group("get_single_point");
+    group.throughput(Throughput::Elements(MAX_POINTS as u64));
+    group.bench_function("max_sequence", |b| {
+        b.iter_batched(
+            || asdu.clone(),
+            |mut asdu| asdu.get_single_point().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+// 经内存传输层收发, 包括编码, 写入, 读取与解码
+fn loopback(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (local, remote) = duplex_pair();
+    let mut tx = Framed::new(local, Codec::default());
+    let mut rx = Framed::new(remote, Codec::default());
+    let mut group = c.benchmark_group("loopback");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("frames", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let send = async {
+                    for sn in 0..FRAMES {
+                        let apdu = new_iframe(max_sequence(), sn as u16, 0);
+                        tx.feed(apdu).await.unwrap();
+                    }
+                    tx.flush().await.unwrap();
+                };
+                let recv = async {
+                    for _ in 0..FRAMES {
+                        rx.next().await.unwrap().unwrap();
+                    }
+                };
+                tokio::join!(send, recv);
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    apdu_decode,
+    asdu_try_from,
+    get_single_point,
+    loopback
+);
+criterion_main!(benches);
@@ -6,14 +6,13 @@ use std::{
 };
 
 use anyhow::Result;
-use tokio::{sync::oneshot, time::sleep};
+use tokio::time::sleep;
 use tokio_iecp5::{
     asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, TypeID},
     cproc::{
         BitsString32CommandInfo, DoubleCommandInfo, SetpointCommandFloatInfo,
         SetpointCommandNormalInfo, SetpointCommandScaledInfo, SingleCommandInfo,
     },
-    csys::{ObjectQCC, ObjectQOI},
     Client, ClientHandler, ClientOption, Error,
 };
 
@@ -30,139 +29,32 @@ enum IEC104DateType {
 pub struct IEC104Client {
     /// 应用服务数据单元公共地址
     remote_addr: CommonAddr,
-    // TODO: change to mutex
-    client: Arc<Client<Arc<IEC104ClientHandler>>>,
+    client: Client<Arc<IEC104ClientHandler>>,
     inner: Arc<IEC104ClientHandler>,
-    shutdown_tx: Option<oneshot::Sender<()>>,
-    terminated_rx: Option<oneshot::Receiver<()>>,
 }
 
 impl IEC104Client {
     pub fn new(socket_addr: SocketAddr, remote_addr: CommonAddr) -> Self {
-        let op = ClientOption::new(socket_addr, true);
+        // 连接后自动 STARTDT, 激活后总召唤与时钟同步, 之后每 10 分钟总召唤, 每小时时钟同步
+        let op = ClientOption::new(socket_addr, true)
+            .with_auto_gi(remote_addr, Duration::from_secs(600))
+            .with_auto_clock_sync(remote_addr, Duration::from_secs(3600));
         let inner = Arc::new(IEC104ClientHandler::new());
-        let client = Arc::new(Client::new(inner.clone(), op));
+        let client = Client::new(inner.clone(), op);
 
         IEC104Client {
             remote_addr,
             client,
             inner,
-            shutdown_tx: None,
-            terminated_rx: None,
         }
     }
 
-    pub async fn start(&mut self) -> Result<(), Error> {
-        self.client.start().await?;
-
-        if self.shutdown_tx.is_some() {
-            return Ok(());
-        }
-
-        let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-        let (terminated_tx, terminated_rx) = oneshot::channel();
-        self.shutdown_tx = Some(shutdown_tx);
-        self.terminated_rx = Some(terminated_rx);
-
-        let client = self.client.clone();
-        let remote_addr = self.remote_addr;
-
-        tokio::spawn(async move {
-            loop {
-                if shutdown_rx.try_recv().is_ok() {
-                    terminated_tx.send(()).unwrap();
-                    break;
-                }
-                if !client.is_connected().await {
-                    // client 会自动连接
-                    // client.start().await;
-                    continue;
-                }
-                if !client.is_active().await {
-                    if client.send_start_dt().await.is_err() {
-                        continue;
-                    }
-                    log::info!("IEC104 TRIGGER: STARTDT");
-                }
-
-                sleep(Duration::from_secs(1)).await;
-
-                if client
-                    .counter_interrogation_cmd(
-                        CauseOfTransmission::new(false, false, Cause::Activation),
-                        remote_addr,
-                        ObjectQCC::new(0x05),
-                    )
-                    .await
-                    .is_err()
-                {
-                    continue;
-                }
-                log::info!("IEC104 TRIGGER: Interrogation CUM BEGIN");
-
-                sleep(Duration::from_secs(10)).await;
-
-                if client
-                    .counter_interrogation_cmd(
-                        CauseOfTransmission::new(false, false, Cause::ActivationTerm),
-                        remote_addr,
-                        ObjectQCC::new(0x05),
-                    )
-                    .await
-                    .is_err()
-                {
-                    continue;
-                }
-                log::info!("IEC104 TRIGGER: Interrogation CUM END");
-
-                if client
-                    .interrogation_cmd(
-                        CauseOfTransmission::new(false, false, Cause::Activation),
-                        remote_addr,
-                        ObjectQOI::new(20),
-                    )
-                    .await
-                    .is_err()
-                {
-                    continue;
-                }
-                log::info!("IEC104 TRIGGER: Interrogation ALL BEGIN");
-
-                sleep(Duration::from_secs(20)).await;
-
-                if client
-                    .interrogation_cmd(
-                        CauseOfTransmission::new(false, false, Cause::ActivationTerm),
-                        remote_addr,
-                        ObjectQOI::new(20),
-                    )
-                    .await
-                    .is_err()
-                {
-                    continue;
-                }
-                log::info!("IEC104 TRIGGER: Interrogation ALL END");
-
-                if client.send_stop_dt().await.is_err() {
-                    continue;
-                }
-                log::info!("IEC104 TRIGGER: STOPDT");
-                sleep(Duration::from_secs(1)).await
-            }
-        });
-
-        Ok(())
+    pub async fn start(&self) -> Result<(), Error> {
+        self.client.start().await
     }
 
-    pub async fn stop(&mut self) {
-        if let Some(tx) = self.shutdown_tx.take() {
-            tx.send(()).unwrap();
-        }
-        if let Some(rx) = self.terminated_rx.take() {
-            rx.await.unwrap();
-            // TODO:
-            // self.client.stop().await
-        }
+    pub async fn stop(&self) {
+        self.client.stop().await
     }
 
     pub fn read_siq(&self, addr: u16) -> Option<bool> {
@@ -170,25 +62,11 @@ impl IEC104Client {
     }
 
     pub async fn write_siq(&self, addr: u16, v: bool) -> Result<(), Error> {
-        let cmd = SingleCommandInfo::new(addr, v, true);
-        self.client
-            .single_cmd(
-                TypeID::C_SC_NA_1,
-                CauseOfTransmission::new(false, false, Cause::Activation),
-                self.remote_addr,
-                cmd,
-            )
-            .await?;
-
         let cmd = SingleCommandInfo::new(addr, v, false);
         self.client
-            .single_cmd(
-                TypeID::C_SC_NA_1,
-                CauseOfTransmission::new(false, false, Cause::Activation),
-                self.remote_addr,
-                cmd,
-            )
-            .await
+            .select_and_execute_single(TypeID::C_SC_NA_1, self.remote_addr, cmd)
+            .await?;
+        Ok(())
     }
 
     pub fn read_diq(&self, addr: u16) -> Option<u8> {
@@ -196,25 +74,11 @@ impl IEC104Client {
     }
 
     pub async fn write_diq(&self, addr: u16, v: u8) -> Result<(), Error> {
-        let cmd = DoubleCommandInfo::new(addr, v, true);
-        self.client
-            .double_cmd(
-                TypeID::C_DC_NA_1,
-                CauseOfTransmission::new(false, false, Cause::Activation),
-                self.remote_addr,
-                cmd,
-            )
-            .await?;
-
         let cmd = DoubleCommandInfo::new(addr, v, false);
         self.client
-            .double_cmd(
-                TypeID::C_DC_NA_1,
-                CauseOfTransmission::new(false, false, Cause::Activation),
-                self.remote_addr,
-                cmd,
-            )
-            .await
+            .select_and_execute_double(TypeID::C_DC_NA_1, self.remote_addr, cmd)
+            .await?;
+        Ok(())
     }
 
     pub fn read_nva(&self, addr: u16) -> Option<i16> {
@@ -369,17 +233,22 @@ async fn main() -> Result<(), Error> {
         .filter_level(log::LevelFilter::Debug)
         .init();
 
-    let mut client = IEC104Client::new("127.0.0.1:2404".parse().unwrap(), 1);
+    let client = IEC104Client::new("127.0.0.1:2404".parse().unwrap(), 1);
+    client.start().await?;
 
     loop {
+        tokio::select! {
+            _ = sleep(Duration::from_millis(500)) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
         println!("{:?}", client.read_siq(0));
         println!("{:?}", client.read_siq(1));
         println!("{:?}", client.read_siq(2));
         println!("{:?}", client.read_siq(3));
         println!("{:?}", client.read_diq(655));
         println!("{:?}", client.read_diq(658));
-        client.start().await?;
-        sleep(Duration::from_millis(500)).await;
         log::info!("main sleeping...");
     }
+    client.stop().await;
+    Ok(())
 }
@@ -1,103 +1,40 @@
 use anyhow::Result;
-use std::{
-    collections::HashMap,
-    future, io,
-    net::SocketAddr,
-    sync::{Arc, Mutex},
-};
+use std::{future, io, net::SocketAddr};
 
 use tokio::{
     net::{TcpListener, TcpStream},
     select,
 };
 use tokio_iecp5::{
-    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr, TypeID},
-    csys::{ObjectQCC, ObjectQOI},
-    mproc::{double, single, DoublePointInfo, ObjectSIQ, SinglePointInfo},
-    Error, Server, ServerHandler,
+    asdu::{Asdu, InfoObjAddr, TypeID},
+    server_handler_fn, DataStore, Error, Point, PointValue, Server, ServerHandler,
 };
 
-struct ExampleServer {
-    siq: Arc<Mutex<HashMap<u16, bool>>>,
-    diq: Arc<Mutex<HashMap<u16, u8>>>,
-}
-
-impl ExampleServer {
-    pub fn new(siq: HashMap<u16, bool>, diq: HashMap<u16, u8>) -> Self {
-        ExampleServer {
-            siq: Arc::new(Mutex::new(siq)),
-            diq: Arc::new(Mutex::new(diq)),
-        }
-    }
-}
-
-impl ServerHandler for ExampleServer {
-    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
-
-    fn call(&self, asdu: Asdu) -> Self::Future {
-        let mut asdu = asdu;
-        let type_id = asdu.identifier.type_id;
-        match type_id {
-            TypeID::C_SC_NA_1 | TypeID::C_SC_TA_1 => {
-                let mut single_cmd = asdu.get_single_cmd().unwrap();
-                let ad = single_cmd.ioa.addr().get();
-                let v = single_cmd.sco.scs().get();
-                if let Some(value) = self.siq.lock().unwrap().get_mut(&ad) {
-                    *value = v;
-                }
-            }
-            TypeID::C_DC_NA_1 | TypeID::C_DC_TA_1 => {
-                let mut double_cmd = asdu.get_double_cmd().unwrap();
-                let ad = double_cmd.ioa.addr().get();
-                let v = double_cmd.dco.dcs().get().value();
-                if let Some(value) = self.diq.lock().unwrap().get_mut(&ad) {
-                    *value = v;
-                }
+// 遥控命令改变 DataStore 中的状态
+fn control(store: &DataStore, asdu: Asdu) -> Result<Vec<Asdu>, Error> {
+    let mut asdu = asdu;
+    let ca = asdu.identifier.common_addr;
+    let type_id = asdu.identifier.type_id;
+    match type_id {
+        TypeID::C_SC_NA_1 | TypeID::C_SC_TA_1 => {
+            let mut single_cmd = asdu.get_single_cmd()?;
+            let ad = single_cmd.ioa.addr().get();
+            let v = single_cmd.sco.scs().get();
+            if store.get(ca, ad).is_some() {
+                let _ = store.set(ca, ad, PointValue::Single(v));
             }
-            _ => (),
-        };
-        future::ready(Ok(Vec::new()))
-    }
-
-    fn call_interrogation(&self, _: Asdu, _qoi: ObjectQOI) -> Self::Future {
-        let mut asdus = vec![];
-
-        let mut siq_infos = vec![];
-        for (addr, v) in self.siq.lock().unwrap().iter() {
-            siq_infos.push(SinglePointInfo::new(
-                InfoObjAddr::new(0, *addr),
-                ObjectSIQ::new_with_value(*v),
-                None,
-            ));
         }
-        let siq_asdu = single(
-            false,
-            CauseOfTransmission::new(false, false, Cause::InterrogatedByStation),
-            0,
-            siq_infos,
-        )
-        .unwrap();
-        asdus.push(siq_asdu);
-
-        let mut diq_infos = vec![];
-        for (addr, v) in self.diq.lock().unwrap().iter() {
-            diq_infos.push(DoublePointInfo::new_double(*addr, *v));
+        TypeID::C_DC_NA_1 | TypeID::C_DC_TA_1 => {
+            let mut double_cmd = asdu.get_double_cmd()?;
+            let ad = double_cmd.ioa.addr().get();
+            let v = double_cmd.dco.dcs().get().value();
+            if store.get(ca, ad).is_some() {
+                let _ = store.set(ca, ad, PointValue::Double(v));
+            }
         }
-        let diq_asdu = double(
-            false,
-            CauseOfTransmission::new(false, false, Cause::InterrogatedByStation),
-            0,
-            diq_infos,
-        )
-        .unwrap();
-        asdus.push(diq_asdu);
-
-        future::ready(Ok(asdus))
-    }
-
-    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
-        future::ready(Ok(Vec::new()))
-    }
+        _ => (),
+    };
+    Ok(Vec::new())
 }
 
 #[tokio::main]
@@ -134,18 +71,36 @@ async fn server(socket_addr: SocketAddr) -> Result<()> {
     println!("Starting up server on {socket_addr}");
     let listener = TcpListener::bind(socket_addr).await?;
     let server = Server::new(listener);
-    let mut siq = HashMap::new();
-    let mut diq = HashMap::new();
-    siq.insert(100, false);
-    siq.insert(111, true);
-    siq.insert(121, false);
-    diq.insert(3000, 2);
-    diq.insert(2345, 3);
-    diq.insert(4523, 3);
-    diq.insert(4524, 3);
-    diq.insert(4525, 2);
-    diq.insert(4526, 1);
-    let handler = Arc::new(ExampleServer::new(siq, diq));
+    let store = DataStore::new();
+    for (ioa, v) in [(100, false), (111, true), (121, false)] {
+        store.insert(1, ioa, Point::new(PointValue::Single(v)));
+    }
+    for (ioa, v) in [
+        (3000, 2),
+        (2345, 3),
+        (4523, 3),
+        (4524, 3),
+        (4525, 2),
+        (4526, 1),
+    ] {
+        store.insert(1, ioa, Point::new(PointValue::Double(v)));
+    }
+    // 遥控改变的状态以突发方式上送
+    store.attach(server.handle());
+    let handler = {
+        let (call_store, gi_store, read_store) = (store.clone(), store.clone(), store);
+        server_handler_fn(
+            move |asdu| future::ready(control(&call_store, asdu)),
+            move |asdu: Asdu, qoi| {
+                future::ready(gi_store.interrogation(asdu.identifier.common_addr, qoi))
+            },
+            |_, _| future::ready(Ok(Vec::new())),
+        )
+        .with_read(move |asdu: Asdu, mut ioa: InfoObjAddr| {
+            let resp = read_store.read(asdu.identifier.common_addr, ioa.addr().get());
+            future::ready(resp.map(|asdu| asdu.into_iter().collect()))
+        })
+    };
     let new_service = |_socket_addr| Ok(Some(handler.clone()));
     let on_connected = |stream, socket_addr| async move {
         accept_tcp_connection(stream, socket_addr, new_service)
@@ -0,0 +1,14 @@
+#![no_main]
+
+// Asdu::try_from 与按类型标识的解码不能 panic
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+use tokio_iecp5::asdu::Asdu;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(mut asdu) = Asdu::try_from(Bytes::copy_from_slice(data)) {
+        let _ = asdu.decode_payload();
+        let _ = asdu.to_string();
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+// 两种重新同步方式下解码任意字节流不能 panic
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use tokio_iecp5::{Codec, Resync};
+use tokio_util::codec::Decoder;
+
+fuzz_target!(|data: &[u8]| {
+    for resync in [Resync::Strict, Resync::Lenient] {
+        let mut codec = Codec::default().with_resync(resync);
+        let mut buf = BytesMut::from(data);
+        while let Ok(Some(_)) = codec.decode(&mut buf) {}
+    }
+});
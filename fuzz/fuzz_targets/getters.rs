@@ -0,0 +1,62 @@
+#![no_main]
+
+// 任意类型标识的 ASDU 调用每个 get_* 方法都不能 panic, 类型不匹配时返回错误.
+// 第一个字节选择类型标识, 其余为信息对象
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+use tokio_iecp5::asdu::Asdu;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&vsq, raw)) = data.split_first() else {
+        return;
+    };
+    for type_id in 1..=127u8 {
+        let mut buf = vec![type_id, vsq, 0x06, 0x00, 0x01, 0x00];
+        buf.extend_from_slice(raw);
+        let Ok(mut asdu) = Asdu::try_from(Bytes::from(buf)) else {
+            continue;
+        };
+        let _ = asdu.get_single_point();
+        let _ = asdu.get_double_point();
+        let _ = asdu.get_measured_value_normal();
+        let _ = asdu.get_measured_value_scaled();
+        let _ = asdu.get_measured_value_float();
+        let _ = asdu.get_integrated_totals();
+        let _ = asdu.get_packed_single_point();
+        let _ = asdu.get_single_cmd();
+        let _ = asdu.get_double_cmd();
+        let _ = asdu.get_setpoint_normal_cmd();
+        let _ = asdu.get_setpoint_scaled_cmd();
+        let _ = asdu.get_setpoint_float_cmd();
+        let _ = asdu.get_bits_string32_cmd();
+        let _ = asdu.get_interrogation_cmd();
+        let _ = asdu.get_counter_interrogation_cmd();
+        let _ = asdu.get_read_cmd();
+        let _ = asdu.get_clock_synchronization_cmd();
+        let _ = asdu.get_clock_synchronization_time();
+        let _ = asdu.get_delay_acquire_cmd();
+        let _ = asdu.get_reset_process_cmd();
+        let _ = asdu.get_parameter_normal();
+        let _ = asdu.get_parameter_scaled();
+        let _ = asdu.get_parameter_float();
+        let _ = asdu.get_parameter_activation();
+        let _ = asdu.get_end_of_initialization();
+        let _ = asdu.get_file_ready();
+        let _ = asdu.get_section_ready();
+        let _ = asdu.get_file_call();
+        let _ = asdu.get_last_section();
+        let _ = asdu.get_file_ack();
+        let _ = asdu.get_segment();
+        let _ = asdu.get_directory();
+        let _ = asdu.get_authentication_challenge();
+        let _ = asdu.get_authentication_reply();
+        let _ = asdu.get_aggressive_mode_request();
+        let _ = asdu.get_key_status_request();
+        let _ = asdu.get_key_status();
+        let _ = asdu.get_session_key_change();
+        let _ = asdu.get_authentication_error();
+        let _ = asdu.get_security_statistic();
+        let _ = asdu.decode_payload();
+    }
+});
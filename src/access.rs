@@ -0,0 +1,151 @@
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+use futures::{future::BoxFuture, FutureExt};
+
+use crate::{asdu::CommonAddr, Role};
+
+// CIDR 网段, 如 10.0.0.0/8, fe80::/10; 不带前缀长度时为单个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn new(addr: IpAddr, prefix: u8) -> anyhow::Result<Self> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(anyhow!("prefix length {prefix} exceeds {max}"));
+        }
+        Ok(Cidr { addr, prefix })
+    }
+
+    // IPv4 映射的 IPv6 地址(::ffff:a.b.c.d)按 IPv4 匹配
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+            ip => ip,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.trim().parse()?;
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse()?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+        Cidr::new(addr, prefix)
+    }
+}
+
+// 按对端 IP 的访问控制: 命中拒绝列表的连接被拒绝; 允许列表不为空时, 只接受命中允许列表的连接
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessControl {
+    allow: Vec<Cidr>,
+    deny: Vec<Cidr>,
+}
+
+impl AccessControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn allow(mut self, cidr: Cidr) -> Self {
+        self.allow.push(cidr);
+        self
+    }
+
+    #[must_use]
+    pub fn deny(mut self, cidr: Cidr) -> Self {
+        self.deny.push(cidr);
+        self
+    }
+
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        if self.deny.iter().any(|cidr| cidr.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+// 按对端地址覆盖会话的协议角色与本站公共地址, 未设置的项使用 Server 的配置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerPolicy {
+    pub(crate) role: Option<Role>,
+    pub(crate) common_addrs: Option<Vec<CommonAddr>>,
+}
+
+impl PeerPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    // 该对端可访问的公共地址, 见 Server::with_common_addrs
+    #[must_use]
+    pub fn with_common_addrs<I>(mut self, common_addrs: I) -> Self
+    where
+        I: IntoIterator<Item = CommonAddr>,
+    {
+        self.common_addrs = Some(common_addrs.into_iter().collect());
+        self
+    }
+}
+
+// 连接过滤的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// 以 Server 的配置接受
+    Accept,
+    /// 接受, 按 PeerPolicy 覆盖会话配置
+    AcceptWith(PeerPolicy),
+    /// 关闭连接, on_process_error 收到 ErrAccessDenied
+    Reject,
+}
+
+// 接受连接后, 交给 on_connected 之前按对端地址决定是否接受, 可查询外部的配置或数据库
+pub trait ConnectFilter: Send + Sync + 'static {
+    fn filter(&self, peer: SocketAddr) -> BoxFuture<'static, Decision>;
+}
+
+impl<F, Fut> ConnectFilter for F
+where
+    F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Decision> + Send + 'static,
+{
+    fn filter(&self, peer: SocketAddr) -> BoxFuture<'static, Decision> {
+        self(peer).boxed()
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use bit_struct::*;
+use bytes::Bytes;
+
+use crate::asdu::{Asdu, CommonAddr, TypeID};
+
+// 地址转换表: 改写经过网关或前置机的 ASDU 的公共地址与信息对象地址.
+// map 方向为内侧(如下级远方站) -> 外侧(如上级主站), unmap 为其反方向, 客户端与服务端的处理函数均可使用.
+// 查找顺序: 逐点规则, 地址段规则, 公共地址规则; 都不匹配时, 开启透传则地址不变, 否则该信息对象不转发.
+// 按类型标识的偏移在上述规则之后叠加, 用于命令与状态使用不同地址段的约定
+#[derive(Debug, Clone, Default)]
+pub struct AddressMap {
+    points: BTreeMap<(CommonAddr, u16), (CommonAddr, u16)>,
+    ranges: Vec<RangeRule>,
+    common_addrs: BTreeMap<CommonAddr, CommonAddr>,
+    type_offsets: BTreeMap<u8, i32>,
+    passthrough: bool,
+}
+
+// 地址段规则: 公共地址 ca 下 ioas 范围内的地址平移到公共地址 to_ca, 起始地址 to_start
+#[derive(Debug, Clone)]
+struct RangeRule {
+    ca: CommonAddr,
+    ioas: RangeInclusive<u16>,
+    to_ca: CommonAddr,
+    to_start: u16,
+}
+
+impl RangeRule {
+    fn map(&self, ca: CommonAddr, ioa: u16) -> Option<(CommonAddr, u16)> {
+        if ca != self.ca || !self.ioas.contains(&ioa) {
+            return None;
+        }
+        let ioa = u32::from(self.to_start) + u32::from(ioa - self.ioas.start());
+        Some((self.to_ca, u16::try_from(ioa).ok()?))
+    }
+
+    fn unmap(&self, ca: CommonAddr, ioa: u16) -> Option<(CommonAddr, u16)> {
+        if ca != self.to_ca || ioa < self.to_start {
+            return None;
+        }
+        let ioa = u32::from(*self.ioas.start()) + u32::from(ioa - self.to_start);
+        let ioa = u16::try_from(ioa).ok()?;
+        self.ioas.contains(&ioa).then_some((self.ca, ioa))
+    }
+}
+
+impl AddressMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 单个点 from 映射为 to, 优先于其他规则
+    #[must_use]
+    pub fn with_point(mut self, from: (CommonAddr, u16), to: (CommonAddr, u16)) -> Self {
+        self.points.insert(from, to);
+        self
+    }
+
+    // 公共地址 ca 下 ioas 范围内的地址映射到公共地址 to_ca 下从 to_start 开始的地址段.
+    // 多个地址段重叠时先添加的优先
+    #[must_use]
+    pub fn with_range(
+        mut self,
+        ca: CommonAddr,
+        ioas: RangeInclusive<u16>,
+        to_ca: CommonAddr,
+        to_start: u16,
+    ) -> Self {
+        self.ranges.push(RangeRule {
+            ca,
+            ioas,
+            to_ca,
+            to_start,
+        });
+        self
+    }
+
+    // 公共地址 ca 下的全部点映射到公共地址 to_ca, 信息对象地址不变
+    #[must_use]
+    pub fn with_common_addr(mut self, ca: CommonAddr, to_ca: CommonAddr) -> Self {
+        self.common_addrs.insert(ca, to_ca);
+        self
+    }
+
+    // 类型标识为 type_id 的信息对象在映射后的地址上再加 offset, 如控制命令的地址 = 状态量地址 + 6000
+    #[must_use]
+    pub fn with_type_offset(mut self, type_id: TypeID, offset: i32) -> Self {
+        self.type_offsets.insert(u8::from(type_id), offset);
+        self
+    }
+
+    // 没有匹配规则的地址是否原样转发, 默认不转发
+    #[must_use]
+    pub fn with_passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    // 内侧地址 -> 外侧地址, 没有匹配的规则且未开启透传时返回 None
+    pub fn map(&self, type_id: TypeID, ca: CommonAddr, ioa: u16) -> Option<(CommonAddr, u16)> {
+        let (ca, ioa) = self
+            .points
+            .get(&(ca, ioa))
+            .copied()
+            .or_else(|| self.ranges.iter().find_map(|r| r.map(ca, ioa)))
+            .or_else(|| self.common_addrs.get(&ca).map(|&to| (to, ioa)))
+            .or_else(|| self.passthrough.then_some((ca, ioa)))?;
+        Some((ca, self.offset(type_id, ioa, 1)?))
+    }
+
+    // 外侧地址 -> 内侧地址, map 的逆映射
+    pub fn unmap(&self, type_id: TypeID, ca: CommonAddr, ioa: u16) -> Option<(CommonAddr, u16)> {
+        let ioa = self.offset(type_id, ioa, -1)?;
+        self.points
+            .iter()
+            .find(|(_, &to)| to == (ca, ioa))
+            .map(|(&from, _)| from)
+            .or_else(|| self.ranges.iter().find_map(|r| r.unmap(ca, ioa)))
+            .or_else(|| {
+                self.common_addrs
+                    .iter()
+                    .find(|(_, &to)| to == ca)
+                    .map(|(&from, _)| (from, ioa))
+            })
+            .or_else(|| self.passthrough.then_some((ca, ioa)))
+    }
+
+    // 改写 ASDU 中各信息对象的地址. 映射到不同公共地址的信息对象分为多个 ASDU,
+    // 没有匹配规则的信息对象被丢弃, 全部丢弃时返回空集合.
+    // 顺序编码(SQ=1)的 ASDU 整体平移, 映射后地址不再连续时丢弃
+    pub fn map_asdu(&self, asdu: &Asdu) -> Vec<Asdu> {
+        self.rewrite(asdu, |type_id, ca, ioa| self.map(type_id, ca, ioa))
+    }
+
+    // 同 map_asdu, 方向为外侧 -> 内侧
+    pub fn unmap_asdu(&self, asdu: &Asdu) -> Vec<Asdu> {
+        self.rewrite(asdu, |type_id, ca, ioa| self.unmap(type_id, ca, ioa))
+    }
+
+    fn offset(&self, type_id: TypeID, ioa: u16, sign: i32) -> Option<u16> {
+        let offset = self
+            .type_offsets
+            .get(&u8::from(type_id))
+            .copied()
+            .unwrap_or(0);
+        u16::try_from(i32::from(ioa) + sign * offset).ok()
+    }
+
+    fn rewrite<F>(&self, asdu: &Asdu, lookup: F) -> Vec<Asdu>
+    where
+        F: Fn(TypeID, CommonAddr, u16) -> Option<(CommonAddr, u16)>,
+    {
+        let type_id = asdu.identifier.type_id;
+        let ca = asdu.identifier.common_addr;
+        let mut vs = asdu.identifier.variable_struct;
+        let info_num = vs.number().get().value() as usize;
+        let is_seq = vs.is_sequence().get().value() != 0;
+        let raw = &asdu.raw;
+        let with_ca = |to_ca: CommonAddr, raw: Vec<u8>, number: usize| {
+            let mut asdu = asdu.clone();
+            asdu.identifier.common_addr = to_ca;
+            if let Some(number) = u7::new(number as u8) {
+                asdu.identifier.variable_struct.number().set(number);
+            }
+            asdu.raw = Bytes::from(raw);
+            asdu
+        };
+
+        // 没有信息对象时只改写公共地址
+        if info_num == 0 || raw.len() < 3 {
+            return lookup(type_id, ca, 0)
+                .map(|(to_ca, _)| with_ca(to_ca, raw.to_vec(), info_num))
+                .into_iter()
+                .collect();
+        }
+        if is_seq {
+            let Some(start) = read_ioa(raw) else {
+                return Vec::new();
+            };
+            let Some((to_ca, to_start)) = lookup(type_id, ca, start) else {
+                return Vec::new();
+            };
+            // 最后一个地址的映射须保持连续
+            let last = u16::try_from(u32::from(start) + info_num as u32 - 1).ok();
+            let expect = u16::try_from(u32::from(to_start) + info_num as u32 - 1).ok();
+            if last.and_then(|last| lookup(type_id, ca, last)) != expect.map(|e| (to_ca, e)) {
+                log::debug!("[ADDRESS MAP] sequence {ca}/{start} not contiguous after mapping");
+                return Vec::new();
+            }
+            let mut raw = raw.to_vec();
+            write_ioa(&mut raw, to_start);
+            return vec![with_ca(to_ca, raw, info_num)];
+        }
+        if !raw.len().is_multiple_of(info_num) {
+            return Vec::new();
+        }
+
+        // 按映射后的公共地址分组: 信息对象, 个数
+        let mut groups: BTreeMap<CommonAddr, (Vec<u8>, usize)> = BTreeMap::new();
+        for obj in raw.chunks(raw.len() / info_num) {
+            let Some((to_ca, to_ioa)) = read_ioa(obj).and_then(|ioa| lookup(type_id, ca, ioa))
+            else {
+                continue;
+            };
+            let (buf, n) = groups.entry(to_ca).or_default();
+            let at = buf.len();
+            buf.extend_from_slice(obj);
+            write_ioa(&mut buf[at..], to_ioa);
+            *n += 1;
+        }
+        groups
+            .into_iter()
+            .map(|(to_ca, (raw, n))| with_ca(to_ca, raw, n))
+            .collect()
+    }
+}
+
+// 信息对象地址(3 字节), 超出 16 位时返回 None
+fn read_ioa(obj: &[u8]) -> Option<u16> {
+    if obj.len() < 3 || obj[2] != 0 {
+        return None;
+    }
+    Some(u16::from_le_bytes([obj[0], obj[1]]))
+}
+
+fn write_ioa(obj: &mut [u8], ioa: u16) {
+    obj[..2].copy_from_slice(&ioa.to_le_bytes());
+    obj[2] = 0;
+}
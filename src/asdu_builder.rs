@@ -0,0 +1,213 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    asdu::{
+        Asdu, Cause, CauseOfTransmission, CommonAddr, InfoObjAddr, IntoCommonAddr, IntoInfoObjAddr,
+        OriginAddr, TypeID, ASDU_SIZE_MAX, IDENTIFIER_SIZE, INVALID_COMMON_ADDR,
+    },
+    datastore::Infos,
+    mproc::{check_monitor_cause, split_into_asdus, ObjectQDS, INFO_NUM_MAX},
+    Error, PointValue,
+};
+
+// 监视方向过程信息 ASDU 的构造器: 在 build 时统一校验类型标识与传送原因, 值的类型,
+// 时标, 顺序编码及信息元素个数, 编码与 mproc 中的构造函数一致. 例如
+// AsduBuilder::new(TypeID::M_ME_NC_1).cot(Cause::Spontaneous).ca(1).add(100, PointValue::Float(1.5), qds).build()
+#[derive(Debug, Clone)]
+pub struct AsduBuilder {
+    type_id: TypeID,
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    orig_addr: OriginAddr,
+    sequence: bool,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    ioa: InfoObjAddr,
+    value: PointValue,
+    quality: ObjectQDS,
+    time: Option<DateTime<Utc>>,
+}
+
+impl AsduBuilder {
+    // 传送原因默认为突发, 公共地址须以 ca 设置
+    pub fn new(type_id: TypeID) -> Self {
+        AsduBuilder {
+            type_id,
+            cot: CauseOfTransmission::new(false, false, Cause::Spontaneous),
+            ca: INVALID_COMMON_ADDR,
+            orig_addr: 0,
+            sequence: false,
+            items: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn cot(mut self, cause: Cause) -> Self {
+        self.cot.cause().set(cause);
+        self
+    }
+
+    // 试验(T)位
+    #[must_use]
+    pub fn test(mut self, test: bool) -> Self {
+        self.cot.test().set(test);
+        self
+    }
+
+    #[must_use]
+    pub fn ca(mut self, ca: impl IntoCommonAddr) -> Self {
+        self.ca = ca.into_common_addr();
+        self
+    }
+
+    #[must_use]
+    pub fn orig_addr(mut self, orig_addr: OriginAddr) -> Self {
+        self.orig_addr = orig_addr;
+        self
+    }
+
+    // 顺序编码(SQ = 1), 信息对象地址须依次加 1, 只用于不带时标的类型
+    #[must_use]
+    pub fn sequence(mut self, sequence: bool) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    // 添加信息对象, 带时标的类型以 build 时的时间为时标
+    #[must_use]
+    pub fn add(
+        mut self,
+        ioa: impl IntoInfoObjAddr,
+        value: PointValue,
+        quality: impl Into<ObjectQDS>,
+    ) -> Self {
+        self.items.push(Item {
+            ioa: ioa.into_info_obj_addr(),
+            value,
+            quality: quality.into(),
+            time: None,
+        });
+        self
+    }
+
+    // 添加带时标的信息对象, 只用于带时标的类型
+    #[must_use]
+    pub fn add_timestamped(
+        mut self,
+        ioa: impl IntoInfoObjAddr,
+        value: PointValue,
+        quality: impl Into<ObjectQDS>,
+        time: DateTime<Utc>,
+    ) -> Self {
+        self.items.push(Item {
+            ioa: ioa.into_info_obj_addr(),
+            value,
+            quality: quality.into(),
+            time: Some(time),
+        });
+        self
+    }
+
+    // 构造一个 ASDU, 信息元素个数须为 1~127, 编码后的长度不超过 249 字节
+    pub fn build(self) -> Result<Asdu, Error> {
+        self.check()?;
+        let n = self.items.len();
+        if !(1..=INFO_NUM_MAX).contains(&n) {
+            return Err(Error::ErrInfoNum(n));
+        }
+        let asdu = self.encode(self.items.clone())?;
+        let len = IDENTIFIER_SIZE + asdu.raw.len();
+        if len > ASDU_SIZE_MAX {
+            return Err(Error::ErrAsduTooLarge(len));
+        }
+        Ok(asdu)
+    }
+
+    // 同 build, 信息对象过多时拆分为多个 ASDU, 见 split_into_asdus
+    pub fn build_all(self) -> Result<Vec<Asdu>, Error> {
+        self.check()?;
+        if self.items.is_empty() {
+            return Err(Error::ErrInfoNum(0));
+        }
+        split_into_asdus(self.items.clone(), |items| self.encode(items))
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.ca == INVALID_COMMON_ADDR {
+            return Err(anyhow!("asdu builder: common address not set").into());
+        }
+        check_monitor_cause(self.type_id, self.cot)?;
+        let timed = is_timed(self.type_id);
+        if self.sequence && timed {
+            return Err(Error::ErrTypeIDNotMatch(self.type_id));
+        }
+        for item in &self.items {
+            if !value_matches(self.type_id, &item.value) || (item.time.is_some() && !timed) {
+                return Err(Error::ErrTypeIDNotMatch(self.type_id));
+            }
+        }
+        Ok(())
+    }
+
+    fn encode(&self, items: Vec<Item>) -> Result<Asdu, Error> {
+        let mut infos = Infos::default();
+        for item in items {
+            infos.push_value(item.ioa, item.value, item.quality, item.time);
+        }
+        let mut asdu = infos.into_asdu(self.type_id, self.sequence, self.cot, self.ca)?;
+        asdu.identifier.orig_addr = self.orig_addr;
+        Ok(asdu)
+    }
+}
+
+fn is_timed(type_id: TypeID) -> bool {
+    matches!(
+        type_id,
+        TypeID::M_SP_TA_1
+            | TypeID::M_SP_TB_1
+            | TypeID::M_DP_TA_1
+            | TypeID::M_DP_TB_1
+            | TypeID::M_ME_TA_1
+            | TypeID::M_ME_TB_1
+            | TypeID::M_ME_TC_1
+            | TypeID::M_ME_TD_1
+            | TypeID::M_ME_TE_1
+            | TypeID::M_ME_TF_1
+            | TypeID::M_IT_TA_1
+            | TypeID::M_IT_TB_1
+    )
+}
+
+// 值的类型是否与类型标识一致
+fn value_matches(type_id: TypeID, value: &PointValue) -> bool {
+    match value {
+        PointValue::Single(_) => matches!(
+            type_id,
+            TypeID::M_SP_NA_1 | TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1
+        ),
+        PointValue::Double(_) => matches!(
+            type_id,
+            TypeID::M_DP_NA_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1
+        ),
+        PointValue::Normalized(_) => matches!(
+            type_id,
+            TypeID::M_ME_NA_1 | TypeID::M_ME_TA_1 | TypeID::M_ME_TD_1 | TypeID::M_ME_ND_1
+        ),
+        PointValue::Scaled(_) => matches!(
+            type_id,
+            TypeID::M_ME_NB_1 | TypeID::M_ME_TB_1 | TypeID::M_ME_TE_1
+        ),
+        PointValue::Float(_) => matches!(
+            type_id,
+            TypeID::M_ME_NC_1 | TypeID::M_ME_TC_1 | TypeID::M_ME_TF_1
+        ),
+        PointValue::Counter(_) => matches!(
+            type_id,
+            TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1
+        ),
+    }
+}
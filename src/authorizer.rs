@@ -0,0 +1,185 @@
+use std::{io::Cursor, net::SocketAddr, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    asdu::{Asdu, AsduParams, Cause, CommonAddr, OriginAddr, TypeID},
+    command::first_ioa,
+    cproc::is_valid_dcs,
+    time::Cp56Time2a,
+};
+
+// 控制命令的来源与目标, 交给 CommandAuthorizer 判断是否允许执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandRequest {
+    /// 控制站的套接字地址
+    pub peer: SocketAddr,
+    /// 源发站地址
+    pub orig_addr: OriginAddr,
+    pub type_id: TypeID,
+    /// 传送原因, 激活或停止激活
+    pub cause: Cause,
+    pub ca: CommonAddr,
+    /// 信息对象地址
+    pub ioa: u16,
+}
+
+impl CommandRequest {
+    pub(crate) fn of(asdu: &Asdu, params: &AsduParams, peer: SocketAddr) -> Self {
+        let mut cot = asdu.identifier.cot;
+        CommandRequest {
+            peer,
+            orig_addr: asdu.identifier.orig_addr,
+            type_id: asdu.identifier.type_id,
+            cause: cot.cause().get(),
+            ca: asdu.identifier.common_addr,
+            ioa: first_ioa(asdu, params).unwrap_or_default() as u16,
+        }
+    }
+}
+
+// 授权结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authorization {
+    /// 允许, 命令交给 ServerHandler 处理
+    Allow,
+    /// 拒绝, 回复否定的激活确认(停止激活时为否定的停止激活确认)
+    Deny,
+    /// 拒绝, 回复否定的未知信息对象地址, 不向控制站暴露该点的存在
+    UnknownIOA,
+}
+
+// 控制命令的闭锁/授权: 单命令, 双命令, 调节步命令, 设定值命令及 32 比特串命令
+// 交给 ServerHandler 之前回调, 可按控制站地址, 源发站地址, 公共地址与信息对象地址
+// 实现基于角色的控制权限及闭锁. 回调在会话任务中同步执行, 不应阻塞
+pub trait CommandAuthorizer: Send + Sync + 'static {
+    fn authorize(&self, request: &CommandRequest) -> Authorization;
+}
+
+impl<F> CommandAuthorizer for F
+where
+    F: Fn(&CommandRequest) -> Authorization + Send + Sync + 'static,
+{
+    fn authorize(&self, request: &CommandRequest) -> Authorization {
+        self(request)
+    }
+}
+
+// 需要授权的控制方向过程信息
+pub(crate) fn is_control_command(type_id: TypeID) -> bool {
+    matches!(
+        type_id,
+        TypeID::C_SC_NA_1
+            | TypeID::C_DC_NA_1
+            | TypeID::C_RC_NA_1
+            | TypeID::C_SE_NA_1
+            | TypeID::C_SE_NB_1
+            | TypeID::C_SE_NC_1
+            | TypeID::C_BO_NA_1
+            | TypeID::C_SC_TA_1
+            | TypeID::C_DC_TA_1
+            | TypeID::C_RC_TA_1
+            | TypeID::C_SE_TA_1
+            | TypeID::C_SE_TB_1
+            | TypeID::C_SE_TC_1
+            | TypeID::C_BO_TA_1
+    )
+}
+
+// 含标准不允许的值的命令, 目前检查双命令状态 DCS 0 与 3
+pub(crate) fn is_invalid_command(asdu: &Asdu) -> bool {
+    match asdu.identifier.type_id {
+        TypeID::C_DC_NA_1 | TypeID::C_DC_TA_1 => {
+            asdu.raw.get(3).is_some_and(|dco| !is_valid_dcs(dco & 0x03))
+        }
+        _ => false,
+    }
+}
+
+// 拒绝时的响应
+pub(crate) fn rejection(asdu: &Asdu, request: &CommandRequest, auth: Authorization) -> Asdu {
+    match (auth, request.cause) {
+        (Authorization::UnknownIOA, _) => asdu.mirror_negative(Cause::UnknownIOA),
+        (_, Cause::Deactivation) => asdu.mirror_negative(Cause::DeactivationCon),
+        _ => asdu.mirror_negative(Cause::ActivationCon),
+    }
+}
+
+// 带时标的控制命令(C_SC_TA_1, C_DC_TA_1, C_RC_TA_1, C_SE_TA_1/TB_1/TC_1, C_BO_TA_1)的时标检查.
+// 时标早于 now - max_age - max_skew 或晚于 now + max_skew 的命令视为过期, 由会话直接回复否定确认.
+// 时标按 UTC 解释
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandAgePolicy {
+    max_age: Duration,
+    max_skew: Duration,
+    reject_invalid: bool,
+}
+
+impl CommandAgePolicy {
+    pub fn new(max_age: Duration) -> Self {
+        CommandAgePolicy {
+            max_age,
+            max_skew: Duration::ZERO,
+            reject_invalid: true,
+        }
+    }
+
+    // 允许的两端时钟偏差, 默认为 0
+    #[must_use]
+    pub fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    // 是否拒绝时标无效(IV)的命令, 默认拒绝
+    #[must_use]
+    pub fn with_reject_invalid(mut self, reject_invalid: bool) -> Self {
+        self.reject_invalid = reject_invalid;
+        self
+    }
+
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    pub fn max_skew(&self) -> Duration {
+        self.max_skew
+    }
+
+    // 时标是否在允许的范围内
+    pub fn accepts(&self, tag: &Cp56Time2a, now: DateTime<Utc>) -> bool {
+        if tag.invalid && self.reject_invalid {
+            return false;
+        }
+        let (Ok(max_age), Ok(max_skew)) = (
+            chrono::Duration::from_std(self.max_age),
+            chrono::Duration::from_std(self.max_skew),
+        ) else {
+            return true;
+        };
+        tag.time >= now - max_age - max_skew && tag.time <= now + max_skew
+    }
+}
+
+pub(crate) fn is_time_tagged_command(type_id: TypeID) -> bool {
+    matches!(
+        type_id,
+        TypeID::C_SC_TA_1
+            | TypeID::C_DC_TA_1
+            | TypeID::C_RC_TA_1
+            | TypeID::C_SE_TA_1
+            | TypeID::C_SE_TB_1
+            | TypeID::C_SE_TC_1
+            | TypeID::C_BO_TA_1
+    )
+}
+
+// 带时标命令的时标, 位于信息对象的最后 7 字节; 长度不足或日期非法时返回 None
+pub(crate) fn command_time(asdu: &Asdu) -> Option<Cp56Time2a> {
+    let len = asdu.raw.len();
+    if len < 3 + 7 {
+        return None;
+    }
+    let tail = asdu.raw.slice(len - 7..);
+    Cp56Time2a::decode(&mut Cursor::new(&tail)).ok().flatten()
+}
@@ -0,0 +1,348 @@
+// IEC 104 主站命令行工具, 用于调试与投运, 例如
+//
+// iecp5-cli 192.168.1.10:2404 interrogate --ca 1
+// iecp5-cli 192.168.1.10 single-cmd --ca 1 --ioa 6001 --value on --select
+// iecp5-cli 192.168.1.10 monitor
+
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    process::ExitCode,
+    time::Duration,
+};
+
+use chrono::Utc;
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::timeout,
+};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, TypeID},
+    cproc::{
+        set_point_cmd_float, set_point_cmd_normal, set_point_cmd_scaled, SetpointCommandFloatInfo,
+        SetpointCommandNormalInfo, SetpointCommandScaledInfo, SingleCommandInfo,
+    },
+    csys::{clock_synchronization_cmd, interrogation_cmd, read_cmd, Qoi},
+    Client, ClientEvent, ClientOption, CommandResult, Error, FnHandler,
+};
+
+const USAGE: &str = "usage: iecp5-cli <host[:port]> <command> [options]
+
+commands:
+    interrogate     station or group interrogation (--qoi, default 20), print until ActTerm
+    read            read command for one object (--ioa)
+    single-cmd      single command (--ioa, --value on|off, --select)
+    setpoint        set-point command (--ioa, --value, --type float|normal|scaled, --select)
+    clock-sync      clock synchronization with the local time
+    monitor         print incoming ASDUs until Ctrl-C
+
+options:
+    --ca <n>        common address, default 1
+    --ioa <n>       information object address
+    --value <v>     command value
+    --type <t>      set-point type, default float
+    --qoi <n>       qualifier of interrogation, 20 station, 21~36 group 1~16
+    --select        select before execute
+    --wait <secs>   timeout of connection and each response, default 5";
+
+struct Args {
+    addr: SocketAddr,
+    command: String,
+    ca: CommonAddr,
+    ioa: Option<u16>,
+    value: Option<String>,
+    setpoint: String,
+    qoi: u8,
+    select: bool,
+    wait: Duration,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let host = args.next().ok_or("missing host")?;
+        let command = args.next().ok_or("missing command")?;
+        let mut parsed = Args {
+            addr: resolve(&host)?,
+            command,
+            ca: 1,
+            ioa: None,
+            value: None,
+            setpoint: "float".to_string(),
+            qoi: 20,
+            select: false,
+            wait: Duration::from_secs(5),
+        };
+        while let Some(flag) = args.next() {
+            if flag == "--select" {
+                parsed.select = true;
+                continue;
+            }
+            let value = args.next().ok_or(format!("missing value for {flag}"))?;
+            let invalid = || format!("invalid value for {flag}: {value}");
+            match flag.as_str() {
+                "--ca" => parsed.ca = value.parse().map_err(|_| invalid())?,
+                "--ioa" => parsed.ioa = Some(value.parse().map_err(|_| invalid())?),
+                "--value" => parsed.value = Some(value),
+                "--type" => parsed.setpoint = value,
+                "--qoi" => parsed.qoi = value.parse().map_err(|_| invalid())?,
+                "--wait" => {
+                    parsed.wait = Duration::from_secs_f64(value.parse().map_err(|_| invalid())?)
+                }
+                _ => return Err(format!("unknown option {flag}")),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn ioa(&self) -> Result<u16, String> {
+        self.ioa.ok_or_else(|| "missing --ioa".to_string())
+    }
+
+    fn value(&self) -> Result<&str, String> {
+        self.value
+            .as_deref()
+            .ok_or_else(|| "missing --value".to_string())
+    }
+}
+
+// 未指定端口时使用 2404
+fn resolve(host: &str) -> Result<SocketAddr, String> {
+    let host = if host.contains(':') && !host.ends_with(']') {
+        host.to_string()
+    } else {
+        format!("{host}:2404")
+    };
+    host.to_socket_addrs()
+        .map_err(|e| format!("resolve {host}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("resolve {host}: no address"))
+}
+
+fn parse_bool(v: &str) -> Result<bool, String> {
+    match v.to_ascii_lowercase().as_str() {
+        "on" | "1" | "true" => Ok(true),
+        "off" | "0" | "false" => Ok(false),
+        _ => Err(format!("invalid single command value {v}")),
+    }
+}
+
+fn print_asdu(asdu: &Asdu) {
+    println!("{} {asdu}", Utc::now().format("%H:%M:%S%.3f"));
+}
+
+fn print_result(result: CommandResult) {
+    let state = if result.is_positive() {
+        "confirmed"
+    } else {
+        "rejected"
+    };
+    let terminated = if result.terminated {
+        ", terminated"
+    } else {
+        ""
+    };
+    println!("{state}{terminated}: {}", result.asdu);
+}
+
+type CliClient = Client<FnHandler<Box<dyn FnMut(Asdu) + Send>>>;
+
+// 连接并激活数据传输, 收到的 ASDU 转发到返回的通道
+async fn connect(args: &Args) -> Result<(CliClient, mpsc::UnboundedReceiver<Asdu>), String> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let forward: Box<dyn FnMut(Asdu) + Send> = Box::new(move |asdu| {
+        let _ = tx.send(asdu);
+    });
+    let option = ClientOption::new(args.addr, false).with_command_timeout(args.wait);
+    let client = Client::builder().option(option).on_asdu(forward).build();
+
+    let mut events = client.events();
+    client.start().await.map_err(|e| e.to_string())?;
+    wait_event(&mut events, args.wait, |e| {
+        matches!(e, ClientEvent::Connected)
+    })
+    .await
+    .map_err(|e| format!("connect {}: {e}", args.addr))?;
+    wait_event(&mut events, args.wait, |e| {
+        matches!(e, ClientEvent::Activated)
+    })
+    .await
+    .map_err(|e| format!("STARTDT: {e}"))?;
+    Ok((client, rx))
+}
+
+async fn wait_event(
+    events: &mut broadcast::Receiver<ClientEvent>,
+    wait: Duration,
+    expect: impl Fn(&ClientEvent) -> bool,
+) -> Result<(), String> {
+    let recv = async {
+        loop {
+            match events.recv().await {
+                Ok(ClientEvent::Disconnected(reason)) => return Err(reason.to_string()),
+                Ok(e) if expect(&e) => return Ok(()),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    };
+    timeout(wait, recv)
+        .await
+        .unwrap_or_else(|_| Err("timeout".to_string()))
+}
+
+// 接收公共地址为 --ca 的 ASDU 直到 done 返回 true, 每次等待不超过 --wait.
+// all 为 false 时只打印最后一个
+async fn print_until(
+    rx: &mut mpsc::UnboundedReceiver<Asdu>,
+    args: &Args,
+    all: bool,
+    done: impl Fn(&Asdu) -> bool,
+) -> Result<(), String> {
+    loop {
+        let asdu = timeout(args.wait, rx.recv())
+            .await
+            .map_err(|_| "timeout".to_string())?
+            .ok_or("connection closed")?;
+        if asdu.identifier.common_addr != args.ca {
+            continue;
+        }
+        let done = done(&asdu);
+        if all || done {
+            print_asdu(&asdu);
+        }
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let (client, mut rx) = connect(&args).await?;
+    let act = CauseOfTransmission::new(false, false, Cause::Activation);
+    let err = |e: Error| e.to_string();
+
+    match args.command.as_str() {
+        "interrogate" => {
+            let qoi = Qoi::from(args.qoi);
+            client
+                .send_asdu(interrogation_cmd(act, args.ca, qoi).map_err(err)?)
+                .await
+                .map_err(err)?;
+            // 打印召唤过程中收到的全部 ASDU, 直到激活终止或否定确认
+            print_until(&mut rx, &args, true, |asdu| {
+                let mut cot = asdu.identifier.cot;
+                asdu.identifier.type_id == TypeID::C_IC_NA_1
+                    && (cot.cause().get() == Cause::ActivationTerm || cot.is_rejected())
+            })
+            .await?;
+        }
+        "read" => {
+            let req = CauseOfTransmission::new(false, false, Cause::Request);
+            client
+                .send_asdu(read_cmd(req, args.ca, args.ioa()?).map_err(err)?)
+                .await
+                .map_err(err)?;
+            // 只打印以请求为传送原因的响应, 或读命令的否定确认
+            print_until(&mut rx, &args, false, |asdu| {
+                let mut cot = asdu.identifier.cot;
+                cot.cause().get() == Cause::Request
+                    || (asdu.identifier.type_id == TypeID::C_RD_NA_1 && cot.is_rejected())
+            })
+            .await?;
+        }
+        "single-cmd" => {
+            let cmd = SingleCommandInfo::new(args.ioa()?, parse_bool(args.value()?)?, false);
+            let result = if args.select {
+                client
+                    .select_and_execute_single(TypeID::C_SC_NA_1, args.ca, cmd)
+                    .await
+            } else {
+                client
+                    .single_cmd_confirmed(TypeID::C_SC_NA_1, act, args.ca, cmd)
+                    .await
+            };
+            print_result(result.map_err(err)?);
+        }
+        "setpoint" => {
+            let ioa = args.ioa()?;
+            let value = args.value()?;
+            let invalid = || format!("invalid set-point value {value}");
+            let result = match args.setpoint.as_str() {
+                "float" => {
+                    let cmd =
+                        SetpointCommandFloatInfo::new(ioa, value.parse().map_err(|_| invalid())?);
+                    if args.select {
+                        client
+                            .select_and_execute_setpoint_float(TypeID::C_SE_NC_1, args.ca, cmd)
+                            .await
+                    } else {
+                        let asdu = set_point_cmd_float(TypeID::C_SE_NC_1, act, args.ca, cmd);
+                        client.send_cmd_confirmed(asdu.map_err(err)?).await
+                    }
+                }
+                "normal" => {
+                    let cmd =
+                        SetpointCommandNormalInfo::new(ioa, value.parse().map_err(|_| invalid())?);
+                    if args.select {
+                        client
+                            .select_and_execute_setpoint_normal(TypeID::C_SE_NA_1, args.ca, cmd)
+                            .await
+                    } else {
+                        let asdu = set_point_cmd_normal(TypeID::C_SE_NA_1, act, args.ca, cmd);
+                        client.send_cmd_confirmed(asdu.map_err(err)?).await
+                    }
+                }
+                "scaled" => {
+                    let cmd =
+                        SetpointCommandScaledInfo::new(ioa, value.parse().map_err(|_| invalid())?);
+                    if args.select {
+                        client
+                            .select_and_execute_setpoint_scaled(TypeID::C_SE_NB_1, args.ca, cmd)
+                            .await
+                    } else {
+                        let asdu = set_point_cmd_scaled(TypeID::C_SE_NB_1, act, args.ca, cmd);
+                        client.send_cmd_confirmed(asdu.map_err(err)?).await
+                    }
+                }
+                t => return Err(format!("unknown set-point type {t}")),
+            };
+            print_result(result.map_err(err)?);
+        }
+        "clock-sync" => {
+            let asdu = clock_synchronization_cmd(act, args.ca, Utc::now()).map_err(err)?;
+            print_result(client.send_cmd_confirmed(asdu).await.map_err(err)?);
+        }
+        "monitor" => loop {
+            tokio::select! {
+                asdu = rx.recv() => match asdu {
+                    Some(asdu) => print_asdu(&asdu),
+                    None => break,
+                },
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        },
+        c => return Err(format!("unknown command {c}")),
+    }
+
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
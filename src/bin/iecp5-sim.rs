@@ -0,0 +1,367 @@
+// RTU 模拟器: 按点表提供站召唤, 计数量召唤, 读命令, 周期上送, 突发变化及命令回显, 例如
+//
+// iecp5-sim points.csv --listen 0.0.0.0:2404 --cyclic 10
+//
+// 点表每行为 ca,ioa,type,value,period,noise,group, 可以有表头, # 开头的行为注释:
+//   type   single | double | normal | scaled | float | counter
+//   value  初值, 单点为 0/1, 双点为 0~3
+//   period 变化周期(秒), 0 或省略为不变化
+//   noise  变化幅度: 测量值在初值上下随机波动, 累计量每周期增加 1 ~ 1+noise;
+//          单点与双点每周期取反
+//   group  召唤组, 累计量为计数量召唤组 1~4, 省略为 0
+
+use std::{
+    future,
+    net::SocketAddr,
+    process::ExitCode,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{net::TcpListener, time::interval};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CommonAddr, InfoObjAddr, TypeID},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    DataStore, Error, Point, PointValue, Scheduler, Server, ServerHandler,
+};
+
+const USAGE: &str = "usage: iecp5-sim <points.csv> [options]
+
+options:
+    --listen <addr>     listen address, default 0.0.0.0:2404
+    --cyclic <secs>     periodic transmission of measured values, default off
+    --background <secs> background scan, default off";
+
+// 点表中的一个点
+#[derive(Debug, Clone, Copy)]
+struct Profile {
+    ca: CommonAddr,
+    ioa: u16,
+    value: PointValue,
+    period: Option<Duration>,
+    noise: f64,
+    group: u8,
+}
+
+fn parse_profiles(text: &str) -> Result<Vec<Profile>, String> {
+    let mut profiles = Vec::new();
+    let mut header = true;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        // 第一个非注释行可以是表头
+        if std::mem::take(&mut header) && fields[0].parse::<u16>().is_err() {
+            continue;
+        }
+        let invalid = |what: &str| format!("line {}: invalid {what}: {line}", n + 1);
+        let field = |i: usize| fields.get(i).copied().filter(|f| !f.is_empty());
+        let ca = field(0)
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| invalid("ca"))?;
+        let ioa = field(1)
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(|| invalid("ioa"))?;
+        let initial = field(3).unwrap_or("0");
+        let value = match field(2).map(str::to_ascii_lowercase).as_deref() {
+            Some("single") => initial
+                .parse::<u8>()
+                .ok()
+                .filter(|v| *v <= 1)
+                .map(|v| PointValue::Single(v == 1)),
+            Some("double") => initial
+                .parse::<u8>()
+                .ok()
+                .filter(|v| *v <= 3)
+                .map(PointValue::Double),
+            Some("normal") => initial.parse().ok().map(PointValue::Normalized),
+            Some("scaled") => initial.parse().ok().map(PointValue::Scaled),
+            Some("float") => initial.parse().ok().map(PointValue::Float),
+            Some("counter") => initial.parse().ok().map(PointValue::Counter),
+            _ => return Err(invalid("type")),
+        }
+        .ok_or_else(|| invalid("value"))?;
+        let period = field(4)
+            .map(|f| f.parse::<f64>().map_err(|_| invalid("period")))
+            .transpose()?
+            .filter(|p| *p > 0.0)
+            .map(Duration::from_secs_f64);
+        let noise = field(5)
+            .map(|f| f.parse().map_err(|_| invalid("noise")))
+            .transpose()?
+            .unwrap_or(0.0);
+        let group = field(6)
+            .map(|f| f.parse().map_err(|_| invalid("group")))
+            .transpose()?
+            .unwrap_or(0);
+        profiles.push(Profile {
+            ca,
+            ioa,
+            value,
+            period,
+            noise,
+            group,
+        });
+    }
+    Ok(profiles)
+}
+
+// 点值变化用的 xorshift 随机数, 返回 [-1, 1)
+fn jitter() -> f64 {
+    static STATE: AtomicU64 = AtomicU64::new(0);
+    let mut x = STATE.load(Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0x2545_f491_4f6c_dd1d, |d| d.as_nanos() as u64)
+            | 1;
+    }
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    STATE.store(x, Ordering::Relaxed);
+    (x >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+}
+
+// 按点表的初值与幅度计算下一个值
+fn next_value(profile: &Profile, current: PointValue) -> PointValue {
+    let noise = profile.noise * jitter();
+    match (profile.value, current) {
+        (_, PointValue::Single(v)) => PointValue::Single(!v),
+        (_, PointValue::Double(v)) => PointValue::Double(if v == 1 { 2 } else { 1 }),
+        (PointValue::Normalized(v), _) => {
+            PointValue::Normalized((v as f64 + noise).clamp(-32768.0, 32767.0) as i16)
+        }
+        (PointValue::Scaled(v), _) => {
+            PointValue::Scaled((v as f64 + noise).clamp(-32768.0, 32767.0) as i16)
+        }
+        (PointValue::Float(v), _) => PointValue::Float(v + noise as f32),
+        (_, PointValue::Counter(v)) => PointValue::Counter(v.wrapping_add(1 + noise.abs() as i32)),
+        (_, v) => v,
+    }
+}
+
+async fn simulate(store: DataStore, profile: Profile, period: Duration) {
+    let mut ticker = interval(period);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let Some(point) = store.get(profile.ca, profile.ioa) else {
+            return;
+        };
+        let value = next_value(&profile, point.value);
+        if let Err(e) = store.set(profile.ca, profile.ioa, value) {
+            log::warn!("[SIM] update {}/{}: {e}", profile.ca, profile.ioa);
+        }
+    }
+}
+
+// 点数据库之上的命令回显: 遥控与设定命令写入同一信息对象地址的点,
+// 回复激活确认与激活终止; 选择命令只回复激活确认
+#[derive(Clone)]
+struct SimHandler {
+    store: DataStore,
+}
+
+impl SimHandler {
+    fn command(&self, asdu: Asdu) -> Result<Vec<Asdu>, Error> {
+        let mut cmd = asdu.clone();
+        let ca = asdu.identifier.common_addr;
+        let (mut ioa, select, value) = match asdu.identifier.type_id {
+            TypeID::C_SC_NA_1 | TypeID::C_SC_TA_1 => {
+                let mut c = cmd.get_single_cmd()?;
+                (
+                    c.ioa,
+                    c.sco.se().get(),
+                    PointValue::Single(c.sco.scs().get()),
+                )
+            }
+            TypeID::C_DC_NA_1 | TypeID::C_DC_TA_1 => {
+                let mut c = cmd.get_double_cmd()?;
+                let value = PointValue::Double(c.dco.dcs().get().value());
+                (c.ioa, c.dco.se().get(), value)
+            }
+            TypeID::C_SE_NA_1 | TypeID::C_SE_TA_1 => {
+                let mut c = cmd.get_setpoint_normal_cmd()?;
+                let select = c.qos.se().get().value() == 1;
+                (c.ioa, select, PointValue::Normalized(c.nva))
+            }
+            TypeID::C_SE_NB_1 | TypeID::C_SE_TB_1 => {
+                let mut c = cmd.get_setpoint_scaled_cmd()?;
+                let select = c.qos.se().get().value() == 1;
+                (c.ioa, select, PointValue::Scaled(c.sva))
+            }
+            TypeID::C_SE_NC_1 | TypeID::C_SE_TC_1 => {
+                let mut c = cmd.get_setpoint_float_cmd()?;
+                let select = c.qos.se().get().value() == 1;
+                (c.ioa, select, PointValue::Float(c.r))
+            }
+            _ => return Ok(vec![asdu.mirror_negative(Cause::UnknownTypeID)]),
+        };
+        let addr = ioa.addr().get();
+        let Some(point) = self.store.get(ca, addr) else {
+            return Ok(vec![asdu.mirror_negative(Cause::UnknownIOA)]);
+        };
+        if std::mem::discriminant(&point.value) != std::mem::discriminant(&value) {
+            return Ok(vec![asdu.mirror_negative(Cause::ActivationCon)]);
+        }
+        let mut cot = asdu.identifier.cot;
+        if cot.cause().get() == Cause::Deactivation {
+            return Ok(vec![asdu.mirror(Cause::DeactivationCon)]);
+        }
+        if select {
+            return Ok(vec![asdu.mirror(Cause::ActivationCon)]);
+        }
+        self.store.set(ca, addr, value)?;
+        Ok(vec![
+            asdu.mirror(Cause::ActivationCon),
+            asdu.mirror(Cause::ActivationTerm),
+        ])
+    }
+}
+
+impl ServerHandler for SimHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        self.store.call_interrogation(asdu, qoi)
+    }
+
+    fn call_counter_interrogation(&self, asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
+        self.store.call_counter_interrogation(asdu, qcc)
+    }
+
+    fn call_read(&self, asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        self.store.call_read(asdu, ioa)
+    }
+
+    fn call_clock_sync(&self, asdu: Asdu, time: Option<DateTime<Utc>>) -> Self::Future {
+        self.store.call_clock_sync(asdu, time)
+    }
+
+    fn call_reset_process(&self, asdu: Asdu, qrp: ObjectQRP) -> Self::Future {
+        self.store.call_reset_process(asdu, qrp)
+    }
+
+    fn call_delay_acquire(&self, asdu: Asdu, msec: u16) -> Self::Future {
+        self.store.call_delay_acquire(asdu, msec)
+    }
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        future::ready(self.command(asdu))
+    }
+}
+
+struct Args {
+    points: String,
+    listen: SocketAddr,
+    cyclic: Option<Duration>,
+    background: Option<Duration>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args {
+            points: args.next().ok_or("missing point list")?,
+            listen: "0.0.0.0:2404".parse().unwrap(),
+            cyclic: None,
+            background: None,
+        };
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(format!("missing value for {flag}"))?;
+            let invalid = || format!("invalid value for {flag}: {value}");
+            let secs = || {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|s| *s > 0.0)
+                    .map(Duration::from_secs_f64)
+                    .ok_or_else(invalid)
+            };
+            match flag.as_str() {
+                "--listen" => parsed.listen = value.parse().map_err(|_| invalid())?,
+                "--cyclic" => parsed.cyclic = Some(secs()?),
+                "--background" => parsed.background = Some(secs()?),
+                _ => return Err(format!("unknown option {flag}")),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let text =
+        std::fs::read_to_string(&args.points).map_err(|e| format!("read {}: {e}", args.points))?;
+    let profiles = parse_profiles(&text)?;
+
+    let store = DataStore::new();
+    for p in &profiles {
+        store.insert(p.ca, p.ioa, Point::new(p.value));
+        store
+            .set_group(p.ca, p.ioa, p.group)
+            .map_err(|e| e.to_string())?;
+    }
+    let listener = TcpListener::bind(args.listen)
+        .await
+        .map_err(|e| format!("bind {}: {e}", args.listen))?;
+    let server = Server::new(listener).with_common_addrs(store.common_addrs());
+    store.attach(server.handle());
+
+    let mut scheduler = Scheduler::new(store.clone());
+    for ca in store.common_addrs() {
+        if let Some(period) = args.cyclic {
+            scheduler = scheduler.with_cyclic(ca, 0, period);
+        }
+        if let Some(period) = args.background {
+            scheduler = scheduler.with_background(ca, 0, period);
+        }
+    }
+    scheduler.spawn(server.handle());
+    for p in profiles {
+        if let Some(period) = p.period {
+            tokio::spawn(simulate(store.clone(), p, period));
+        }
+    }
+
+    println!(
+        "serving {} points of {:?} on {}",
+        store.len(),
+        store.common_addrs(),
+        args.listen
+    );
+    let handler = SimHandler { store };
+    let on_connected = |stream, addr| {
+        let handler = handler.clone();
+        async move {
+            log::info!("[SIM] connection from {addr}");
+            Ok(Some((handler, stream)))
+        }
+    };
+    server
+        .serve(&on_connected, |e| log::warn!("[SIM] {e}"))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
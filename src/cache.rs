@@ -0,0 +1,147 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    asdu::{Asdu, CommonAddr},
+    subscribe::point_updates,
+    Error, Nva, Point, PointValue, Quality, QualityFilter, ScaleTable,
+};
+
+// 客户端的点缓存: 自动应用收到的监视方向 ASDU(单点, 双点, 测量值, 累计量),
+// 保存对端各点的最新值, 品质与时标. 信息对象不带时标时以收到的时间为时标,
+// 响应组召唤(计数量组召唤)的点记录其所属组. 克隆的 PointCache 共享同一份数据,
+// 经 ClientOption::with_point_cache 交给客户端后, 可在其他任务中随时查询
+#[derive(Debug, Clone, Default)]
+pub struct PointCache {
+    points: Arc<RwLock<BTreeMap<(CommonAddr, u16), Point>>>,
+    scales: Arc<RwLock<BTreeMap<(CommonAddr, u16), ScaleTable>>>,
+}
+
+impl PointCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 应用一个 ASDU, 返回更新的点数, 非监视方向过程信息的 ASDU 忽略.
+    // 已有点的组号只在响应组召唤时更新
+    pub fn apply(&self, asdu: &Asdu) -> Result<usize, Error> {
+        self.apply_with(asdu, QualityFilter::Accept)
+    }
+
+    // 同 apply, 品质无效的数据按 filter 处理, 返回的点数不含被丢弃的数据
+    pub fn apply_with(&self, asdu: &Asdu, filter: QualityFilter) -> Result<usize, Error> {
+        let updates = point_updates(asdu)?;
+        let mut n = 0;
+        let mut points = self.points.write().unwrap();
+        for update in updates {
+            let quality = Quality::from(update.point.quality);
+            if !filter.accepts(quality) {
+                continue;
+            }
+            n += 1;
+            let point = points
+                .entry((update.ca, update.ioa))
+                .or_insert(update.point);
+            let group = if update.point.group == 0 {
+                point.group
+            } else {
+                update.point.group
+            };
+            let value = if quality.invalid && filter == QualityFilter::Flag {
+                point.value
+            } else {
+                update.point.value
+            };
+            *point = Point {
+                value,
+                group,
+                ..update.point
+            };
+        }
+        Ok(n)
+    }
+
+    // 点的品质, 见 Quality
+    pub fn quality(&self, ca: CommonAddr, ioa: u16) -> Option<Quality> {
+        self.get(ca, ioa).map(|p| p.quality.into())
+    }
+
+    pub fn get(&self, ca: CommonAddr, ioa: u16) -> Option<Point> {
+        self.points.read().unwrap().get(&(ca, ioa)).copied()
+    }
+
+    pub fn get_single(&self, ca: CommonAddr, ioa: u16) -> Option<bool> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Single(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_double(&self, ca: CommonAddr, ioa: u16) -> Option<u8> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Double(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_normalized(&self, ca: CommonAddr, ioa: u16) -> Option<i16> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Normalized(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    // 规一化值换算的工程值, 未设置换算表时为 -1 ~ +1 的规一化值
+    pub fn get_engineering(&self, ca: CommonAddr, ioa: u16) -> Option<f32> {
+        let nva = Nva(self.get_normalized(ca, ioa)?);
+        Some(match self.scales.read().unwrap().get(&(ca, ioa)) {
+            Some(table) => table.to_engineering(nva),
+            None => nva.to_f32(),
+        })
+    }
+
+    // 设置规一化值的点的工程值换算表, 见 get_engineering
+    pub fn set_scale(&self, ca: CommonAddr, ioa: u16, table: ScaleTable) {
+        self.scales.write().unwrap().insert((ca, ioa), table);
+    }
+
+    pub fn get_scaled(&self, ca: CommonAddr, ioa: u16) -> Option<i16> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Scaled(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_float(&self, ca: CommonAddr, ioa: u16) -> Option<f32> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_counter(&self, ca: CommonAddr, ioa: u16) -> Option<i32> {
+        match self.get(ca, ioa)?.value {
+            PointValue::Counter(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    // 全部点的副本, 按公共地址与信息对象地址排序
+    pub fn snapshot(&self) -> BTreeMap<(CommonAddr, u16), Point> {
+        self.points.read().unwrap().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.read().unwrap().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.points.write().unwrap().clear();
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use bytes::BytesMut;
+use tokio_util::codec::Encoder;
+
+use crate::{asdu::AsduParams, Apdu, Codec, FrameObserver};
+
+// pcap 文件格式 (libpcap), Wireshark 按 TCP 端口 2404 解析 IEC 104
+pub const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+pub const PCAP_VERSION_MAJOR: u16 = 2;
+pub const PCAP_VERSION_MINOR: u16 = 4;
+pub const PCAP_SNAPLEN: u32 = 65535;
+// 链路类型: 原始 IP 报文, 不带以太网头
+pub const LINKTYPE_RAW: u32 = 101;
+
+const IPV4_HEADER_SIZE: usize = 20;
+const IPV6_HEADER_SIZE: usize = 40;
+const TCP_HEADER_SIZE: usize = 20;
+
+// 将会话收发的 APDU 写入 pcap 文件, 每个 APDU 封装为一个带 IP/TCP 头的报文.
+// 作为 FrameObserver 注册到 ClientOption 或 Server, 发送的帧记为 local -> remote,
+// 接收的帧记为 remote -> local. 同一个 PcapWriter 只记录一条连接的地址,
+// 注册到有多个连接的 Server 时, 各连接的报文记在同一对地址下
+pub struct PcapWriter {
+    inner: Mutex<PcapInner>,
+}
+
+struct PcapInner {
+    writer: Box<dyn Write + Send>,
+    codec: Codec,
+    local: SocketAddr,
+    remote: SocketAddr,
+    // 两个方向的 TCP 序号, 使 Wireshark 能够按流重组
+    local_seq: u32,
+    remote_seq: u32,
+    // Instant 与系统时间的对应关系, 用于换算报文时间戳
+    base_instant: Instant,
+    base_time: SystemTime,
+}
+
+impl PcapWriter {
+    // 在 writer 中写入 pcap 文件头
+    pub fn new<W>(writer: W, local: SocketAddr, remote: SocketAddr) -> io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut writer: Box<dyn Write + Send> = Box::new(writer);
+        writer.write_u32::<LittleEndian>(PCAP_MAGIC)?;
+        writer.write_u16::<LittleEndian>(PCAP_VERSION_MAJOR)?;
+        writer.write_u16::<LittleEndian>(PCAP_VERSION_MINOR)?;
+        writer.write_i32::<LittleEndian>(0)?; // 时区
+        writer.write_u32::<LittleEndian>(0)?; // 时间戳精度
+        writer.write_u32::<LittleEndian>(PCAP_SNAPLEN)?;
+        writer.write_u32::<LittleEndian>(LINKTYPE_RAW)?;
+        Ok(PcapWriter {
+            inner: Mutex::new(PcapInner {
+                writer,
+                codec: Codec::default(),
+                local,
+                remote,
+                local_seq: 1,
+                remote_seq: 1,
+                base_instant: Instant::now(),
+                base_time: SystemTime::now(),
+            }),
+        })
+    }
+
+    // 创建 pcap 文件
+    pub fn create<P>(path: P, local: SocketAddr, remote: SocketAddr) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        PcapWriter::new(BufWriter::new(File::create(path)?), local, remote)
+    }
+
+    // ASDU 各字段长度, 与会话的配置一致
+    pub fn with_asdu_params(self, params: AsduParams) -> Self {
+        self.inner.lock().unwrap().codec = Codec::new(params);
+        self
+    }
+
+    // 写入一个 APDU, tx 为 true 时方向为 local -> remote
+    pub fn write_apdu(&self, apdu: &Apdu, at: Instant, tx: bool) -> io::Result<()> {
+        self.inner.lock().unwrap().write_apdu(apdu, at, tx)
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.inner.lock().unwrap().writer.flush()
+    }
+}
+
+impl PcapInner {
+    fn write_apdu(&mut self, apdu: &Apdu, at: Instant, tx: bool) -> io::Result<()> {
+        let mut payload = BytesMut::new();
+        let apdu = Apdu {
+            apci: apdu.apci,
+            asdu: apdu.asdu.clone(),
+        };
+        self.codec
+            .encode(apdu, &mut payload)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let (src, dst, seq, ack) = if tx {
+            (self.local, self.remote, self.local_seq, self.remote_seq)
+        } else {
+            (self.remote, self.local, self.remote_seq, self.local_seq)
+        };
+        let packet = tcp_packet(src, dst, seq, ack, &payload);
+        if tx {
+            self.local_seq = self.local_seq.wrapping_add(payload.len() as u32);
+        } else {
+            self.remote_seq = self.remote_seq.wrapping_add(payload.len() as u32);
+        }
+
+        let time = self.base_time + at.saturating_duration_since(self.base_instant);
+        let ts = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.writer.write_u32::<LittleEndian>(ts.as_secs() as u32)?;
+        self.writer.write_u32::<LittleEndian>(ts.subsec_micros())?;
+        self.writer.write_u32::<LittleEndian>(packet.len() as u32)?;
+        self.writer.write_u32::<LittleEndian>(packet.len() as u32)?;
+        self.writer.write_all(&packet)
+    }
+}
+
+impl FrameObserver for PcapWriter {
+    fn on_rx(&self, apdu: &Apdu, at: Instant) {
+        if let Err(e) = self.write_apdu(apdu, at, false) {
+            log::warn!("[CAPTURE] write error: {e}");
+        }
+    }
+
+    fn on_tx(&self, apdu: &Apdu, at: Instant) {
+        if let Err(e) = self.write_apdu(apdu, at, true) {
+            log::warn!("[CAPTURE] write error: {e}");
+        }
+    }
+}
+
+impl Drop for PcapWriter {
+    fn drop(&mut self) {
+        if let Ok(inner) = self.inner.get_mut() {
+            let _ = inner.writer.flush();
+        }
+    }
+}
+
+// 构造 IP/TCP 报文(PSH, ACK), IPv4 与 IPv6 地址混用时按 IPv4 映射地址处理
+fn tcp_packet(src: SocketAddr, dst: SocketAddr, seq: u32, ack: u32, payload: &[u8]) -> Vec<u8> {
+    let tcp_len = TCP_HEADER_SIZE + payload.len();
+    let mut packet = Vec::with_capacity(IPV6_HEADER_SIZE + tcp_len);
+    match (src.ip(), dst.ip()) {
+        (IpAddr::V4(s), IpAddr::V4(d)) => {
+            let mut header = Vec::with_capacity(IPV4_HEADER_SIZE);
+            header.push(0x45); // 版本 4, 首部长度 5
+            header.push(0);
+            header
+                .write_u16::<BigEndian>((IPV4_HEADER_SIZE + tcp_len) as u16)
+                .unwrap();
+            header.write_u16::<BigEndian>(0).unwrap(); // 标识
+            header.write_u16::<BigEndian>(0x4000).unwrap(); // DF
+            header.push(64); // TTL
+            header.push(6); // TCP
+            header.write_u16::<BigEndian>(0).unwrap();
+            header.extend_from_slice(&s.octets());
+            header.extend_from_slice(&d.octets());
+            let checksum = ipv4_checksum(&header);
+            header[10..12].copy_from_slice(&checksum.to_be_bytes());
+            packet.extend_from_slice(&header);
+        }
+        (s, d) => {
+            let s = match s {
+                IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+                IpAddr::V6(v6) => v6,
+            };
+            let d = match d {
+                IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+                IpAddr::V6(v6) => v6,
+            };
+            packet.write_u32::<BigEndian>(0x6000_0000).unwrap(); // 版本 6
+            packet.write_u16::<BigEndian>(tcp_len as u16).unwrap();
+            packet.push(6); // TCP
+            packet.push(64); // 跳数限制
+            packet.extend_from_slice(&s.octets());
+            packet.extend_from_slice(&d.octets());
+        }
+    }
+
+    packet.write_u16::<BigEndian>(src.port()).unwrap();
+    packet.write_u16::<BigEndian>(dst.port()).unwrap();
+    packet.write_u32::<BigEndian>(seq).unwrap();
+    packet.write_u32::<BigEndian>(ack).unwrap();
+    packet.push((TCP_HEADER_SIZE as u8 / 4) << 4); // 首部长度
+    packet.push(0x18); // PSH, ACK
+    packet.write_u16::<BigEndian>(u16::MAX).unwrap(); // 窗口
+    packet.write_u16::<BigEndian>(0).unwrap(); // 校验和, 不计算
+    packet.write_u16::<BigEndian>(0).unwrap(); // 紧急指针
+    packet.extend_from_slice(payload);
+    packet
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]) as u32)
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
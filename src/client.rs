@@ -1,5 +1,10 @@
 use std::{
-    collections::VecDeque, fmt::Debug, net::SocketAddr, ops::Deref, sync::Arc, time::Duration,
+    collections::VecDeque,
+    fmt::Debug,
+    net::SocketAddr,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use anyhow::Result;
@@ -7,35 +12,75 @@ use chrono::{DateTime, Utc};
 use futures_util::{SinkExt as _, StreamExt as _};
 use std::future::Future;
 use tokio::{
-    net::TcpStream,
     select,
-    sync::{mpsc, Mutex},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc::{self, error::TrySendError},
+        oneshot, watch, Notify,
+    },
+    task::JoinHandle,
     time::sleep,
 };
-use tokio_util::codec::Framed;
+use tokio_util::{codec::Framed, sync::CancellationToken};
 
+#[cfg(feature = "proxy")]
+use crate::ProxyConfig;
+#[cfg(feature = "tls")]
+use crate::TlsConfig;
 use crate::{
     apci::{
-        new_iframe, new_sframe, new_uframe, update_ack_no_out, ApciKind, SApci, UApci,
+        new_iframe, new_sframe, new_uframe, update_ack_no_out, ApciKind, SApci, SeqNum, UApci,
         U_STARTDT_ACTIVE, U_STARTDT_CONFIRM, U_STOPDT_ACTIVE, U_STOPDT_CONFIRM, U_TESTFR_ACTIVE,
         U_TESTFR_CONFIRM,
     },
-    asdu::{Asdu, CauseOfTransmission, CommonAddr, TypeID},
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, CommonAddr, OriginAddr, TypeID},
+    command_queue::CommandQueue,
+    cpara::{
+        parameter_activation, parameter_float, parameter_normal, parameter_scaled,
+        ParameterActivationInfo, ParameterFloatInfo, ParameterNormalInfo, ParameterScaledInfo,
+    },
     cproc::{
         bits_string32_cmd, double_cmd, set_point_cmd_float, set_point_cmd_normal,
         set_point_cmd_scaled, single_cmd, BitsString32CommandInfo, DoubleCommandInfo,
         SetpointCommandFloatInfo, SetpointCommandNormalInfo, SetpointCommandScaledInfo,
         SingleCommandInfo,
     },
-    csys::{counter_interrogation_cmd, interrogation_cmd, ObjectQCC, ObjectQOI},
-    Codec, Error,
+    csys::{
+        clock_synchronization_cmd, counter_interrogation_cmd, interrogation_cmd, ObjectQCC,
+        ObjectQOI, Qoi,
+    },
+    link_state::{LinkEvent, LinkState, SharedLinkState},
+    msys::ObjectCOI,
+    observer::Observers,
+    pacing::Pacer,
+    queue::Delivery,
+    redundancy::RedundancyConnector,
+    stats::SharedStats,
+    trace::{self, Direction},
+    watchdog::LinkIdle,
+    Codec, Connector, Error, FrameObserver, Pacing, PointCache, QualityFilter, ReconnectPolicy,
+    RedundancyGroup, RemoteAddr, Resync, Role, SendQueue, SendQueueOption, SocketOption, Stats,
+    Switchover, TcpConnector, TestFrPolicy,
 };
 
-// TODO:
+// 客户端处理收到的 ASDU 的接口, 返回的 ASDU 经同一连接发送. Client, ClientBuilder 与
+// ClientPool 共用这一实现; 闭包经 ClientBuilder::on_asdu 适配, 无需处理时使用 DefaultHandler
 pub trait ClientHandler {
     type Future: Future<Output = Result<Vec<Asdu>, Error>> + Send;
 
     fn call(&self, asdu: Asdu) -> Self::Future;
+
+    // 收到对端的测试命令(C_TS_NA_1/C_TS_TA_1, 反向链路或双角色网关), 返回 false 时回复否定的激活确认.
+    // 默认接受. 确认由连接任务自动回复, 命令不再交给 call
+    fn call_test(&self, _asdu: &Asdu) -> bool {
+        true
+    }
+
+    // 收到对端的时钟同步命令(C_CS_NA_1), time 为命令中的时标, 返回 false 时回复否定的激活确认.
+    // 默认接受但不校时. 确认由连接任务自动回复, 命令不再交给 call
+    fn call_clock_sync(&self, _asdu: &Asdu, _time: Option<DateTime<Utc>>) -> bool {
+        true
+    }
 }
 
 impl<D> ClientHandler for D
@@ -48,19 +93,187 @@ where
     fn call(&self, asdu: Asdu) -> Self::Future {
         self.deref().call(asdu)
     }
+
+    fn call_test(&self, asdu: &Asdu) -> bool {
+        self.deref().call_test(asdu)
+    }
+
+    fn call_clock_sync(&self, asdu: &Asdu, time: Option<DateTime<Utc>>) -> bool {
+        self.deref().call_clock_sync(asdu, time)
+    }
+}
+
+// 对端发来的测试命令与时钟同步命令交给专门的钩子, 返回自动回复的激活确认; 其他 ASDU 返回 None
+fn reverse_command_con<S: ClientHandler>(handler: &S, asdu: &Asdu) -> Option<Asdu> {
+    let mut cot = asdu.identifier.cot;
+    if cot.cause().get() != Cause::Activation {
+        return None;
+    }
+    let accepted = match asdu.identifier.type_id {
+        TypeID::C_TS_NA_1 | TypeID::C_TS_TA_1 => handler.call_test(asdu),
+        TypeID::C_CS_NA_1 => {
+            let time = asdu
+                .clone()
+                .get_clock_synchronization_cmd()
+                .ok()
+                .and_then(|(_, time)| time);
+            handler.call_clock_sync(asdu, time)
+        }
+        _ => return None,
+    };
+    Some(if accepted {
+        asdu.mirror(Cause::ActivationCon)
+    } else {
+        asdu.mirror_negative(Cause::ActivationCon)
+    })
 }
 
+// 客户端与连接任务之间只共享原子量, watch 通道与短暂持有的同步锁,
+// 发送经命令通道交给连接任务, 调用方不会在锁上等待
 pub struct Client<S> {
     op: ClientOption,
     handler: S,
-    is_active: Arc<Mutex<bool>>,
-    sender: Arc<Mutex<Option<mpsc::UnboundedSender<Request>>>>,
+    // 链路状态, 数据传输是否激活由此判断
+    link: SharedLinkState,
+    // 当前连接的命令通道, 连接断开后为 None
+    sender: Arc<watch::Sender<Option<mpsc::Sender<Request>>>>,
+    waiters: Arc<Mutex<Vec<AsduWaiter>>>,
+    connector: Arc<dyn Connector>,
+    events: broadcast::Sender<ClientEvent>,
+    // 待发送的 I 帧, 按优先级发送, 跨越重连保留
+    queue: Arc<Mutex<SendQueue>>,
+    // 直接写入发送队列后唤醒连接任务
+    wake: Arc<Notify>,
+    // 会话统计, 跨越重连累计
+    stats: SharedStats,
+    // 连接任务及其停止信号, 未启动时为 None
+    task: Arc<Mutex<Option<ClientTask>>>,
+    // 按公共地址排队的控制命令, 见 ClientOption::with_single_outstanding_command
+    pub(crate) commands: Arc<CommandQueue>,
+}
+
+// 连接任务的停止信号与 JoinHandle
+type ClientTask = (CancellationToken, JoinHandle<Result<(), Error>>);
+
+// 等待对端响应的订阅者, 收到的 ASDU 满足过滤条件时转发一份副本
+pub(crate) struct AsduWaiter {
+    filter: Box<dyn Fn(&Asdu) -> bool + Send + Sync>,
+    tx: mpsc::UnboundedSender<Asdu>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct ClientOption {
-    socket_addr: SocketAddr,
+    remote: RemoteAddr,
     auto_reconnect: bool,
+    // 协议角色, 默认为控制站
+    pub(crate) role: Role,
+    // 连接后自动发送 STARTDT
+    pub(crate) auto_start_dt: bool,
+    // 等待命令确认的超时时间
+    pub(crate) command_timeout: Duration,
+    // 收到激活确认后是否继续等待激活终止
+    pub(crate) wait_termination: bool,
+    // 同一公共地址同一时间只执行一个控制命令
+    pub(crate) single_outstanding_command: bool,
+    // 等待召唤过程结束(激活终止)的超时时间
+    pub(crate) interrogation_timeout: Duration,
+    // 向广播地址召唤时预期响应的站, 为空时收集到超时为止
+    pub(crate) common_addrs: Vec<CommonAddr>,
+    // 周期时钟同步: 公共地址, 周期
+    pub(crate) clock_sync: Option<(CommonAddr, Duration)>,
+    // 自动总召唤: 公共地址, 周期
+    pub(crate) auto_gi: Option<(CommonAddr, Duration)>,
+    // TLS 配置, 为 None 时使用明文 TCP
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsConfig>,
+    // 代理, 为 None 时直接连接
+    #[cfg(feature = "proxy")]
+    pub(crate) proxy: Option<ProxyConfig>,
+    // 冗余连接组, 为 Some 时忽略 remote
+    pub(crate) redundancy: Option<RedundancyGroup>,
+    // 连接失败后的重连策略
+    pub(crate) reconnect: ReconnectPolicy,
+    // 连续连接失败的最大重连次数, None 为不限
+    pub(crate) max_retries: Option<u32>,
+    // I 帧发送队列配置
+    pub(crate) send_queue: SendQueueOption,
+    // ASDU 各字段长度
+    pub(crate) asdu_params: AsduParams,
+    // 源发站地址, 发送的 ASDU 未指定时使用
+    pub(crate) orig_addr: OriginAddr,
+    // 原始帧监听者
+    pub(crate) observers: Observers,
+    // 点缓存, 为 Some 时应用收到的监视方向 ASDU
+    pub(crate) point_cache: Option<PointCache>,
+    // 品质无效的数据对点缓存与订阅的处理方式
+    pub(crate) quality_filter: QualityFilter,
+    // 命令通道深度, 对端停止接收时限制积压的请求数
+    pub(crate) channel_depth: usize,
+    // t1: 发送的 I 帧或 U 帧等待确认的超时时间
+    pub(crate) t1: Duration,
+    // I 帧的发送节奏
+    pub(crate) pacing: Pacing,
+    // 测试帧的发送与链路失效判定
+    pub(crate) testfr: TestFrPolicy,
+    // 收到错误的起始字符或长度时的处理方式
+    pub(crate) resync: Resync,
+    // TCP 套接字配置, 自定义连接器时不生效
+    pub(crate) socket: SocketOption,
+}
+
+// 客户端连接的生命周期事件
+#[derive(Debug, Clone)]
+pub enum ClientEvent {
+    /// 传输层连接建立
+    Connected,
+    /// 连接断开及原因
+    Disconnected(Arc<Error>),
+    /// 收到 STARTDT 确认(被控站为收到 STARTDT), 数据传输激活
+    Activated,
+    /// 收到 STOPDT 确认(被控站为收到 STOPDT), 数据传输停止
+    Deactivated,
+    /// TESTFR 测试帧的往返时间
+    TestRoundTrip(Duration),
+    /// 冗余组的主备切换
+    Switchover(Switchover),
+    /// 连接失败, 等待 delay 后进行第 attempt 次重连
+    Reconnecting { attempt: u32, delay: Duration },
+    /// 收到被控站的初始化结束(M_EI_NA_1), ASDU 仍交给 ClientHandler::call.
+    /// 被控站重新启动后一般需要重新总召唤
+    EndOfInitialization { ca: CommonAddr, coi: ObjectCOI },
+}
+
+// Error 不能比较, 断开原因按错误类型与描述比较
+impl PartialEq for ClientEvent {
+    fn eq(&self, other: &Self) -> bool {
+        use ClientEvent as E;
+        match (self, other) {
+            (E::Connected, E::Connected)
+            | (E::Activated, E::Activated)
+            | (E::Deactivated, E::Deactivated) => true,
+            (E::Disconnected(a), E::Disconnected(b)) => {
+                std::mem::discriminant(&**a) == std::mem::discriminant(&**b)
+                    && a.to_string() == b.to_string()
+            }
+            (E::TestRoundTrip(a), E::TestRoundTrip(b)) => a == b,
+            (E::Switchover(a), E::Switchover(b)) => a == b,
+            (
+                E::Reconnecting { attempt, delay },
+                E::Reconnecting {
+                    attempt: other_attempt,
+                    delay: other_delay,
+                },
+            ) => attempt == other_attempt && delay == other_delay,
+            (
+                E::EndOfInitialization { ca, coi },
+                E::EndOfInitialization {
+                    ca: other_ca,
+                    coi: other_coi,
+                },
+            ) => ca == other_ca && coi == other_coi,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -70,8 +283,24 @@ pub enum Request {
     S(SApci),
 }
 
+// 命令通道的默认深度
+pub const DEFAULT_CHANNEL_DEPTH: usize = 256;
+
+// 先取连接任务自身产生的请求(确认, 测试帧, 处理函数的响应), 再取经有界命令通道发送的请求.
+// 命令通道关闭后只等待内部请求
+pub(crate) async fn recv_request(
+    internal: &mut mpsc::UnboundedReceiver<Request>,
+    external: &mut mpsc::Receiver<Request>,
+) -> Option<Request> {
+    select! {
+        biased;
+        req = internal.recv() => req,
+        Some(req) = external.recv() => Some(req),
+    }
+}
+
 pub struct SeqPending {
-    pub seq: u16,
+    pub seq: SeqNum,
     pub send_time: DateTime<Utc>,
 }
 
@@ -80,47 +309,164 @@ where
     S: ClientHandler + Clone + Send + Sync + 'static,
 {
     pub fn new(handler: S, option: ClientOption) -> Self {
+        let (events, _) = broadcast::channel(16);
+        let connector: Arc<dyn Connector> = match &option.redundancy {
+            Some(group) => Arc::new(RedundancyConnector::new(
+                group
+                    .addrs()
+                    .iter()
+                    .map(|addr| (*addr, tcp_connector(&option, (*addr).into())))
+                    .collect(),
+                events.clone(),
+            )),
+            None => Arc::new(tcp_connector(&option, option.remote.clone())),
+        };
+        Self::build(handler, option, connector, events)
+    }
+
+    // 使用自定义的连接器建立传输层连接, ClientOption 中的对端地址, TLS 与冗余组配置不再生效
+    pub fn new_with_connector<C>(handler: S, option: ClientOption, connector: C) -> Self
+    where
+        C: Connector,
+    {
+        Self::new_with_shared_connector(handler, option, Arc::new(connector))
+    }
+
+    pub(crate) fn new_with_shared_connector(
+        handler: S,
+        option: ClientOption,
+        connector: Arc<dyn Connector>,
+    ) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self::build(handler, option, connector, events)
+    }
+
+    fn build(
+        handler: S,
+        option: ClientOption,
+        connector: Arc<dyn Connector>,
+        events: broadcast::Sender<ClientEvent>,
+    ) -> Self {
         Client {
-            op: option,
             handler,
-            is_active: Arc::new(Mutex::new(false)),
-            sender: Arc::new(Mutex::new(None)),
+            link: SharedLinkState::new(),
+            sender: Arc::new(watch::Sender::new(None)),
+            waiters: Arc::new(Mutex::new(Vec::new())),
+            queue: Arc::new(Mutex::new(SendQueue::new(option.send_queue))),
+            wake: Arc::new(Notify::new()),
+            stats: SharedStats::new("client"),
+            task: Arc::new(Mutex::new(None)),
+            commands: Arc::new(CommandQueue::default()),
+            op: option,
+            connector,
+            events,
         }
     }
 
-    // TODO: 防止上层连续调用，导致重复建立连接
+    // 订阅连接的生命周期事件: 连接, 断开, 激活, 停止激活, 测试帧往返时间, 主备切换
+    pub fn events(&self) -> broadcast::Receiver<ClientEvent> {
+        self.events.subscribe()
+    }
+
+    // 启动连接任务, 任务已在运行(包括等待重连)时直接返回
     pub async fn start(&self) -> Result<(), Error> {
-        if self.is_connected().await {
+        let mut task = self.task.lock().unwrap();
+        if task
+            .as_ref()
+            .is_some_and(|(_, handle)| !handle.is_finished())
+        {
             return Ok(());
         }
 
-        tokio::spawn(client_loop(
-            self.is_active.clone(),
+        let shutdown = CancellationToken::new();
+        let client = client_loop(
+            self.link.clone(),
+            self.wake.clone(),
             self.sender.clone(),
+            self.waiters.clone(),
+            self.connector.clone(),
+            self.events.clone(),
+            self.queue.clone(),
+            self.stats.clone(),
             self.handler.clone(),
-            self.op,
+            self.op.clone(),
+            shutdown.clone(),
+        );
+        let handle = tokio::spawn(trace::in_connection_span(
+            "client",
+            self.op.remote.clone(),
+            client,
         ));
+        *task = Some((shutdown, handle));
 
         Ok(())
     }
 
-    pub async fn stop(&mut self) {
-        if !self.is_connected().await {
-            if let Some(sender) = self.sender.lock().await.take() {
-                sender.closed().await;
-            }
+    // 停止连接任务并等待其结束: 发完队列中的 I 帧, 数据传输激活时发送 STOPDT
+    // 并等待确认(最长 t1), 确认收到的 I 帧后关闭连接, 不再重连
+    pub async fn stop(&self) {
+        let task = self.task.lock().unwrap().take();
+        if let Some((shutdown, handle)) = task {
+            shutdown.cancel();
+            let _ = handle.await;
         }
     }
 
-    pub async fn is_connected(&self) -> bool {
-        if let Some(sender) = &*self.sender.lock().await {
-            return !sender.is_closed();
-        }
-        false
+    pub fn is_connected(&self) -> bool {
+        self.sender
+            .borrow()
+            .as_ref()
+            .is_some_and(|sender| !sender.is_closed())
+    }
+
+    // 数据传输是否激活(已收到 STARTDT 确认)
+    pub fn is_active(&self) -> bool {
+        self.is_connected() && self.link.is_active()
+    }
+
+    // 当前的链路状态
+    pub fn link_state(&self) -> LinkState {
+        self.link.get()
+    }
+
+    // 订阅链路状态的变化, 转换规则见 LinkState
+    pub fn watch_link_state(&self) -> watch::Receiver<LinkState> {
+        self.link.subscribe()
     }
 
-    pub async fn is_active(&self) -> bool {
-        self.is_connected().await && *self.is_active.lock().await
+    // 会话统计: 收发帧数, 字节数, 按类型标识的 ASDU 个数, 超时与序号错误次数
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot()
+    }
+
+    // 启用的点缓存
+    pub fn point_cache(&self) -> Option<&PointCache> {
+        self.op.point_cache.as_ref()
+    }
+
+    pub(crate) fn option(&self) -> ClientOption {
+        self.op.clone()
+    }
+
+    pub(crate) fn quality_filter(&self) -> QualityFilter {
+        self.op.quality_filter
+    }
+
+    pub(crate) fn asdu_params(&self) -> AsduParams {
+        self.op.asdu_params
+    }
+
+    // 订阅满足条件的 ASDU, 接收端被丢弃后自动取消订阅
+    pub(crate) async fn subscribe_asdu<F>(&self, filter: F) -> mpsc::UnboundedReceiver<Asdu>
+    where
+        F: Fn(&Asdu) -> bool + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.waiters.lock().unwrap().push(AsduWaiter {
+            filter: Box::new(filter),
+            tx,
+        });
+        rx
     }
 }
 
@@ -128,21 +474,128 @@ impl<S> Client<S>
 where
     S: ClientHandler + Clone + Send + Sync + 'static,
 {
-    pub async fn send_asdu(&self, asdu: Asdu) -> Result<(), Error> {
-        if !self.is_connected().await {
+    // 发送 ASDU. 未激活或连接断开时, 若开启了 buffer_offline, 突发数据进入发送队列, 激活后补发
+    pub async fn send_asdu(&self, mut asdu: Asdu) -> Result<(), Error> {
+        if asdu.identifier.orig_addr == 0 {
+            asdu.identifier.orig_addr = self.op.orig_addr;
+        }
+        if !self.is_active() {
+            let mut queue = self.queue.lock().unwrap();
+            if queue.is_buffered_offline(&asdu) {
+                return queue.push(asdu);
+            }
+        }
+
+        if !self.is_connected() {
             return Err(Error::ErrUseClosedConnection);
         }
 
-        if !self.is_active().await {
+        if !self.is_active() {
             return Err(Error::ErrNotActive);
         }
 
         self.send(Request::I(asdu)).await
     }
 
-    pub async fn send_start_dt(&self) -> anyhow::Result<(), Error> {
-        if !self.is_connected().await {
+    // 批量发送 ASDU, 如总召唤的响应. 整批一次进入命令通道, 不与其他任务发送的 ASDU 交错;
+    // 超过命令通道深度时按通道深度分段. 未激活时, 整批均为可缓存的突发数据才进入发送队列
+    pub async fn send_asdus(&self, mut asdus: Vec<Asdu>) -> Result<(), Error> {
+        if asdus.is_empty() {
+            return Ok(());
+        }
+        for asdu in asdus.iter_mut() {
+            if asdu.identifier.orig_addr == 0 {
+                asdu.identifier.orig_addr = self.op.orig_addr;
+            }
+        }
+        if !self.is_active() {
+            let mut queue = self.queue.lock().unwrap();
+            if asdus.iter().all(|asdu| queue.is_buffered_offline(asdu)) {
+                return asdus.into_iter().try_for_each(|asdu| queue.push(asdu));
+            }
+        }
+
+        let Some(sender) = self.sender.borrow().clone() else {
+            return Err(Error::ErrUseClosedConnection);
+        };
+        if !self.is_active() {
+            return Err(Error::ErrNotActive);
+        }
+
+        let mut asdus = asdus.into_iter().peekable();
+        while asdus.peek().is_some() {
+            let n = asdus.len().min(sender.max_capacity());
+            let permits = sender
+                .reserve_many(n)
+                .await
+                .map_err(|_| Error::ErrUseClosedConnection)?;
+            for (permit, asdu) in permits.zip(&mut asdus) {
+                permit.send(Request::I(asdu));
+            }
+        }
+        Ok(())
+    }
+
+    // 同 send_asdu, 但 ASDU 须在 timeout 内写入传输层, wait_ack 为 true 时须在 timeout 内被对端确认,
+    // 否则返回 ErrTimeout, 超时时仍在发送队列中的 ASDU 不再发送. 未激活时不缓存, 返回 ErrNotActive
+    pub async fn send_asdu_timeout(
+        &self,
+        mut asdu: Asdu,
+        timeout: Duration,
+        wait_ack: bool,
+    ) -> Result<(), Error> {
+        if asdu.identifier.orig_addr == 0 {
+            asdu.identifier.orig_addr = self.op.orig_addr;
+        }
+        if !self.is_connected() {
+            return Err(Error::ErrUseClosedConnection);
+        }
+        if !self.is_active() {
+            return Err(Error::ErrNotActive);
+        }
+        let (tx, rx) = oneshot::channel();
+        self.queue
+            .lock()
+            .unwrap()
+            .push_with(asdu, Some(Delivery { tx, wait_ack }))?;
+        self.wake.notify_one();
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(())) => Ok(()),
+            // 连接断开, 或 ASDU 因队列溢出被丢弃
+            Ok(Err(_)) => Err(Error::ErrUseClosedConnection),
+            Err(_) => Err(Error::ErrTimeout),
+        }
+    }
+
+    // 同 send_asdu, 但命令通道已满(对端停止接收)时不等待, 返回 ErrBufferFull
+    pub fn try_send_asdu(&self, mut asdu: Asdu) -> Result<(), Error> {
+        if asdu.identifier.orig_addr == 0 {
+            asdu.identifier.orig_addr = self.op.orig_addr;
+        }
+        if !self.is_active() {
+            let mut queue = self.queue.lock().unwrap();
+            if queue.is_buffered_offline(&asdu) {
+                return queue.push(asdu);
+            }
+        }
+        let Some(sender) = self.sender.borrow().clone() else {
             return Err(Error::ErrUseClosedConnection);
+        };
+        if !self.is_active() {
+            return Err(Error::ErrNotActive);
+        }
+        sender.try_send(Request::I(asdu)).map_err(|e| match e {
+            TrySendError::Full(_) => Error::ErrBufferFull,
+            TrySendError::Closed(_) => Error::ErrUseClosedConnection,
+        })
+    }
+
+    pub async fn send_start_dt(&self) -> Result<(), Error> {
+        if !self.is_connected() {
+            return Err(Error::ErrNotConnected);
+        }
+        if !self.op.role.is_controlling() {
+            return Err(Error::ErrNotControlling("STARTDT"));
         }
 
         self.send(Request::U(UApci {
@@ -151,10 +604,41 @@ where
         .await
     }
 
-    pub async fn send_stop_dt(&self) -> Result<(), Error> {
-        if !self.is_connected().await {
+    // 发送 TESTFR 测试帧并等待确认, 返回往返时间, 可用于监视链路质量.
+    // t1 内未收到确认返回 ErrTimeout, 连接断开返回 ErrUseClosedConnection
+    pub async fn ping(&self) -> Result<Duration, Error> {
+        if !self.is_connected() {
             return Err(Error::ErrUseClosedConnection);
         }
+        let mut events = self.events.subscribe();
+        let start = tokio::time::Instant::now();
+        self.send(Request::U(UApci {
+            function: U_TESTFR_ACTIVE,
+        }))
+        .await?;
+        let wait = async {
+            loop {
+                match events.recv().await {
+                    Ok(ClientEvent::TestRoundTrip(_)) => return Ok(start.elapsed()),
+                    Ok(ClientEvent::Disconnected(_)) | Err(RecvError::Closed) => {
+                        return Err(Error::ErrUseClosedConnection)
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                }
+            }
+        };
+        tokio::time::timeout(self.op.t1, wait)
+            .await
+            .unwrap_or(Err(Error::ErrTimeout))
+    }
+
+    pub async fn send_stop_dt(&self) -> Result<(), Error> {
+        if !self.is_connected() {
+            return Err(Error::ErrNotConnected);
+        }
+        if !self.op.role.is_controlling() {
+            return Err(Error::ErrNotControlling("STOPDT"));
+        }
 
         self.send(Request::U(UApci {
             function: U_STOPDT_ACTIVE,
@@ -162,9 +646,11 @@ where
         .await
     }
 
+    // 命令通道已满时等待对端接收
     async fn send(&self, req: Request) -> Result<(), Error> {
-        if let Some(sender) = &*self.sender.lock().await {
-            if let Err(e) = sender.send(req) {
+        let sender = self.sender.borrow().clone();
+        if let Some(sender) = sender {
+            if let Err(e) = sender.send(req).await {
                 return Err(Error::ErrAnyHow(anyhow::anyhow!(
                     "sender send error: {}",
                     e
@@ -185,7 +671,7 @@ where
         &self,
         cot: CauseOfTransmission,
         ca: CommonAddr,
-        qoi: ObjectQOI,
+        qoi: impl Into<ObjectQOI>,
     ) -> Result<(), Error> {
         self.send_asdu(interrogation_cmd(cot, ca, qoi)?).await
     }
@@ -194,12 +680,22 @@ where
         &self,
         cot: CauseOfTransmission,
         ca: CommonAddr,
-        qcc: ObjectQCC,
+        qcc: impl Into<ObjectQCC>,
     ) -> Result<(), Error> {
         self.send_asdu(counter_interrogation_cmd(cot, ca, qcc)?)
             .await
     }
 
+    // 时钟同步
+    pub async fn clock_sync_cmd(&self, ca: CommonAddr, time: DateTime<Utc>) -> Result<(), Error> {
+        self.send_asdu(clock_synchronization_cmd(
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            ca,
+            time,
+        )?)
+        .await
+    }
+
     // siq
     pub async fn single_cmd(
         &self,
@@ -269,150 +765,369 @@ where
         self.send_asdu(bits_string32_cmd(type_id, cot, ca, cmd)?)
             .await
     }
+
+    // 测量值参数, 规一化值
+    pub async fn parameter_normal(
+        &self,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        p: ParameterNormalInfo,
+    ) -> Result<(), Error> {
+        self.send_asdu(parameter_normal(cot, ca, p)?).await
+    }
+
+    // 测量值参数, 标度化值
+    pub async fn parameter_scaled(
+        &self,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        p: ParameterScaledInfo,
+    ) -> Result<(), Error> {
+        self.send_asdu(parameter_scaled(cot, ca, p)?).await
+    }
+
+    // 测量值参数, 短浮点数
+    pub async fn parameter_float(
+        &self,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        p: ParameterFloatInfo,
+    ) -> Result<(), Error> {
+        self.send_asdu(parameter_float(cot, ca, p)?).await
+    }
+
+    // 参数激活
+    pub async fn parameter_activation(
+        &self,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        p: ParameterActivationInfo,
+    ) -> Result<(), Error> {
+        self.send_asdu(parameter_activation(cot, ca, p)?).await
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn client_loop<S>(
-    is_active: Arc<Mutex<bool>>,
-    sender: Arc<Mutex<Option<mpsc::UnboundedSender<Request>>>>,
+    link: SharedLinkState,
+    wake: Arc<Notify>,
+    sender: Arc<watch::Sender<Option<mpsc::Sender<Request>>>>,
+    waiters: Arc<Mutex<Vec<AsduWaiter>>>,
+    connector: Arc<dyn Connector>,
+    events: broadcast::Sender<ClientEvent>,
+    queue: Arc<Mutex<SendQueue>>,
+    stats: SharedStats,
     handler: S,
     op: ClientOption,
+    shutdown: CancellationToken,
 ) -> Result<(), Error>
 where
     S: ClientHandler + Clone + Send + Sync + 'static,
 {
+    // 冗余组切换前链路是否处于激活状态
+    let mut restore_active = false;
+    // 连续连接失败的次数
+    let mut attempt = 0;
     loop {
         {
-            let mut send_sn = 0;
-            let mut ack_sendsn = 0;
-            let mut rcv_sn = 0;
-            let mut ack_rcvsn = 0;
+            let mut send_sn = SeqNum::default();
+            let mut ack_sendsn = SeqNum::default();
+            let mut rcv_sn = SeqNum::default();
+            let mut ack_rcvsn = SeqNum::default();
 
-            let mut idle_timeout3_sine = Utc::now();
+            let mut idle = LinkIdle::new();
             let mut test4alive_send_since = DateTime::<Utc>::MAX_UTC;
+            // 连续未被确认的测试帧个数
+            let mut testfr_missed = 0;
             let mut un_ack_rcv_since = DateTime::<Utc>::MAX_UTC;
 
             let mut start_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
             let mut stop_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
 
             let mut pending: VecDeque<SeqPending> = VecDeque::new();
+            // 等待对端确认的 send_asdu_timeout 调用, 连接断开时随之丢弃
+            let mut awaiting_ack: Vec<(SeqNum, oneshot::Sender<()>)> = Vec::new();
 
-            let transport = TcpStream::connect(op.socket_addr).await;
-            if transport.is_err() {
-                if !op.auto_reconnect {
-                    return Err(Error::ErrAnyHow(anyhow::anyhow!("connect error")));
+            let mut clock_sync_since = DateTime::<Utc>::MIN_UTC;
+            // 本次激活后最近一次自动总召唤的时间
+            let mut gi_since: Option<DateTime<Utc>> = None;
+            let mut pacer = Pacer::new(op.pacing);
+
+            link.apply(LinkEvent::Connect);
+            let transport = select! {
+                transport = connector.connect() => transport,
+                _ = shutdown.cancelled() => {
+                    link.apply(LinkEvent::Disconnected);
+                    return Ok(());
+                }
+            };
+            if let Err(e) = &transport {
+                log::warn!("connect error: {e}");
+                attempt += 1;
+                if !op.auto_reconnect || op.max_retries.is_some_and(|max| attempt > max) {
+                    link.apply(LinkEvent::Disconnected);
+                    return Err(Error::ErrAnyHow(anyhow::anyhow!("connect error: {e}")));
+                }
+                let delay = op.reconnect.delay(attempt);
+                let _ = events.send(ClientEvent::Reconnecting { attempt, delay });
+                select! {
+                    _ = sleep(delay) => continue,
+                    _ = shutdown.cancelled() => {
+                        link.apply(LinkEvent::Disconnected);
+                        return Ok(());
+                    }
                 }
-                sleep(Duration::from_secs(60)).await;
-                continue;
             }
-            let mut framed = Framed::new(transport.unwrap(), Codec);
+            attempt = 0;
+            let mut framed = Framed::new(
+                transport.unwrap(),
+                Codec::new(op.asdu_params).with_resync(op.resync),
+            );
             let (tx, mut rx) = mpsc::unbounded_channel();
-            *sender.lock().await = Some(tx.clone());
+            let (cmd_tx, mut cmd_rx) = mpsc::channel(op.channel_depth.max(1));
+            sender.send_replace(Some(cmd_tx));
+            link.apply(LinkEvent::Connected);
+            // 命令通道就绪后再通知, 收到 Connected 后即可发送
+            let _ = events.send(ClientEvent::Connected);
+            stats.opened();
+            // 连接后发送 STARTDT 启动数据传输; 未启用时, 切换到备用链路后仍发送 STARTDT 恢复数据传输.
+            // 被控站等待对端的 STARTDT
+            if restore_active {
+                log::info!("[REDUNDANCY] restore data transfer with STARTDT");
+            }
+            if op.role.is_controlling() && (restore_active || op.auto_start_dt) {
+                let _ = tx.send(Request::U(UApci {
+                    function: U_STARTDT_ACTIVE,
+                }));
+            }
             let mut check_timer = tokio::time::interval(Duration::from_millis(100));
+            // 收到停止信号后, 发完队列再停止数据传输并关闭连接
+            let mut stopping = false;
 
-            'outer: loop {
+            let reason: Error = 'outer: loop {
+                let can_send = link.is_active() && !queue.lock().unwrap().is_empty();
+                if stopping
+                    && !can_send
+                    && rx.is_empty()
+                    && cmd_rx.is_empty()
+                    && stop_dt_active_send_since == DateTime::<Utc>::MAX_UTC
+                {
+                    // 被控站不发送 STOPDT, 直接关闭连接
+                    if link.is_active() && op.role.is_controlling() {
+                        if let Err(e) = tx.send(Request::U(UApci {
+                            function: U_STOPDT_ACTIVE,
+                        })) {
+                            break 'outer e.into();
+                        }
+                        stop_dt_active_send_since = Utc::now();
+                    } else {
+                        if ack_rcvsn != rcv_sn {
+                            let apdu = new_sframe(rcv_sn.value());
+                            trace::frame(Direction::Tx, &apdu);
+                            stats.record_tx(&apdu);
+                            op.observers.on_tx(&apdu);
+                            if let Err(e) = framed.send(apdu).await {
+                                break 'outer e.into();
+                            }
+                        }
+                        break 'outer Error::ErrClientStopped;
+                    }
+                }
                 select! {
+                    // send_asdu_timeout 直接写入发送队列后唤醒
+                    _ = wake.notified() => {}
+
+                    _ = shutdown.cancelled(), if !stopping => {
+                        stopping = true;
+                        cmd_rx.close();
+                    }
+
                     _ = check_timer.tick() => {
-                        if Utc::now() - Duration::from_secs(15) >= test4alive_send_since ||
-                           Utc::now() - Duration::from_secs(15) >= start_dt_active_send_since ||
-                           Utc::now() - Duration::from_secs(15) >= stop_dt_active_send_since  {
+                        stats.depth(queue.lock().unwrap().len(), pending.len());
+                        // 测试帧 t1 内未被确认, 连续丢失 max_missed 次后关闭连接, 否则重发
+                        if Utc::now() - op.t1 >= test4alive_send_since {
+                            testfr_missed += 1;
+                            if testfr_missed >= op.testfr.max_missed() {
+                                log::error!("[CHECK TIMER] test frame alive confirm timeout t, missed {testfr_missed}");
+                                stats.timeout();
+                                link.apply(LinkEvent::Timeout);
+                                break 'outer Error::ErrT1Timeout
+                            }
+                            log::warn!("[CHECK TIMER] test frame confirm missed {testfr_missed}/{}, resend", op.testfr.max_missed());
+                            test4alive_send_since = DateTime::<Utc>::MAX_UTC;
+                            idle.restart();
+                            if let Err(e) = tx.send(Request::U(UApci{ function: U_TESTFR_ACTIVE})) {
+                                break 'outer e.into()
+                            };
+                        }
+
+                        // t1 超时: U 帧未确认或 I 帧未被确认, 关闭连接
+                        if Utc::now() - op.t1 >= start_dt_active_send_since ||
+                           Utc::now() - op.t1 >= stop_dt_active_send_since  {
                            log::error!("[CHECK TIMER] test frame alive confirm timeout t");
-                           break 'outer
+                           stats.timeout();
+                           link.apply(LinkEvent::Timeout);
+                           break 'outer Error::ErrT1Timeout
                         }
 
-                        if  ack_sendsn != send_sn &&
-                            Utc::now() - Duration::from_secs(15) >= pending[0].send_time {
-                            log::warn!("[CHECK TIMER] send ack [sq:{ack_sendsn}] timeout");
-                            ack_sendsn += 1;
-                            pending.pop_front();
+                        if pending.front().is_some_and(|p| Utc::now() - op.t1 >= p.send_time) {
+                            log::error!("[CHECK TIMER] send ack [sq:{ack_sendsn}] timeout");
+                            stats.timeout();
+                            link.apply(LinkEvent::Timeout);
+                            break 'outer Error::ErrT1Timeout
                         }
 
                         if ack_rcvsn != rcv_sn && (un_ack_rcv_since + Duration::from_secs(10) <= Utc::now() ||
-                            idle_timeout3_sine + Duration::from_millis(100) <= Utc::now()) {
-                                if let Err(e) = tx.send(Request::S(SApci { rcv_sn  })) {
-                                    break 'outer
+                            idle.rx_idle() >= Duration::from_millis(100)) {
+                                if let Err(e) = tx.send(Request::S(SApci { rcv_sn: rcv_sn.value() })) {
+                                    break 'outer e.into()
                                 };
                                 ack_rcvsn = rcv_sn;
 
                             }
 
 
-                        if idle_timeout3_sine + Duration::from_secs(20) <= Utc::now() {
+                        if let Some((ca, interval)) = op.clock_sync.filter(|_| op.role.is_controlling()) {
+                            if link.is_active() && clock_sync_since + interval <= Utc::now() {
+                                let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+                                if let Ok(asdu) = clock_synchronization_cmd(cot, ca, Utc::now()) {
+                                    log::debug!("[CHECK TIMER] clock synchronization");
+                                    if let Err(e) = tx.send(Request::I(asdu)) {
+                                        break 'outer e.into()
+                                    };
+                                }
+                                clock_sync_since = Utc::now();
+                            }
+                        }
+
+                        // 激活后立即总召唤, 之后按周期召唤, 周期为 0 时只召唤一次
+                        if let Some((ca, interval)) = op.auto_gi.filter(|_| op.role.is_controlling()) {
+                            let due = gi_since.is_none_or(|since| !interval.is_zero() && since + interval <= Utc::now());
+                            if link.is_active() && due {
+                                let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+                                if let Ok(asdu) = interrogation_cmd(cot, ca, Qoi::StationInterrogation) {
+                                    log::debug!("[CHECK TIMER] general interrogation");
+                                    if let Err(e) = tx.send(Request::I(asdu)) {
+                                        break 'outer e.into()
+                                    };
+                                }
+                                gi_since = Some(Utc::now());
+                            }
+                        }
+
+                        // 已有未确认的测试帧时不再发送, 由上面的丢失计数重发
+                        if test4alive_send_since == DateTime::<Utc>::MAX_UTC && idle.testfr_due(&op.testfr) {
                             log::debug!("[CHECK TIMER] test for active");
                             if let Err(e) = tx.send(Request::U(UApci{ function: U_TESTFR_ACTIVE})) {
-                                break 'outer
+                                break 'outer e.into()
+                            };
+                            idle.restart();
+                        }
+                    }
+
+                    _ = pacer.ready(), if can_send => {
+                        let Some((asdu, delivery)) = queue.lock().unwrap().pop_with() else {
+                            continue
+                        };
+                        let apdu = new_iframe(asdu, send_sn.value(), rcv_sn.value());
+                        if let ApciKind::I(iapci) = ApciKind::from(apdu.apci) {
+                            trace::frame(Direction::Tx, &apdu);
+                            stats.record_tx(&apdu);
+                            op.observers.on_tx(&apdu);
+                            if let Err(e) = framed.send(apdu).await {
+                                break 'outer e.into()
                             };
-                            idle_timeout3_sine = Utc::now();
-                            test4alive_send_since = idle_timeout3_sine;
+                            pending.push_back(SeqPending {
+                                seq: SeqNum::new(iapci.send_sn),
+                                send_time: Utc::now()
+                            });
+                            match delivery {
+                                Some(d) if d.wait_ack => awaiting_ack.push((SeqNum::new(iapci.send_sn), d.tx)),
+                                Some(d) => {
+                                    let _ = d.tx.send(());
+                                }
+                                None => (),
+                            }
+                            pacer.sent();
+                            idle.on_tx_data();
+                            ack_rcvsn = rcv_sn;
+                            send_sn = send_sn.next();
                         }
                     }
 
-                    send_data = rx.recv() => {
+                    send_data = recv_request(&mut rx, &mut cmd_rx) => {
                         if let Some(data) = send_data {
                             match data {
                                 Request::I(asdu) => {
-                                    if !*is_active.lock().await {
+                                    let mut queue = queue.lock().unwrap();
+                                    if !link.is_active() && !queue.is_buffered_offline(&asdu) {
                                         log::warn!("[TX] Server is not active, drop I-frame {asdu:?}");
                                         continue
                                     }
-                                    let apdu = new_iframe(asdu, send_sn, rcv_sn);
-                                    if let ApciKind::I(iapci) = ApciKind::from(apdu.apci) {
-                                        log::debug!("[TX] I-frame: {apdu}");
-                                        log::trace!("[TX] I-frame: {:?} {:?}", iapci, apdu.asdu);
-                                        if let Err(e) = framed.send(apdu).await {
-                                            break 'outer
-                                        };
-                                        pending.push_back(SeqPending {
-                                            seq: iapci.send_sn,
-                                            send_time: Utc::now()
-                                        });
-                                        ack_rcvsn = rcv_sn;
-                                        send_sn  = (send_sn + 1) % 32767;
+                                    if let Err(e) = queue.push(asdu) {
+                                        log::warn!("[TX] {e}, drop I-frame");
                                     }
                                 },
                                 Request::U(uapci) => {
                                     match uapci.function {
-                                        U_STARTDT_ACTIVE => start_dt_active_send_since = Utc::now(),
-                                        U_STOPDT_ACTIVE => stop_dt_active_send_since = Utc::now(),
+                                        U_STARTDT_ACTIVE => {
+                                            start_dt_active_send_since = Utc::now();
+                                            link.apply(LinkEvent::StartDtSent);
+                                        }
+                                        U_STOPDT_ACTIVE => {
+                                            stop_dt_active_send_since = Utc::now();
+                                            link.apply(LinkEvent::StopDtSent);
+                                        }
+                                        // 已有未确认的测试帧时从其发送时间计 t1
+                                        U_TESTFR_ACTIVE if test4alive_send_since == DateTime::<Utc>::MAX_UTC => {
+                                            test4alive_send_since = Utc::now()
+                                        }
                                         _ => ()
 
                                     }
                                     let apdu = new_uframe(uapci.function);
-                                    log::debug!("[TX] U-frame: {apdu}");
-                                    log::trace!("[TX] U-frame: {:?}", uapci);
+                                    trace::frame(Direction::Tx, &apdu);
+                                    stats.record_tx(&apdu);
+                                    op.observers.on_tx(&apdu);
                                     if let Err(e) = framed.send(apdu).await {
-                                        break 'outer
+                                        break 'outer e.into()
                                     }
                                 }
                                 Request::S(sapci) => {
                                     let apdu = new_sframe(sapci.rcv_sn);
-                                    log::debug!("[TX] S-frame: {apdu}");
-                                    log::trace!("[TX] S-frame: {:?}", sapci);
+                                    trace::frame(Direction::Tx, &apdu);
+                                    stats.record_tx(&apdu);
+                                    op.observers.on_tx(&apdu);
                                     if let Err(e) = framed.send(apdu).await {
-                                        break 'outer
+                                        break 'outer e.into()
                                     }
                                 }
                             }
                         } else {
                             log::warn!("[TX] sink closed");
-                            break 'outer
+                            break 'outer Error::ErrUseClosedConnection
                         }
                     }
 
                     apdu = framed.next() => match apdu {
                         Some(Ok(apdu)) => {
-                            idle_timeout3_sine = Utc::now(); // 每收到一个i帧,S帧,U帧, 重置空闲定时器 t3
+                            stats.discarded(framed.codec_mut().take_discarded());
+                            stats.record_rx(&apdu);
+                            op.observers.on_rx(&apdu);
+                            idle.on_rx(); // 每收到一个i帧,S帧,U帧, 重置空闲定时器 t3
 
                             let kind = apdu.apci.into();
                             match kind {
                                 ApciKind::I(iapci) => {
-                                    log::debug!("[RX] I-frame: {apdu}");
-                                    log::trace!("[RX] I-frame: {iapci:#?} {:#?}", apdu.asdu);
+                                    trace::frame(Direction::Rx, &apdu);
 
-                                    if !update_ack_no_out(iapci.rcv_sn, &mut ack_sendsn, &mut send_sn, &mut pending) ||
-                                        iapci.send_sn != rcv_sn {
+                                    if !update_ack_no_out(SeqNum::new(iapci.rcv_sn), &mut ack_sendsn, &mut send_sn, &mut pending) ||
+                                        SeqNum::new(iapci.send_sn) != rcv_sn {
                                         log::error!("fatal incoming acknowledge either earlier than previous or later than sendTime {:?} send_sn:{}",iapci, send_sn);
-                                        break 'outer
+                                        stats.seq_error();
+                                        break 'outer Error::ErrSequence
                                     }
+                                    notify_acked(&mut awaiting_ack, &pending);
 
                                     if ack_rcvsn == rcv_sn {
                                         un_ack_rcv_since = Utc::now();
@@ -420,44 +1135,89 @@ where
 
 
                                     if let Some(asdu) = apdu.asdu {
-                                        // for asdu in handler.call(asdu)? {
-                                        //     tx.send(Request::I(asdu))?;
-                                        // }
-                                        match handler.call(asdu).await {
+                                        if asdu.identifier.type_id == TypeID::M_EI_NA_1 {
+                                            if let Ok((_, coi)) = asdu.clone().get_end_of_initialization() {
+                                                let ca = asdu.identifier.common_addr;
+                                                let _ = events.send(ClientEvent::EndOfInitialization { ca, coi });
+                                            }
+                                        }
+                                        if let Some(cache) = &op.point_cache {
+                                            if let Err(e) = cache.apply_with(&asdu, op.quality_filter) {
+                                                log::warn!("[CACHE] apply {asdu}: {e}");
+                                            }
+                                        }
+                                        {
+                                            let mut waiters = waiters.lock().unwrap();
+                                            waiters.retain(|w| !w.tx.is_closed());
+                                            for w in waiters.iter().filter(|w| (w.filter)(&asdu)) {
+                                                let _ = w.tx.send(asdu.clone());
+                                            }
+                                        }
+                                        let responses = match reverse_command_con(&handler, &asdu) {
+                                            Some(con) => Ok(vec![con]),
+                                            None => handler.call(asdu).await,
+                                        };
+                                        match responses {
                                             Ok(asdus) => {
                                                 for asdu in asdus {
                                                     if let Err(e) = tx.send(Request::I(asdu)) {
-                                                        break 'outer
+                                                        break 'outer e.into()
                                                     }
                                                 }
                                             }
                                             Err(e) => {
-                                                break 'outer
+                                                break 'outer e.into()
                                             }
 
                                         }
                                     }
 
-                                    rcv_sn = (iapci.send_sn + 1) % 32767;
+                                    rcv_sn = SeqNum::new(iapci.send_sn).next();
                                 }
                                 ApciKind::U(uapci) => {
-                                    log::debug!("[RX] U-frame: {apdu}");
-                                    log::trace!("[RX] U-frame: {uapci:#?}");
+                                    trace::frame(Direction::Rx, &apdu);
                                     match uapci.function {
                                         U_STARTDT_CONFIRM => {
                                             start_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
-                                            *is_active.lock().await = true;
+                                            link.apply(LinkEvent::StartDtConfirmed);
+                                            // 每次激活后重新总召唤与时钟同步
+                                            gi_since = None;
+                                            clock_sync_since = DateTime::<Utc>::MIN_UTC;
+                                            let _ = events.send(ClientEvent::Activated);
                                         }
                                         U_STOPDT_CONFIRM => {
                                             stop_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
-                                            *is_active.lock().await = false;
+                                            link.apply(LinkEvent::StopDtConfirmed);
+                                            queue.lock().unwrap().retain_offline();
+                                            let _ = events.send(ClientEvent::Deactivated);
+                                        }
+                                        // 被控站响应对端的 STARTDT/STOPDT
+                                        U_STARTDT_ACTIVE if !op.role.is_controlling() => {
+                                            if let Err(e) = tx.send(Request::U(UApci { function: U_STARTDT_CONFIRM })) {
+                                                break 'outer e.into()
+                                            }
+                                            link.apply(LinkEvent::StartDtConfirmed);
+                                            let _ = events.send(ClientEvent::Activated);
+                                        }
+                                        U_STOPDT_ACTIVE if !op.role.is_controlling() => {
+                                            if let Err(e) = tx.send(Request::U(UApci { function: U_STOPDT_CONFIRM })) {
+                                                break 'outer e.into()
+                                            }
+                                            link.apply(LinkEvent::StopDtConfirmed);
+                                            queue.lock().unwrap().retain_offline();
+                                            let _ = events.send(ClientEvent::Deactivated);
                                         }
                                         U_TESTFR_CONFIRM => {
+                                            if test4alive_send_since != DateTime::<Utc>::MAX_UTC {
+                                                let rtt = (Utc::now() - test4alive_send_since).to_std().unwrap_or_default();
+                                                let _ = events.send(ClientEvent::TestRoundTrip(rtt));
+                                            }
                                             test4alive_send_since = DateTime::<Utc>::MAX_UTC;
+                                            testfr_missed = 0;
                                         }
                                         U_TESTFR_ACTIVE => {
                                             if let Err(e) = tx.send(Request::U(UApci { function: U_TESTFR_CONFIRM })) {
-                                                break 'outer
+                                                break 'outer e.into()
                                             }
                                         }
                                         _ => {
@@ -467,25 +1227,51 @@ where
                                     }
                                 }
                                 ApciKind::S(sapci) => {
-                                    log::debug!("[RX] S-frame: {apdu}");
-                                    log::trace!("[RX] S-frame: {sapci:#?}");
-                                    if !update_ack_no_out(sapci.rcv_sn, &mut ack_sendsn, &mut send_sn, &mut pending) {
+                                    trace::frame(Direction::Rx, &apdu);
+                                    if !update_ack_no_out(SeqNum::new(sapci.rcv_sn), &mut ack_sendsn, &mut send_sn, &mut pending) {
                                         log::error!("fatal incoming acknowledge either earlier than previous or later than sendTime {:?} rcv_sn:{}", sapci,rcv_sn);
-                                        break 'outer
+                                        stats.seq_error();
+                                        break 'outer Error::ErrSequence
                                     }
-                                    ack_sendsn = sapci.rcv_sn;
+                                    notify_acked(&mut awaiting_ack, &pending);
+                                    ack_sendsn = SeqNum::new(sapci.rcv_sn);
                                 }
                             }
 
                         },
                         _ =>  {
                             log::info!("[RX] Stream closed");
-                            break 'outer
+                            break 'outer Error::ErrConnectionClosed
                         }
                     }
                 }
+            };
+            stats.closed();
+            stats.depth(0, 0);
+            if shutdown.is_cancelled() {
+                let _ = framed.close().await;
+                sender.send_replace(None);
+                link.apply(LinkEvent::Disconnected);
+                log::info!("stopped: {reason}");
+                let _ = events.send(ClientEvent::Disconnected(Arc::new(reason)));
+                return Ok(());
             }
-            *is_active.lock().await = false;
+            log::info!("disconnected: {reason}");
+            let _ = events.send(ClientEvent::Disconnected(Arc::new(reason)));
+            restore_active = op.redundancy.is_some() && link.is_active();
+            link.apply(LinkEvent::Disconnected);
+            queue.lock().unwrap().retain_offline();
+        }
+    }
+}
+
+// 通知已被对端确认的 ASDU 的发送方
+fn notify_acked(awaiting: &mut Vec<(SeqNum, oneshot::Sender<()>)>, pending: &VecDeque<SeqPending>) {
+    for (seq, tx) in std::mem::take(awaiting) {
+        if pending.iter().any(|p| p.seq == seq) {
+            awaiting.push((seq, tx));
+        } else {
+            let _ = tx.send(());
         }
     }
 }
@@ -493,17 +1279,253 @@ where
 impl ClientOption {
     pub fn new(socket_addr: SocketAddr, auto_reconnect: bool) -> Self {
         ClientOption {
-            socket_addr,
+            remote: socket_addr.into(),
             auto_reconnect,
+            ..Default::default()
         }
     }
+
+    // 连接主机名, host 为 "主机名:端口" 或 "[IPv6 地址]:端口". 每次(重)连接时重新解析,
+    // 依次尝试解析出的地址直到连接成功
+    pub fn new_host(host: impl Into<String>, auto_reconnect: bool) -> Self {
+        ClientOption {
+            remote: RemoteAddr::Host(host.into()),
+            auto_reconnect,
+            ..Default::default()
+        }
+    }
+
+    // 对端地址
+    pub fn remote(&self) -> &RemoteAddr {
+        &self.remote
+    }
+
+    // 协议角色, 默认为控制站. 为 Role::Controlled 时客户端发起连接后作为被控站:
+    // 不发送 STARTDT/STOPDT 而是响应对端的 STARTDT/STOPDT, 不进行自动总召唤与时钟同步,
+    // 收到的命令交给 ClientHandler, 其返回的 ASDU(确认, 响应数据)发送给对端
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    // 连接建立后是否自动发送 STARTDT, 默认为 true, 收到确认后发出 ClientEvent::Activated.
+    // 为 false 时由应用调用 send_start_dt 启动数据传输
+    pub fn with_auto_start_dt(mut self, auto_start_dt: bool) -> Self {
+        self.auto_start_dt = auto_start_dt;
+        self
+    }
+
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    pub fn with_wait_termination(mut self, wait: bool) -> Self {
+        self.wait_termination = wait;
+        self
+    }
+
+    // 同一公共地址同一时间只执行一个控制命令, 默认关闭. 开启后经 *_confirmed 与选择后执行发送的控制命令
+    // 按公共地址排队, 前一个命令收到激活终止或超时(command_timeout)后才发送下一个;
+    // 排队的命令见 Client::queued_commands, 可由 Client::cancel_queued_command 取消
+    pub fn with_single_outstanding_command(mut self, enable: bool) -> Self {
+        self.single_outstanding_command = enable;
+        self
+    }
+
+    pub fn with_interrogation_timeout(mut self, timeout: Duration) -> Self {
+        self.interrogation_timeout = timeout;
+        self
+    }
+
+    // 对端的各站公共地址. 向广播地址召唤时, 这些站全部激活终止(或否定确认)后召唤结束;
+    // 未设置时无法判断还有哪些站未响应, 召唤持续到 interrogation_timeout
+    pub fn with_common_addrs<I>(mut self, common_addrs: I) -> Self
+    where
+        I: IntoIterator<Item = CommonAddr>,
+    {
+        self.common_addrs = common_addrs.into_iter().collect();
+        self
+    }
+
+    // 数据传输激活后向 ca 发送时钟同步命令, 之后按周期同步
+    pub fn with_auto_clock_sync(mut self, ca: CommonAddr, interval: Duration) -> Self {
+        self.clock_sync = Some((ca, interval));
+        self
+    }
+
+    #[deprecated(note = "use with_auto_clock_sync")]
+    pub fn with_clock_sync(self, ca: CommonAddr, interval: Duration) -> Self {
+        self.with_auto_clock_sync(ca, interval)
+    }
+
+    // 数据传输激活后向 ca 发起站召唤, 之后按周期召唤, interval 为 0 时每次激活只召唤一次.
+    // 召唤的响应与突发数据一样交给 ClientHandler 及点缓存
+    pub fn with_auto_gi(mut self, ca: CommonAddr, interval: Duration) -> Self {
+        self.auto_gi = Some((ca, interval));
+        self
+    }
+
+    // 使用 IEC 62351-3 TLS 加密链路, 端口一般为 IEC62351_TLS_PORT
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    // 经 SOCKS5 或 HTTP CONNECT 代理连接被控站, 对端为主机名时由代理解析
+    #[cfg(feature = "proxy")]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    // 连接失败后的重连策略, 默认每 60 秒重连一次
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    // 连续连接失败 max 次后不再重连
+    pub fn with_max_retries(mut self, max: u32) -> Self {
+        self.max_retries = Some(max);
+        self
+    }
+
+    // I 帧发送队列的容量, 溢出处理及未激活期间是否缓存突发数据
+    pub fn with_send_queue(mut self, send_queue: SendQueueOption) -> Self {
+        self.send_queue = send_queue;
+        self
+    }
+
+    // ASDU 各字段长度, 默认为 IEC 104 标准长度, 仅在对端使用非标准长度时设置
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        self.asdu_params = params;
+        self
+    }
+
+    // 源发站地址, 多个主站经同一前置机接入时, 服务端按此地址将响应送回对应的主站
+    pub fn with_orig_addr(mut self, orig_addr: OriginAddr) -> Self {
+        self.orig_addr = orig_addr;
+        self
+    }
+
+    // 注册原始帧监听者, 接收每一个收发的 APDU, 可多次调用注册多个
+    pub fn with_frame_observer<O>(mut self, observer: O) -> Self
+    where
+        O: FrameObserver,
+    {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    // 命令通道深度, 默认 DEFAULT_CHANNEL_DEPTH. 对端停止接收时,
+    // 通道满后 send_asdu 等待, try_send_asdu 返回 ErrBufferFull
+    pub fn with_channel_depth(mut self, depth: usize) -> Self {
+        self.channel_depth = depth;
+        self
+    }
+
+    // 发送或测试 APDU 的超时时间 t1, 默认 15 秒. 发送的 I 帧, TESTFR, STARTDT 或 STOPDT
+    // 在 t1 内未被确认时关闭连接, 断开原因为 ErrT1Timeout
+    pub fn with_t1(mut self, t1: Duration) -> Self {
+        self.t1 = t1;
+        self
+    }
+
+    // I 帧的发送节奏, 默认不限制
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    // 收到错误的起始字符或长度时的处理方式, 默认 Resync::Strict 关闭连接.
+    // Resync::Lenient 丢弃字节直到下一个起始字符, 丢弃的字节数见 Stats::discarded_bytes
+    pub fn with_resync(mut self, resync: Resync) -> Self {
+        self.resync = resync;
+        self
+    }
+
+    // 测试帧的发送间隔(t3)与链路失效前允许丢失的确认数, 默认空闲 20 秒发送, 丢失 1 次即关闭连接
+    pub fn with_testfr(mut self, testfr: TestFrPolicy) -> Self {
+        self.testfr = testfr;
+        self
+    }
+
+    // 启用点缓存, 收到的监视方向 ASDU 在交给处理函数之前应用到 cache
+    pub fn with_point_cache(mut self, cache: PointCache) -> Self {
+        self.point_cache = Some(cache);
+        self
+    }
+
+    // 品质无效(IV)的数据对点缓存与订阅的处理方式, 默认照常应用.
+    // 交给处理函数的 ASDU 不受影响
+    pub fn with_quality_filter(mut self, filter: QualityFilter) -> Self {
+        self.quality_filter = filter;
+        self
+    }
+
+    // TCP_NODELAY, 保活, 本地地址与网络接口等套接字配置, 冗余组的各地址共用
+    pub fn with_socket(mut self, socket: SocketOption) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    // 使用冗余连接组: 连接第一个可达的地址, 连接断开后切换到下一个地址,
+    // 切换前链路处于激活状态时自动发送 STARTDT
+    pub fn with_redundancy_group(mut self, group: RedundancyGroup) -> Self {
+        self.redundancy = Some(group);
+        self
+    }
 }
 
 impl Default for ClientOption {
     fn default() -> Self {
         Self {
-            socket_addr: "127.0.0.1:2404".parse().unwrap(),
+            remote: RemoteAddr::Addr("127.0.0.1:2404".parse().unwrap()),
             auto_reconnect: true,
+            role: Role::Controlling,
+            auto_start_dt: true,
+            command_timeout: Duration::from_secs(10),
+            wait_termination: false,
+            single_outstanding_command: false,
+            interrogation_timeout: Duration::from_secs(60),
+            common_addrs: Vec::new(),
+            clock_sync: None,
+            auto_gi: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "proxy")]
+            proxy: None,
+            redundancy: None,
+            reconnect: ReconnectPolicy::default(),
+            max_retries: None,
+            send_queue: SendQueueOption::default(),
+            asdu_params: AsduParams::default(),
+            orig_addr: 0,
+            observers: Observers::default(),
+            point_cache: None,
+            quality_filter: QualityFilter::default(),
+            channel_depth: DEFAULT_CHANNEL_DEPTH,
+            t1: Duration::from_secs(15),
+            testfr: TestFrPolicy::default(),
+            resync: Resync::Strict,
+            pacing: Pacing::default(),
+            socket: SocketOption::default(),
         }
     }
 }
+
+fn tcp_connector(op: &ClientOption, remote: RemoteAddr) -> TcpConnector {
+    let connector = TcpConnector::new_remote(remote).with_socket(op.socket.clone());
+    #[cfg(feature = "proxy")]
+    let connector = match &op.proxy {
+        Some(proxy) => connector.with_proxy(proxy.clone()),
+        None => connector,
+    };
+    #[cfg(feature = "tls")]
+    if let Some(tls) = &op.tls {
+        return connector.with_tls(tls.clone());
+    }
+    connector
+}
@@ -0,0 +1,121 @@
+use std::{
+    future,
+    sync::{Arc, Mutex},
+};
+
+use crate::{asdu::Asdu, Client, ClientHandler, ClientOption, Connector, Error, PointCache};
+
+// 默认的客户端处理函数: 不处理收到的 ASDU, 也不回复.
+// 经 Client::builder 创建且未设置点缓存时自动启用 PointCache, 收到的数据记录在缓存中,
+// 适用于只需要点缓存与订阅接口的轮询工具
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultHandler;
+
+impl ClientHandler for DefaultHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 以闭包处理收到的 ASDU, 闭包可以修改捕获的状态, 由连接任务串行调用
+pub struct FnHandler<F> {
+    f: Arc<Mutex<F>>,
+}
+
+impl<F> Clone for FnHandler<F> {
+    fn clone(&self) -> Self {
+        FnHandler { f: self.f.clone() }
+    }
+}
+
+impl<F> ClientHandler for FnHandler<F>
+where
+    F: FnMut(Asdu) + Send,
+{
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        (self.f.lock().unwrap())(asdu);
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 客户端的构建器, 处理函数可选
+pub struct ClientBuilder<S> {
+    handler: S,
+    option: ClientOption,
+    connector: Option<Arc<dyn Connector>>,
+    // 未设置处理函数时启用点缓存记录收到的数据
+    record: bool,
+}
+
+impl Client<DefaultHandler> {
+    // 创建构建器, 默认使用 DefaultHandler 与 ClientOption::default()
+    pub fn builder() -> ClientBuilder<DefaultHandler> {
+        ClientBuilder {
+            handler: DefaultHandler,
+            option: ClientOption::default(),
+            connector: None,
+            record: true,
+        }
+    }
+}
+
+impl<S> ClientBuilder<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    pub fn option(mut self, option: ClientOption) -> Self {
+        self.option = option;
+        self
+    }
+
+    // 使用自定义的连接器, 同 Client::new_with_connector
+    pub fn connector<C>(mut self, connector: C) -> Self
+    where
+        C: Connector,
+    {
+        self.connector = Some(Arc::new(connector));
+        self
+    }
+
+    pub fn handler<H>(self, handler: H) -> ClientBuilder<H>
+    where
+        H: ClientHandler + Clone + Send + Sync + 'static,
+    {
+        ClientBuilder {
+            handler,
+            option: self.option,
+            connector: self.connector,
+            record: false,
+        }
+    }
+
+    // 以闭包处理收到的每一个 ASDU, 不回复
+    pub fn on_asdu<F>(self, f: F) -> ClientBuilder<FnHandler<F>>
+    where
+        F: FnMut(Asdu) + Send + 'static,
+    {
+        self.handler(FnHandler {
+            f: Arc::new(Mutex::new(f)),
+        })
+    }
+
+    pub fn build(self) -> Client<S> {
+        let ClientBuilder {
+            handler,
+            mut option,
+            connector,
+            record,
+        } = self;
+        if record && option.point_cache.is_none() {
+            option.point_cache = Some(PointCache::new());
+        }
+        match connector {
+            Some(connector) => Client::new_with_shared_connector(handler, option, connector),
+            None => Client::new(handler, option),
+        }
+    }
+}
@@ -1,20 +1,95 @@
 use anyhow::{anyhow, Result};
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{BufMut, BytesMut};
 use tokio_util::codec::{Decoder, Encoder};
 
 use crate::frame::{
-    apci::{Apci, ApciKind, APCI_FIELD_SIZE, APDU_SIZE_MAX, START_FRAME},
+    apci::{Apci, ApciKind, APCICTL_FIELD_SIZE, APCI_FIELD_SIZE, APDU_SIZE_MAX, START_FRAME},
+    asdu::{Asdu, AsduParams},
     Apdu,
 };
 
+// 收到错误的起始字符或长度时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Resync {
+    /// 解码返回错误, 连接关闭
+    #[default]
+    Strict,
+    /// 丢弃字节直到下一个起始字符 0x68, 连接保持. 丢弃的字节数计入统计
+    Lenient,
+}
+
 #[derive(Debug, PartialEq, Default)]
-pub struct Codec;
+pub struct Codec {
+    params: AsduParams,
+    resync: Resync,
+    // 重新同步时丢弃的字节数, 累计
+    discarded: u64,
+}
+
+impl Codec {
+    // 使用非标准字段长度的 ASDU, IEC 104 应使用 Codec::default()
+    pub fn new(params: AsduParams) -> Self {
+        Codec {
+            params,
+            ..Default::default()
+        }
+    }
+
+    // 错误的起始字符或长度的处理方式, 默认 Resync::Strict
+    #[must_use]
+    pub fn with_resync(mut self, resync: Resync) -> Self {
+        self.resync = resync;
+        self
+    }
+
+    pub fn params(&self) -> AsduParams {
+        self.params
+    }
+
+    // 重新同步时累计丢弃的字节数
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    // 取出上次调用后丢弃的字节数
+    pub(crate) fn take_discarded(&mut self) -> u64 {
+        std::mem::take(&mut self.discarded)
+    }
+
+    // 丢弃 buf 开头的 n 个字节
+    fn discard(&mut self, buf: &mut BytesMut, n: usize) {
+        let _ = buf.split_to(n);
+        self.discarded += n as u64;
+    }
+
+    // 解析一段字节中的全部 APDU, 如日志或抓包中记录的帧, 末尾不完整的帧视为错误
+    pub fn decode_slice(&mut self, data: &[u8]) -> Result<Vec<Apdu>> {
+        let mut buf = BytesMut::from(data);
+        let mut apdus = Vec::new();
+        while let Some(apdu) = self.decode(&mut buf)? {
+            apdus.push(apdu);
+        }
+        if !buf.is_empty() {
+            return Err(anyhow!("incomplete APDU: {} bytes left", buf.len()));
+        }
+        Ok(apdus)
+    }
+}
 
 impl Encoder<Apdu> for Codec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, apdu: Apdu, buf: &mut BytesMut) -> Result<()> {
-        let apci = apdu.apci;
+        let mut apci = apdu.apci;
+        let asdu_raw = match apdu.asdu {
+            Some(asdu) => {
+                let raw = asdu.to_bytes_with(&self.params)?;
+                // 长度随 ASDU 字段长度变化
+                apci.apdu_length = (APCICTL_FIELD_SIZE + raw.len()) as u8;
+                Some(raw)
+            }
+            None => None,
+        };
 
         buf.put_u8(apci.start);
         buf.put_u8(apci.apdu_length);
@@ -23,8 +98,7 @@ impl Encoder<Apdu> for Codec {
         buf.put_u8(apci.ctrl3);
         buf.put_u8(apci.ctrl4);
 
-        if let Some(asdu) = apdu.asdu {
-            let asdu_raw: Bytes = asdu.try_into()?;
+        if let Some(asdu_raw) = asdu_raw {
             buf.extend(asdu_raw);
         }
 
@@ -38,9 +112,36 @@ impl Decoder for Codec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>> {
+        if self.resync == Resync::Lenient {
+            // 跳过起始字符之前的字节, 以及长度错误的帧的起始字符
+            loop {
+                match buf.iter().position(|&b| b == START_FRAME) {
+                    Some(at) => self.discard(buf, at),
+                    None => {
+                        let n = buf.len();
+                        self.discard(buf, n);
+                        return Ok(None);
+                    }
+                }
+                if buf.len() < 2
+                    || (APCI_FIELD_SIZE..=APDU_SIZE_MAX).contains(&(buf[1] as usize + 2))
+                {
+                    break;
+                }
+                log::warn!(
+                    "[CODEC] invalid APDU length {}, resync",
+                    buf[1] as usize + 2
+                );
+                self.discard(buf, 1);
+            }
+        }
         if buf.len() < APCI_FIELD_SIZE {
             return Ok(None);
         }
+        // 不等待整帧到达, 起始字符错误时立即报错
+        if buf[0] != START_FRAME {
+            return Err(anyhow!("Invalid start frame:{}", buf[0]));
+        }
         let len = buf[1] as usize + 2;
         if !(APCI_FIELD_SIZE..=APDU_SIZE_MAX).contains(&len) {
             return Err(anyhow!("Invalid APDU length:{}", len));
@@ -50,9 +151,6 @@ impl Decoder for Codec {
             return Ok(None);
         }
         let apci_data = buf.split_to(APCI_FIELD_SIZE);
-        if apci_data[0] != START_FRAME {
-            return Err(anyhow!("Invalid start frame:{}", apci_data[0]));
-        }
         let apci = Apci {
             start: apci_data[0],
             apdu_length: apci_data[1],
@@ -67,7 +165,7 @@ impl Decoder for Codec {
         match apci_kind {
             ApciKind::I(_) => {
                 let asdu_data = buf.split_to(len - APCI_FIELD_SIZE).freeze();
-                let asdu = asdu_data.try_into();
+                let asdu = Asdu::from_bytes_with(asdu_data, &self.params);
 
                 if asdu.is_err() {
                     return Ok(Some(Apdu { apci, asdu: None }));
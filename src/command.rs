@@ -0,0 +1,527 @@
+use std::time::Duration;
+
+use bit_struct::*;
+use chrono::Utc;
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, CommonAddr, IntoInfoObjAddr, TypeID},
+    authorizer::is_control_command,
+    client::{Client, ClientHandler},
+    command_queue::{CommandTicket, QueuedCommand},
+    cproc::{
+        double_cmd, set_point_cmd_float, set_point_cmd_normal, set_point_cmd_scaled, single_cmd,
+        DoubleCommandInfo, SetpointCommandFloatInfo, SetpointCommandNormalInfo,
+        SetpointCommandScaledInfo, SingleCommandInfo,
+    },
+    csys::{read_cmd, test_command_cp56time2a},
+    interrogation::recv_until,
+    metrics,
+    payload::AsduPayload,
+    Error,
+};
+
+// 命令的肯定确认结果, 否定确认或未知的类型标识/传送原因/公共地址/信息对象地址
+// 以 Error::ErrNegativeConfirm 返回
+#[derive(Debug, Clone)]
+pub struct CommandResult {
+    /// 确认报文的传送原因, 激活确认或停止激活确认
+    pub cause: Cause,
+    /// 是否收到激活终止
+    pub terminated: bool,
+    /// 对端返回的确认报文
+    pub asdu: Asdu,
+}
+
+impl CommandResult {
+    // 肯定的激活确认或停止激活确认
+    pub fn is_positive(&self) -> bool {
+        self.cause == Cause::ActivationCon || self.cause == Cause::DeactivationCon
+    }
+
+    // 撤销该命令的句柄, 见 Client::cancel_command
+    pub fn handle(&self) -> CommandHandle {
+        CommandHandle::of(&self.asdu)
+    }
+}
+
+// 已发出命令的句柄, 保存撤销命令所需的停止激活报文(类型标识, 公共地址与信息对象与原命令相同)
+#[derive(Debug, Clone)]
+pub struct CommandHandle {
+    deactivation: Asdu,
+}
+
+impl CommandHandle {
+    // 由发出的命令或其确认报文生成
+    pub fn of(cmd: &Asdu) -> Self {
+        let mut deactivation = cmd.clone();
+        deactivation.identifier.cot.cause().set(Cause::Deactivation);
+        deactivation.identifier.cot.set_negative(false);
+        CommandHandle { deactivation }
+    }
+
+    pub fn type_id(&self) -> TypeID {
+        self.deactivation.identifier.type_id
+    }
+
+    pub fn ca(&self) -> CommonAddr {
+        self.deactivation.identifier.common_addr
+    }
+
+    // 句柄不关联连接, 按 IEC 104 的 3 字节地址解码, 适用于任意信息对象地址长度
+    pub fn ioa(&self) -> Option<u32> {
+        first_ioa(&self.deactivation, &AsduParams::IEC104)
+    }
+}
+
+// 命令与确认报文的匹配键: 类型标识, 公共地址, 信息对象地址
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CommandKey {
+    type_id: TypeID,
+    ca: CommonAddr,
+    ioa: Option<u32>,
+    params: AsduParams,
+}
+
+impl CommandKey {
+    pub(crate) fn of(asdu: &Asdu, params: &AsduParams) -> Self {
+        CommandKey {
+            type_id: asdu.identifier.type_id,
+            ca: asdu.identifier.common_addr,
+            ioa: first_ioa(asdu, params),
+            params: *params,
+        }
+    }
+
+    // 判断收到的报文是否为该命令的镜像
+    pub(crate) fn matches(&self, asdu: &Asdu) -> bool {
+        let mut cot = asdu.identifier.cot;
+        let cause = cot.cause().get();
+        self.type_id == asdu.identifier.type_id
+            && self.ca == asdu.identifier.common_addr
+            && self.ioa == first_ioa(asdu, &self.params)
+            && matches!(
+                cause,
+                Cause::ActivationCon
+                    | Cause::DeactivationCon
+                    | Cause::ActivationTerm
+                    | Cause::UnknownTypeID
+                    | Cause::UnknownCOT
+                    | Cause::UnknownCA
+                    | Cause::UnknownIOA
+            )
+    }
+}
+
+// 第一个信息对象地址, 按 params 的信息对象地址长度解码
+pub(crate) fn first_ioa(asdu: &Asdu, params: &AsduParams) -> Option<u32> {
+    let raw = asdu.raw.get(..params.ioa_size)?;
+    let mut addr = [0u8; 4];
+    addr[..raw.len()].copy_from_slice(raw);
+    Some(u32::from_le_bytes(addr))
+}
+
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 发送命令并等待对端的确认, 超时时间见 ClientOption::with_command_timeout,
+    // 否定确认返回 Error::ErrNegativeConfirm
+    pub async fn send_cmd_confirmed(&self, asdu: Asdu) -> Result<CommandResult, Error> {
+        // 排队执行时持有到激活终止或超时
+        let turn = self.command_turn(&asdu).await?;
+        let wait_termination = self.option().wait_termination || turn.is_some();
+        self.confirm(asdu, wait_termination).await
+    }
+
+    // 排队中与执行中的控制命令, 见 ClientOption::with_single_outstanding_command
+    pub fn queued_commands(&self) -> Vec<QueuedCommand> {
+        self.commands.list()
+    }
+
+    // 撤销已发出的命令(如选择后尚未执行, 或持续执行中的调节命令): 发送停止激活并等待停止激活确认.
+    // 停止激活不参与单命令排队; 仍在等待该命令确认的调用随之返回, 结果的 cause 为 DeactivationCon
+    pub async fn cancel_command(&self, handle: &CommandHandle) -> Result<CommandResult, Error> {
+        let asdu = handle.deactivation.clone();
+        let key = CommandKey::of(&asdu, &self.option().asdu_params);
+        // 原命令迟到的激活确认与激活终止不作为停止激活的确认
+        let rx = self
+            .subscribe_asdu(move |a| {
+                let mut cot = a.identifier.cot;
+                let cause = cot.cause().get();
+                key.matches(a) && cause != Cause::ActivationCon && cause != Cause::ActivationTerm
+            })
+            .await;
+        self.send_asdu(asdu).await?;
+        let timeout = self.option().command_timeout;
+        wait_confirm(rx, handle.type_id(), timeout, false).await
+    }
+
+    // 取消排队中的命令, 其调用返回 Error::ErrCommandCancelled. 命令已发送或不存在时返回 false
+    pub fn cancel_queued_command(&self, id: u64) -> bool {
+        self.commands.cancel(id)
+    }
+
+    // 取消公共地址下所有排队中的命令, 返回取消的个数
+    pub fn cancel_queued_commands(&self, ca: CommonAddr) -> usize {
+        self.commands.cancel_all(ca)
+    }
+
+    // 开启单命令执行时, 控制命令按公共地址排队, 返回的 CommandTicket 被丢弃前该公共地址不发送其他命令.
+    // 停止激活用于撤销执行中的命令, 不排队
+    async fn command_turn(&self, asdu: &Asdu) -> Result<Option<CommandTicket>, Error> {
+        let type_id = asdu.identifier.type_id;
+        let mut cot = asdu.identifier.cot;
+        if !self.option().single_outstanding_command
+            || !is_control_command(type_id)
+            || cot.cause().get() == Cause::Deactivation
+        {
+            return Ok(None);
+        }
+        let mut ticket = self.commands.enqueue(asdu, &self.option().asdu_params);
+        ticket.ready().await?;
+        Ok(Some(ticket))
+    }
+
+    async fn confirm(&self, asdu: Asdu, wait_termination: bool) -> Result<CommandResult, Error> {
+        let key = CommandKey::of(&asdu, &self.option().asdu_params);
+        let type_id = asdu.identifier.type_id;
+        let rx = self.subscribe_asdu(move |a| key.matches(a)).await;
+        self.send_asdu(asdu).await?;
+        let timeout = self.option().command_timeout;
+        wait_confirm(rx, type_id, timeout, wait_termination).await
+    }
+
+    // 单命令, 等待确认
+    pub async fn single_cmd_confirmed(
+        &self,
+        type_id: TypeID,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        cmd: SingleCommandInfo,
+    ) -> Result<CommandResult, Error> {
+        self.send_cmd_confirmed(single_cmd(type_id, cot, ca, cmd)?)
+            .await
+    }
+
+    // 双命令, 等待确认
+    pub async fn double_cmd_confirmed(
+        &self,
+        type_id: TypeID,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        cmd: DoubleCommandInfo,
+    ) -> Result<CommandResult, Error> {
+        self.send_cmd_confirmed(double_cmd(type_id, cot, ca, cmd)?)
+            .await
+    }
+
+    // 测试命令: 发送带当前时标的 C_TS_TA_1, 等待激活确认并校验回送的测试字与时标, 返回往返时延.
+    // 超时时间见 ClientOption::with_command_timeout, 回送内容不一致时返回 Error::ErrTestMismatch
+    pub async fn test_cmd(&self, ca: CommonAddr) -> Result<Duration, Error> {
+        let asdu = test_command_cp56time2a(
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            ca,
+            Utc::now(),
+        )?;
+        let sent = asdu.raw.clone();
+        let key = CommandKey::of(&asdu, &self.option().asdu_params);
+        let mut rx = self.subscribe_asdu(move |a| key.matches(a)).await;
+        let start = Instant::now();
+        self.send_asdu(asdu).await?;
+
+        let deadline = start + self.option().command_timeout;
+        let reply = recv_until(&mut rx, deadline).await?;
+        let rtt = start.elapsed();
+        metrics::command_rtt(TypeID::C_TS_TA_1, rtt);
+        let mut cot = reply.identifier.cot;
+        let cause = cot.cause().get();
+        if cot.is_rejected() || cause != Cause::ActivationCon {
+            return Err(Error::ErrNegativeConfirm(reply.identifier.type_id, cause));
+        }
+        if reply.raw != sent {
+            return Err(Error::ErrTestMismatch);
+        }
+        Ok(rtt)
+    }
+
+    // 读命令: 发送 C_RD_NA_1, 等待该信息对象以传送原因<5>(请求)上送的响应并解码.
+    // 超时时间见 ClientOption::with_command_timeout, 否定确认(如未知的信息对象地址)返回 Error::ErrNegativeConfirm
+    pub async fn read(
+        &self,
+        ca: CommonAddr,
+        ioa: impl IntoInfoObjAddr,
+    ) -> Result<AsduPayload, Error> {
+        let asdu = read_cmd(
+            CauseOfTransmission::new(false, false, Cause::Request),
+            ca,
+            ioa,
+        )?;
+        let params = self.option().asdu_params;
+        let ioa = first_ioa(&asdu, &params);
+        let mut rx = self
+            .subscribe_asdu(move |a| {
+                let mut cot = a.identifier.cot;
+                let matched = if a.identifier.type_id == TypeID::C_RD_NA_1 {
+                    cot.is_rejected()
+                } else {
+                    cot.cause().get() == Cause::Request
+                };
+                matched && a.identifier.common_addr == ca && first_ioa(a, &params) == ioa
+            })
+            .await;
+        self.send_asdu(asdu).await?;
+
+        let deadline = Instant::now() + self.option().command_timeout;
+        let mut reply = recv_until(&mut rx, deadline).await?;
+        if reply.identifier.type_id == TypeID::C_RD_NA_1 {
+            let mut cot = reply.identifier.cot;
+            return Err(Error::ErrNegativeConfirm(
+                TypeID::C_RD_NA_1,
+                cot.cause().get(),
+            ));
+        }
+        reply.decode_payload()
+    }
+}
+
+// 选择后执行(SBO): 先发送 S/E = 1 的选择命令, 收到肯定的激活确认后再发送 S/E = 0 的执行命令.
+// 选择超时, 或执行被否定/超时时, 发送选择命令的停止激活撤销选择.
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 单命令, 选择后执行
+    pub async fn select_and_execute_single(
+        &self,
+        type_id: TypeID,
+        ca: CommonAddr,
+        cmd: SingleCommandInfo,
+    ) -> Result<CommandResult, Error> {
+        let build = |se: bool, cause: Cause| {
+            let mut sco = cmd.sco;
+            sco.se().set(se);
+            single_cmd(
+                type_id,
+                CauseOfTransmission::new(false, false, cause),
+                ca,
+                SingleCommandInfo {
+                    ioa: cmd.ioa,
+                    sco,
+                    time: cmd.time,
+                },
+            )
+        };
+        self.select_and_execute(
+            build(true, Cause::Activation)?,
+            build(false, Cause::Activation)?,
+            build(true, Cause::Deactivation)?,
+        )
+        .await
+    }
+
+    // 双命令, 选择后执行
+    pub async fn select_and_execute_double(
+        &self,
+        type_id: TypeID,
+        ca: CommonAddr,
+        cmd: DoubleCommandInfo,
+    ) -> Result<CommandResult, Error> {
+        let build = |se: bool, cause: Cause| {
+            let mut dco = cmd.dco;
+            dco.se().set(se);
+            double_cmd(
+                type_id,
+                CauseOfTransmission::new(false, false, cause),
+                ca,
+                DoubleCommandInfo {
+                    ioa: cmd.ioa,
+                    dco,
+                    time: cmd.time,
+                },
+            )
+        };
+        self.select_and_execute(
+            build(true, Cause::Activation)?,
+            build(false, Cause::Activation)?,
+            build(true, Cause::Deactivation)?,
+        )
+        .await
+    }
+
+    // 设定命令, 规一化值, 选择后执行
+    pub async fn select_and_execute_setpoint_normal(
+        &self,
+        type_id: TypeID,
+        ca: CommonAddr,
+        cmd: SetpointCommandNormalInfo,
+    ) -> Result<CommandResult, Error> {
+        let build = |se: bool, cause: Cause| {
+            let mut qos = cmd.qos;
+            qos.se().set(u1::new(se as u8).unwrap());
+            set_point_cmd_normal(
+                type_id,
+                CauseOfTransmission::new(false, false, cause),
+                ca,
+                SetpointCommandNormalInfo {
+                    ioa: cmd.ioa,
+                    nva: cmd.nva,
+                    qos,
+                    time: cmd.time,
+                },
+            )
+        };
+        self.select_and_execute(
+            build(true, Cause::Activation)?,
+            build(false, Cause::Activation)?,
+            build(true, Cause::Deactivation)?,
+        )
+        .await
+    }
+
+    // 设定命令, 标度化值, 选择后执行
+    pub async fn select_and_execute_setpoint_scaled(
+        &self,
+        type_id: TypeID,
+        ca: CommonAddr,
+        cmd: SetpointCommandScaledInfo,
+    ) -> Result<CommandResult, Error> {
+        let build = |se: bool, cause: Cause| {
+            let mut qos = cmd.qos;
+            qos.se().set(u1::new(se as u8).unwrap());
+            set_point_cmd_scaled(
+                type_id,
+                CauseOfTransmission::new(false, false, cause),
+                ca,
+                SetpointCommandScaledInfo {
+                    ioa: cmd.ioa,
+                    sva: cmd.sva,
+                    qos,
+                    time: cmd.time,
+                },
+            )
+        };
+        self.select_and_execute(
+            build(true, Cause::Activation)?,
+            build(false, Cause::Activation)?,
+            build(true, Cause::Deactivation)?,
+        )
+        .await
+    }
+
+    // 设定命令, 短浮点数, 选择后执行
+    pub async fn select_and_execute_setpoint_float(
+        &self,
+        type_id: TypeID,
+        ca: CommonAddr,
+        cmd: SetpointCommandFloatInfo,
+    ) -> Result<CommandResult, Error> {
+        let build = |se: bool, cause: Cause| {
+            let mut qos = cmd.qos;
+            qos.se().set(u1::new(se as u8).unwrap());
+            set_point_cmd_float(
+                type_id,
+                CauseOfTransmission::new(false, false, cause),
+                ca,
+                SetpointCommandFloatInfo {
+                    ioa: cmd.ioa,
+                    r: cmd.r,
+                    qos,
+                    time: cmd.time,
+                },
+            )
+        };
+        self.select_and_execute(
+            build(true, Cause::Activation)?,
+            build(false, Cause::Activation)?,
+            build(true, Cause::Deactivation)?,
+        )
+        .await
+    }
+
+    // 开启单命令执行时, 选择, 执行与撤销选择作为一个命令排队
+    async fn select_and_execute(
+        &self,
+        select: Asdu,
+        execute: Asdu,
+        cancel: Asdu,
+    ) -> Result<CommandResult, Error> {
+        let turn = self.command_turn(&select).await?;
+        let op = self.option();
+        // 选择命令只有激活确认, 没有激活终止
+        match self.confirm(select, false).await {
+            Ok(_) => (),
+            Err(Error::ErrTimeout) => {
+                self.cancel_select(cancel).await;
+                return Err(Error::ErrTimeout);
+            }
+            Err(e) => return Err(e),
+        }
+
+        match self
+            .confirm(execute, op.wait_termination || turn.is_some())
+            .await
+        {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                self.cancel_select(cancel).await;
+                Err(e)
+            }
+        }
+    }
+
+    // 撤销选择, 失败时仅记录日志
+    async fn cancel_select(&self, cancel: Asdu) {
+        if let Err(e) = self.send_asdu(cancel).await {
+            log::warn!("[SBO] cancel select failed: {e}");
+        }
+    }
+}
+
+async fn wait_confirm(
+    mut rx: mpsc::UnboundedReceiver<Asdu>,
+    type_id: TypeID,
+    timeout: Duration,
+    wait_termination: bool,
+) -> Result<CommandResult, Error> {
+    let sent = Instant::now();
+    let deadline = sent + timeout;
+    let mut result: Option<CommandResult> = None;
+    loop {
+        let asdu = match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(asdu)) => asdu,
+            Ok(None) => return Err(Error::ErrUseClosedConnection),
+            // 已收到确认但未等到激活终止, 返回已有的确认
+            Err(_) => return result.ok_or(Error::ErrTimeout),
+        };
+        let mut cot = asdu.identifier.cot;
+        let cause = cot.cause().get();
+        // 往返时间计到第一个确认报文
+        if result.is_none() {
+            metrics::command_rtt(type_id, sent.elapsed());
+        }
+        if cot.is_rejected() {
+            return Err(Error::ErrNegativeConfirm(asdu.identifier.type_id, cause));
+        }
+
+        if cause == Cause::ActivationTerm {
+            let mut r = result.unwrap_or(CommandResult {
+                cause: Cause::ActivationCon,
+                terminated: false,
+                asdu: asdu.clone(),
+            });
+            r.terminated = true;
+            return Ok(r);
+        }
+
+        let r = CommandResult {
+            cause,
+            terminated: false,
+            asdu,
+        };
+        if !wait_termination || cause != Cause::ActivationCon {
+            return Ok(r);
+        }
+        result = Some(r);
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    asdu::{Asdu, AsduParams, CommonAddr, TypeID},
+    command::first_ioa,
+    Error,
+};
+
+// 排队中或执行中的控制命令, 见 ClientOption::with_single_outstanding_command
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedCommand {
+    /// 队列分配的编号, 用于 Client::cancel_queued_command
+    pub id: u64,
+    /// 公共地址
+    pub ca: CommonAddr,
+    /// 类型标识
+    pub type_id: TypeID,
+    /// 信息对象地址
+    pub ioa: Option<u32>,
+    /// 已发送, 等待确认或激活终止
+    pub in_flight: bool,
+    /// 进入队列的时间
+    pub queued_at: Instant,
+}
+
+struct Entry {
+    cmd: QueuedCommand,
+    // 轮到该命令时通知等待者, 被丢弃时等待者返回 ErrCommandCancelled
+    wake: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    by_ca: BTreeMap<CommonAddr, VecDeque<Entry>>,
+}
+
+// 按公共地址排队的控制命令, 每个公共地址同一时间只有队首的命令在执行
+#[derive(Default)]
+pub(crate) struct CommandQueue {
+    inner: Mutex<Inner>,
+}
+
+// 命令在队列中的位置, 丢弃时出队并唤醒同一公共地址的下一个命令
+pub(crate) struct CommandTicket {
+    queue: Arc<CommandQueue>,
+    ca: CommonAddr,
+    id: u64,
+    wake: Option<oneshot::Receiver<()>>,
+}
+
+impl CommandQueue {
+    pub(crate) fn enqueue(self: &Arc<Self>, asdu: &Asdu, params: &AsduParams) -> CommandTicket {
+        let ca = asdu.identifier.common_addr;
+        let (tx, rx) = oneshot::channel();
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        let entries = inner.by_ca.entry(ca).or_default();
+        let in_flight = entries.is_empty();
+        let wake = if in_flight {
+            let _ = tx.send(());
+            None
+        } else {
+            Some(tx)
+        };
+        entries.push_back(Entry {
+            cmd: QueuedCommand {
+                id,
+                ca,
+                type_id: asdu.identifier.type_id,
+                ioa: first_ioa(asdu, params),
+                in_flight,
+                queued_at: Instant::now(),
+            },
+            wake,
+        });
+        CommandTicket {
+            queue: self.clone(),
+            ca,
+            id,
+            wake: Some(rx),
+        }
+    }
+
+    // 按公共地址与队列顺序列出命令
+    pub(crate) fn list(&self) -> Vec<QueuedCommand> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .by_ca
+            .values()
+            .flatten()
+            .map(|entry| entry.cmd.clone())
+            .collect()
+    }
+
+    // 取消尚未发送的命令, 已发送的命令不能取消
+    pub(crate) fn cancel(&self, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        for entries in inner.by_ca.values_mut() {
+            if let Some(pos) = entries
+                .iter()
+                .position(|entry| entry.cmd.id == id && !entry.cmd.in_flight)
+            {
+                entries.remove(pos);
+                return true;
+            }
+        }
+        false
+    }
+
+    // 取消公共地址下所有尚未发送的命令, 返回取消的个数
+    pub(crate) fn cancel_all(&self, ca: CommonAddr) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entries) = inner.by_ca.get_mut(&ca) else {
+            return 0;
+        };
+        let before = entries.len();
+        entries.retain(|entry| entry.cmd.in_flight);
+        before - entries.len()
+    }
+
+    fn remove(&self, ca: CommonAddr, id: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entries) = inner.by_ca.get_mut(&ca) else {
+            return;
+        };
+        entries.retain(|entry| entry.cmd.id != id);
+        match entries.front_mut() {
+            Some(head) if !head.cmd.in_flight => {
+                head.cmd.in_flight = true;
+                if let Some(wake) = head.wake.take() {
+                    let _ = wake.send(());
+                }
+            }
+            Some(_) => (),
+            None => {
+                inner.by_ca.remove(&ca);
+            }
+        }
+    }
+}
+
+impl CommandTicket {
+    // 等待轮到该命令, 排队期间被取消时返回 ErrCommandCancelled
+    pub(crate) async fn ready(&mut self) -> Result<(), Error> {
+        match self.wake.take() {
+            Some(wake) => wake.await.map_err(|_| Error::ErrCommandCancelled),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for CommandTicket {
+    fn drop(&mut self) {
+        self.queue.remove(self.ca, self.id);
+    }
+}
@@ -0,0 +1,394 @@
+// 信号表: 按名称, 公共地址, 信息对象地址, 类型, 换算与召唤组描述站内各点,
+// 从 CSV(或启用 toml 特性时从 TOML)加载, 检查重复的地址与类型不符后
+// 用于建立服务端的 DataStore 或配置客户端的 PointCache.
+//
+// CSV 每行为 name,ca,ioa,type,scale_min,scale_max,group, 可以有表头, # 开头的行为注释,
+// 含逗号的名称用双引号括起. 换算只用于规一化值, 省略时为 -1 ~ +1:
+//
+// name,ca,ioa,type,scale_min,scale_max,group
+// feeder1.breaker,1,1001,double,,,1
+// feeder1.current,1,4001,normalized,0,600,2
+//
+// TOML 每个信号为一个 [[signal]] 表:
+//
+// [[signal]]
+// name = "feeder1.current"
+// ca = 1
+// ioa = 4001
+// type = "normalized"
+// scale = { min = 0.0, max = 600.0 }
+// group = 2
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+
+use crate::{
+    asdu::{Asdu, CommonAddr},
+    datastore::{COUNTER_GROUP_CAUSES, GROUP_CAUSES},
+    subscribe::point_updates,
+    DataStore, Error, Point, PointCache, PointValue, ScaleTable,
+};
+
+// 信号的类型, 对应 PointValue 的各变体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum SignalType {
+    /// 单点信息
+    Single,
+    /// 双点信息
+    Double,
+    /// 测量值, 规一化值
+    #[cfg_attr(feature = "serde", serde(alias = "normal"))]
+    Normalized,
+    /// 测量值, 标度化值
+    Scaled,
+    /// 测量值, 短浮点数
+    Float,
+    /// 累计量
+    Counter,
+}
+
+impl SignalType {
+    // 该类型的初值: 单点为分, 双点为不确定(0), 其他为 0
+    pub fn default_value(self) -> PointValue {
+        match self {
+            SignalType::Single => PointValue::Single(false),
+            SignalType::Double => PointValue::Double(0),
+            SignalType::Normalized => PointValue::Normalized(0),
+            SignalType::Scaled => PointValue::Scaled(0),
+            SignalType::Float => PointValue::Float(0.0),
+            SignalType::Counter => PointValue::Counter(0),
+        }
+    }
+
+    // 召唤组的上限: 累计量为计数量召唤组 1~4, 其他为召唤组 1~16
+    fn max_group(self) -> u8 {
+        match self {
+            SignalType::Counter => COUNTER_GROUP_CAUSES.len() as u8,
+            _ => GROUP_CAUSES.len() as u8,
+        }
+    }
+}
+
+impl From<PointValue> for SignalType {
+    fn from(value: PointValue) -> Self {
+        match value {
+            PointValue::Single(_) => SignalType::Single,
+            PointValue::Double(_) => SignalType::Double,
+            PointValue::Normalized(_) => SignalType::Normalized,
+            PointValue::Scaled(_) => SignalType::Scaled,
+            PointValue::Float(_) => SignalType::Float,
+            PointValue::Counter(_) => SignalType::Counter,
+        }
+    }
+}
+
+impl FromStr for SignalType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "single" => Ok(SignalType::Single),
+            "double" => Ok(SignalType::Double),
+            "normalized" | "normal" => Ok(SignalType::Normalized),
+            "scaled" => Ok(SignalType::Scaled),
+            "float" => Ok(SignalType::Float),
+            "counter" => Ok(SignalType::Counter),
+            _ => Err(anyhow!("unknown signal type {s}").into()),
+        }
+    }
+}
+
+impl fmt::Display for SignalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SignalType::Single => "single",
+            SignalType::Double => "double",
+            SignalType::Normalized => "normalized",
+            SignalType::Scaled => "scaled",
+            SignalType::Float => "float",
+            SignalType::Counter => "counter",
+        };
+        f.write_str(name)
+    }
+}
+
+// 信号表中的一个信号
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signal {
+    pub name: String,
+    pub ca: CommonAddr,
+    pub ioa: u16,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: SignalType,
+    /// 规一化值与工程值的换算
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scale: Option<ScaleTable>,
+    /// 所属召唤组, 累计量为计数量召唤组; 0 为只响应站召唤(总计数量召唤)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub group: u8,
+}
+
+impl Signal {
+    pub fn new(name: impl Into<String>, ca: CommonAddr, ioa: u16, kind: SignalType) -> Self {
+        Signal {
+            name: name.into(),
+            ca,
+            ioa,
+            kind,
+            scale: None,
+            group: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn with_scale(mut self, scale: ScaleTable) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    #[must_use]
+    pub fn with_group(mut self, group: u8) -> Self {
+        self.group = group;
+        self
+    }
+}
+
+#[cfg(feature = "toml")]
+#[derive(serde::Deserialize)]
+struct SignalFile {
+    #[serde(default)]
+    signal: Vec<Signal>,
+}
+
+// 经过检查的信号表: 公共地址与信息对象地址不重复, 名称不重复,
+// 换算只用于规一化值, 召唤组在类型允许的范围内
+#[derive(Debug, Clone, Default)]
+pub struct SignalList {
+    signals: Vec<Signal>,
+    by_addr: BTreeMap<(CommonAddr, u16), usize>,
+}
+
+impl SignalList {
+    pub fn new(signals: Vec<Signal>) -> Result<Self, Error> {
+        let mut by_addr = BTreeMap::new();
+        let mut names = HashSet::new();
+        for (i, s) in signals.iter().enumerate() {
+            if let Some(prev) = by_addr.insert((s.ca, s.ioa), i) {
+                return Err(anyhow!(
+                    "duplicate address {}/{}: {} and {}",
+                    s.ca,
+                    s.ioa,
+                    signals[prev].name,
+                    s.name
+                )
+                .into());
+            }
+            if !s.name.is_empty() && !names.insert(s.name.as_str()) {
+                return Err(anyhow!("duplicate signal name {}", s.name).into());
+            }
+            if s.scale.is_some() && s.kind != SignalType::Normalized {
+                return Err(anyhow!("{}: scaling for {} signal", s.name, s.kind).into());
+            }
+            if s.group > s.kind.max_group() {
+                return Err(anyhow!(
+                    "{}: group {} out of range 0~{} for {} signal",
+                    s.name,
+                    s.group,
+                    s.kind.max_group(),
+                    s.kind
+                )
+                .into());
+            }
+        }
+        Ok(SignalList { signals, by_addr })
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, Error> {
+        let mut signals = Vec::new();
+        let mut header = true;
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_csv(line);
+            // 第一个非注释行可以是表头
+            if std::mem::take(&mut header)
+                && fields.get(1).is_none_or(|f| f.parse::<u16>().is_err())
+            {
+                continue;
+            }
+            let signal = parse_csv_signal(&fields).map_err(|e| anyhow!("line {}: {e}", n + 1))?;
+            signals.push(signal);
+        }
+        Self::new(signals)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        let file: SignalFile = toml::from_str(text).map_err(|e| anyhow!("{e}"))?;
+        Self::new(file.signal)
+    }
+
+    // 按扩展名加载: .toml 为 TOML(需要 toml 特性), 其他为 CSV
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if !is_toml {
+            return Self::from_csv(&text);
+        }
+        #[cfg(feature = "toml")]
+        return Self::from_toml(&text);
+        #[cfg(not(feature = "toml"))]
+        Err(anyhow!(
+            "{}: TOML signal lists need the toml feature",
+            path.display()
+        )
+        .into())
+    }
+
+    pub fn signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    pub fn len(&self) -> usize {
+        self.signals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty()
+    }
+
+    pub fn get(&self, ca: CommonAddr, ioa: u16) -> Option<&Signal> {
+        self.by_addr.get(&(ca, ioa)).map(|&i| &self.signals[i])
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Signal> {
+        self.signals.iter().find(|s| s.name == name)
+    }
+
+    // 在点数据库中建立各点: 以类型的初值添加新点, 已有的点保留当前值,
+    // 并设置召唤组与换算. 已有点的类型与信号表不符时返回错误, 不修改点数据库
+    pub fn apply_to_store(&self, store: &DataStore) -> Result<(), Error> {
+        for s in &self.signals {
+            if let Some(point) = store.get(s.ca, s.ioa) {
+                let kind = SignalType::from(point.value);
+                if kind != s.kind {
+                    return Err(type_mismatch(s, kind));
+                }
+            }
+        }
+        for s in &self.signals {
+            if store.get(s.ca, s.ioa).is_none() {
+                store.insert(s.ca, s.ioa, Point::new(s.kind.default_value()));
+            }
+            store.set_group(s.ca, s.ioa, s.group)?;
+            if let Some(scale) = s.scale {
+                store.set_scale(s.ca, s.ioa, scale);
+            }
+        }
+        Ok(())
+    }
+
+    // 设置点缓存中规一化值的换算, 之后可按工程值查询
+    pub fn apply_to_cache(&self, cache: &PointCache) {
+        for s in &self.signals {
+            if let Some(scale) = s.scale {
+                cache.set_scale(s.ca, s.ioa, scale);
+            }
+        }
+    }
+
+    // 检查收到的监视方向 ASDU 与信号表是否一致: 信号表中的点类型不符时返回错误,
+    // 不在信号表中的点忽略
+    pub fn check(&self, asdu: &Asdu) -> Result<(), Error> {
+        for update in point_updates(asdu)? {
+            if let Some(s) = self.get(update.ca, update.ioa) {
+                let kind = SignalType::from(update.point.value);
+                if kind != s.kind {
+                    return Err(type_mismatch(s, kind));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn type_mismatch(s: &Signal, kind: SignalType) -> Error {
+    anyhow!(
+        "{} ({}/{}): configured as {}, got {kind}",
+        s.name,
+        s.ca,
+        s.ioa,
+        s.kind
+    )
+    .into()
+}
+
+// 按逗号拆分一行, 双引号括起的字段可以含逗号, 其中的 "" 为一个双引号
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+fn parse_csv_signal(fields: &[String]) -> anyhow::Result<Signal> {
+    let field = |i: usize| fields.get(i).map(String::as_str).filter(|f| !f.is_empty());
+    let number = |i: usize, what: &str| -> anyhow::Result<Option<f32>> {
+        field(i)
+            .map(|f| f.parse().map_err(|_| anyhow!("invalid {what} {f}")))
+            .transpose()
+    };
+    let name = field(0).unwrap_or_default();
+    let ca = field(1)
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| anyhow!("invalid ca"))?;
+    let ioa = field(2)
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| anyhow!("invalid ioa"))?;
+    let kind = field(3).ok_or_else(|| anyhow!("missing type"))?;
+    let kind = kind
+        .parse()
+        .map_err(|_| anyhow!("unknown signal type {kind}"))?;
+    let mut signal = Signal::new(name, ca, ioa, kind);
+    match (number(4, "scale_min")?, number(5, "scale_max")?) {
+        (Some(min), Some(max)) => signal.scale = Some(ScaleTable::new(min, max)),
+        (None, None) => {}
+        _ => return Err(anyhow!("scaling needs both scale_min and scale_max")),
+    }
+    if let Some(group) = field(6) {
+        signal.group = group
+            .parse()
+            .map_err(|_| anyhow!("invalid group {group}"))?;
+    }
+    Ok(signal)
+}
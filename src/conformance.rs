@@ -0,0 +1,491 @@
+use std::{fmt::Display, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use tokio::time::{timeout_at, Instant};
+use tokio_util::codec::Framed;
+
+use crate::{
+    apci::{
+        new_iframe, new_sframe, new_uframe, ApciKind, SeqNum, U_STARTDT_ACTIVE, U_STARTDT_CONFIRM,
+        U_TESTFR_ACTIVE, U_TESTFR_CONFIRM,
+    },
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, CommonAddr, TypeID, GLOBAL_COMMON_ADDR},
+    csys::{interrogation_cmd, Qoi},
+    Apdu, Codec, Connector, Error, Transport,
+};
+
+// 一致性测试用例, 每个用例使用一条新的连接
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// STARTDT 之前收到的 I 帧不得响应
+    StartDtBeforeData,
+    /// 收到发送序号错误的 I 帧时关闭连接
+    SequenceError,
+    /// 收到确认了未发送 I 帧的 S 帧时关闭连接
+    AckError,
+    /// 广播公共地址的总召唤以本站公共地址应答
+    BroadcastCa,
+    /// 未被确认的 I 帧不超过 k 个
+    MaxUnacknowledged,
+    /// 发送的 I 帧在 t1 内未被确认时关闭连接
+    T1Expiry,
+}
+
+impl Case {
+    pub const ALL: [Case; 6] = [
+        Case::StartDtBeforeData,
+        Case::SequenceError,
+        Case::AckError,
+        Case::BroadcastCa,
+        Case::MaxUnacknowledged,
+        Case::T1Expiry,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Case::StartDtBeforeData => "startdt_before_data",
+            Case::SequenceError => "sequence_error",
+            Case::AckError => "ack_error",
+            Case::BroadcastCa => "broadcast_ca",
+            Case::MaxUnacknowledged => "max_unacknowledged",
+            Case::T1Expiry => "t1_expiry",
+        }
+    }
+}
+
+// 用例的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    /// 不符合标准, 及原因
+    Fail(String),
+    /// 被控站的数据不足以判断, 如总召唤的数据少于 k 个
+    Inconclusive(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseReport {
+    pub case: Case,
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+}
+
+// 一致性测试报告
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub cases: Vec<CaseReport>,
+}
+
+impl Report {
+    // 没有不符合标准的用例
+    pub fn is_pass(&self) -> bool {
+        !self
+            .cases
+            .iter()
+            .any(|c| matches!(c.verdict, Verdict::Fail(_)))
+    }
+
+    pub fn verdict(&self, case: Case) -> Option<&Verdict> {
+        self.cases
+            .iter()
+            .find(|c| c.case == case)
+            .map(|c| &c.verdict)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CaseReport> {
+        self.cases
+            .iter()
+            .filter(|c| matches!(c.verdict, Verdict::Fail(_)))
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (mut pass, mut fail, mut inconclusive) = (0, 0, 0);
+        for c in &self.cases {
+            let ms = c.elapsed.as_millis();
+            match &c.verdict {
+                Verdict::Pass => {
+                    pass += 1;
+                    writeln!(f, "PASS  {} ({ms}ms)", c.case.name())?;
+                }
+                Verdict::Fail(reason) => {
+                    fail += 1;
+                    writeln!(f, "FAIL  {} ({ms}ms): {reason}", c.case.name())?;
+                }
+                Verdict::Inconclusive(reason) => {
+                    inconclusive += 1;
+                    writeln!(f, "N/A   {} ({ms}ms): {reason}", c.case.name())?;
+                }
+            }
+        }
+        write!(
+            f,
+            "{pass} passed, {fail} failed, {inconclusive} inconclusive"
+        )
+    }
+}
+
+// IEC 104 被控站一致性测试. 通过 connector 连接被测站, 可以是本库的 Server,
+// 也可以是第三方的 RTU; 测试中总召唤的数据用于检查发送窗口与 t1,
+// 被测站应在 common_addr 上对总召唤返回多于 k 个信息对象
+pub struct Conformance {
+    connector: Box<dyn Connector>,
+    params: AsduParams,
+    common_addr: CommonAddr,
+    // 被测站的参数 k 与 t1
+    k: u16,
+    t1: Duration,
+    // 等待被测站响应的时间, 超过即认为没有响应
+    response_timeout: Duration,
+    cases: Vec<Case>,
+}
+
+impl Conformance {
+    pub fn new<C>(connector: C) -> Self
+    where
+        C: Connector,
+    {
+        Conformance {
+            connector: Box::new(connector),
+            params: AsduParams::default(),
+            common_addr: 1,
+            k: 12,
+            t1: Duration::from_secs(15),
+            response_timeout: Duration::from_secs(5),
+            cases: Case::ALL.to_vec(),
+        }
+    }
+
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    // 被测站的公共地址, 默认为 1
+    pub fn with_common_addr(mut self, common_addr: CommonAddr) -> Self {
+        self.common_addr = common_addr;
+        self
+    }
+
+    // 被测站未被确认的 I 帧的最大数目, 默认为 12
+    pub fn with_k(mut self, k: u16) -> Self {
+        self.k = k;
+        self
+    }
+
+    // 被测站的发送或测试 APDU 的超时, 默认为 15 秒
+    pub fn with_t1(mut self, t1: Duration) -> Self {
+        self.t1 = t1;
+        self
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+
+    // 运行的用例, 默认为全部
+    pub fn with_cases(mut self, cases: &[Case]) -> Self {
+        self.cases = cases.to_vec();
+        self
+    }
+
+    pub async fn run(&self) -> Report {
+        let mut report = Report::default();
+        for &case in &self.cases {
+            report.cases.push(self.run_case(case).await);
+        }
+        report
+    }
+
+    pub async fn run_case(&self, case: Case) -> CaseReport {
+        let start = Instant::now();
+        let result = match case {
+            Case::StartDtBeforeData => self.startdt_before_data().await,
+            Case::SequenceError => self.sequence_error().await,
+            Case::AckError => self.ack_error().await,
+            Case::BroadcastCa => self.broadcast_ca().await,
+            Case::MaxUnacknowledged => self.max_unacknowledged().await,
+            Case::T1Expiry => self.t1_expiry().await,
+        };
+        let verdict = result.unwrap_or_else(|e| Verdict::Fail(e.to_string()));
+        log::info!("[CONFORMANCE] {}: {verdict:?}", case.name());
+        CaseReport {
+            case,
+            verdict,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    async fn connect(&self) -> Result<Peer, Error> {
+        let transport = self.connector.connect().await?;
+        Ok(Peer {
+            framed: Framed::new(transport, Codec::new(self.params)),
+            send_sn: SeqNum::default(),
+            rcv_sn: SeqNum::default(),
+        })
+    }
+
+    fn interrogation(&self, ca: CommonAddr) -> Result<Asdu, Error> {
+        interrogation_cmd(
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            ca,
+            Qoi::StationInterrogation,
+        )
+    }
+
+    async fn start_dt(&self, peer: &mut Peer) -> Result<Option<Verdict>, Error> {
+        peer.send(new_uframe(U_STARTDT_ACTIVE)).await?;
+        match peer.recv(self.response_timeout, true).await? {
+            Recv::Frame(apdu) => match ApciKind::from(apdu.apci) {
+                ApciKind::U(u) if u.function == U_STARTDT_CONFIRM => Ok(None),
+                _ => Ok(Some(Verdict::Fail(format!(
+                    "expect STARTDT confirm, got {apdu}"
+                )))),
+            },
+            Recv::Silence => Ok(Some(Verdict::Fail("no STARTDT confirm".into()))),
+            Recv::Closed => Ok(Some(Verdict::Fail("connection closed on STARTDT".into()))),
+        }
+    }
+
+    // 未启动数据传输时发送总召唤, 被测站不应响应(或关闭连接), 之后仍应确认 STARTDT
+    async fn startdt_before_data(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        peer.send_asdu(self.interrogation(self.common_addr)?)
+            .await?;
+        match peer.recv(self.response_timeout, true).await? {
+            Recv::Frame(apdu) => {
+                return Ok(Verdict::Fail(format!(
+                    "responded before STARTDT with {apdu}"
+                )))
+            }
+            Recv::Closed => return Ok(Verdict::Pass),
+            Recv::Silence => (),
+        }
+        Ok(self.start_dt(&mut peer).await?.unwrap_or(Verdict::Pass))
+    }
+
+    // 发送序号跳跃的 I 帧, 被测站应关闭连接
+    async fn sequence_error(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        if let Some(verdict) = self.start_dt(&mut peer).await? {
+            return Ok(verdict);
+        }
+        peer.send_sn = SeqNum::new(5);
+        peer.send_asdu(self.interrogation(self.common_addr)?)
+            .await?;
+        Ok(peer
+            .expect_closed(self.response_timeout)
+            .await?
+            .unwrap_or_else(|| {
+                Verdict::Fail("connection kept open after N(S) = 5, expect 0".into())
+            }))
+    }
+
+    // 确认被测站未发送过的 I 帧, 被测站应关闭连接
+    async fn ack_error(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        if let Some(verdict) = self.start_dt(&mut peer).await? {
+            return Ok(verdict);
+        }
+        peer.send(new_sframe(10)).await?;
+        Ok(peer
+            .expect_closed(self.response_timeout)
+            .await?
+            .unwrap_or_else(|| {
+                Verdict::Fail("connection kept open after S-frame N(R) = 10".into())
+            }))
+    }
+
+    // 以广播公共地址发送总召唤, 被测站应以本站公共地址确认
+    async fn broadcast_ca(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        if let Some(verdict) = self.start_dt(&mut peer).await? {
+            return Ok(verdict);
+        }
+        peer.send_asdu(self.interrogation(GLOBAL_COMMON_ADDR)?)
+            .await?;
+        loop {
+            let apdu = match peer.recv(self.response_timeout, true).await? {
+                Recv::Frame(apdu) => apdu,
+                Recv::Silence => {
+                    return Ok(Verdict::Fail(
+                        "no activation confirm for broadcast interrogation".into(),
+                    ))
+                }
+                Recv::Closed => {
+                    return Ok(Verdict::Fail(
+                        "connection closed on broadcast interrogation".into(),
+                    ))
+                }
+            };
+            let Some(asdu) = apdu.asdu else {
+                continue;
+            };
+            let mut cot = asdu.identifier.cot;
+            if asdu.identifier.type_id != TypeID::C_IC_NA_1
+                || cot.cause().get() != Cause::ActivationCon
+            {
+                continue;
+            }
+            return Ok(if cot.is_negative() {
+                Verdict::Fail(format!("broadcast interrogation rejected: {asdu}"))
+            } else if asdu.identifier.common_addr == GLOBAL_COMMON_ADDR {
+                Verdict::Fail("confirmed with broadcast address instead of station address".into())
+            } else {
+                Verdict::Pass
+            });
+        }
+    }
+
+    // 总召唤后不确认, 被测站发送 k 个 I 帧后应停止发送, 确认后继续发送
+    async fn max_unacknowledged(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        if let Some(verdict) = self.start_dt(&mut peer).await? {
+            return Ok(verdict);
+        }
+        peer.send_asdu(self.interrogation(self.common_addr)?)
+            .await?;
+        let mut count = 0;
+        loop {
+            match peer.recv(self.response_timeout, false).await? {
+                Recv::Frame(Apdu { asdu: Some(_), .. }) => count += 1,
+                Recv::Frame(_) => (),
+                Recv::Silence => break,
+                Recv::Closed => {
+                    return Ok(Verdict::Fail(format!(
+                        "connection closed after {count} unacknowledged I-frames"
+                    )))
+                }
+            }
+        }
+        if count > self.k {
+            return Ok(Verdict::Fail(format!(
+                "sent {count} unacknowledged I-frames, k = {}",
+                self.k
+            )));
+        }
+        if count < self.k {
+            return Ok(Verdict::Inconclusive(format!(
+                "only {count} I-frames sent, need more than k = {}",
+                self.k
+            )));
+        }
+        // 确认之后被测站应继续发送剩余的数据, 否则无法判断是否受窗口限制
+        peer.send(new_sframe(peer.rcv_sn.value())).await?;
+        match peer.recv(self.response_timeout, true).await? {
+            Recv::Frame(Apdu { asdu: Some(_), .. }) => Ok(Verdict::Pass),
+            _ => Ok(Verdict::Inconclusive(format!(
+                "exactly k = {} I-frames sent, none after acknowledge",
+                self.k
+            ))),
+        }
+    }
+
+    // 总召唤后不确认, 被测站应在 t1 超时后关闭连接
+    async fn t1_expiry(&self) -> Result<Verdict, Error> {
+        let mut peer = self.connect().await?;
+        if let Some(verdict) = self.start_dt(&mut peer).await? {
+            return Ok(verdict);
+        }
+        peer.send_asdu(self.interrogation(self.common_addr)?)
+            .await?;
+        let deadline = Instant::now() + self.t1 + self.response_timeout;
+        let mut sent = false;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match peer.recv(wait, false).await? {
+                Recv::Frame(Apdu { asdu: Some(_), .. }) => sent = true,
+                Recv::Frame(_) => (),
+                Recv::Closed if sent => return Ok(Verdict::Pass),
+                Recv::Closed => {
+                    return Ok(Verdict::Fail(
+                        "connection closed before sending any I-frame".into(),
+                    ))
+                }
+                Recv::Silence if sent => {
+                    return Ok(Verdict::Fail(format!(
+                        "connection kept open {:?} after unacknowledged I-frame",
+                        self.t1 + self.response_timeout
+                    )))
+                }
+                Recv::Silence => {
+                    return Ok(Verdict::Inconclusive(
+                        "no I-frame sent for interrogation".into(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+// 接收的结果
+enum Recv {
+    Frame(Apdu),
+    // 等待时间内没有收到 I 帧或 U 帧
+    Silence,
+    // 连接关闭或出错
+    Closed,
+}
+
+// 模拟控制站的一条连接
+struct Peer {
+    framed: Framed<Box<dyn Transport>, Codec>,
+    send_sn: SeqNum,
+    rcv_sn: SeqNum,
+}
+
+impl Peer {
+    async fn send(&mut self, apdu: Apdu) -> Result<(), Error> {
+        self.framed.send(apdu).await.map_err(Error::ErrAnyHow)
+    }
+
+    async fn send_asdu(&mut self, asdu: Asdu) -> Result<(), Error> {
+        let apdu = new_iframe(asdu, self.send_sn.value(), self.rcv_sn.value());
+        self.send_sn = self.send_sn.next();
+        self.send(apdu).await
+    }
+
+    // 在 wait 时间内接收下一个 I 帧或 U 帧, ack 为 true 时立即以 S 帧确认 I 帧.
+    // 测试帧自动回复确认, S 帧忽略
+    async fn recv(&mut self, wait: Duration, ack: bool) -> Result<Recv, Error> {
+        let deadline = Instant::now() + wait;
+        loop {
+            let apdu = match timeout_at(deadline, self.framed.next()).await {
+                Ok(Some(Ok(apdu))) => apdu,
+                Ok(_) => return Ok(Recv::Closed),
+                Err(_) => return Ok(Recv::Silence),
+            };
+            match ApciKind::from(apdu.apci) {
+                ApciKind::I(_) => {
+                    self.rcv_sn = self.rcv_sn.next();
+                    if ack && self.send(new_sframe(self.rcv_sn.value())).await.is_err() {
+                        return Ok(Recv::Closed);
+                    }
+                    return Ok(Recv::Frame(apdu));
+                }
+                ApciKind::U(u) if u.function == U_TESTFR_ACTIVE => {
+                    if self.send(new_uframe(U_TESTFR_CONFIRM)).await.is_err() {
+                        return Ok(Recv::Closed);
+                    }
+                }
+                ApciKind::S(_) => (),
+                ApciKind::U(_) => return Ok(Recv::Frame(apdu)),
+            }
+        }
+    }
+
+    // 期望在 wait 时间内连接关闭, 关闭时返回 Pass, 否则返回 None
+    async fn expect_closed(&mut self, wait: Duration) -> Result<Option<Verdict>, Error> {
+        let deadline = Instant::now() + wait;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match self.recv(wait, true).await? {
+                Recv::Frame(_) => (),
+                Recv::Closed => return Ok(Some(Verdict::Pass)),
+                Recv::Silence => return Ok(None),
+            }
+        }
+    }
+}
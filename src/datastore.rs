@@ -0,0 +1,712 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::anyhow;
+use bit_struct::*;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, InfoObjAddr, TypeID},
+    csys::{CounterGroup, FreezeMode, ObjectQCC, ObjectQOI, ObjectQRP, Qcc, Qoi},
+    mproc::{
+        double_inner, integrated_totals_inner, measured_value_float_inner,
+        measured_value_normal_inner, measured_value_scaled_inner, single_inner, split_into_asdus,
+        BinaryCounterReadingInfo, DoublePointInfo, MeasuredValueFloatInfo, MeasuredValueNormalInfo,
+        MeasuredValueScaledInfo, ObjectBCR, ObjectDIQ, ObjectQDS, ObjectSIQ, SinglePointInfo,
+    },
+    Error, Nva, ScaleTable, ServerHandle, ServerHandler,
+};
+
+// 响应第1~16组召唤的传送原因
+pub(crate) const GROUP_CAUSES: [Cause; 16] = [
+    Cause::InterrogatedByGroup1,
+    Cause::InterrogatedByGroup2,
+    Cause::InterrogatedByGroup3,
+    Cause::InterrogatedByGroup4,
+    Cause::InterrogatedByGroup5,
+    Cause::InterrogatedByGroup6,
+    Cause::InterrogatedByGroup7,
+    Cause::InterrogatedByGroup8,
+    Cause::InterrogatedByGroup9,
+    Cause::InterrogatedByGroup10,
+    Cause::InterrogatedByGroup11,
+    Cause::InterrogatedByGroup12,
+    Cause::InterrogatedByGroup13,
+    Cause::InterrogatedByGroup14,
+    Cause::InterrogatedByGroup15,
+    Cause::InterrogatedByGroup16,
+];
+
+// 响应第1~4组计数量召唤的传送原因
+pub(crate) const COUNTER_GROUP_CAUSES: [Cause; 4] = [
+    Cause::RequestByGroup1Counter,
+    Cause::RequestByGroup2Counter,
+    Cause::RequestByGroup3Counter,
+    Cause::RequestByGroup4Counter,
+];
+
+// 点的值, 对应监视方向的信息对象类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointValue {
+    /// 单点信息
+    Single(bool),
+    /// 双点信息, 0~3
+    Double(u8),
+    /// 测量值, 规一化值
+    Normalized(i16),
+    /// 测量值, 标度化值
+    Scaled(i16),
+    /// 测量值, 短浮点数
+    Float(f32),
+    /// 累计量
+    Counter(i32),
+}
+
+impl PointValue {
+    // 上送时的类型标识, timed 为 true 时带 CP56Time2a 时标
+    pub fn type_id(&self, timed: bool) -> TypeID {
+        match (self, timed) {
+            (PointValue::Single(_), false) => TypeID::M_SP_NA_1,
+            (PointValue::Single(_), true) => TypeID::M_SP_TB_1,
+            (PointValue::Double(_), false) => TypeID::M_DP_NA_1,
+            (PointValue::Double(_), true) => TypeID::M_DP_TB_1,
+            (PointValue::Normalized(_), false) => TypeID::M_ME_NA_1,
+            (PointValue::Normalized(_), true) => TypeID::M_ME_TD_1,
+            (PointValue::Scaled(_), false) => TypeID::M_ME_NB_1,
+            (PointValue::Scaled(_), true) => TypeID::M_ME_TE_1,
+            (PointValue::Float(_), false) => TypeID::M_ME_NC_1,
+            (PointValue::Float(_), true) => TypeID::M_ME_TF_1,
+            (PointValue::Counter(_), false) => TypeID::M_IT_NA_1,
+            (PointValue::Counter(_), true) => TypeID::M_IT_TB_1,
+        }
+    }
+}
+
+// 点: 值, 品质描述词, 时标及所属的召唤组
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub value: PointValue,
+    /// 品质描述词, 单点与双点信息只使用其中的 IV, NT, SB, BL
+    pub quality: ObjectQDS,
+    /// 最近一次变化的时间, 突发上送时带此时标
+    pub time: Option<DateTime<Utc>>,
+    /// 所属召唤组 1~16, 累计量为计数量召唤组 1~4; 0 为只响应站召唤(总计数量召唤)
+    pub group: u8,
+}
+
+impl Point {
+    pub fn new(value: PointValue) -> Self {
+        Point {
+            value,
+            quality: ObjectQDS::of_defaults(),
+            time: None,
+            group: 0,
+        }
+    }
+
+    pub fn with_quality(mut self, quality: ObjectQDS) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn with_time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn with_group(mut self, group: u8) -> Self {
+        self.group = group;
+        self
+    }
+}
+
+// 服务端的点数据库: 按公共地址与信息对象地址保存各点的值, 品质与时标.
+// 用于生成站召唤, 组召唤, 计数量召唤及读命令的响应; 值或品质变化时生成突发 ASDU,
+// 关联 ServerHandle 后直接发送到激活的连接. 克隆的 DataStore 共享同一份数据,
+// 可以同时交给多个 ServerSession 的处理函数及采集任务
+#[derive(Clone, Default)]
+pub struct DataStore {
+    inner: Arc<RwLock<StoreInner>>,
+}
+
+#[derive(Default)]
+struct StoreInner {
+    points: BTreeMap<(CommonAddr, u16), Point>,
+    // 累计量的冻结值, 未冻结过的累计量以当前值响应计数量召唤
+    frozen: BTreeMap<(CommonAddr, u16), Frozen>,
+    // 上次冻结之后被复位的累计量, 下一次冻结值的 CA 置位
+    reset: BTreeSet<(CommonAddr, u16)>,
+    scales: BTreeMap<(CommonAddr, u16), ScaleTable>,
+    handle: Option<ServerHandle>,
+    double_transmission: bool,
+}
+
+impl DataStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 双重传输: 带时标的变化同时以不带时标与带时标的两种类型上送,
+    // 不带时标的在前. 默认关闭, 只上送带时标的类型
+    pub fn with_double_transmission(self, enabled: bool) -> Self {
+        self.inner.write().unwrap().double_transmission = enabled;
+        self
+    }
+
+    // 变化产生的突发 ASDU 经 handle 发送到激活的连接
+    pub fn attach(&self, handle: ServerHandle) {
+        self.inner.write().unwrap().handle = Some(handle);
+    }
+
+    // 添加或替换点, 不产生突发数据
+    pub fn insert(&self, ca: CommonAddr, ioa: u16, point: Point) -> Option<Point> {
+        self.inner.write().unwrap().points.insert((ca, ioa), point)
+    }
+
+    pub fn remove(&self, ca: CommonAddr, ioa: u16) -> Option<Point> {
+        let mut inner = self.inner.write().unwrap();
+        inner.frozen.remove(&(ca, ioa));
+        inner.reset.remove(&(ca, ioa));
+        inner.points.remove(&(ca, ioa))
+    }
+
+    pub fn get(&self, ca: CommonAddr, ioa: u16) -> Option<Point> {
+        self.inner.read().unwrap().points.get(&(ca, ioa)).copied()
+    }
+
+    // 设置点所属的召唤组: 累计量为计数量召唤组 1~4, 其他为召唤组 1~16, 0 为不属于任何组
+    pub fn set_group(&self, ca: CommonAddr, ioa: u16, group: u8) -> Result<(), Error> {
+        let mut inner = self.inner.write().unwrap();
+        let Some(point) = inner.points.get_mut(&(ca, ioa)) else {
+            return Err(anyhow!("unknown point {ca}/{ioa}").into());
+        };
+        let max = match point.value {
+            PointValue::Counter(_) => COUNTER_GROUP_CAUSES.len(),
+            _ => GROUP_CAUSES.len(),
+        };
+        if group as usize > max {
+            return Err(anyhow!("group {group} out of range 0~{max} for {ca}/{ioa}").into());
+        }
+        point.group = group;
+        Ok(())
+    }
+
+    // 召唤组 group 中的点的信息对象地址
+    pub fn group_members(&self, ca: CommonAddr, group: u8) -> Vec<u16> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .points
+            .range((ca, 0)..=(ca, u16::MAX))
+            .filter(|(_, point)| point.group == group)
+            .map(|((_, ioa), _)| *ioa)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().unwrap().points.is_empty()
+    }
+
+    // 公共地址 ca 下是否有点
+    pub fn has_common_addr(&self, ca: CommonAddr) -> bool {
+        let inner = self.inner.read().unwrap();
+        inner
+            .points
+            .range((ca, 0)..=(ca, u16::MAX))
+            .next()
+            .is_some()
+    }
+
+    // 已有点的全部公共地址
+    pub fn common_addrs(&self) -> Vec<CommonAddr> {
+        let inner = self.inner.read().unwrap();
+        let mut addrs: Vec<CommonAddr> = inner.points.keys().map(|(ca, _)| *ca).collect();
+        addrs.dedup();
+        addrs
+    }
+
+    // 更新点的值与品质, 值或品质变化时返回突发 ASDU(time 为 Some 时带 CP56Time2a 时标),
+    // 并在关联了 ServerHandle 时发送; 值与品质不变时返回空集合. 点不存在时以召唤组 0 添加.
+    // 启用双重传输且带时标时, 先返回不带时标的 ASDU, 再返回带时标的 ASDU
+    pub fn update(
+        &self,
+        ca: CommonAddr,
+        ioa: u16,
+        value: PointValue,
+        quality: ObjectQDS,
+        time: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Asdu>, Error> {
+        let mut inner = self.inner.write().unwrap();
+        let mut changed = false;
+        let point = inner.points.entry((ca, ioa)).or_insert_with(|| {
+            changed = true;
+            Point::new(value)
+        });
+        changed |= point.value != value || point.quality != quality;
+        point.value = value;
+        point.quality = quality;
+        point.time = time.or(point.time);
+        if !changed {
+            return Ok(Vec::new());
+        }
+
+        let point = *point;
+        let timed = match time {
+            Some(_) if inner.double_transmission => vec![false, true],
+            Some(_) => vec![true],
+            None => vec![false],
+        };
+        let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+        let mut asdus = Vec::with_capacity(timed.len());
+        for timed in timed {
+            let mut infos = Infos::default();
+            infos.push(ioa, &point);
+            let asdu = infos
+                .into_asdus(cot, ca, timed)?
+                .pop()
+                .ok_or(Error::ErrTypeIDNotMatch(value.type_id(timed)))?;
+            asdus.push(asdu);
+        }
+        if let Some(handle) = &inner.handle {
+            for asdu in &asdus {
+                if let Err(e) = handle.broadcast_asdu(asdu.clone()) {
+                    log::debug!("[DATASTORE] spontaneous {ca}/{ioa} not sent: {e}");
+                }
+            }
+        }
+        Ok(asdus)
+    }
+
+    // 以有效的品质和当前时间更新点的值
+    pub fn set(&self, ca: CommonAddr, ioa: u16, value: PointValue) -> Result<Vec<Asdu>, Error> {
+        self.update(ca, ioa, value, ObjectQDS::of_defaults(), Some(Utc::now()))
+    }
+
+    // 设置规一化值的点的工程值换算表, 见 set_engineering
+    pub fn set_scale(&self, ca: CommonAddr, ioa: u16, table: ScaleTable) {
+        self.inner.write().unwrap().scales.insert((ca, ioa), table);
+    }
+
+    // 以工程值更新规一化值的点, 按换算表转换, 未设置换算表时 value 为 -1 ~ +1 的规一化值
+    pub fn set_engineering(
+        &self,
+        ca: CommonAddr,
+        ioa: u16,
+        value: f32,
+    ) -> Result<Vec<Asdu>, Error> {
+        let nva = match self.inner.read().unwrap().scales.get(&(ca, ioa)) {
+            Some(table) => table.to_nva(value),
+            None => Nva::from_f32(value),
+        };
+        self.set(ca, ioa, PointValue::Normalized(nva.into()))
+    }
+
+    // 规一化值的点的工程值, 其他类型的点返回 None
+    pub fn get_engineering(&self, ca: CommonAddr, ioa: u16) -> Option<f32> {
+        let inner = self.inner.read().unwrap();
+        let PointValue::Normalized(v) = inner.points.get(&(ca, ioa))?.value else {
+            return None;
+        };
+        Some(match inner.scales.get(&(ca, ioa)) {
+            Some(table) => table.to_engineering(Nva(v)),
+            None => Nva(v).to_f32(),
+        })
+    }
+
+    // 总召唤的响应数据(不含激活确认与激活终止): QOI 为 20 时为公共地址下除累计量外的全部点,
+    // 21~36 时为第1~16组的点, 其他 QOI 返回空集合. 响应不带时标, 同类型的点合并到同一个 ASDU
+    pub fn interrogation(
+        &self,
+        ca: CommonAddr,
+        qoi: impl Into<ObjectQOI>,
+    ) -> Result<Vec<Asdu>, Error> {
+        let (cause, group) = match Qoi::from(qoi.into()) {
+            Qoi::StationInterrogation => (Cause::InterrogatedByStation, None),
+            Qoi::Group(n @ 1..=16) => (GROUP_CAUSES[(n - 1) as usize], Some(n)),
+            _ => return Ok(Vec::new()),
+        };
+        self.collect(ca, cause, |point| {
+            !matches!(point.value, PointValue::Counter(_)) && group.is_none_or(|g| point.group == g)
+        })
+    }
+
+    // 计数量召唤的响应数据: QCC 的 RQT 为 5 时为公共地址下的全部累计量, 1~4 时为第1~4组的累计量.
+    // FRZ 为读时响应冻结值(未冻结过的累计量为当前值); 为冻结(带或不带复位)时冻结后返回以突发传送原因
+    // 上送的冻结值; 为复位时只复位, 返回空集合. 见 freeze_counters
+    pub fn counter_interrogation(
+        &self,
+        ca: CommonAddr,
+        qcc: impl Into<ObjectQCC>,
+    ) -> Result<Vec<Asdu>, Error> {
+        let qcc = Qcc::from(qcc.into());
+        let (cause, group) = match qcc.request {
+            CounterGroup::General => (Cause::RequestByGeneralCounter, None),
+            CounterGroup::Group(n @ 1..=4) => (COUNTER_GROUP_CAUSES[(n - 1) as usize], Some(n)),
+            _ => return Ok(Vec::new()),
+        };
+        match qcc.freeze {
+            FreezeMode::Read => self.collect_counters(ca, cause, group),
+            FreezeMode::Freeze | FreezeMode::FreezeWithReset => {
+                self.freeze_counters(ca, group, qcc.freeze);
+                self.collect_counters(ca, Cause::Spontaneous, group)
+            }
+            FreezeMode::Reset => {
+                self.freeze_counters(ca, group, qcc.freeze);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    // 冻结或复位公共地址下计数量召唤组 group(None 为全部)的累计量, 返回处理的点数.
+    // 冻结: 当前值保存为冻结值, 顺序号加 1(0~31 循环); 带复位时随后将当前值清零;
+    // 复位: 只将当前值清零, 下一次冻结值的 CA(计数量被调整)置位. 读不做任何处理
+    pub fn freeze_counters(&self, ca: CommonAddr, group: Option<u8>, mode: FreezeMode) -> usize {
+        if mode == FreezeMode::Read {
+            return 0;
+        }
+        let mut inner = self.inner.write().unwrap();
+        let StoreInner {
+            points,
+            frozen,
+            reset,
+            ..
+        } = &mut *inner;
+        let mut n = 0;
+        for ((_, ioa), point) in points.range_mut((ca, 0)..=(ca, u16::MAX)) {
+            let PointValue::Counter(value) = point.value else {
+                continue;
+            };
+            if group.is_some_and(|g| point.group != g) {
+                continue;
+            }
+            let key = (ca, *ioa);
+            // 只复位时不产生冻结值, 未冻结过的累计量仍以当前值响应
+            if mode != FreezeMode::Reset {
+                let adjusted = reset.remove(&key);
+                frozen.entry(key).or_default().freeze(value, adjusted);
+            }
+            if mode != FreezeMode::Freeze {
+                point.value = PointValue::Counter(0);
+            }
+            if mode == FreezeMode::Reset {
+                reset.insert(key);
+            }
+            n += 1;
+        }
+        n
+    }
+
+    // 周期/循环上送的数据: 公共地址下召唤组 group 的测量值(规一化值, 标度化值, 短浮点数),
+    // group 为 0 时为全部测量值. 不带时标
+    pub fn cyclic(&self, ca: CommonAddr, group: u8) -> Result<Vec<Asdu>, Error> {
+        self.collect(ca, Cause::Periodic, |point| {
+            matches!(
+                point.value,
+                PointValue::Normalized(_) | PointValue::Scaled(_) | PointValue::Float(_)
+            ) && (group == 0 || point.group == group)
+        })
+    }
+
+    // 背景扫描的数据: 公共地址下召唤组 group 中除累计量外的点, group 为 0 时为全部点. 不带时标
+    pub fn background(&self, ca: CommonAddr, group: u8) -> Result<Vec<Asdu>, Error> {
+        self.collect(ca, Cause::Background, |point| {
+            !matches!(point.value, PointValue::Counter(_)) && (group == 0 || point.group == group)
+        })
+    }
+
+    // 读命令的响应, 点不存在时返回 None
+    pub fn read(&self, ca: CommonAddr, ioa: u16) -> Result<Option<Asdu>, Error> {
+        let Some(point) = self.get(ca, ioa) else {
+            return Ok(None);
+        };
+        let cot = CauseOfTransmission::new(false, false, Cause::Request);
+        let mut infos = Infos::default();
+        infos.push(ioa, &point);
+        Ok(infos.into_asdus(cot, ca, false)?.pop())
+    }
+
+    // 计数量召唤组 group(None 为全部)的累计量, 有冻结值时以冻结值及其顺序号响应
+    fn collect_counters(
+        &self,
+        ca: CommonAddr,
+        cause: Cause,
+        group: Option<u8>,
+    ) -> Result<Vec<Asdu>, Error> {
+        let mut infos = Infos::default();
+        {
+            let inner = self.inner.read().unwrap();
+            for ((_, ioa), point) in inner.points.range((ca, 0)..=(ca, u16::MAX)) {
+                let PointValue::Counter(value) = point.value else {
+                    continue;
+                };
+                if group.is_some_and(|g| point.group != g) {
+                    continue;
+                }
+                let mut q = point.quality;
+                let frozen = inner.frozen.get(&(ca, *ioa));
+                infos.counter.push(BinaryCounterReadingInfo {
+                    ioa: InfoObjAddr::new(0, *ioa),
+                    bcr: ObjectBCR {
+                        invalid: q.invalid().get(),
+                        ca: frozen.is_some_and(|f| f.adjusted),
+                        cy: false,
+                        seq: frozen.map_or(0, |f| f.seq),
+                        value: frozen.map_or(value, |f| f.value),
+                    },
+                    time: None,
+                });
+            }
+        }
+        infos.into_asdus(CauseOfTransmission::new(false, false, cause), ca, false)
+    }
+
+    fn collect<F>(&self, ca: CommonAddr, cause: Cause, filter: F) -> Result<Vec<Asdu>, Error>
+    where
+        F: Fn(&Point) -> bool,
+    {
+        let mut infos = Infos::default();
+        {
+            let inner = self.inner.read().unwrap();
+            for ((_, ioa), point) in inner.points.range((ca, 0)..=(ca, u16::MAX)) {
+                if filter(point) {
+                    infos.push(*ioa, point);
+                }
+            }
+        }
+        infos.into_asdus(CauseOfTransmission::new(false, false, cause), ca, false)
+    }
+}
+
+// 累计量的冻结值
+#[derive(Debug, Clone, Copy, Default)]
+struct Frozen {
+    value: i32,
+    // 顺序号 0~31, 每次冻结加 1
+    seq: u8,
+    // 冻结值所在的周期内计数量被复位
+    adjusted: bool,
+}
+
+impl Frozen {
+    fn freeze(&mut self, value: i32, adjusted: bool) {
+        self.value = value;
+        self.seq = (self.seq + 1) % 32;
+        self.adjusted = adjusted;
+    }
+}
+
+// 只提供数据的被控站: 以点数据库响应站召唤, 组召唤, 计数量召唤及读命令,
+// 不支持的控制命令回复否定的未知类型标识. 需要处理控制命令时, 在自定义的 ServerHandler 中
+// 调用 interrogation, counter_interrogation 与 read
+impl ServerHandler for DataStore {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        if !self.has_common_addr(asdu.identifier.common_addr) {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::UnknownCA)]));
+        }
+        if matches!(Qoi::from(qoi), Qoi::Reserved(_)) {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::ActivationCon)]));
+        }
+        let ca = asdu.identifier.common_addr;
+        future::ready(
+            self.interrogation(ca, qoi)
+                .map(|data| procedure(&asdu, data)),
+        )
+    }
+
+    fn call_counter_interrogation(&self, asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
+        if !self.has_common_addr(asdu.identifier.common_addr) {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::UnknownCA)]));
+        }
+        if matches!(Qcc::from(qcc).request, CounterGroup::Reserved(_)) {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::ActivationCon)]));
+        }
+        let ca = asdu.identifier.common_addr;
+        future::ready(
+            self.counter_interrogation(ca, qcc)
+                .map(|data| procedure(&asdu, data)),
+        )
+    }
+
+    fn call_read(&self, asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        let mut ioa = ioa;
+        let ca = asdu.identifier.common_addr;
+        if !self.has_common_addr(ca) {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::UnknownCA)]));
+        }
+        future::ready(
+            self.read(ca, ioa.addr().get())
+                .map(|asdu| asdu.into_iter().collect()),
+        )
+    }
+
+    fn call_clock_sync(&self, asdu: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)]))
+    }
+
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        future::ready(Ok(vec![asdu.mirror_negative(Cause::UnknownTypeID)]))
+    }
+}
+
+// 召唤过程: 激活确认, 数据, 激活终止
+fn procedure(asdu: &Asdu, data: Vec<Asdu>) -> Vec<Asdu> {
+    let mut asdus = Vec::with_capacity(data.len() + 2);
+    asdus.push(asdu.mirror(Cause::ActivationCon));
+    asdus.extend(data);
+    asdus.push(asdu.mirror(Cause::ActivationTerm));
+    asdus
+}
+
+// 按类型分组的信息对象
+#[derive(Default)]
+pub(crate) struct Infos {
+    single: Vec<SinglePointInfo>,
+    double: Vec<DoublePointInfo>,
+    normal: Vec<MeasuredValueNormalInfo>,
+    scaled: Vec<MeasuredValueScaledInfo>,
+    float: Vec<MeasuredValueFloatInfo>,
+    counter: Vec<BinaryCounterReadingInfo>,
+}
+
+impl Infos {
+    fn push(&mut self, addr: u16, point: &Point) {
+        let ioa = InfoObjAddr::new(0, addr);
+        self.push_value(ioa, point.value, point.quality, point.time);
+    }
+
+    pub(crate) fn push_value(
+        &mut self,
+        ioa: InfoObjAddr,
+        value: PointValue,
+        quality: ObjectQDS,
+        time: Option<DateTime<Utc>>,
+    ) {
+        let mut q = quality;
+        let (invalid, nt, sb, bl) = (q.invalid().get(), q.nt().get(), q.sb().get(), q.bl().get());
+        match value {
+            PointValue::Single(v) => self.single.push(SinglePointInfo {
+                ioa,
+                siq: ObjectSIQ::new(invalid, nt, sb, bl, u3!(0), v),
+                time,
+            }),
+            PointValue::Double(v) => self.double.push(DoublePointInfo {
+                ioa,
+                diq: ObjectDIQ::new(invalid, nt, sb, bl, u2!(0), u2::new(v % 4).unwrap()),
+                time,
+            }),
+            PointValue::Normalized(nva) => self.normal.push(MeasuredValueNormalInfo {
+                ioa,
+                nva,
+                qds: Some(quality),
+                time,
+            }),
+            PointValue::Scaled(sva) => self.scaled.push(MeasuredValueScaledInfo {
+                ioa,
+                sva,
+                qds: quality,
+                time,
+            }),
+            PointValue::Float(r) => self.float.push(MeasuredValueFloatInfo {
+                ioa,
+                r,
+                qds: quality,
+                time,
+            }),
+            PointValue::Counter(value) => self.counter.push(BinaryCounterReadingInfo {
+                ioa,
+                bcr: ObjectBCR {
+                    invalid,
+                    ca: false,
+                    cy: false,
+                    seq: 0,
+                    value,
+                },
+                time,
+            }),
+        }
+    }
+
+    // 以 type_id 编码为一个 ASDU, 只编码与 type_id 同类的值
+    pub(crate) fn into_asdu(
+        self,
+        type_id: TypeID,
+        is_sequence: bool,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+    ) -> Result<Asdu, Error> {
+        match type_id {
+            TypeID::M_SP_NA_1 | TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1 => {
+                single_inner(type_id, is_sequence, cot, ca, self.single)
+            }
+            TypeID::M_DP_NA_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1 => {
+                double_inner(type_id, is_sequence, cot, ca, self.double)
+            }
+            TypeID::M_ME_NA_1 | TypeID::M_ME_TA_1 | TypeID::M_ME_TD_1 | TypeID::M_ME_ND_1 => {
+                measured_value_normal_inner(type_id, is_sequence, cot, ca, self.normal)
+            }
+            TypeID::M_ME_NB_1 | TypeID::M_ME_TB_1 | TypeID::M_ME_TE_1 => {
+                measured_value_scaled_inner(type_id, is_sequence, cot, ca, self.scaled)
+            }
+            TypeID::M_ME_NC_1 | TypeID::M_ME_TC_1 | TypeID::M_ME_TF_1 => {
+                measured_value_float_inner(type_id, is_sequence, cot, ca, self.float)
+            }
+            TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1 => {
+                integrated_totals_inner(type_id, is_sequence, cot, ca, self.counter)
+            }
+            _ => Err(Error::ErrTypeIDNotMatch(type_id)),
+        }
+    }
+
+    // 编码为 ASDU, 信息对象过多时拆分
+    fn into_asdus(
+        self,
+        cot: CauseOfTransmission,
+        ca: CommonAddr,
+        timed: bool,
+    ) -> Result<Vec<Asdu>, Error> {
+        let mut asdus = Vec::new();
+        let type_id = PointValue::Single(false).type_id(timed);
+        asdus.extend(split_into_asdus(self.single, |infos| {
+            single_inner(type_id, false, cot, ca, infos)
+        })?);
+        let type_id = PointValue::Double(0).type_id(timed);
+        asdus.extend(split_into_asdus(self.double, |infos| {
+            double_inner(type_id, false, cot, ca, infos)
+        })?);
+        let type_id = PointValue::Normalized(0).type_id(timed);
+        asdus.extend(split_into_asdus(self.normal, |infos| {
+            measured_value_normal_inner(type_id, false, cot, ca, infos)
+        })?);
+        let type_id = PointValue::Scaled(0).type_id(timed);
+        asdus.extend(split_into_asdus(self.scaled, |infos| {
+            measured_value_scaled_inner(type_id, false, cot, ca, infos)
+        })?);
+        let type_id = PointValue::Float(0.0).type_id(timed);
+        asdus.extend(split_into_asdus(self.float, |infos| {
+            measured_value_float_inner(type_id, false, cot, ca, infos)
+        })?);
+        let type_id = PointValue::Counter(0).type_id(timed);
+        asdus.extend(split_into_asdus(self.counter, |infos| {
+            integrated_totals_inner(type_id, false, cot, ca, infos)
+        })?);
+        Ok(asdus)
+    }
+}
@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::{
     client::Request,
-    frame::asdu::{CauseOfTransmission, TypeID},
+    frame::asdu::{Cause, CauseOfTransmission, TypeID},
 };
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -16,6 +16,24 @@ pub enum Error {
     #[error("asdu: [cause of transmission: {0:?}] for command not standard requirement")]
     ErrCmdCause(CauseOfTransmission),
 
+    #[error("asdu: information object address {0} breaks the sequence")]
+    ErrIoaNotSequential(u16),
+
+    #[error("asdu: double command state {0} not permitted")]
+    ErrInvalidDcs(u8),
+
+    #[error("asdu: number of information objects {0} out of range 1~127")]
+    ErrInfoNum(usize),
+
+    #[error("asdu: [type identifier: {0:?}] information length {2} shorter than expected {1}")]
+    ErrAsduTooShort(TypeID, usize, usize),
+
+    #[error("asdu: length {0} exceeds limit")]
+    ErrAsduTooLarge(usize),
+
+    #[error("asdu: segment length {0} exceeds limit")]
+    ErrSegmentTooLarge(usize),
+
     #[error("Invalid frame")]
     ErrInvalidFrame,
 
@@ -26,6 +44,40 @@ pub enum Error {
     ErrUseClosedConnection,
     #[error("")]
     ErrNotActive,
+    #[error("not connected")]
+    ErrNotConnected,
+    #[error("{0} is sent by the controlling station")]
+    ErrNotControlling(&'static str),
+    #[error("connection from {0} rejected: access denied")]
+    ErrAccessDenied(std::net::SocketAddr),
+    #[error("connection from {0} rejected: session limit reached")]
+    ErrTooManySessions(std::net::SocketAddr),
+    #[error("connection from {0} rejected: session limit per IP reached")]
+    ErrTooManySessionsPerIp(std::net::SocketAddr),
+    #[error("no frame received within idle timeout")]
+    ErrIdleTimeout,
+    #[error("I-frame received before STARTDT")]
+    ErrIFrameBeforeStartDt,
+    #[error("timeout waiting for response")]
+    ErrTimeout,
+    #[error("t1 expired waiting for acknowledgement")]
+    ErrT1Timeout,
+    #[error("sequence number error")]
+    ErrSequence,
+    #[error("connection closed by peer")]
+    ErrConnectionClosed,
+    #[error("client stopped")]
+    ErrClientStopped,
+    #[error("send queue is full")]
+    ErrQueueFull,
+    #[error("send channel is full")]
+    ErrBufferFull,
+    #[error("negative confirmation: [type identifier: {0:?}] [cause of transmission: {1:?}]")]
+    ErrNegativeConfirm(TypeID, Cause),
+    #[error("test command confirmation doesn't echo the test word and time tag")]
+    ErrTestMismatch,
+    #[error("queued command cancelled")]
+    ErrCommandCancelled,
 
     #[error("anyhow error")]
     ErrAnyHow(#[from] anyhow::Error),
@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use tokio::{sync::mpsc, time::timeout};
+
+use crate::{
+    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, TypeID},
+    client::{Client, ClientHandler},
+    file::{
+        checksum, file_ack, file_call, DirectoryInfo, FileAckInfo, FileCallInfo,
+        AFQ_NEGATIVE_SECTION, AFQ_POSITIVE_FILE, AFQ_POSITIVE_SECTION,
+        LSQ_FILE_TRANSFER_WITHOUT_DEACT, LSQ_FILE_TRANSFER_WITH_DEACT,
+        LSQ_SECTION_TRANSFER_WITHOUT_DEACT, LSQ_SECTION_TRANSFER_WITH_DEACT, SCQ_DEFAULT,
+        SCQ_REQUEST_FILE, SCQ_REQUEST_SECTION, SCQ_SELECT_FILE,
+    },
+    Error,
+};
+
+// 默认等待每一步响应的超时时间
+const DEFAULT_STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 文件传输客户端, 驱动 选择文件 -> 召唤文件 -> 接收节与段 -> 确认 的过程
+pub struct FileTransferClient<'a, S> {
+    client: &'a Client<S>,
+    timeout: Duration,
+}
+
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    pub fn file_transfer(&self) -> FileTransferClient<'_, S> {
+        FileTransferClient {
+            client: self,
+            timeout: DEFAULT_STEP_TIMEOUT,
+        }
+    }
+}
+
+impl<'a, S> FileTransferClient<'a, S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 设置等待每一步响应的超时时间
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // 召唤目录, 返回所有目录项直到最后目录文件(LFD)
+    pub async fn call_directory(
+        &self,
+        ca: CommonAddr,
+        ioa: u16,
+    ) -> Result<Vec<DirectoryInfo>, Error> {
+        let mut rx = self
+            .client
+            .subscribe_asdu(move |asdu| {
+                asdu.identifier.common_addr == ca
+                    && (asdu.identifier.type_id == TypeID::F_DR_TA_1
+                        || asdu.identifier.type_id == TypeID::F_SC_NA_1)
+            })
+            .await;
+
+        self.call(
+            Cause::Request,
+            ca,
+            FileCallInfo::new(ioa, 0, 0, SCQ_DEFAULT),
+        )
+        .await?;
+
+        let mut entries = Vec::new();
+        loop {
+            let mut asdu = self.recv(&mut rx).await?;
+            if asdu.identifier.type_id != TypeID::F_DR_TA_1 {
+                check_mirror(&mut asdu)?;
+                continue;
+            }
+            let mut infos = asdu.get_directory()?;
+            let last = infos.iter_mut().any(|info| info.sof.lfd().get());
+            entries.extend(infos);
+            if last {
+                return Ok(entries);
+            }
+        }
+    }
+
+    // 读取文件, 返回组装后的文件内容
+    pub async fn read_file(&self, ca: CommonAddr, ioa: u16, nof: u16) -> Result<Bytes, Error> {
+        let mut rx = self
+            .client
+            .subscribe_asdu(move |asdu| {
+                asdu.identifier.common_addr == ca
+                    && matches!(
+                        asdu.identifier.type_id,
+                        TypeID::F_FR_NA_1
+                            | TypeID::F_SR_NA_1
+                            | TypeID::F_SC_NA_1
+                            | TypeID::F_LS_NA_1
+                            | TypeID::F_SG_NA_1
+                    )
+            })
+            .await;
+
+        // 选择文件
+        self.call(
+            Cause::FileTransfer,
+            ca,
+            FileCallInfo::new(ioa, nof, 0, SCQ_SELECT_FILE),
+        )
+        .await?;
+        loop {
+            let mut asdu = self.recv(&mut rx).await?;
+            if asdu.identifier.type_id != TypeID::F_FR_NA_1 {
+                check_mirror(&mut asdu)?;
+                continue;
+            }
+            let mut ready = asdu.get_file_ready()?;
+            if ready.nof != nof {
+                continue;
+            }
+            if ready.frq.negative().get() {
+                return Err(Error::ErrAnyHow(anyhow::anyhow!(
+                    "file {} not ready: negative confirm",
+                    nof
+                )));
+            }
+            break;
+        }
+
+        // 召唤文件
+        self.call(
+            Cause::FileTransfer,
+            ca,
+            FileCallInfo::new(ioa, nof, 0, SCQ_REQUEST_FILE),
+        )
+        .await?;
+
+        let mut file = BytesMut::new();
+        let mut section = BytesMut::new();
+        loop {
+            let mut asdu = self.recv(&mut rx).await?;
+            match asdu.identifier.type_id {
+                TypeID::F_SR_NA_1 => {
+                    let mut ready = asdu.get_section_ready()?;
+                    if ready.nof != nof {
+                        continue;
+                    }
+                    if ready.srq.not_ready().get() {
+                        return Err(Error::ErrAnyHow(anyhow::anyhow!(
+                            "section {} of file {} not ready",
+                            ready.nos,
+                            nof
+                        )));
+                    }
+                    section.clear();
+                    // 召唤节
+                    self.call(
+                        Cause::FileTransfer,
+                        ca,
+                        FileCallInfo::new(ioa, nof, ready.nos, SCQ_REQUEST_SECTION),
+                    )
+                    .await?;
+                }
+                TypeID::F_SG_NA_1 => {
+                    let seg = asdu.get_segment()?;
+                    if seg.nof == nof {
+                        section.extend_from_slice(&seg.segment);
+                    }
+                }
+                TypeID::F_LS_NA_1 => {
+                    let mut last = asdu.get_last_section()?;
+                    if last.nof != nof {
+                        continue;
+                    }
+                    let lsq = last.lsq.lsq().get();
+                    if lsq == LSQ_SECTION_TRANSFER_WITHOUT_DEACT
+                        || lsq == LSQ_SECTION_TRANSFER_WITH_DEACT
+                    {
+                        if checksum(&section) != last.chs {
+                            self.ack(ca, ioa, nof, last.nos, AFQ_NEGATIVE_SECTION)
+                                .await?;
+                            return Err(Error::ErrAnyHow(anyhow::anyhow!(
+                                "section {} of file {} checksum mismatch",
+                                last.nos,
+                                nof
+                            )));
+                        }
+                        file.extend_from_slice(&section);
+                        section.clear();
+                        self.ack(ca, ioa, nof, last.nos, AFQ_POSITIVE_SECTION)
+                            .await?;
+                    } else if lsq == LSQ_FILE_TRANSFER_WITHOUT_DEACT
+                        || lsq == LSQ_FILE_TRANSFER_WITH_DEACT
+                    {
+                        if checksum(&file) != last.chs {
+                            return Err(Error::ErrAnyHow(anyhow::anyhow!(
+                                "file {} checksum mismatch",
+                                nof
+                            )));
+                        }
+                        self.ack(ca, ioa, nof, 0, AFQ_POSITIVE_FILE).await?;
+                        return Ok(file.freeze());
+                    }
+                }
+                _ => check_mirror(&mut asdu)?,
+            }
+        }
+    }
+
+    async fn call(&self, cause: Cause, ca: CommonAddr, info: FileCallInfo) -> Result<(), Error> {
+        let cot = CauseOfTransmission::new(false, false, cause);
+        self.client.send_asdu(file_call(cot, ca, info)?).await
+    }
+
+    async fn ack(&self, ca: CommonAddr, ioa: u16, nof: u16, nos: u8, afq: u8) -> Result<(), Error> {
+        let cot = CauseOfTransmission::new(false, false, Cause::FileTransfer);
+        self.client
+            .send_asdu(file_ack(cot, ca, FileAckInfo::new(ioa, nof, nos, afq))?)
+            .await
+    }
+
+    async fn recv(&self, rx: &mut mpsc::UnboundedReceiver<Asdu>) -> Result<Asdu, Error> {
+        match timeout(self.timeout, rx.recv()).await {
+            Ok(Some(asdu)) => Ok(asdu),
+            Ok(None) => Err(Error::ErrUseClosedConnection),
+            Err(_) => Err(Error::ErrTimeout),
+        }
+    }
+}
+
+// 对端镜像回来的召唤命令带有否定确认或未知原因时, 终止文件传输
+fn check_mirror(asdu: &mut Asdu) -> Result<(), Error> {
+    if asdu.identifier.cot.is_rejected() {
+        let cause = asdu.identifier.cot.cause().get();
+        return Err(Error::ErrNegativeConfirm(asdu.identifier.type_id, cause));
+    }
+    Ok(())
+}
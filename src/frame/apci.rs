@@ -152,34 +152,77 @@ pub fn new_uframe(function: u8) -> Apdu {
     }
 }
 
-fn seq_no_count(next_ack_no: u16, mut next_send_no: u16) -> u16 {
-    if next_ack_no > next_send_no {
-        next_send_no += 32768;
+// 15 位的发送/接收序列号, 按模 32768 循环
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SeqNum(u16);
+
+impl SeqNum {
+    // 序列号的模
+    pub const MODULUS: u16 = 32768;
+
+    // 取 value 的低 15 位
+    pub fn new(value: u16) -> Self {
+        SeqNum(value % Self::MODULUS)
+    }
+
+    pub fn value(self) -> u16 {
+        self.0
+    }
+
+    // 下一个序列号, 32767 之后为 0
+    #[must_use]
+    pub fn next(self) -> Self {
+        self.wrapping_add(1)
+    }
+
+    #[must_use]
+    pub fn wrapping_add(self, n: u16) -> Self {
+        SeqNum(((u32::from(self.0) + u32::from(n)) % u32::from(Self::MODULUS)) as u16)
+    }
+
+    // 从 self 递增到 to 的步数, 如 32767 到 1 为 2
+    pub fn distance(self, to: SeqNum) -> u16 {
+        (to.0 + Self::MODULUS - self.0) % Self::MODULUS
+    }
+}
+
+impl From<u16> for SeqNum {
+    fn from(value: u16) -> Self {
+        SeqNum::new(value)
+    }
+}
+
+impl From<SeqNum> for u16 {
+    fn from(sn: SeqNum) -> Self {
+        sn.0
     }
-    next_send_no - next_ack_no
 }
 
+impl Display for SeqNum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// 处理对端的确认号 ack_no: 须在 [ack_sendsn, send_sn] 内, 否则返回 false.
+// 移除 pending 中已被确认的 I 帧并更新 ack_sendsn
 pub fn update_ack_no_out(
-    ack_no: u16,
-    ack_sendsn: &mut u16,
-    send_sn: &mut u16,
+    ack_no: SeqNum,
+    ack_sendsn: &mut SeqNum,
+    send_sn: &mut SeqNum,
     pending: &mut VecDeque<SeqPending>,
 ) -> bool {
     if ack_no == *ack_sendsn {
         return true;
     }
 
-    if seq_no_count(*ack_sendsn, *send_sn) < seq_no_count(ack_no, *send_sn) {
+    if ack_sendsn.distance(*send_sn) < ack_no.distance(*send_sn) {
         return false;
     }
 
-    for i in 0..pending.len() {
-        if let Some(p) = pending.pop_front() {
-            if p.seq == ack_no - 1 {
-                break;
-            }
-        }
-    }
+    // pending 按发送顺序从 ack_sendsn 开始排列
+    let acked = usize::from(ack_sendsn.distance(ack_no)).min(pending.len());
+    pending.drain(..acked);
     *ack_sendsn = ack_no;
     true
 }
@@ -1,13 +1,15 @@
 use std::{
     fmt::{Debug, Display},
-    io::Cursor,
+    io::{self, Cursor, Read},
 };
 
 use anyhow::{anyhow, Result};
 use bit_struct::*;
-use byteorder::ReadBytesExt;
+use byteorder::{LittleEndian, ReadBytesExt};
 use bytes::{BufMut, Bytes, BytesMut};
 
+use crate::error::Error;
+
 // ASDUSizeMax asdu max size
 pub(crate) const ASDU_SIZE_MAX: usize = 249;
 
@@ -27,22 +29,77 @@ pub const INVALID_COMMON_ADDR: u16 = 0;
 // GlobalCommonAddr is the broadcast address. Use is restricted
 // to C_IC_NA_1, C_CI_NA_1, C_CS_NA_1 and C_RP_NA_1.
 // When in 8-bit mode 255 is mapped to this value on the fly.
-#[allow(dead_code)]
-const GLOBAL_COMMON_ADDR: u16 = 255;
+pub const GLOBAL_COMMON_ADDR: u16 = 65535;
 
 pub const IDENTIFIER_SIZE: usize = 6;
 
+// ASDU 各字段的长度. IEC 104 固定为传送原因 2 字节(含源站址), 公共地址 2 字节, 信息对象地址 3 字节;
+// IEC 101 由系统参数确定. Asdu 在内存中始终按 IEC 104 的长度保存, 只在编解码时转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsduParams {
+    /// 传送原因长度 [1, 2], 为 1 时不传送源站址
+    pub cot_size: usize,
+    /// 公共地址长度 [1, 2], 为 1 时 255 对应全局地址 65535
+    pub ca_size: usize,
+    /// 信息对象地址长度 [1, 3]
+    pub ioa_size: usize,
+}
+
+impl AsduParams {
+    pub const IEC104: AsduParams = AsduParams {
+        cot_size: 2,
+        ca_size: 2,
+        ioa_size: 3,
+    };
+
+    pub fn new(cot_size: usize, ca_size: usize, ioa_size: usize) -> Result<Self> {
+        let params = AsduParams {
+            cot_size,
+            ca_size,
+            ioa_size,
+        };
+        params.valid()?;
+        Ok(params)
+    }
+
+    pub fn valid(&self) -> Result<()> {
+        if !(1..=2).contains(&self.cot_size)
+            || !(1..=2).contains(&self.ca_size)
+            || !(1..=3).contains(&self.ioa_size)
+        {
+            return Err(anyhow!("invalid asdu params: {self:?}"));
+        }
+        Ok(())
+    }
+
+    // 数据单元标识符的长度
+    pub fn identifier_size(&self) -> usize {
+        2 + self.cot_size + self.ca_size
+    }
+}
+
+impl Default for AsduParams {
+    fn default() -> Self {
+        AsduParams::IEC104
+    }
+}
+
 pub type OriginAddr = u8;
 pub type CommonAddr = u16;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Asdu {
     pub identifier: Identifier,
     pub raw: Bytes,
 }
 
+// 默认输出 describe 的可读文本, {:#} 输出原始字节
 impl Display for Asdu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !f.alternate() {
+            return f.write_str(self.describe().as_str());
+        }
         f.write_str(self.identifier.to_string().as_str())?;
         f.write_str(
             self.raw
@@ -58,6 +115,7 @@ impl Display for Asdu {
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identifier {
     /// 类型标识
     pub type_id: TypeID,
@@ -73,7 +131,7 @@ pub struct Identifier {
 
 impl Display for Identifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("[{:02X}]", self.type_id as u8))?;
+        f.write_fmt(format_args!("[{:02X}]", u8::from(self.type_id)))?;
         f.write_fmt(format_args!("[{:02X}]", self.variable_struct.raw()))?;
         f.write_fmt(format_args!("[{:02X}]", self.cot.raw()))?;
         f.write_fmt(format_args!("[{:02X}]", self.orig_addr))?;
@@ -106,9 +164,9 @@ enums! {
         Deactivation,               // 停止激活 （遥控、参数设置 控制方向）
         DeactivationCon,            // 停止激活确认（遥控、参数设置 监视方向）
         ActivationTerm,             // 激活终止 （遥控 监视方向）
-        FileTransfer,               // 文件传输
         ReturnInfoRemote,           // 远程信息返回
         ReturnInfoLocal,            // 本地信息返回
+        FileTransfer,               // 文件传输
         Authentication,             // 认证
         SessionKey,                 // 会话密钥
         UserRoleAndUpdateKey,       // 用户角色和更新密钥
@@ -157,8 +215,32 @@ bit_struct! {
     }
 }
 
+impl CauseOfTransmission {
+    // P/N 位, true 为否定确认
+    pub fn is_negative(&self) -> bool {
+        let mut cot = *self;
+        cot.positive().get()
+    }
+
+    pub fn set_negative(&mut self, negative: bool) {
+        self.positive().set(negative);
+    }
+
+    // 否定确认, 或未知的类型标识, 传送原因, 公共地址, 信息对象地址
+    pub fn is_rejected(&self) -> bool {
+        let mut cot = *self;
+        cot.is_negative()
+            || matches!(
+                cot.cause().get(),
+                Cause::UnknownTypeID | Cause::UnknownCOT | Cause::UnknownCA | Cause::UnknownIOA
+            )
+    }
+}
+
 #[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
 pub enum TypeID {
     M_SP_NA_1 = 1,  // 单点信息
     M_SP_TA_1 = 2,  // 带时标单点信息
@@ -241,105 +323,237 @@ pub enum TypeID {
     F_SG_NA_1 = 125, // 段
     F_DR_TA_1 = 126, // 目录
     F_SC_NB_1 = 127, // 日志查询-请求存档文件
+    // 保留或专用范围内未定义的类型标识, 保留原始值以便回复未知的类型标识
+    Unknown(u8),
 }
 
-impl TryFrom<u8> for TypeID {
-    type Error = anyhow::Error;
-
-    fn try_from(value: u8) -> Result<Self> {
+impl From<u8> for TypeID {
+    fn from(value: u8) -> Self {
         match value {
-            1 => Ok(Self::M_SP_NA_1),
-            2 => Ok(Self::M_SP_TA_1),
-            3 => Ok(Self::M_DP_NA_1),
-            4 => Ok(Self::M_DP_TA_1),
-            5 => Ok(Self::M_ST_NA_1),
-            6 => Ok(Self::M_ST_TA_1),
-            7 => Ok(Self::M_BO_NA_1),
-            8 => Ok(Self::M_BO_TA_1),
-            9 => Ok(Self::M_ME_NA_1),
-            10 => Ok(Self::M_ME_TA_1),
-            11 => Ok(Self::M_ME_NB_1),
-            12 => Ok(Self::M_ME_TB_1),
-            13 => Ok(Self::M_ME_NC_1),
-            14 => Ok(Self::M_ME_TC_1),
-            15 => Ok(Self::M_IT_NA_1),
-            16 => Ok(Self::M_IT_TA_1),
-            17 => Ok(Self::M_EP_TA_1),
-            18 => Ok(Self::M_EP_TB_1),
-            19 => Ok(Self::M_EP_TC_1),
-            20 => Ok(Self::M_PS_NA_1),
-            21 => Ok(Self::M_ME_ND_1),
-            30 => Ok(Self::M_SP_TB_1),
-            31 => Ok(Self::M_DP_TB_1),
-            32 => Ok(Self::M_ST_TB_1),
-            33 => Ok(Self::M_BO_TB_1),
-            34 => Ok(Self::M_ME_TD_1),
-            35 => Ok(Self::M_ME_TE_1),
-            36 => Ok(Self::M_ME_TF_1),
-            37 => Ok(Self::M_IT_TB_1),
-            38 => Ok(Self::M_EP_TD_1),
-            39 => Ok(Self::M_EP_TE_1),
-            40 => Ok(Self::M_EP_TF_1),
-            41 => Ok(Self::S_IT_TC_1),
-            45 => Ok(Self::C_SC_NA_1),
-            46 => Ok(Self::C_DC_NA_1),
-            47 => Ok(Self::C_RC_NA_1),
-            48 => Ok(Self::C_SE_NA_1),
-            49 => Ok(Self::C_SE_NB_1),
-            50 => Ok(Self::C_SE_NC_1),
-            51 => Ok(Self::C_BO_NA_1),
-            52 => Ok(Self::M_IT_TA_1),
-            53 => Ok(Self::M_IT_TA_1),
-            54 => Ok(Self::M_IT_TA_1),
-            55 => Ok(Self::M_IT_TA_1),
-            56 => Ok(Self::M_IT_TA_1),
-            57 => Ok(Self::M_IT_TA_1),
-            58 => Ok(Self::C_SC_TA_1),
-            59 => Ok(Self::C_DC_TA_1),
-            60 => Ok(Self::C_RC_TA_1),
-            61 => Ok(Self::C_SE_TA_1),
-            62 => Ok(Self::C_SE_TB_1),
-            63 => Ok(Self::C_SE_TC_1),
-            64 => Ok(Self::C_BO_TA_1),
-            70 => Ok(Self::M_EI_NA_1),
-            81 => Ok(Self::S_CH_NA_1),
-            82 => Ok(Self::S_RP_NA_1),
-            83 => Ok(Self::S_AR_NA_1),
-            84 => Ok(Self::S_KR_NA_1),
-            85 => Ok(Self::S_KS_NA_1),
-            86 => Ok(Self::S_KC_NA_1),
-            87 => Ok(Self::S_ER_NA_1),
-            90 => Ok(Self::S_US_NA_1),
-            91 => Ok(Self::S_UQ_NA_1),
-            92 => Ok(Self::S_UR_NA_1),
-            93 => Ok(Self::S_UK_NA_1),
-            94 => Ok(Self::S_UA_NA_1),
-            95 => Ok(Self::S_UC_NA_1),
-            100 => Ok(Self::C_IC_NA_1),
-            101 => Ok(Self::C_CI_NA_1),
-            102 => Ok(Self::C_RD_NA_1),
-            103 => Ok(Self::C_CS_NA_1),
-            104 => Ok(Self::C_TS_NA_1),
-            105 => Ok(Self::C_RP_NA_1),
-            106 => Ok(Self::C_CD_NA_1),
-            107 => Ok(Self::C_TS_TA_1),
-            110 => Ok(Self::P_ME_NA_1),
-            111 => Ok(Self::P_ME_NB_1),
-            112 => Ok(Self::P_ME_NC_1),
-            113 => Ok(Self::P_AC_NA_1),
-            120 => Ok(Self::F_FR_NA_1),
-            121 => Ok(Self::F_SR_NA_1),
-            122 => Ok(Self::F_SC_NA_1),
-            123 => Ok(Self::F_LS_NA_1),
-            124 => Ok(Self::F_AF_NA_1),
-            125 => Ok(Self::F_SG_NA_1),
-            126 => Ok(Self::F_DR_TA_1),
-            127 => Ok(Self::F_SC_NB_1),
-            _ => Err(anyhow!("Unknown TypeId: {}", value)),
+            1 => Self::M_SP_NA_1,
+            2 => Self::M_SP_TA_1,
+            3 => Self::M_DP_NA_1,
+            4 => Self::M_DP_TA_1,
+            5 => Self::M_ST_NA_1,
+            6 => Self::M_ST_TA_1,
+            7 => Self::M_BO_NA_1,
+            8 => Self::M_BO_TA_1,
+            9 => Self::M_ME_NA_1,
+            10 => Self::M_ME_TA_1,
+            11 => Self::M_ME_NB_1,
+            12 => Self::M_ME_TB_1,
+            13 => Self::M_ME_NC_1,
+            14 => Self::M_ME_TC_1,
+            15 => Self::M_IT_NA_1,
+            16 => Self::M_IT_TA_1,
+            17 => Self::M_EP_TA_1,
+            18 => Self::M_EP_TB_1,
+            19 => Self::M_EP_TC_1,
+            20 => Self::M_PS_NA_1,
+            21 => Self::M_ME_ND_1,
+            30 => Self::M_SP_TB_1,
+            31 => Self::M_DP_TB_1,
+            32 => Self::M_ST_TB_1,
+            33 => Self::M_BO_TB_1,
+            34 => Self::M_ME_TD_1,
+            35 => Self::M_ME_TE_1,
+            36 => Self::M_ME_TF_1,
+            37 => Self::M_IT_TB_1,
+            38 => Self::M_EP_TD_1,
+            39 => Self::M_EP_TE_1,
+            40 => Self::M_EP_TF_1,
+            41 => Self::S_IT_TC_1,
+            45 => Self::C_SC_NA_1,
+            46 => Self::C_DC_NA_1,
+            47 => Self::C_RC_NA_1,
+            48 => Self::C_SE_NA_1,
+            49 => Self::C_SE_NB_1,
+            50 => Self::C_SE_NC_1,
+            51 => Self::C_BO_NA_1,
+            58 => Self::C_SC_TA_1,
+            59 => Self::C_DC_TA_1,
+            60 => Self::C_RC_TA_1,
+            61 => Self::C_SE_TA_1,
+            62 => Self::C_SE_TB_1,
+            63 => Self::C_SE_TC_1,
+            64 => Self::C_BO_TA_1,
+            70 => Self::M_EI_NA_1,
+            81 => Self::S_CH_NA_1,
+            82 => Self::S_RP_NA_1,
+            83 => Self::S_AR_NA_1,
+            84 => Self::S_KR_NA_1,
+            85 => Self::S_KS_NA_1,
+            86 => Self::S_KC_NA_1,
+            87 => Self::S_ER_NA_1,
+            90 => Self::S_US_NA_1,
+            91 => Self::S_UQ_NA_1,
+            92 => Self::S_UR_NA_1,
+            93 => Self::S_UK_NA_1,
+            94 => Self::S_UA_NA_1,
+            95 => Self::S_UC_NA_1,
+            100 => Self::C_IC_NA_1,
+            101 => Self::C_CI_NA_1,
+            102 => Self::C_RD_NA_1,
+            103 => Self::C_CS_NA_1,
+            104 => Self::C_TS_NA_1,
+            105 => Self::C_RP_NA_1,
+            106 => Self::C_CD_NA_1,
+            107 => Self::C_TS_TA_1,
+            110 => Self::P_ME_NA_1,
+            111 => Self::P_ME_NB_1,
+            112 => Self::P_ME_NC_1,
+            113 => Self::P_AC_NA_1,
+            120 => Self::F_FR_NA_1,
+            121 => Self::F_SR_NA_1,
+            122 => Self::F_SC_NA_1,
+            123 => Self::F_LS_NA_1,
+            124 => Self::F_AF_NA_1,
+            125 => Self::F_SG_NA_1,
+            126 => Self::F_DR_TA_1,
+            127 => Self::F_SC_NB_1,
+            _ => Self::Unknown(value),
         }
     }
 }
 
+impl From<TypeID> for u8 {
+    fn from(type_id: TypeID) -> u8 {
+        match type_id {
+            TypeID::M_SP_NA_1 => 1,
+            TypeID::M_SP_TA_1 => 2,
+            TypeID::M_DP_NA_1 => 3,
+            TypeID::M_DP_TA_1 => 4,
+            TypeID::M_ST_NA_1 => 5,
+            TypeID::M_ST_TA_1 => 6,
+            TypeID::M_BO_NA_1 => 7,
+            TypeID::M_BO_TA_1 => 8,
+            TypeID::M_ME_NA_1 => 9,
+            TypeID::M_ME_TA_1 => 10,
+            TypeID::M_ME_NB_1 => 11,
+            TypeID::M_ME_TB_1 => 12,
+            TypeID::M_ME_NC_1 => 13,
+            TypeID::M_ME_TC_1 => 14,
+            TypeID::M_IT_NA_1 => 15,
+            TypeID::M_IT_TA_1 => 16,
+            TypeID::M_EP_TA_1 => 17,
+            TypeID::M_EP_TB_1 => 18,
+            TypeID::M_EP_TC_1 => 19,
+            TypeID::M_PS_NA_1 => 20,
+            TypeID::M_ME_ND_1 => 21,
+            TypeID::M_SP_TB_1 => 30,
+            TypeID::M_DP_TB_1 => 31,
+            TypeID::M_ST_TB_1 => 32,
+            TypeID::M_BO_TB_1 => 33,
+            TypeID::M_ME_TD_1 => 34,
+            TypeID::M_ME_TE_1 => 35,
+            TypeID::M_ME_TF_1 => 36,
+            TypeID::M_IT_TB_1 => 37,
+            TypeID::M_EP_TD_1 => 38,
+            TypeID::M_EP_TE_1 => 39,
+            TypeID::M_EP_TF_1 => 40,
+            TypeID::S_IT_TC_1 => 41,
+            TypeID::C_SC_NA_1 => 45,
+            TypeID::C_DC_NA_1 => 46,
+            TypeID::C_RC_NA_1 => 47,
+            TypeID::C_SE_NA_1 => 48,
+            TypeID::C_SE_NB_1 => 49,
+            TypeID::C_SE_NC_1 => 50,
+            TypeID::C_BO_NA_1 => 51,
+            TypeID::C_SC_TA_1 => 58,
+            TypeID::C_DC_TA_1 => 59,
+            TypeID::C_RC_TA_1 => 60,
+            TypeID::C_SE_TA_1 => 61,
+            TypeID::C_SE_TB_1 => 62,
+            TypeID::C_SE_TC_1 => 63,
+            TypeID::C_BO_TA_1 => 64,
+            TypeID::M_EI_NA_1 => 70,
+            TypeID::S_CH_NA_1 => 81,
+            TypeID::S_RP_NA_1 => 82,
+            TypeID::S_AR_NA_1 => 83,
+            TypeID::S_KR_NA_1 => 84,
+            TypeID::S_KS_NA_1 => 85,
+            TypeID::S_KC_NA_1 => 86,
+            TypeID::S_ER_NA_1 => 87,
+            TypeID::S_US_NA_1 => 90,
+            TypeID::S_UQ_NA_1 => 91,
+            TypeID::S_UR_NA_1 => 92,
+            TypeID::S_UK_NA_1 => 93,
+            TypeID::S_UA_NA_1 => 94,
+            TypeID::S_UC_NA_1 => 95,
+            TypeID::C_IC_NA_1 => 100,
+            TypeID::C_CI_NA_1 => 101,
+            TypeID::C_RD_NA_1 => 102,
+            TypeID::C_CS_NA_1 => 103,
+            TypeID::C_TS_NA_1 => 104,
+            TypeID::C_RP_NA_1 => 105,
+            TypeID::C_CD_NA_1 => 106,
+            TypeID::C_TS_TA_1 => 107,
+            TypeID::P_ME_NA_1 => 110,
+            TypeID::P_ME_NB_1 => 111,
+            TypeID::P_ME_NC_1 => 112,
+            TypeID::P_AC_NA_1 => 113,
+            TypeID::F_FR_NA_1 => 120,
+            TypeID::F_SR_NA_1 => 121,
+            TypeID::F_SC_NA_1 => 122,
+            TypeID::F_LS_NA_1 => 123,
+            TypeID::F_AF_NA_1 => 124,
+            TypeID::F_SG_NA_1 => 125,
+            TypeID::F_DR_TA_1 => 126,
+            TypeID::F_SC_NB_1 => 127,
+            TypeID::Unknown(value) => value,
+        }
+    }
+}
+
+impl TypeID {
+    // 不含信息对象地址的信息元素长度(含时标), 变长或尚未支持的类型返回 None
+    pub fn info_obj_size(self) -> Option<usize> {
+        let size = match self {
+            TypeID::M_SP_NA_1 | TypeID::M_DP_NA_1 => 1,
+            TypeID::M_SP_TA_1 | TypeID::M_DP_TA_1 => 4,
+            TypeID::M_SP_TB_1 | TypeID::M_DP_TB_1 => 8,
+            TypeID::M_ST_NA_1 => 2,
+            TypeID::M_ST_TA_1 => 5,
+            TypeID::M_ST_TB_1 => 9,
+            TypeID::M_BO_NA_1 => 5,
+            TypeID::M_BO_TA_1 => 8,
+            TypeID::M_BO_TB_1 => 12,
+            TypeID::M_ME_NA_1 | TypeID::M_ME_NB_1 => 3,
+            TypeID::M_ME_TA_1 | TypeID::M_ME_TB_1 => 6,
+            TypeID::M_ME_TD_1 | TypeID::M_ME_TE_1 => 10,
+            TypeID::M_ME_ND_1 => 2,
+            TypeID::M_ME_NC_1 | TypeID::M_IT_NA_1 | TypeID::M_PS_NA_1 => 5,
+            TypeID::M_ME_TC_1 | TypeID::M_IT_TA_1 => 8,
+            TypeID::M_ME_TF_1 | TypeID::M_IT_TB_1 => 12,
+            TypeID::M_EI_NA_1 => 1,
+            TypeID::C_SC_NA_1 | TypeID::C_DC_NA_1 | TypeID::C_RC_NA_1 => 1,
+            TypeID::C_SC_TA_1 | TypeID::C_DC_TA_1 | TypeID::C_RC_TA_1 => 8,
+            TypeID::C_SE_NA_1 | TypeID::C_SE_NB_1 => 3,
+            TypeID::C_SE_TA_1 | TypeID::C_SE_TB_1 => 10,
+            TypeID::C_SE_NC_1 => 5,
+            TypeID::C_SE_TC_1 => 12,
+            TypeID::C_BO_NA_1 => 4,
+            TypeID::C_BO_TA_1 => 11,
+            TypeID::C_IC_NA_1 | TypeID::C_CI_NA_1 | TypeID::C_RP_NA_1 => 1,
+            TypeID::C_RD_NA_1 => 0,
+            TypeID::C_CS_NA_1 => 7,
+            TypeID::C_TS_NA_1 | TypeID::C_CD_NA_1 => 2,
+            TypeID::C_TS_TA_1 => 9,
+            TypeID::P_ME_NA_1 | TypeID::P_ME_NB_1 => 3,
+            TypeID::P_ME_NC_1 => 5,
+            TypeID::P_AC_NA_1 => 1,
+            TypeID::F_FR_NA_1 => 6,
+            TypeID::F_SR_NA_1 => 7,
+            TypeID::F_SC_NA_1 => 4,
+            TypeID::F_LS_NA_1 => 5,
+            TypeID::F_AF_NA_1 => 4,
+            TypeID::F_DR_TA_1 => 13,
+            _ => return None,
+        };
+        Some(size)
+    }
+}
+
 // 信息对象地址 (IEC104)
 bit_struct! {
     pub struct InfoObjAddr(u24) {
@@ -351,12 +565,332 @@ bit_struct! {
 // InfoObjAddrIrrelevant Zero means that the information object address is irrelevant.
 pub const INFO_OBJ_ADDR_IRRELEVANT: u16 = 0;
 
+// 解码对端报文的各 get_* 方法与 Asdu::try_from 不 panic: 长度不足, 类型标识不匹配,
+// 地址溢出等均以 Err 返回, 以下为其共用的读取函数
+
+// 读取 3 字节的信息对象地址
+pub(crate) fn read_info_obj_addr<R: Read>(rdr: &mut R) -> io::Result<InfoObjAddr> {
+    let addr = rdr.read_u24::<LittleEndian>()?;
+    Ok(InfoObjAddr::new((addr >> 16) as u8, addr as u16))
+}
+
+// 顺序编码(SQ=1)时下一个信息对象的地址, 超出 65535 时返回 ErrIoaNotSequential
+pub(crate) fn next_info_obj_addr(mut ioa: InfoObjAddr) -> Result<InfoObjAddr, Error> {
+    let addr = ioa.addr().get();
+    let next = addr
+        .checked_add(1)
+        .ok_or(Error::ErrIoaNotSequential(addr))?;
+    ioa.addr().set(next);
+    Ok(ioa)
+}
+
+// 读取一个字节的品质描述词或限定词, 取值无效时返回 ErrInvalidFrame
+pub(crate) fn read_object<T, R>(rdr: &mut R) -> Result<T, Error>
+where
+    T: TryFrom<u8>,
+    R: Read,
+{
+    T::try_from(rdr.read_u8()?).map_err(|_| Error::ErrInvalidFrame)
+}
+
+// 经过校验的信息对象地址: 1 ~ 16777215, 或不超过 ioa_size 字节所能表示的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ioa(u32);
+
+impl Ioa {
+    pub fn new(addr: u32) -> Result<Self> {
+        Self::with_size(addr, AsduParams::IEC104.ioa_size)
+    }
+
+    // 按信息对象地址长度 ioa_size [1, 3] 校验
+    pub fn with_size(addr: u32, ioa_size: usize) -> Result<Self> {
+        if !(1..=3).contains(&ioa_size) {
+            return Err(anyhow!("invalid ioa size {ioa_size}"));
+        }
+        let max = (1u32 << (8 * ioa_size)) - 1;
+        if !(1..=max).contains(&addr) {
+            return Err(anyhow!(
+                "information object address {addr} out of range 1~{max}"
+            ));
+        }
+        Ok(Ioa(addr))
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for Ioa {
+    type Error = anyhow::Error;
+
+    fn try_from(addr: u32) -> Result<Self> {
+        Ioa::new(addr)
+    }
+}
+
+impl TryFrom<InfoObjAddr> for Ioa {
+    type Error = anyhow::Error;
+
+    fn try_from(ioa: InfoObjAddr) -> Result<Self> {
+        Ioa::new(ioa.raw().value())
+    }
+}
+
+impl From<Ioa> for InfoObjAddr {
+    fn from(ioa: Ioa) -> Self {
+        InfoObjAddr::try_from(u24::new(ioa.0).unwrap()).unwrap()
+    }
+}
+
+impl Display for Ioa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// 经过校验的站地址(公共地址): 不为 0(无效公共地址), 可以为全局地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StationAddr(CommonAddr);
+
+impl StationAddr {
+    pub const GLOBAL: StationAddr = StationAddr(GLOBAL_COMMON_ADDR);
+
+    pub fn new(ca: CommonAddr) -> Result<Self> {
+        if ca == INVALID_COMMON_ADDR {
+            return Err(anyhow!("invalid common address {ca}"));
+        }
+        Ok(StationAddr(ca))
+    }
+
+    pub fn get(&self) -> CommonAddr {
+        self.0
+    }
+}
+
+impl TryFrom<CommonAddr> for StationAddr {
+    type Error = anyhow::Error;
+
+    fn try_from(ca: CommonAddr) -> Result<Self> {
+        StationAddr::new(ca)
+    }
+}
+
+impl From<StationAddr> for CommonAddr {
+    fn from(ca: StationAddr) -> Self {
+        ca.0
+    }
+}
+
+impl Display for StationAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ASDU 构造函数的公共地址参数, 接受 CommonAddr(u16, 兼容原有的调用)与 StationAddr
+pub trait IntoCommonAddr {
+    fn into_common_addr(self) -> CommonAddr;
+}
+
+impl IntoCommonAddr for CommonAddr {
+    fn into_common_addr(self) -> CommonAddr {
+        self
+    }
+}
+
+impl IntoCommonAddr for StationAddr {
+    fn into_common_addr(self) -> CommonAddr {
+        self.0
+    }
+}
+
+// ASDU 构造函数的信息对象地址参数, 接受 u16(不超过 65535 的地址), InfoObjAddr 与 Ioa
+pub trait IntoInfoObjAddr {
+    fn into_info_obj_addr(self) -> InfoObjAddr;
+}
+
+impl IntoInfoObjAddr for u16 {
+    fn into_info_obj_addr(self) -> InfoObjAddr {
+        InfoObjAddr::new(0, self)
+    }
+}
+
+impl IntoInfoObjAddr for InfoObjAddr {
+    fn into_info_obj_addr(self) -> InfoObjAddr {
+        self
+    }
+}
+
+impl IntoInfoObjAddr for Ioa {
+    fn into_info_obj_addr(self) -> InfoObjAddr {
+        self.into()
+    }
+}
+
 impl Asdu {
+    // 按类型标识与可变结构限定词(SQ, N)校验信息体长度, 在逐个解析信息对象之前拒绝
+    // 声明的信息对象数目多于实际携带数据的 ASDU. 变长类型不校验
+    pub fn check_info_len(&self) -> Result<(), Error> {
+        let Some(elem_size) = self.identifier.type_id.info_obj_size() else {
+            return Ok(());
+        };
+        let info_num = self.identifier.variable_struct.number().get().value() as usize;
+        let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
+        let ioa_size = AsduParams::IEC104.ioa_size;
+        let expected = if is_seq {
+            ioa_size + info_num * elem_size
+        } else {
+            info_num * (ioa_size + elem_size)
+        };
+        if self.raw.len() < expected {
+            return Err(Error::ErrAsduTooShort(
+                self.identifier.type_id,
+                expected,
+                self.raw.len(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Asdu {
+    // 镜像响应, 保留请求的源发站地址
     pub fn mirror(&self, cause: Cause) -> Self {
         let mut asdu = self.clone();
         asdu.identifier.cot.cause().set(cause);
+        asdu.identifier.cot.set_negative(false);
         asdu
     }
+
+    // 否定的镜像响应(P/N = 1), 如不支持的命令值回复否定的激活确认
+    pub fn mirror_negative(&self, cause: Cause) -> Self {
+        let mut asdu = self.mirror(cause);
+        asdu.identifier.cot.set_negative(true);
+        asdu
+    }
+
+    // 设置源发站地址, 多个主站经同一前置机接入时用于区分响应的去向
+    pub fn with_orig_addr(mut self, orig_addr: OriginAddr) -> Self {
+        self.identifier.orig_addr = orig_addr;
+        self
+    }
+}
+
+impl Asdu {
+    // 按 params 指定的字段长度解码
+    pub fn from_bytes_with(bytes: Bytes, params: &AsduParams) -> Result<Self> {
+        if *params == AsduParams::IEC104 {
+            return Asdu::try_from(bytes);
+        }
+        params.valid()?;
+        let mut rdr = Cursor::new(&bytes);
+        let type_id = TypeID::from(rdr.read_u8()?);
+        let variable_struct = VariableStruct::try_from(rdr.read_u8()?)
+            .map_err(|_| anyhow!("Failed to parse variable struct"))?;
+        let cot = CauseOfTransmission::try_from(rdr.read_u8()?)
+            .map_err(|_| anyhow!("Failed to parse cot struct"))?;
+        let orig_addr = if params.cot_size == 2 {
+            rdr.read_u8()?
+        } else {
+            0
+        };
+        let common_addr = if params.ca_size == 2 {
+            rdr.read_u16::<byteorder::LittleEndian>()?
+        } else {
+            match rdr.read_u8()? {
+                0xff => GLOBAL_COMMON_ADDR,
+                ca => ca as u16,
+            }
+        };
+        let mut identifier = Identifier {
+            type_id,
+            variable_struct,
+            cot,
+            orig_addr,
+            common_addr,
+        };
+        let raw = convert_ioa_size(
+            &bytes[params.identifier_size()..],
+            &mut identifier.variable_struct,
+            params.ioa_size,
+            AsduParams::IEC104.ioa_size,
+        )?;
+
+        Ok(Asdu { identifier, raw })
+    }
+
+    // 按 params 指定的字段长度编码
+    pub fn to_bytes_with(self, params: &AsduParams) -> Result<Bytes> {
+        if *params == AsduParams::IEC104 {
+            return self.try_into();
+        }
+        params.valid()?;
+        let mut identifier = self.identifier;
+        let mut buf = BytesMut::with_capacity(ASDU_SIZE_MAX);
+        buf.put_u8(u8::from(identifier.type_id));
+        buf.put_u8(identifier.variable_struct.raw());
+        buf.put_u8(identifier.cot.raw());
+        if params.cot_size == 2 {
+            buf.put_u8(identifier.orig_addr);
+        }
+        if params.ca_size == 2 {
+            buf.put_u16_le(identifier.common_addr);
+        } else {
+            match identifier.common_addr {
+                GLOBAL_COMMON_ADDR => buf.put_u8(0xff),
+                ca if ca < 0xff => buf.put_u8(ca as u8),
+                ca => return Err(anyhow!("common address {ca} exceeds 1 byte")),
+            }
+        }
+        buf.extend(convert_ioa_size(
+            &self.raw,
+            &mut identifier.variable_struct,
+            AsduParams::IEC104.ioa_size,
+            params.ioa_size,
+        )?);
+
+        Ok(buf.freeze())
+    }
+}
+
+// 把信息对象地址的长度从 from 字节转换为 to 字节. 同一 ASDU 中各信息对象长度相同,
+// 非顺序编码时由总长度推算每个信息对象的长度
+fn convert_ioa_size(
+    raw: &[u8],
+    variable_struct: &mut VariableStruct,
+    from: usize,
+    to: usize,
+) -> Result<Bytes> {
+    let info_num = variable_struct.number().get().value() as usize;
+    let is_seq = variable_struct.is_sequence().get().value() != 0;
+    if from == to || info_num == 0 {
+        return Ok(Bytes::copy_from_slice(raw));
+    }
+    let addr_num = if is_seq { 1 } else { info_num };
+    if raw.len() < addr_num * from || (!is_seq && !raw.len().is_multiple_of(info_num)) {
+        return Err(anyhow!("invalid information object length {}", raw.len()));
+    }
+    let elem_size = if is_seq {
+        raw.len() - from
+    } else {
+        raw.len() / info_num - from
+    };
+
+    let mut buf = BytesMut::with_capacity(raw.len() + addr_num * to - addr_num * from);
+    for obj in raw.chunks(from + elem_size).take(addr_num) {
+        let mut ioa = [0u8; 4];
+        ioa[..from].copy_from_slice(&obj[..from]);
+        let ioa = u32::from_le_bytes(ioa);
+        if to < 4 && ioa >> (8 * to) != 0 {
+            return Err(anyhow!(
+                "information object address {ioa} exceeds {to} bytes"
+            ));
+        }
+        buf.extend_from_slice(&ioa.to_le_bytes()[..to]);
+        buf.extend_from_slice(&obj[from..]);
+    }
+    Ok(buf.freeze())
 }
 
 // 尝试把 Bytes 转换为 Asdu
@@ -374,7 +908,7 @@ impl TryFrom<Bytes> for Asdu {
         // 允许你使用多种方式读取数据。
         let mut rdr = Cursor::new(&bytes);
         // 尝试把 u8 转换为 TypeID
-        let type_id = TypeID::try_from(rdr.read_u8()?)?;
+        let type_id = TypeID::from(rdr.read_u8()?);
         // 尝试把 u8 转换为 VariableStruct
         let variable_struct = VariableStruct::try_from(rdr.read_u8()?)
             .map_err(|_| anyhow!("Failed to parse variable struct"))?;
@@ -407,7 +941,7 @@ impl TryInto<Bytes> for Asdu {
     fn try_into(self) -> Result<Bytes, Self::Error> {
         let mut buf = BytesMut::with_capacity(ASDU_SIZE_MAX);
 
-        buf.put_u8(self.identifier.type_id as u8);
+        buf.put_u8(u8::from(self.identifier.type_id));
         buf.put_u8(self.identifier.variable_struct.raw());
         buf.put_u8(self.identifier.cot.raw());
         buf.put_u8(self.identifier.orig_addr);
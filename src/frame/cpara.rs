@@ -0,0 +1,352 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use bit_struct::*;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use bytes::Bytes;
+
+use crate::error::Error;
+
+use super::asdu::{
+    read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission, CommonAddr, Identifier,
+    InfoObjAddr, TypeID, VariableStruct,
+};
+
+// 在控制方向参数的应用服务数据单元
+
+// 测量值参数, 规一化值
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterNormalInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 规一化值
+    pub nva: i16,
+    /// 测量值参数限定词
+    pub qpm: ObjectQPM,
+}
+
+impl ParameterNormalInfo {
+    pub fn new(addr: u16, v: i16, qpm: ObjectQPM) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        ParameterNormalInfo { ioa, nva: v, qpm }
+    }
+}
+
+// 测量值参数, 标度化值
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterScaledInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 标度化值
+    pub sva: i16,
+    /// 测量值参数限定词
+    pub qpm: ObjectQPM,
+}
+
+impl ParameterScaledInfo {
+    pub fn new(addr: u16, v: i16, qpm: ObjectQPM) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        ParameterScaledInfo { ioa, sva: v, qpm }
+    }
+}
+
+// 测量值参数, 短浮点数
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterFloatInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 短浮点数
+    pub r: f32,
+    /// 测量值参数限定词
+    pub qpm: ObjectQPM,
+}
+
+impl ParameterFloatInfo {
+    pub fn new(addr: u16, v: f32, qpm: ObjectQPM) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        ParameterFloatInfo { ioa, r: v, qpm }
+    }
+}
+
+// 参数激活
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterActivationInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 参数激活限定词
+    pub qpa: ObjectQPA,
+}
+
+impl ParameterActivationInfo {
+    pub fn new(addr: u16, qpa: ObjectQPA) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        ParameterActivationInfo { ioa, qpa }
+    }
+}
+
+// QPM - Qualifier of Parameter of Measured values(测量值参数限定词)
+// QPM := CP8 {KPA, LPC, POP}
+// KPA := UI6 [1...6] <0...63>
+//   <0> := 未用
+//   <1> := 门限值
+//   <2> := 平滑系数(滤波时间常数)
+//   <3> := 传送测量值的下限
+//   <4> := 传送测量值的上限
+//   <5...31> := 为本配套标准的标准定义保留（兼容范围）
+//   <32...63> := 为特定使用保留（专用范围）
+// LPC := BS1 [7] <0, 1>
+//   <0> := 未改变
+//   <1> := 改变
+// POP := BS1 [8] <0, 1>
+//   <0> := 运行
+//   <1> := 未运行
+bit_struct! {
+    pub struct ObjectQPM(u8) {
+        /// 参数在运行: 0: 运行, 1: 未运行
+        pop: bool,
+        /// 当地参数改变: 0: 未改变, 1: 改变
+        lpc: bool,
+        /// 参数类别: 1: 门限值, 2: 平滑系数, 3: 下限, 4: 上限
+        kpa: u6,
+    }
+}
+
+// QPA - Qualifier of Parameter Activation(参数激活限定词)
+// QPA := UI8 [1...8] <0...255>
+//   <0> := 未用
+//   <1> := 激活/停止激活这之前装载的参数(信息对象地址 = 0)
+//   <2> := 激活/停止激活所寻址信息对象的参数
+//   <3> := 激活/停止激活所寻址的持续循环或周期传输的信息对象
+//   <4...127> := 为本配套标准的标准定义保留（兼容范围）
+//   <128...255> := 为特定使用保留（专用范围）
+bit_struct! {
+    pub struct ObjectQPA(u8) {
+        qpa: u8,
+    }
+}
+
+// ParameterNormal send a type identification [P_ME_NA_1], 测量值参数, 规一化值, 只有单个信息对象(SQ = 0)
+// [P_ME_NA_1] See companion standard 101, subclass 7.3.5.1
+// 传送原因(coa)用于
+// 控制方向：
+// <6> := 激活
+// 监视方向：
+// <7> := 激活确认
+// <20> := 响应站召唤
+// <21> := 响应第1组召唤
+// 至
+// <36> := 响应第16组召唤
+// <44> := 未知的类型标识
+// <45> := 未知的传送原因
+// <46> := 未知的应用服务数据单元公共地址
+// <47> := 未知的信息对象地址
+pub fn parameter_normal(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    p: ParameterNormalInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+
+    if cause != Cause::Activation {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(p.ioa.raw().value())?;
+    buf.write_i16::<LittleEndian>(p.nva)?;
+    buf.write_u8(p.qpm.raw())?;
+
+    Ok(Asdu {
+        identifier: Identifier {
+            type_id: TypeID::P_ME_NA_1,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    })
+}
+
+// ParameterScaled send a type identification [P_ME_NB_1], 测量值参数, 标度化值, 只有单个信息对象(SQ = 0)
+// [P_ME_NB_1] See companion standard 101, subclass 7.3.5.2
+// 传送原因(coa)用于
+// 控制方向：
+// <6> := 激活
+// 监视方向：
+// <7> := 激活确认
+// <20> := 响应站召唤
+// <21> := 响应第1组召唤
+// 至
+// <36> := 响应第16组召唤
+// <44> := 未知的类型标识
+// <45> := 未知的传送原因
+// <46> := 未知的应用服务数据单元公共地址
+// <47> := 未知的信息对象地址
+pub fn parameter_scaled(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    p: ParameterScaledInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+
+    if cause != Cause::Activation {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(p.ioa.raw().value())?;
+    buf.write_i16::<LittleEndian>(p.sva)?;
+    buf.write_u8(p.qpm.raw())?;
+
+    Ok(Asdu {
+        identifier: Identifier {
+            type_id: TypeID::P_ME_NB_1,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    })
+}
+
+// ParameterFloat send a type identification [P_ME_NC_1], 测量值参数, 短浮点数, 只有单个信息对象(SQ = 0)
+// [P_ME_NC_1] See companion standard 101, subclass 7.3.5.3
+// 传送原因(coa)用于
+// 控制方向：
+// <6> := 激活
+// 监视方向：
+// <7> := 激活确认
+// <20> := 响应站召唤
+// <21> := 响应第1组召唤
+// 至
+// <36> := 响应第16组召唤
+// <44> := 未知的类型标识
+// <45> := 未知的传送原因
+// <46> := 未知的应用服务数据单元公共地址
+// <47> := 未知的信息对象地址
+pub fn parameter_float(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    p: ParameterFloatInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+
+    if cause != Cause::Activation {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(p.ioa.raw().value())?;
+    buf.write_f32::<LittleEndian>(p.r)?;
+    buf.write_u8(p.qpm.raw())?;
+
+    Ok(Asdu {
+        identifier: Identifier {
+            type_id: TypeID::P_ME_NC_1,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    })
+}
+
+// ParameterActivation send a type identification [P_AC_NA_1], 参数激活, 只有单个信息对象(SQ = 0)
+// [P_AC_NA_1] See companion standard 101, subclass 7.3.5.4
+// 传送原因(coa)用于
+// 控制方向：
+// <6> := 激活
+// <8> := 停止激活
+// 监视方向：
+// <7> := 激活确认
+// <9> := 停止激活确认
+// <44> := 未知的类型标识
+// <45> := 未知的传送原因
+// <46> := 未知的应用服务数据单元公共地址
+// <47> := 未知的信息对象地址
+pub fn parameter_activation(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    p: ParameterActivationInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+
+    if !(cause == Cause::Activation || cause == Cause::Deactivation) {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(p.ioa.raw().value())?;
+    buf.write_u8(p.qpa.raw())?;
+
+    Ok(Asdu {
+        identifier: Identifier {
+            type_id: TypeID::P_AC_NA_1,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    })
+}
+
+impl Asdu {
+    // [P_ME_NA_1] 获取测量值参数, 规一化值信息体
+    pub fn get_parameter_normal(&mut self) -> Result<ParameterNormalInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nva = rdr.read_i16::<LittleEndian>()?;
+        let qpm = read_object::<ObjectQPM, _>(&mut rdr)?;
+
+        Ok(ParameterNormalInfo { ioa, nva, qpm })
+    }
+
+    // [P_ME_NB_1] 获取测量值参数, 标度化值信息体
+    pub fn get_parameter_scaled(&mut self) -> Result<ParameterScaledInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let sva = rdr.read_i16::<LittleEndian>()?;
+        let qpm = read_object::<ObjectQPM, _>(&mut rdr)?;
+
+        Ok(ParameterScaledInfo { ioa, sva, qpm })
+    }
+
+    // [P_ME_NC_1] 获取测量值参数, 短浮点数信息体
+    pub fn get_parameter_float(&mut self) -> Result<ParameterFloatInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let r = rdr.read_f32::<LittleEndian>()?;
+        let qpm = read_object::<ObjectQPM, _>(&mut rdr)?;
+
+        Ok(ParameterFloatInfo { ioa, r, qpm })
+    }
+
+    // [P_AC_NA_1] 获取参数激活信息体
+    pub fn get_parameter_activation(&mut self) -> Result<ParameterActivationInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let qpa = read_object::<ObjectQPA, _>(&mut rdr)?;
+
+        Ok(ParameterActivationInfo { ioa, qpa })
+    }
+}
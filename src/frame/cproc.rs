@@ -1,4 +1,3 @@
-use core::panic;
 use std::io::Cursor;
 
 use anyhow::Result;
@@ -10,7 +9,10 @@ use chrono::{DateTime, Utc};
 use crate::{error::Error, frame::asdu::TypeID};
 
 use super::{
-    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, Identifier, InfoObjAddr, VariableStruct},
+    asdu::{
+        read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr,
+        IntoCommonAddr, VariableStruct,
+    },
     time::{cp56time2a, decode_cp56time2a},
 };
 
@@ -18,6 +20,7 @@ use super::{
 
 // 单命令
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SingleCommandInfo {
     /// 信息对象地址
     pub ioa: InfoObjAddr,
@@ -30,7 +33,7 @@ pub struct SingleCommandInfo {
 impl SingleCommandInfo {
     pub fn new(addr: u16, v: bool, se: bool) -> Self {
         let ioa = InfoObjAddr::new(0, addr);
-        let sco = ObjectSCO::new(v, u1!(0), u5!(0), se);
+        let sco = ObjectSCO::new(se, u5!(0), u1!(0), v);
         SingleCommandInfo {
             ioa,
             sco,
@@ -41,6 +44,7 @@ impl SingleCommandInfo {
 
 // 双命令
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoubleCommandInfo {
     /// 信息对象地址
     pub ioa: InfoObjAddr,
@@ -51,20 +55,35 @@ pub struct DoubleCommandInfo {
 }
 
 impl DoubleCommandInfo {
+    // v 取低 2 位, 不检查是否为允许的状态; 不允许的状态 0 与 3 在 double_cmd 编码时返回错误
     pub fn new(addr: u16, v: u8, se: bool) -> Self {
         let v = v % 4;
         let ioa = InfoObjAddr::new(0, addr);
-        let dco = ObjectDCO::new(u2::new(v).unwrap(), u5!(0), se);
+        let dco = ObjectDCO::new(se, u5!(0), u2::new(v).unwrap());
         DoubleCommandInfo {
             ioa,
             dco,
             time: None,
         }
     }
+
+    // 双命令状态只允许 1(分) 与 2(合), 其余返回 ErrInvalidDcs
+    pub fn try_new(addr: u16, v: u8, se: bool) -> Result<Self, Error> {
+        if !is_valid_dcs(v) {
+            return Err(Error::ErrInvalidDcs(v));
+        }
+        Ok(Self::new(addr, v, se))
+    }
+}
+
+// 双命令状态 DCS: 0 与 3 为不允许
+pub(crate) fn is_valid_dcs(v: u8) -> bool {
+    v == 1 || v == 2
 }
 
 // 设定命令, 规一化值
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetpointCommandNormalInfo {
     /// 信息对象地址
     pub ioa: InfoObjAddr,
@@ -79,7 +98,7 @@ pub struct SetpointCommandNormalInfo {
 impl SetpointCommandNormalInfo {
     pub fn new(addr: u16, v: i16) -> Self {
         let ioa = InfoObjAddr::new(0, addr);
-        let qos = ObjectQOS::new(u7!(0), u1!(0));
+        let qos = ObjectQOS::new(u1!(0), u7!(0));
         SetpointCommandNormalInfo {
             ioa,
             nva: v,
@@ -115,6 +134,7 @@ impl SetpointCommandNormalInfo {
 // |S/E|          QL               | QOS=设定命令品质限定词 (在 DL/T 634.5101 7.2.6.39 中定义) |
 // |    CP56Time2a (在 DL/T 634.5101 7.2.6.18 中定义) | 7 个八位位组的二进制时间               |
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetpointCommandScaledInfo {
     /// 信息对象地址
     pub ioa: InfoObjAddr,
@@ -129,7 +149,7 @@ pub struct SetpointCommandScaledInfo {
 impl SetpointCommandScaledInfo {
     pub fn new(addr: u16, v: i16) -> Self {
         let ioa = InfoObjAddr::new(0, addr);
-        let qos = ObjectQOS::new(u7!(0), u1!(0));
+        let qos = ObjectQOS::new(u1!(0), u7!(0));
         SetpointCommandScaledInfo {
             ioa,
             sva: v,
@@ -140,6 +160,8 @@ impl SetpointCommandScaledInfo {
 }
 
 // 设定命令, 短浮点数
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetpointCommandFloatInfo {
     pub ioa: InfoObjAddr,
     pub r: f32,
@@ -150,7 +172,7 @@ pub struct SetpointCommandFloatInfo {
 impl SetpointCommandFloatInfo {
     pub fn new(addr: u16, v: f32) -> Self {
         let ioa = InfoObjAddr::new(0, addr);
-        let qos = ObjectQOS::new(u7!(0), u1!(0));
+        let qos = ObjectQOS::new(u1!(0), u7!(0));
         SetpointCommandFloatInfo {
             ioa,
             r: v,
@@ -161,6 +183,8 @@ impl SetpointCommandFloatInfo {
 }
 
 // 比特串命令
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitsString32CommandInfo {
     pub ioa: InfoObjAddr,
     pub bcr: i32,
@@ -204,10 +228,10 @@ impl BitsString32CommandInfo {
 //   <1> := 选择
 bit_struct! {
     pub struct ObjectSCO(u8) {
-        scs: bool,  // 单命令状态
-        res: u1,    // 预留: 置 0
-        qu: u5,     // 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
         se: bool,   // 选择标志: 0: 执行, 1: 选择
+        qu: u5,     // 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
+        res: u1,    // 预留: 置 0
+        scs: bool,  // 单命令状态
     }
 }
 
@@ -237,12 +261,12 @@ bit_struct! {
 //   <1> := 选择
 bit_struct! {
     pub struct ObjectDCO(u8) {
-        /// 双命令状态
-        dcs: u2,
-        /// 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
-        qu: u5,
         /// 选择标志: 0:执行, 1:选择
         se: bool,
+        /// 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
+        qu: u5,
+        /// 双命令状态
+        dcs: u2,
     }
 }
 
@@ -258,12 +282,12 @@ bit_struct! {
 //   <1> := 选择
 bit_struct! {
     pub struct ObjectQOC(u8) {
-        /// 预留: 置 0
-        res: u2,
-        /// 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
-        qu: u5,
         /// 选择标志: 0: 执行, 1: 选择
         se: u1,
+        /// 输出方式: 0: 被控确定, 1: 短脉冲, 2: 长脉冲, 3: 持续脉冲
+        qu: u5,
+        /// 预留: 置 0
+        res: u2,
     }
 }
 
@@ -284,10 +308,10 @@ bit_struct! {
 //   <1> := 选择
 bit_struct! {
     pub struct ObjectQOS(u8) {
-        /// 0: 默认 1-63: 预留为标准定义 64-127:特殊使用
-        ql: u7,
         /// 选择标志: 0: 执行, 1: 选择
         se: u1,
+        /// 0: 默认 1-63: 预留为标准定义 64-127:特殊使用
+        ql: u7,
     }
 }
 
@@ -309,9 +333,10 @@ bit_struct! {
 pub fn single_cmd(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: SingleCommandInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -367,15 +392,21 @@ pub fn single_cmd(
 pub fn double_cmd(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: DoubleCommandInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
+    let mut cmd = cmd;
     let cause = cot.cause().get();
 
     if !(cause == Cause::Activation || cause == Cause::Deactivation) {
         return Err(Error::ErrCmdCause(cot));
     }
+    let dcs = cmd.dco.dcs().get().value();
+    if !is_valid_dcs(dcs) {
+        return Err(Error::ErrInvalidDcs(dcs));
+    }
 
     let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
 
@@ -486,9 +517,10 @@ pub fn double_cmd(
 pub fn set_point_cmd_normal(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: SetpointCommandNormalInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -545,9 +577,10 @@ pub fn set_point_cmd_normal(
 pub fn set_point_cmd_scaled(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: SetpointCommandScaledInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -604,9 +637,10 @@ pub fn set_point_cmd_scaled(
 pub fn set_point_cmd_float(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: SetpointCommandFloatInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -663,9 +697,10 @@ pub fn set_point_cmd_float(
 pub fn bits_string32_cmd(
     type_id: TypeID,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     cmd: BitsString32CommandInfo,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -707,15 +742,14 @@ impl Asdu {
     // [C_SC_NA_1] or [C_SC_TA_1] 获取单命令信息体
     pub fn get_single_cmd(&mut self) -> Result<SingleCommandInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
-        let sco = ObjectSCO::try_from(rdr.read_u8()?).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let sco = read_object::<ObjectSCO, _>(&mut rdr)?;
 
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_SC_NA_1 => (),
             TypeID::C_SC_TA_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
         Ok(SingleCommandInfo { ioa, sco, time })
     }
@@ -723,14 +757,13 @@ impl Asdu {
     // [C_DC_NA_1] or [C_DC_TA_1] 获取双命令信息体
     pub fn get_double_cmd(&mut self) -> Result<DoubleCommandInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
-        let dco = ObjectDCO::try_from(rdr.read_u8()?).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let dco = read_object::<ObjectDCO, _>(&mut rdr)?;
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_DC_NA_1 => (),
             TypeID::C_DC_TA_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
         Ok(DoubleCommandInfo { ioa, dco, time })
     }
@@ -738,16 +771,15 @@ impl Asdu {
     // GetSetpointNormalCmd [C_SE_NA_1] or [C_SE_TA_1] 获取设定命令,规一化值信息体
     pub fn get_setpoint_normal_cmd(&mut self) -> Result<SetpointCommandNormalInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
         let nva = rdr.read_i16::<LittleEndian>()?;
-        let qos = ObjectQOS::try_from(rdr.read_u8()?).unwrap();
+        let qos = read_object::<ObjectQOS, _>(&mut rdr)?;
 
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_SE_NA_1 => (),
             TypeID::C_SE_TA_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
 
         Ok(SetpointCommandNormalInfo {
@@ -761,16 +793,15 @@ impl Asdu {
     // [C_SE_NB_1] or [C_SE_TB_1] 获取设定命令,标度化值信息体
     pub fn get_setpoint_scaled_cmd(&mut self) -> Result<SetpointCommandScaledInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
         let sva = rdr.read_i16::<LittleEndian>()?;
-        let qos = ObjectQOS::try_from(rdr.read_u8()?).unwrap();
+        let qos = read_object::<ObjectQOS, _>(&mut rdr)?;
 
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_SE_NB_1 => (),
             TypeID::C_SE_TB_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
 
         Ok(SetpointCommandScaledInfo {
@@ -784,16 +815,15 @@ impl Asdu {
     // [C_SE_NC_1] or [C_SE_TC_1] 获取设定命令，短浮点数信息体
     pub fn get_setpoint_float_cmd(&mut self) -> Result<SetpointCommandFloatInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
         let r = rdr.read_f32::<LittleEndian>()?;
-        let qos = ObjectQOS::try_from(rdr.read_u8()?).unwrap();
+        let qos = read_object::<ObjectQOS, _>(&mut rdr)?;
 
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_SE_NC_1 => (),
             TypeID::C_SE_TC_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
 
         Ok(SetpointCommandFloatInfo { ioa, r, qos, time })
@@ -802,15 +832,14 @@ impl Asdu {
     // [C_BO_NA_1] or [C_BO_TA_1] 获取比特串命令信息体
     pub fn get_bits_string32_cmd(&mut self) -> Result<BitsString32CommandInfo> {
         let mut rdr = Cursor::new(&self.raw);
-        let ioa =
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap();
+        let ioa = read_info_obj_addr(&mut rdr)?;
         let bcr = rdr.read_i32::<LittleEndian>()?;
 
         let mut time = None;
         match self.identifier.type_id {
             TypeID::C_BO_NA_1 => (),
             TypeID::C_BO_TA_1 => time = decode_cp56time2a(&mut rdr)?,
-            _ => panic!("ErrTypeIDNotMatch"),
+            _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id).into()),
         }
 
         Ok(BitsString32CommandInfo { ioa, bcr, time })
@@ -10,16 +10,16 @@ use crate::error::Error;
 
 use super::{
     asdu::{
-        Asdu, Cause, CauseOfTransmission, CommonAddr, Identifier, InfoObjAddr, TypeID,
-        VariableStruct, INFO_OBJ_ADDR_IRRELEVANT,
+        read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr,
+        IntoCommonAddr, IntoInfoObjAddr, TypeID, VariableStruct, INFO_OBJ_ADDR_IRRELEVANT,
     },
-    time::{cp16time2a_from_msec, cp56time2a},
+    time::{cp16time2a_from_msec, cp56time2a, decode_cp56time2a, Cp56Time2a},
 };
 
 // 在控制方向系统信息的应用服务数据单元
 
 // FBPTestWord test special value
-const FBPTEST_WORD: u16 = 0x55aa;
+pub(crate) const FBPTEST_WORD: u16 = 0x55aa;
 
 pub type QualifierOfResetProcessCmd = u8;
 
@@ -44,6 +44,201 @@ bit_struct! {
     }
 }
 
+// 召唤限定词 QOI 的取值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Qoi {
+    /// 20: 站召唤(总召唤)
+    StationInterrogation,
+    /// 21~36: 第 1~16 组召唤
+    Group(u8),
+    /// 0~19, 37~255: 保留
+    Reserved(u8),
+}
+
+impl From<u8> for Qoi {
+    fn from(v: u8) -> Self {
+        match v {
+            20 => Qoi::StationInterrogation,
+            21..=36 => Qoi::Group(v - 20),
+            _ => Qoi::Reserved(v),
+        }
+    }
+}
+
+impl From<Qoi> for u8 {
+    fn from(qoi: Qoi) -> Self {
+        match qoi {
+            Qoi::StationInterrogation => 20,
+            Qoi::Group(n) => 20u8.saturating_add(n),
+            Qoi::Reserved(v) => v,
+        }
+    }
+}
+
+impl From<Qoi> for ObjectQOI {
+    fn from(qoi: Qoi) -> Self {
+        ObjectQOI::new(qoi.into())
+    }
+}
+
+impl From<ObjectQOI> for Qoi {
+    fn from(qoi: ObjectQOI) -> Self {
+        Qoi::from(qoi.raw())
+    }
+}
+
+// 计数量召唤限定词 QCC 的请求 RQT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterGroup {
+    /// 1~4: 请求第 1~4 组计数量
+    Group(u8),
+    /// 5: 总的请求计数量
+    General,
+    /// 0, 6~63: 保留
+    Reserved(u8),
+}
+
+impl From<u8> for CounterGroup {
+    fn from(v: u8) -> Self {
+        match v {
+            1..=4 => CounterGroup::Group(v),
+            5 => CounterGroup::General,
+            _ => CounterGroup::Reserved(v),
+        }
+    }
+}
+
+impl From<CounterGroup> for u8 {
+    fn from(group: CounterGroup) -> Self {
+        match group {
+            CounterGroup::Group(n) => n & 0x3f,
+            CounterGroup::General => 5,
+            CounterGroup::Reserved(v) => v & 0x3f,
+        }
+    }
+}
+
+// 计数量召唤限定词 QCC 的冻结/复位 FRZ
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FreezeMode {
+    /// 0: 读(无冻结或复位)
+    #[default]
+    Read,
+    /// 1: 计数量冻结不带复位
+    Freeze,
+    /// 2: 计数量冻结带复位
+    FreezeWithReset,
+    /// 3: 计数量复位
+    Reset,
+}
+
+impl From<u8> for FreezeMode {
+    fn from(v: u8) -> Self {
+        match v & 0x03 {
+            0 => FreezeMode::Read,
+            1 => FreezeMode::Freeze,
+            2 => FreezeMode::FreezeWithReset,
+            _ => FreezeMode::Reset,
+        }
+    }
+}
+
+impl From<FreezeMode> for u8 {
+    fn from(frz: FreezeMode) -> Self {
+        match frz {
+            FreezeMode::Read => 0,
+            FreezeMode::Freeze => 1,
+            FreezeMode::FreezeWithReset => 2,
+            FreezeMode::Reset => 3,
+        }
+    }
+}
+
+// 计数量召唤限定词 QCC := RQT(低 6 位) + FRZ(高 2 位)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Qcc {
+    pub request: CounterGroup,
+    pub freeze: FreezeMode,
+}
+
+impl Qcc {
+    pub fn new(request: CounterGroup, freeze: FreezeMode) -> Self {
+        Qcc { request, freeze }
+    }
+
+    // 读全部计数量
+    pub fn general() -> Self {
+        Qcc::new(CounterGroup::General, FreezeMode::Read)
+    }
+}
+
+impl From<u8> for Qcc {
+    fn from(v: u8) -> Self {
+        Qcc::new(CounterGroup::from(v & 0x3f), FreezeMode::from(v >> 6))
+    }
+}
+
+impl From<Qcc> for u8 {
+    fn from(qcc: Qcc) -> Self {
+        (u8::from(qcc.freeze) << 6) | u8::from(qcc.request)
+    }
+}
+
+impl From<Qcc> for ObjectQCC {
+    fn from(qcc: Qcc) -> Self {
+        ObjectQCC::new(qcc.into())
+    }
+}
+
+impl From<ObjectQCC> for Qcc {
+    fn from(qcc: ObjectQCC) -> Self {
+        Qcc::from(qcc.raw())
+    }
+}
+
+// 复位进程命令限定词 QRP 的取值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Qrp {
+    /// 1: 进程的总复位
+    ResetProcess,
+    /// 2: 复位事件缓冲区等待处理的带时标的信息
+    ResetEventBuffer,
+    /// 0, 3~255: 保留
+    Reserved(u8),
+}
+
+impl From<u8> for Qrp {
+    fn from(v: u8) -> Self {
+        match v {
+            1 => Qrp::ResetProcess,
+            2 => Qrp::ResetEventBuffer,
+            _ => Qrp::Reserved(v),
+        }
+    }
+}
+
+impl From<Qrp> for u8 {
+    fn from(qrp: Qrp) -> Self {
+        match qrp {
+            Qrp::ResetProcess => 1,
+            Qrp::ResetEventBuffer => 2,
+            Qrp::Reserved(v) => v,
+        }
+    }
+}
+
+impl From<Qrp> for ObjectQRP {
+    fn from(qrp: Qrp) -> Self {
+        ObjectQRP::new(qrp.into())
+    }
+}
+
+impl From<ObjectQRP> for Qrp {
+    fn from(qrp: ObjectQRP) -> Self {
+        Qrp::from(qrp.raw())
+    }
+}
+
 // InterrogationCmd send a new interrogation command [C_IC_NA_1]. 总召唤命令, 只有单个信息对象(SQ = 0)
 // [C_IC_NA_1] See companion standard 101, subclass 7.3.4.1
 // 传送原因(cot)用于
@@ -60,9 +255,11 @@ bit_struct! {
 // <47> := 未知的信息对象地址
 pub fn interrogation_cmd(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
-    qoi: ObjectQOI,
+    ca: impl IntoCommonAddr,
+    qoi: impl Into<ObjectQOI>,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let qoi = qoi.into();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -102,9 +299,11 @@ pub fn interrogation_cmd(
 // <47> := 未知的信息对象地址
 pub fn counter_interrogation_cmd(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
-    qcc: ObjectQCC,
+    ca: impl IntoCommonAddr,
+    qcc: impl Into<ObjectQCC>,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let qcc = qcc.into();
     let mut cot = cot;
     cot.cause().set(Cause::Activation);
 
@@ -136,7 +335,13 @@ pub fn counter_interrogation_cmd(
 // <45> := 未知的传送原因
 // <46> := 未知的应用服务数据单元公共地址
 // <47> := 未知的信息对象地址
-pub fn read_cmd(cot: CauseOfTransmission, ca: CommonAddr, ioa: InfoObjAddr) -> Result<Asdu, Error> {
+pub fn read_cmd(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    ioa: impl IntoInfoObjAddr,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let ioa = ioa.into_info_obj_addr();
     let mut cot = cot;
     cot.cause().set(Cause::Request);
 
@@ -171,9 +376,19 @@ pub fn read_cmd(cot: CauseOfTransmission, ca: CommonAddr, ioa: InfoObjAddr) -> R
 // <47> := 未知的信息对象地址
 pub fn clock_synchronization_cmd(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     time: DateTime<Utc>,
 ) -> Result<Asdu, Error> {
+    clock_synchronization_cmd_cp56(cot, ca, Cp56Time2a::new(time))
+}
+
+// 同 clock_synchronization_cmd, 时标带无效(IV)与夏季时间(SU)品质位
+pub fn clock_synchronization_cmd_cp56(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    time: Cp56Time2a,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     cot.cause().set(Cause::Activation);
 
@@ -181,7 +396,7 @@ pub fn clock_synchronization_cmd(
 
     let mut buf = vec![];
     buf.write_u24::<LittleEndian>(InfoObjAddr::new(0, INFO_OBJ_ADDR_IRRELEVANT).raw().value())?;
-    buf.extend_from_slice(&cp56time2a(time));
+    buf.extend_from_slice(&time.encode());
 
     Ok(Asdu {
         identifier: Identifier {
@@ -206,7 +421,8 @@ pub fn clock_synchronization_cmd(
 // <45> := 未知的传送原因
 // <46> := 未知的应用服务数据单元公共地址
 // <47> := 未知的信息对象地址
-pub fn test_command(cot: CauseOfTransmission, ca: CommonAddr) -> Result<Asdu, Error> {
+pub fn test_command(cot: CauseOfTransmission, ca: impl IntoCommonAddr) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     cot.cause().set(Cause::Activation);
 
@@ -241,9 +457,11 @@ pub fn test_command(cot: CauseOfTransmission, ca: CommonAddr) -> Result<Asdu, Er
 // <47> := 未知的信息对象地址
 pub fn reset_process_cmd(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
-    qrp: QualifierOfResetProcessCmd,
+    ca: impl IntoCommonAddr,
+    qrp: impl Into<ObjectQRP>,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let qrp = qrp.into();
     let mut cot = cot;
     cot.cause().set(Cause::Activation);
 
@@ -251,7 +469,7 @@ pub fn reset_process_cmd(
 
     let mut buf = vec![];
     buf.write_u24::<LittleEndian>(InfoObjAddr::new(0, INFO_OBJ_ADDR_IRRELEVANT).raw().value())?;
-    buf.write_u8(qrp)?;
+    buf.write_u8(qrp.raw())?;
 
     Ok(Asdu {
         identifier: Identifier {
@@ -279,9 +497,10 @@ pub fn reset_process_cmd(
 // <47> := 未知的信息对象地址
 pub fn delay_acquire_command(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     msec: u16,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     let cause = cot.cause().get();
 
@@ -319,9 +538,10 @@ pub fn delay_acquire_command(
 // <47> := 未知的信息对象地址
 pub fn test_command_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     time: DateTime<Utc>,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
     let mut cot = cot;
     cot.cause().set(Cause::Activation);
     let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
@@ -333,7 +553,7 @@ pub fn test_command_cp56time2a(
 
     Ok(Asdu {
         identifier: Identifier {
-            type_id: TypeID::C_CD_NA_1,
+            type_id: TypeID::C_TS_TA_1,
             variable_struct,
             cot,
             orig_addr: 0,
@@ -348,8 +568,8 @@ impl Asdu {
     pub fn get_interrogation_cmd(&mut self) -> Result<(InfoObjAddr, ObjectQOI)> {
         let mut rdr = Cursor::new(&self.raw);
         Ok((
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap(),
-            ObjectQOI::try_from(rdr.read_u8()?).unwrap(),
+            read_info_obj_addr(&mut rdr)?,
+            read_object::<ObjectQOI, _>(&mut rdr)?,
         ))
     }
 
@@ -357,8 +577,56 @@ impl Asdu {
     pub fn get_counter_interrogation_cmd(&mut self) -> Result<(InfoObjAddr, ObjectQCC)> {
         let mut rdr = Cursor::new(&self.raw);
         Ok((
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap(),
-            ObjectQCC::try_from(rdr.read_u8()?).unwrap(),
+            read_info_obj_addr(&mut rdr)?,
+            read_object::<ObjectQCC, _>(&mut rdr)?,
+        ))
+    }
+
+    // GetReadCmd [C_RD_NA_1] 获得读命令信息地址
+    pub fn get_read_cmd(&mut self) -> Result<InfoObjAddr> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok(read_info_obj_addr(&mut rdr)?)
+    }
+
+    // GetClockSynchronizationCmd [C_CS_NA_1] 获得时钟同步命令信息体(信息对象地址,时间)
+    pub fn get_clock_synchronization_cmd(
+        &mut self,
+    ) -> Result<(InfoObjAddr, Option<DateTime<Utc>>)> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok((read_info_obj_addr(&mut rdr)?, decode_cp56time2a(&mut rdr)?))
+    }
+
+    // 同 get_clock_synchronization_cmd, 时标保留毫秒与品质位
+    pub fn get_clock_synchronization_time(&mut self) -> Result<(InfoObjAddr, Option<Cp56Time2a>)> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok((read_info_obj_addr(&mut rdr)?, Cp56Time2a::decode(&mut rdr)?))
+    }
+
+    // GetDelayAcquireCommand [C_CD_NA_1] 获取延时获得命令信息体(信息对象地址,延时毫秒数)
+    pub fn get_delay_acquire_cmd(&mut self) -> Result<(InfoObjAddr, u16)> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok((
+            read_info_obj_addr(&mut rdr)?,
+            rdr.read_u16::<LittleEndian>()?,
+        ))
+    }
+
+    // GetTestCmd [C_TS_NA_1] 获得测试命令信息体(信息对象地址,测试字)
+    pub fn get_test_cmd(&mut self) -> Result<(InfoObjAddr, u16)> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok((
+            read_info_obj_addr(&mut rdr)?,
+            rdr.read_u16::<LittleEndian>()?,
+        ))
+    }
+
+    // GetTestCmdCP56Time2a [C_TS_TA_1] 获得带时标的测试命令信息体(信息对象地址,测试字,时标)
+    pub fn get_test_cmd_cp56time2a(&mut self) -> Result<(InfoObjAddr, u16, Option<Cp56Time2a>)> {
+        let mut rdr = Cursor::new(&self.raw);
+        Ok((
+            read_info_obj_addr(&mut rdr)?,
+            rdr.read_u16::<LittleEndian>()?,
+            Cp56Time2a::decode(&mut rdr)?,
         ))
     }
 
@@ -366,8 +634,8 @@ impl Asdu {
     pub fn get_reset_process_cmd(&mut self) -> Result<(InfoObjAddr, ObjectQRP)> {
         let mut rdr = Cursor::new(&self.raw);
         Ok((
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap(),
-            ObjectQRP::try_from(rdr.read_u8()?).unwrap(),
+            read_info_obj_addr(&mut rdr)?,
+            read_object::<ObjectQRP, _>(&mut rdr)?,
         ))
     }
 }
@@ -0,0 +1,358 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    asdu::{Asdu, Cause, InfoObjAddr},
+    mproc::ObjectBCR,
+    payload::AsduPayload,
+};
+use crate::Quality;
+
+impl Asdu {
+    // Describe 输出类似协议分析软件的一行文本, 例如
+    // M_ME_NC_1 COT=spont CA=1 IOA=4005 val=12.5 QDS=GOOD t=2024-02-01T08:00:00.000Z
+    // 多个信息对象以 "; " 分隔, 无法解析的信息体以十六进制输出
+    pub fn describe(&self) -> String {
+        let mut id = self.identifier;
+        let mut s = format!("{:?} COT={}", id.type_id, cause_name(id.cot.cause().get()));
+        if id.cot.is_negative() {
+            s.push_str(" neg");
+        }
+        if id.cot.test().get() {
+            s.push_str(" test");
+        }
+        if id.orig_addr != 0 {
+            let _ = write!(s, " OA={}", id.orig_addr);
+        }
+        let _ = write!(s, " CA={}", id.common_addr);
+
+        let objects = match self.clone().decode_payload() {
+            Ok(AsduPayload::Unknown(_)) | Err(_) => vec![format!("raw={}", hex(&self.raw))],
+            Ok(payload) => describe_payload(payload),
+        };
+        for (i, object) in objects.iter().enumerate() {
+            s.push_str(if i == 0 { " " } else { "; " });
+            s.push_str(object);
+        }
+        s
+    }
+}
+
+fn describe_payload(payload: AsduPayload) -> Vec<String> {
+    match payload {
+        AsduPayload::SinglePoint(infos) => infos
+            .into_iter()
+            .map(|mut i| {
+                let val = if i.siq.spi().get() { "on" } else { "off" };
+                let s = format!("{} val={val} SIQ={}", ioa(i.ioa), quality(i.siq));
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::DoublePoint(infos) => infos
+            .into_iter()
+            .map(|mut i| {
+                let val = double_name(i.diq.spi().get().value());
+                let s = format!("{} val={val} DIQ={}", ioa(i.ioa), quality(i.diq));
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::MeasuredNormal(infos) => infos
+            .into_iter()
+            .map(|i| {
+                let mut s = format!("{} val={}", ioa(i.ioa), i.nva);
+                // M_ME_ND_1 不带品质描述词
+                if let Some(qds) = i.qds {
+                    let _ = write!(s, " QDS={}", quality(qds));
+                }
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::MeasuredScaled(infos) => infos
+            .into_iter()
+            .map(|i| {
+                let s = format!("{} val={} QDS={}", ioa(i.ioa), i.sva, quality(i.qds));
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::MeasuredFloat(infos) => infos
+            .into_iter()
+            .map(|i| {
+                let s = format!("{} val={} QDS={}", ioa(i.ioa), i.r, quality(i.qds));
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::IntegratedTotals(infos) => infos
+            .into_iter()
+            .map(|i| {
+                let s = format!("{} val={} {}", ioa(i.ioa), i.bcr.value, bcr_flags(&i.bcr));
+                with_time(s, i.time)
+            })
+            .collect(),
+        AsduPayload::PackedSinglePoint(infos) => infos
+            .into_iter()
+            .map(|mut i| {
+                format!(
+                    "{} val={:#06x} chg={:#06x} QDS={}",
+                    ioa(i.ioa),
+                    i.scd.spi().get(),
+                    i.scd.vflag().get(),
+                    quality(i.qds)
+                )
+            })
+            .collect(),
+
+        AsduPayload::SingleCommand(mut i) => {
+            let val = if i.sco.scs().get() { "on" } else { "off" };
+            let s = format!(
+                "{} val={val} {} QU={}",
+                ioa(i.ioa),
+                select_name(i.sco.se().get()),
+                i.sco.qu().get()
+            );
+            vec![with_time(s, i.time)]
+        }
+        AsduPayload::DoubleCommand(mut i) => {
+            let val = double_name(i.dco.dcs().get().value());
+            let s = format!(
+                "{} val={val} {} QU={}",
+                ioa(i.ioa),
+                select_name(i.dco.se().get()),
+                i.dco.qu().get()
+            );
+            vec![with_time(s, i.time)]
+        }
+        AsduPayload::SetpointNormal(mut i) => {
+            let s = format!(
+                "{} val={} {} QL={}",
+                ioa(i.ioa),
+                i.nva,
+                select_name(i.qos.se().get().value() == 1),
+                i.qos.ql().get()
+            );
+            vec![with_time(s, i.time)]
+        }
+        AsduPayload::SetpointScaled(mut i) => {
+            let s = format!(
+                "{} val={} {} QL={}",
+                ioa(i.ioa),
+                i.sva,
+                select_name(i.qos.se().get().value() == 1),
+                i.qos.ql().get()
+            );
+            vec![with_time(s, i.time)]
+        }
+        AsduPayload::SetpointFloat(mut i) => {
+            let s = format!(
+                "{} val={} {} QL={}",
+                ioa(i.ioa),
+                i.r,
+                select_name(i.qos.se().get().value() == 1),
+                i.qos.ql().get()
+            );
+            vec![with_time(s, i.time)]
+        }
+        AsduPayload::BitsString32(i) => {
+            let s = format!("{} val={:#010x}", ioa(i.ioa), i.bcr);
+            vec![with_time(s, i.time)]
+        }
+
+        AsduPayload::Interrogation(mut qoi) => vec![format!("QOI={}", qoi.range().get())],
+        AsduPayload::CounterInterrogation(mut qcc) => {
+            let qcc = qcc.qcc().get();
+            vec![format!("RQT={} FRZ={}", qcc & 0x3f, qcc >> 6)]
+        }
+        AsduPayload::Read(addr) => vec![ioa(addr)],
+        AsduPayload::ClockSync(time) => vec![match time {
+            Some(t) => format!("t={}", timestamp(t)),
+            None => "t=invalid".to_string(),
+        }],
+        AsduPayload::ResetProcess(mut qrp) => vec![format!("QRP={}", qrp.qrp().get())],
+        AsduPayload::DelayAcquire(ms) => vec![format!("delay={ms}ms")],
+
+        AsduPayload::ParameterNormal(mut i) => vec![format!(
+            "{} val={} KPA={}",
+            ioa(i.ioa),
+            i.nva,
+            i.qpm.kpa().get()
+        )],
+        AsduPayload::ParameterScaled(mut i) => vec![format!(
+            "{} val={} KPA={}",
+            ioa(i.ioa),
+            i.sva,
+            i.qpm.kpa().get()
+        )],
+        AsduPayload::ParameterFloat(mut i) => vec![format!(
+            "{} val={} KPA={}",
+            ioa(i.ioa),
+            i.r,
+            i.qpm.kpa().get()
+        )],
+        AsduPayload::ParameterActivation(mut i) => {
+            vec![format!("{} QPA={}", ioa(i.ioa), i.qpa.qpa().get())]
+        }
+
+        AsduPayload::FileReady(i) => vec![format!("{} NOF={} LOF={}", ioa(i.ioa), i.nof, i.lof)],
+        AsduPayload::SectionReady(i) => vec![format!(
+            "{} NOF={} NOS={} LOF={}",
+            ioa(i.ioa),
+            i.nof,
+            i.nos,
+            i.lof
+        )],
+        AsduPayload::FileCall(mut i) => vec![format!(
+            "{} NOF={} NOS={} SCQ={}",
+            ioa(i.ioa),
+            i.nof,
+            i.nos,
+            i.scq.scq().get()
+        )],
+        AsduPayload::LastSection(mut i) => vec![format!(
+            "{} NOF={} NOS={} LSQ={} CHS={:#04x}",
+            ioa(i.ioa),
+            i.nof,
+            i.nos,
+            i.lsq.lsq().get(),
+            i.chs
+        )],
+        AsduPayload::FileAck(mut i) => vec![format!(
+            "{} NOF={} NOS={} AFQ={}",
+            ioa(i.ioa),
+            i.nof,
+            i.nos,
+            i.afq.afq().get()
+        )],
+        AsduPayload::Segment(i) => vec![format!(
+            "{} NOF={} NOS={} len={}",
+            ioa(i.ioa),
+            i.nof,
+            i.nos,
+            i.segment.len()
+        )],
+        AsduPayload::Directory(infos) => infos
+            .into_iter()
+            .map(|i| {
+                let s = format!("{} NOF={} LOF={}", ioa(i.ioa), i.nof, i.lof);
+                with_time(s, i.time)
+            })
+            .collect(),
+
+        AsduPayload::Unknown(raw) => vec![format!("raw={}", hex(&raw))],
+    }
+}
+
+fn ioa(ioa: InfoObjAddr) -> String {
+    format!("IOA={}", ioa.raw().value())
+}
+
+fn with_time(mut s: String, time: Option<DateTime<Utc>>) -> String {
+    if let Some(t) = time {
+        let _ = write!(s, " t={}", timestamp(t));
+    }
+    s
+}
+
+fn timestamp(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+fn hex(raw: &[u8]) -> String {
+    raw.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+// 品质描述词: 全部未置位为 GOOD, 否则列出置位的 IV, NT, SB, BL, OV
+fn quality(q: impl Into<Quality>) -> String {
+    let q: Quality = q.into();
+    if q.is_good() {
+        return "GOOD".to_string();
+    }
+    [
+        (q.invalid, "IV"),
+        (q.nt, "NT"),
+        (q.sb, "SB"),
+        (q.bl, "BL"),
+        (q.ov, "OV"),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join("|")
+}
+
+fn bcr_flags(bcr: &ObjectBCR) -> String {
+    let mut s = format!("SQ={}", bcr.seq);
+    for (set, name) in [(bcr.cy, "CY"), (bcr.ca, "CA"), (bcr.invalid, "IV")] {
+        if set {
+            let _ = write!(s, " {name}");
+        }
+    }
+    s
+}
+
+fn double_name(v: u8) -> &'static str {
+    match v {
+        1 => "off",
+        2 => "on",
+        _ => "indeterminate",
+    }
+}
+
+fn select_name(select: bool) -> &'static str {
+    if select {
+        "select"
+    } else {
+        "execute"
+    }
+}
+
+// 传送原因的缩写, 与常用的协议分析软件一致
+fn cause_name(cause: Cause) -> &'static str {
+    match cause {
+        Cause::Unused => "unused",
+        Cause::Periodic => "per/cyc",
+        Cause::Background => "back",
+        Cause::Spontaneous => "spont",
+        Cause::Initialized => "init",
+        Cause::Request => "req",
+        Cause::Activation => "act",
+        Cause::ActivationCon => "actcon",
+        Cause::Deactivation => "deact",
+        Cause::DeactivationCon => "deactcon",
+        Cause::ActivationTerm => "actterm",
+        Cause::ReturnInfoRemote => "retrem",
+        Cause::ReturnInfoLocal => "retloc",
+        Cause::FileTransfer => "file",
+        Cause::Authentication => "auth",
+        Cause::SessionKey => "seskey",
+        Cause::UserRoleAndUpdateKey => "usrkey",
+        Cause::Reserved1 | Cause::Reserved2 | Cause::Reserved3 => "reserved",
+        Cause::InterrogatedByStation => "inrogen",
+        Cause::InterrogatedByGroup1 => "inro1",
+        Cause::InterrogatedByGroup2 => "inro2",
+        Cause::InterrogatedByGroup3 => "inro3",
+        Cause::InterrogatedByGroup4 => "inro4",
+        Cause::InterrogatedByGroup5 => "inro5",
+        Cause::InterrogatedByGroup6 => "inro6",
+        Cause::InterrogatedByGroup7 => "inro7",
+        Cause::InterrogatedByGroup8 => "inro8",
+        Cause::InterrogatedByGroup9 => "inro9",
+        Cause::InterrogatedByGroup10 => "inro10",
+        Cause::InterrogatedByGroup11 => "inro11",
+        Cause::InterrogatedByGroup12 => "inro12",
+        Cause::InterrogatedByGroup13 => "inro13",
+        Cause::InterrogatedByGroup14 => "inro14",
+        Cause::InterrogatedByGroup15 => "inro15",
+        Cause::InterrogatedByGroup16 => "inro16",
+        Cause::RequestByGeneralCounter => "reqcogen",
+        Cause::RequestByGroup1Counter => "reqco1",
+        Cause::RequestByGroup2Counter => "reqco2",
+        Cause::RequestByGroup3Counter => "reqco3",
+        Cause::RequestByGroup4Counter => "reqco4",
+        Cause::Reserved4 | Cause::Reserved5 => "reserved",
+        Cause::UnknownTypeID => "unknown_type",
+        Cause::UnknownCOT => "unknown_cause",
+        Cause::UnknownCA => "unknown_ca",
+        Cause::UnknownIOA => "unknown_ioa",
+    }
+}
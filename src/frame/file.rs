@@ -0,0 +1,671 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use bit_struct::*;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use bytes::{Buf, Bytes};
+use chrono::{DateTime, Utc};
+
+use crate::error::Error;
+
+use super::{
+    asdu::{
+        next_info_obj_addr, read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission,
+        CommonAddr, Identifier, InfoObjAddr, TypeID, VariableStruct, ASDU_SIZE_MAX,
+        IDENTIFIER_SIZE,
+    },
+    time::{cp56time2a, decode_cp56time2a},
+};
+
+// 文件传输的应用服务数据单元
+
+// 段的最大长度: ASDU 最大长度 - 数据单元标识 - 信息对象地址(3) - NOF(2) - NOS(1) - LOS(1)
+pub const SEGMENT_SIZE_MAX: usize = ASDU_SIZE_MAX - IDENTIFIER_SIZE - 3 - 2 - 1 - 1;
+
+// SCQ 选择和召唤限定词 UI4 [1...4]
+pub const SCQ_DEFAULT: u8 = 0; // 缺省
+pub const SCQ_SELECT_FILE: u8 = 1; // 选择文件
+pub const SCQ_REQUEST_FILE: u8 = 2; // 请求文件
+pub const SCQ_DEACTIVATE_FILE: u8 = 3; // 停止激活文件
+pub const SCQ_DELETE_FILE: u8 = 4; // 删除文件
+pub const SCQ_SELECT_SECTION: u8 = 5; // 选择节
+pub const SCQ_REQUEST_SECTION: u8 = 6; // 请求节
+pub const SCQ_DEACTIVATE_SECTION: u8 = 7; // 停止激活节
+
+// LSQ 最后的节和段的限定词
+pub const LSQ_FILE_TRANSFER_WITHOUT_DEACT: u8 = 1; // 不带停止激活的文件传输
+pub const LSQ_FILE_TRANSFER_WITH_DEACT: u8 = 2; // 带停止激活的文件传输
+pub const LSQ_SECTION_TRANSFER_WITHOUT_DEACT: u8 = 3; // 不带停止激活的节传输
+pub const LSQ_SECTION_TRANSFER_WITH_DEACT: u8 = 4; // 带停止激活的节传输
+
+// AFQ 文件认可或节认可限定词 UI4 [1...4]
+pub const AFQ_POSITIVE_FILE: u8 = 1; // 文件传输的肯定认可
+pub const AFQ_NEGATIVE_FILE: u8 = 2; // 文件传输的否定认可
+pub const AFQ_POSITIVE_SECTION: u8 = 3; // 节传输的肯定认可
+pub const AFQ_NEGATIVE_SECTION: u8 = 4; // 节传输的否定认可
+
+// 文件已准备好
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileReadyInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 文件长度
+    pub lof: u32,
+    /// 文件准备就绪限定词
+    pub frq: ObjectFRQ,
+}
+
+impl FileReadyInfo {
+    pub fn new(addr: u16, nof: u16, lof: u32, negative: bool) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        let frq = ObjectFRQ::new(negative, u7!(0));
+        FileReadyInfo { ioa, nof, lof, frq }
+    }
+}
+
+// 节已准备好
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionReadyInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 节名称
+    pub nos: u8,
+    /// 节长度
+    pub lof: u32,
+    /// 节准备就绪限定词
+    pub srq: ObjectSRQ,
+}
+
+impl SectionReadyInfo {
+    pub fn new(addr: u16, nof: u16, nos: u8, lof: u32, not_ready: bool) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        let srq = ObjectSRQ::new(not_ready, u7!(0));
+        SectionReadyInfo {
+            ioa,
+            nof,
+            nos,
+            lof,
+            srq,
+        }
+    }
+}
+
+// 召唤目录, 选择文件, 召唤文件, 召唤节
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileCallInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 节名称
+    pub nos: u8,
+    /// 选择和召唤限定词
+    pub scq: ObjectSCQ,
+}
+
+impl FileCallInfo {
+    pub fn new(addr: u16, nof: u16, nos: u8, scq: u8) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        let scq = ObjectSCQ::new(u4!(0), u4::new(scq % 16).unwrap());
+        FileCallInfo { ioa, nof, nos, scq }
+    }
+}
+
+// 最后的节, 最后的段
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LastSectionInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 节名称
+    pub nos: u8,
+    /// 最后的节和段的限定词
+    pub lsq: ObjectLSQ,
+    /// 校验和
+    pub chs: u8,
+}
+
+impl LastSectionInfo {
+    pub fn new(addr: u16, nof: u16, nos: u8, lsq: u8, chs: u8) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        LastSectionInfo {
+            ioa,
+            nof,
+            nos,
+            lsq: ObjectLSQ::new(lsq),
+            chs,
+        }
+    }
+}
+
+// 确认文件, 确认节
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileAckInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 节名称
+    pub nos: u8,
+    /// 文件认可或节认可限定词
+    pub afq: ObjectAFQ,
+}
+
+impl FileAckInfo {
+    pub fn new(addr: u16, nof: u16, nos: u8, afq: u8) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        let afq = ObjectAFQ::new(u4!(0), u4::new(afq % 16).unwrap());
+        FileAckInfo { ioa, nof, nos, afq }
+    }
+}
+
+// 段
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 节名称
+    pub nos: u8,
+    /// 段数据, 长度即 LOS
+    pub segment: Bytes,
+}
+
+impl SegmentInfo {
+    pub fn new(addr: u16, nof: u16, nos: u8, segment: Bytes) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        SegmentInfo {
+            ioa,
+            nof,
+            nos,
+            segment,
+        }
+    }
+}
+
+// 目录
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectoryInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 文件名称
+    pub nof: u16,
+    /// 文件长度
+    pub lof: u32,
+    /// 文件状态
+    pub sof: ObjectSOF,
+    /// 文件创建时间
+    pub time: Option<DateTime<Utc>>,
+}
+
+// FRQ - File Ready Qualifier(文件准备就绪限定词)
+// FRQ := CP8 {UI7, BS1}
+// UI7 := [1...7] <0...127>
+//   <0> := 缺省
+// BS1 := [8] <0, 1>
+//   <0> := 选择、请求、停止激活或删除的肯定确认
+//   <1> := 选择、请求、停止激活或删除的否定确认
+bit_struct! {
+    pub struct ObjectFRQ(u8) {
+        /// 0: 肯定确认, 1: 否定确认
+        negative: bool,
+        qualifier: u7,
+    }
+}
+
+// SRQ - Section Ready Qualifier(节准备就绪限定词)
+// SRQ := CP8 {UI7, BS1}
+// BS1 := [8] <0, 1>
+//   <0> := 节准备就绪去装载
+//   <1> := 节未准备就绪去装载
+bit_struct! {
+    pub struct ObjectSRQ(u8) {
+        /// 0: 节准备就绪, 1: 节未准备就绪
+        not_ready: bool,
+        qualifier: u7,
+    }
+}
+
+// SCQ - Select and Call Qualifier(选择和召唤限定词)
+// SCQ := CP8 {UI4, UI4}
+// UI4 := [1...4] <0...15>
+//   <0> := 缺省
+//   <1> := 选择文件
+//   <2> := 请求文件
+//   <3> := 停止激活文件
+//   <4> := 删除文件
+//   <5> := 选择节
+//   <6> := 请求节
+//   <7> := 停止激活节
+// UI4 := [5...8] <0...15>
+//   <0> := 缺省
+//   <1> := 无所请求的存储空间
+//   <2> := 校验和错
+//   <3> := 非所期望的通信服务
+//   <4> := 非所期望的文件名称
+//   <5> := 非所期望的节名称
+bit_struct! {
+    pub struct ObjectSCQ(u8) {
+        /// 错误原因
+        err: u4,
+        /// 选择和召唤
+        scq: u4,
+    }
+}
+
+// LSQ - Last Section or Segment Qualifier(最后的节和段的限定词)
+// LSQ := UI8 [1...8] <0...255>
+//   <1> := 不带停止激活的文件传输
+//   <2> := 带停止激活的文件传输
+//   <3> := 不带停止激活的节传输
+//   <4> := 带停止激活的节传输
+bit_struct! {
+    pub struct ObjectLSQ(u8) {
+        lsq: u8,
+    }
+}
+
+// AFQ - Acknowledge File or Section Qualifier(文件认可或节认可限定词)
+// AFQ := CP8 {UI4, UI4}
+// UI4 := [1...4] <0...15>
+//   <1> := 文件传输的肯定认可
+//   <2> := 文件传输的否定认可
+//   <3> := 节传输的肯定认可
+//   <4> := 节传输的否定认可
+// UI4 := [5...8] <0...15> 错误原因, 同 SCQ
+bit_struct! {
+    pub struct ObjectAFQ(u8) {
+        /// 错误原因
+        err: u4,
+        /// 认可
+        afq: u4,
+    }
+}
+
+// SOF - Status of File(文件状态)
+// SOF := CP8 {STATUS, LFD, FOR, FA}
+// STATUS := UI5 [1...5] <0...31>
+// LFD := BS1 [6] 0: 后面还有目录文件, 1: 最后目录文件
+// FOR := BS1 [7] 0: 定义文件名, 1: 定义子目录名
+// FA := BS1 [8] 0: 文件等待传输, 1: 此文件的传输已经激活
+bit_struct! {
+    pub struct ObjectSOF(u8) {
+        /// 文件传输已激活
+        fa: bool,
+        /// 是否为子目录
+        is_dir: bool,
+        /// 最后目录文件
+        lfd: bool,
+        /// 文件状态
+        status: u5,
+    }
+}
+
+// checksum 计算文件或节的校验和, 所有八位位组的算术和(不考虑溢出)
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+fn file_asdu(
+    type_id: TypeID,
+    is_sequence: bool,
+    number: usize,
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    buf: Vec<u8>,
+) -> Asdu {
+    let variable_struct = VariableStruct::new(
+        u1::new(is_sequence as u8).unwrap(),
+        u7::new(number as u8).unwrap(),
+    );
+
+    Asdu {
+        identifier: Identifier {
+            type_id,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    }
+}
+
+// FileReady send a type identification [F_FR_NA_1], 文件已准备好, 只有单个信息对象(SQ = 0)
+// [F_FR_NA_1] See companion standard 101, subclass 7.3.6.1
+// 传送原因(cot)用于
+// 监视方向：
+// <13> := 文件传输
+pub fn file_ready(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: FileReadyInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    if cot.cause().get() != Cause::FileTransfer {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u24::<LittleEndian>(info.lof)?;
+    buf.write_u8(info.frq.raw())?;
+
+    Ok(file_asdu(TypeID::F_FR_NA_1, false, 1, cot, ca, buf))
+}
+
+// SectionReady send a type identification [F_SR_NA_1], 节已准备好, 只有单个信息对象(SQ = 0)
+// [F_SR_NA_1] See companion standard 101, subclass 7.3.6.2
+// 传送原因(cot)用于
+// 监视方向：
+// <13> := 文件传输
+pub fn section_ready(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: SectionReadyInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    if cot.cause().get() != Cause::FileTransfer {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u8(info.nos)?;
+    buf.write_u24::<LittleEndian>(info.lof)?;
+    buf.write_u8(info.srq.raw())?;
+
+    Ok(file_asdu(TypeID::F_SR_NA_1, false, 1, cot, ca, buf))
+}
+
+// FileCall send a type identification [F_SC_NA_1], 召唤目录, 选择文件, 召唤文件, 召唤节, 只有单个信息对象(SQ = 0)
+// [F_SC_NA_1] See companion standard 101, subclass 7.3.6.3
+// 传送原因(cot)用于
+// 控制方向：
+// <5> := 请求(召唤目录)
+// <13> := 文件传输
+// 监视方向：
+// <44> := 未知的类型标识
+// <45> := 未知的传送原因
+// <46> := 未知的应用服务数据单元公共地址
+// <47> := 未知的信息对象地址
+pub fn file_call(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: FileCallInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+    if !(cause == Cause::Request || cause == Cause::FileTransfer) {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u8(info.nos)?;
+    buf.write_u8(info.scq.raw())?;
+
+    Ok(file_asdu(TypeID::F_SC_NA_1, false, 1, cot, ca, buf))
+}
+
+// LastSection send a type identification [F_LS_NA_1], 最后的节, 最后的段, 只有单个信息对象(SQ = 0)
+// [F_LS_NA_1] See companion standard 101, subclass 7.3.6.4
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <13> := 文件传输
+pub fn last_section(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: LastSectionInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    if cot.cause().get() != Cause::FileTransfer {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u8(info.nos)?;
+    buf.write_u8(info.lsq.raw())?;
+    buf.write_u8(info.chs)?;
+
+    Ok(file_asdu(TypeID::F_LS_NA_1, false, 1, cot, ca, buf))
+}
+
+// FileAck send a type identification [F_AF_NA_1], 确认文件, 确认节, 只有单个信息对象(SQ = 0)
+// [F_AF_NA_1] See companion standard 101, subclass 7.3.6.5
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <13> := 文件传输
+pub fn file_ack(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: FileAckInfo,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    if cot.cause().get() != Cause::FileTransfer {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u8(info.nos)?;
+    buf.write_u8(info.afq.raw())?;
+
+    Ok(file_asdu(TypeID::F_AF_NA_1, false, 1, cot, ca, buf))
+}
+
+// Segment send a type identification [F_SG_NA_1], 段, 只有单个信息对象(SQ = 0)
+// [F_SG_NA_1] See companion standard 101, subclass 7.3.6.6
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <13> := 文件传输
+pub fn segment(cot: CauseOfTransmission, ca: CommonAddr, info: SegmentInfo) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    if cot.cause().get() != Cause::FileTransfer {
+        return Err(Error::ErrCmdCause(cot));
+    }
+    if info.segment.len() > SEGMENT_SIZE_MAX {
+        return Err(Error::ErrSegmentTooLarge(info.segment.len()));
+    }
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.nof)?;
+    buf.write_u8(info.nos)?;
+    buf.write_u8(info.segment.len() as u8)?;
+    buf.extend_from_slice(&info.segment);
+
+    Ok(file_asdu(TypeID::F_SG_NA_1, false, 1, cot, ca, buf))
+}
+
+// Directory send a type identification [F_DR_TA_1], 目录
+// [F_DR_TA_1] See companion standard 101, subclass 7.3.6.7
+// 传送原因(cot)用于
+// 监视方向：
+// <3> := 突发(自发)
+// <5> := 被请求
+pub fn directory(
+    is_sequence: bool,
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    infos: Vec<DirectoryInfo>,
+) -> Result<Asdu, Error> {
+    let mut cot = cot;
+    let cause = cot.cause().get();
+    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let number = infos.len();
+    let mut once = false;
+    let mut buf = vec![];
+    for info in infos {
+        if !is_sequence || !once {
+            once = true;
+            buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+        }
+        buf.write_u16::<LittleEndian>(info.nof)?;
+        buf.write_u24::<LittleEndian>(info.lof)?;
+        buf.write_u8(info.sof.raw())?;
+        if let Some(time) = info.time {
+            buf.extend_from_slice(&cp56time2a(time));
+        } else {
+            buf.extend_from_slice(&cp56time2a(Utc::now()));
+        }
+    }
+
+    Ok(file_asdu(
+        TypeID::F_DR_TA_1,
+        is_sequence,
+        number,
+        cot,
+        ca,
+        buf,
+    ))
+}
+
+impl Asdu {
+    // [F_FR_NA_1] 获取文件已准备好信息体
+    pub fn get_file_ready(&mut self) -> Result<FileReadyInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let lof = rdr.read_u24::<LittleEndian>()?;
+        let frq = read_object::<ObjectFRQ, _>(&mut rdr)?;
+
+        Ok(FileReadyInfo { ioa, nof, lof, frq })
+    }
+
+    // [F_SR_NA_1] 获取节已准备好信息体
+    pub fn get_section_ready(&mut self) -> Result<SectionReadyInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let nos = rdr.read_u8()?;
+        let lof = rdr.read_u24::<LittleEndian>()?;
+        let srq = read_object::<ObjectSRQ, _>(&mut rdr)?;
+
+        Ok(SectionReadyInfo {
+            ioa,
+            nof,
+            nos,
+            lof,
+            srq,
+        })
+    }
+
+    // [F_SC_NA_1] 获取召唤目录, 选择文件, 召唤文件, 召唤节信息体
+    pub fn get_file_call(&mut self) -> Result<FileCallInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let nos = rdr.read_u8()?;
+        let scq = read_object::<ObjectSCQ, _>(&mut rdr)?;
+
+        Ok(FileCallInfo { ioa, nof, nos, scq })
+    }
+
+    // [F_LS_NA_1] 获取最后的节, 最后的段信息体
+    pub fn get_last_section(&mut self) -> Result<LastSectionInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let nos = rdr.read_u8()?;
+        let lsq = read_object::<ObjectLSQ, _>(&mut rdr)?;
+        let chs = rdr.read_u8()?;
+
+        Ok(LastSectionInfo {
+            ioa,
+            nof,
+            nos,
+            lsq,
+            chs,
+        })
+    }
+
+    // [F_AF_NA_1] 获取确认文件, 确认节信息体
+    pub fn get_file_ack(&mut self) -> Result<FileAckInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let nos = rdr.read_u8()?;
+        let afq = read_object::<ObjectAFQ, _>(&mut rdr)?;
+
+        Ok(FileAckInfo { ioa, nof, nos, afq })
+    }
+
+    // [F_SG_NA_1] 获取段信息体
+    pub fn get_segment(&mut self) -> Result<SegmentInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_info_obj_addr(&mut rdr)?;
+        let nof = rdr.read_u16::<LittleEndian>()?;
+        let nos = rdr.read_u8()?;
+        let los = rdr.read_u8()? as usize;
+        if rdr.remaining() < los {
+            return Err(anyhow!(
+                "segment length {} exceeds remaining {}",
+                los,
+                rdr.remaining()
+            ));
+        }
+        let pos = rdr.position() as usize;
+        let segment = self.raw.slice(pos..pos + los);
+
+        Ok(SegmentInfo {
+            ioa,
+            nof,
+            nos,
+            segment,
+        })
+    }
+
+    // [F_DR_TA_1] 获取目录信息体集合
+    pub fn get_directory(&mut self) -> Result<Vec<DirectoryInfo>> {
+        self.check_info_len()?;
+        let mut rdr = Cursor::new(&self.raw);
+        let info_num = self.identifier.variable_struct.number().get().value() as usize;
+        let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
+        let mut info = Vec::with_capacity(info_num);
+        let mut once = false;
+        let mut ioa = InfoObjAddr::new(0, 0);
+        for _ in 0..info_num {
+            if !is_seq || !once {
+                once = true;
+                ioa = read_info_obj_addr(&mut rdr)?;
+            } else {
+                ioa = next_info_obj_addr(ioa)?;
+            }
+            let nof = rdr.read_u16::<LittleEndian>()?;
+            let lof = rdr.read_u24::<LittleEndian>()?;
+            let sof = read_object::<ObjectSOF, _>(&mut rdr)?;
+            let time = decode_cp56time2a(&mut rdr)?;
+            info.push(DirectoryInfo {
+                ioa,
+                nof,
+                lof,
+                sof,
+                time,
+            });
+        }
+        Ok(info)
+    }
+}
@@ -1,13 +1,25 @@
 pub mod apci;
 pub mod asdu;
+pub mod cpara;
 pub mod cproc;
 pub mod csys;
+mod describe;
+pub mod file;
 pub mod mproc;
 pub mod msys;
+pub mod payload;
+pub mod secauth;
 pub mod time;
 
-use self::{apci::Apci, asdu::Asdu};
+use self::{
+    apci::Apci,
+    asdu::{Asdu, AsduParams},
+};
+use crate::Codec;
+use anyhow::{anyhow, Result};
+use bytes::BytesMut;
 use std::fmt::Display;
+use tokio_util::codec::Encoder;
 
 // APDU = APCI + 可选的 ASDU
 #[derive(Debug)]
@@ -20,8 +32,63 @@ impl Display for Apdu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.apci.to_string().as_str())?;
         if let Some(asdu) = &self.asdu {
-            f.write_str(asdu.to_string().as_str())?;
+            if f.alternate() {
+                write!(f, "{asdu:#}")?;
+            } else {
+                write!(f, " {asdu}")?;
+            }
         }
         Ok(())
     }
 }
+
+impl Apdu {
+    // 解析十六进制文本表示的一个完整 APDU, 如 "680e0000000064010600010000000014".
+    // 字节之间可以有空白, ':' 或 '-', 不区分大小写
+    pub fn from_hex(text: &str) -> Result<Apdu> {
+        Self::from_hex_with(text, &AsduParams::default())
+    }
+
+    pub fn from_hex_with(text: &str, params: &AsduParams) -> Result<Apdu> {
+        let bytes = decode_hex(text)?;
+        let mut apdus = Codec::new(*params).decode_slice(&bytes)?;
+        match apdus.len() {
+            1 => Ok(apdus.remove(0)),
+            n => Err(anyhow!("expect 1 APDU, got {n}")),
+        }
+    }
+
+    // 编码后的十六进制文本(小写, 无分隔), 长度字段按 ASDU 重新计算
+    pub fn to_hex(&self) -> String {
+        // 标准字段长度的编码不会失败
+        self.to_hex_with(&AsduParams::default())
+            .expect("encode APDU with IEC 104 parameters")
+    }
+
+    pub fn to_hex_with(&self, params: &AsduParams) -> Result<String> {
+        let apdu = Apdu {
+            apci: self.apci,
+            asdu: self.asdu.clone(),
+        };
+        let mut buf = BytesMut::new();
+        Codec::new(*params).encode(apdu, &mut buf)?;
+        Ok(buf.iter().map(|b| format!("{b:02x}")).collect())
+    }
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>> {
+    let digits: Vec<char> = text
+        .chars()
+        .filter(|c| !(c.is_whitespace() || *c == ':' || *c == '-'))
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(anyhow!("odd number of hex digits: {text}"));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte: String = pair.iter().collect();
+            u8::from_str_radix(&byte, 16).map_err(|_| anyhow!("invalid hex byte {byte:?}"))
+        })
+        .collect()
+}
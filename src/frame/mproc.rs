@@ -10,8 +10,9 @@ use crate::error::Error;
 
 use super::{
     asdu::{
-        Asdu, Cause, CauseOfTransmission, CommonAddr, Identifier, InfoObjAddr, TypeID,
-        VariableStruct,
+        next_info_obj_addr, read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission,
+        CommonAddr, Identifier, InfoObjAddr, IntoCommonAddr, IntoInfoObjAddr, TypeID,
+        VariableStruct, ASDU_SIZE_MAX, IDENTIFIER_SIZE,
     },
     time::{cp24time2a, cp56time2a, decode_cp24time2a, decode_cp56time2a},
 };
@@ -19,6 +20,7 @@ use super::{
 // 在监视方向过程信息的应用服务数据单元
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SinglePointInfo {
     pub ioa: InfoObjAddr,
     pub siq: ObjectSIQ,
@@ -42,7 +44,8 @@ impl SinglePointInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoublePointInfo {
     pub ioa: InfoObjAddr,
     pub diq: ObjectDIQ,
@@ -66,7 +69,8 @@ impl DoublePointInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeasuredValueNormalInfo {
     pub ioa: InfoObjAddr,
     pub nva: i16,
@@ -74,7 +78,8 @@ pub struct MeasuredValueNormalInfo {
     pub time: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeasuredValueScaledInfo {
     pub ioa: InfoObjAddr,
     pub sva: i16,
@@ -83,6 +88,7 @@ pub struct MeasuredValueScaledInfo {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeasuredValueFloatInfo {
     pub ioa: InfoObjAddr,
     pub r: f32,
@@ -90,13 +96,32 @@ pub struct MeasuredValueFloatInfo {
     pub time: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryCounterReadingInfo {
     pub ioa: InfoObjAddr,
     pub bcr: ObjectBCR,
     pub time: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedSinglePointInfo {
+    pub ioa: InfoObjAddr,
+    pub scd: ObjectSCD,
+    pub qds: ObjectQDS,
+}
+
+impl PackedSinglePointInfo {
+    pub fn new(addr: u16, spi: u16, vflag: u16) -> Self {
+        let ioa = InfoObjAddr::new(0, addr);
+        let scd = ObjectSCD::new(0, vflag, spi);
+        let qds = ObjectQDS::of_defaults();
+
+        PackedSinglePointInfo { ioa, scd, qds }
+    }
+}
+
 // SIQ - Single-point Information with Quality descriptor(带品质描述词的单点信息) 单点遥信对象
 bit_struct! {
     pub struct ObjectSIQ(u8) {
@@ -151,7 +176,8 @@ bit_struct! {
 }
 
 // BCR - Binary Counter Reading(二进制计数器读数) 二进制计数器遥测对象
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectBCR {
     pub invalid: bool, // 数据无效标志
     pub ca: bool,      // 上次读数后计数量有调整
@@ -160,11 +186,97 @@ pub struct ObjectBCR {
     pub value: i32,
 }
 
+// 顺序(SQ = 1)编码只传送第一个信息对象地址, 其余信息对象的地址须依次加 1
+fn check_sequence(ioas: impl Iterator<Item = InfoObjAddr>) -> Result<(), Error> {
+    let mut expect: Option<InfoObjAddr> = None;
+    for mut ioa in ioas {
+        if let Some(mut prev) = expect {
+            let addr = prev.addr().get();
+            if ioa.res().get() != prev.res().get() || addr.checked_add(1) != Some(ioa.addr().get())
+            {
+                return Err(Error::ErrIoaNotSequential(ioa.addr().get()));
+            }
+        }
+        expect = Some(ioa);
+    }
+    Ok(())
+}
+
+// 从 start 开始依次加 1 的 n 个信息对象地址
+fn sequence_addrs(start: InfoObjAddr, n: usize) -> Result<Vec<InfoObjAddr>, Error> {
+    let mut ioa = start;
+    let mut addrs = Vec::with_capacity(n);
+    for i in 0..n {
+        if i > 0 {
+            let addr = ioa.addr().get();
+            let Some(next) = addr.checked_add(1) else {
+                return Err(Error::ErrIoaNotSequential(addr));
+            };
+            ioa.addr().set(next);
+        }
+        addrs.push(ioa);
+    }
+    Ok(addrs)
+}
+
+// 监视方向各类型标识允许的传送原因, 见各构造函数的说明
+pub(crate) fn check_monitor_cause(type_id: TypeID, cot: CauseOfTransmission) -> Result<(), Error> {
+    let mut c = cot;
+    let cause = c.cause().get();
+    let interrogated =
+        cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16;
+    let ok = match type_id {
+        TypeID::M_SP_NA_1 | TypeID::M_DP_NA_1 | TypeID::M_PS_NA_1 => {
+            interrogated
+                || matches!(
+                    cause,
+                    Cause::Background
+                        | Cause::Spontaneous
+                        | Cause::Request
+                        | Cause::ReturnInfoRemote
+                        | Cause::ReturnInfoLocal
+                )
+        }
+        TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1 => {
+            matches!(
+                cause,
+                Cause::Spontaneous
+                    | Cause::Request
+                    | Cause::ReturnInfoRemote
+                    | Cause::ReturnInfoLocal
+            )
+        }
+        TypeID::M_ME_NA_1 | TypeID::M_ME_NB_1 | TypeID::M_ME_NC_1 | TypeID::M_ME_ND_1 => {
+            interrogated
+                || matches!(
+                    cause,
+                    Cause::Periodic | Cause::Background | Cause::Spontaneous | Cause::Request
+                )
+        }
+        TypeID::M_ME_TA_1
+        | TypeID::M_ME_TB_1
+        | TypeID::M_ME_TC_1
+        | TypeID::M_ME_TD_1
+        | TypeID::M_ME_TE_1
+        | TypeID::M_ME_TF_1 => matches!(cause, Cause::Spontaneous | Cause::Request),
+        TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1 => {
+            cause == Cause::Spontaneous
+                || (cause >= Cause::InterrogatedByStation && cause <= Cause::RequestByGroup4Counter)
+        }
+        _ => return Err(Error::ErrTypeIDNotMatch(type_id)),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(Error::ErrCmdCause(cot))
+    }
+}
+
 // single sends a type identification [M_SP_NA_1], [M_SP_TA_1] or [M_SP_TB_1].单点信息
 // [M_SP_NA_1] See companion standard 101,subclass 7.3.1.1
 // [M_SP_TA_1] See companion standard 101,subclass 7.3.1.2
 // [M_SP_TB_1] See companion standard 101,subclass 7.3.1.22
-fn single_inner(
+pub(crate) fn single_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -172,6 +284,9 @@ fn single_inner(
     infos: Vec<SinglePointInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
 
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
@@ -235,24 +350,33 @@ fn single_inner(
 pub fn single(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<SinglePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_SP_NA_1, cot)?;
 
     single_inner(TypeID::M_SP_NA_1, is_sequence, cot, ca, infos)
 }
 
+// SingleSequence sends a type identification [M_SP_NA_1] with SQ = 1. 顺序的不带时标单点信息
+// 信息对象地址从 start 开始依次加 1, 品质描述词为有效
+pub fn single_sequence(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    start: impl IntoInfoObjAddr,
+    values: Vec<bool>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let start = start.into_info_obj_addr();
+    let infos = sequence_addrs(start, values.len())?
+        .into_iter()
+        .zip(values)
+        .map(|(ioa, v)| SinglePointInfo::new(ioa, ObjectSIQ::new_with_value(v), None))
+        .collect();
+    single(true, cot, ca, infos)
+}
+
 // SingleCP24Time2a sends a type identification [M_SP_TA_1],带时标CP24Time2a的单点信息，只有(SQ = 0)单个信息元素集合
 // [M_SP_TA_1] See companion standard 101,subclass 7.3.1.2
 // 传送原因(cot)用于
@@ -263,18 +387,11 @@ pub fn single(
 // <12> := 当地命令引起的返送信息
 pub fn single_cp24time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<SinglePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal)
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_SP_TA_1, cot)?;
     single_inner(TypeID::M_SP_TA_1, false, cot, ca, infos)
 }
 
@@ -288,18 +405,11 @@ pub fn single_cp24time2a(
 // <12> := 当地命令引起的返送信息
 pub fn single_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<SinglePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal)
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_SP_TB_1, cot)?;
     single_inner(TypeID::M_SP_TB_1, false, cot, ca, infos)
 }
 
@@ -307,7 +417,7 @@ pub fn single_cp56time2a(
 // [M_DP_NA_1] See companion standard 101,subclass 7.3.1.3
 // [M_DP_TA_1] See companion standard 101,subclass 7.3.1.4
 // [M_DP_TB_1] See companion standard 101,subclass 7.3.1.23
-fn double_inner(
+pub(crate) fn double_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -315,6 +425,9 @@ fn double_inner(
     infos: Vec<DoublePointInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
 
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
@@ -379,23 +492,36 @@ fn double_inner(
 pub fn double(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<DoublePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_DP_NA_1, cot)?;
     double_inner(TypeID::M_DP_NA_1, is_sequence, cot, ca, infos)
 }
 
+// DoubleSequence sends a type identification [M_DP_NA_1] with SQ = 1. 顺序的不带时标双点信息
+// 信息对象地址从 start 开始依次加 1, 双点值取值 [0, 3], 品质描述词为有效
+pub fn double_sequence(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    start: impl IntoInfoObjAddr,
+    values: Vec<u8>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let start = start.into_info_obj_addr();
+    let infos = sequence_addrs(start, values.len())?
+        .into_iter()
+        .zip(values)
+        .map(|(ioa, v)| DoublePointInfo {
+            ioa,
+            diq: ObjectDIQ::new(false, false, false, false, u2!(0), u2::new(v % 4).unwrap()),
+            time: None,
+        })
+        .collect();
+    double(true, cot, ca, infos)
+}
+
 // DoubleCP24Time2a sends a type identification [M_DP_TA_1] .带CP24Time2a双点信息,只有(SQ = 0)单个信息元素集合
 // [M_DP_TA_1] See companion standard 101,subclass 7.3.1.4
 // 传送原因(cot)用于
@@ -407,18 +533,11 @@ pub fn double(
 pub fn double_cp24time2a(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<DoublePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal)
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_DP_TA_1, cot)?;
 
     double_inner(TypeID::M_DP_TA_1, is_sequence, cot, ca, infos)
 }
@@ -434,18 +553,11 @@ pub fn double_cp24time2a(
 pub fn double_cp56time2a(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<DoublePointInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || cause == Cause::ReturnInfoRemote
-        || cause == Cause::ReturnInfoLocal)
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_DP_TB_1, cot)?;
 
     double_inner(TypeID::M_DP_TB_1, is_sequence, cot, ca, infos)
 }
@@ -462,7 +574,7 @@ pub fn double_cp56time2a(
 // [M_ME_TA_1] See companion standard 101, subclass 7.3.1.10
 // [M_ME_TD_1] See companion standard 101, subclass 7.3.1.26
 // [M_ME_ND_1] See companion standard 101, subclass 7.3.1.21， The quality descriptor must default to asdu.GOOD
-fn measured_value_normal_inner(
+pub(crate) fn measured_value_normal_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -470,6 +582,9 @@ fn measured_value_normal_inner(
     infos: Vec<MeasuredValueNormalInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
         u7::new(infos.len() as u8).unwrap(),
@@ -547,23 +662,38 @@ fn measured_value_normal_inner(
 pub fn measured_value_normal(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueNormalInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Periodic
-        || cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_NA_1, cot)?;
 
     measured_value_normal_inner(TypeID::M_ME_NA_1, is_sequence, cot, ca, infos)
 }
 
+// MeasuredValueNormalSequence sends a type identification [M_ME_NA_1] with SQ = 1. 顺序的测量值,规一化值
+// 信息对象地址从 start 开始依次加 1, 品质描述词为有效
+pub fn measured_value_normal_sequence(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    start: impl IntoInfoObjAddr,
+    values: Vec<i16>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let start = start.into_info_obj_addr();
+    let infos = sequence_addrs(start, values.len())?
+        .into_iter()
+        .zip(values)
+        .map(|(ioa, nva)| MeasuredValueNormalInfo {
+            ioa,
+            nva,
+            qds: None,
+            time: None,
+        })
+        .collect();
+    measured_value_normal(true, cot, ca, infos)
+}
+
 // MeasuredValueNormalCP24Time2a sends a type identification [M_ME_TA_1].带时标CP24Time2a的测量值,规一化值,只有(SQ = 0)单个信息元素集合
 // [M_ME_TA_1] See companion standard 101, subclass 7.3.1.10
 // 传送原因(cot)用于
@@ -572,14 +702,11 @@ pub fn measured_value_normal(
 // <5> := 被请求
 pub fn measured_value_normal_cp24time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueNormalInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TA_1, cot)?;
 
     measured_value_normal_inner(TypeID::M_ME_TA_1, false, cot, ca, infos)
 }
@@ -592,14 +719,11 @@ pub fn measured_value_normal_cp24time2a(
 // <5> := 被请求
 pub fn measured_value_normal_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueNormalInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TD_1, cot)?;
 
     measured_value_normal_inner(TypeID::M_ME_TD_1, false, cot, ca, infos)
 }
@@ -619,19 +743,11 @@ pub fn measured_value_normal_cp56time2a(
 // <36> := 响应第16组召唤
 pub fn measured_value_normal_noquality(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueNormalInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Periodic
-        || cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_ND_1, cot)?;
 
     measured_value_normal_inner(TypeID::M_ME_ND_1, false, cot, ca, infos)
 }
@@ -640,7 +756,7 @@ pub fn measured_value_normal_noquality(
 // [M_ME_NB_1] See companion standard 101, subclass 7.3.1.11
 // [M_ME_TB_1] See companion standard 101, subclass 7.3.1.12
 // [M_ME_TE_1] See companion standard 101, subclass 7.3.1.27
-fn measured_value_scaled_inner(
+pub(crate) fn measured_value_scaled_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -648,6 +764,9 @@ fn measured_value_scaled_inner(
     infos: Vec<MeasuredValueScaledInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
         u7::new(infos.len() as u8).unwrap(),
@@ -706,22 +825,40 @@ fn measured_value_scaled_inner(
 // <36> := 响应第16组召唤
 pub fn measured_value_scaled(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueScaledInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Periodic
-        || cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_NB_1, cot)?;
     measured_value_scaled_inner(TypeID::M_ME_NB_1, false, cot, ca, infos)
 }
 
+// MeasuredValueScaledSequence sends a type identification [M_ME_NB_1] with SQ = 1. 顺序的测量值,标度化值
+// 信息对象地址从 start 开始依次加 1, 品质描述词为有效
+// 传送原因(cot)同 measured_value_scaled
+pub fn measured_value_scaled_sequence(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    start: impl IntoInfoObjAddr,
+    values: Vec<i16>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let start = start.into_info_obj_addr();
+    check_monitor_cause(TypeID::M_ME_NB_1, cot)?;
+
+    let infos = sequence_addrs(start, values.len())?
+        .into_iter()
+        .zip(values)
+        .map(|(ioa, sva)| MeasuredValueScaledInfo {
+            ioa,
+            sva,
+            qds: ObjectQDS::of_defaults(),
+            time: None,
+        })
+        .collect();
+    measured_value_scaled_inner(TypeID::M_ME_NB_1, true, cot, ca, infos)
+}
+
 // MeasuredValueScaledCP24Time2a sends a type identification [M_ME_TB_1].带时标CP24Time2a的测量值,标度化值,只有(SQ = 0)单个信息元素集合
 // [M_ME_TB_1] See companion standard 101, subclass 7.3.1.12
 // 传送原因(cot)用于
@@ -730,14 +867,11 @@ pub fn measured_value_scaled(
 // <5> := 被请求
 pub fn measured_value_scaled_cp24time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueScaledInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TB_1, cot)?;
     measured_value_scaled_inner(TypeID::M_ME_TB_1, false, cot, ca, infos)
 }
 
@@ -749,14 +883,11 @@ pub fn measured_value_scaled_cp24time2a(
 // <5> := 被请求
 pub fn measured_value_scaled_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueScaledInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TE_1, cot)?;
     measured_value_scaled_inner(TypeID::M_ME_TE_1, false, cot, ca, infos)
 }
 
@@ -764,7 +895,7 @@ pub fn measured_value_scaled_cp56time2a(
 // [M_ME_NC_1] See companion standard 101, subclass 7.3.1.13
 // [M_ME_TC_1] See companion standard 101, subclass 7.3.1.14
 // [M_ME_TF_1] See companion standard 101, subclass 7.3.1.28
-fn measured_value_float_inner(
+pub(crate) fn measured_value_float_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -772,6 +903,9 @@ fn measured_value_float_inner(
     infos: Vec<MeasuredValueFloatInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
         u7::new(infos.len() as u8).unwrap(),
@@ -831,23 +965,38 @@ fn measured_value_float_inner(
 pub fn measured_value_float(
     is_sequence: bool,
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueFloatInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Periodic
-        || cause == Cause::Background
-        || cause == Cause::Spontaneous
-        || cause == Cause::Request
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::InterrogatedByGroup16))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_NC_1, cot)?;
 
     measured_value_float_inner(TypeID::M_ME_NC_1, is_sequence, cot, ca, infos)
 }
 
+// MeasuredValueFloatSequence sends a type identification [M_ME_NC_1] with SQ = 1. 顺序的测量值,短浮点数
+// 信息对象地址从 start 开始依次加 1, 品质描述词为有效
+pub fn measured_value_float_sequence(
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    start: impl IntoInfoObjAddr,
+    values: Vec<f32>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let start = start.into_info_obj_addr();
+    let infos = sequence_addrs(start, values.len())?
+        .into_iter()
+        .zip(values)
+        .map(|(ioa, r)| MeasuredValueFloatInfo {
+            ioa,
+            r,
+            qds: ObjectQDS::of_defaults(),
+            time: None,
+        })
+        .collect();
+    measured_value_float(true, cot, ca, infos)
+}
+
 // MeasuredValueFloatCP24Time2a sends a type identification [M_ME_TC_1].带时标CP24Time2a的测量值,短浮点数,只有(SQ = 0)单个信息元素集合
 // [M_ME_TC_1] See companion standard 101, subclass 7.3.1.14
 // 传送原因(cot)用于
@@ -856,14 +1005,11 @@ pub fn measured_value_float(
 // <5> := 被请求
 pub async fn measured_value_float_cp24time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueFloatInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TC_1, cot)?;
 
     measured_value_float_inner(TypeID::M_ME_TC_1, false, cot, ca, infos)
 }
@@ -876,14 +1022,11 @@ pub async fn measured_value_float_cp24time2a(
 // <5> := 被请求
 pub async fn measured_value_float_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<MeasuredValueFloatInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous || cause == Cause::Request) {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_ME_TF_1, cot)?;
 
     measured_value_float_inner(TypeID::M_ME_TF_1, false, cot, ca, infos)
 }
@@ -891,7 +1034,7 @@ pub async fn measured_value_float_cp56time2a(
 // [M_IT_NA_1] See companion standard 101, subclass 7.3.1.15
 // [M_IT_TA_1] See companion standard 101, subclass 7.3.1.16
 // [M_IT_TB_1] See companion standard 101, subclass 7.3.1.29
-fn integrated_totals_inner(
+pub(crate) fn integrated_totals_inner(
     type_id: TypeID,
     is_sequence: bool,
     cot: CauseOfTransmission,
@@ -899,6 +1042,9 @@ fn integrated_totals_inner(
     infos: Vec<BinaryCounterReadingInfo>,
 ) -> Result<Asdu, Error> {
     // TODO: check infos len
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
     let variable_struct = VariableStruct::new(
         u1::new(is_sequence as u8).unwrap(),
         u7::new(infos.len() as u8).unwrap(),
@@ -965,16 +1111,11 @@ fn integrated_totals_inner(
 // <41> := 响应第4组计数量召唤
 pub fn integrated_totals(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<BinaryCounterReadingInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::RequestByGroup4Counter))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_IT_NA_1, cot)?;
 
     integrated_totals_inner(TypeID::M_IT_NA_1, false, cot, ca, infos)
 }
@@ -991,16 +1132,11 @@ pub fn integrated_totals(
 // <41> := 响应第4组计数量召唤
 pub async fn integrated_totals_cp24time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<BinaryCounterReadingInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::RequestByGroup4Counter))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_IT_TA_1, cot)?;
 
     integrated_totals_inner(TypeID::M_IT_TA_1, false, cot, ca, infos)
 }
@@ -1017,46 +1153,157 @@ pub async fn integrated_totals_cp24time2a(
 // <41> := 响应第4组计数量召唤
 pub async fn integrated_totals_cp56time2a(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
+    ca: impl IntoCommonAddr,
     infos: Vec<BinaryCounterReadingInfo>,
 ) -> Result<Asdu, Error> {
-    let mut cot = cot;
-    let cause = cot.cause().get();
-    if !(cause == Cause::Spontaneous
-        || (cause >= Cause::InterrogatedByStation && cause <= Cause::RequestByGroup4Counter))
-    {
-        return Err(Error::ErrCmdCause(cot));
-    }
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_IT_TB_1, cot)?;
 
     integrated_totals_inner(TypeID::M_IT_TB_1, false, cot, ca, infos)
 }
 
+// PackedSinglePointWithSCD sends a type identification [M_PS_NA_1]. 带变位检出的成组单点信息
+// [M_PS_NA_1] See companion standard 101, subclass 7.3.1.20
+// 传送原因(cot)用于
+// 监视方向：
+// <2> := 背景扫描
+// <3> := 突发(自发)
+// <5> := 被请求
+// <11> := 远方命令引起的返送信息
+// <12> := 当地命令引起的返送信息
+// <20> := 响应站召唤
+// <21> := 响应第1组召唤
+// 至
+// <36> := 响应第16组召唤
+pub fn packed_single_point_with_scd(
+    is_sequence: bool,
+    cot: CauseOfTransmission,
+    ca: impl IntoCommonAddr,
+    infos: Vec<PackedSinglePointInfo>,
+) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    check_monitor_cause(TypeID::M_PS_NA_1, cot)?;
+    if is_sequence {
+        check_sequence(infos.iter().map(|info| info.ioa))?;
+    }
+
+    let variable_struct = VariableStruct::new(
+        u1::new(is_sequence as u8).unwrap(),
+        u7::new(infos.len() as u8).unwrap(),
+    );
+    let mut once = false;
+    let mut buf = vec![];
+    for mut info in infos {
+        if !is_sequence || !once {
+            once = true;
+            buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+        }
+        // SCD 先传状态位 ST, 再传变位检出 CD
+        buf.write_u16::<LittleEndian>(info.scd.spi().get())?;
+        buf.write_u16::<LittleEndian>(info.scd.vflag().get())?;
+        buf.write_u8(info.qds.raw())?;
+    }
+    Ok(Asdu {
+        identifier: Identifier {
+            type_id: TypeID::M_PS_NA_1,
+            variable_struct,
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    })
+}
+
+// 信息元素个数(VSQ 的 number)的最大值
+pub(crate) const INFO_NUM_MAX: usize = 127;
+
+// 信息对象过多时拆分为多个 ASDU, 每个 ASDU 不超过 127 个信息对象且长度不超过 ASDU_SIZE_MAX.
+// build 为对应的编码函数, 如 |infos| single(false, cot, ca, infos), 每次最多传入 127 个信息对象,
+// 顺序(SQ = 1)编码时 build 须自行推进起始地址.
+// 编码后超过 ASDU_SIZE_MAX 的再按长度拆分, 顺序编码拆分后每个 ASDU 带上各自起始的信息对象地址
+pub fn split_into_asdus<T, F>(infos: Vec<T>, mut build: F) -> Result<Vec<Asdu>, Error>
+where
+    F: FnMut(Vec<T>) -> Result<Asdu, Error>,
+{
+    let mut asdus = Vec::new();
+    let mut infos = infos.into_iter().peekable();
+    while infos.peek().is_some() {
+        let chunk: Vec<T> = infos.by_ref().take(INFO_NUM_MAX).collect();
+        asdus.extend(split_encoded(build(chunk)?));
+    }
+    Ok(asdus)
+}
+
+// 按长度拆分已编码的 ASDU, 要求各信息对象长度相同
+fn split_encoded(asdu: Asdu) -> Vec<Asdu> {
+    let mut vsq = asdu.identifier.variable_struct;
+    let info_num = vsq.number().get().value() as usize;
+    let is_seq = vsq.is_sequence().get().value() != 0;
+    if info_num == 0 || IDENTIFIER_SIZE + asdu.raw.len() <= ASDU_SIZE_MAX {
+        return vec![asdu];
+    }
+
+    // 顺序编码时只有第一个信息对象带地址
+    let ioa_size = if is_seq { 3 } else { 0 };
+    let elem_size = (asdu.raw.len() - ioa_size) / info_num;
+    let per_asdu = (ASDU_SIZE_MAX - IDENTIFIER_SIZE - ioa_size) / elem_size;
+    let raw = &asdu.raw;
+    let start = u24::new(u32::from_le_bytes([raw[0], raw[1], raw[2], 0])).unwrap();
+    let start = InfoObjAddr::try_from(start).unwrap();
+
+    let mut asdus = Vec::new();
+    let mut offset = 0;
+    while offset < info_num {
+        let n = per_asdu.min(info_num - offset);
+        let mut buf = Vec::with_capacity(ioa_size + n * elem_size);
+        if is_seq {
+            let mut ioa = start;
+            let addr = ioa.addr().get().wrapping_add(offset as u16);
+            ioa.addr().set(addr);
+            buf.extend_from_slice(&ioa.raw().value().to_le_bytes()[..3]);
+        }
+        let from = ioa_size + offset * elem_size;
+        buf.extend_from_slice(&raw[from..from + n * elem_size]);
+
+        let mut identifier = asdu.identifier;
+        identifier
+            .variable_struct
+            .number()
+            .set(u7::new(n as u8).unwrap());
+        asdus.push(Asdu {
+            identifier,
+            raw: Bytes::from(buf),
+        });
+        offset += n;
+    }
+    asdus
+}
+
 impl Asdu {
     // [M_SP_NA_1], [M_SP_TA_1] or [M_SP_TB_1] 获取单点信息信息体集合
     pub fn get_single_point(&mut self) -> Result<Vec<SinglePointInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
-            let siq = ObjectSIQ::try_from(rdr.read_u8()?).unwrap();
+            let siq = read_object::<ObjectSIQ, _>(&mut rdr)?;
             let mut time = None;
             match self.identifier.type_id {
                 TypeID::M_SP_NA_1 => (),
                 TypeID::M_SP_TA_1 => time = decode_cp24time2a(&mut rdr)?,
                 TypeID::M_SP_TB_1 => time = decode_cp56time2a(&mut rdr)?,
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(SinglePointInfo { ioa, siq, time });
         }
@@ -1065,29 +1312,27 @@ impl Asdu {
 
     // [M_DP_NA_1], [M_DP_TA_1] or [M_DP_TB_1] 获得双点信息体集合
     pub fn get_double_point(&mut self) -> Result<Vec<DoublePointInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
-            let diq = ObjectDIQ::try_from(rdr.read_u8()?).unwrap();
+            let diq = read_object::<ObjectDIQ, _>(&mut rdr)?;
             let mut time = None;
             match self.identifier.type_id {
                 TypeID::M_DP_NA_1 => (),
                 TypeID::M_DP_TA_1 => time = decode_cp24time2a(&mut rdr)?,
                 TypeID::M_DP_TB_1 => time = decode_cp56time2a(&mut rdr)?,
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(DoublePointInfo { ioa, diq, time });
         }
@@ -1096,39 +1341,37 @@ impl Asdu {
 
     // [M_ME_NA_1], [M_ME_TA_1],[ M_ME_TD_1] or [M_ME_ND_1] 获得测量值,规一化值信息体集合
     pub fn get_measured_value_normal(&mut self) -> Result<Vec<MeasuredValueNormalInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
             let nva = rdr.read_i16::<LittleEndian>()?;
             let mut qds = None;
             let mut time = None;
             match self.identifier.type_id {
                 TypeID::M_ME_NA_1 => {
-                    qds = Some(ObjectQDS::try_from(rdr.read_u8()?).unwrap());
+                    qds = Some(read_object::<ObjectQDS, _>(&mut rdr)?);
                 }
                 TypeID::M_ME_TA_1 => {
-                    qds = Some(ObjectQDS::try_from(rdr.read_u8()?).unwrap());
+                    qds = Some(read_object::<ObjectQDS, _>(&mut rdr)?);
                     time = decode_cp24time2a(&mut rdr)?
                 }
                 TypeID::M_ME_TD_1 => {
-                    qds = Some(ObjectQDS::try_from(rdr.read_u8()?).unwrap());
+                    qds = Some(read_object::<ObjectQDS, _>(&mut rdr)?);
                     time = decode_cp56time2a(&mut rdr)?
                 }
                 TypeID::M_ME_ND_1 => (), // 不带品质
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(MeasuredValueNormalInfo {
                 ioa,
@@ -1142,30 +1385,28 @@ impl Asdu {
 
     // [M_ME_NB_1], [M_ME_TB_1] or [M_ME_TE_1] 获得测量值，标度化值信息体集合
     pub fn get_measured_value_scaled(&mut self) -> Result<Vec<MeasuredValueScaledInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
             let sva = rdr.read_i16::<LittleEndian>()?;
-            let qds = ObjectQDS::try_from(rdr.read_u8()?).unwrap();
+            let qds = read_object::<ObjectQDS, _>(&mut rdr)?;
             let mut time = None;
             match self.identifier.type_id {
                 TypeID::M_ME_NB_1 => (),
                 TypeID::M_ME_TB_1 => time = decode_cp24time2a(&mut rdr)?,
                 TypeID::M_ME_TE_1 => time = decode_cp56time2a(&mut rdr)?,
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(MeasuredValueScaledInfo {
                 ioa,
@@ -1179,30 +1420,28 @@ impl Asdu {
 
     // [M_ME_NC_1], [M_ME_TC_1] or [M_ME_TF_1]. 获得测量值,短浮点数信息体集合
     pub fn get_measured_value_float(&mut self) -> Result<Vec<MeasuredValueFloatInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
             let r = rdr.read_f32::<LittleEndian>()?;
-            let qds = ObjectQDS::try_from(rdr.read_u8()?).unwrap();
+            let qds = read_object::<ObjectQDS, _>(&mut rdr)?;
             let mut time = None;
             match self.identifier.type_id {
                 TypeID::M_ME_NC_1 => (),
                 TypeID::M_ME_TC_1 => time = decode_cp24time2a(&mut rdr)?,
                 TypeID::M_ME_TF_1 => time = decode_cp56time2a(&mut rdr)?,
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(MeasuredValueFloatInfo { ioa, r, qds, time });
         }
@@ -1211,21 +1450,19 @@ impl Asdu {
 
     // [M_IT_NA_1], [M_IT_TA_1] or [M_IT_TB_1]. 获得累计量信息体集合
     pub fn get_integrated_totals(&mut self) -> Result<Vec<BinaryCounterReadingInfo>, Error> {
+        self.check_info_len()?;
         let mut rdr = Cursor::new(&self.raw);
         let info_num = self.identifier.variable_struct.number().get().value() as usize;
         let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
         let mut info = Vec::with_capacity(info_num);
         let mut once = false;
-        let mut ioa = InfoObjAddr::try_from(u24!(0)).unwrap();
-        let mut info_obj_addr_std;
+        let mut ioa = InfoObjAddr::new(0, 0);
         for i in 0..info_num {
             if !is_seq || !once {
                 once = true;
-                info_obj_addr_std = rdr.read_u24::<LittleEndian>()?;
-                ioa = InfoObjAddr::try_from(u24::new(info_obj_addr_std).unwrap()).unwrap();
+                ioa = read_info_obj_addr(&mut rdr)?;
             } else {
-                let addr = ioa.addr().get() + 1;
-                ioa.addr().set(addr);
+                ioa = next_info_obj_addr(ioa)?;
             }
             let value = rdr.read_i32::<LittleEndian>()?;
             let b = rdr.read_u8()?;
@@ -1241,10 +1478,35 @@ impl Asdu {
                 TypeID::M_IT_NA_1 => (),
                 TypeID::M_IT_TA_1 => time = decode_cp24time2a(&mut rdr)?,
                 TypeID::M_IT_TB_1 => time = decode_cp56time2a(&mut rdr)?,
-                _ => panic!("ErrTypeIDNotMatch"),
+                _ => return Err(Error::ErrTypeIDNotMatch(self.identifier.type_id)),
             }
             info.push(BinaryCounterReadingInfo { ioa, bcr, time });
         }
         Ok(info)
     }
+
+    // [M_PS_NA_1]. 获得带变位检出的成组单点信息信息体集合
+    pub fn get_packed_single_point(&mut self) -> Result<Vec<PackedSinglePointInfo>, Error> {
+        self.check_info_len()?;
+        let mut rdr = Cursor::new(&self.raw);
+        let info_num = self.identifier.variable_struct.number().get().value() as usize;
+        let is_seq = self.identifier.variable_struct.is_sequence().get().value() != 0;
+        let mut info = Vec::with_capacity(info_num);
+        let mut once = false;
+        let mut ioa = InfoObjAddr::new(0, 0);
+        for i in 0..info_num {
+            if !is_seq || !once {
+                once = true;
+                ioa = read_info_obj_addr(&mut rdr)?;
+            } else {
+                ioa = next_info_obj_addr(ioa)?;
+            }
+            let spi = rdr.read_u16::<LittleEndian>()?;
+            let vflag = rdr.read_u16::<LittleEndian>()?;
+            let scd = ObjectSCD::new(0, vflag, spi);
+            let qds = read_object::<ObjectQDS, _>(&mut rdr)?;
+            info.push(PackedSinglePointInfo { ioa, scd, qds });
+        }
+        Ok(info)
+    }
 }
\ No newline at end of file
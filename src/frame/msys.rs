@@ -2,22 +2,24 @@ use std::io::Cursor;
 
 use anyhow::Result;
 use bit_struct::*;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
 use bytes::Bytes;
 
 use crate::error::Error;
 
 use super::asdu::{
-    Asdu, CauseOfTransmission, CommonAddr, Identifier, InfoObjAddr, TypeID, VariableStruct,
+    read_info_obj_addr, read_object, Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr,
+    IntoCommonAddr, TypeID, VariableStruct, INFO_OBJ_ADDR_IRRELEVANT,
 };
 
 // 在监视方向系统信息的应用服务数据单元
 
 // COI -Cause of Initialization(初始化原因)
+// COI := CP8 {UI7[1..7], BS1[8]}
 bit_struct! {
     pub struct ObjectCOI(u8) {
-        cause: u7, // 0: 电源上电, 1:手动复位, 2:远方复位
         flag: u1,  // 是否改变了当地参数
+        cause: u7, // 0: 电源上电, 1:手动复位, 2:远方复位
     }
 }
 
@@ -26,15 +28,18 @@ bit_struct! {
 // 传送原因(cot)用于
 // 监视方向：
 // <4> := 被初始化
-async fn end_of_initialization(
+pub fn end_of_initialization(
     cot: CauseOfTransmission,
-    ca: CommonAddr,
-    ioa: InfoObjAddr,
+    ca: impl IntoCommonAddr,
     coi: ObjectCOI,
 ) -> Result<Asdu, Error> {
+    let ca = ca.into_common_addr();
+    let mut cot = cot;
+    cot.cause().set(Cause::Initialized);
+
     let variable_struct = VariableStruct::new(u1::new(0).unwrap(), u7::new(1).unwrap());
     let mut buf = vec![];
-    buf.write_u24::<LittleEndian>(ioa.raw().value())?;
+    buf.write_u24::<LittleEndian>(InfoObjAddr::new(0, INFO_OBJ_ADDR_IRRELEVANT).raw().value())?;
     buf.write_u8(coi.raw())?;
 
     Ok(Asdu {
@@ -51,11 +56,11 @@ async fn end_of_initialization(
 
 impl Asdu {
     // GetEndOfInitialization get GetEndOfInitialization for asdu when the identification [M_EI_NA_1]
-    fn get_end_of_initialization(&mut self) -> Result<(InfoObjAddr, ObjectCOI)> {
+    pub fn get_end_of_initialization(&mut self) -> Result<(InfoObjAddr, ObjectCOI)> {
         let mut rdr = Cursor::new(&self.raw);
         Ok((
-            InfoObjAddr::try_from(u24::new(rdr.read_u24::<LittleEndian>()?).unwrap()).unwrap(),
-            ObjectCOI::try_from(rdr.read_u8()?).unwrap(),
+            read_info_obj_addr(&mut rdr)?,
+            read_object::<ObjectCOI, _>(&mut rdr)?,
         ))
     }
 }
@@ -0,0 +1,261 @@
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+
+use super::{
+    asdu::{Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr, TypeID},
+    cpara::{
+        parameter_activation, parameter_float, parameter_normal, parameter_scaled,
+        ParameterActivationInfo, ParameterFloatInfo, ParameterNormalInfo, ParameterScaledInfo,
+    },
+    cproc::{
+        bits_string32_cmd, double_cmd, set_point_cmd_float, set_point_cmd_normal,
+        set_point_cmd_scaled, single_cmd, BitsString32CommandInfo, DoubleCommandInfo,
+        SetpointCommandFloatInfo, SetpointCommandNormalInfo, SetpointCommandScaledInfo,
+        SingleCommandInfo,
+    },
+    csys::{
+        clock_synchronization_cmd, counter_interrogation_cmd, delay_acquire_command,
+        interrogation_cmd, read_cmd, reset_process_cmd, ObjectQCC, ObjectQOI, ObjectQRP,
+    },
+    file::{
+        directory, file_ack, file_call, file_ready, last_section, section_ready, segment,
+        DirectoryInfo, FileAckInfo, FileCallInfo, FileReadyInfo, LastSectionInfo, SectionReadyInfo,
+        SegmentInfo,
+    },
+    mproc::{
+        double_inner, integrated_totals_inner, measured_value_float_inner,
+        measured_value_normal_inner, measured_value_scaled_inner, packed_single_point_with_scd,
+        single_inner, BinaryCounterReadingInfo, DoublePointInfo, MeasuredValueFloatInfo,
+        MeasuredValueNormalInfo, MeasuredValueScaledInfo, PackedSinglePointInfo, SinglePointInfo,
+    },
+};
+use crate::error::Error;
+
+// ASDU 的结构化信息体, 按类型标识归类.
+// 带时标与不带时标的类型共用一个变体, 时标由类型标识决定是否编码
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsduPayload {
+    // 监视方向的过程信息
+    /// [M_SP_NA_1] [M_SP_TA_1] [M_SP_TB_1] 单点信息
+    SinglePoint(Vec<SinglePointInfo>),
+    /// [M_DP_NA_1] [M_DP_TA_1] [M_DP_TB_1] 双点信息
+    DoublePoint(Vec<DoublePointInfo>),
+    /// [M_ME_NA_1] [M_ME_TA_1] [M_ME_TD_1] [M_ME_ND_1] 测量值, 规一化值
+    MeasuredNormal(Vec<MeasuredValueNormalInfo>),
+    /// [M_ME_NB_1] [M_ME_TB_1] [M_ME_TE_1] 测量值, 标度化值
+    MeasuredScaled(Vec<MeasuredValueScaledInfo>),
+    /// [M_ME_NC_1] [M_ME_TC_1] [M_ME_TF_1] 测量值, 短浮点数
+    MeasuredFloat(Vec<MeasuredValueFloatInfo>),
+    /// [M_IT_NA_1] [M_IT_TA_1] [M_IT_TB_1] 累计量
+    IntegratedTotals(Vec<BinaryCounterReadingInfo>),
+    /// [M_PS_NA_1] 带变位检出的成组单点信息
+    PackedSinglePoint(Vec<PackedSinglePointInfo>),
+
+    // 控制方向的过程信息
+    /// [C_SC_NA_1] [C_SC_TA_1] 单命令
+    SingleCommand(SingleCommandInfo),
+    /// [C_DC_NA_1] [C_DC_TA_1] 双命令
+    DoubleCommand(DoubleCommandInfo),
+    /// [C_SE_NA_1] [C_SE_TA_1] 设定命令, 规一化值
+    SetpointNormal(SetpointCommandNormalInfo),
+    /// [C_SE_NB_1] [C_SE_TB_1] 设定命令, 标度化值
+    SetpointScaled(SetpointCommandScaledInfo),
+    /// [C_SE_NC_1] [C_SE_TC_1] 设定命令, 短浮点数
+    SetpointFloat(SetpointCommandFloatInfo),
+    /// [C_BO_NA_1] [C_BO_TA_1] 比特串命令
+    BitsString32(BitsString32CommandInfo),
+
+    // 控制方向的系统命令, 信息对象地址无关(为0)
+    /// [C_IC_NA_1] 总召唤
+    Interrogation(ObjectQOI),
+    /// [C_CI_NA_1] 计数量召唤
+    CounterInterrogation(ObjectQCC),
+    /// [C_RD_NA_1] 读命令
+    Read(InfoObjAddr),
+    /// [C_CS_NA_1] 时钟同步命令, 时标无效时为 None
+    ClockSync(Option<DateTime<Utc>>),
+    /// [C_RP_NA_1] 复位进程命令
+    ResetProcess(ObjectQRP),
+    /// [C_CD_NA_1] 延时获得命令, 毫秒
+    DelayAcquire(u16),
+
+    // 控制方向的参数
+    /// [P_ME_NA_1] 测量值参数, 规一化值
+    ParameterNormal(ParameterNormalInfo),
+    /// [P_ME_NB_1] 测量值参数, 标度化值
+    ParameterScaled(ParameterScaledInfo),
+    /// [P_ME_NC_1] 测量值参数, 短浮点数
+    ParameterFloat(ParameterFloatInfo),
+    /// [P_AC_NA_1] 参数激活
+    ParameterActivation(ParameterActivationInfo),
+
+    // 文件传输
+    /// [F_FR_NA_1] 文件准备就绪
+    FileReady(FileReadyInfo),
+    /// [F_SR_NA_1] 节准备就绪
+    SectionReady(SectionReadyInfo),
+    /// [F_SC_NA_1] 召唤目录, 选择文件, 召唤文件, 召唤节
+    FileCall(FileCallInfo),
+    /// [F_LS_NA_1] 最后的节, 最后的段
+    LastSection(LastSectionInfo),
+    /// [F_AF_NA_1] 确认文件, 确认节
+    FileAck(FileAckInfo),
+    /// [F_SG_NA_1] 段
+    Segment(SegmentInfo),
+    /// [F_DR_TA_1] 目录
+    Directory(Vec<DirectoryInfo>),
+
+    /// 尚未结构化的类型, 保留原始信息体
+    Unknown(Bytes),
+}
+
+impl Asdu {
+    // DecodePayload 按类型标识解析信息体
+    pub fn decode_payload(&mut self) -> Result<AsduPayload, Error> {
+        let payload = match self.identifier.type_id {
+            TypeID::M_SP_NA_1 | TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1 => {
+                AsduPayload::SinglePoint(self.get_single_point()?)
+            }
+            TypeID::M_DP_NA_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1 => {
+                AsduPayload::DoublePoint(self.get_double_point()?)
+            }
+            TypeID::M_ME_NA_1 | TypeID::M_ME_TA_1 | TypeID::M_ME_TD_1 | TypeID::M_ME_ND_1 => {
+                AsduPayload::MeasuredNormal(self.get_measured_value_normal()?)
+            }
+            TypeID::M_ME_NB_1 | TypeID::M_ME_TB_1 | TypeID::M_ME_TE_1 => {
+                AsduPayload::MeasuredScaled(self.get_measured_value_scaled()?)
+            }
+            TypeID::M_ME_NC_1 | TypeID::M_ME_TC_1 | TypeID::M_ME_TF_1 => {
+                AsduPayload::MeasuredFloat(self.get_measured_value_float()?)
+            }
+            TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1 => {
+                AsduPayload::IntegratedTotals(self.get_integrated_totals()?)
+            }
+            TypeID::M_PS_NA_1 => AsduPayload::PackedSinglePoint(self.get_packed_single_point()?),
+
+            TypeID::C_SC_NA_1 | TypeID::C_SC_TA_1 => {
+                AsduPayload::SingleCommand(self.get_single_cmd()?)
+            }
+            TypeID::C_DC_NA_1 | TypeID::C_DC_TA_1 => {
+                AsduPayload::DoubleCommand(self.get_double_cmd()?)
+            }
+            TypeID::C_SE_NA_1 | TypeID::C_SE_TA_1 => {
+                AsduPayload::SetpointNormal(self.get_setpoint_normal_cmd()?)
+            }
+            TypeID::C_SE_NB_1 | TypeID::C_SE_TB_1 => {
+                AsduPayload::SetpointScaled(self.get_setpoint_scaled_cmd()?)
+            }
+            TypeID::C_SE_NC_1 | TypeID::C_SE_TC_1 => {
+                AsduPayload::SetpointFloat(self.get_setpoint_float_cmd()?)
+            }
+            TypeID::C_BO_NA_1 | TypeID::C_BO_TA_1 => {
+                AsduPayload::BitsString32(self.get_bits_string32_cmd()?)
+            }
+
+            TypeID::C_IC_NA_1 => AsduPayload::Interrogation(self.get_interrogation_cmd()?.1),
+            TypeID::C_CI_NA_1 => {
+                AsduPayload::CounterInterrogation(self.get_counter_interrogation_cmd()?.1)
+            }
+            TypeID::C_RD_NA_1 => AsduPayload::Read(self.get_read_cmd()?),
+            TypeID::C_CS_NA_1 => AsduPayload::ClockSync(self.get_clock_synchronization_cmd()?.1),
+            TypeID::C_RP_NA_1 => AsduPayload::ResetProcess(self.get_reset_process_cmd()?.1),
+            TypeID::C_CD_NA_1 => AsduPayload::DelayAcquire(self.get_delay_acquire_cmd()?.1),
+
+            TypeID::P_ME_NA_1 => AsduPayload::ParameterNormal(self.get_parameter_normal()?),
+            TypeID::P_ME_NB_1 => AsduPayload::ParameterScaled(self.get_parameter_scaled()?),
+            TypeID::P_ME_NC_1 => AsduPayload::ParameterFloat(self.get_parameter_float()?),
+            TypeID::P_AC_NA_1 => AsduPayload::ParameterActivation(self.get_parameter_activation()?),
+
+            TypeID::F_FR_NA_1 => AsduPayload::FileReady(self.get_file_ready()?),
+            TypeID::F_SR_NA_1 => AsduPayload::SectionReady(self.get_section_ready()?),
+            TypeID::F_SC_NA_1 => AsduPayload::FileCall(self.get_file_call()?),
+            TypeID::F_LS_NA_1 => AsduPayload::LastSection(self.get_last_section()?),
+            TypeID::F_AF_NA_1 => AsduPayload::FileAck(self.get_file_ack()?),
+            TypeID::F_SG_NA_1 => AsduPayload::Segment(self.get_segment()?),
+            TypeID::F_DR_TA_1 => AsduPayload::Directory(self.get_directory()?),
+
+            _ => AsduPayload::Unknown(self.raw.clone()),
+        };
+        Ok(payload)
+    }
+
+    // FromPayload 按数据单元标识符编码信息体, 与 decode_payload 对称.
+    // 类型标识, 顺序(SQ), 传送原因, 源站址, 公共地址均取自 identifier, 信息元素个数由信息体决定
+    pub fn from_payload(identifier: Identifier, payload: AsduPayload) -> Result<Asdu, Error> {
+        let type_id = identifier.type_id;
+        let mut vs = identifier.variable_struct;
+        let is_sequence = vs.is_sequence().get().value() == 1;
+        let ca = identifier.common_addr;
+        // 控制方向的编码函数会校验传送原因, 先以激活编码, 再替换为 identifier 的传送原因
+        let act = CauseOfTransmission::new(false, false, Cause::Activation);
+        let file = CauseOfTransmission::new(false, false, Cause::FileTransfer);
+
+        let mut asdu = match payload {
+            AsduPayload::SinglePoint(infos) => single_inner(type_id, is_sequence, act, ca, infos)?,
+            AsduPayload::DoublePoint(infos) => double_inner(type_id, is_sequence, act, ca, infos)?,
+            AsduPayload::MeasuredNormal(infos) => {
+                measured_value_normal_inner(type_id, is_sequence, act, ca, infos)?
+            }
+            AsduPayload::MeasuredScaled(infos) => {
+                measured_value_scaled_inner(type_id, is_sequence, act, ca, infos)?
+            }
+            AsduPayload::MeasuredFloat(infos) => {
+                measured_value_float_inner(type_id, is_sequence, act, ca, infos)?
+            }
+            AsduPayload::IntegratedTotals(infos) => {
+                integrated_totals_inner(type_id, is_sequence, act, ca, infos)?
+            }
+            AsduPayload::PackedSinglePoint(infos) => packed_single_point_with_scd(
+                is_sequence,
+                CauseOfTransmission::new(false, false, Cause::Spontaneous),
+                ca,
+                infos,
+            )?,
+
+            AsduPayload::SingleCommand(cmd) => single_cmd(type_id, act, ca, cmd)?,
+            AsduPayload::DoubleCommand(cmd) => double_cmd(type_id, act, ca, cmd)?,
+            AsduPayload::SetpointNormal(cmd) => set_point_cmd_normal(type_id, act, ca, cmd)?,
+            AsduPayload::SetpointScaled(cmd) => set_point_cmd_scaled(type_id, act, ca, cmd)?,
+            AsduPayload::SetpointFloat(cmd) => set_point_cmd_float(type_id, act, ca, cmd)?,
+            AsduPayload::BitsString32(cmd) => bits_string32_cmd(type_id, act, ca, cmd)?,
+
+            AsduPayload::Interrogation(qoi) => interrogation_cmd(act, ca, qoi)?,
+            AsduPayload::CounterInterrogation(qcc) => counter_interrogation_cmd(act, ca, qcc)?,
+            AsduPayload::Read(ioa) => read_cmd(act, ca, ioa)?,
+            AsduPayload::ClockSync(time) => {
+                clock_synchronization_cmd(act, ca, time.unwrap_or_else(Utc::now))?
+            }
+            AsduPayload::ResetProcess(qrp) => reset_process_cmd(act, ca, qrp)?,
+            AsduPayload::DelayAcquire(msec) => delay_acquire_command(act, ca, msec)?,
+
+            AsduPayload::ParameterNormal(p) => parameter_normal(act, ca, p)?,
+            AsduPayload::ParameterScaled(p) => parameter_scaled(act, ca, p)?,
+            AsduPayload::ParameterFloat(p) => parameter_float(act, ca, p)?,
+            AsduPayload::ParameterActivation(p) => parameter_activation(act, ca, p)?,
+
+            AsduPayload::FileReady(info) => file_ready(file, ca, info)?,
+            AsduPayload::SectionReady(info) => section_ready(file, ca, info)?,
+            AsduPayload::FileCall(info) => file_call(file, ca, info)?,
+            AsduPayload::LastSection(info) => last_section(file, ca, info)?,
+            AsduPayload::FileAck(info) => file_ack(file, ca, info)?,
+            AsduPayload::Segment(info) => segment(file, ca, info)?,
+            AsduPayload::Directory(infos) => directory(
+                is_sequence,
+                CauseOfTransmission::new(false, false, Cause::Request),
+                ca,
+                infos,
+            )?,
+
+            AsduPayload::Unknown(raw) => return Ok(Asdu { identifier, raw }),
+        };
+
+        // 信息体与类型标识不一致(如固定类型的系统命令, 参数, 文件)
+        if asdu.identifier.type_id != type_id {
+            return Err(Error::ErrTypeIDNotMatch(type_id));
+        }
+        asdu.identifier.cot = identifier.cot;
+        asdu.identifier.orig_addr = identifier.orig_addr;
+        Ok(asdu)
+    }
+}
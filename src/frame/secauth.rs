@@ -0,0 +1,630 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use bit_struct::*;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use bytes::{Buf, Bytes};
+use chrono::{DateTime, Utc};
+
+use crate::error::Error;
+
+use super::{
+    asdu::{
+        read_info_obj_addr, Asdu, Cause, CauseOfTransmission, CommonAddr, Identifier, InfoObjAddr,
+        TypeID, VariableStruct,
+    },
+    mproc::ObjectBCR,
+    time::{cp56time2a, decode_cp56time2a},
+};
+
+// IEC 62351-5 / IEC 60870-5-7 安全认证的应用服务数据单元.
+// 实现挑战-应答(S_CH_NA_1, S_RP_NA_1), 主动模式请求(S_AR_NA_1), 会话密钥的状态请求, 状态与更换
+// (S_KR_NA_1, S_KS_NA_1, S_KC_NA_1), 认证错误(S_ER_NA_1)及安全统计累计量(S_IT_TC_1);
+// 用户与更新密钥的管理(S_US_NA_1 ~ S_UC_NA_1)未实现.
+// 本 crate 不包含密码算法, MAC 由使用者实现 MacCalculator 计算
+
+// MAL MAC 算法
+pub const MAL_HMAC_SHA1_4: u8 = 1; // HMAC-SHA-1 截断为 4 字节(串行链路)
+pub const MAL_HMAC_SHA1_10: u8 = 2; // HMAC-SHA-1 截断为 10 字节(网络)
+pub const MAL_HMAC_SHA256_8: u8 = 3; // HMAC-SHA-256 截断为 8 字节(串行链路)
+pub const MAL_HMAC_SHA256_16: u8 = 4; // HMAC-SHA-256 截断为 16 字节(网络)
+pub const MAL_HMAC_SHA1_8: u8 = 5; // HMAC-SHA-1 截断为 8 字节
+pub const MAL_AES_GMAC: u8 = 6; // AES-GMAC, 12 字节
+
+// RSC 挑战原因
+pub const RSC_CRITICAL: u8 = 1; // 关键功能
+
+// KWA 密钥封装算法
+pub const KWA_AES_128: u8 = 1; // AES-128 密钥封装
+pub const KWA_AES_256: u8 = 2; // AES-256 密钥封装
+
+// KST 密钥状态
+pub const KST_OK: u8 = 1; // 正常
+pub const KST_NOT_INIT: u8 = 2; // 未初始化
+pub const KST_COMM_FAIL: u8 = 3; // 通信失败
+pub const KST_AUTH_FAIL: u8 = 4; // 认证失败
+
+// ERR 认证错误码
+pub const ERR_AUTHENTICATION_FAILED: u8 = 1; // 认证失败
+pub const ERR_NO_RESPONSE: u8 = 3; // 无应答
+pub const ERR_AGGRESSIVE_MODE_NOT_SUPPORTED: u8 = 4; // 不支持主动模式
+pub const ERR_MAC_ALGORITHM_NOT_SUPPORTED: u8 = 5; // 不支持的 MAC 算法
+pub const ERR_KEY_WRAP_NOT_SUPPORTED: u8 = 6; // 不支持的密钥封装算法
+pub const ERR_AUTHORIZATION_FAILED: u8 = 7; // 授权失败
+pub const ERR_UNKNOWN_USER: u8 = 11; // 未知用户
+
+// 算法 mal 的 MAC 长度, 未知的算法返回 None
+pub fn mac_length(mal: u8) -> Option<usize> {
+    match mal {
+        MAL_HMAC_SHA1_4 => Some(4),
+        MAL_HMAC_SHA1_10 => Some(10),
+        MAL_HMAC_SHA256_8 => Some(8),
+        MAL_HMAC_SHA256_16 => Some(16),
+        MAL_HMAC_SHA1_8 => Some(8),
+        MAL_AES_GMAC => Some(12),
+        _ => None,
+    }
+}
+
+// MAC 计算钩子: 以当前方向的会话密钥计算 MAC, 返回未截断的完整值
+pub trait MacCalculator {
+    // 使用的 MAC 算法 MAL
+    fn algorithm(&self) -> u8;
+
+    fn calculate(&self, data: &[u8]) -> Vec<u8>;
+}
+
+// 计算 parts 依次拼接后的 MAC, 并按算法截断.
+// 认证应答的 parts 为挑战报文与被挑战的关键 ASDU(均为编码后的 ASDU);
+// 主动模式请求的 parts 为最近一次挑战报文与不含 MAC 的主动模式请求
+pub fn calculate_mac(calculator: &dyn MacCalculator, parts: &[&[u8]]) -> Result<Bytes, Error> {
+    let mal = calculator.algorithm();
+    let len = mac_length(mal).ok_or_else(|| anyhow!("unsupported MAC algorithm {mal}"))?;
+    let mac = calculator.calculate(&parts.concat());
+    if mac.len() < len {
+        return Err(anyhow!("MAC length {} less than {len}", mac.len()).into());
+    }
+    Ok(Bytes::copy_from_slice(&mac[..len]))
+}
+
+// 校验 MAC, 比较时间与 MAC 的内容无关
+pub fn verify_mac(calculator: &dyn MacCalculator, parts: &[&[u8]], mac: &[u8]) -> bool {
+    let Ok(expect) = calculate_mac(calculator, parts) else {
+        return false;
+    };
+    expect.len() == mac.len() && expect.iter().zip(mac).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+// 认证挑战
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthenticationChallengeInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 挑战序号
+    pub csq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// MAC 算法
+    pub mal: u8,
+    /// 挑战原因
+    pub rsc: u8,
+    /// 挑战数据(伪随机数)
+    pub challenge: Bytes,
+}
+
+impl AuthenticationChallengeInfo {
+    pub fn new(addr: u16, csq: u32, usr: u16, mal: u8, challenge: Bytes) -> Self {
+        AuthenticationChallengeInfo {
+            ioa: InfoObjAddr::new(0, addr),
+            csq,
+            usr,
+            mal,
+            rsc: RSC_CRITICAL,
+            challenge,
+        }
+    }
+}
+
+// 认证应答
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthenticationReplyInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 挑战序号, 同被应答的挑战
+    pub csq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// MAC 值
+    pub mac: Bytes,
+}
+
+impl AuthenticationReplyInfo {
+    pub fn new(addr: u16, csq: u32, usr: u16, mac: Bytes) -> Self {
+        AuthenticationReplyInfo {
+            ioa: InfoObjAddr::new(0, addr),
+            csq,
+            usr,
+            mac,
+        }
+    }
+}
+
+// 主动模式请求: 关键 ASDU 与其 MAC 一起发送, 省去挑战-应答的往返
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggressiveModeInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 编码后的关键 ASDU
+    pub asdu: Bytes,
+    /// 挑战序号, 最近一次挑战的序号加 1
+    pub csq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// MAC 值
+    pub mac: Bytes,
+}
+
+impl AggressiveModeInfo {
+    pub fn new(addr: u16, asdu: Bytes, csq: u32, usr: u16, mac: Bytes) -> Self {
+        AggressiveModeInfo {
+            ioa: InfoObjAddr::new(0, addr),
+            asdu,
+            csq,
+            usr,
+            mac,
+        }
+    }
+}
+
+// 会话密钥状态
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyStatusInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 密钥更换序号
+    pub ksq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// 密钥封装算法
+    pub kwa: u8,
+    /// 密钥状态
+    pub kst: u8,
+    /// MAC 算法
+    pub mal: u8,
+    /// 挑战数据, 用于下一次会话密钥更换
+    pub challenge: Bytes,
+    /// 上一次更换的会话密钥计算的 MAC, 没有时为空
+    pub mac: Bytes,
+}
+
+// 会话密钥更换
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionKeyChangeInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 密钥更换序号, 同密钥状态
+    pub ksq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// 以更新密钥封装的会话密钥
+    pub wrapped_key: Bytes,
+}
+
+// 认证错误
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuthenticationErrorInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 挑战序号
+    pub csq: u32,
+    /// 用户号
+    pub usr: u16,
+    /// 关联号, 出错的连接
+    pub aid: u16,
+    /// 错误码
+    pub err: u8,
+    /// 出错时间
+    pub time: Option<DateTime<Utc>>,
+    /// 错误描述
+    pub text: Bytes,
+}
+
+// 带时标的安全统计累计量, 信息对象地址区分统计项
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecurityStatisticInfo {
+    /// 信息对象地址
+    pub ioa: InfoObjAddr,
+    /// 关联号
+    pub aid: u16,
+    /// 二进制计数器读数
+    pub bcr: ObjectBCR,
+    /// 时标
+    pub time: Option<DateTime<Utc>>,
+}
+
+fn secauth_asdu(type_id: TypeID, cot: CauseOfTransmission, ca: CommonAddr, buf: Vec<u8>) -> Asdu {
+    Asdu {
+        identifier: Identifier {
+            type_id,
+            variable_struct: VariableStruct::new(u1!(0), u7!(1)),
+            cot,
+            orig_addr: 0,
+            common_addr: ca,
+        },
+        raw: Bytes::from(buf),
+    }
+}
+
+fn check_cause(cot: CauseOfTransmission, cause: Cause) -> Result<(), Error> {
+    let mut c = cot;
+    if c.cause().get() != cause {
+        return Err(Error::ErrCmdCause(cot));
+    }
+    Ok(())
+}
+
+fn write_data(buf: &mut Vec<u8>, data: &[u8]) -> Result<(), Error> {
+    let len =
+        u16::try_from(data.len()).map_err(|_| anyhow!("data length {} too large", data.len()))?;
+    buf.write_u16::<LittleEndian>(len)?;
+    buf.extend_from_slice(data);
+    Ok(())
+}
+
+// AuthenticationChallenge send a type identification [S_CH_NA_1], 认证挑战, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <14> := 认证
+pub fn authentication_challenge(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: AuthenticationChallengeInfo,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::Authentication)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u32::<LittleEndian>(info.csq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    buf.write_u8(info.mal)?;
+    buf.write_u8(info.rsc)?;
+    write_data(&mut buf, &info.challenge)?;
+
+    Ok(secauth_asdu(TypeID::S_CH_NA_1, cot, ca, buf))
+}
+
+// AuthenticationReply send a type identification [S_RP_NA_1], 认证应答, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <14> := 认证
+pub fn authentication_reply(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: AuthenticationReplyInfo,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::Authentication)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u32::<LittleEndian>(info.csq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    write_data(&mut buf, &info.mac)?;
+
+    Ok(secauth_asdu(TypeID::S_RP_NA_1, cot, ca, buf))
+}
+
+// AggressiveModeRequest send a type identification [S_AR_NA_1], 主动模式请求, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)一般同关键 ASDU, 不做检查
+pub fn aggressive_mode_request(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: AggressiveModeInfo,
+) -> Result<Asdu, Error> {
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    write_data(&mut buf, &info.asdu)?;
+    buf.write_u32::<LittleEndian>(info.csq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    buf.extend_from_slice(&info.mac);
+
+    Ok(secauth_asdu(TypeID::S_AR_NA_1, cot, ca, buf))
+}
+
+// KeyStatusRequest send a type identification [S_KR_NA_1], 会话密钥状态请求, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 控制方向：
+// <15> := 会话密钥
+pub fn key_status_request(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    addr: u16,
+    usr: u16,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::SessionKey)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(InfoObjAddr::new(0, addr).raw().value())?;
+    buf.write_u16::<LittleEndian>(usr)?;
+
+    Ok(secauth_asdu(TypeID::S_KR_NA_1, cot, ca, buf))
+}
+
+// KeyStatus send a type identification [S_KS_NA_1], 会话密钥状态, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 监视方向：
+// <15> := 会话密钥
+pub fn key_status(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: KeyStatusInfo,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::SessionKey)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u32::<LittleEndian>(info.ksq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    buf.write_u8(info.kwa)?;
+    buf.write_u8(info.kst)?;
+    buf.write_u8(info.mal)?;
+    write_data(&mut buf, &info.challenge)?;
+    write_data(&mut buf, &info.mac)?;
+
+    Ok(secauth_asdu(TypeID::S_KS_NA_1, cot, ca, buf))
+}
+
+// SessionKeyChange send a type identification [S_KC_NA_1], 会话密钥更换, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 控制方向：
+// <15> := 会话密钥
+pub fn session_key_change(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: SessionKeyChangeInfo,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::SessionKey)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u32::<LittleEndian>(info.ksq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    write_data(&mut buf, &info.wrapped_key)?;
+
+    Ok(secauth_asdu(TypeID::S_KC_NA_1, cot, ca, buf))
+}
+
+// AuthenticationError send a type identification [S_ER_NA_1], 认证错误, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 控制方向/监视方向：
+// <14> := 认证
+pub fn authentication_error(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: AuthenticationErrorInfo,
+) -> Result<Asdu, Error> {
+    check_cause(cot, Cause::Authentication)?;
+
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u32::<LittleEndian>(info.csq)?;
+    buf.write_u16::<LittleEndian>(info.usr)?;
+    buf.write_u16::<LittleEndian>(info.aid)?;
+    buf.write_u8(info.err)?;
+    buf.extend_from_slice(&cp56time2a(info.time.unwrap_or_else(Utc::now)));
+    write_data(&mut buf, &info.text)?;
+
+    Ok(secauth_asdu(TypeID::S_ER_NA_1, cot, ca, buf))
+}
+
+// SecurityStatistic send a type identification [S_IT_TC_1], 带时标 CP56Time2a 的安全统计累计量, 只有单个信息对象(SQ = 0)
+// 传送原因(cot)用于
+// 监视方向：
+// <3> := 突发(自发)
+// <37> := 响应总计数量召唤
+pub fn security_statistic(
+    cot: CauseOfTransmission,
+    ca: CommonAddr,
+    info: SecurityStatisticInfo,
+) -> Result<Asdu, Error> {
+    let mut c = cot;
+    if !matches!(
+        c.cause().get(),
+        Cause::Spontaneous | Cause::RequestByGeneralCounter
+    ) {
+        return Err(Error::ErrCmdCause(cot));
+    }
+
+    let mut v = info.bcr.seq & 0x1f;
+    if info.bcr.cy {
+        v |= 0x20;
+    }
+    if info.bcr.ca {
+        v |= 0x40;
+    }
+    if info.bcr.invalid {
+        v |= 0x80
+    }
+    let mut buf = vec![];
+    buf.write_u24::<LittleEndian>(info.ioa.raw().value())?;
+    buf.write_u16::<LittleEndian>(info.aid)?;
+    buf.write_i32::<LittleEndian>(info.bcr.value)?;
+    buf.write_u8(v)?;
+    buf.extend_from_slice(&cp56time2a(info.time.unwrap_or_else(Utc::now)));
+
+    Ok(secauth_asdu(TypeID::S_IT_TC_1, cot, ca, buf))
+}
+
+fn read_ioa(rdr: &mut Cursor<&Bytes>) -> Result<InfoObjAddr> {
+    Ok(read_info_obj_addr(rdr)?)
+}
+
+// 长度 + 数据, 数据为 raw 的切片
+fn read_data(rdr: &mut Cursor<&Bytes>, raw: &Bytes) -> Result<Bytes> {
+    let len = rdr.read_u16::<LittleEndian>()? as usize;
+    read_bytes(rdr, raw, len)
+}
+
+fn read_bytes(rdr: &mut Cursor<&Bytes>, raw: &Bytes, len: usize) -> Result<Bytes> {
+    if rdr.remaining() < len {
+        return Err(anyhow!(
+            "data length {} exceeds remaining {}",
+            len,
+            rdr.remaining()
+        ));
+    }
+    let pos = rdr.position() as usize;
+    rdr.advance(len);
+    Ok(raw.slice(pos..pos + len))
+}
+
+impl Asdu {
+    // [S_CH_NA_1] 获取认证挑战信息体
+    pub fn get_authentication_challenge(&mut self) -> Result<AuthenticationChallengeInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let csq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let mal = rdr.read_u8()?;
+        let rsc = rdr.read_u8()?;
+        let challenge = read_data(&mut rdr, &self.raw)?;
+
+        Ok(AuthenticationChallengeInfo {
+            ioa,
+            csq,
+            usr,
+            mal,
+            rsc,
+            challenge,
+        })
+    }
+
+    // [S_RP_NA_1] 获取认证应答信息体
+    pub fn get_authentication_reply(&mut self) -> Result<AuthenticationReplyInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let csq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let mac = read_data(&mut rdr, &self.raw)?;
+
+        Ok(AuthenticationReplyInfo { ioa, csq, usr, mac })
+    }
+
+    // [S_AR_NA_1] 获取主动模式请求信息体, MAC 为其余的全部字节
+    pub fn get_aggressive_mode_request(&mut self) -> Result<AggressiveModeInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let asdu = read_data(&mut rdr, &self.raw)?;
+        let csq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let mac = self.raw.slice(rdr.position() as usize..);
+
+        Ok(AggressiveModeInfo {
+            ioa,
+            asdu,
+            csq,
+            usr,
+            mac,
+        })
+    }
+
+    // [S_KR_NA_1] 获取会话密钥状态请求的信息对象地址与用户号
+    pub fn get_key_status_request(&mut self) -> Result<(InfoObjAddr, u16)> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        Ok((ioa, usr))
+    }
+
+    // [S_KS_NA_1] 获取会话密钥状态信息体
+    pub fn get_key_status(&mut self) -> Result<KeyStatusInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let ksq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let kwa = rdr.read_u8()?;
+        let kst = rdr.read_u8()?;
+        let mal = rdr.read_u8()?;
+        let challenge = read_data(&mut rdr, &self.raw)?;
+        let mac = read_data(&mut rdr, &self.raw)?;
+
+        Ok(KeyStatusInfo {
+            ioa,
+            ksq,
+            usr,
+            kwa,
+            kst,
+            mal,
+            challenge,
+            mac,
+        })
+    }
+
+    // [S_KC_NA_1] 获取会话密钥更换信息体
+    pub fn get_session_key_change(&mut self) -> Result<SessionKeyChangeInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let ksq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let wrapped_key = read_data(&mut rdr, &self.raw)?;
+
+        Ok(SessionKeyChangeInfo {
+            ioa,
+            ksq,
+            usr,
+            wrapped_key,
+        })
+    }
+
+    // [S_ER_NA_1] 获取认证错误信息体
+    pub fn get_authentication_error(&mut self) -> Result<AuthenticationErrorInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let csq = rdr.read_u32::<LittleEndian>()?;
+        let usr = rdr.read_u16::<LittleEndian>()?;
+        let aid = rdr.read_u16::<LittleEndian>()?;
+        let err = rdr.read_u8()?;
+        let time = decode_cp56time2a(&mut rdr)?;
+        let text = read_data(&mut rdr, &self.raw)?;
+
+        Ok(AuthenticationErrorInfo {
+            ioa,
+            csq,
+            usr,
+            aid,
+            err,
+            time,
+            text,
+        })
+    }
+
+    // [S_IT_TC_1] 获取安全统计累计量信息体
+    pub fn get_security_statistic(&mut self) -> Result<SecurityStatisticInfo> {
+        let mut rdr = Cursor::new(&self.raw);
+        let ioa = read_ioa(&mut rdr)?;
+        let aid = rdr.read_u16::<LittleEndian>()?;
+        let value = rdr.read_i32::<LittleEndian>()?;
+        let b = rdr.read_u8()?;
+        let bcr = ObjectBCR {
+            invalid: b & 0x80 == 0x80,
+            ca: b & 0x40 == 0x40,
+            cy: b & 0x20 == 0x20,
+            seq: b & 0x1f,
+            value,
+        };
+        let time = decode_cp56time2a(&mut rdr)?;
+
+        Ok(SecurityStatisticInfo {
+            ioa,
+            aid,
+            bcr,
+            time,
+        })
+    }
+}
@@ -1,9 +1,9 @@
 use std::io::Cursor;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use byteorder::{LittleEndian, ReadBytesExt};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
-use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, TimeZone, Timelike, Utc};
 
 // CP56Time2a := CP56{Milliseconds,Minutes,Reserve1, Invalid, Hours, Reserve2, Summer time,
 // Day of month, Day of week, Months, Reserve3, Years, Reserve4}
@@ -40,24 +40,104 @@ use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
 // | RES4(D7)            Year(D6--D0)    | Year = 0-99
 
 pub fn cp56time2a(time: DateTime<Utc>) -> Bytes {
-    let mut buf = BytesMut::with_capacity(8);
+    Cp56Time2a::new(time).encode()
+}
 
-    let msec = (time.nanosecond() / 1000000) as u16 + time.second() as u16 * 1000;
-    let minute = time.minute() as u8;
-    let hour = time.hour() as u8;
-    let weekday = time.weekday().number_from_monday() as u8;
-    let day = time.day() as u8;
-    let month = time.month() as u8;
-    let year = (time.year() - 2000) as u8;
+// 带品质位的 CP56Time2a: 时间, 无效(IV)与夏季时间(SU), 星期由时间计算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cp56Time2a {
+    pub time: DateTime<Utc>,
+    /// IV, 时间无效(如时钟未同步)
+    pub invalid: bool,
+    /// SU, 夏季时间
+    pub summer_time: bool,
+}
 
-    buf.put_u16_le(msec);
-    buf.put_u8(minute);
-    buf.put_u8(hour);
-    buf.put_u8(weekday << 5 | day);
-    buf.put_u8(month);
-    buf.put_u8(year);
+impl Cp56Time2a {
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Cp56Time2a {
+            time,
+            invalid: false,
+            summer_time: false,
+        }
+    }
 
-    buf.freeze()
+    pub fn with_invalid(mut self, invalid: bool) -> Self {
+        self.invalid = invalid;
+        self
+    }
+
+    pub fn with_summer_time(mut self, summer_time: bool) -> Self {
+        self.summer_time = summer_time;
+        self
+    }
+
+    pub fn encode(&self) -> Bytes {
+        self.encode_with(&TimeOption::default())
+    }
+
+    // 按 option 的时区编码各时间字段
+    pub fn encode_with(&self, option: &TimeOption) -> Bytes {
+        let time = shift_to(self.time, option.offset);
+        let mut buf = BytesMut::with_capacity(8);
+
+        let msec = (time.nanosecond() / 1000000) as u16 + time.second() as u16 * 1000;
+        let minute = time.minute() as u8 | if self.invalid { 0x80 } else { 0 };
+        let hour = time.hour() as u8 | if self.summer_time { 0x80 } else { 0 };
+        let weekday = time.weekday().number_from_monday() as u8;
+        let day = time.day() as u8;
+        let month = time.month() as u8;
+        let year = (time.year() - 2000) as u8;
+
+        buf.put_u16_le(msec);
+        buf.put_u8(minute);
+        buf.put_u8(hour);
+        buf.put_u8(weekday << 5 | day);
+        buf.put_u8(month);
+        buf.put_u8(year);
+
+        buf.freeze()
+    }
+
+    // 解码 7 字节的 CP56Time2a, 保留毫秒与品质位; 不足 7 字节时返回 None, 日期非法时返回错误
+    pub fn decode(rdr: &mut Cursor<&Bytes>) -> Result<Option<Self>> {
+        if rdr.remaining() < 7 {
+            return Ok(None);
+        }
+        let millisecond = rdr.read_u16::<LittleEndian>()?;
+        let min = rdr.read_u8()?;
+        let hour = rdr.read_u8()?;
+        let day = (rdr.read_u8()? & 0x1f) as u32;
+        let month = (rdr.read_u8()? & 0x0f) as u32;
+        let year = 2000 + (rdr.read_u8()? & 0x7f) as i32;
+
+        let time = Utc
+            .with_ymd_and_hms(
+                year,
+                month,
+                day,
+                (hour & 0x1f) as u32,
+                (min & 0x3f) as u32,
+                (millisecond / 1000) as u32,
+            )
+            .single()
+            .and_then(|t| t.checked_add_signed(Duration::milliseconds((millisecond % 1000) as i64)))
+            .ok_or_else(|| anyhow!("invalid CP56Time2a {year}-{month}-{day}"))?;
+        Ok(Some(Cp56Time2a {
+            time,
+            invalid: min & 0x80 != 0,
+            summer_time: hour & 0x80 != 0,
+        }))
+    }
+
+    // 按 option 的时区解码, 返回的时间为 UTC
+    pub fn decode_with(rdr: &mut Cursor<&Bytes>, option: &TimeOption) -> Result<Option<Self>> {
+        Ok(Self::decode(rdr)?.map(|t| Cp56Time2a {
+            time: shift_from(t.time, option.offset),
+            ..t
+        }))
+    }
 }
 
 // CP24Time2a := CP24 {Milliseconds,Minutes,Reserve1, Invalid}
@@ -99,55 +179,130 @@ pub fn cp16time2a_from_msec(msec: u16) -> Bytes {
     buf.freeze()
 }
 
+// 时标解码选项: 时标字段所在的时区及 CP24Time2a 的时间重建方式.
+// CP56Time2a/CP24Time2a 只携带本地时间的各字段, 默认按 UTC 解释
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOption {
+    /// 时标字段所在时区相对 UTC 的偏移
+    pub offset: FixedOffset,
+    /// CP24Time2a 缺少的小时及以上字段的补全方式
+    pub cp24: Cp24Policy,
+}
+
+impl Default for TimeOption {
+    fn default() -> Self {
+        TimeOption {
+            offset: FixedOffset::east_opt(0).unwrap(),
+            cp24: Cp24Policy::default(),
+        }
+    }
+}
+
+impl TimeOption {
+    pub fn new(offset: FixedOffset) -> Self {
+        TimeOption {
+            offset,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_cp24(mut self, cp24: Cp24Policy) -> Self {
+        self.cp24 = cp24;
+        self
+    }
+}
+
+// CP24Time2a 只有分钟与毫秒, 小时及以上字段需要由参考时间补全
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cp24Policy {
+    /// 不补全, 解码返回错误
+    Reject,
+    /// 以当前时间为参考
+    #[default]
+    Now,
+    /// 以给定时间为参考(如报文接收时间)
+    Reference(DateTime<Utc>),
+}
+
+impl Cp24Policy {
+    // 取距参考时间最近(前后半小时内)的时间, 避免整点前后的事件被归到错误的小时
+    fn reconstruct(&self, millisecond: u16, min: u32) -> Result<DateTime<Utc>> {
+        let reference = match self {
+            Cp24Policy::Reject => return Err(anyhow!("CP24Time2a reconstruction rejected")),
+            Cp24Policy::Now => Utc::now(),
+            Cp24Policy::Reference(t) => *t,
+        };
+        if min > 59 || millisecond > 59999 {
+            return Err(anyhow!("invalid CP24Time2a {min}:{millisecond}"));
+        }
+        let hour = reference
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .ok_or_else(|| anyhow!("invalid reference time {reference}"))?;
+        let time =
+            hour + Duration::minutes(min as i64) + Duration::milliseconds(millisecond as i64);
+        let diff = time - reference;
+        if diff > Duration::minutes(30) {
+            Ok(time - Duration::hours(1))
+        } else if diff < Duration::minutes(-30) {
+            Ok(time + Duration::hours(1))
+        } else {
+            Ok(time)
+        }
+    }
+}
+
 // decode info object byte to CP56Time2a
 pub fn decode_cp56time2a(rdr: &mut Cursor<&Bytes>) -> Result<Option<DateTime<Utc>>> {
-    if rdr.remaining() < 7 {
-        return Ok(None);
-    }
-    let millisecond = rdr.read_u16::<LittleEndian>()?;
-    let msec = millisecond % 1000;
-    let sec = (millisecond / 1000) as u32;
-    let min = rdr.read_u8()?;
-    let invalid = min & 0x80;
-    let min = (min & 0x3f) as u32;
-    let hour = (rdr.read_u8()? & 0x1f) as u32;
-    let day = (rdr.read_u8()? & 0x1f) as u32;
-    let month = (rdr.read_u8()? & 0x0f) as u32;
-    let year = 2000 + (rdr.read_u8()? & 0x7f) as i32;
-
-    if invalid != 0 {
-        Ok(None)
-    } else {
-        Ok(Some(
-            Utc.with_ymd_and_hms(year, month, day, hour, min, sec)
-                .unwrap(),
-        ))
+    decode_cp56time2a_with(rdr, &TimeOption::default())
+}
+
+// 按 option 的时区解码 CP56Time2a, 保留毫秒; 时标无效时返回 None
+pub fn decode_cp56time2a_with(
+    rdr: &mut Cursor<&Bytes>,
+    option: &TimeOption,
+) -> Result<Option<DateTime<Utc>>> {
+    match Cp56Time2a::decode_with(rdr, option)? {
+        Some(t) if !t.invalid => Ok(Some(t.time)),
+        _ => Ok(None),
     }
 }
 
 // Decodecode info object byte to CP24Time2a
 pub fn decode_cp24time2a(rdr: &mut Cursor<&Bytes>) -> Result<Option<DateTime<Utc>>> {
+    decode_cp24time2a_with(rdr, &TimeOption::default())
+}
+
+// 按 option 的补全方式解码 CP24Time2a, 保留毫秒; 时标无效时返回 None.
+// 参考时间为 UTC, 时区偏移不是整小时时先换算到本地时间再补全
+pub fn decode_cp24time2a_with(
+    rdr: &mut Cursor<&Bytes>,
+    option: &TimeOption,
+) -> Result<Option<DateTime<Utc>>> {
     if rdr.remaining() < 3 {
         return Ok(None);
     }
     let millisecond = rdr.read_u16::<LittleEndian>()?;
-    let msec = millisecond % 1000;
-    let sec = (millisecond / 1000) as u32;
     let min = rdr.read_u8()?;
-    let invalid = min & 0x80;
-    let min = (min & 0x3f) as u32;
-
-    let now_utc = Utc::now();
-    let hour = now_utc.hour();
-    let day = now_utc.day();
-    let month = now_utc.month();
-    let year = now_utc.year();
-    if invalid != 0 {
-        Ok(None)
-    } else {
-        Ok(Some(
-            Utc.with_ymd_and_hms(year, month, day, hour, min, sec)
-                .unwrap(),
-        ))
+    if min & 0x80 != 0 {
+        return Ok(None);
     }
+    let policy = match option.cp24 {
+        Cp24Policy::Now => Cp24Policy::Reference(shift_to(Utc::now(), option.offset)),
+        Cp24Policy::Reference(t) => Cp24Policy::Reference(shift_to(t, option.offset)),
+        Cp24Policy::Reject => Cp24Policy::Reject,
+    };
+    let time = policy.reconstruct(millisecond, (min & 0x3f) as u32)?;
+    Ok(Some(shift_from(time, option.offset)))
+}
+
+// 把按 offset 时区的字段解释为 UTC 的时间换算为真正的 UTC 时间
+fn shift_from(time: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+    time - Duration::seconds(offset.local_minus_utc() as i64)
+}
+
+// 把 UTC 时间换算为字段按 offset 时区表示的时间
+fn shift_to(time: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+    time + Duration::seconds(offset.local_minus_utc() as i64)
 }
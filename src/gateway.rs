@@ -0,0 +1,232 @@
+use std::{collections::BTreeSet, future, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use tokio::{
+    select,
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+
+use crate::{
+    asdu::{Asdu, Cause, CommonAddr, InfoObjAddr},
+    authorizer::is_control_command,
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    subscribe::point_updates,
+    AddressMap, Client, ClientEvent, ClientHandler, DataStore, Error, ServerHandle, ServerHandler,
+};
+
+// 规约转换(前置)网关: Client 连接下级远方站(RTU), Server 向上级主站(SCADA)提供数据.
+// 远方站上送的过程信息按地址转换表(AddressMap, map 方向为远方站 -> 上级)写入共享的 DataStore, 由 DataStore 突发上送并响应上级的召唤与读命令;
+// 上级下发的控制命令按映射表改写地址后经 Client 转发, 远方站的确认与终止改回上级地址后上送.
+// 映射表中没有的地址不转发. 与远方站的连接断开后, 已上送的点置为无效(IV).
+// Gateway 作为 ServerHandler 交给 Server 的各会话, 一般以 Arc 共享
+pub struct Gateway<S> {
+    client: Client<S>,
+    store: DataStore,
+    map: AddressMap,
+    // 转发任务, 未启动时为 None
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<S> Gateway<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // client 连接远方站, store 保存上送给上级的数据, 可与其他采集任务共享
+    pub fn new(client: Client<S>, store: DataStore) -> Self {
+        Gateway {
+            client,
+            store,
+            map: AddressMap::new(),
+            task: Mutex::new(None),
+        }
+    }
+
+    // 使用完整的地址转换表, 替换之前设置的规则
+    #[must_use]
+    pub fn with_address_map(mut self, map: AddressMap) -> Self {
+        self.map = map;
+        self
+    }
+
+    // 远方站公共地址 rtu_ca 下的全部点以上级公共地址 scada_ca 转发, 信息对象地址不变
+    #[must_use]
+    pub fn with_common_addr(mut self, rtu_ca: CommonAddr, scada_ca: CommonAddr) -> Self {
+        self.map = self.map.with_common_addr(rtu_ca, scada_ca);
+        self
+    }
+
+    // 逐点映射, 优先于公共地址映射
+    #[must_use]
+    pub fn with_point(mut self, rtu: (CommonAddr, u16), scada: (CommonAddr, u16)) -> Self {
+        self.map = self.map.with_point(rtu, scada);
+        self
+    }
+
+    pub fn client(&self) -> &Client<S> {
+        &self.client
+    }
+
+    pub fn store(&self) -> &DataStore {
+        &self.store
+    }
+
+    // 启动转发任务与到远方站的连接. upstream 为上级 Server 的句柄, DataStore 关联到该句柄,
+    // 命令的确认与终止经其上送. 已启动时直接返回
+    pub async fn start(&self, upstream: ServerHandle) -> Result<(), Error> {
+        let running = self
+            .task
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|t| !t.is_finished());
+        if running {
+            return Ok(());
+        }
+        self.store.attach(upstream.clone());
+        let task = tokio::spawn(forward(
+            self.client.asdu_stream().await,
+            self.client.events(),
+            self.store.clone(),
+            self.map.clone(),
+            upstream,
+        ));
+        *self.task.lock().unwrap() = Some(task);
+        self.client.start().await
+    }
+
+    // 停止转发任务与到远方站的连接
+    pub async fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+        self.client.stop().await;
+    }
+}
+
+// 转发任务: 远方站的过程信息写入 DataStore, 命令的确认与终止上送, 连接断开后已上送的点置为无效
+async fn forward(
+    mut asdus: impl Stream<Item = Asdu> + Unpin,
+    mut events: broadcast::Receiver<ClientEvent>,
+    store: DataStore,
+    map: AddressMap,
+    upstream: ServerHandle,
+) {
+    // 已写入 DataStore 的上级地址
+    let mut forwarded = BTreeSet::new();
+    loop {
+        select! {
+            asdu = asdus.next() => {
+                let Some(asdu) = asdu else {
+                    return;
+                };
+                if is_control_command(asdu.identifier.type_id) {
+                    forward_confirm(&asdu, &map, &upstream);
+                    continue;
+                }
+                let updates = match point_updates(&asdu) {
+                    Ok(updates) => updates,
+                    Err(e) => {
+                        log::warn!("[GATEWAY] decode {asdu}: {e}");
+                        continue;
+                    }
+                };
+                let type_id = asdu.identifier.type_id;
+                for update in updates {
+                    let Some((ca, ioa)) = map.map(type_id, update.ca, update.ioa) else {
+                        continue;
+                    };
+                    let point = update.point;
+                    if let Err(e) = store.update(ca, ioa, point.value, point.quality, point.time) {
+                        log::warn!("[GATEWAY] update {ca}/{ioa}: {e}");
+                        continue;
+                    }
+                    forwarded.insert((ca, ioa));
+                }
+            }
+            event = events.recv() => match event {
+                Ok(ClientEvent::Disconnected(reason)) => {
+                    log::info!("[GATEWAY] remote disconnected ({reason}), invalidate {} points", forwarded.len());
+                    invalidate(&store, &forwarded, Utc::now());
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+}
+
+// 远方站对控制命令的确认与终止改为上级地址后上送
+fn forward_confirm(asdu: &Asdu, map: &AddressMap, upstream: &ServerHandle) {
+    let mut cot = asdu.identifier.cot;
+    if matches!(cot.cause().get(), Cause::Activation | Cause::Deactivation) {
+        return;
+    }
+    for con in map.map_asdu(asdu) {
+        if let Err(e) = upstream.broadcast_asdu(con) {
+            log::warn!("[GATEWAY] forward {asdu}: {e}");
+        }
+    }
+}
+
+// 以当前的值将点的品质置为无效
+fn invalidate(store: &DataStore, points: &BTreeSet<(CommonAddr, u16)>, time: DateTime<Utc>) {
+    for &(ca, ioa) in points {
+        let Some(point) = store.get(ca, ioa) else {
+            continue;
+        };
+        let mut quality = point.quality;
+        quality.invalid().set(true);
+        let _ = store.update(ca, ioa, point.value, quality, Some(time));
+    }
+}
+
+// 召唤, 读命令与时钟同步由 DataStore 响应; 控制命令改写地址后转发到远方站,
+// 不在映射表中的地址回复否定的未知信息对象地址, 远方站未激活时回复否定的激活确认
+impl<S> ServerHandler for Gateway<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        self.store.call_interrogation(asdu, qoi)
+    }
+
+    fn call_counter_interrogation(&self, asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
+        self.store.call_counter_interrogation(asdu, qcc)
+    }
+
+    fn call_read(&self, asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        self.store.call_read(asdu, ioa)
+    }
+
+    fn call_clock_sync(&self, asdu: Asdu, time: Option<DateTime<Utc>>) -> Self::Future {
+        self.store.call_clock_sync(asdu, time)
+    }
+
+    fn call_reset_process(&self, asdu: Asdu, qrp: ObjectQRP) -> Self::Future {
+        self.store.call_reset_process(asdu, qrp)
+    }
+
+    fn call_delay_acquire(&self, asdu: Asdu, msec: u16) -> Self::Future {
+        self.store.call_delay_acquire(asdu, msec)
+    }
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        if !is_control_command(asdu.identifier.type_id) {
+            return self.store.call(asdu);
+        }
+        let Some(cmd) = self.map.unmap_asdu(&asdu).pop() else {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::UnknownIOA)]));
+        };
+        match self.client.try_send_asdu(cmd) {
+            Ok(()) => future::ready(Ok(Vec::new())),
+            Err(e) => {
+                log::warn!("[GATEWAY] forward {asdu}: {e}");
+                future::ready(Ok(vec![asdu.mirror_negative(Cause::ActivationCon)]))
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{future::Future, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use futures::{future::BoxFuture, FutureExt};
+
+use crate::{
+    asdu::{Asdu, Cause, InfoObjAddr},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    Error, ServerHandler,
+};
+
+type ReadFn =
+    dyn Fn(Asdu, InfoObjAddr) -> BoxFuture<'static, Result<Vec<Asdu>, Error>> + Send + Sync;
+
+// 由闭包组成的 ServerHandler, 见 server_handler_fn
+pub struct ServerHandlerFn<C, I, Q> {
+    call: Arc<C>,
+    interrogation: Arc<I>,
+    counter: Arc<Q>,
+    read: Option<Arc<ReadFn>>,
+}
+
+impl<C, I, Q> Clone for ServerHandlerFn<C, I, Q> {
+    fn clone(&self) -> Self {
+        ServerHandlerFn {
+            call: self.call.clone(),
+            interrogation: self.interrogation.clone(),
+            counter: self.counter.clone(),
+            read: self.read.clone(),
+        }
+    }
+}
+
+// 以异步闭包构造 ServerHandler, 类似 tower 的 service_fn:
+// call 处理其他 ASDU(一般为控制命令), interrogation 处理总召唤/组召唤, counter 处理计数量召唤.
+// 读命令默认回复未知的信息对象地址(可用 with_read 设置), 时钟同步回复肯定的激活确认,
+// 复位进程与延时获得回复肯定的激活确认
+pub fn server_handler_fn<C, CF, I, IF, Q, QF>(
+    call: C,
+    interrogation: I,
+    counter: Q,
+) -> ServerHandlerFn<C, I, Q>
+where
+    C: Fn(Asdu) -> CF + Send + Sync + 'static,
+    CF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+    I: Fn(Asdu, ObjectQOI) -> IF + Send + Sync + 'static,
+    IF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+    Q: Fn(Asdu, ObjectQCC) -> QF + Send + Sync + 'static,
+    QF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+{
+    ServerHandlerFn {
+        call: Arc::new(call),
+        interrogation: Arc::new(interrogation),
+        counter: Arc::new(counter),
+        read: None,
+    }
+}
+
+impl<C, I, Q> ServerHandlerFn<C, I, Q> {
+    // 处理读命令, 返回空集合表示信息对象地址未知
+    #[must_use]
+    pub fn with_read<R, RF>(mut self, read: R) -> Self
+    where
+        R: Fn(Asdu, InfoObjAddr) -> RF + Send + Sync + 'static,
+        RF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+    {
+        self.read = Some(Arc::new(move |asdu, ioa| read(asdu, ioa).boxed()));
+        self
+    }
+}
+
+impl<C, CF, I, IF, Q, QF> ServerHandler for ServerHandlerFn<C, I, Q>
+where
+    C: Fn(Asdu) -> CF + Send + Sync + 'static,
+    CF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+    I: Fn(Asdu, ObjectQOI) -> IF + Send + Sync + 'static,
+    IF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+    Q: Fn(Asdu, ObjectQCC) -> QF + Send + Sync + 'static,
+    QF: Future<Output = Result<Vec<Asdu>, Error>> + Send + 'static,
+{
+    type Future = BoxFuture<'static, Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        (self.interrogation)(asdu, qoi).boxed()
+    }
+
+    fn call_counter_interrogation(&self, asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
+        (self.counter)(asdu, qcc).boxed()
+    }
+
+    fn call_read(&self, asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        match &self.read {
+            Some(read) => read(asdu, ioa),
+            None => futures::future::ready(Ok(Vec::new())).boxed(),
+        }
+    }
+
+    fn call_clock_sync(&self, asdu: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        futures::future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)])).boxed()
+    }
+
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        futures::future::ready(Ok(Vec::new())).boxed()
+    }
+
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        futures::future::ready(Ok(Vec::new())).boxed()
+    }
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        (self.call)(asdu).boxed()
+    }
+}
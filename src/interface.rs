@@ -0,0 +1,36 @@
+use crate::{
+    asdu::{Asdu, AsduParams},
+    Client, ClientHandler, Error, ServerSessionHandle,
+};
+
+// 连接的一端, 控制方向(Client)与监视方向(ServerSessionHandle)共用.
+// 生成 ASDU 的辅助代码面向该 trait 编写即可用于两个方向
+pub trait Connect {
+    // 该连接的 ASDU 参数(公共地址, 信息对象地址等字段长度)
+    fn params(&self) -> AsduParams;
+    // 发送 ASDU, 不等待: 未激活时返回 ErrNotActive, 命令通道满时返回 ErrBufferFull
+    fn send(&self, asdu: Asdu) -> Result<(), Error>;
+}
+
+impl<S> Connect for Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    fn params(&self) -> AsduParams {
+        self.asdu_params()
+    }
+
+    fn send(&self, asdu: Asdu) -> Result<(), Error> {
+        self.try_send_asdu(asdu)
+    }
+}
+
+impl Connect for ServerSessionHandle {
+    fn params(&self) -> AsduParams {
+        self.params()
+    }
+
+    fn send(&self, asdu: Asdu) -> Result<(), Error> {
+        self.send_asdu(asdu)
+    }
+}
@@ -0,0 +1,269 @@
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, TypeID, GLOBAL_COMMON_ADDR},
+    client::{Client, ClientHandler},
+    csys::{
+        counter_interrogation_cmd, interrogation_cmd, CounterGroup, FreezeMode, ObjectQCC,
+        ObjectQOI, Qcc, Qoi,
+    },
+    mproc::{
+        BinaryCounterReadingInfo, DoublePointInfo, MeasuredValueFloatInfo, MeasuredValueNormalInfo,
+        MeasuredValueScaledInfo, SinglePointInfo,
+    },
+    Error,
+};
+
+// 总召唤(站召唤或组召唤)的结果
+#[derive(Debug, Default)]
+pub struct InterrogationSnapshot {
+    /// 单点信息
+    pub single_points: Vec<SinglePointInfo>,
+    /// 双点信息
+    pub double_points: Vec<DoublePointInfo>,
+    /// 测量值, 规一化值
+    pub normals: Vec<MeasuredValueNormalInfo>,
+    /// 测量值, 标度化值
+    pub scaleds: Vec<MeasuredValueScaledInfo>,
+    /// 测量值, 短浮点数
+    pub floats: Vec<MeasuredValueFloatInfo>,
+    /// 召唤过程中收到的全部 ASDU, 包括未归类的类型
+    pub asdus: Vec<Asdu>,
+    /// 向广播地址召唤时否定确认的站及其传送原因
+    pub rejected: Vec<(CommonAddr, Cause)>,
+}
+
+impl InterrogationSnapshot {
+    fn push(&mut self, asdu: Asdu) -> Result<(), Error> {
+        let mut a = asdu.clone();
+        match a.identifier.type_id {
+            TypeID::M_SP_NA_1 | TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1 => {
+                self.single_points.extend(a.get_single_point()?)
+            }
+            TypeID::M_DP_NA_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1 => {
+                self.double_points.extend(a.get_double_point()?)
+            }
+            TypeID::M_ME_NA_1 | TypeID::M_ME_TA_1 | TypeID::M_ME_TD_1 | TypeID::M_ME_ND_1 => {
+                self.normals.extend(a.get_measured_value_normal()?)
+            }
+            TypeID::M_ME_NB_1 | TypeID::M_ME_TB_1 | TypeID::M_ME_TE_1 => {
+                self.scaleds.extend(a.get_measured_value_scaled()?)
+            }
+            TypeID::M_ME_NC_1 | TypeID::M_ME_TC_1 | TypeID::M_ME_TF_1 => {
+                self.floats.extend(a.get_measured_value_float()?)
+            }
+            _ => (),
+        }
+        self.asdus.push(asdu);
+        Ok(())
+    }
+}
+
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 总召唤: 发送 C_IC_NA_1, 收集响应站召唤/组召唤的 ASDU 直到激活终止.
+    // 向广播地址召唤时收集所有站的响应, 各站的公共地址见 InterrogationSnapshot::asdus,
+    // 何时结束见 ClientOption::with_common_addrs. 整个过程的超时时间见 ClientOption::with_interrogation_timeout
+    pub async fn general_interrogation(
+        &self,
+        ca: CommonAddr,
+        qoi: impl Into<ObjectQOI>,
+    ) -> Result<InterrogationSnapshot, Error> {
+        let qoi = qoi.into();
+        // 响应的传送原因与 QOI 取值一致: 20 响应站召唤, 21~36 响应第1~16组召唤
+        let range = u8::from(Qoi::from(qoi));
+        let mut rx = self
+            .subscribe_asdu(move |asdu| {
+                matches_ca(ca, asdu)
+                    && (asdu.identifier.type_id == TypeID::C_IC_NA_1
+                        || asdu.identifier.cot.raw() & 0x3f == range)
+            })
+            .await;
+
+        self.send_asdu(interrogation_cmd(
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            ca,
+            qoi,
+        )?)
+        .await?;
+
+        let deadline = Instant::now() + self.option().interrogation_timeout;
+        let mut snapshot = InterrogationSnapshot::default();
+        let mut procedure = Procedure::new(TypeID::C_IC_NA_1, ca, &self.option().common_addrs);
+        loop {
+            let asdu = match recv_until(&mut rx, deadline).await {
+                Err(Error::ErrTimeout) => break,
+                asdu => asdu?,
+            };
+            if asdu.identifier.type_id != TypeID::C_IC_NA_1 {
+                snapshot.push(asdu)?;
+                continue;
+            }
+            if procedure.update(&asdu)? {
+                break;
+            }
+        }
+        snapshot.rejected = procedure.finish()?;
+        Ok(snapshot)
+    }
+}
+
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 计数量召唤: 发送 C_CI_NA_1, 等待激活确认与激活终止.
+    // QCC 的 FRZ 为 0(读)时, 收集响应计数量召唤的累计量; 为冻结/复位时, 计数量随后以突发方式上送, 返回空集合.
+    // 向广播地址召唤时收集所有站的累计量, 何时结束见 ClientOption::with_common_addrs; 否定确认的站只记录日志
+    pub async fn counter_interrogation(
+        &self,
+        ca: CommonAddr,
+        qcc: impl Into<ObjectQCC>,
+    ) -> Result<Vec<BinaryCounterReadingInfo>, Error> {
+        let qcc = qcc.into();
+        let Qcc { request, freeze } = Qcc::from(qcc);
+        let read = freeze == FreezeMode::Read;
+        // 响应的传送原因: 37 响应总计数量召唤, 38~41 响应第1~4组计数量召唤
+        let cause = match request {
+            CounterGroup::General => 37,
+            request => 37 + u8::from(request),
+        };
+        let mut rx = self
+            .subscribe_asdu(move |asdu| {
+                matches_ca(ca, asdu)
+                    && (asdu.identifier.type_id == TypeID::C_CI_NA_1
+                        || (read && asdu.identifier.cot.raw() & 0x3f == cause))
+            })
+            .await;
+
+        self.send_asdu(counter_interrogation_cmd(
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            ca,
+            qcc,
+        )?)
+        .await?;
+
+        let deadline = Instant::now() + self.option().interrogation_timeout;
+        let mut counters = Vec::new();
+        let mut procedure = Procedure::new(TypeID::C_CI_NA_1, ca, &self.option().common_addrs);
+        loop {
+            let mut asdu = match recv_until(&mut rx, deadline).await {
+                Err(Error::ErrTimeout) => break,
+                asdu => asdu?,
+            };
+            match asdu.identifier.type_id {
+                TypeID::C_CI_NA_1 if procedure.update(&asdu)? => break,
+                TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1 => {
+                    counters.extend(asdu.get_integrated_totals()?)
+                }
+                _ => (),
+            }
+        }
+        procedure.finish()?;
+        Ok(counters)
+    }
+}
+
+pub(crate) async fn recv_until(
+    rx: &mut mpsc::UnboundedReceiver<Asdu>,
+    deadline: Instant,
+) -> Result<Asdu, Error> {
+    match tokio::time::timeout_at(deadline, rx.recv()).await {
+        Ok(Some(asdu)) => Ok(asdu),
+        Ok(None) => Err(Error::ErrUseClosedConnection),
+        Err(_) => Err(Error::ErrTimeout),
+    }
+}
+
+// 召唤的公共地址为广播地址时接受任意站的响应
+fn matches_ca(ca: CommonAddr, asdu: &Asdu) -> bool {
+    ca == GLOBAL_COMMON_ADDR || asdu.identifier.common_addr == ca
+}
+
+// 召唤过程的状态. 向广播地址召唤时各站以自己的公共地址确认与终止, 某站的否定确认只结束该站;
+// 预期的站全部结束后召唤结束, 未设置预期的站时持续到超时
+struct Procedure {
+    type_id: TypeID,
+    broadcast: bool,
+    expected: Vec<CommonAddr>,
+    // 已确认, 尚未激活终止的站
+    active: Vec<CommonAddr>,
+    // 已激活终止的站
+    terminated: Vec<CommonAddr>,
+    // 否定确认的站及其传送原因
+    rejected: Vec<(CommonAddr, Cause)>,
+}
+
+impl Procedure {
+    fn new(type_id: TypeID, ca: CommonAddr, expected: &[CommonAddr]) -> Self {
+        let broadcast = ca == GLOBAL_COMMON_ADDR;
+        Procedure {
+            type_id,
+            broadcast,
+            expected: if broadcast {
+                expected.to_vec()
+            } else {
+                Vec::new()
+            },
+            active: Vec::new(),
+            terminated: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    // 处理召唤命令的镜像报文, 返回召唤是否结束
+    fn update(&mut self, asdu: &Asdu) -> Result<bool, Error> {
+        let ca = asdu.identifier.common_addr;
+        if !self.broadcast {
+            let terminated = check_procedure(asdu)?;
+            if terminated {
+                self.terminated.push(ca);
+            }
+            return Ok(terminated);
+        }
+        let mut cot = asdu.identifier.cot;
+        let cause = cot.cause().get();
+        self.active.retain(|active| *active != ca);
+        if cot.is_rejected() {
+            log::warn!(
+                "[RX] broadcast {:?} rejected by {ca}: {cause:?}",
+                self.type_id
+            );
+            self.rejected.push((ca, cause));
+        } else if cause == Cause::ActivationTerm {
+            self.terminated.push(ca);
+        } else {
+            self.active.push(ca);
+        }
+        Ok(!self.expected.is_empty() && self.expected.iter().all(|ca| self.is_finished(*ca)))
+    }
+
+    fn is_finished(&self, ca: CommonAddr) -> bool {
+        self.terminated.contains(&ca) || self.rejected.iter().any(|(rejected, _)| *rejected == ca)
+    }
+
+    // 召唤结束或超时后的结果: 仍有站未结束时为超时, 所有站都否定确认时为否定确认, 否则返回否定确认的站
+    fn finish(self) -> Result<Vec<(CommonAddr, Cause)>, Error> {
+        let pending =
+            !self.active.is_empty() || self.expected.iter().any(|ca| !self.is_finished(*ca));
+        if pending || (self.terminated.is_empty() && self.rejected.is_empty()) {
+            return Err(Error::ErrTimeout);
+        }
+        match (self.terminated.is_empty(), self.rejected.first()) {
+            (true, Some((_, cause))) => Err(Error::ErrNegativeConfirm(self.type_id, *cause)),
+            _ => Ok(self.rejected),
+        }
+    }
+}
+
+// 检查召唤命令的镜像报文, 返回是否已激活终止
+pub(crate) fn check_procedure(asdu: &Asdu) -> Result<bool, Error> {
+    let mut cot = asdu.identifier.cot;
+    let cause = cot.cause().get();
+    if cot.is_rejected() {
+        return Err(Error::ErrNegativeConfirm(asdu.identifier.type_id, cause));
+    }
+    Ok(cause == Cause::ActivationTerm)
+}
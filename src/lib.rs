@@ -1,13 +1,97 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
+mod access;
+mod address_map;
+mod asdu_builder;
+mod authorizer;
+mod cache;
+pub mod capture;
 mod client;
+mod client_builder;
 mod codec;
+mod command;
+mod command_queue;
+pub mod config;
+pub mod conformance;
+mod datastore;
 mod error;
+mod file_transfer;
 mod frame;
+pub mod gateway;
+mod handler_fn;
+mod interface;
+mod interrogation;
+pub mod link101;
+mod link_state;
+mod metrics;
+mod observer;
+mod pacing;
+mod pool;
+#[cfg(feature = "proxy")]
+mod proxy;
+mod quality;
+mod queue;
+mod reconnect;
+mod redundancy;
+pub mod replay;
+mod role;
+mod router;
+mod scale;
+mod scheduler;
+#[cfg(feature = "serde")]
+mod serde_impl;
 mod server;
+mod session;
+mod socket;
+mod stats;
+mod subscribe;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "tls")]
+mod tls;
+mod trace;
+mod transport;
+mod watchdog;
 
+pub use access::{AccessControl, Cidr, ConnectFilter, Decision, PeerPolicy};
+pub use address_map::AddressMap;
+pub use asdu_builder::AsduBuilder;
+pub use authorizer::{Authorization, CommandAgePolicy, CommandAuthorizer, CommandRequest};
+pub use cache::PointCache;
 pub use client::*;
+pub use client_builder::*;
 pub use codec::*;
+pub use command::*;
+pub use command_queue::QueuedCommand;
+pub use datastore::*;
 pub use error::*;
+pub use file_transfer::*;
 pub use frame::*;
+pub use handler_fn::{server_handler_fn, ServerHandlerFn};
+pub use interface::Connect;
+pub use interrogation::*;
+pub use link_state::{LinkEvent, LinkState};
+#[cfg(feature = "metrics")]
+pub use metrics::describe_metrics;
+pub use observer::FrameObserver;
+pub use pacing::Pacing;
+pub use pool::{ClientPool, PoolEvent, RemoteHealth};
+#[cfg(feature = "proxy")]
+pub use proxy::{ProxyConfig, ProxyKind};
+pub use quality::{Quality, QualityFilter};
+pub use queue::*;
+pub use reconnect::*;
+pub use redundancy::{RedundancyGroup, Switchover};
+pub use role::Role;
+pub use router::CaRouter;
+pub use scale::{Nva, ScaleTable};
+pub use scheduler::Scheduler;
 pub use server::*;
+pub use session::{ServerHandle, ServerSessionHandle, SessionContext};
+pub use socket::{Keepalive, SocketOption};
+pub use stats::{FrameCount, Stats};
+pub use subscribe::PointUpdate;
+#[cfg(feature = "tls")]
+pub use tls::*;
+pub use transport::*;
+pub use watchdog::TestFrPolicy;
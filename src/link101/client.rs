@@ -0,0 +1,293 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    select,
+    sync::{mpsc, Mutex},
+    time::timeout,
+};
+use tokio_util::codec::Framed;
+
+use crate::{
+    asdu::{Asdu, AsduParams},
+    ClientHandler, Error, Transport,
+};
+
+use super::{
+    codec::Ft12Codec,
+    frame::{
+        ControlField, Ft12Frame, PRM_REQUEST_CLASS1, PRM_REQUEST_CLASS2, PRM_REQUEST_LINK_STATUS,
+        PRM_RESET_LINK, PRM_USER_DATA_CONFIRMED, SEC_ACK, SEC_LINK_STATUS, SEC_NACK, SEC_NO_DATA,
+        SEC_USER_DATA,
+    },
+};
+
+// IEC 101 非平衡传输主站配置
+#[derive(Debug, Clone)]
+pub struct Link101Option {
+    // 从站链路地址
+    pub(crate) link_addr: u16,
+    // 链路地址域长度 [0, 2]
+    pub(crate) link_addr_size: usize,
+    // ASDU 各字段长度
+    pub(crate) asdu_params: AsduParams,
+    // 请求 2 级用户数据的轮询周期
+    pub(crate) poll_interval: Duration,
+    // 等待从站响应的超时时间
+    pub(crate) response_timeout: Duration,
+    // 超时后重发的次数
+    pub(crate) max_retries: u32,
+}
+
+impl Link101Option {
+    pub fn new(link_addr: u16) -> Self {
+        Link101Option {
+            link_addr,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_link_addr_size(mut self, size: usize) -> Self {
+        self.link_addr_size = size;
+        self
+    }
+
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        self.asdu_params = params;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+}
+
+impl Default for Link101Option {
+    fn default() -> Self {
+        Link101Option {
+            link_addr: 1,
+            link_addr_size: 1,
+            // IEC 101 常用: 传送原因 1 字节, 公共地址 1 字节, 信息对象地址 2 字节
+            asdu_params: AsduParams {
+                cot_size: 1,
+                ca_size: 1,
+                ioa_size: 2,
+            },
+            poll_interval: Duration::from_millis(500),
+            response_timeout: Duration::from_secs(1),
+            max_retries: 3,
+        }
+    }
+}
+
+// IEC 101 非平衡传输的主站(启动站), 通过串口(RS-232/485)或任意字节流轮询从站,
+// 收到的 ASDU 交给与 104 客户端相同的 ClientHandler 处理
+pub struct Serial101Client<S> {
+    op: Link101Option,
+    handler: S,
+    sender: Arc<Mutex<Option<mpsc::UnboundedSender<Asdu>>>>,
+}
+
+impl<S> Serial101Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    pub fn new(handler: S, option: Link101Option) -> Self {
+        Serial101Client {
+            op: option,
+            handler,
+            sender: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // 在 transport 上启动链路: 请求链路状态, 复位远方链路, 然后周期轮询用户数据
+    pub async fn start<T>(&self, transport: T) -> Result<(), Error>
+    where
+        T: Transport,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.sender.lock().await = Some(tx.clone());
+        let op = self.op.clone();
+        let handler = self.handler.clone();
+        tokio::spawn(async move {
+            if let Err(e) = link_loop(transport, op, handler, tx, rx).await {
+                log::error!("[101] link error: {e}");
+            }
+        });
+        Ok(())
+    }
+
+    // 打开串口并启动链路, IEC 101 一般为 8 位数据位, 偶校验, 1 位停止位
+    #[cfg(feature = "serial")]
+    pub async fn open(&self, path: &str, baud_rate: u32) -> Result<(), Error> {
+        use tokio_serial::SerialPortBuilderExt;
+
+        let port = tokio_serial::new(path, baud_rate)
+            .data_bits(tokio_serial::DataBits::Eight)
+            .parity(tokio_serial::Parity::Even)
+            .stop_bits(tokio_serial::StopBits::One)
+            .open_native_async()
+            .map_err(std::io::Error::from)?;
+        self.start(port).await
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        if let Some(sender) = &*self.sender.lock().await {
+            return !sender.is_closed();
+        }
+        false
+    }
+
+    // 以发送/确认用户数据发送 ASDU
+    pub async fn send_asdu(&self, asdu: Asdu) -> Result<(), Error> {
+        match &*self.sender.lock().await {
+            Some(sender) if !sender.is_closed() => {
+                sender.send(asdu).map_err(|_| Error::ErrUseClosedConnection)
+            }
+            _ => Err(Error::ErrUseClosedConnection),
+        }
+    }
+}
+
+struct Link<T> {
+    framed: Framed<T, Ft12Codec>,
+    op: Link101Option,
+    // 下一个帧计数有效的帧使用的 FCB
+    fcb: bool,
+}
+
+impl<T> Link<T>
+where
+    T: Transport,
+{
+    // 发送启动帧并等待从站响应, 超时后以相同的 FCB 重发
+    async fn request(&mut self, frame: Ft12Frame) -> Result<Ft12Frame, Error> {
+        for attempt in 0..=self.op.max_retries {
+            if attempt > 0 {
+                log::warn!("[101] response timeout, retry {attempt}");
+            }
+            log::debug!("[101] [TX] {frame:?}");
+            self.framed.send(frame.clone()).await?;
+            match timeout(self.op.response_timeout, self.framed.next()).await {
+                Ok(Some(resp)) => {
+                    let resp = resp?;
+                    log::debug!("[101] [RX] {resp:?}");
+                    return Ok(resp);
+                }
+                Ok(None) => return Err(Error::ErrUseClosedConnection),
+                Err(_) => continue,
+            }
+        }
+        Err(Error::ErrTimeout)
+    }
+
+    async fn fixed(&mut self, function: u8, fcv: bool) -> Result<Ft12Frame, Error> {
+        let fcb = fcv.then_some(self.fcb);
+        let frame = Ft12Frame::Fixed {
+            control: ControlField::primary(function, fcb),
+            link_addr: self.op.link_addr,
+        };
+        let resp = self.request(frame).await?;
+        if fcv {
+            self.fcb = !self.fcb;
+        }
+        Ok(resp)
+    }
+
+    async fn user_data(&mut self, asdu: Asdu) -> Result<(), Error> {
+        let frame = Ft12Frame::Variable {
+            control: ControlField::primary(PRM_USER_DATA_CONFIRMED, Some(self.fcb)),
+            link_addr: self.op.link_addr,
+            asdu,
+        };
+        let resp = self.request(frame).await?;
+        self.fcb = !self.fcb;
+        expect(resp, SEC_ACK)
+    }
+}
+
+// 单个字符 E5 等同于肯定确认或无所请求的数据
+fn expect(resp: Ft12Frame, function: u8) -> Result<(), Error> {
+    let Some(mut control) = resp.control() else {
+        return Ok(());
+    };
+    match control.function_code() {
+        f if f == function => Ok(()),
+        SEC_NACK => Err(Error::ErrAnyHow(anyhow::anyhow!("link busy"))),
+        f => Err(Error::ErrAnyHow(anyhow::anyhow!(
+            "unexpected link function code {f}, expect {function}"
+        ))),
+    }
+}
+
+async fn link_loop<T, S>(
+    transport: T,
+    op: Link101Option,
+    handler: S,
+    tx: mpsc::UnboundedSender<Asdu>,
+    mut rx: mpsc::UnboundedReceiver<Asdu>,
+) -> Result<(), Error>
+where
+    T: Transport,
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    let codec = Ft12Codec::new(op.link_addr_size, op.asdu_params);
+    let mut poll = tokio::time::interval(op.poll_interval);
+    let mut link = Link {
+        framed: Framed::new(transport, codec),
+        op,
+        fcb: true,
+    };
+
+    // 链路初始化
+    let resp = link.fixed(PRM_REQUEST_LINK_STATUS, false).await?;
+    expect(resp, SEC_LINK_STATUS)?;
+    let resp = link.fixed(PRM_RESET_LINK, false).await?;
+    expect(resp, SEC_ACK)?;
+    // 复位后第一个帧计数有效的帧 FCB 为 1
+    link.fcb = true;
+    log::info!("[101] link {} initialized", link.op.link_addr);
+
+    loop {
+        select! {
+            asdu = rx.recv() => match asdu {
+                Some(asdu) => link.user_data(asdu).await?,
+                None => return Ok(()),
+            },
+            _ = poll.tick() => {
+                // ACD 置位时继续请求 1 级用户数据
+                let mut class = PRM_REQUEST_CLASS2;
+                loop {
+                    let resp = link.fixed(class, true).await?;
+                    let Some(mut control) = resp.control() else {
+                        break;
+                    };
+                    match resp {
+                        Ft12Frame::Variable { asdu, .. } if control.function_code() == SEC_USER_DATA => {
+                            for asdu in handler.call(asdu).await? {
+                                let _ = tx.send(asdu);
+                            }
+                        }
+                        _ if control.function_code() == SEC_NO_DATA => (),
+                        _ => expect(resp, SEC_USER_DATA)?,
+                    }
+                    if !control.acd() {
+                        break;
+                    }
+                    class = PRM_REQUEST_CLASS1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{anyhow, Result};
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::asdu::{Asdu, AsduParams};
+
+use super::frame::{
+    checksum, ControlField, Ft12Frame, END_FRAME, SINGLE_CHAR, START_FIXED, START_VARIABLE,
+};
+
+// FT1.2 帧编解码, link_addr_size 为链路地址域长度 [0, 2], params 为 ASDU 各字段长度
+#[derive(Debug, PartialEq)]
+pub struct Ft12Codec {
+    link_addr_size: usize,
+    params: AsduParams,
+}
+
+impl Ft12Codec {
+    pub fn new(link_addr_size: usize, params: AsduParams) -> Self {
+        Ft12Codec {
+            link_addr_size,
+            params,
+        }
+    }
+
+    fn put_link_addr(&self, buf: &mut BytesMut, link_addr: u16) {
+        match self.link_addr_size {
+            0 => (),
+            1 => buf.put_u8(link_addr as u8),
+            _ => buf.put_u16_le(link_addr),
+        }
+    }
+
+    fn get_link_addr(&self, data: &[u8]) -> u16 {
+        match self.link_addr_size {
+            0 => 0,
+            1 => data[0] as u16,
+            _ => u16::from_le_bytes([data[0], data[1]]),
+        }
+    }
+}
+
+impl Encoder<Ft12Frame> for Ft12Codec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, frame: Ft12Frame, buf: &mut BytesMut) -> Result<()> {
+        match frame {
+            Ft12Frame::SingleChar => buf.put_u8(SINGLE_CHAR),
+            Ft12Frame::Fixed { control, link_addr } => {
+                let mut data = BytesMut::with_capacity(3);
+                data.put_u8(control.raw());
+                self.put_link_addr(&mut data, link_addr);
+
+                buf.put_u8(START_FIXED);
+                buf.extend_from_slice(&data);
+                buf.put_u8(checksum(&data));
+                buf.put_u8(END_FRAME);
+            }
+            Ft12Frame::Variable {
+                control,
+                link_addr,
+                asdu,
+            } => {
+                let mut data = BytesMut::with_capacity(255);
+                data.put_u8(control.raw());
+                self.put_link_addr(&mut data, link_addr);
+                data.extend(asdu.to_bytes_with(&self.params)?);
+                if data.len() > u8::MAX as usize {
+                    return Err(anyhow!("FT1.2 frame length {} exceeds 255", data.len()));
+                }
+
+                buf.put_u8(START_VARIABLE);
+                buf.put_u8(data.len() as u8);
+                buf.put_u8(data.len() as u8);
+                buf.put_u8(START_VARIABLE);
+                buf.extend_from_slice(&data);
+                buf.put_u8(checksum(&data));
+                buf.put_u8(END_FRAME);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for Ft12Codec {
+    type Item = Ft12Frame;
+
+    type Error = anyhow::Error;
+
+    // 串行链路上可能出现干扰字节, 校验失败时丢弃启动字符并重新同步
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>> {
+        loop {
+            let Some(&start) = buf.first() else {
+                return Ok(None);
+            };
+            match start {
+                SINGLE_CHAR => {
+                    buf.advance(1);
+                    return Ok(Some(Ft12Frame::SingleChar));
+                }
+                START_FIXED => {
+                    let len = 4 + self.link_addr_size;
+                    if buf.len() < len {
+                        return Ok(None);
+                    }
+                    let data = &buf[1..len - 2];
+                    if buf[len - 1] != END_FRAME || buf[len - 2] != checksum(data) {
+                        log::warn!("[FT1.2] invalid fixed frame {:02X?}", &buf[..len]);
+                        buf.advance(1);
+                        continue;
+                    }
+                    let control = ControlField::try_from(data[0]).unwrap();
+                    let link_addr = self.get_link_addr(&data[1..]);
+                    buf.advance(len);
+                    return Ok(Some(Ft12Frame::Fixed { control, link_addr }));
+                }
+                START_VARIABLE => {
+                    if buf.len() < 4 {
+                        return Ok(None);
+                    }
+                    let l = buf[1] as usize;
+                    if buf[2] != buf[1] || buf[3] != START_VARIABLE || l < 1 + self.link_addr_size {
+                        log::warn!("[FT1.2] invalid variable frame header {:02X?}", &buf[..4]);
+                        buf.advance(1);
+                        continue;
+                    }
+                    let len = 4 + l + 2;
+                    if buf.len() < len {
+                        return Ok(None);
+                    }
+                    let data = &buf[4..4 + l];
+                    if buf[len - 1] != END_FRAME || buf[len - 2] != checksum(data) {
+                        log::warn!("[FT1.2] invalid variable frame {:02X?}", &buf[..len]);
+                        buf.advance(1);
+                        continue;
+                    }
+                    let control = ControlField::try_from(data[0]).unwrap();
+                    let link_addr = self.get_link_addr(&data[1..]);
+                    let asdu_data = buf
+                        .split_to(len)
+                        .freeze()
+                        .slice(5 + self.link_addr_size..len - 2);
+                    match Asdu::from_bytes_with(asdu_data, &self.params) {
+                        Ok(asdu) => {
+                            return Ok(Some(Ft12Frame::Variable {
+                                control,
+                                link_addr,
+                                asdu,
+                            }))
+                        }
+                        Err(e) => {
+                            log::warn!("[FT1.2] invalid asdu: {e}");
+                            continue;
+                        }
+                    }
+                }
+                _ => {
+                    buf.advance(1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bit_struct::*;
+
+use crate::asdu::Asdu;
+
+// FT1.2 帧格式 (IEC 60870-5-1, 60870-5-2)
+//
+// 单个字符:   | E5 |
+// 固定帧长帧: | 10 | C | A | CS | 16 |
+// 可变帧长帧: | 68 | L | L | 68 | C | A | ASDU | CS | 16 |
+//
+// L 为控制域, 链路地址域与 ASDU 的总长度, CS 为控制域, 链路地址域与 ASDU 的算术和(模 256),
+// 链路地址域 A 的长度为 0, 1 或 2 个字节
+
+pub const SINGLE_CHAR: u8 = 0xE5; // 单个字符, 肯定确认或无所请求的数据
+pub const START_FIXED: u8 = 0x10; // 固定帧长帧启动字符
+pub const START_VARIABLE: u8 = 0x68; // 可变帧长帧启动字符
+pub const END_FRAME: u8 = 0x16; // 结束字符
+
+// 启动站(PRM = 1)功能码
+pub const PRM_RESET_LINK: u8 = 0; // 复位远方链路
+pub const PRM_RESET_PROCESS: u8 = 1; // 复位用户进程
+pub const PRM_TEST_LINK: u8 = 2; // 链路测试(平衡传输)
+pub const PRM_USER_DATA_CONFIRMED: u8 = 3; // 发送/确认用户数据
+pub const PRM_USER_DATA_NO_REPLY: u8 = 4; // 发送/无回答用户数据
+pub const PRM_REQUEST_LINK_STATUS: u8 = 9; // 请求链路状态
+pub const PRM_REQUEST_CLASS1: u8 = 10; // 请求 1 级用户数据
+pub const PRM_REQUEST_CLASS2: u8 = 11; // 请求 2 级用户数据
+
+// 从动站(PRM = 0)功能码
+pub const SEC_ACK: u8 = 0; // 肯定确认
+pub const SEC_NACK: u8 = 1; // 否定确认, 链路忙
+pub const SEC_USER_DATA: u8 = 8; // 以数据响应请求帧
+pub const SEC_NO_DATA: u8 = 9; // 无所请求的数据
+pub const SEC_LINK_STATUS: u8 = 11; // 链路状态
+pub const SEC_LINK_NOT_FUNCTIONING: u8 = 14; // 链路服务未工作
+pub const SEC_LINK_NOT_IMPLEMENTED: u8 = 15; // 链路服务未完成
+
+// 控制域
+bit_struct! {
+    pub struct ControlField(u8) {
+        dir: bool,      // 平衡传输时的传输方向, 非平衡传输时保留
+        prm: bool,      // 1: 启动站发出, 0: 从动站发出
+        fcb: bool,      // 启动站: 帧计数位 FCB; 从动站: 要求访问位 ACD
+        fcv: bool,      // 启动站: 帧计数有效位 FCV; 从动站: 数据流控制位 DFC
+        function: u4,   // 功能码
+    }
+}
+
+impl ControlField {
+    // 启动站的控制域, fcb 为 None 时帧计数位无效
+    pub fn primary(function: u8, fcb: Option<bool>) -> Self {
+        ControlField::new(
+            false,
+            true,
+            fcb.unwrap_or(false),
+            fcb.is_some(),
+            u4::new(function & 0x0f).unwrap(),
+        )
+    }
+
+    // 从动站的控制域, acd 表示有 1 级用户数据
+    pub fn secondary(function: u8, acd: bool) -> Self {
+        ControlField::new(false, false, acd, false, u4::new(function & 0x0f).unwrap())
+    }
+
+    pub fn function_code(&mut self) -> u8 {
+        self.function().get().value()
+    }
+
+    // 从动站的要求访问位 ACD
+    pub fn acd(&mut self) -> bool {
+        self.fcb().get()
+    }
+}
+
+// FT1.2 帧
+#[derive(Debug, Clone)]
+pub enum Ft12Frame {
+    /// 单个字符 E5
+    SingleChar,
+    /// 固定帧长帧
+    Fixed {
+        control: ControlField,
+        link_addr: u16,
+    },
+    /// 可变帧长帧, 携带 ASDU
+    Variable {
+        control: ControlField,
+        link_addr: u16,
+        asdu: Asdu,
+    },
+}
+
+impl Ft12Frame {
+    // 帧的控制域, 单个字符没有控制域
+    pub fn control(&self) -> Option<ControlField> {
+        match self {
+            Ft12Frame::SingleChar => None,
+            Ft12Frame::Fixed { control, .. } | Ft12Frame::Variable { control, .. } => {
+                Some(*control)
+            }
+        }
+    }
+}
+
+// 帧校验和: 控制域, 地址域与用户数据的算术和(模 256)
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
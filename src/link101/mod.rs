@@ -0,0 +1,9 @@
+// IEC 60870-5-101 串行链路层: FT1.2 帧格式, 非平衡传输主站
+// ASDU 的编解码与 104 共用, 字段长度由 AsduParams 配置
+mod client;
+pub mod codec;
+pub mod frame;
+
+pub use client::*;
+pub use codec::Ft12Codec;
+pub use frame::{ControlField, Ft12Frame};
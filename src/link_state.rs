@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+// 客户端连接的链路状态.
+//
+// 状态转换:
+// Closed     --Connect-->          Connecting   启动连接任务或重连
+// Connecting --Connected-->        Connected    传输层连接建立
+// Connected  --StartDtSent-->      Pending      控制站发送 STARTDT
+// Pending    --StartDtConfirmed--> Active       收到 STARTDT 确认
+// Connected  --StartDtConfirmed--> Active       被控站收到并确认对端的 STARTDT
+// Active     --StopDtSent-->       Stopping     控制站发送 STOPDT
+// Connected  --StopDtSent-->       Stopping
+// Stopping   --StopDtConfirmed-->  Connected    收到 STOPDT 确认
+// Active     --StopDtConfirmed-->  Connected    被控站收到并确认对端的 STOPDT
+// Pending    --Timeout-->          Closed       STARTDT 在 t1 内未被确认
+// Stopping   --Timeout-->          Closed       STOPDT 在 t1 内未被确认
+// Connected  --Timeout-->          Closed       测试帧在 t1 内未被确认
+// Active     --Timeout-->          Closed       I 帧或测试帧在 t1 内未被确认
+// 任意状态   --Disconnected-->     Closed       连接断开, 连接失败且不再重连, 或客户端停止
+// 其他组合不改变状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LinkState {
+    /// 正在建立传输层连接, 包括等待重连
+    Connecting,
+    /// 已连接, 数据传输未激活
+    Connected,
+    /// 已发送 STARTDT, 等待确认
+    Pending,
+    /// 数据传输激活
+    Active,
+    /// 已发送 STOPDT, 等待确认. 确认之前仍可收发 I 帧
+    Stopping,
+    /// 未启动, 已停止或连接断开
+    #[default]
+    Closed,
+}
+
+// 驱动链路状态转换的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkEvent {
+    /// 开始建立连接
+    Connect,
+    /// 传输层连接建立
+    Connected,
+    /// 发送 STARTDT
+    StartDtSent,
+    /// 收到 STARTDT 确认, 或被控站确认了对端的 STARTDT
+    StartDtConfirmed,
+    /// 发送 STOPDT
+    StopDtSent,
+    /// 收到 STOPDT 确认, 或被控站确认了对端的 STOPDT
+    StopDtConfirmed,
+    /// STARTDT/STOPDT 在 t1 内未被确认
+    Timeout,
+    /// 连接断开
+    Disconnected,
+}
+
+impl LinkState {
+    // 按转换规则返回下一个状态, 不允许的转换返回 None
+    pub fn transition(self, event: LinkEvent) -> Option<LinkState> {
+        use LinkEvent as E;
+        use LinkState as S;
+        let next = match (self, event) {
+            (_, E::Disconnected) => S::Closed,
+            (S::Closed | S::Connecting, E::Connect) => S::Connecting,
+            (S::Connecting, E::Connected) => S::Connected,
+            (S::Connected, E::StartDtSent) => S::Pending,
+            (S::Connected | S::Pending, E::StartDtConfirmed) => S::Active,
+            (S::Connected | S::Active, E::StopDtSent) => S::Stopping,
+            (S::Active | S::Stopping, E::StopDtConfirmed) => S::Connected,
+            (S::Connected | S::Pending | S::Active | S::Stopping, E::Timeout) => S::Closed,
+            _ => return None,
+        };
+        Some(next)
+    }
+
+    // 数据传输是否激活, STOPDT 确认之前仍视为激活
+    pub fn is_active(self) -> bool {
+        matches!(self, LinkState::Active | LinkState::Stopping)
+    }
+
+    // 传输层连接是否建立
+    pub fn is_connected(self) -> bool {
+        !matches!(self, LinkState::Connecting | LinkState::Closed)
+    }
+}
+
+// 连接任务持有的链路状态, 经 watch 通道通知观察者
+#[derive(Clone)]
+pub(crate) struct SharedLinkState {
+    tx: Arc<watch::Sender<LinkState>>,
+}
+
+impl SharedLinkState {
+    pub(crate) fn new() -> Self {
+        SharedLinkState {
+            tx: Arc::new(watch::Sender::new(LinkState::Closed)),
+        }
+    }
+
+    // 应用事件, 返回转换后的状态; 不允许的转换保持原状态并记录日志
+    pub(crate) fn apply(&self, event: LinkEvent) -> LinkState {
+        self.tx
+            .send_if_modified(|current| match current.transition(event) {
+                Some(next) if next != *current => {
+                    log::debug!("[LINK] {current:?} --{event:?}--> {next:?}");
+                    *current = next;
+                    true
+                }
+                Some(_) => false,
+                None => {
+                    log::debug!("[LINK] ignore {event:?} in {current:?}");
+                    false
+                }
+            });
+        self.get()
+    }
+
+    pub(crate) fn get(&self) -> LinkState {
+        *self.tx.borrow()
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.get().is_active()
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<LinkState> {
+        self.tx.subscribe()
+    }
+}
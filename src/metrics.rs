@@ -0,0 +1,100 @@
+// 未开启 metrics 特性时各函数为空操作, 参数不被使用
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use crate::apci::ApciKind;
+use crate::{asdu::TypeID, Apdu};
+
+// 开启 metrics 特性时经 metrics 门面输出指标, 由应用安装的导出器(如 metrics-exporter-prometheus)采集;
+// 否则均为空操作. role 为 client 或 server
+//
+// iec104_sessions{role}                       当前连接数
+// iec104_frames_total{role, direction, kind}  收发的 I/S/U 帧数
+// iec104_bytes_total{role, direction}         收发的字节数
+// iec104_seq_errors_total{role}               序号错误次数
+// iec104_t1_timeouts_total{role}              等待确认超时次数
+// iec104_discarded_bytes_total{role}          解码器重新同步时丢弃的字节数
+// iec104_send_queue_depth{role}               发送队列中等待发送的 I 帧数
+// iec104_unacked_frames{role}                 已发送未被确认的 I 帧数
+// iec104_command_rtt_seconds{type_id}         命令到收到确认的往返时间
+
+// 注册各指标的说明, 在安装导出器后调用一次
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    ::metrics::describe_gauge!("iec104_sessions", "connected IEC 104 sessions");
+    ::metrics::describe_counter!("iec104_frames_total", "APDUs sent and received");
+    ::metrics::describe_counter!("iec104_bytes_total", "APDU bytes sent and received");
+    ::metrics::describe_counter!("iec104_seq_errors_total", "sequence number errors");
+    ::metrics::describe_counter!("iec104_t1_timeouts_total", "t1 acknowledgement timeouts");
+    ::metrics::describe_counter!(
+        "iec104_discarded_bytes_total",
+        "bytes discarded while resynchronizing to a start byte"
+    );
+    ::metrics::describe_gauge!("iec104_send_queue_depth", "I-frames waiting to be sent");
+    ::metrics::describe_gauge!(
+        "iec104_unacked_frames",
+        "I-frames sent but not acknowledged"
+    );
+    ::metrics::describe_histogram!(
+        "iec104_command_rtt_seconds",
+        ::metrics::Unit::Seconds,
+        "time from sending a command to its confirmation"
+    );
+}
+
+pub(crate) fn session_opened(role: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!("iec104_sessions", "role" => role).increment(1.0);
+}
+
+pub(crate) fn session_closed(role: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!("iec104_sessions", "role" => role).decrement(1.0);
+}
+
+pub(crate) fn frame(role: &'static str, direction: &'static str, apdu: &Apdu) {
+    #[cfg(feature = "metrics")]
+    {
+        let kind = match ApciKind::from(apdu.apci) {
+            ApciKind::I(_) => "I",
+            ApciKind::S(_) => "S",
+            ApciKind::U(_) => "U",
+        };
+        ::metrics::counter!("iec104_frames_total", "role" => role, "direction" => direction, "kind" => kind)
+            .increment(1);
+        ::metrics::counter!("iec104_bytes_total", "role" => role, "direction" => direction)
+            .increment(2 + apdu.apci.apdu_length as u64);
+    }
+}
+
+pub(crate) fn seq_error(role: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("iec104_seq_errors_total", "role" => role).increment(1);
+}
+
+pub(crate) fn timeout(role: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("iec104_t1_timeouts_total", "role" => role).increment(1);
+}
+
+pub(crate) fn discarded(role: &'static str, n: u64) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("iec104_discarded_bytes_total", "role" => role).increment(n);
+}
+
+// 发送队列与未确认 I 帧的深度
+pub(crate) fn depth(role: &'static str, queued: usize, unacked: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        ::metrics::gauge!("iec104_send_queue_depth", "role" => role).set(queued as f64);
+        ::metrics::gauge!("iec104_unacked_frames", "role" => role).set(unacked as f64);
+    }
+}
+
+pub(crate) fn command_rtt(type_id: TypeID, rtt: Duration) {
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!("iec104_command_rtt_seconds", "type_id" => format!("{type_id:?}"))
+        .record(rtt.as_secs_f64());
+}
@@ -0,0 +1,49 @@
+use std::{sync::Arc, time::Instant};
+
+use crate::Apdu;
+
+// 原始帧监听: 接收会话收发的每一个 APDU, 可用于协议分析, 原始报文存储及自定义统计.
+// 接收的帧在解码之后, 发送的帧在编码之前回调, 回调在会话任务中同步执行, 不应阻塞
+pub trait FrameObserver: Send + Sync + 'static {
+    fn on_rx(&self, apdu: &Apdu, at: Instant) {}
+
+    fn on_tx(&self, apdu: &Apdu, at: Instant) {}
+}
+
+// 已注册的帧监听者
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Arc<dyn FrameObserver>>);
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Arc<dyn FrameObserver>) {
+        self.0.push(observer);
+    }
+
+    pub(crate) fn on_rx(&self, apdu: &Apdu) {
+        if self.0.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        for observer in &self.0 {
+            observer.on_rx(apdu, now);
+        }
+    }
+
+    pub(crate) fn on_tx(&self, apdu: &Apdu) {
+        if self.0.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        for observer in &self.0 {
+            observer.on_tx(apdu, now);
+        }
+    }
+}
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observers")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
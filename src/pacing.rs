@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use tokio::time::{sleep_until, Instant};
+
+// I 帧的发送节奏: 相邻两个 I 帧之间的最小间隔, 用于处理能力有限的被控站或前置设备,
+// 避免召唤响应等突发的大量 I 帧溢出对端的接收缓冲. 默认不限制.
+// U 帧与 S 帧不受限制, 等待发送的 I 帧仍按优先级在发送队列中排队
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pacing {
+    min_gap: Duration,
+}
+
+impl Pacing {
+    // 每秒最多发送 frames_per_sec 个 I 帧, 按 1/frames_per_sec 秒的间隔均匀发送; 0 为不限制
+    pub fn max_rate(frames_per_sec: u32) -> Self {
+        let min_gap = match frames_per_sec {
+            0 => Duration::ZERO,
+            n => Duration::from_secs(1) / n,
+        };
+        Pacing { min_gap }
+    }
+
+    // 相邻两个 I 帧之间至少间隔 gap
+    pub fn min_gap(gap: Duration) -> Self {
+        Pacing { min_gap: gap }
+    }
+
+    // 同时限制速率与间隔时, 取较长的间隔
+    #[must_use]
+    pub fn with_min_gap(mut self, gap: Duration) -> Self {
+        self.min_gap = self.min_gap.max(gap);
+        self
+    }
+
+    pub fn gap(&self) -> Duration {
+        self.min_gap
+    }
+
+    pub fn is_limited(&self) -> bool {
+        !self.min_gap.is_zero()
+    }
+}
+
+// 一个连接的发送节奏状态
+pub(crate) struct Pacer {
+    gap: Duration,
+    // 下一个 I 帧最早的发送时间, 不限制或尚未发送时为 None
+    next: Option<Instant>,
+}
+
+impl Pacer {
+    pub(crate) fn new(pacing: Pacing) -> Self {
+        Pacer {
+            gap: pacing.min_gap,
+            next: None,
+        }
+    }
+
+    // 等到可以发送下一个 I 帧
+    pub(crate) async fn ready(&self) {
+        if let Some(next) = self.next {
+            sleep_until(next).await;
+        }
+    }
+
+    // 记录发送了一个 I 帧
+    pub(crate) fn sent(&mut self) {
+        if !self.gap.is_zero() {
+            self.next = Some(Instant::now() + self.gap);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures::{
+    future::join_all,
+    stream::{self, Stream},
+    StreamExt,
+};
+use tokio::{
+    select,
+    sync::{self, broadcast},
+    task::JoinHandle,
+    time::{sleep_until, Instant},
+};
+
+use crate::{
+    asdu::{Asdu, CommonAddr},
+    csys::ObjectQOI,
+    Client, ClientEvent, ClientHandler, Error,
+};
+
+// 多个远方站的主站: 每个远方站一个 Client(各自的 ClientOption), 在同一个运行时中并发运行.
+// 数据传输激活后按远方站的顺序错开发起总召唤, 避免同时召唤造成前置负载尖峰;
+// 汇总各远方站的事件与收到的 ASDU, 并记录各远方站的运行状态
+pub struct ClientPool<S> {
+    remotes: BTreeMap<String, Arc<Remote<S>>>,
+    interrogation: Option<Interrogation>,
+    events: broadcast::Sender<PoolEvent>,
+    // 各远方站的监视任务, 未启动时为空
+    tasks: sync::Mutex<Vec<JoinHandle<()>>>,
+}
+
+struct Remote<S> {
+    client: Client<S>,
+    common_addrs: Vec<CommonAddr>,
+    health: Mutex<RemoteHealth>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interrogation {
+    qoi: ObjectQOI,
+    stagger: Duration,
+    period: Option<Duration>,
+}
+
+// 远方站的事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolEvent {
+    pub remote: String,
+    pub event: ClientEvent,
+}
+
+// 远方站的运行状态
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteHealth {
+    /// 传输层连接已建立
+    pub connected: bool,
+    /// 数据传输已激活
+    pub active: bool,
+    /// 最近一次收到 ASDU 的时间
+    pub last_asdu: Option<DateTime<Utc>>,
+    /// 最近一次完成总召唤的时间
+    pub last_interrogation: Option<DateTime<Utc>>,
+    /// 连接断开的次数
+    pub disconnects: u32,
+    /// 最近一次连接断开或总召唤失败的原因
+    pub last_error: Option<String>,
+}
+
+impl<S> Default for ClientPool<S> {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(256);
+        ClientPool {
+            remotes: BTreeMap::new(),
+            interrogation: None,
+            events,
+            tasks: sync::Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<S> ClientPool<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 添加远方站, common_addrs 为总召唤的公共地址. 名称相同时替换之前的远方站
+    #[must_use]
+    pub fn with_remote<I>(
+        mut self,
+        name: impl Into<String>,
+        common_addrs: I,
+        client: Client<S>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = CommonAddr>,
+    {
+        let remote = Remote {
+            client,
+            common_addrs: common_addrs.into_iter().collect(),
+            health: Mutex::new(RemoteHealth::default()),
+        };
+        self.remotes.insert(name.into(), Arc::new(remote));
+        self
+    }
+
+    // 数据传输激活后发起总召唤, 第 n 个远方站(按名称排序, 从 0 开始)延迟 n * stagger
+    #[must_use]
+    pub fn with_interrogation(mut self, qoi: impl Into<ObjectQOI>, stagger: Duration) -> Self {
+        let qoi = qoi.into();
+        let period = self.interrogation.and_then(|i| i.period);
+        self.interrogation = Some(Interrogation {
+            qoi,
+            stagger,
+            period,
+        });
+        self
+    }
+
+    // 激活期间每隔 period 重复总召唤, 需要先设置 with_interrogation
+    #[must_use]
+    pub fn with_interrogation_period(mut self, period: Duration) -> Self {
+        if let Some(i) = &mut self.interrogation {
+            i.period = Some(period);
+        }
+        self
+    }
+
+    // 启动全部远方站的连接任务与监视任务, 已启动时直接返回
+    pub async fn start(&self) -> Result<(), Error> {
+        let mut tasks = self.tasks.lock().await;
+        if !tasks.is_empty() {
+            return Ok(());
+        }
+        for (index, (name, remote)) in self.remotes.iter().enumerate() {
+            let plan = self
+                .interrogation
+                .map(|i| (i, i.stagger.saturating_mul(index as u32)));
+            let client_events = remote.client.events();
+            let asdus = remote.client.asdu_stream().await;
+            tasks.push(tokio::spawn(monitor(
+                name.clone(),
+                remote.clone(),
+                plan,
+                client_events,
+                asdus,
+                self.events.clone(),
+            )));
+            remote.client.start().await?;
+        }
+        Ok(())
+    }
+
+    // 停止全部远方站, 并发等待各连接任务结束
+    pub async fn stop(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.lock().await);
+        for task in tasks {
+            task.abort();
+        }
+        join_all(self.remotes.values().map(|r| r.client.stop())).await;
+    }
+
+    pub fn client(&self, name: &str) -> Option<&Client<S>> {
+        self.remotes.get(name).map(|r| &r.client)
+    }
+
+    // 远方站的名称, 按名称排序
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.remotes.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.remotes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remotes.is_empty()
+    }
+
+    // 订阅全部远方站的事件, 在 start 之前订阅才能收到首次连接的事件
+    pub fn events(&self) -> broadcast::Receiver<PoolEvent> {
+        self.events.subscribe()
+    }
+
+    // 全部远方站收到的 ASDU 作为一个异步流, 每项带远方站的名称, 流被丢弃后自动取消订阅
+    pub async fn asdu_stream(&self) -> impl Stream<Item = (String, Asdu)> + Unpin + Send + 'static {
+        let mut streams = Vec::with_capacity(self.remotes.len());
+        for (name, remote) in &self.remotes {
+            let name = name.clone();
+            let asdus = remote.client.asdu_stream().await;
+            streams.push(asdus.map(move |asdu| (name.clone(), asdu)));
+        }
+        stream::select_all(streams)
+    }
+
+    pub fn health(&self, name: &str) -> Option<RemoteHealth> {
+        self.remotes.get(name).map(|r| r.health())
+    }
+
+    // 全部远方站的运行状态, 按名称排序
+    pub fn health_all(&self) -> Vec<(String, RemoteHealth)> {
+        self.remotes
+            .iter()
+            .map(|(name, r)| (name.clone(), r.health()))
+            .collect()
+    }
+}
+
+impl<S> Remote<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    fn health(&self) -> RemoteHealth {
+        RemoteHealth {
+            connected: self.client.is_connected(),
+            active: self.client.is_active(),
+            ..self.health.lock().unwrap().clone()
+        }
+    }
+
+    async fn interrogate(&self, qoi: ObjectQOI) -> Result<(), Error> {
+        for &ca in &self.common_addrs {
+            self.client.general_interrogation(ca, qoi).await?;
+        }
+        Ok(())
+    }
+}
+
+// 远方站的监视任务: 转发事件, 激活后按计划总召唤, 记录运行状态
+async fn monitor<S>(
+    name: String,
+    remote: Arc<Remote<S>>,
+    plan: Option<(Interrogation, Duration)>,
+    mut client_events: broadcast::Receiver<ClientEvent>,
+    mut asdus: impl Stream<Item = Asdu> + Unpin,
+    events: broadcast::Sender<PoolEvent>,
+) where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 下一次总召唤的时间, 未激活时为 None
+    let mut next_interrogation: Option<Instant> = None;
+    loop {
+        select! {
+            event = client_events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                match &event {
+                    ClientEvent::Activated => {
+                        next_interrogation = plan.map(|(_, delay)| Instant::now() + delay);
+                    }
+                    ClientEvent::Deactivated => next_interrogation = None,
+                    ClientEvent::Disconnected(reason) => {
+                        next_interrogation = None;
+                        let mut health = remote.health.lock().unwrap();
+                        health.disconnects += 1;
+                        health.last_error = Some(reason.to_string());
+                    }
+                    _ => {}
+                }
+                let _ = events.send(PoolEvent { remote: name.clone(), event });
+            }
+            Some(_) = asdus.next() => {
+                remote.health.lock().unwrap().last_asdu = Some(Utc::now());
+            }
+            _ = sleep_until(next_interrogation.unwrap_or_else(Instant::now)), if next_interrogation.is_some() => {
+                let Some((interrogation, _)) = plan else {
+                    continue;
+                };
+                let result = remote.interrogate(interrogation.qoi).await;
+                let mut health = remote.health.lock().unwrap();
+                match result {
+                    Ok(()) => health.last_interrogation = Some(Utc::now()),
+                    Err(e) => {
+                        log::warn!("[POOL] {name}: interrogation: {e}");
+                        health.last_error = Some(e.to_string());
+                    }
+                }
+                next_interrogation = interrogation.period.map(|p| Instant::now() + p);
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::{fmt, io, net::IpAddr};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::RemoteAddr;
+
+// 代理协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// SOCKS5 (RFC 1928), 如 ssh -D 建立的跳板机动态转发
+    Socks5,
+    /// HTTP CONNECT 隧道
+    Http,
+}
+
+// 经代理连接被控站, 用于变电站 DMZ 等只能经代理访问 RTU 的网络.
+// 对端为主机名时由代理解析
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    kind: ProxyKind,
+    proxy: RemoteAddr,
+    // 用户名, 密码
+    auth: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    pub fn socks5(proxy: impl Into<RemoteAddr>) -> Self {
+        Self::new(ProxyKind::Socks5, proxy.into())
+    }
+
+    pub fn http(proxy: impl Into<RemoteAddr>) -> Self {
+        Self::new(ProxyKind::Http, proxy.into())
+    }
+
+    pub fn new(kind: ProxyKind, proxy: RemoteAddr) -> Self {
+        ProxyConfig {
+            kind,
+            proxy,
+            auth: None,
+        }
+    }
+
+    // SOCKS5 为用户名/密码认证(RFC 1929), HTTP 为 Basic 认证
+    #[must_use]
+    pub fn with_auth(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((user.into(), password.into()));
+        self
+    }
+
+    pub fn kind(&self) -> ProxyKind {
+        self.kind
+    }
+
+    pub fn proxy(&self) -> &RemoteAddr {
+        &self.proxy
+    }
+
+    // 在已连接到代理的 stream 上建立到 target 的隧道
+    pub(crate) async fn handshake(
+        &self,
+        stream: &mut TcpStream,
+        target: &RemoteAddr,
+    ) -> io::Result<()> {
+        let (host, port) = split_target(target)?;
+        match self.kind {
+            ProxyKind::Socks5 => socks5_connect(stream, &host, port, self.auth.as_ref()).await,
+            ProxyKind::Http => http_connect(stream, &host, port, self.auth.as_ref()).await,
+        }
+    }
+}
+
+// 不输出密码
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("kind", &self.kind)
+            .field("proxy", &self.proxy)
+            .field("user", &self.auth.as_ref().map(|(user, _)| user))
+            .finish()
+    }
+}
+
+fn proxy_error(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionRefused, msg.into())
+}
+
+// 拆分为主机与端口, IPv6 地址去掉方括号
+fn split_target(target: &RemoteAddr) -> io::Result<(String, u16)> {
+    match target {
+        RemoteAddr::Addr(addr) => Ok((addr.ip().to_string(), addr.port())),
+        RemoteAddr::Host(host) => {
+            let (name, port) = host.rsplit_once(':').ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("missing port in {host}"),
+                )
+            })?;
+            let port = port.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid port in {host}"),
+                )
+            })?;
+            let name = name.trim_start_matches('[').trim_end_matches(']');
+            Ok((name.to_string(), port))
+        }
+    }
+}
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_NO_AUTH: u8 = 0x00;
+const SOCKS5_USER_PASS: u8 = 0x02;
+const SOCKS5_NO_ACCEPTABLE: u8 = 0xff;
+const SOCKS5_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&(String, String)>,
+) -> io::Result<()> {
+    // 协商认证方式
+    let greeting: &[u8] = match auth {
+        Some(_) => &[SOCKS5_VERSION, 2, SOCKS5_NO_AUTH, SOCKS5_USER_PASS],
+        None => &[SOCKS5_VERSION, 1, SOCKS5_NO_AUTH],
+    };
+    stream.write_all(greeting).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS5_VERSION {
+        return Err(proxy_error(format!("invalid SOCKS version {}", reply[0])));
+    }
+    match (reply[1], auth) {
+        (SOCKS5_NO_AUTH, _) => (),
+        (SOCKS5_USER_PASS, Some((user, password))) => {
+            if user.len() > 255 || password.len() > 255 {
+                return Err(proxy_error("SOCKS5 user or password too long"));
+            }
+            let mut req = vec![0x01, user.len() as u8];
+            req.extend_from_slice(user.as_bytes());
+            req.push(password.len() as u8);
+            req.extend_from_slice(password.as_bytes());
+            stream.write_all(&req).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                return Err(proxy_error("SOCKS5 authentication failed"));
+            }
+        }
+        (SOCKS5_NO_ACCEPTABLE, _) => {
+            return Err(proxy_error("SOCKS5 proxy accepts none of the methods"))
+        }
+        (method, _) => return Err(proxy_error(format!("unexpected SOCKS5 method {method}"))),
+    }
+
+    // 建立连接
+    let mut req = vec![SOCKS5_VERSION, SOCKS5_CONNECT, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            req.push(SOCKS5_ATYP_IPV4);
+            req.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            req.push(SOCKS5_ATYP_IPV6);
+            req.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(proxy_error(format!("host name too long: {host}")));
+            }
+            req.push(SOCKS5_ATYP_DOMAIN);
+            req.push(host.len() as u8);
+            req.extend_from_slice(host.as_bytes());
+        }
+    }
+    req.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&req).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[1] != 0 {
+        return Err(proxy_error(format!(
+            "SOCKS5 connect to {host}:{port} failed with reply {}",
+            head[1]
+        )));
+    }
+    // 跳过代理绑定的地址与端口
+    let addr_len = match head[3] {
+        SOCKS5_ATYP_IPV4 => 4,
+        SOCKS5_ATYP_IPV6 => 16,
+        SOCKS5_ATYP_DOMAIN => stream.read_u8().await? as usize,
+        atyp => return Err(proxy_error(format!("invalid SOCKS5 address type {atyp}"))),
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+// 响应头的长度上限
+const HTTP_MAX_HEADER: usize = 8 * 1024;
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&(String, String)>,
+) -> io::Result<()> {
+    let authority = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => format!("[{ip}]:{port}"),
+        _ => format!("{host}:{port}"),
+    };
+    let mut req = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some((user, password)) = auth {
+        let credentials = base64(format!("{user}:{password}").as_bytes());
+        req.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    req.push_str("\r\n");
+    stream.write_all(req.as_bytes()).await?;
+
+    // 逐字节读取响应头, 不读入隧道内的数据
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= HTTP_MAX_HEADER {
+            return Err(proxy_error("HTTP proxy response header too long"));
+        }
+        head.push(stream.read_u8().await?);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status = head.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(proxy_error(format!(
+            "HTTP CONNECT to {authority} failed: {status}"
+        ))),
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,101 @@
+use bit_struct::*;
+
+use crate::mproc::{ObjectBCR, ObjectDIQ, ObjectQDS, ObjectSIQ};
+
+// 统一的品质描述: SIQ, DIQ, QDS 及累计量的品质位, 省去按类型逐一解析位结构.
+// 单点与双点信息没有 OV, 累计量只有 IV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quality {
+    /// IV, 数据无效
+    pub invalid: bool,
+    /// NT, 非最新状态
+    pub nt: bool,
+    /// SB, 被取代/人工设置
+    pub sb: bool,
+    /// BL, 封锁
+    pub bl: bool,
+    /// OV, 溢出
+    pub ov: bool,
+}
+
+impl Quality {
+    // 全部品质位均未置位
+    pub fn is_good(&self) -> bool {
+        *self == Quality::default()
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        self.invalid
+    }
+}
+
+impl From<ObjectSIQ> for Quality {
+    fn from(mut siq: ObjectSIQ) -> Self {
+        Quality {
+            invalid: siq.invalid().get(),
+            nt: siq.nt().get(),
+            sb: siq.sb().get(),
+            bl: siq.bl().get(),
+            ov: false,
+        }
+    }
+}
+
+impl From<ObjectDIQ> for Quality {
+    fn from(mut diq: ObjectDIQ) -> Self {
+        Quality {
+            invalid: diq.invalid().get(),
+            nt: diq.nt().get(),
+            sb: diq.sb().get(),
+            bl: diq.bl().get(),
+            ov: false,
+        }
+    }
+}
+
+impl From<ObjectQDS> for Quality {
+    fn from(mut qds: ObjectQDS) -> Self {
+        Quality {
+            invalid: qds.invalid().get(),
+            nt: qds.nt().get(),
+            sb: qds.sb().get(),
+            bl: qds.bl().get(),
+            ov: qds.ov().get(),
+        }
+    }
+}
+
+impl From<&ObjectBCR> for Quality {
+    fn from(bcr: &ObjectBCR) -> Self {
+        Quality {
+            invalid: bcr.invalid,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Quality> for ObjectQDS {
+    fn from(q: Quality) -> Self {
+        ObjectQDS::new(q.invalid, q.nt, q.sb, q.bl, u3!(0), q.ov)
+    }
+}
+
+// 客户端对品质无效(IV)的数据的处理方式, 作用于点缓存与点订阅
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QualityFilter {
+    /// 与其他数据一样应用, 由使用者检查品质
+    #[default]
+    Accept,
+    /// 点缓存保留上一次的值只更新品质与时标, 订阅照常推送(品质中带 IV)
+    Flag,
+    /// 丢弃, 不更新点缓存也不推送给订阅
+    Reject,
+}
+
+impl QualityFilter {
+    // quality 的数据是否交给点缓存与订阅
+    pub fn accepts(&self, quality: Quality) -> bool {
+        !(quality.invalid && *self == QualityFilter::Reject)
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use tokio::sync::oneshot;
+
+use crate::{
+    asdu::{Asdu, Cause},
+    Error,
+};
+
+// 发送优先级: 命令及其确认 > 突发(自发)数据 > 周期/背景扫描数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// 命令, 命令的确认, 否定应答
+    Command,
+    /// 突发(自发), 召唤响应等数据及激活终止
+    Spontaneous,
+    /// 周期, 背景扫描数据
+    Background,
+}
+
+impl Priority {
+    // 按传送原因划分 ASDU 的发送优先级
+    pub fn of(asdu: &Asdu) -> Self {
+        let mut cot = asdu.identifier.cot;
+        match cot.cause().get() {
+            Cause::Periodic | Cause::Background => Priority::Background,
+            // 激活终止与召唤响应的数据同一优先级, 保证在数据之后发送
+            Cause::ActivationTerm => Priority::Spontaneous,
+            Cause::Activation
+            | Cause::ActivationCon
+            | Cause::Deactivation
+            | Cause::DeactivationCon
+            | Cause::UnknownTypeID
+            | Cause::UnknownCOT
+            | Cause::UnknownCA
+            | Cause::UnknownIOA => Priority::Command,
+            _ => Priority::Spontaneous,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// 发送队列满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// 丢弃队列中优先级最低且最早的 ASDU, 新 ASDU 的优先级更低时丢弃新 ASDU
+    #[default]
+    DropOldest,
+    /// 丢弃新 ASDU
+    DropNewest,
+    /// 拒绝新 ASDU, 返回 ErrQueueFull
+    Reject,
+}
+
+// 发送队列配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendQueueOption {
+    // 队列容量(全部优先级的 ASDU 总数)
+    pub(crate) capacity: usize,
+    // 队列满时的处理方式
+    pub(crate) overflow: OverflowPolicy,
+    // 未激活(STOPDT)或连接断开期间是否缓存突发数据, 重新激活后补发
+    pub(crate) buffer_offline: bool,
+}
+
+impl SendQueueOption {
+    pub fn new(capacity: usize) -> Self {
+        SendQueueOption {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn with_buffer_offline(mut self, buffer: bool) -> Self {
+        self.buffer_offline = buffer;
+        self
+    }
+}
+
+impl Default for SendQueueOption {
+    fn default() -> Self {
+        SendQueueOption {
+            capacity: 1024,
+            overflow: OverflowPolicy::default(),
+            buffer_offline: false,
+        }
+    }
+}
+
+// 等待 ASDU 交给传输层(或被对端确认)的通知, 见 Client::send_asdu_timeout.
+// 等待方放弃(超时)后, 仍在队列中的 ASDU 不再发送
+#[derive(Debug)]
+pub(crate) struct Delivery {
+    pub(crate) tx: oneshot::Sender<()>,
+    // 为 true 时在对端确认后通知, 否则在写入传输层后通知
+    pub(crate) wait_ack: bool,
+}
+
+#[derive(Debug)]
+struct Queued {
+    asdu: Asdu,
+    delivery: Option<Delivery>,
+}
+
+// 按优先级发送的有界队列, 同一优先级内先进先出
+#[derive(Debug)]
+pub struct SendQueue {
+    op: SendQueueOption,
+    queues: [VecDeque<Queued>; 3],
+}
+
+impl SendQueue {
+    pub fn new(op: SendQueueOption) -> Self {
+        SendQueue {
+            op,
+            queues: Default::default(),
+        }
+    }
+
+    pub fn option(&self) -> SendQueueOption {
+        self.op
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    pub fn push(&mut self, asdu: Asdu) -> Result<(), Error> {
+        self.push_with(asdu, None)
+    }
+
+    pub(crate) fn push_with(
+        &mut self,
+        asdu: Asdu,
+        delivery: Option<Delivery>,
+    ) -> Result<(), Error> {
+        let priority = Priority::of(&asdu);
+        if self.len() >= self.op.capacity {
+            match self.op.overflow {
+                OverflowPolicy::Reject => return Err(Error::ErrQueueFull),
+                OverflowPolicy::DropNewest => {
+                    log::warn!("[QUEUE] send queue full, drop {asdu:?}");
+                    return Ok(());
+                }
+                OverflowPolicy::DropOldest => {
+                    let lowest = (0..self.queues.len())
+                        .rev()
+                        .find(|&i| !self.queues[i].is_empty());
+                    match lowest {
+                        Some(i) if i >= priority.index() => {
+                            let dropped = self.queues[i].pop_front().map(|queued| queued.asdu);
+                            log::warn!("[QUEUE] send queue full, drop {dropped:?}");
+                        }
+                        _ => {
+                            log::warn!("[QUEUE] send queue full, drop {asdu:?}");
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+        self.queues[priority.index()].push_back(Queued { asdu, delivery });
+        Ok(())
+    }
+
+    // 放回刚取出但未能发送的 ASDU, 排在同一优先级的队首, 不受容量限制
+    pub(crate) fn push_front(&mut self, asdu: Asdu) {
+        let priority = Priority::of(&asdu);
+        self.queues[priority.index()].push_front(Queued {
+            asdu,
+            delivery: None,
+        });
+    }
+
+    // 取出优先级最高且最早的 ASDU
+    pub fn pop(&mut self) -> Option<Asdu> {
+        self.pop_with().map(|(asdu, _)| asdu)
+    }
+
+    // 同 pop, 同时取出发送通知; 跳过等待方已放弃的 ASDU
+    pub(crate) fn pop_with(&mut self) -> Option<(Asdu, Option<Delivery>)> {
+        loop {
+            let queued = self.queues.iter_mut().find_map(VecDeque::pop_front)?;
+            if queued.delivery.as_ref().is_some_and(|d| d.tx.is_closed()) {
+                log::debug!("[QUEUE] sender gave up, drop {:?}", queued.asdu);
+                continue;
+            }
+            return Some((queued.asdu, queued.delivery));
+        }
+    }
+
+    // 取出全部 ASDU; 开启 buffer_offline 时只保留突发数据, 否则全部丢弃
+    pub fn take_offline(&mut self) -> Vec<Asdu> {
+        let spontaneous = std::mem::take(&mut self.queues[Priority::Spontaneous.index()]);
+        for queue in self.queues.iter_mut() {
+            queue.clear();
+        }
+        if self.op.buffer_offline {
+            spontaneous.into_iter().map(|queued| queued.asdu).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    // 丢弃未激活期间不缓存的 ASDU
+    pub fn retain_offline(&mut self) {
+        let kept = self.take_offline();
+        self.queues[Priority::Spontaneous.index()].extend(kept.into_iter().map(|asdu| Queued {
+            asdu,
+            delivery: None,
+        }));
+    }
+
+    // 未激活期间是否缓存该 ASDU
+    pub fn is_buffered_offline(&self, asdu: &Asdu) -> bool {
+        self.op.buffer_offline && Priority::of(asdu) == Priority::Spontaneous
+    }
+}
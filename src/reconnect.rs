@@ -0,0 +1,62 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 连接失败后的重连策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReconnectPolicy {
+    /// 立即重连
+    Immediate,
+    /// 固定间隔重连
+    Fixed(Duration),
+    /// 指数退避: 第 n 次重连等待 initial * 2^(n-1), 不超过 max.
+    /// jitter 为 true 时在 [delay/2, delay] 内随机取值, 避免多个客户端同时重连
+    ExponentialBackoff {
+        initial: Duration,
+        max: Duration,
+        jitter: bool,
+    },
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy::Fixed(Duration::from_secs(60))
+    }
+}
+
+impl ReconnectPolicy {
+    // 第 attempt 次(从1开始)重连前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            ReconnectPolicy::Immediate => Duration::ZERO,
+            ReconnectPolicy::Fixed(delay) => delay,
+            ReconnectPolicy::ExponentialBackoff {
+                initial,
+                max,
+                jitter,
+            } => {
+                let factor = 1u32
+                    .checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u32::MAX);
+                let delay = initial.saturating_mul(factor).min(max);
+                if !jitter {
+                    return delay;
+                }
+                let half = delay / 2;
+                half + half.mul_f64(random_fraction())
+            }
+        }
+    }
+}
+
+// [0, 1) 内的伪随机数, 仅用于重连抖动
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    // xorshift 打散低位
+    let mut x = nanos as u64 | 1;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    (x % 1_000_000) as f64 / 1_000_000.0
+}
@@ -0,0 +1,83 @@
+use std::{io, net::SocketAddr, sync::Mutex};
+
+use futures::future::BoxFuture;
+use tokio::sync::broadcast;
+
+use crate::{ClientEvent, Connector, TcpConnector, Transport};
+
+// 冗余连接组: 同一被控站的多个前置地址(主/备), 按顺序排列, 第一个为主
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedundancyGroup {
+    addrs: Vec<SocketAddr>,
+}
+
+impl RedundancyGroup {
+    pub fn new(addrs: Vec<SocketAddr>) -> Self {
+        RedundancyGroup { addrs }
+    }
+
+    pub fn addrs(&self) -> &[SocketAddr] {
+        &self.addrs
+    }
+}
+
+// 主备切换事件: 连接从 from 切换到 to, 首次连接时 from 为 None
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Switchover {
+    pub from: Option<SocketAddr>,
+    pub to: SocketAddr,
+}
+
+// 冗余组的连接器: 首次连接时从主开始依次尝试, 连接断开后从下一个地址开始尝试,
+// 当前连接的地址保持为工作链路, 其余为备用链路
+pub(crate) struct RedundancyConnector {
+    connectors: Vec<(SocketAddr, TcpConnector)>,
+    current: Mutex<Option<usize>>,
+    events: broadcast::Sender<ClientEvent>,
+}
+
+impl RedundancyConnector {
+    pub(crate) fn new(
+        connectors: Vec<(SocketAddr, TcpConnector)>,
+        events: broadcast::Sender<ClientEvent>,
+    ) -> Self {
+        RedundancyConnector {
+            connectors,
+            current: Mutex::new(None),
+            events,
+        }
+    }
+}
+
+impl Connector for RedundancyConnector {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>> {
+        Box::pin(async move {
+            let n = self.connectors.len();
+            let last = *self.current.lock().unwrap();
+            let start = last.map(|i| i + 1).unwrap_or(0);
+            let mut err = io::Error::new(io::ErrorKind::NotConnected, "empty redundancy group");
+            for k in 0..n {
+                let i = (start + k) % n;
+                let (addr, connector) = &self.connectors[i];
+                match connector.connect().await {
+                    Ok(stream) => {
+                        *self.current.lock().unwrap() = Some(i);
+                        let from = last.map(|j| self.connectors[j].0);
+                        if from != Some(*addr) {
+                            log::info!("[REDUNDANCY] switch over from {from:?} to {addr}");
+                            let _ = self
+                                .events
+                                .send(ClientEvent::Switchover(Switchover { from, to: *addr }));
+                        }
+                        return Ok(stream);
+                    }
+                    Err(e) => {
+                        log::warn!("[REDUNDANCY] connect to {addr} error: {e}");
+                        err = e;
+                    }
+                }
+            }
+            Err(err)
+        })
+    }
+}
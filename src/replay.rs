@@ -0,0 +1,438 @@
+use std::{
+    io::{Cursor, Read},
+    net::SocketAddr,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    select,
+    time::{sleep_until, timeout_at, Instant},
+};
+use tokio_util::codec::{Decoder, Framed};
+
+use crate::{
+    apci::{
+        new_iframe, new_sframe, new_uframe, ApciKind, SeqNum, U_TESTFR_ACTIVE, U_TESTFR_CONFIRM,
+    },
+    asdu::AsduParams,
+    capture::{LINKTYPE_RAW, PCAP_MAGIC},
+    Apdu, Codec, Error, Transport,
+};
+
+// pcap 文件的其他常见格式
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+
+// 记录中帧的发送方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// 控制站(主站, 客户端)
+    Master,
+    /// 被控站(从站, 服务端)
+    Slave,
+}
+
+// 记录的一个 APDU
+#[derive(Debug)]
+pub struct ReplayRecord {
+    /// 相对第一个帧的时间
+    pub offset: Duration,
+    /// 发送方
+    pub origin: Origin,
+    pub apdu: Apdu,
+}
+
+// 从抓包文件或十六进制文本中读取的报文, 作为模拟主站或模拟从站按原有时序回放,
+// 用于以现场报文对 Client 或 ServerSession 的处理逻辑做回归测试.
+//
+// 回放时只发送本方的 I 帧与 U 帧(测试帧除外), I 帧按当前会话重新编号,
+// 收到对端的 I 帧立即以 S 帧确认. 发送每个帧之前, 除等待原有的时间间隔外,
+// 还需收到记录中该帧之前对端发送的 I 帧与 U 帧个数, 使双方的交互顺序与记录一致
+#[derive(Debug)]
+pub struct Replay {
+    records: Vec<ReplayRecord>,
+    params: AsduParams,
+    // 时间间隔的缩放系数, 0 为不等待
+    speed: f64,
+    // 等待对端帧的超时时间
+    response_timeout: Duration,
+    // 发送完最后一个帧后继续接收对端帧的时间
+    linger: Duration,
+}
+
+impl Replay {
+    pub fn new(records: Vec<ReplayRecord>) -> Self {
+        Replay {
+            records,
+            params: AsduParams::default(),
+            speed: 1.0,
+            response_timeout: Duration::from_secs(5),
+            linger: Duration::from_millis(100),
+        }
+    }
+
+    // 读取 pcap 文件中与 server_port 之间的第一条 TCP 连接,
+    // 支持原始 IP, 以太网及 Linux cooked 链路类型
+    pub fn from_pcap<R>(reader: R, server_port: u16) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::from_pcap_with(reader, server_port, &AsduParams::default())
+    }
+
+    pub fn from_pcap_with<R>(
+        mut reader: R,
+        server_port: u16,
+        params: &AsduParams,
+    ) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let packets = match LittleEndian::read_u32(data.get(..4).unwrap_or(&[0; 4])) {
+            PCAP_MAGIC | PCAP_MAGIC_NANOS => read_pcap::<LittleEndian>(&data)?,
+            _ => read_pcap::<BigEndian>(&data)?,
+        };
+
+        let mut conn: Option<(SocketAddr, SocketAddr)> = None;
+        let mut master = Stream::new(Origin::Master, *params);
+        let mut slave = Stream::new(Origin::Slave, *params);
+        let mut records = Vec::new();
+        let mut first = None;
+        for (ts, linktype, packet) in packets {
+            let Some((src, dst, payload)) = tcp_payload(linktype, packet) else {
+                continue;
+            };
+            let stream = if dst.port() == server_port {
+                &mut master
+            } else if src.port() == server_port {
+                &mut slave
+            } else {
+                continue;
+            };
+            let client = if dst.port() == server_port { src } else { dst };
+            let server = if dst.port() == server_port { dst } else { src };
+            match conn {
+                None => conn = Some((client, server)),
+                Some(c) if c != (client, server) => continue,
+                _ => (),
+            }
+            if payload.is_empty() {
+                continue;
+            }
+            let offset = ts.saturating_sub(*first.get_or_insert(ts));
+            stream.push(offset, payload, &mut records)?;
+        }
+        Ok(Replay::new(records).with_asdu_params(*params))
+    }
+
+    // 读取十六进制文本, 每行为: 相对时间(毫秒) 方向 帧字节.
+    // 方向以控制站(客户端)为视角, TX 为控制站发送, RX 为被控站发送, # 开头的行为注释, 如
+    //
+    // 0 TX 68 04 07 00 00 00
+    // 12 RX 68 04 0B 00 00 00
+    pub fn from_hex(text: &str) -> Result<Self, Error> {
+        Self::from_hex_with(text, &AsduParams::default())
+    }
+
+    pub fn from_hex_with(text: &str, params: &AsduParams) -> Result<Self, Error> {
+        let mut records = Vec::new();
+        let mut master = Stream::new(Origin::Master, *params);
+        let mut slave = Stream::new(Origin::Slave, *params);
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || Error::ErrAnyHow(anyhow!("invalid replay line {}: {line}", n + 1));
+            let mut fields = line.split_whitespace();
+            let offset = fields
+                .next()
+                .and_then(|ms| ms.parse::<u64>().ok())
+                .map(Duration::from_millis)
+                .ok_or_else(invalid)?;
+            let stream = match fields.next().map(|d| d.to_ascii_uppercase()).as_deref() {
+                Some("TX") => &mut master,
+                Some("RX") => &mut slave,
+                _ => return Err(invalid()),
+            };
+            let bytes = fields
+                .map(|b| u8::from_str_radix(b, 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            stream.push(offset, &bytes, &mut records)?;
+        }
+        Ok(Replay::new(records).with_asdu_params(*params))
+    }
+
+    // ASDU 各字段长度, 与被测会话的配置一致
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    // 时间间隔的缩放系数, 1.0 为原有时序, 2.0 为两倍速, 0 为不等待
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_response_timeout(mut self, timeout: Duration) -> Self {
+        self.response_timeout = timeout;
+        self
+    }
+
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    pub fn records(&self) -> &[ReplayRecord] {
+        &self.records
+    }
+
+    // 作为模拟主站回放, 对端一般为 Server 的会话, 返回对端发送的帧
+    pub async fn play_as_master<T>(&self, transport: T) -> Result<Vec<Apdu>, Error>
+    where
+        T: Transport,
+    {
+        self.play(Origin::Master, transport).await
+    }
+
+    // 作为模拟从站回放, 对端一般为 Client, 返回对端发送的帧
+    pub async fn play_as_slave<T>(&self, transport: T) -> Result<Vec<Apdu>, Error>
+    where
+        T: Transport,
+    {
+        self.play(Origin::Slave, transport).await
+    }
+
+    async fn play<T>(&self, us: Origin, transport: T) -> Result<Vec<Apdu>, Error>
+    where
+        T: Transport,
+    {
+        let mut framed = Framed::new(transport, Codec::new(self.params));
+        let mut received = Vec::new();
+        let start = Instant::now();
+        // 记录中已出现的对端帧个数, 与实际收到的对端帧个数
+        let mut expected = 0;
+        let mut seen = 0;
+        let mut send_sn = SeqNum::default();
+        let mut rcv_sn = SeqNum::default();
+
+        let mut recv = |apdu: Apdu, seen: &mut usize, rcv_sn: &mut SeqNum| -> Option<Apdu> {
+            let reply = match ApciKind::from(apdu.apci) {
+                ApciKind::I(_) => {
+                    *seen += 1;
+                    *rcv_sn = rcv_sn.next();
+                    Some(new_sframe(rcv_sn.value()))
+                }
+                ApciKind::U(u) if u.function == U_TESTFR_ACTIVE => {
+                    Some(new_uframe(U_TESTFR_CONFIRM))
+                }
+                ApciKind::U(u) if u.function == U_TESTFR_CONFIRM => None,
+                ApciKind::U(_) => {
+                    *seen += 1;
+                    None
+                }
+                ApciKind::S(_) => None,
+            };
+            received.push(apdu);
+            reply
+        };
+
+        for record in &self.records {
+            let kind = ApciKind::from(record.apdu.apci);
+            if !replayable(&kind) {
+                continue;
+            }
+            if record.origin != us {
+                expected += 1;
+                continue;
+            }
+
+            let due = start + record.offset.mul_f64(self.speed);
+            let deadline = Instant::now().max(due) + self.response_timeout;
+            while seen < expected || Instant::now() < due {
+                select! {
+                    apdu = timeout_at(deadline, framed.next()) => {
+                        let apdu = match apdu {
+                            Ok(Some(apdu)) => apdu?,
+                            Ok(None) => return Err(Error::ErrUseClosedConnection),
+                            Err(_) => return Err(Error::ErrTimeout),
+                        };
+                        if let Some(reply) = recv(apdu, &mut seen, &mut rcv_sn) {
+                            framed.send(reply).await?;
+                        }
+                    }
+                    _ = sleep_until(due), if seen >= expected => (),
+                }
+            }
+
+            let apdu = match (kind, &record.apdu.asdu) {
+                (ApciKind::I(_), Some(asdu)) => {
+                    let apdu = new_iframe(asdu.clone(), send_sn.value(), rcv_sn.value());
+                    send_sn = send_sn.next();
+                    apdu
+                }
+                _ => Apdu {
+                    apci: record.apdu.apci,
+                    asdu: None,
+                },
+            };
+            framed.send(apdu).await?;
+        }
+
+        // 接收对端的剩余响应
+        let deadline = Instant::now() + self.linger;
+        while let Ok(Some(apdu)) = timeout_at(deadline, framed.next()).await {
+            if let Some(reply) = recv(apdu?, &mut seen, &mut rcv_sn) {
+                framed.send(reply).await?;
+            }
+        }
+        Ok(received)
+    }
+}
+
+// 回放的帧: 带 ASDU 的 I 帧, 测试帧以外的 U 帧. S 帧与测试帧由双方按各自的定时器产生
+fn replayable(kind: &ApciKind) -> bool {
+    match kind {
+        ApciKind::I(_) => true,
+        ApciKind::U(u) => u.function != U_TESTFR_ACTIVE && u.function != U_TESTFR_CONFIRM,
+        ApciKind::S(_) => false,
+    }
+}
+
+// 一个方向的字节流, 按 APDU 切分
+struct Stream {
+    origin: Origin,
+    codec: Codec,
+    buf: BytesMut,
+}
+
+impl Stream {
+    fn new(origin: Origin, params: AsduParams) -> Self {
+        Stream {
+            origin,
+            codec: Codec::new(params),
+            buf: BytesMut::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        offset: Duration,
+        data: &[u8],
+        records: &mut Vec<ReplayRecord>,
+    ) -> Result<(), Error> {
+        self.buf.extend_from_slice(data);
+        while let Some(apdu) = self.codec.decode(&mut self.buf)? {
+            if matches!(ApciKind::from(apdu.apci), ApciKind::I(_)) && apdu.asdu.is_none() {
+                log::warn!("[REPLAY] skip I-frame with invalid asdu");
+                continue;
+            }
+            records.push(ReplayRecord {
+                offset,
+                origin: self.origin,
+                apdu,
+            });
+        }
+        Ok(())
+    }
+}
+
+// pcap 文件中的报文: 时间戳, 链路类型, 报文数据
+type PcapPacket<'a> = (Duration, u32, &'a [u8]);
+
+fn read_pcap<B: ByteOrder>(data: &[u8]) -> Result<Vec<PcapPacket<'_>>, Error> {
+    let mut rdr = Cursor::new(data);
+    let magic = rdr.read_u32::<B>()?;
+    let nanos = match magic {
+        PCAP_MAGIC => false,
+        PCAP_MAGIC_NANOS => true,
+        _ => return Err(Error::ErrAnyHow(anyhow!("not a pcap file: {magic:#x}"))),
+    };
+    rdr.set_position(20);
+    let linktype = rdr.read_u32::<B>()?;
+
+    let mut packets = Vec::new();
+    while (rdr.position() as usize) < data.len() {
+        let secs = rdr.read_u32::<B>()? as u64;
+        let frac = rdr.read_u32::<B>()?;
+        let incl_len = rdr.read_u32::<B>()? as usize;
+        let _orig_len = rdr.read_u32::<B>()?;
+        let pos = rdr.position() as usize;
+        let packet = data
+            .get(pos..pos + incl_len)
+            .ok_or_else(|| Error::ErrAnyHow(anyhow!("truncated pcap record")))?;
+        rdr.set_position((pos + incl_len) as u64);
+        let ts = if nanos {
+            Duration::new(secs, frac)
+        } else {
+            Duration::new(secs, frac.saturating_mul(1000))
+        };
+        packets.push((ts, linktype, packet));
+    }
+    Ok(packets)
+}
+
+// 从链路层报文中取出 TCP 的源地址, 目的地址与负载
+fn tcp_payload(linktype: u32, packet: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let ip = match linktype {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => packet,
+        LINKTYPE_ETHERNET => {
+            let mut ethertype = BigEndian::read_u16(packet.get(12..14)?);
+            let mut offset = 14;
+            // 802.1Q VLAN 标签
+            while ethertype == 0x8100 {
+                ethertype = BigEndian::read_u16(packet.get(offset + 2..offset + 4)?);
+                offset += 4;
+            }
+            packet.get(offset..)?
+        }
+        LINKTYPE_LINUX_SLL => packet.get(16..)?,
+        _ => return None,
+    };
+
+    let (src_ip, dst_ip, tcp): (std::net::IpAddr, std::net::IpAddr, &[u8]) = match ip.first()? >> 4
+    {
+        4 => {
+            let ihl = (ip[0] & 0x0f) as usize * 4;
+            let total = BigEndian::read_u16(ip.get(2..4)?) as usize;
+            if *ip.get(9)? != 6 {
+                return None;
+            }
+            let src: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let dst: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            (src.into(), dst.into(), ip.get(ihl..total.min(ip.len()))?)
+        }
+        6 => {
+            let payload_len = BigEndian::read_u16(ip.get(4..6)?) as usize;
+            if *ip.get(6)? != 6 {
+                return None;
+            }
+            let src: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let dst: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            let end = (40 + payload_len).min(ip.len());
+            (src.into(), dst.into(), ip.get(40..end)?)
+        }
+        _ => return None,
+    };
+
+    let src_port = BigEndian::read_u16(tcp.get(0..2)?);
+    let dst_port = BigEndian::read_u16(tcp.get(2..4)?);
+    let data_offset = (tcp.get(12)? >> 4) as usize * 4;
+    Some((
+        SocketAddr::new(src_ip, src_port),
+        SocketAddr::new(dst_ip, dst_port),
+        tcp.get(data_offset..)?,
+    ))
+}
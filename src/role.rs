@@ -0,0 +1,16 @@
+// 连接的协议角色, 与 TCP 连接的方向无关. 一般由控制站(主站)发起连接,
+// 某些网络结构中由被控站(子站)主动连接控制站, 此时 Client 作为被控站, Server 的会话作为控制站
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Role {
+    /// 控制站: 发送 STARTDT/STOPDT 控制数据传输
+    #[default]
+    Controlling,
+    /// 被控站: 响应对端的 STARTDT/STOPDT
+    Controlled,
+}
+
+impl Role {
+    pub fn is_controlling(&self) -> bool {
+        *self == Role::Controlling
+    }
+}
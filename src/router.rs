@@ -0,0 +1,102 @@
+use std::{collections::BTreeMap, future};
+
+use chrono::{DateTime, Utc};
+use futures::future::Either;
+
+use crate::{
+    asdu::{Asdu, Cause, CommonAddr, InfoObjAddr},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    Error, ServerHandler,
+};
+
+type Unknown = future::Ready<Result<Vec<Asdu>, Error>>;
+
+// 按公共地址路由的 ServerHandler: 一个端点服务多个逻辑站(扇区)时, 每个公共地址一个处理函数.
+// 未注册的公共地址回复否定的未知公共地址. 广播公共地址的站命令由服务端按
+// Server::with_common_addrs 展开后逐个路由, 一般以 common_addrs() 设置
+#[derive(Clone)]
+pub struct CaRouter<H> {
+    routes: BTreeMap<CommonAddr, H>,
+}
+
+impl<H> Default for CaRouter<H> {
+    fn default() -> Self {
+        CaRouter {
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<H> CaRouter<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 公共地址 ca 的命令交给 handler, 重复注册时替换
+    #[must_use]
+    pub fn with_route(mut self, ca: CommonAddr, handler: H) -> Self {
+        self.routes.insert(ca, handler);
+        self
+    }
+
+    // 已注册的全部公共地址, 升序
+    pub fn common_addrs(&self) -> Vec<CommonAddr> {
+        self.routes.keys().copied().collect()
+    }
+
+    pub fn get(&self, ca: CommonAddr) -> Option<&H> {
+        self.routes.get(&ca)
+    }
+}
+
+impl<H> CaRouter<H>
+where
+    H: ServerHandler,
+{
+    fn route<F>(&self, asdu: Asdu, f: F) -> Either<H::Future, Unknown>
+    where
+        F: FnOnce(&H, Asdu) -> H::Future,
+    {
+        match self.routes.get(&asdu.identifier.common_addr) {
+            Some(handler) => Either::Left(f(handler, asdu)),
+            None => Either::Right(future::ready(Ok(vec![
+                asdu.mirror_negative(Cause::UnknownCA)
+            ]))),
+        }
+    }
+}
+
+impl<H> ServerHandler for CaRouter<H>
+where
+    H: ServerHandler,
+{
+    type Future = Either<H::Future, Unknown>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_interrogation(asdu, qoi))
+    }
+
+    fn call_counter_interrogation(&self, asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_counter_interrogation(asdu, qcc))
+    }
+
+    fn call_read(&self, asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_read(asdu, ioa))
+    }
+
+    fn call_clock_sync(&self, asdu: Asdu, time: Option<DateTime<Utc>>) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_clock_sync(asdu, time))
+    }
+
+    fn call_reset_process(&self, asdu: Asdu, qrp: ObjectQRP) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_reset_process(asdu, qrp))
+    }
+
+    fn call_delay_acquire(&self, asdu: Asdu, msec: u16) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call_delay_acquire(asdu, msec))
+    }
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        self.route(asdu, |h, asdu| h.call(asdu))
+    }
+}
@@ -0,0 +1,54 @@
+// 规一化值(NVA): 以 i16 定点数传送 -1 ~ +1-2⁻¹⁵, 最低位为 2⁻¹⁵
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nva(pub i16);
+
+impl Nva {
+    // 由规一化的浮点数转换, 超出 -1 ~ +1-2⁻¹⁵ 时取边界值
+    pub fn from_f32(normalized: f32) -> Self {
+        let v = (normalized * 32768.0).round();
+        Nva(v.clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / 32768.0
+    }
+}
+
+impl From<i16> for Nva {
+    fn from(v: i16) -> Self {
+        Nva(v)
+    }
+}
+
+impl From<Nva> for i16 {
+    fn from(v: Nva) -> Self {
+        v.0
+    }
+}
+
+// 规一化值与工程值的线性换算: -1 对应 min, +1 对应 max
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScaleTable {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ScaleTable {
+    pub fn new(min: f32, max: f32) -> Self {
+        ScaleTable { min, max }
+    }
+
+    pub fn to_engineering(&self, nva: Nva) -> f32 {
+        self.min + (nva.to_f32() + 1.0) / 2.0 * (self.max - self.min)
+    }
+
+    // 工程值超出 min ~ max 时取边界值; min 与 max 相等时为 0
+    pub fn to_nva(&self, value: f32) -> Nva {
+        if self.max == self.min {
+            return Nva(0);
+        }
+        Nva::from_f32((value - self.min) / (self.max - self.min) * 2.0 - 1.0)
+    }
+}
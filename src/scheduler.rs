@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use tokio::{
+    task::JoinHandle,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+
+use crate::{
+    asdu::{Asdu, CommonAddr},
+    DataStore, Error, ServerHandle,
+};
+
+// 周期/循环上送与背景扫描的调度器: 按各自的周期从 DataStore 取出召唤组的数据,
+// 以传送原因周期/循环(测量值)或背景扫描(除累计量外的全部点)经 ServerHandle 上送到激活的连接.
+// 周期与背景扫描数据在发送队列中优先级最低, 没有激活的连接时丢弃
+pub struct Scheduler {
+    store: DataStore,
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    ca: CommonAddr,
+    group: u8,
+    kind: Kind,
+    interval: Duration,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Cyclic,
+    Background,
+}
+
+impl Scheduler {
+    pub fn new(store: DataStore) -> Self {
+        Scheduler {
+            store,
+            entries: Vec::new(),
+        }
+    }
+
+    // 每隔 interval 上送公共地址 ca 下召唤组 group 的测量值, group 为 0 时为全部测量值.
+    // 可多次调用为不同的组设置不同的周期, interval 不能为 0
+    pub fn with_cyclic(mut self, ca: CommonAddr, group: u8, interval: Duration) -> Self {
+        self.entries.push(Entry {
+            ca,
+            group,
+            kind: Kind::Cyclic,
+            interval,
+        });
+        self
+    }
+
+    // 每隔 interval 背景扫描公共地址 ca 下召唤组 group 的点, group 为 0 时为全部点.
+    // 背景扫描的周期一般为分钟级, 远长于周期/循环上送
+    pub fn with_background(mut self, ca: CommonAddr, group: u8, interval: Duration) -> Self {
+        self.entries.push(Entry {
+            ca,
+            group,
+            kind: Kind::Background,
+            interval,
+        });
+        self
+    }
+
+    // 运行调度, 不会返回. 第一次上送在启动一个周期之后
+    pub async fn run(self, handle: ServerHandle) {
+        let Scheduler { store, entries } = self;
+        join_all(
+            entries
+                .into_iter()
+                .map(|entry| run_entry(store.clone(), handle.clone(), entry)),
+        )
+        .await;
+    }
+
+    // 在后台任务中运行调度, abort 返回的 JoinHandle 停止
+    pub fn spawn(self, handle: ServerHandle) -> JoinHandle<()> {
+        tokio::spawn(self.run(handle))
+    }
+}
+
+async fn run_entry(store: DataStore, handle: ServerHandle, entry: Entry) {
+    let mut ticker = interval_at(Instant::now() + entry.interval, entry.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let asdus = match entry.kind {
+            Kind::Cyclic => store.cyclic(entry.ca, entry.group),
+            Kind::Background => store.background(entry.ca, entry.group),
+        };
+        if let Err(e) = asdus.and_then(|asdus| send_all(&handle, asdus)) {
+            log::debug!("[SCHEDULER] {}/{} not sent: {e}", entry.ca, entry.group);
+        }
+    }
+}
+
+fn send_all(handle: &ServerHandle, asdus: Vec<Asdu>) -> Result<(), Error> {
+    for asdu in asdus {
+        handle.broadcast_asdu(asdu)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use bit_struct::*;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    asdu::{Cause, CauseOfTransmission, InfoObjAddr, Ioa, StationAddr, VariableStruct},
+    cpara::{ObjectQPA, ObjectQPM},
+    cproc::{ObjectDCO, ObjectQOC, ObjectQOS, ObjectSCO},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    file::{ObjectAFQ, ObjectFRQ, ObjectLSQ, ObjectSCQ, ObjectSOF, ObjectSRQ},
+    mproc::{ObjectDIQ, ObjectQDS, ObjectSCD, ObjectSIQ},
+    msys::ObjectCOI,
+};
+
+// 位结构按编码后的原始值序列化, 反序列化时校验各字段的取值
+macro_rules! serde_raw_u8 {
+    ($($name:ident),* $(,)?) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(self.raw())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let v = u8::deserialize(deserializer)?;
+                $name::try_from(v).map_err(|_| {
+                    D::Error::custom(format!("invalid {} {v:#04x}", stringify!($name)))
+                })
+            }
+        }
+    )*};
+}
+
+serde_raw_u8!(
+    VariableStruct,
+    CauseOfTransmission,
+    ObjectSIQ,
+    ObjectDIQ,
+    ObjectQDS,
+    ObjectSCO,
+    ObjectDCO,
+    ObjectQOC,
+    ObjectQOS,
+    ObjectQOI,
+    ObjectQCC,
+    ObjectQRP,
+    ObjectQPM,
+    ObjectQPA,
+    ObjectCOI,
+    ObjectFRQ,
+    ObjectSRQ,
+    ObjectSCQ,
+    ObjectLSQ,
+    ObjectAFQ,
+    ObjectSOF,
+);
+
+// 信息对象地址按 3 字节的值序列化
+impl Serialize for InfoObjAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.raw().value())
+    }
+}
+
+impl<'de> Deserialize<'de> for InfoObjAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = u32::deserialize(deserializer)?;
+        u24::new(v)
+            .and_then(|v| InfoObjAddr::try_from(v).ok())
+            .ok_or_else(|| D::Error::custom(format!("invalid information object address {v}")))
+    }
+}
+
+impl Serialize for ObjectSCD {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.raw().value())
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectSCD {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = u64::deserialize(deserializer)?;
+        u40::new(v)
+            .and_then(|v| ObjectSCD::try_from(v).ok())
+            .ok_or_else(|| D::Error::custom(format!("invalid ObjectSCD {v:#x}")))
+    }
+}
+
+// 传送原因按编号序列化
+impl Serialize for Cause {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cause {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = u8::deserialize(deserializer)?;
+        // 传送原因占 COT 的低 6 位
+        match CauseOfTransmission::try_from(v) {
+            Ok(mut cot) if v < 0x40 => Ok(cot.cause().get()),
+            _ => Err(D::Error::custom(format!("invalid cause {v}"))),
+        }
+    }
+}
+
+// Ioa 与 StationAddr 反序列化时同样校验取值范围
+impl Serialize for Ioa {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ioa {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ioa::new(u32::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for StationAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for StationAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        StationAddr::new(u16::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
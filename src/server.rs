@@ -1,37 +1,314 @@
-use std::{collections::VecDeque, io, net::SocketAddr, ops::Deref, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
 use futures::{SinkExt, StreamExt};
+use socket2::SockRef;
 use std::future::Future;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    net::{TcpListener, TcpStream},
+    net::{TcpListener, TcpSocket, TcpStream},
     select,
     sync::mpsc,
+    task::JoinSet,
 };
-use tokio_util::codec::Framed;
+use tokio_util::{codec::Framed, sync::CancellationToken};
 
+#[cfg(feature = "tls")]
+use crate::ServerTlsConfig;
 use crate::{
+    access::{AccessControl, ConnectFilter, Decision},
     apci::{
-        new_iframe, new_sframe, new_uframe, update_ack_no_out, ApciKind, SApci, UApci,
+        new_iframe, new_sframe, new_uframe, update_ack_no_out, ApciKind, SApci, SeqNum, UApci,
         U_STARTDT_ACTIVE, U_STARTDT_CONFIRM, U_STOPDT_ACTIVE, U_STOPDT_CONFIRM, U_TESTFR_ACTIVE,
         U_TESTFR_CONFIRM,
     },
-    asdu::{Asdu, Cause, TypeID, INFO_OBJ_ADDR_IRRELEVANT, INVALID_COMMON_ADDR},
-    csys::{ObjectQCC, ObjectQOI},
-    Codec, Error, Request, SeqPending,
+    asdu::{
+        Asdu, AsduParams, Cause, CauseOfTransmission, CommonAddr, InfoObjAddr, OriginAddr, TypeID,
+        GLOBAL_COMMON_ADDR, INFO_OBJ_ADDR_IRRELEVANT, INVALID_COMMON_ADDR,
+    },
+    authorizer::{
+        command_time, is_control_command, is_invalid_command, is_time_tagged_command, rejection,
+    },
+    client::recv_request,
+    csys::{clock_synchronization_cmd, ObjectQCC, ObjectQOI, ObjectQRP, FBPTEST_WORD},
+    msys::{end_of_initialization, ObjectCOI},
+    observer::Observers,
+    pacing::Pacer,
+    session::{ServerHandle, SessionGuard, SessionRegistry},
+    socket::{bind_device, Keepalive},
+    time::Cp56Time2a,
+    trace::{self, Direction},
+    watchdog::LinkIdle,
+    Authorization, Codec, CommandAgePolicy, CommandAuthorizer, CommandRequest, Error,
+    FrameObserver, Pacing, Request, Resync, Role, SendQueue, SendQueueOption, SeqPending, Stats,
+    TestFrPolicy, DEFAULT_CHANNEL_DEPTH,
 };
 
-// TODO: add ServerSession to server
 pub struct Server {
     listener: TcpListener,
+    sessions: Arc<SessionRegistry>,
+    // 各会话的配置
+    session: SessionOption,
+    // 停止服务的信号, 取消后不再接受新连接并关闭全部会话
+    shutdown: CancellationToken,
+    // 监听与连接数的限制
+    option: ServerOption,
+    // 按对端 IP 的允许/拒绝列表
+    access: AccessControl,
+    // 按对端地址决定是否接受连接及会话配置
+    connect_filter: Option<Arc<dyn ConnectFilter>>,
+    // 为 Some 时, 对 on_connected 返回的传输层进行 TLS 握手
+    #[cfg(feature = "tls")]
+    tls: Option<ServerTlsConfig>,
+}
+
+// 会话的配置, 每个连接复制一份
+#[derive(Clone)]
+struct SessionOption {
+    // ASDU 各字段长度
+    params: AsduParams,
+    // 原始帧监听者
+    observers: Observers,
+    // 控制命令的授权
+    authorizer: Option<Arc<dyn CommandAuthorizer>>,
+    // 带时标控制命令的时标检查
+    command_age: Option<CommandAgePolicy>,
+    // 发送的 I 帧或测试帧等待确认的超时时间
+    t1: Duration,
+    // 本站的公共地址, 用于响应广播公共地址的命令
+    common_addrs: Arc<[CommonAddr]>,
+    // 时钟同步命令的处理方式
+    clock_sync: ClockSyncMode,
+    // I 帧的发送节奏
+    pacing: Pacing,
+    // 会话的协议角色
+    role: Role,
+    // 数据传输激活前收到 I 帧的处理方式
+    inactive_iframe: InactiveIFramePolicy,
+    // 收到错误的起始字符或长度时的处理方式
+    resync: Resync,
+    // 测试帧的发送间隔与允许丢失的确认数
+    testfr: TestFrPolicy,
+    // 未收到任何帧超过该时长时关闭连接
+    idle_timeout: Option<Duration>,
+    // 启动后上送的初始化结束
+    end_of_init: Option<EndOfInit>,
+}
+
+// 初始化结束的初始化原因, pending 在各会话间共享, 只由第一个激活的会话上送一次
+#[derive(Clone)]
+struct EndOfInit {
+    coi: ObjectCOI,
+    pending: Arc<AtomicBool>,
+}
+
+impl Default for SessionOption {
+    fn default() -> Self {
+        SessionOption {
+            params: AsduParams::default(),
+            observers: Observers::default(),
+            authorizer: None,
+            command_age: None,
+            t1: Duration::from_secs(15),
+            common_addrs: Arc::new([]),
+            clock_sync: ClockSyncMode::Handler,
+            pacing: Pacing::default(),
+            role: Role::Controlled,
+            inactive_iframe: InactiveIFramePolicy::Process,
+            resync: Resync::Strict,
+            testfr: TestFrPolicy::default(),
+            idle_timeout: None,
+            end_of_init: None,
+        }
+    }
+}
+
+// 时钟同步命令(C_CS_NA_1)的处理方式
+#[derive(Clone, Default)]
+pub enum ClockSyncMode {
+    /// 交给 ServerHandler::call_clock_sync
+    #[default]
+    Handler,
+    /// 不校时, 以本站时钟回复激活确认
+    LocalClock,
+    /// 以收到的时标(含 IV/SU 品质位)调用回调校时, 返回 true 时回复肯定的激活确认, 否则回复否定确认
+    Apply(Arc<dyn Fn(Cp56Time2a) -> bool + Send + Sync>),
+}
+
+// 数据传输未激活(未收到 STARTDT, 或已被 STOPDT 停止)时收到 I 帧的处理方式.
+// 按标准被控站在 STARTDT 之前不应处理 I 帧, 严格模式下应关闭连接
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InactiveIFramePolicy {
+    /// 照常处理, 兼容不发送 STARTDT 的控制站
+    #[default]
+    Process,
+    /// 确认但不处理, 丢弃其中的 ASDU
+    Ignore,
+    /// 关闭连接, on_process_error 收到 ErrIFrameBeforeStartDt
+    Close,
+}
+
+// 服务端的监听与连接配置. 超出会话数限制的连接在握手前关闭, on_process_error 收到
+// ErrTooManySessions 或 ErrTooManySessionsPerIp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerOption {
+    // 同时存在的会话数上限
+    pub(crate) max_sessions: Option<usize>,
+    // 同一对端 IP 的会话数上限
+    pub(crate) max_sessions_per_ip: Option<usize>,
+    // 监听队列长度, 仅 Server::bind 使用
+    pub(crate) backlog: u32,
+    // 未收到任何帧超过该时长时关闭会话, on_process_error 收到 ErrIdleTimeout
+    pub(crate) idle_timeout: Option<Duration>,
+    // 监听套接字的 SO_KEEPALIVE, 由接受的连接继承, 仅 Server::bind 使用
+    pub(crate) keepalive: bool,
+    // 保活参数, 设置后对监听套接字与接受的连接生效
+    pub(crate) keepalive_params: Option<Keepalive>,
+    // 接受的连接设置 TCP_NODELAY
+    pub(crate) nodelay: bool,
+    // 监听套接字绑定的网络接口名, 仅 Server::bind 使用
+    pub(crate) bind_device: Option<String>,
+}
+
+impl Default for ServerOption {
+    fn default() -> Self {
+        ServerOption {
+            max_sessions: None,
+            max_sessions_per_ip: None,
+            backlog: 1024,
+            idle_timeout: None,
+            keepalive: false,
+            keepalive_params: None,
+            nodelay: true,
+            bind_device: None,
+        }
+    }
+}
+
+impl ServerOption {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_max_sessions(mut self, max: usize) -> Self {
+        self.max_sessions = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_sessions_per_ip(mut self, max: usize) -> Self {
+        self.max_sessions_per_ip = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn with_backlog(mut self, backlog: u32) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    #[must_use]
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn with_keepalive(mut self, keepalive: bool) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    // 开启 SO_KEEPALIVE 并设置保活参数
+    #[must_use]
+    pub fn with_keepalive_params(mut self, keepalive: Keepalive) -> Self {
+        self.keepalive = true;
+        self.keepalive_params = Some(keepalive);
+        self
+    }
+
+    #[must_use]
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    // 只接受经该网络接口的连接, 用于多网卡网关. 仅 Linux 与 Android 支持
+    #[must_use]
+    pub fn with_bind_device(mut self, device: impl Into<String>) -> Self {
+        self.bind_device = Some(device.into());
+        self
+    }
+}
+
+// 各对端 IP 当前的会话数
+#[derive(Debug, Clone, Default)]
+struct Connections(Arc<Mutex<HashMap<IpAddr, usize>>>);
+
+impl Connections {
+    // 按 option 的限制登记一个连接, 会话结束时随 ConnectionGuard 释放
+    fn acquire(&self, peer: SocketAddr, option: &ServerOption) -> Result<ConnectionGuard, Error> {
+        let mut counts = self.0.lock().unwrap();
+        if option
+            .max_sessions
+            .is_some_and(|max| counts.values().sum::<usize>() >= max)
+        {
+            return Err(Error::ErrTooManySessions(peer));
+        }
+        let count = counts.entry(peer.ip()).or_default();
+        if option.max_sessions_per_ip.is_some_and(|max| *count >= max) {
+            return Err(Error::ErrTooManySessionsPerIp(peer));
+        }
+        *count += 1;
+        Ok(ConnectionGuard {
+            connections: self.clone(),
+            ip: peer.ip(),
+        })
+    }
+}
+
+struct ConnectionGuard {
+    connections: Connections,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut counts = self.connections.0.lock().unwrap();
+        if let Some(count) = counts.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&self.ip);
+            }
+        }
+    }
 }
 
+// 被控站对收到的 ASDU 的处理. 请求来自哪个连接通过 SessionContext::current 获取
 pub trait ServerHandler {
     type Future: Future<Output = Result<Vec<Asdu>, Error>> + Send;
 
     fn call_interrogation(&self, _: Asdu, qoi: ObjectQOI) -> Self::Future;
     fn call_counter_interrogation(&self, _: Asdu, qcc: ObjectQCC) -> Self::Future;
+    // 读命令, 返回被请求的信息对象; 返回空集合表示信息对象地址未知, 将回复未知的信息对象地址
+    fn call_read(&self, _: Asdu, ioa: InfoObjAddr) -> Self::Future;
+    // 时钟同步命令, 返回的 ASDU 一般为带本端时间的激活确认
+    fn call_clock_sync(&self, _: Asdu, time: Option<DateTime<Utc>>) -> Self::Future;
+    // 复位进程命令, 返回空集合时回复肯定的激活确认
+    fn call_reset_process(&self, _: Asdu, qrp: ObjectQRP) -> Self::Future;
+    // 延时获得命令, 返回空集合时回复肯定的激活确认
+    fn call_delay_acquire(&self, _: Asdu, msec: u16) -> Self::Future;
     fn call(&self, asdu: Asdu) -> Self::Future;
 }
 
@@ -52,16 +329,287 @@ where
     fn call_counter_interrogation(&self, _asdu: Asdu, qcc: ObjectQCC) -> Self::Future {
         self.deref().call_counter_interrogation(_asdu, qcc)
     }
+    fn call_read(&self, _asdu: Asdu, ioa: InfoObjAddr) -> Self::Future {
+        self.deref().call_read(_asdu, ioa)
+    }
+    fn call_clock_sync(&self, _asdu: Asdu, time: Option<DateTime<Utc>>) -> Self::Future {
+        self.deref().call_clock_sync(_asdu, time)
+    }
+    fn call_reset_process(&self, _asdu: Asdu, qrp: ObjectQRP) -> Self::Future {
+        self.deref().call_reset_process(_asdu, qrp)
+    }
+    fn call_delay_acquire(&self, _asdu: Asdu, msec: u16) -> Self::Future {
+        self.deref().call_delay_acquire(_asdu, msec)
+    }
 }
 
 struct ServerSession {
     sender: Option<mpsc::UnboundedSender<Request>>,
+    registry: Arc<SessionRegistry>,
+    peer: SocketAddr,
+    op: SessionOption,
+    shutdown: CancellationToken,
 }
 
 impl Server {
     #[must_use]
     pub fn new(listener: TcpListener) -> Self {
-        Self { listener }
+        Self {
+            listener,
+            sessions: Arc::new(SessionRegistry::new(
+                false,
+                SendQueueOption::default(),
+                DEFAULT_CHANNEL_DEPTH,
+            )),
+            session: SessionOption::default(),
+            shutdown: CancellationToken::new(),
+            option: ServerOption::default(),
+            access: AccessControl::default(),
+            connect_filter: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    // 按 option 的监听队列长度, 保活与网络接口配置监听 addr.
+    // addr 为 IPv6 的任意地址([::])时同时接受 IPv4 连接
+    pub fn bind(addr: SocketAddr, option: ServerOption) -> io::Result<Self> {
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        if addr.is_ipv6() && addr.ip().is_unspecified() {
+            SockRef::from(&socket).set_only_v6(false)?;
+        }
+        socket.set_reuseaddr(true)?;
+        socket.set_keepalive(option.keepalive)?;
+        if let Some(keepalive) = &option.keepalive_params {
+            keepalive.apply(SockRef::from(&socket))?;
+        }
+        if let Some(device) = &option.bind_device {
+            bind_device(SockRef::from(&socket), device)?;
+        }
+        socket.bind(addr)?;
+        let listener = socket.listen(option.backlog)?;
+        Ok(Self::new(listener).with_option(option))
+    }
+
+    // 监听的本地地址
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // 会话数限制, 会话空闲超时与 TCP 选项
+    #[must_use]
+    pub fn with_option(mut self, option: ServerOption) -> Self {
+        self.session.idle_timeout = option.idle_timeout;
+        self.option = option;
+        self
+    }
+
+    // 是否允许所有连接同时处于数据传输激活状态, 默认同一时刻只有一个连接激活,
+    // 新连接的 STARTDT 会使原激活的连接转为非激活
+    #[must_use]
+    pub fn with_all_active(mut self, all_active: bool) -> Self {
+        let queue = self.sessions.queue_option();
+        let depth = self.sessions.channel_depth();
+        self.sessions = Arc::new(SessionRegistry::new(all_active, queue, depth));
+        self
+    }
+
+    // I 帧发送队列的容量, 溢出处理及没有激活的连接时是否缓存突发数据
+    #[must_use]
+    pub fn with_send_queue(mut self, queue: SendQueueOption) -> Self {
+        let all_active = self.sessions.all_active();
+        let depth = self.sessions.channel_depth();
+        self.sessions = Arc::new(SessionRegistry::new(all_active, queue, depth));
+        self
+    }
+
+    // 每个连接的命令通道深度, 默认 DEFAULT_CHANNEL_DEPTH.
+    // 对端停止接收导致通道满时, broadcast_asdu 返回 ErrBufferFull
+    #[must_use]
+    pub fn with_channel_depth(mut self, depth: usize) -> Self {
+        let all_active = self.sessions.all_active();
+        let queue = self.sessions.queue_option();
+        self.sessions = Arc::new(SessionRegistry::new(all_active, queue, depth.max(1)));
+        self
+    }
+
+    // ASDU 各字段长度, 默认为 IEC 104 标准长度, 仅在对端使用非标准长度时设置
+    #[must_use]
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        self.session.params = params;
+        self
+    }
+
+    // 注册原始帧监听者, 接收全部连接收发的每一个 APDU, 可多次调用注册多个
+    #[must_use]
+    pub fn with_frame_observer<O>(mut self, observer: O) -> Self
+    where
+        O: FrameObserver,
+    {
+        self.session.observers.push(Arc::new(observer));
+        self
+    }
+
+    // 控制命令交给 ServerHandler 之前的授权, 被拒绝的命令由会话直接回复否定确认
+    #[must_use]
+    pub fn with_command_authorizer<A>(mut self, authorizer: A) -> Self
+    where
+        A: CommandAuthorizer,
+    {
+        self.session.authorizer = Some(Arc::new(authorizer));
+        self
+    }
+
+    // 带时标的控制命令交给 ServerHandler 之前检查时标, 过期的命令由会话直接回复否定确认
+    #[must_use]
+    pub fn with_command_age(mut self, policy: CommandAgePolicy) -> Self {
+        self.session.command_age = Some(policy);
+        self
+    }
+
+    // 发送或测试 APDU 的超时时间 t1, 默认 15 秒. 发送的 I 帧或 TESTFR 在 t1 内未被确认时
+    // 关闭连接, on_process_error 收到 ErrT1Timeout
+    #[must_use]
+    pub fn with_t1(mut self, t1: Duration) -> Self {
+        self.session.t1 = t1;
+        self
+    }
+
+    // 本站的公共地址. 召唤, 计数量召唤, 时钟同步与复位进程命令使用广播公共地址(GLOBAL_COMMON_ADDR)时,
+    // 依次以各公共地址交给 ServerHandler, 响应带本站的公共地址; 未设置时广播命令原样交给 ServerHandler.
+    // 其他命令使用广播公共地址, 或设置后收到不在其中的公共地址时, 回复否定的未知公共地址.
+    // 一个端点服务多个逻辑站时, 配合 CaRouter 将命令交给各公共地址的处理函数
+    #[must_use]
+    pub fn with_common_addrs<I>(mut self, common_addrs: I) -> Self
+    where
+        I: IntoIterator<Item = CommonAddr>,
+    {
+        self.session.common_addrs = common_addrs.into_iter().collect();
+        self
+    }
+
+    // 服务启动后第一个激活的会话在 STARTDT 确认之后, 以 coi 为每个本站公共地址(见 with_common_addrs)
+    // 上送初始化结束 M_EI_NA_1. 未设置公共地址时不上送
+    #[must_use]
+    pub fn with_end_of_initialization(mut self, coi: ObjectCOI) -> Self {
+        self.session.end_of_init = Some(EndOfInit {
+            coi,
+            pending: Arc::new(AtomicBool::new(true)),
+        });
+        self
+    }
+
+    // 各会话 I 帧的发送节奏, 默认不限制
+    #[must_use]
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        self.session.pacing = pacing;
+        self
+    }
+
+    // 会话的协议角色, 默认为被控站. 为 Role::Controlling 时由被控站发起连接, 会话作为控制站:
+    // 接受连接后发送 STARTDT, 收到确认后激活, 收到的 ASDU 全部交给 ServerHandler::call,
+    // 经 ServerHandle 向激活的会话发送命令
+    #[must_use]
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.session.role = role;
+        self
+    }
+
+    // 数据传输激活前收到 I 帧的处理方式, 默认照常处理
+    #[must_use]
+    pub fn with_inactive_iframe_policy(mut self, policy: InactiveIFramePolicy) -> Self {
+        self.session.inactive_iframe = policy;
+        self
+    }
+
+    // 收到错误的起始字符或长度时的处理方式, 默认 Resync::Strict 关闭连接.
+    // Resync::Lenient 丢弃字节直到下一个起始字符, 丢弃的字节数计入会话统计
+    #[must_use]
+    pub fn with_resync(mut self, resync: Resync) -> Self {
+        self.session.resync = resync;
+        self
+    }
+
+    // 各会话测试帧的发送间隔(t3)与链路失效前允许丢失的确认数, 默认空闲 20 秒发送, 丢失 1 次即关闭连接
+    #[must_use]
+    pub fn with_testfr(mut self, testfr: TestFrPolicy) -> Self {
+        self.session.testfr = testfr;
+        self
+    }
+
+    // 按对端 IP 的允许/拒绝列表, 被拒绝的连接直接关闭, on_process_error 收到 ErrAccessDenied
+    #[must_use]
+    pub fn with_access_control(mut self, access: AccessControl) -> Self {
+        self.access = access;
+        self
+    }
+
+    // 通过访问控制后的连接过滤, 返回 Decision::AcceptWith 时按对端覆盖协议角色与公共地址.
+    // 过滤在接受新连接的任务中执行, 应尽快返回
+    #[must_use]
+    pub fn with_connect_filter<F>(mut self, filter: F) -> Self
+    where
+        F: ConnectFilter,
+    {
+        self.connect_filter = Some(Arc::new(filter));
+        self
+    }
+
+    // 时钟同步命令的处理方式, 默认交给 ServerHandler
+    #[must_use]
+    pub fn with_clock_sync(mut self, mode: ClockSyncMode) -> Self {
+        self.session.clock_sync = mode;
+        self
+    }
+
+    // 使用外部的停止信号, 例如应用的根 CancellationToken 或其子令牌
+    #[must_use]
+    pub fn with_shutdown(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    // 停止服务: serve 不再接受新连接, 各会话发完队列中的 I 帧并等待对端确认(最长 t1)后
+    // 关闭连接, 全部会话结束后 serve 返回
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    // 当前全部连接的对端地址及是否处于激活状态
+    pub fn sessions(&self) -> Vec<(SocketAddr, bool)> {
+        self.sessions.sessions()
+    }
+
+    // 当前全部连接的对端地址及会话统计
+    pub fn stats(&self) -> Vec<(SocketAddr, Stats)> {
+        self.sessions.stats()
+    }
+
+    // 获取服务端句柄, 可在 serve 运行期间从其他任务主动上送 ASDU
+    pub fn handle(&self) -> ServerHandle {
+        ServerHandle::new(self.sessions.clone(), self.shutdown.clone())
+    }
+
+    // 向处于激活状态的连接发送 ASDU, 一般为突发(自发)传送原因
+    pub fn broadcast_asdu(&self, asdu: Asdu) -> Result<(), Error> {
+        self.handle().broadcast_asdu(asdu)
+    }
+
+    // 批量发送, 同 ServerHandle::broadcast_batch
+    pub fn broadcast_batch(&self, asdus: Vec<Asdu>) -> Result<(), Error> {
+        self.handle().broadcast_batch(asdus)
+    }
+
+    // 使用 IEC 62351-3 TLS 加密链路, 端口一般为 IEC62351_TLS_PORT
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn with_tls(mut self, tls: ServerTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
     }
 
     pub async fn serve<S, T, F, OnConnected, OnprocessError>(
@@ -76,31 +624,130 @@ impl Server {
         F: Future<Output = io::Result<Option<(S, T)>>>,
         OnprocessError: FnOnce(Error) + Clone + Send + 'static,
     {
+        let mut tasks = JoinSet::new();
+        let connections = Connections::default();
         loop {
-            let (stream, socket_addr) = self.listener.accept().await?;
+            let (stream, socket_addr) = select! {
+                accepted = self.listener.accept() => accepted?,
+                Some(_) = tasks.join_next() => continue,
+                _ = self.shutdown.cancelled() => break,
+            };
+            // 双栈监听时 IPv4 对端为映射地址(::ffff:a.b.c.d), 还原为 IPv4 以匹配访问控制
+            let socket_addr = SocketAddr::new(socket_addr.ip().to_canonical(), socket_addr.port());
             log::debug!("Accepted connection from {socket_addr}");
 
+            let decision = if !self.access.is_allowed(socket_addr.ip()) {
+                Decision::Reject
+            } else if let Some(filter) = &self.connect_filter {
+                filter.filter(socket_addr).await
+            } else {
+                Decision::Accept
+            };
+            let mut op = self.session.clone();
+            match decision {
+                Decision::Accept => (),
+                Decision::AcceptWith(policy) => {
+                    if let Some(role) = policy.role {
+                        op.role = role;
+                    }
+                    if let Some(common_addrs) = policy.common_addrs {
+                        op.common_addrs = common_addrs.into();
+                    }
+                }
+                Decision::Reject => {
+                    log::warn!("Reject connection from {socket_addr}: access denied");
+                    on_process_error.clone()(Error::ErrAccessDenied(socket_addr));
+                    continue;
+                }
+            }
+
+            // 超出限制的连接直接关闭
+            let guard = match connections.acquire(socket_addr, &self.option) {
+                Ok(guard) => guard,
+                Err(err) => {
+                    log::warn!("Reject connection from {socket_addr}: {err}");
+                    on_process_error.clone()(err);
+                    continue;
+                }
+            };
+            if self.option.nodelay {
+                if let Err(err) = stream.set_nodelay(true) {
+                    log::warn!("Set TCP_NODELAY for {socket_addr}: {err}");
+                }
+            }
+            if let Some(keepalive) = &self.option.keepalive_params {
+                if let Err(err) = keepalive.apply(SockRef::from(&stream)) {
+                    log::warn!("Set keepalive for {socket_addr}: {err}");
+                }
+            }
+
             let Some((handler, transport)) = on_connected(stream, socket_addr).await? else {
                 log::debug!("No ServerHandler for connection from {socket_addr}");
                 continue;
             };
             let on_process_error = on_process_error.clone();
+            let registry = self.sessions.clone();
+            let shutdown = self.shutdown.clone();
+            #[cfg(feature = "tls")]
+            let acceptor = self.tls.as_ref().map(|tls| tls.acceptor());
 
-            tokio::spawn(async move {
+            let session = async move {
+                let _guard = guard;
                 log::debug!("Processing requests from {socket_addr}");
-                let mut session = ServerSession::new();
-                if let Err(err) = session.run(transport, handler).await {
+                let mut session = ServerSession::new(registry, socket_addr, op, shutdown);
+                #[cfg(feature = "tls")]
+                let result = match acceptor {
+                    Some(acceptor) => match acceptor.accept(transport).await {
+                        Ok(stream) => session.run(stream, handler).await,
+                        Err(err) => Err(Error::Io(err)),
+                    },
+                    None => session.run(transport, handler).await,
+                };
+                #[cfg(not(feature = "tls"))]
+                let result = session.run(transport, handler).await;
+                if let Err(err) = result {
                     session.sender = None;
                     on_process_error(err);
                 }
-            });
+            };
+            tasks.spawn(trace::in_connection_span("server", socket_addr, session));
         }
+        log::info!("Shutting down, waiting for {} sessions", tasks.len());
+        while tasks.join_next().await.is_some() {}
+        Ok(())
     }
 }
 
+// 不经过监听, 直接在 transport 上运行一个会话, 用于内存中的测试
+pub(crate) async fn serve_transport<S, T>(
+    transport: T,
+    handler: S,
+    peer: SocketAddr,
+    registry: Arc<SessionRegistry>,
+    shutdown: CancellationToken,
+) -> Result<(), Error>
+where
+    S: ServerHandler + Send + Sync + 'static,
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut session = ServerSession::new(registry, peer, SessionOption::default(), shutdown);
+    session.run(transport, handler).await
+}
+
 impl ServerSession {
-    pub fn new() -> Self {
-        ServerSession { sender: None }
+    fn new(
+        registry: Arc<SessionRegistry>,
+        peer: SocketAddr,
+        op: SessionOption,
+        shutdown: CancellationToken,
+    ) -> Self {
+        ServerSession {
+            sender: None,
+            registry,
+            peer,
+            op,
+            shutdown,
+        }
     }
 
     pub async fn run<S, T>(&mut self, transport: T, handler: S) -> Result<(), Error>
@@ -108,99 +755,199 @@ impl ServerSession {
         S: ServerHandler + Send + Sync + 'static,
         T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     {
-        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel(self.registry.channel_depth());
         self.sender = Some(tx.clone());
+        let session = self.registry.register(self.peer, cmd_tx);
+        // ServerHandler 的调用均在会话任务内, 通过 SessionContext::current 获取当前会话
+        let context = session.context(self.peer, self.op.params, self.op.role);
+        context
+            .scope(self.process(transport, handler, session, tx, rx, cmd_rx))
+            .await
+    }
 
-        let mut framed = Framed::new(transport, Codec);
+    async fn process<S, T>(
+        &mut self,
+        transport: T,
+        handler: S,
+        session: SessionGuard,
+        tx: mpsc::UnboundedSender<Request>,
+        mut rx: mpsc::UnboundedReceiver<Request>,
+        mut cmd_rx: mpsc::Receiver<Request>,
+    ) -> Result<(), Error>
+    where
+        S: ServerHandler + Send + Sync + 'static,
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let stats = session.stats();
+        stats.opened();
 
-        let mut is_active = false;
+        let mut framed = Framed::new(
+            transport,
+            Codec::new(self.op.params).with_resync(self.op.resync),
+        );
 
-        let mut send_sn = 0;
-        let mut ack_sendsn = 0;
-        let mut rcv_sn = 0;
-        let mut ack_rcvsn = 0;
+        let mut send_sn = SeqNum::default();
+        let mut ack_sendsn = SeqNum::default();
+        let mut rcv_sn = SeqNum::default();
+        let mut ack_rcvsn = SeqNum::default();
 
-        let mut idle_timeout3_sine = Utc::now();
+        let mut idle = LinkIdle::new();
         let mut test4alive_send_since = DateTime::<Utc>::MAX_UTC;
+        // 连续未被确认的测试帧个数
+        let mut testfr_missed = 0;
         let mut un_ack_rcv_since = DateTime::<Utc>::MAX_UTC;
 
-        // 对于server端，无需对应的U-Frame 无需判断
-        // let mut start_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
+        // 被控站无需等待 U 帧确认; 作为控制站时等待 STARTDT 确认
+        let mut start_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
         // let mut stop_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
 
         let mut pending: VecDeque<SeqPending> = VecDeque::new();
+        // 待发送的 I 帧, 按优先级发送
+        let mut queue = SendQueue::new(self.registry.queue_option());
+
+        let mut pacer = Pacer::new(self.op.pacing);
+
+        if self.op.role.is_controlling() {
+            tx.send(Request::U(UApci {
+                function: U_STARTDT_ACTIVE,
+            }))?;
+        }
 
         let mut check_timer = tokio::time::interval(Duration::from_millis(100));
+        // 会话的结束原因, t1 超时时为 Err
+        let mut result = Ok(());
+        // 收到停止信号后, 发完队列并等待已发送的 I 帧被确认后关闭连接
+        let mut stopping = false;
 
         'outer: loop {
+            // 会话未激活时, 队列中的突发数据转交其他激活的会话或缓存
+            let active = session.is_active();
+            if !active && !queue.is_empty() {
+                for asdu in queue.take_offline() {
+                    let _ = self.registry.requeue(asdu);
+                }
+            }
+            let can_send = active && !queue.is_empty();
+            if stopping && !can_send && pending.is_empty() && rx.is_empty() && cmd_rx.is_empty() {
+                if ack_rcvsn != rcv_sn {
+                    let apdu = new_sframe(rcv_sn.value());
+                    trace::frame(Direction::Tx, &apdu);
+                    stats.record_tx(&apdu);
+                    self.op.observers.on_tx(&apdu);
+                    framed.send(apdu).await?;
+                }
+                log::info!("[SHUTDOWN] close connection to {}", self.peer);
+                break 'outer;
+            }
             select! {
 
+                _ = self.shutdown.cancelled(), if !stopping => {
+                    stopping = true;
+                    cmd_rx.close();
+                }
+
                 _ = check_timer.tick() => {
-                    if Utc::now() - Duration::from_secs(15) >= test4alive_send_since {
-                       // Utc::now() - Duration::from_secs(15) >= start_dt_active_send_since ||
-                       // Utc::now() - Duration::from_secs(15) >= stop_dt_active_send_since
+                    stats.depth(queue.len(), pending.len());
+                    // 测试帧 t1 内未被确认, 连续丢失 max_missed 次后关闭连接, 否则重发
+                    if Utc::now() - self.op.t1 >= test4alive_send_since {
+                        testfr_missed += 1;
+                        if testfr_missed >= self.op.testfr.max_missed() {
+                            log::error!("[CHECK TIMER] test frame alive confirm timeout t, missed {testfr_missed}");
+                            stats.timeout();
+                            result = Err(Error::ErrT1Timeout);
+                            break 'outer
+                        }
+                        log::warn!("[CHECK TIMER] test frame confirm missed {testfr_missed}/{}, resend", self.op.testfr.max_missed());
+                        tx.send(Request::U(UApci{ function: U_TESTFR_ACTIVE}))?;
+                        idle.restart();
+                        test4alive_send_since = Utc::now();
+                    }
+
+                    if self.op.idle_timeout.is_some_and(|timeout| idle.rx_idle() >= timeout) {
+                        log::info!("[CHECK TIMER] no frame from {} for {:?}, close", self.peer, idle.rx_idle());
+                        result = Err(Error::ErrIdleTimeout);
+                        break 'outer
+                    }
+
+                    // t1 超时: STARTDT 未确认或 I 帧未被确认, 关闭连接
+                    if Utc::now() - self.op.t1 >= start_dt_active_send_since {
                        log::error!("[CHECK TIMER] test frame alive confirm timeout t");
+                       stats.timeout();
+                       result = Err(Error::ErrT1Timeout);
                        break 'outer
                     }
 
-                    if  ack_sendsn != send_sn &&
-                        Utc::now() - Duration::from_secs(15) >= pending[0].send_time {
-                        log::warn!("[CHECK TIMER] send ack [sq:{ack_sendsn}] timeout");
-                        ack_sendsn += 1;
-                        pending.pop_front();
+                    if pending.front().is_some_and(|p| Utc::now() - self.op.t1 >= p.send_time) {
+                        log::error!("[CHECK TIMER] send ack [sq:{ack_sendsn}] timeout");
+                        stats.timeout();
+                        result = Err(Error::ErrT1Timeout);
+                        break 'outer
                     }
 
                     if ack_rcvsn != rcv_sn && (un_ack_rcv_since + Duration::from_secs(10) <= Utc::now() ||
-                        idle_timeout3_sine + Duration::from_millis(100) <= Utc::now()) {
-                            tx.send(Request::S(SApci { rcv_sn  }))?;
+                        idle.rx_idle() >= Duration::from_millis(100)) {
+                            tx.send(Request::S(SApci { rcv_sn: rcv_sn.value() }))?;
                             ack_rcvsn = rcv_sn;
                         }
 
-                    if idle_timeout3_sine + Duration::from_secs(20) <= Utc::now() {
+                    // 已有未确认的测试帧时不再发送, 由上面的丢失计数重发
+                    if test4alive_send_since == DateTime::<Utc>::MAX_UTC && idle.testfr_due(&self.op.testfr) {
                         log::debug!("[CHECK TIMER] test for active");
                         tx.send(Request::U(UApci{ function: U_TESTFR_ACTIVE}))?;
-                        idle_timeout3_sine = Utc::now();
-                        test4alive_send_since = idle_timeout3_sine;
+                        idle.restart();
+                        test4alive_send_since = Utc::now();
+                    }
+                }
+
+                _ = pacer.ready(), if can_send => {
+                    let Some(asdu) = queue.pop() else {
+                        continue
+                    };
+                    let apdu = new_iframe(asdu, send_sn.value(), rcv_sn.value());
+                    if let ApciKind::I(iapci) = ApciKind::from(apdu.apci) {
+                        trace::frame(Direction::Tx, &apdu);
+                        stats.record_tx(&apdu);
+                        self.op.observers.on_tx(&apdu);
+                        framed.send(apdu).await?;
+                        pending.push_back(SeqPending {
+                            seq: SeqNum::new(iapci.send_sn),
+                            send_time: Utc::now()
+                        });
+                        pacer.sent();
+                        idle.on_tx_data();
+                        ack_rcvsn = rcv_sn;
+                        send_sn = send_sn.next();
                     }
                 }
 
-                send_data = rx.recv() => {
+                send_data = recv_request(&mut rx, &mut cmd_rx) => {
                     if let Some(data) = send_data {
                         match data {
                             Request::I(asdu) => {
-                                if !is_active {
+                                if !session.is_active() && !queue.is_buffered_offline(&asdu) {
                                     log::warn!("[TX] Server is not active, drop I-frame {asdu:?}");
                                     continue
                                 }
-                                let apdu = new_iframe(asdu, send_sn, rcv_sn);
-                                if let ApciKind::I(iapci) = ApciKind::from(apdu.apci) {
-                                    log::debug!("[TX] I-frame: {apdu}");
-                                    log::trace!("[TX] I-frame: {:?} {:?}", iapci, apdu.asdu);
-                                    framed.send(apdu).await?;
-                                    pending.push_back(SeqPending {
-                                        seq: iapci.send_sn,
-                                        send_time: Utc::now()
-                                    });
-                                    ack_rcvsn = rcv_sn;
-                                    send_sn  = (send_sn + 1) % 32767;
+                                if let Err(e) = queue.push(asdu) {
+                                    log::warn!("[TX] {e}, drop I-frame");
                                 }
                             },
                             Request::U(uapci) => {
-                                // match uapci.function {
-                                //     U_STARTDT_ACTIVE => start_dt_active_send_since = Utc::now(),
-                                //     U_STOPDT_ACTIVE => stop_dt_active_send_since = Utc::now(),
-                                //     _ => ()
-                                //
-                                // }
+                                if uapci.function == U_STARTDT_ACTIVE {
+                                    start_dt_active_send_since = Utc::now();
+                                }
                                 let apdu = new_uframe(uapci.function);
-                                log::debug!("[TX] U-frame: {apdu}");
-                                log::trace!("[TX] U-frame: {:?}", uapci);
+                                trace::frame(Direction::Tx, &apdu);
+                                stats.record_tx(&apdu);
+                                self.op.observers.on_tx(&apdu);
                                 framed.send(apdu).await?;
                             }
                             Request::S(sapci) => {
                                 let apdu = new_sframe(sapci.rcv_sn);
-                                log::debug!("[TX] S-frame: {apdu}");
-                                log::trace!("[TX] S-frame: {:?}", sapci);
+                                trace::frame(Direction::Tx, &apdu);
+                                stats.record_tx(&apdu);
+                                self.op.observers.on_tx(&apdu);
                                 framed.send(apdu).await?;
                             }
                         }
@@ -213,106 +960,350 @@ impl ServerSession {
                 apdu = framed.next() => match apdu {
                     Some(apdu) => {
                         let apdu = apdu?;
-                        idle_timeout3_sine = Utc::now(); // 每收到一个 I 帧,S 帧,U 帧, 重置空闲定时器 t3
+                        stats.discarded(framed.codec_mut().take_discarded());
+                        stats.record_rx(&apdu);
+                        self.op.observers.on_rx(&apdu);
+                        idle.on_rx(); // 每收到一个 I 帧,S 帧,U 帧, 重置空闲定时器 t3
 
                         let kind = apdu.apci.into();
                         match kind {
                             ApciKind::I(iapci) => {
-                                log::debug!("[RX] I-frame: {apdu}");
-                                log::trace!("[RX] I-frame: {iapci:#?} {:#?}", apdu.asdu);
+                                trace::frame(Direction::Rx, &apdu);
 
-                                if !update_ack_no_out(iapci.rcv_sn, &mut ack_sendsn, &mut send_sn, &mut pending) ||
-                                    iapci.send_sn != rcv_sn {
+                                if !update_ack_no_out(SeqNum::new(iapci.rcv_sn), &mut ack_sendsn, &mut send_sn, &mut pending) ||
+                                    SeqNum::new(iapci.send_sn) != rcv_sn {
                                     log::error!("fatal incoming acknowledge either earlier than previous or later than sendTime {:?} send_sn:{}",iapci, send_sn);
+                                    stats.seq_error();
+                                    result = Err(Error::ErrSequence);
                                     break 'outer
                                 }
 
                                 if ack_rcvsn == rcv_sn {
                                     un_ack_rcv_since = Utc::now();
                                 }
+                                // 先更新接收序号, 处理过程中直接回复并 continue 的 ASDU 同样计入
+                                rcv_sn = SeqNum::new(iapci.send_sn).next();
 
+                                if !session.is_active() {
+                                    match self.op.inactive_iframe {
+                                        InactiveIFramePolicy::Process => {}
+                                        InactiveIFramePolicy::Ignore => {
+                                            log::warn!("[RX] I-frame from {} before STARTDT, ignored", self.peer);
+                                            continue;
+                                        }
+                                        InactiveIFramePolicy::Close => {
+                                            log::error!("[RX] I-frame from {} before STARTDT, close connection", self.peer);
+                                            result = Err(Error::ErrIFrameBeforeStartDt);
+                                            break 'outer
+                                        }
+                                    }
+                                }
 
-                                if let Some(asdu) = apdu.asdu {
-                                    let mut asdu = asdu;
-                                    let ca = asdu.identifier.common_addr;
-                                    let cause = asdu.identifier.cot.cause().get();
-                                    let type_id = asdu.identifier.type_id;
-                                    match type_id {
-                                        TypeID::C_IC_NA_1 => {
-                                            if !(cause == Cause::Activation || cause == Cause::Deactivation) {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownCOT)))?;
-                                                continue;
+                                // 作为控制站时, 收到的监视方向 ASDU 与命令确认全部交给 ServerHandler::call
+                                if let Some(asdu) = apdu.asdu.as_ref().filter(|_| self.op.role.is_controlling()) {
+                                    for asdu in handler.call(asdu.clone()).await? {
+                                        tx.send(Request::I(asdu))?;
+                                    }
+                                } else if let Some(asdu) = apdu.asdu {
+                                    // 广播公共地址的召唤, 时钟同步与复位进程命令依次以本站的各公共地址处理,
+                                    // 未知的公共地址直接回复否定确认
+                                    let Some(targets) = dispatch_targets(&asdu, &self.op.common_addrs) else {
+                                        tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                        continue;
+                                    };
+                                    for asdu in targets {
+                                        let mut asdu = asdu;
+                                        let ca = asdu.identifier.common_addr;
+                                        let cause = asdu.identifier.cot.cause().get();
+                                        let type_id = asdu.identifier.type_id;
+                                        let orig_addr = asdu.identifier.orig_addr;
+                                        match type_id {
+                                            TypeID::C_IC_NA_1 => {
+                                                if !(cause == Cause::Activation || cause == Cause::Deactivation) {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, qoi) = match asdu.get_interrogation_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                for asdu in handler.call_interrogation(asdu, qoi).await? {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                            if ca == INVALID_COMMON_ADDR {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownCA)))?;
-                                                continue;
+                                            TypeID::C_CI_NA_1 => {
+                                                if cause != Cause::Activation {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, qcc) = match asdu.get_counter_interrogation_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                for asdu in handler.call_counter_interrogation(asdu, qcc).await? {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                            let (mut ioa, qoi) = asdu.get_interrogation_cmd()?;
-                                            let ioa = ioa.addr().get();
-                                            if ioa != INFO_OBJ_ADDR_IRRELEVANT {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownIOA)))?;
-                                                continue;
+                                            TypeID::C_CS_NA_1 => {
+                                                if cause != Cause::Activation {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, time) = match asdu.get_clock_synchronization_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                match &self.op.clock_sync {
+                                                    ClockSyncMode::Handler => {
+                                                        for asdu in handler.call_clock_sync(asdu, time).await? {
+                                                            tx.send(response(asdu, orig_addr))?;
+                                                        }
+                                                    }
+                                                    ClockSyncMode::LocalClock => {
+                                                        let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+                                                        let con = clock_synchronization_cmd(cot, ca, Utc::now())?;
+                                                        tx.send(response(con.mirror(Cause::ActivationCon), orig_addr))?;
+                                                    }
+                                                    ClockSyncMode::Apply(apply) => {
+                                                        let received = asdu.get_clock_synchronization_time().ok().and_then(|(_, t)| t);
+                                                        let con = match received {
+                                                            Some(time) if apply(time) => asdu.mirror(Cause::ActivationCon),
+                                                            _ => asdu.mirror_negative(Cause::ActivationCon),
+                                                        };
+                                                        tx.send(Request::I(con))?;
+                                                    }
+                                                }
                                             }
-                                            for asdu in handler.call_interrogation(asdu, qoi).await? {
-                                                tx.send(Request::I(asdu))?;
+                                            TypeID::C_RD_NA_1 => {
+                                                if cause != Cause::Request {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let ioa = match asdu.get_read_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let asdus = handler.call_read(asdu.clone(), ioa).await?;
+                                                if asdus.is_empty() {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                for asdu in asdus {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                        }
-                                        TypeID::C_CI_NA_1 => {
-                                            if cause != Cause::Activation {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownCOT)))?;
-                                                continue;
+
+                                            TypeID::C_RP_NA_1 => {
+                                                if cause != Cause::Activation {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, qrp) = match asdu.get_reset_process_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                let asdus = handler.call_reset_process(asdu.clone(), qrp).await?;
+                                                if asdus.is_empty() {
+                                                    tx.send(Request::I(asdu.mirror(Cause::ActivationCon)))?;
+                                                }
+                                                for asdu in asdus {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                            if ca == INVALID_COMMON_ADDR {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownCA)))?;
-                                                continue;
+                                            TypeID::C_CD_NA_1 => {
+                                                if !(cause == Cause::Spontaneous || cause == Cause::Activation) {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, msec) = match asdu.get_delay_acquire_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                let asdus = handler.call_delay_acquire(asdu.clone(), msec).await?;
+                                                // 突发传送的延时值无需确认
+                                                if asdus.is_empty() && cause == Cause::Activation {
+                                                    tx.send(Request::I(asdu.mirror(Cause::ActivationCon)))?;
+                                                }
+                                                for asdu in asdus {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                            let (mut ioa, qcc) = asdu.get_counter_interrogation_cmd()?;
-                                            let ioa = ioa.addr().get();
-                                            if ioa != INFO_OBJ_ADDR_IRRELEVANT {
-                                                tx.send(Request::I(asdu.mirror(Cause::UnknownIOA)))?;
-                                                continue;
+                                            // 测试命令由会话直接回复: 测试字正确时原样镜像(含时标), 否则回复否定的激活确认
+                                            TypeID::C_TS_NA_1 | TypeID::C_TS_TA_1 => {
+                                                if cause != Cause::Activation {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCOT)))?;
+                                                    continue;
+                                                }
+                                                if ca == INVALID_COMMON_ADDR {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownCA)))?;
+                                                    continue;
+                                                }
+                                                let (mut ioa, word) = match asdu.get_test_cmd() {
+                                                    Ok(cmd) => cmd,
+                                                    Err(e) => {
+                                                        tx.send(malformed(&asdu, &e, &self.op.params, self.peer))?;
+                                                        continue;
+                                                    }
+                                                };
+                                                let ioa = ioa.addr().get();
+                                                if ioa != INFO_OBJ_ADDR_IRRELEVANT {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownIOA)))?;
+                                                    continue;
+                                                }
+                                                let con = if word == FBPTEST_WORD {
+                                                    asdu.mirror(Cause::ActivationCon)
+                                                } else {
+                                                    log::info!("[RX] {type_id:?} {ca} from {} rejected: test word {word:#06x}", self.peer);
+                                                    asdu.mirror_negative(Cause::ActivationCon)
+                                                };
+                                                tx.send(Request::I(con))?;
                                             }
-                                            for asdu in handler.call_counter_interrogation(asdu, qcc).await? {
-                                                tx.send(Request::I(asdu))?;
-                                                continue;
+                                            // 未定义的类型标识仍交给 ServerHandler::call, 无响应时回复未知的类型标识
+                                            TypeID::Unknown(_) => {
+                                                let asdus = handler.call(asdu.clone()).await?;
+                                                if asdus.is_empty() {
+                                                    tx.send(Request::I(asdu.mirror_negative(Cause::UnknownTypeID)))?;
+                                                }
+                                                for asdu in asdus {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
-                                        }
-                                        // TypeID::C_RD_NA_1 => {
-                                        //     if cause != Cause::Request {
-                                        //         tx.send(Request::I(asdu.mirror(Cause::UnknownCOT)))?;
-                                        //     }
-                                        //     if ca == INVALID_COMMON_ADDR {
-                                        //         tx.send(Request::I(asdu.mirror(Cause::UnknownCA)))?;
-                                        //     }
-                                        //     for asdu in handler.call_counter_interrogation(asdu, asdu.get_read_cmd()?).await? {
-                                        //         tx.send(Request::I(asdu))?;
-                                        //     }
-                                        // }
-
-                                        _ => {
-                                            for asdu in handler.call(asdu).await? {
-                                                tx.send(Request::I(asdu))?;
+                                            _ => {
+                                                if is_invalid_command(&asdu) {
+                                                    let request = CommandRequest::of(&asdu, &self.op.params, self.peer);
+                                                    log::info!("[RX] {type_id:?} {ca}/{} from {} rejected: value not permitted", request.ioa, self.peer);
+                                                    tx.send(Request::I(rejection(&asdu, &request, Authorization::Deny)))?;
+                                                    continue;
+                                                }
+                                                if let Some(policy) = self.op.command_age.filter(|_| is_time_tagged_command(type_id)) {
+                                                    if !command_time(&asdu).is_some_and(|tag| policy.accepts(&tag, Utc::now())) {
+                                                        let request = CommandRequest::of(&asdu, &self.op.params, self.peer);
+                                                        log::info!("[RX] {type_id:?} {ca}/{} from {} rejected: stale time tag", request.ioa, self.peer);
+                                                        tx.send(Request::I(rejection(&asdu, &request, Authorization::Deny)))?;
+                                                        continue;
+                                                    }
+                                                }
+                                                if let Some(authorizer) = self.op.authorizer.as_ref().filter(|_| is_control_command(type_id)) {
+                                                    let request = CommandRequest::of(&asdu, &self.op.params, self.peer);
+                                                    let auth = authorizer.authorize(&request);
+                                                    if auth != Authorization::Allow {
+                                                        log::info!("[RX] {type_id:?} {ca}/{} from {} rejected: {auth:?}", request.ioa, self.peer);
+                                                        tx.send(Request::I(rejection(&asdu, &request, auth)))?;
+                                                        continue;
+                                                    }
+                                                }
+                                                for asdu in handler.call(asdu).await? {
+                                                    tx.send(response(asdu, orig_addr))?;
+                                                }
                                             }
                                         }
                                     }
                                 }
 
-                                rcv_sn = (iapci.send_sn + 1) % 32767;
                             }
                             ApciKind::U(uapci) => {
-                                log::debug!("[RX] U-frame: {apdu}");
-                                log::trace!("[RX] U-frame: {uapci:#?}");
+                                trace::frame(Direction::Rx, &apdu);
                                 match uapci.function {
-                                    U_STARTDT_ACTIVE => {
+                                    U_STARTDT_CONFIRM if self.op.role.is_controlling() => {
+                                        start_dt_active_send_since = DateTime::<Utc>::MAX_UTC;
+                                        if let Some(peer) = session.activate() {
+                                            log::info!("[RX] STARTDT confirm from {}, deactivate {peer}", self.peer);
+                                        }
+                                        session.flush_offline();
+                                    }
+                                    U_STOPDT_CONFIRM if self.op.role.is_controlling() => {
+                                        session.deactivate();
+                                    }
+                                    U_STARTDT_ACTIVE if !self.op.role.is_controlling() => {
+                                        if let Some(peer) = session.activate() {
+                                            log::info!("[RX] STARTDT from {}, deactivate {peer}", self.peer);
+                                        }
                                         tx.send(Request::U(UApci { function: U_STARTDT_CONFIRM }))?;
-                                        is_active = true;
+                                        if let Some(eoi) = self.op.end_of_init.as_ref().filter(|eoi| eoi.pending.swap(false, Ordering::AcqRel)) {
+                                            if self.op.common_addrs.is_empty() {
+                                                log::warn!("[TX] no common address configured, end of initialization not sent");
+                                            }
+                                            let cot = CauseOfTransmission::new(false, false, Cause::Initialized);
+                                            for &ca in self.op.common_addrs.iter() {
+                                                tx.send(Request::I(end_of_initialization(cot, ca, eoi.coi)?))?;
+                                            }
+                                        }
+                                        session.flush_offline();
                                     }
-                                    U_STOPDT_ACTIVE => {
+                                    U_STOPDT_ACTIVE if !self.op.role.is_controlling() => {
                                         tx.send(Request::U(UApci { function: U_STOPDT_CONFIRM }))?;
-                                        is_active = false;
+                                        session.deactivate();
                                     }
                                     U_TESTFR_CONFIRM => {
                                         test4alive_send_since = DateTime::<Utc>::MAX_UTC;
+                                        testfr_missed = 0;
                                     }
                                     U_TESTFR_ACTIVE => {
                                         tx.send(Request::U(UApci { function: U_TESTFR_CONFIRM }))?;
@@ -324,13 +1315,14 @@ impl ServerSession {
                                 }
                             }
                             ApciKind::S(sapci) => {
-                                log::debug!("[RX] S-frame: {apdu}");
-                                log::trace!("[RX] S-frame: {sapci:#?}");
-                                if !update_ack_no_out(sapci.rcv_sn, &mut ack_sendsn, &mut send_sn, &mut pending) {
+                                trace::frame(Direction::Rx, &apdu);
+                                if !update_ack_no_out(SeqNum::new(sapci.rcv_sn), &mut ack_sendsn, &mut send_sn, &mut pending) {
                                     log::error!("fatal incoming acknowledge either earlier than previous or later than sendTime {:?} rcv_sn:{}", sapci,rcv_sn);
+                                    stats.seq_error();
+                                    result = Err(Error::ErrSequence);
                                     break 'outer
                                 }
-                                ack_sendsn = sapci.rcv_sn;
+                                ack_sendsn = SeqNum::new(sapci.rcv_sn);
                             }
                         }
 
@@ -345,9 +1337,18 @@ impl ServerSession {
             }
         }
 
+        if stopping {
+            let _ = framed.close().await;
+        }
         self.sender = None;
+        stats.closed();
+        stats.depth(0, 0);
+        drop(session);
+        for asdu in queue.take_offline() {
+            let _ = self.registry.requeue(asdu);
+        }
 
-        Ok(())
+        result
     }
 
     pub async fn stop(&mut self) {
@@ -365,3 +1366,55 @@ impl ServerSession {
         false
     }
 }
+
+// 待处理的 ASDU: 广播公共地址的站命令按本站各公共地址展开.
+// 不允许广播的命令, 或设置了本站公共地址而不在其中时返回 None
+fn dispatch_targets(asdu: &Asdu, common_addrs: &[CommonAddr]) -> Option<Vec<Asdu>> {
+    let ca = asdu.identifier.common_addr;
+    if ca != GLOBAL_COMMON_ADDR {
+        let known = common_addrs.is_empty() || common_addrs.contains(&ca);
+        return known.then(|| vec![asdu.clone()]);
+    }
+    if !matches!(
+        asdu.identifier.type_id,
+        TypeID::C_IC_NA_1 | TypeID::C_CI_NA_1 | TypeID::C_CS_NA_1 | TypeID::C_RP_NA_1
+    ) {
+        return None;
+    }
+    if common_addrs.is_empty() {
+        return Some(vec![asdu.clone()]);
+    }
+    Some(
+        common_addrs
+            .iter()
+            .map(|&ca| {
+                let mut asdu = asdu.clone();
+                asdu.identifier.common_addr = ca;
+                asdu
+            })
+            .collect(),
+    )
+}
+
+// 格式错误的命令回复否定确认而不关闭会话: 按会话的信息对象地址长度判断,
+// 地址不完整时为未知的信息对象地址, 信息元素长度错误时为未知的传送原因
+fn malformed(asdu: &Asdu, e: &anyhow::Error, params: &AsduParams, peer: SocketAddr) -> Request {
+    let cause = if asdu.raw.len() < params.ioa_size {
+        Cause::UnknownIOA
+    } else {
+        Cause::UnknownCOT
+    };
+    log::warn!(
+        "[RX] malformed {:?} from {peer}: {e}",
+        asdu.identifier.type_id
+    );
+    Request::I(asdu.mirror_negative(cause))
+}
+
+// 处理函数返回的响应未指定源发站地址时, 使用请求的源发站地址, 以便前置机区分多个主站
+fn response(mut asdu: Asdu, orig_addr: OriginAddr) -> Request {
+    if asdu.identifier.orig_addr == 0 {
+        asdu.identifier.orig_addr = orig_addr;
+    }
+    Request::I(asdu)
+}
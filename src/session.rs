@@ -0,0 +1,483 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    asdu::{Asdu, AsduParams},
+    stats::SharedStats,
+    Error, Request, Role, SendQueue, SendQueueOption, Stats,
+};
+
+tokio::task_local! {
+    static CURRENT: SessionContext;
+}
+
+// 服务端的全部会话.
+// 被控站通常允许多个 TCP 连接, 但同一时刻只有一个连接处于数据传输激活状态(STARTDT),
+// 新的连接收到 STARTDT 后, 原激活的连接转为非激活. all_active 为 true 时所有连接均可激活.
+pub(crate) struct SessionRegistry {
+    all_active: bool,
+    queue: SendQueueOption,
+    // 每个会话的命令通道深度
+    channel_depth: usize,
+    inner: Mutex<Sessions>,
+}
+
+struct Sessions {
+    next_id: u64,
+    sessions: HashMap<u64, SessionEntry>,
+    // 没有激活的连接时缓存的突发数据, 有连接激活后补发
+    offline: SendQueue,
+}
+
+struct SessionEntry {
+    peer: SocketAddr,
+    sender: mpsc::Sender<Request>,
+    active: bool,
+    stats: SharedStats,
+}
+
+impl SessionRegistry {
+    pub(crate) fn new(all_active: bool, queue: SendQueueOption, channel_depth: usize) -> Self {
+        SessionRegistry {
+            all_active,
+            queue,
+            channel_depth,
+            inner: Mutex::new(Sessions {
+                next_id: 0,
+                sessions: HashMap::new(),
+                offline: SendQueue::new(queue),
+            }),
+        }
+    }
+
+    pub(crate) fn all_active(&self) -> bool {
+        self.all_active
+    }
+
+    pub(crate) fn queue_option(&self) -> SendQueueOption {
+        self.queue
+    }
+
+    pub(crate) fn channel_depth(&self) -> usize {
+        self.channel_depth
+    }
+
+    pub(crate) fn register(
+        self: &Arc<Self>,
+        peer: SocketAddr,
+        sender: mpsc::Sender<Request>,
+    ) -> SessionGuard {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.sessions.insert(
+            id,
+            SessionEntry {
+                peer,
+                sender,
+                active: false,
+                stats: SharedStats::new("server"),
+            },
+        );
+        SessionGuard {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    // 会话收到 STARTDT, 返回被转为非激活的会话地址
+    pub(crate) fn activate(&self, id: u64) -> Option<SocketAddr> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut replaced = None;
+        if !self.all_active {
+            for (other, entry) in inner.sessions.iter_mut() {
+                if *other != id && entry.active {
+                    entry.active = false;
+                    replaced = Some(entry.peer);
+                }
+            }
+        }
+        if let Some(entry) = inner.sessions.get_mut(&id) {
+            entry.active = true;
+        }
+        replaced
+    }
+
+    // 向刚激活的会话补发缓存的突发数据, 应在 STARTDT 确认之后调用.
+    // 命令通道满时其余数据留在缓存中
+    pub(crate) fn flush_offline(&self, id: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let Sessions {
+            sessions, offline, ..
+        } = &mut *inner;
+        if let Some(entry) = sessions.get(&id).filter(|entry| entry.active) {
+            while entry.sender.capacity() > 0 {
+                let Some(asdu) = offline.pop() else {
+                    break;
+                };
+                // 会话的接收循环可能同时占满通道, 未发出的 ASDU 放回缓存队首
+                if let Err(e) = entry.sender.try_send(Request::I(asdu)) {
+                    if let Request::I(asdu) = e.into_inner() {
+                        offline.push_front(asdu);
+                    }
+                    break;
+                }
+            }
+            if !offline.is_empty() {
+                log::warn!("[TX] channel full, {} buffered ASDU left", offline.len());
+            }
+        }
+    }
+
+    pub(crate) fn deactivate(&self, id: u64) {
+        if let Some(entry) = self.inner.lock().unwrap().sessions.get_mut(&id) {
+            entry.active = false;
+        }
+    }
+
+    pub(crate) fn is_active(&self, id: u64) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .sessions
+            .get(&id)
+            .is_some_and(|entry| entry.active)
+    }
+
+    // 向处于激活状态的会话发送 ASDU, 返回发送的会话个数.
+    // 激活的会话的命令通道全部已满时返回 ErrBufferFull
+    pub(crate) fn broadcast(&self, asdu: &Asdu) -> Result<usize, Error> {
+        let inner = self.inner.lock().unwrap();
+        let (mut n, mut full) = (0, 0);
+        for entry in inner.sessions.values().filter(|entry| entry.active) {
+            match entry.sender.try_send(Request::I(asdu.clone())) {
+                Ok(()) => n += 1,
+                Err(TrySendError::Full(_)) => {
+                    log::warn!("[TX] channel to {} full", entry.peer);
+                    full += 1;
+                }
+                Err(TrySendError::Closed(_)) => (),
+            }
+        }
+        if n == 0 && full > 0 {
+            return Err(Error::ErrBufferFull);
+        }
+        Ok(n)
+    }
+
+    // 向处于激活状态的会话发送 ASDU, 没有激活的会话时按发送队列配置缓存突发数据,
+    // 不缓存时返回 ErrNotActive
+    pub(crate) fn requeue(&self, asdu: Asdu) -> Result<(), Error> {
+        if self.broadcast(&asdu)? > 0 {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.offline.is_buffered_offline(&asdu) {
+            return Err(Error::ErrNotActive);
+        }
+        inner.offline.push(asdu)
+    }
+
+    // 向指定会话发送 ASDU, 会话未激活时返回 ErrNotActive, 命令通道满时返回 ErrBufferFull
+    pub(crate) fn send_to(&self, id: u64, asdu: Asdu) -> Result<(), Error> {
+        let inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.sessions.get(&id) else {
+            return Err(Error::ErrUseClosedConnection);
+        };
+        if !entry.active {
+            return Err(Error::ErrNotActive);
+        }
+        entry
+            .sender
+            .try_send(Request::I(asdu))
+            .map_err(|e| match e {
+                TrySendError::Full(_) => Error::ErrBufferFull,
+                TrySendError::Closed(_) => Error::ErrUseClosedConnection,
+            })
+    }
+
+    // 同 broadcast, 但整批一次进入每个会话的命令通道, 不与其他 ASDU 交错.
+    // 剩余容量不足整批的会话不发送, 全部激活的会话均不足时返回 ErrBufferFull
+    pub(crate) fn broadcast_batch(&self, asdus: &[Asdu]) -> Result<usize, Error> {
+        let inner = self.inner.lock().unwrap();
+        let (mut n, mut full) = (0, 0);
+        for entry in inner.sessions.values().filter(|entry| entry.active) {
+            match entry.sender.try_reserve_many(asdus.len()) {
+                Ok(permits) => {
+                    for (permit, asdu) in permits.zip(asdus) {
+                        permit.send(Request::I(asdu.clone()));
+                    }
+                    n += 1;
+                }
+                Err(TrySendError::Full(_)) => {
+                    log::warn!("[TX] channel to {} full", entry.peer);
+                    full += 1;
+                }
+                Err(TrySendError::Closed(_)) => (),
+            }
+        }
+        if n == 0 && full > 0 {
+            return Err(Error::ErrBufferFull);
+        }
+        Ok(n)
+    }
+
+    // 同 requeue, 整批发送或整批缓存
+    pub(crate) fn requeue_batch(&self, asdus: Vec<Asdu>) -> Result<(), Error> {
+        if asdus.is_empty() || self.broadcast_batch(&asdus)? > 0 {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if !asdus
+            .iter()
+            .all(|asdu| inner.offline.is_buffered_offline(asdu))
+        {
+            return Err(Error::ErrNotActive);
+        }
+        asdus
+            .into_iter()
+            .try_for_each(|asdu| inner.offline.push(asdu))
+    }
+
+    // 全部会话的对端地址及是否激活
+    pub(crate) fn sessions(&self) -> Vec<(SocketAddr, bool)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .sessions
+            .values()
+            .map(|entry| (entry.peer, entry.active))
+            .collect()
+    }
+
+    pub(crate) fn session_stats(&self, id: u64) -> SharedStats {
+        self.inner
+            .lock()
+            .unwrap()
+            .sessions
+            .get(&id)
+            .map(|entry| entry.stats.clone())
+            .unwrap_or_else(|| SharedStats::new("server"))
+    }
+
+    // 全部会话的对端地址及统计
+    pub(crate) fn stats(&self) -> Vec<(SocketAddr, Stats)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .sessions
+            .values()
+            .map(|entry| (entry.peer, entry.stats.snapshot()))
+            .collect()
+    }
+}
+
+// 会话结束(包括出错返回)时自动注销
+pub(crate) struct SessionGuard {
+    registry: Arc<SessionRegistry>,
+    id: u64,
+}
+
+impl SessionGuard {
+    pub(crate) fn activate(&self) -> Option<SocketAddr> {
+        self.registry.activate(self.id)
+    }
+
+    pub(crate) fn deactivate(&self) {
+        self.registry.deactivate(self.id)
+    }
+
+    pub(crate) fn flush_offline(&self) {
+        self.registry.flush_offline(self.id)
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.registry.is_active(self.id)
+    }
+
+    pub(crate) fn stats(&self) -> SharedStats {
+        self.registry.session_stats(self.id)
+    }
+
+    pub(crate) fn context(
+        &self,
+        peer: SocketAddr,
+        params: AsduParams,
+        role: Role,
+    ) -> SessionContext {
+        SessionContext {
+            registry: self.registry.clone(),
+            id: self.id,
+            peer,
+            params,
+            role,
+        }
+    }
+}
+
+// 当前会话的信息, 在 ServerHandler 的各方法中(包括返回的 Future 内)通过 SessionContext::current 获取,
+// 用于同时连接多个控制站时按对端区分处理
+#[derive(Clone)]
+pub struct SessionContext {
+    registry: Arc<SessionRegistry>,
+    id: u64,
+    peer: SocketAddr,
+    params: AsduParams,
+    role: Role,
+}
+
+impl SessionContext {
+    // 在会话任务之外(如 ServerHandler 自行 spawn 的任务中)返回 None
+    pub fn current() -> Option<SessionContext> {
+        CURRENT.try_with(SessionContext::clone).ok()
+    }
+
+    pub(crate) async fn scope<F: Future>(self, f: F) -> F::Output {
+        CURRENT.scope(self, f).await
+    }
+
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    // 会话编号, 在同一个 Server 内唯一
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn params(&self) -> AsduParams {
+        self.params
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    // 是否处于数据传输激活状态(收到 STARTDT)
+    pub fn is_active(&self) -> bool {
+        self.registry.is_active(self.id)
+    }
+
+    // 当前会话的句柄, 可在会话任务之外向该会话主动上送 ASDU
+    pub fn handle(&self) -> ServerSessionHandle {
+        ServerSessionHandle {
+            registry: self.registry.clone(),
+            id: self.id,
+            peer: self.peer,
+            params: self.params,
+        }
+    }
+}
+
+// 服务端单个会话的句柄, 与 ServerHandle::broadcast_asdu 不同, 只向该会话发送.
+// 会话结束后发送返回 ErrUseClosedConnection
+#[derive(Clone)]
+pub struct ServerSessionHandle {
+    registry: Arc<SessionRegistry>,
+    id: u64,
+    peer: SocketAddr,
+    params: AsduParams,
+}
+
+impl ServerSessionHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    pub fn params(&self) -> AsduParams {
+        self.params
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.registry.is_active(self.id)
+    }
+
+    // 向该会话发送 ASDU, 不缓存也不等待: 未激活时返回 ErrNotActive, 命令通道满时返回 ErrBufferFull
+    pub fn send_asdu(&self, asdu: Asdu) -> Result<(), Error> {
+        self.registry.send_to(self.id, asdu)
+    }
+}
+
+impl std::fmt::Debug for ServerSessionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerSessionHandle")
+            .field("id", &self.id)
+            .field("peer", &self.peer)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for SessionContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionContext")
+            .field("id", &self.id)
+            .field("peer", &self.peer)
+            .field("params", &self.params)
+            .field("role", &self.role)
+            .field("active", &self.is_active())
+            .finish()
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.registry
+            .inner
+            .lock()
+            .unwrap()
+            .sessions
+            .remove(&self.id);
+    }
+}
+
+// 服务端句柄, 用于在回调之外主动上送 ASDU(如突发的变位, 测量值变化)
+#[derive(Clone)]
+pub struct ServerHandle {
+    registry: Arc<SessionRegistry>,
+    shutdown: CancellationToken,
+}
+
+impl ServerHandle {
+    pub(crate) fn new(registry: Arc<SessionRegistry>, shutdown: CancellationToken) -> Self {
+        ServerHandle { registry, shutdown }
+    }
+
+    // 向处于激活状态的连接发送 ASDU. 没有激活的连接时, 若开启了 buffer_offline,
+    // 突发数据缓存至有连接激活后补发, 否则返回 ErrNotActive.
+    // 对端停止接收导致命令通道满时返回 ErrBufferFull, 不等待
+    pub fn broadcast_asdu(&self, asdu: Asdu) -> Result<(), Error> {
+        self.registry.requeue(asdu)
+    }
+
+    // 批量发送, 如一次变位产生的多个 ASDU. 整批一次进入每个激活连接的命令通道,
+    // 保持批内顺序且不与其他 ASDU 交错; 命令通道剩余容量不足整批时返回 ErrBufferFull
+    pub fn broadcast_batch(&self, asdus: Vec<Asdu>) -> Result<(), Error> {
+        self.registry.requeue_batch(asdus)
+    }
+
+    // 当前全部连接的对端地址及是否处于激活状态
+    pub fn sessions(&self) -> Vec<(SocketAddr, bool)> {
+        self.registry.sessions()
+    }
+
+    // 当前全部连接的对端地址及会话统计
+    pub fn stats(&self) -> Vec<(SocketAddr, Stats)> {
+        self.registry.stats()
+    }
+
+    // 停止服务, 同 Server::shutdown
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+}
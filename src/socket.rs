@@ -0,0 +1,163 @@
+use std::{io, net::SocketAddr, time::Duration};
+
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::{TcpSocket, TcpStream};
+
+// TCP 保活参数, 未设置的项使用系统默认值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Keepalive {
+    // 连接空闲多久后开始发送保活探测
+    pub(crate) time: Option<Duration>,
+    // 保活探测的间隔
+    pub(crate) interval: Option<Duration>,
+    // 连续多少次探测无响应后断开连接
+    pub(crate) retries: Option<u32>,
+}
+
+impl Keepalive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    // 仅 Linux, Android, macOS, iOS, FreeBSD 与 Windows 生效
+    #[must_use]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    // 仅 Linux, Android, macOS, iOS 与 FreeBSD 生效
+    #[must_use]
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    // 对套接字启用 SO_KEEPALIVE 并设置保活参数
+    pub(crate) fn apply(&self, socket: SockRef<'_>) -> io::Result<()> {
+        let mut keepalive = TcpKeepalive::new();
+        if let Some(time) = self.time {
+            keepalive = keepalive.with_time(time);
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "windows"
+        ))]
+        if let Some(interval) = self.interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd"
+        ))]
+        if let Some(retries) = self.retries {
+            keepalive = keepalive.with_retries(retries);
+        }
+        socket.set_tcp_keepalive(&keepalive)
+    }
+}
+
+// 将套接字绑定到网络接口(SO_BINDTODEVICE), 用于多网卡网关, 仅 Linux 与 Android 支持
+pub(crate) fn bind_device(socket: SockRef<'_>, device: &str) -> io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return socket.bind_device(Some(device.as_bytes()));
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("bind to interface {device} is not supported on this platform"),
+    ))
+}
+
+// 客户端 TCP 套接字配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketOption {
+    // TCP_NODELAY, 默认开启, 避免 Nagle 算法延迟控制命令
+    pub(crate) nodelay: bool,
+    // TCP 保活, 为 None 时不开启
+    pub(crate) keepalive: Option<Keepalive>,
+    // 连接使用的本地地址, 端口为 0 时由系统分配
+    pub(crate) local_addr: Option<SocketAddr>,
+    // 连接使用的网络接口名
+    pub(crate) bind_device: Option<String>,
+}
+
+impl Default for SocketOption {
+    fn default() -> Self {
+        SocketOption {
+            nodelay: true,
+            keepalive: None,
+            local_addr: None,
+            bind_device: None,
+        }
+    }
+}
+
+impl SocketOption {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    #[must_use]
+    pub fn with_keepalive(mut self, keepalive: Keepalive) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    #[must_use]
+    pub fn with_local_addr(mut self, addr: SocketAddr) -> Self {
+        self.local_addr = Some(addr);
+        self
+    }
+
+    // 仅 Linux 与 Android 支持, 其他平台连接时返回 Unsupported 错误
+    #[must_use]
+    pub fn with_bind_device(mut self, device: impl Into<String>) -> Self {
+        self.bind_device = Some(device.into());
+        self
+    }
+
+    // 设置了本地地址时只能连接同一协议族的对端
+    pub(crate) fn accepts(&self, addr: SocketAddr) -> bool {
+        !matches!(self.local_addr, Some(local) if local.is_ipv4() != addr.is_ipv4())
+    }
+
+    // 按配置建立到 addr 的 TCP 连接
+    pub(crate) async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        if let Some(keepalive) = &self.keepalive {
+            keepalive.apply(SockRef::from(&socket))?;
+        }
+        if let Some(device) = &self.bind_device {
+            bind_device(SockRef::from(&socket), device)?;
+        }
+        if let Some(local) = self.local_addr {
+            socket.bind(local)?;
+        }
+        let stream = socket.connect(addr).await?;
+        stream.set_nodelay(self.nodelay)?;
+        Ok(stream)
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{apci::ApciKind, asdu::TypeID, metrics, Apdu};
+
+// 按帧类型统计的帧数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameCount {
+    /// I 帧
+    pub i: u64,
+    /// S 帧
+    pub s: u64,
+    /// U 帧
+    pub u: u64,
+}
+
+impl FrameCount {
+    pub fn total(&self) -> u64 {
+        self.i + self.s + self.u
+    }
+}
+
+// 会话统计, 用于监视链路状态
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// 发送的帧数
+    pub frames_tx: FrameCount,
+    /// 接收的帧数
+    pub frames_rx: FrameCount,
+    /// 发送的字节数
+    pub bytes_tx: u64,
+    /// 接收的字节数
+    pub bytes_rx: u64,
+    /// 按类型标识统计的发送 ASDU 个数
+    pub asdus_tx: HashMap<TypeID, u64>,
+    /// 按类型标识统计的接收 ASDU 个数
+    pub asdus_rx: HashMap<TypeID, u64>,
+    /// 等待确认超时(t1)的次数, 包括 I 帧与 U 帧
+    pub timeouts: u64,
+    /// 序号错误的次数
+    pub seq_errors: u64,
+    /// 重新同步时丢弃的字节数, 见 Resync::Lenient
+    pub discarded_bytes: u64,
+    /// 最后一次发送的时间
+    pub last_tx: Option<DateTime<Utc>>,
+    /// 最后一次接收的时间
+    pub last_rx: Option<DateTime<Utc>>,
+}
+
+impl Stats {
+    pub(crate) fn record_tx(&mut self, apdu: &Apdu) {
+        record(
+            apdu,
+            &mut self.frames_tx,
+            &mut self.bytes_tx,
+            &mut self.asdus_tx,
+        );
+        self.last_tx = Some(Utc::now());
+    }
+
+    pub(crate) fn record_rx(&mut self, apdu: &Apdu) {
+        record(
+            apdu,
+            &mut self.frames_rx,
+            &mut self.bytes_rx,
+            &mut self.asdus_rx,
+        );
+        self.last_rx = Some(Utc::now());
+    }
+}
+
+fn record(apdu: &Apdu, frames: &mut FrameCount, bytes: &mut u64, asdus: &mut HashMap<TypeID, u64>) {
+    match ApciKind::from(apdu.apci) {
+        ApciKind::I(_) => frames.i += 1,
+        ApciKind::S(_) => frames.s += 1,
+        ApciKind::U(_) => frames.u += 1,
+    }
+    // 启动字符与长度域不计入 APDU 长度
+    *bytes += 2 + apdu.apci.apdu_length as u64;
+    if let Some(asdu) = &apdu.asdu {
+        *asdus.entry(asdu.identifier.type_id).or_default() += 1;
+    }
+}
+
+// 会话任务与查询方共享的统计, 更新时同时输出到 metrics(开启 metrics 特性时)
+#[derive(Debug, Clone)]
+pub(crate) struct SharedStats {
+    inner: Arc<Mutex<Stats>>,
+    // client 或 server
+    role: &'static str,
+}
+
+impl SharedStats {
+    pub(crate) fn new(role: &'static str) -> Self {
+        SharedStats {
+            inner: Arc::default(),
+            role,
+        }
+    }
+
+    pub(crate) fn record_tx(&self, apdu: &Apdu) {
+        self.inner.lock().unwrap().record_tx(apdu);
+        metrics::frame(self.role, "tx", apdu);
+    }
+
+    pub(crate) fn record_rx(&self, apdu: &Apdu) {
+        self.inner.lock().unwrap().record_rx(apdu);
+        metrics::frame(self.role, "rx", apdu);
+    }
+
+    pub(crate) fn timeout(&self) {
+        self.inner.lock().unwrap().timeouts += 1;
+        metrics::timeout(self.role);
+    }
+
+    pub(crate) fn seq_error(&self) {
+        self.inner.lock().unwrap().seq_errors += 1;
+        metrics::seq_error(self.role);
+    }
+
+    // 解码器重新同步时丢弃的字节
+    pub(crate) fn discarded(&self, n: u64) {
+        if n == 0 {
+            return;
+        }
+        self.inner.lock().unwrap().discarded_bytes += n;
+        metrics::discarded(self.role, n);
+    }
+
+    pub(crate) fn depth(&self, queued: usize, unacked: usize) {
+        metrics::depth(self.role, queued, unacked);
+    }
+
+    pub(crate) fn opened(&self) {
+        metrics::session_opened(self.role);
+    }
+
+    pub(crate) fn closed(&self) {
+        metrics::session_closed(self.role);
+    }
+
+    pub(crate) fn snapshot(&self) -> Stats {
+        self.inner.lock().unwrap().clone()
+    }
+}
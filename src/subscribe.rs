@@ -0,0 +1,178 @@
+use std::ops::RangeBounds;
+
+use chrono::Utc;
+use futures::{stream, Stream};
+use tokio::sync::mpsc;
+
+use crate::{
+    asdu::{Asdu, Cause, CommonAddr, InfoObjAddr, TypeID},
+    client::{Client, ClientHandler},
+    datastore::{COUNTER_GROUP_CAUSES, GROUP_CAUSES},
+    mproc::ObjectQDS,
+    Error, Point, PointValue, Quality,
+};
+
+// 订阅通道的容量, 应用处理不及时, 收到的数据在订阅任务中排队, 不影响客户端的接收
+const SUBSCRIBE_CAPACITY: usize = 64;
+
+// 收到的一个点的数据
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointUpdate {
+    pub ca: CommonAddr,
+    pub ioa: u16,
+    /// 值, 品质与时标, 信息对象不带时标时为收到的时间;
+    /// 响应组召唤(计数量组召唤)时 group 为组号, 否则为 0
+    pub point: Point,
+    /// 传送原因, 如突发, 响应站召唤
+    pub cause: Cause,
+}
+
+impl<S> Client<S>
+where
+    S: ClientHandler + Clone + Send + Sync + 'static,
+{
+    // 订阅公共地址 ca 下信息对象地址在 ioas 范围内的点, 收到单点, 双点, 测量值, 累计量时推送.
+    // 品质无效的数据按 ClientOption::with_quality_filter 处理. 接收端被丢弃后自动取消订阅
+    pub async fn subscribe<R>(&self, ca: CommonAddr, ioas: R) -> mpsc::Receiver<PointUpdate>
+    where
+        R: RangeBounds<u16> + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::channel(SUBSCRIBE_CAPACITY);
+        let filter = self.quality_filter();
+        let mut asdus = self
+            .subscribe_asdu(move |asdu| asdu.identifier.common_addr == ca)
+            .await;
+        tokio::spawn(async move {
+            while let Some(asdu) = asdus.recv().await {
+                let updates = match point_updates(&asdu) {
+                    Ok(updates) => updates,
+                    Err(e) => {
+                        log::warn!("[SUBSCRIBE] decode {asdu}: {e}");
+                        continue;
+                    }
+                };
+                let updates = updates
+                    .into_iter()
+                    .filter(|u| ioas.contains(&u.ioa) && filter.accepts(u.point.quality.into()));
+                for update in updates {
+                    if tx.send(update).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
+    // 收到的全部 ASDU 作为异步流, 与 ClientHandler 同时生效, 流被丢弃后自动取消订阅.
+    // 流中的 ASDU 不限数量地排队, 不影响客户端的接收
+    pub async fn asdu_stream(&self) -> impl Stream<Item = Asdu> + Unpin + Send + 'static {
+        let mut rx = self.subscribe_asdu(|_| true).await;
+        stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
+    // 同 subscribe, 以异步流推送点的数据
+    pub async fn point_stream<R>(
+        &self,
+        ca: CommonAddr,
+        ioas: R,
+    ) -> impl Stream<Item = PointUpdate> + Unpin + Send + 'static
+    where
+        R: RangeBounds<u16> + Send + Sync + 'static,
+    {
+        let mut rx = self.subscribe(ca, ioas).await;
+        stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+}
+
+// 解析监视方向过程信息的 ASDU, 其他类型返回空集合
+pub(crate) fn point_updates(asdu: &Asdu) -> Result<Vec<PointUpdate>, Error> {
+    let ca = asdu.identifier.common_addr;
+    let mut cot = asdu.identifier.cot;
+    let cause = cot.cause().get();
+    let group = group_of(cause).unwrap_or(0);
+    let now = Utc::now();
+    let mut a = asdu.clone();
+    let mut updates = Vec::new();
+    let mut push = |mut ioa: InfoObjAddr, value, quality, time: Option<_>| {
+        updates.push(PointUpdate {
+            ca,
+            ioa: ioa.addr().get(),
+            point: Point {
+                value,
+                quality,
+                time: Some(time.unwrap_or(now)),
+                group,
+            },
+            cause,
+        })
+    };
+    match a.identifier.type_id {
+        TypeID::M_SP_NA_1 | TypeID::M_SP_TA_1 | TypeID::M_SP_TB_1 => {
+            for mut info in a.get_single_point()? {
+                let v = info.siq.spi().get();
+                push(
+                    info.ioa,
+                    PointValue::Single(v),
+                    Quality::from(info.siq).into(),
+                    info.time,
+                );
+            }
+        }
+        TypeID::M_DP_NA_1 | TypeID::M_DP_TA_1 | TypeID::M_DP_TB_1 => {
+            for info in a.get_double_point()? {
+                let mut diq = info.diq;
+                let v = diq.spi().get().value();
+                push(
+                    info.ioa,
+                    PointValue::Double(v),
+                    Quality::from(diq).into(),
+                    info.time,
+                );
+            }
+        }
+        TypeID::M_ME_NA_1 | TypeID::M_ME_TA_1 | TypeID::M_ME_TD_1 | TypeID::M_ME_ND_1 => {
+            for info in a.get_measured_value_normal()? {
+                let quality = info.qds.unwrap_or_else(ObjectQDS::of_defaults);
+                push(
+                    info.ioa,
+                    PointValue::Normalized(info.nva),
+                    quality,
+                    info.time,
+                );
+            }
+        }
+        TypeID::M_ME_NB_1 | TypeID::M_ME_TB_1 | TypeID::M_ME_TE_1 => {
+            for info in a.get_measured_value_scaled()? {
+                push(info.ioa, PointValue::Scaled(info.sva), info.qds, info.time);
+            }
+        }
+        TypeID::M_ME_NC_1 | TypeID::M_ME_TC_1 | TypeID::M_ME_TF_1 => {
+            for info in a.get_measured_value_float()? {
+                push(info.ioa, PointValue::Float(info.r), info.qds, info.time);
+            }
+        }
+        TypeID::M_IT_NA_1 | TypeID::M_IT_TA_1 | TypeID::M_IT_TB_1 => {
+            for info in a.get_integrated_totals()? {
+                push(
+                    info.ioa,
+                    PointValue::Counter(info.bcr.value),
+                    Quality::from(&info.bcr).into(),
+                    info.time,
+                );
+            }
+        }
+        _ => (),
+    }
+    Ok(updates)
+}
+
+// 响应组召唤与计数量组召唤的传送原因对应的组号
+fn group_of(cause: Cause) -> Option<u8> {
+    GROUP_CAUSES
+        .iter()
+        .chain(COUNTER_GROUP_CAUSES.iter())
+        .zip((1..=16).chain(1..=4))
+        .find(|(c, _)| **c == cause)
+        .map(|(_, group)| group)
+}
@@ -0,0 +1,246 @@
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use futures::{future::BoxFuture, SinkExt, StreamExt};
+use tokio::{
+    io::{duplex, DuplexStream},
+    sync::mpsc,
+    time::timeout,
+};
+use tokio_util::{codec::Framed, sync::CancellationToken};
+
+use crate::{
+    apci::{
+        new_iframe, new_sframe, new_uframe, ApciKind, SeqNum, U_STARTDT_ACTIVE, U_STARTDT_CONFIRM,
+        U_STOPDT_ACTIVE, U_STOPDT_CONFIRM, U_TESTFR_ACTIVE, U_TESTFR_CONFIRM,
+    },
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, CommonAddr, InfoObjAddr, TypeID},
+    mproc::{single, ObjectSIQ, SinglePointInfo},
+    server::serve_transport,
+    session::SessionRegistry,
+    Apdu, Codec, Connector, Error, SendQueueOption, ServerHandle, ServerHandler, Transport,
+    DEFAULT_CHANNEL_DEPTH,
+};
+
+// 内存中 duplex 的缓冲区大小
+pub const DUPLEX_BUFFER: usize = 64 * 1024;
+
+// 内存中互相连接的一对字节流, 不需要打开套接字
+pub fn duplex_pair() -> (DuplexStream, DuplexStream) {
+    duplex(DUPLEX_BUFFER)
+}
+
+// 客户端的内存连接器: 每次连接创建一对 duplex, 一端交给客户端, 另一端从接收器取出,
+// 一般包装为 ScriptedPeer 模拟被控站. 接收器被丢弃后连接失败
+pub struct DuplexConnector {
+    tx: mpsc::UnboundedSender<DuplexStream>,
+}
+
+pub fn duplex_connector() -> (DuplexConnector, mpsc::UnboundedReceiver<DuplexStream>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (DuplexConnector { tx }, rx)
+}
+
+impl Connector for DuplexConnector {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>> {
+        Box::pin(async move {
+            let (local, remote) = duplex_pair();
+            self.tx
+                .send(remote)
+                .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+            Ok(Box::new(local) as Box<dyn Transport>)
+        })
+    }
+}
+
+// 在内存中运行 handler 的服务端会话, 返回模拟控制站的对端
+pub fn serve_in_memory<S>(handler: S) -> ScriptedPeer<DuplexStream>
+where
+    S: ServerHandler + Send + Sync + 'static,
+{
+    serve_in_memory_with_handle(handler).0
+}
+
+// 同 serve_in_memory, 另返回该会话的服务端句柄, 用于测试主动上送
+pub fn serve_in_memory_with_handle<S>(handler: S) -> (ScriptedPeer<DuplexStream>, ServerHandle)
+where
+    S: ServerHandler + Send + Sync + 'static,
+{
+    let (local, remote) = duplex_pair();
+    let peer: SocketAddr = ([127, 0, 0, 1], 0).into();
+    let registry = Arc::new(SessionRegistry::new(
+        false,
+        SendQueueOption::default(),
+        DEFAULT_CHANNEL_DEPTH,
+    ));
+    let shutdown = CancellationToken::new();
+    let handle = ServerHandle::new(registry.clone(), shutdown.clone());
+    tokio::spawn(async move {
+        if let Err(e) = serve_transport(local, handler, peer, registry, shutdown).await {
+            log::warn!("[TEST] in-memory session error: {e}");
+        }
+    });
+    (ScriptedPeer::new(remote), handle)
+}
+
+// 按脚本收发的对端, 用于测试 ClientHandler 与 ServerHandler.
+// 发送的 I 帧自动编号, 收到的 I 帧立即以 S 帧确认, 收到的测试帧自动回复确认.
+// expect_* 在帧不符合预期或超时时 panic, 与 assert! 一样用于测试
+pub struct ScriptedPeer<T> {
+    framed: Framed<T, Codec>,
+    send_sn: SeqNum,
+    rcv_sn: SeqNum,
+    timeout: Duration,
+}
+
+impl<T> ScriptedPeer<T>
+where
+    T: Transport,
+{
+    pub fn new(transport: T) -> Self {
+        ScriptedPeer {
+            framed: Framed::new(transport, Codec::default()),
+            send_sn: SeqNum::default(),
+            rcv_sn: SeqNum::default(),
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    // 等待对端帧的超时时间, 默认 1 秒
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // ASDU 各字段长度, 对端使用非标准长度时设置
+    pub fn with_asdu_params(mut self, params: AsduParams) -> Self {
+        *self.framed.codec_mut() = Codec::new(params);
+        self
+    }
+
+    pub async fn send_apdu(&mut self, apdu: Apdu) -> Result<(), Error> {
+        self.framed.send(apdu).await.map_err(Error::ErrAnyHow)
+    }
+
+    pub async fn send_u(&mut self, function: u8) -> Result<(), Error> {
+        self.send_apdu(new_uframe(function)).await
+    }
+
+    pub async fn send_asdu(&mut self, asdu: Asdu) -> Result<(), Error> {
+        let apdu = new_iframe(asdu, self.send_sn.value(), self.rcv_sn.value());
+        self.send_sn = self.send_sn.next();
+        self.send_apdu(apdu).await
+    }
+
+    // 接收下一个 I 帧或 U 帧, 超时或连接关闭时返回 None
+    pub async fn recv(&mut self) -> Option<Apdu> {
+        loop {
+            let apdu = match timeout(self.timeout, self.framed.next()).await {
+                Ok(Some(Ok(apdu))) => apdu,
+                _ => return None,
+            };
+            match ApciKind::from(apdu.apci) {
+                ApciKind::I(_) => {
+                    self.rcv_sn = self.rcv_sn.next();
+                    self.send_apdu(new_sframe(self.rcv_sn.value())).await.ok()?;
+                    return Some(apdu);
+                }
+                ApciKind::U(u) if u.function == U_TESTFR_ACTIVE => {
+                    self.send_u(U_TESTFR_CONFIRM).await.ok()?;
+                }
+                ApciKind::S(_) => (),
+                ApciKind::U(_) => return Some(apdu),
+            }
+        }
+    }
+
+    // 期望收到功能为 function 的 U 帧
+    pub async fn expect_u(&mut self, function: u8) {
+        match self.recv().await {
+            Some(apdu) => match ApciKind::from(apdu.apci) {
+                ApciKind::U(u) if u.function == function => (),
+                _ => panic!("expect U-frame {function:#04x}, got {apdu}"),
+            },
+            None => panic!("expect U-frame {function:#04x}, got nothing"),
+        }
+    }
+
+    // 期望收到 I 帧, 返回其 ASDU
+    pub async fn expect_asdu(&mut self) -> Asdu {
+        match self.recv().await {
+            Some(Apdu {
+                asdu: Some(asdu), ..
+            }) => asdu,
+            Some(apdu) => panic!("expect I-frame, got {apdu}"),
+            None => panic!("expect I-frame, got nothing"),
+        }
+    }
+
+    // 期望收到类型标识与传送原因一致的 ASDU
+    pub async fn expect_asdu_with(&mut self, type_id: TypeID, cause: Cause) -> Asdu {
+        let asdu = self.expect_asdu().await;
+        assert_asdu(&asdu, type_id, cause);
+        asdu
+    }
+
+    // 期望在 duration 内没有收到 I 帧或 U 帧
+    pub async fn expect_silence(&mut self, duration: Duration) {
+        let saved = std::mem::replace(&mut self.timeout, duration);
+        let apdu = self.recv().await;
+        self.timeout = saved;
+        if let Some(apdu) = apdu {
+            panic!("expect silence, got {apdu}");
+        }
+    }
+
+    // 作为控制站启动数据传输
+    pub async fn start_dt(&mut self) -> Result<(), Error> {
+        self.send_u(U_STARTDT_ACTIVE).await?;
+        self.expect_u(U_STARTDT_CONFIRM).await;
+        Ok(())
+    }
+
+    // 作为控制站停止数据传输
+    pub async fn stop_dt(&mut self) -> Result<(), Error> {
+        self.send_u(U_STOPDT_ACTIVE).await?;
+        self.expect_u(U_STOPDT_CONFIRM).await;
+        Ok(())
+    }
+
+    // 作为被控站等待并确认 STARTDT
+    pub async fn accept_start_dt(&mut self) -> Result<(), Error> {
+        self.expect_u(U_STARTDT_ACTIVE).await;
+        self.send_u(U_STARTDT_CONFIRM).await
+    }
+
+    pub fn into_inner(self) -> Framed<T, Codec> {
+        self.framed
+    }
+}
+
+// 断言 ASDU 的类型标识与传送原因
+pub fn assert_asdu(asdu: &Asdu, type_id: TypeID, cause: Cause) {
+    let mut cot = asdu.identifier.cot;
+    assert_eq!(
+        (asdu.identifier.type_id, cot.cause().get()),
+        (type_id, cause),
+        "unexpected asdu {asdu}"
+    );
+}
+
+// 公共地址 ca 上单个单点信息的突发上送, 品质良好
+pub fn spontaneous(ca: CommonAddr, ioa: u16, value: bool) -> Asdu {
+    spontaneous_point(ca, SinglePointInfo::new_single(ioa, value))
+}
+
+// 同 spontaneous, 品质描述由 siq 给定
+pub fn spontaneous_siq(ca: CommonAddr, ioa: u16, siq: ObjectSIQ) -> Asdu {
+    spontaneous_point(
+        ca,
+        SinglePointInfo::new(InfoObjAddr::new(0, ioa), siq, None),
+    )
+}
+
+fn spontaneous_point(ca: CommonAddr, info: SinglePointInfo) -> Asdu {
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    single(false, cot, ca, vec![info]).expect("single point asdu")
+}
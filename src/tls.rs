@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client,
+    rustls::{
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName},
+        server::WebPkiClientVerifier,
+        ClientConfig, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor, TlsConnector,
+};
+
+use crate::Error;
+
+// IEC 62351-3 为加密的 IEC 104 链路分配的端口
+pub const IEC62351_TLS_PORT: u16 = 19998;
+
+// 客户端 TLS 配置: 校验服务端证书的 CA, 以及用于双向认证的客户端证书与私钥
+#[derive(Clone)]
+pub struct TlsConfig {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl TlsConfig {
+    // ca, cert, key 均为 PEM 文件, server_name 为服务端证书中的域名或 IP
+    pub fn new(
+        ca: impl AsRef<Path>,
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+        server_name: &str,
+    ) -> Result<Self, Error> {
+        let config = ClientConfig::builder()
+            .with_root_certificates(load_roots(ca.as_ref())?)
+            .with_client_auth_cert(load_certs(cert.as_ref())?, load_key(key.as_ref())?)
+            .map_err(|e| anyhow::anyhow!("tls client config: {e}"))?;
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| anyhow::anyhow!("tls server name: {e}"))?;
+        Ok(TlsConfig {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        })
+    }
+
+    pub(crate) async fn connect(
+        &self,
+        stream: TcpStream,
+    ) -> io::Result<client::TlsStream<TcpStream>> {
+        self.connector
+            .connect(self.server_name.clone(), stream)
+            .await
+    }
+}
+
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+// 服务端 TLS 配置: 服务端证书与私钥, 以及校验客户端证书的 CA(IEC 62351-3 要求双向认证)
+#[derive(Clone)]
+pub struct ServerTlsConfig {
+    acceptor: TlsAcceptor,
+}
+
+impl ServerTlsConfig {
+    pub fn new(
+        ca: impl AsRef<Path>,
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let verifier = WebPkiClientVerifier::builder(Arc::new(load_roots(ca.as_ref())?))
+            .build()
+            .map_err(|e| anyhow::anyhow!("tls client verifier: {e}"))?;
+        let config = ServerConfig::builder()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(load_certs(cert.as_ref())?, load_key(key.as_ref())?)
+            .map_err(|e| anyhow::anyhow!("tls server config: {e}"))?;
+        Ok(ServerTlsConfig {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+        })
+    }
+
+    pub(crate) fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.clone()
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(Error::ErrAnyHow(anyhow::anyhow!(
+            "no certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| {
+        Error::ErrAnyHow(anyhow::anyhow!(
+            "no private key found in {}",
+            path.display()
+        ))
+    })
+}
+
+fn load_roots(path: &Path) -> Result<RootCertStore, Error> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots
+            .add(cert)
+            .map_err(|e| anyhow::anyhow!("tls root certificate: {e}"))?;
+    }
+    Ok(roots)
+}
@@ -0,0 +1,93 @@
+use std::{fmt::Display, future::Future};
+
+use crate::{apci::ApciKind, Apdu};
+
+// 帧的传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Tx,
+    Rx,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Tx => f.write_str("TX"),
+            Direction::Rx => f.write_str("RX"),
+        }
+    }
+}
+
+// 记录收发的 I/S/U 帧.
+// 开启 tracing 特性时输出结构化字段 (direction, send_sn, rcv_sn, type_id, cot, ca),
+// 可在可观测性后端中按字段过滤, 并与所在连接的 span 关联; 否则输出到 log
+pub(crate) fn frame(direction: Direction, apdu: &Apdu) {
+    #[cfg(feature = "tracing")]
+    match ApciKind::from(apdu.apci) {
+        ApciKind::I(iapci) => match &apdu.asdu {
+            Some(asdu) => {
+                let mut cot = asdu.identifier.cot;
+                tracing::debug!(
+                    direction = %direction,
+                    send_sn = iapci.send_sn,
+                    rcv_sn = iapci.rcv_sn,
+                    type_id = ?asdu.identifier.type_id,
+                    cot = ?cot.cause().get(),
+                    negative = cot.is_negative(),
+                    ca = asdu.identifier.common_addr,
+                    "I-frame"
+                );
+                tracing::trace!(direction = %direction, asdu = ?asdu, "I-frame");
+            }
+            None => tracing::debug!(
+                direction = %direction,
+                send_sn = iapci.send_sn,
+                rcv_sn = iapci.rcv_sn,
+                "I-frame"
+            ),
+        },
+        ApciKind::S(sapci) => {
+            tracing::debug!(direction = %direction, rcv_sn = sapci.rcv_sn, "S-frame")
+        }
+        ApciKind::U(uapci) => {
+            tracing::debug!(direction = %direction, function = uapci.function, "U-frame")
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    match ApciKind::from(apdu.apci) {
+        ApciKind::I(iapci) => {
+            log::debug!("[{direction}] I-frame: {apdu}");
+            log::trace!("[{direction}] I-frame: {iapci:?} {:?}", apdu.asdu);
+        }
+        ApciKind::S(sapci) => {
+            log::debug!("[{direction}] S-frame: {apdu}");
+            log::trace!("[{direction}] S-frame: {sapci:?}");
+        }
+        ApciKind::U(uapci) => {
+            log::debug!("[{direction}] U-frame: {apdu}");
+            log::trace!("[{direction}] U-frame: {uapci:?}");
+        }
+    }
+}
+
+// 在连接的 span 中运行, role 为 client 或 server, 未开启 tracing 特性时原样返回
+pub(crate) fn in_connection_span<F>(
+    role: &'static str,
+    peer: impl Display,
+    fut: F,
+) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+        return fut.instrument(tracing::info_span!("iec104", role, %peer));
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        fut
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt, io, net::SocketAddr};
+
+use futures::future::BoxFuture;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{lookup_host, TcpStream},
+};
+
+#[cfg(feature = "proxy")]
+use crate::ProxyConfig;
+use crate::SocketOption;
+#[cfg(feature = "tls")]
+use crate::TlsConfig;
+
+// 传输层: 任意可读写的字节流, 如 TCP, TLS, unix socket, 串口服务器, 内存中的 duplex
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T> Transport for T where T: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+// 建立到对端的传输层连接, 客户端每次(重)连接时调用一次
+pub trait Connector: Send + Sync + 'static {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>>;
+}
+
+// 对端地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAddr {
+    /// 固定的套接字地址, IPv4 或 IPv6
+    Addr(SocketAddr),
+    /// "主机名:端口", IPv6 地址写作 "[::1]:2404". 每次(重)连接时重新解析
+    Host(String),
+}
+
+impl RemoteAddr {
+    // 解析出的全部地址, 按解析器返回的顺序
+    pub(crate) async fn resolve(&self) -> io::Result<Vec<SocketAddr>> {
+        match self {
+            RemoteAddr::Addr(addr) => Ok(vec![*addr]),
+            RemoteAddr::Host(host) => Ok(lookup_host(host.as_str()).await?.collect()),
+        }
+    }
+}
+
+impl From<SocketAddr> for RemoteAddr {
+    fn from(addr: SocketAddr) -> Self {
+        RemoteAddr::Addr(addr)
+    }
+}
+
+impl fmt::Display for RemoteAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteAddr::Addr(addr) => addr.fmt(f),
+            RemoteAddr::Host(host) => f.write_str(host),
+        }
+    }
+}
+
+// 默认的连接器: 明文 TCP, 启用 tls 特性并配置 TlsConfig 时为 TLS
+#[derive(Debug, Clone)]
+pub struct TcpConnector {
+    remote: RemoteAddr,
+    socket: SocketOption,
+    #[cfg(feature = "proxy")]
+    proxy: Option<ProxyConfig>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
+}
+
+impl TcpConnector {
+    pub fn new(socket_addr: SocketAddr) -> Self {
+        Self::new_remote(socket_addr.into())
+    }
+
+    // 连接主机名, 每次连接时重新解析, 依次尝试解析出的 IPv4 与 IPv6 地址
+    pub fn new_host(host: impl Into<String>) -> Self {
+        Self::new_remote(RemoteAddr::Host(host.into()))
+    }
+
+    pub fn new_remote(remote: RemoteAddr) -> Self {
+        TcpConnector {
+            remote,
+            socket: SocketOption::default(),
+            #[cfg(feature = "proxy")]
+            proxy: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    pub fn with_socket(mut self, socket: SocketOption) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    // 经代理连接, 套接字配置作用于到代理的连接
+    #[cfg(feature = "proxy")]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+impl Connector for TcpConnector {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>> {
+        Box::pin(async move {
+            let stream = self.connect_tcp().await?;
+            #[cfg(feature = "tls")]
+            if let Some(tls) = &self.tls {
+                let stream = tls.connect(stream).await?;
+                return Ok(Box::new(stream) as Box<dyn Transport>);
+            }
+            Ok(Box::new(stream) as Box<dyn Transport>)
+        })
+    }
+}
+
+impl TcpConnector {
+    // 配置了代理时先连接代理, 再经代理建立到对端的隧道
+    async fn connect_tcp(&self) -> io::Result<TcpStream> {
+        #[cfg(feature = "proxy")]
+        if let Some(proxy) = &self.proxy {
+            let mut stream = self.connect_any(proxy.proxy()).await?;
+            proxy.handshake(&mut stream, &self.remote).await?;
+            return Ok(stream);
+        }
+        self.connect_any(&self.remote).await
+    }
+
+    // 依次连接解析出的地址, 跳过与本地地址协议族不同的地址, 全部失败时返回最后一个错误
+    async fn connect_any(&self, remote: &RemoteAddr) -> io::Result<TcpStream> {
+        let mut last_err = None;
+        for addr in remote.resolve().await? {
+            if !self.socket.accepts(addr) {
+                continue;
+            }
+            match self.socket.connect(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(err) => {
+                    log::debug!("connect {addr} ({remote}): {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no usable address for {remote}"),
+            )
+        }))
+    }
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+// 链路监视: 空闲 interval(t3)后发送 TESTFR 测试帧, 测试帧在 t1 内未被确认记为一次丢失,
+// 连续丢失 max_missed 次后判定链路失效并关闭连接, 断开原因为 ErrT1Timeout.
+// 未达到次数时重发测试帧. 默认 t3 = 20 秒, 丢失 1 次即关闭连接.
+// 默认只以接收方向计空闲; suppress_on_traffic 时发送 I 帧也推迟测试帧
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestFrPolicy {
+    interval: Duration,
+    max_missed: u32,
+    suppress_on_traffic: bool,
+}
+
+impl Default for TestFrPolicy {
+    fn default() -> Self {
+        TestFrPolicy {
+            interval: Duration::from_secs(20),
+            max_missed: 1,
+            suppress_on_traffic: false,
+        }
+    }
+}
+
+impl TestFrPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 不发送周期测试帧, 仍响应对端的 TESTFR, Client::ping 仍可使用
+    pub fn disabled() -> Self {
+        Self::default().with_interval(Duration::ZERO)
+    }
+
+    // 空闲多长时间后发送测试帧(t3), 为 0 时不发送
+    #[must_use]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    // 连续多少个测试帧未被确认后关闭连接, 最小为 1
+    #[must_use]
+    pub fn with_max_missed(mut self, max_missed: u32) -> Self {
+        self.max_missed = max_missed.max(1);
+        self
+    }
+
+    // 正在发送 I 帧时不发送测试帧, 只有收发两个方向都空闲 t3 后才发送.
+    // 对端只回复 S 帧且确认间隔较长时, 避免单向的大量数据期间插入不必要的测试帧
+    #[must_use]
+    pub fn with_suppress_on_traffic(mut self, suppress: bool) -> Self {
+        self.suppress_on_traffic = suppress;
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn max_missed(&self) -> u32 {
+        self.max_missed
+    }
+
+    pub fn suppress_on_traffic(&self) -> bool {
+        self.suppress_on_traffic
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+}
+
+// 链路的空闲计时, 分别记录最近一次收到任意帧与发送 I 帧的时间
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LinkIdle {
+    rx: DateTime<Utc>,
+    tx: DateTime<Utc>,
+}
+
+impl LinkIdle {
+    pub(crate) fn new() -> Self {
+        let now = Utc::now();
+        LinkIdle { rx: now, tx: now }
+    }
+
+    // 收到 I 帧, S 帧或 U 帧, 同时重新计时 t3
+    pub(crate) fn on_rx(&mut self) {
+        self.rx = Utc::now();
+    }
+
+    // 发送测试帧后重新计时 t3
+    pub(crate) fn restart(&mut self) {
+        self.rx = Utc::now();
+    }
+
+    pub(crate) fn on_tx_data(&mut self) {
+        self.tx = Utc::now();
+    }
+
+    // 接收方向的空闲时长
+    pub(crate) fn rx_idle(&self) -> Duration {
+        (Utc::now() - self.rx).to_std().unwrap_or_default()
+    }
+
+    // 发送 I 帧方向的空闲时长
+    pub(crate) fn tx_idle(&self) -> Duration {
+        (Utc::now() - self.tx).to_std().unwrap_or_default()
+    }
+
+    // 是否到了按 policy 发送测试帧的时间
+    pub(crate) fn testfr_due(&self, policy: &TestFrPolicy) -> bool {
+        policy.is_enabled()
+            && self.rx_idle() >= policy.interval()
+            && (!policy.suppress_on_traffic() || self.tx_idle() >= policy.interval())
+    }
+}
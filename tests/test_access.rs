@@ -0,0 +1,55 @@
+use std::net::IpAddr;
+
+use tokio_iecp5::{AccessControl, Cidr};
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn cidr_parse() {
+    let net: Cidr = "10.0.0.0/8".parse().unwrap();
+    assert!(net.contains(ip("10.1.2.3")));
+    assert!(!net.contains(ip("11.0.0.1")));
+
+    // 不带前缀长度时为单个地址
+    let host: Cidr = "192.168.1.10".parse().unwrap();
+    assert!(host.contains(ip("192.168.1.10")));
+    assert!(!host.contains(ip("192.168.1.11")));
+
+    let any: Cidr = "0.0.0.0/0".parse().unwrap();
+    assert!(any.contains(ip("8.8.8.8")));
+    assert!(!any.contains(ip("::1")));
+
+    assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+    assert!("fe80::/129".parse::<Cidr>().is_err());
+    assert!("10.0.0/8".parse::<Cidr>().is_err());
+}
+
+#[test]
+fn cidr_ipv6() {
+    let net: Cidr = "fe80::/10".parse().unwrap();
+    assert!(net.contains(ip("fe80::1")));
+    assert!(!net.contains(ip("fec0::1")));
+
+    // IPv4 映射的 IPv6 地址按 IPv4 匹配
+    let v4: Cidr = "127.0.0.0/8".parse().unwrap();
+    assert!(v4.contains(ip("::ffff:127.0.0.1")));
+}
+
+#[test]
+fn access_control_deny_wins() {
+    let all = AccessControl::new();
+    assert!(all.is_allowed(ip("1.2.3.4")));
+
+    let access = AccessControl::new()
+        .allow("192.168.0.0/16".parse().unwrap())
+        .deny("192.168.1.0/24".parse().unwrap());
+    assert!(access.is_allowed(ip("192.168.2.1")));
+    assert!(!access.is_allowed(ip("192.168.1.1")));
+    assert!(!access.is_allowed(ip("10.0.0.1")));
+
+    let deny_only = AccessControl::new().deny("10.0.0.0/8".parse().unwrap());
+    assert!(deny_only.is_allowed(ip("192.168.1.1")));
+    assert!(!deny_only.is_allowed(ip("10.0.0.1")));
+}
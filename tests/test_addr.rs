@@ -0,0 +1,52 @@
+use tokio_iecp5::{
+    asdu::{Cause, CauseOfTransmission, InfoObjAddr, Ioa, StationAddr, GLOBAL_COMMON_ADDR},
+    csys::{interrogation_cmd, read_cmd, ObjectQOI},
+    mproc::single_sequence,
+};
+
+#[test]
+fn ioa_validation() -> anyhow::Result<()> {
+    assert!(Ioa::new(0).is_err());
+    assert!(Ioa::new(0x1000000).is_err());
+    assert_eq!(Ioa::new(0xffffff)?.get(), 0xffffff);
+    assert!(Ioa::with_size(256, 1).is_err());
+    assert!(Ioa::with_size(255, 1).is_ok());
+    assert!(Ioa::with_size(65536, 2).is_err());
+    assert!(Ioa::with_size(1, 4).is_err());
+
+    let ioa = InfoObjAddr::from(Ioa::try_from(0x010203)?);
+    assert_eq!(ioa.raw().value(), 0x010203);
+    assert_eq!(Ioa::try_from(ioa)?, Ioa::new(0x010203)?);
+    assert!(Ioa::try_from(InfoObjAddr::new(0, 0)).is_err());
+    Ok(())
+}
+
+#[test]
+fn station_addr_validation() -> anyhow::Result<()> {
+    assert!(StationAddr::new(0).is_err());
+    assert_eq!(u16::from(StationAddr::try_from(7)?), 7);
+    assert_eq!(StationAddr::GLOBAL.get(), GLOBAL_COMMON_ADDR);
+    Ok(())
+}
+
+#[test]
+fn builders_accept_newtypes() -> anyhow::Result<()> {
+    let act = CauseOfTransmission::new(false, false, Cause::Activation);
+    let ca = StationAddr::new(3)?;
+    let asdu = interrogation_cmd(act, ca, ObjectQOI::new(20))?;
+    assert_eq!(asdu.identifier.common_addr, 3);
+    // 原有的 u16 参数不变
+    let asdu = interrogation_cmd(act, 3, ObjectQOI::new(20))?;
+    assert_eq!(asdu.identifier.common_addr, 3);
+
+    let req = CauseOfTransmission::new(false, false, Cause::Request);
+    let a = read_cmd(req, ca, Ioa::new(0x10000)?)?;
+    let b = read_cmd(req, 3, InfoObjAddr::new(1, 0))?;
+    assert_eq!(a.raw, b.raw);
+
+    let spont = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    let mut asdu = single_sequence(spont, ca, Ioa::new(100)?, vec![true, false])?;
+    let mut info = asdu.get_single_point()?;
+    assert_eq!(info[1].ioa.addr().get(), 101);
+    Ok(())
+}
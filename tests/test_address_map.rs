@@ -0,0 +1,102 @@
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    mproc::{single, SinglePointInfo},
+    AddressMap,
+};
+
+fn cot() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::Spontaneous)
+}
+
+fn points(asdu: &Asdu) -> Vec<(u16, u16)> {
+    let mut asdu = asdu.clone();
+    let ca = asdu.identifier.common_addr;
+    asdu.get_single_point()
+        .unwrap()
+        .into_iter()
+        .map(|mut p| (ca, p.ioa.addr().get()))
+        .collect()
+}
+
+#[test]
+fn address_map_rules() {
+    let map = AddressMap::new()
+        .with_point((1, 7), (20, 9000))
+        .with_range(1, 100..=199, 10, 1100)
+        .with_common_addr(1, 10)
+        .with_type_offset(TypeID::C_SC_NA_1, 6000);
+
+    // 逐点规则优先, 其次地址段, 最后公共地址
+    assert_eq!(map.map(TypeID::M_SP_NA_1, 1, 7), Some((20, 9000)));
+    assert_eq!(map.map(TypeID::M_SP_NA_1, 1, 150), Some((10, 1150)));
+    assert_eq!(map.map(TypeID::M_SP_NA_1, 1, 300), Some((10, 300)));
+    assert_eq!(map.map(TypeID::M_SP_NA_1, 2, 1), None);
+    // 按类型标识的偏移
+    assert_eq!(map.map(TypeID::C_SC_NA_1, 1, 150), Some((10, 7150)));
+
+    for (type_id, ca, ioa) in [
+        (TypeID::M_SP_NA_1, 1, 7),
+        (TypeID::M_SP_NA_1, 1, 150),
+        (TypeID::M_SP_NA_1, 1, 300),
+        (TypeID::C_SC_NA_1, 1, 150),
+    ] {
+        let (to_ca, to_ioa) = map.map(type_id, ca, ioa).unwrap();
+        assert_eq!(map.unmap(type_id, to_ca, to_ioa), Some((ca, ioa)));
+    }
+    assert_eq!(map.unmap(TypeID::M_SP_NA_1, 11, 1), None);
+
+    let passthrough = AddressMap::new().with_passthrough(true);
+    assert_eq!(passthrough.map(TypeID::M_SP_NA_1, 2, 1), Some((2, 1)));
+}
+
+#[test]
+fn address_map_rewrites_asdu() {
+    let map = AddressMap::new()
+        .with_point((1, 7), (20, 9000))
+        .with_range(1, 100..=199, 10, 1100);
+
+    // 分到不同公共地址, 未映射的信息对象被丢弃
+    let asdu = single(
+        false,
+        cot(),
+        1,
+        vec![
+            SinglePointInfo::new_single(7, true),
+            SinglePointInfo::new_single(100, true),
+            SinglePointInfo::new_single(500, true),
+            SinglePointInfo::new_single(101, false),
+        ],
+    )
+    .unwrap();
+    let mapped = map.map_asdu(&asdu);
+    assert_eq!(mapped.len(), 2);
+    assert_eq!(points(&mapped[0]), [(10, 1100), (10, 1101)]);
+    assert_eq!(points(&mapped[1]), [(20, 9000)]);
+    let back = map.unmap_asdu(&mapped[0]);
+    assert_eq!(points(&back[0]), [(1, 100), (1, 101)]);
+
+    // 顺序编码整体平移, 超出地址段时丢弃
+    let seq = |start: u16, n: u16| {
+        let infos = (start..start + n)
+            .map(|ioa| SinglePointInfo::new_single(ioa, true))
+            .collect();
+        single(true, cot(), 1, infos).unwrap()
+    };
+    let mapped = map.map_asdu(&seq(110, 3));
+    assert_eq!(points(&mapped[0]), [(10, 1110), (10, 1111), (10, 1112)]);
+    assert!(map.map_asdu(&seq(198, 3)).is_empty());
+
+    // 命令
+    let act = CauseOfTransmission::new(false, false, Cause::Activation);
+    let cmd = single_cmd(
+        TypeID::C_SC_NA_1,
+        act,
+        10,
+        SingleCommandInfo::new(1120, true, false),
+    )
+    .unwrap();
+    let mut down = map.unmap_asdu(&cmd).pop().unwrap();
+    assert_eq!(down.identifier.common_addr, 1);
+    assert_eq!(down.get_single_cmd().unwrap().ioa.addr().get(), 120);
+}
@@ -1,13 +1,16 @@
+use std::collections::VecDeque;
+
 use anyhow::{anyhow, Result};
 use bytes::{Bytes, BytesMut};
+use chrono::Utc;
 use tokio_iecp5::apci::*;
-use tokio_iecp5::{Apdu, Codec};
+use tokio_iecp5::{Apdu, Codec, SeqPending};
 use tokio_iecp5::asdu::*;
 use tokio_util::codec::{Decoder, Encoder};
 
 #[test]
 fn decode_iapci() -> Result<()> {
-    let mut codec = Codec;
+    let mut codec = Codec::default();
     let mut buf = BytesMut::from(&[START_FRAME, 0x04, 0x02, 0x00, 0x03, 0x00][..]);
     let apdu = codec.decode(&mut buf)?.ok_or(anyhow!("decode failed"))?;
     let apci_kind = apdu.apci.into();
@@ -23,7 +26,7 @@ fn decode_iapci() -> Result<()> {
 
 #[test]
 fn decode_sapci() -> Result<()> {
-    let mut codec = Codec;
+    let mut codec = Codec::default();
     let mut buf = BytesMut::from(&[START_FRAME, 0x04, 0x01, 0x00, 0x02, 0x00][..]);
     let apdu = codec.decode(&mut buf)?.ok_or(anyhow!("decode failed"))?;
     let apci_kind = apdu.apci.into();
@@ -38,7 +41,7 @@ fn decode_sapci() -> Result<()> {
 
 #[test]
 fn decode_uapci() -> Result<()> {
-    let mut codec = Codec;
+    let mut codec = Codec::default();
     let mut buf = BytesMut::from(&[START_FRAME, 0x04, 0x07, 0x00, 0x00, 0x00][..]);
     let apdu = codec.decode(&mut buf)?.ok_or(anyhow!("decode failed"))?;
     let apci_kind = apdu.apci.into();
@@ -53,7 +56,7 @@ fn decode_uapci() -> Result<()> {
 
 #[test]
 fn encode_iapci() -> Result<()> {
-    let mut codec = Codec;
+    let mut codec = Codec::default();
     let apdu = Apdu {
         apci: Apci {
             start: START_FRAME,
@@ -103,4 +106,65 @@ fn encode_iapci() -> Result<()> {
     codec.encode(apdu, &mut buf)?;
     assert_eq!(buf.as_ref(), &expected[..]);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn seq_num_wraps_at_32768() {
+    let last = SeqNum::new(32767);
+    assert_eq!(last.next(), SeqNum::new(0));
+    assert_eq!(last.wrapping_add(3).value(), 2);
+    assert_eq!(last.distance(SeqNum::new(1)), 2);
+    assert_eq!(SeqNum::new(32768).value(), 0);
+
+    // S 帧控制域中的接收序列号
+    match ApciKind::from(new_sframe(last.value()).apci) {
+        ApciKind::S(apci) => assert_eq!(apci.rcv_sn, 32767),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn ack_across_wraparound() {
+    // 已发送 32766, 32767, 0, 1 四个 I 帧
+    let mut ack_sendsn = SeqNum::new(32766);
+    let mut send_sn = ack_sendsn.wrapping_add(4);
+    let mut pending: VecDeque<SeqPending> = (0..4)
+        .map(|i| SeqPending {
+            seq: ack_sendsn.wrapping_add(i),
+            send_time: Utc::now(),
+        })
+        .collect();
+
+    // 确认到 0 之前
+    assert!(update_ack_no_out(
+        SeqNum::new(0),
+        &mut ack_sendsn,
+        &mut send_sn,
+        &mut pending
+    ));
+    assert_eq!(ack_sendsn, SeqNum::new(0));
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending.front().map(|p| p.seq), Some(SeqNum::new(0)));
+
+    // 超出已发送范围的确认号
+    assert!(!update_ack_no_out(
+        SeqNum::new(3),
+        &mut ack_sendsn,
+        &mut send_sn,
+        &mut pending
+    ));
+    assert!(!update_ack_no_out(
+        SeqNum::new(32767),
+        &mut ack_sendsn,
+        &mut send_sn,
+        &mut pending
+    ));
+
+    assert!(update_ack_no_out(
+        send_sn,
+        &mut ack_sendsn,
+        &mut send_sn,
+        &mut pending
+    ));
+    assert!(pending.is_empty());
+}
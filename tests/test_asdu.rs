@@ -1,6 +1,8 @@
 use anyhow::Result;
 use bytes::Bytes;
-use tokio_iecp5::asdu::{Asdu, Cause, TypeID};
+use tokio_iecp5::asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, TypeID, VariableStruct};
+use tokio_iecp5::mproc::{single, SinglePointInfo};
+use tokio_iecp5::Error;
 
 #[test]
 fn decode_and_encode_asdu() -> Result<()> {
@@ -33,4 +35,112 @@ fn asdu_from_bytes() -> Result<()> {
     let raw: Bytes = asdu.try_into()?;
     assert_eq!(bytes, raw);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn encode_and_decode_with_asdu_params() -> Result<()> {
+    // IEC 101: 1 字节传送原因, 1 字节公共地址, 2 字节信息对象地址
+    let params = AsduParams::new(1, 1, 2)?;
+    let asdu = single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x12,
+        vec![
+            SinglePointInfo::new_single(0x0102, true),
+            SinglePointInfo::new_single(0x0304, false),
+        ],
+    )?;
+    let raw = asdu.clone().to_bytes_with(&params)?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[0x01, 0x02, 0x03, 0x12, 0x02, 0x01, 0x01, 0x04, 0x03, 0x00])
+    );
+
+    let decoded = Asdu::from_bytes_with(raw, &params)?;
+    assert_eq!(decoded.identifier.common_addr, 0x12);
+    assert_eq!(decoded.raw, asdu.raw);
+
+    // 1 字节公共地址时 255 为全局地址
+    let decoded = Asdu::from_bytes_with(
+        Bytes::from_static(&[0x64, 0x01, 0x06, 0xff, 0x00, 0x00, 0x14]),
+        &params,
+    )?;
+    assert_eq!(decoded.identifier.common_addr, 0xffff);
+    assert_eq!(decoded.raw, Bytes::from_static(&[0x00, 0x00, 0x00, 0x14]));
+    assert_eq!(
+        decoded.to_bytes_with(&params)?,
+        Bytes::from_static(&[0x64, 0x01, 0x06, 0xff, 0x00, 0x00, 0x14])
+    );
+
+    // 超出长度的地址不能编码
+    let asdu = single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x1234,
+        vec![SinglePointInfo::new_single(0x01, true)],
+    )?;
+    assert!(asdu.to_bytes_with(&params).is_err());
+    assert!(AsduParams::new(3, 2, 3).is_err());
+    Ok(())
+}
+
+#[test]
+fn mirror_negative_sets_pn() -> Result<()> {
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    let asdu = single(false, cot, 0x01, vec![SinglePointInfo::new_single(1, true)])?;
+
+    let mut negative = asdu.mirror_negative(Cause::ActivationCon);
+    assert!(negative.identifier.cot.is_negative());
+    assert_eq!(negative.identifier.cot.cause().get(), Cause::ActivationCon);
+    assert_eq!(negative.raw, asdu.raw);
+    let raw: Bytes = negative.clone().try_into()?;
+    assert_eq!(raw[2], 0x47);
+
+    let positive = negative.mirror(Cause::ActivationCon);
+    assert!(!positive.identifier.cot.is_negative());
+    assert!(asdu.mirror(Cause::UnknownIOA).identifier.cot.is_rejected());
+    Ok(())
+}
+
+#[test]
+fn info_len_checked_against_vsq() -> Result<()> {
+    // M_ME_NC_1 声明 127 个信息对象, 只携带 4 字节
+    let bytes = Bytes::from_static(&[0x0d, 0x7f, 0x03, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00]);
+    let mut asdu: Asdu = bytes.try_into()?;
+    assert!(matches!(
+        asdu.get_measured_value_float(),
+        Err(Error::ErrAsduTooShort(TypeID::M_ME_NC_1, 1016, 4))
+    ));
+
+    // 顺序编码只有第一个信息对象带地址
+    let bytes = Bytes::from_static(&[0x01, 0x83, 0x03, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00]);
+    let mut asdu: Asdu = bytes.try_into()?;
+    assert!(matches!(
+        asdu.check_info_len(),
+        Err(Error::ErrAsduTooShort(TypeID::M_SP_NA_1, 6, 5))
+    ));
+    assert_eq!(asdu.identifier.type_id.info_obj_size(), Some(1));
+    asdu.identifier.variable_struct = VariableStruct::try_from(0x82).unwrap();
+    assert_eq!(asdu.get_single_point()?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn reserved_type_id_is_unknown() -> Result<()> {
+    for value in [0u8, 22, 52, 57, 128, 255] {
+        assert_eq!(TypeID::from(value), TypeID::Unknown(value));
+        assert_eq!(u8::from(TypeID::from(value)), value);
+    }
+    assert_eq!(TypeID::from(16), TypeID::M_IT_TA_1);
+    assert_eq!(TypeID::from(58), TypeID::C_SC_TA_1);
+    assert_eq!(u8::from(TypeID::F_SC_NB_1), 127);
+
+    // 未知类型的 ASDU 仍可解码与编码
+    let bytes = Bytes::from_static(&[0x35, 0x01, 0x06, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00]);
+    let asdu: Asdu = bytes.clone().try_into()?;
+    assert_eq!(asdu.identifier.type_id, TypeID::Unknown(0x35));
+    assert!(asdu.check_info_len().is_ok());
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(raw, bytes);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use chrono::{TimeZone, Utc};
+use tokio_iecp5::{
+    asdu::{Cause, CauseOfTransmission, InfoObjAddr, Ioa, TypeID},
+    mproc::{
+        measured_value_float_cp56time2a, single, MeasuredValueFloatInfo, ObjectQDS, SinglePointInfo,
+    },
+    AsduBuilder, Error, PointValue, Quality,
+};
+
+#[tokio::test]
+async fn builder_matches_free_functions() -> anyhow::Result<()> {
+    let time = Utc.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap();
+    let built = AsduBuilder::new(TypeID::M_ME_TF_1)
+        .cot(Cause::Spontaneous)
+        .ca(1)
+        .add_timestamped(100, PointValue::Float(1.5), ObjectQDS::of_defaults(), time)
+        .build()?;
+    let expect = measured_value_float_cp56time2a(
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        1,
+        vec![MeasuredValueFloatInfo {
+            ioa: InfoObjAddr::new(0, 100),
+            r: 1.5,
+            qds: ObjectQDS::of_defaults(),
+            time: Some(time),
+        }],
+    )
+    .await?;
+    assert_eq!(built.identifier.type_id, expect.identifier.type_id);
+    assert_eq!(built.raw, expect.raw);
+
+    let built = AsduBuilder::new(TypeID::M_SP_NA_1)
+        .cot(Cause::InterrogatedByStation)
+        .ca(2)
+        .sequence(true)
+        .add(Ioa::new(10)?, PointValue::Single(true), Quality::default())
+        .add(Ioa::new(11)?, PointValue::Single(false), Quality::default())
+        .build()?;
+    let expect = single(
+        true,
+        CauseOfTransmission::new(false, false, Cause::InterrogatedByStation),
+        2,
+        vec![
+            SinglePointInfo::new_single(10, true),
+            SinglePointInfo::new_single(11, false),
+        ],
+    )?;
+    assert_eq!(built.raw, expect.raw);
+    Ok(())
+}
+
+#[test]
+fn builder_validates() {
+    let good = ObjectQDS::of_defaults();
+    let base = || AsduBuilder::new(TypeID::M_ME_NC_1).ca(1);
+
+    // 传送原因
+    let err = base()
+        .cot(Cause::Activation)
+        .add(1, PointValue::Float(1.0), good)
+        .build();
+    assert!(matches!(err, Err(Error::ErrCmdCause(_))));
+    // 值的类型
+    let err = base().add(1, PointValue::Scaled(1), good).build();
+    assert!(matches!(err, Err(Error::ErrTypeIDNotMatch(_))));
+    // 不带时标的类型
+    let err = base()
+        .add_timestamped(1, PointValue::Float(1.0), good, Utc::now())
+        .build();
+    assert!(matches!(err, Err(Error::ErrTypeIDNotMatch(_))));
+    // 信息元素个数
+    assert!(matches!(base().build(), Err(Error::ErrInfoNum(0))));
+    // 顺序编码地址不连续
+    let err = base()
+        .sequence(true)
+        .add(1, PointValue::Float(1.0), good)
+        .add(3, PointValue::Float(1.0), good)
+        .build();
+    assert!(matches!(err, Err(Error::ErrIoaNotSequential(3))));
+    // 公共地址
+    let err = AsduBuilder::new(TypeID::M_ME_NC_1)
+        .add(1, PointValue::Float(1.0), good)
+        .build();
+    assert!(err.is_err());
+}
+
+#[test]
+fn builder_splits_large_asdus() -> anyhow::Result<()> {
+    let builder = (1..=100).fold(AsduBuilder::new(TypeID::M_ME_NC_1).ca(1), |b, addr| {
+        b.add(
+            addr,
+            PointValue::Float(addr as f32),
+            ObjectQDS::of_defaults(),
+        )
+    });
+    assert!(matches!(
+        builder.clone().build(),
+        Err(Error::ErrAsduTooLarge(_))
+    ));
+    let asdus = builder.build_all()?;
+    assert!(asdus.len() > 1);
+    let total: usize = asdus
+        .iter()
+        .map(|a| {
+            let mut vsq = a.identifier.variable_struct;
+            vsq.number().get().value() as usize
+        })
+        .sum();
+    assert_eq!(total, 100);
+    Ok(())
+}
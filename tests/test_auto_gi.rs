@@ -0,0 +1,120 @@
+use std::{future, time::Duration};
+
+use tokio_iecp5::{
+    apci::{U_STOPDT_ACTIVE, U_STOPDT_CONFIRM},
+    asdu::{Asdu, Cause, TypeID},
+    test_util::{assert_asdu, duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 接收激活后的自动命令, 按类型标识排序
+async fn expect_commands<T: tokio_iecp5::Transport>(
+    slave: &mut ScriptedPeer<T>,
+    n: usize,
+) -> Vec<TypeID> {
+    let mut types = Vec::new();
+    for _ in 0..n {
+        let asdu = slave.expect_asdu().await;
+        assert_asdu(&asdu, asdu.identifier.type_id, Cause::Activation);
+        assert_eq!(asdu.identifier.common_addr, 7);
+        types.push(asdu.identifier.type_id);
+    }
+    types.sort_by_key(|t| u8::from(*t));
+    types
+}
+
+#[tokio::test]
+async fn client_auto_gi_and_clock_sync_on_activation() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default()
+        .with_auto_gi(7, Duration::ZERO)
+        .with_auto_clock_sync(7, Duration::from_secs(3600));
+    let client = Client::new_with_connector(NopHandler, option, connector);
+    client.start().await?;
+
+    // 不需要应用发送 STARTDT
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(
+        expect_commands(&mut slave, 2).await,
+        [TypeID::C_IC_NA_1, TypeID::C_CS_NA_1]
+    );
+    // 周期为 0 时只召唤一次
+    slave.expect_silence(Duration::from_millis(300)).await;
+
+    // 重新激活后再次召唤与同步
+    client.send_stop_dt().await?;
+    slave.expect_u(U_STOPDT_ACTIVE).await;
+    slave.send_u(U_STOPDT_CONFIRM).await?;
+    client.send_start_dt().await?;
+    slave.accept_start_dt().await?;
+    assert_eq!(
+        expect_commands(&mut slave, 2).await,
+        [TypeID::C_IC_NA_1, TypeID::C_CS_NA_1]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_periodic_auto_gi() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default().with_auto_gi(7, Duration::from_millis(200));
+    let client = Client::new_with_connector(NopHandler, option, connector);
+    client.start().await?;
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(expect_commands(&mut slave, 1).await, [TypeID::C_IC_NA_1]);
+    let start = tokio::time::Instant::now();
+    assert_eq!(expect_commands(&mut slave, 1).await, [TypeID::C_IC_NA_1]);
+    assert!(start.elapsed() >= Duration::from_millis(150));
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_auto_start_dt() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    let mut events = client.events();
+    client.start().await?;
+
+    // 默认连接后自动发送 STARTDT, 确认后报告激活
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+    assert!(client.is_active());
+    drop(slave);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_manual_start_dt() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default().with_auto_start_dt(false);
+    let client = Client::new_with_connector(NopHandler, option, connector);
+    let mut events = client.events();
+    client.start().await?;
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.expect_silence(Duration::from_millis(200)).await;
+    assert!(!client.is_active());
+
+    client.send_start_dt().await?;
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+    drop(slave);
+    client.stop().await;
+    Ok(())
+}
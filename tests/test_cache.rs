@@ -0,0 +1,133 @@
+use std::{future, time::Duration};
+
+use chrono::{TimeZone, Utc};
+use tokio::time::sleep;
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr},
+    mproc::{
+        double, integrated_totals, measured_value_float_sequence, measured_value_scaled_cp56time2a,
+        single, BinaryCounterReadingInfo, DoublePointInfo, MeasuredValueScaledInfo, ObjectBCR,
+        ObjectQDS, SinglePointInfo,
+    },
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientHandler, ClientOption, Error, PointCache, PointValue,
+};
+
+fn cot(cause: Cause) -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, cause)
+}
+
+#[test]
+fn point_cache_apply() -> anyhow::Result<()> {
+    let cache = PointCache::new();
+    let n = cache.apply(&single(
+        false,
+        cot(Cause::InterrogatedByGroup2),
+        1,
+        vec![
+            SinglePointInfo::new_single(1, true),
+            SinglePointInfo::new_single(2, false),
+        ],
+    )?)?;
+    assert_eq!(n, 2);
+    assert_eq!(cache.get_single(1, 1), Some(true));
+    assert_eq!(cache.get_single(1, 2), Some(false));
+    assert_eq!(cache.get(1, 1).unwrap().group, 2);
+    // 类型不一致
+    assert_eq!(cache.get_float(1, 1), None);
+
+    cache.apply(&double(
+        false,
+        cot(Cause::Spontaneous),
+        2,
+        vec![DoublePointInfo::new_double(5, 2)],
+    )?)?;
+    assert_eq!(cache.get_double(2, 5), Some(2));
+
+    let mut invalid = ObjectQDS::of_defaults();
+    invalid.invalid().set(true);
+    let time = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    cache.apply(&measured_value_scaled_cp56time2a(
+        cot(Cause::Spontaneous),
+        1,
+        vec![MeasuredValueScaledInfo {
+            ioa: InfoObjAddr::new(0, 10),
+            sva: 250,
+            qds: invalid,
+            time: Some(time),
+        }],
+    )?)?;
+    let mut point = cache.get(1, 10).unwrap();
+    assert_eq!(point.value, PointValue::Scaled(250));
+    assert!(point.quality.invalid().get());
+    assert_eq!(point.time, Some(time));
+    // 已有点的组号不被突发数据清除
+    assert_eq!(point.group, 0);
+
+    cache.apply(&integrated_totals(
+        cot(Cause::RequestByGroup3Counter),
+        1,
+        vec![BinaryCounterReadingInfo {
+            ioa: InfoObjAddr::new(0, 20),
+            bcr: ObjectBCR {
+                invalid: false,
+                ca: false,
+                cy: false,
+                seq: 1,
+                value: -9,
+            },
+            time: None,
+        }],
+    )?)?;
+    assert_eq!(cache.get_counter(1, 20), Some(-9));
+    assert_eq!(cache.get(1, 20).unwrap().group, 3);
+
+    let snapshot = cache.snapshot();
+    let keys: Vec<_> = snapshot.keys().copied().collect();
+    assert_eq!(keys, vec![(1, 1), (1, 2), (1, 10), (1, 20), (2, 5)]);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    Ok(())
+}
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn client_point_cache() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::default().with_point_cache(PointCache::new());
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    slave
+        .send_asdu(measured_value_float_sequence(
+            cot(Cause::Spontaneous),
+            3,
+            InfoObjAddr::new(0, 100),
+            vec![1.0, 2.0, 3.0],
+        )?)
+        .await?;
+
+    let cache = client.point_cache().unwrap();
+    for _ in 0..100 {
+        if cache.len() == 3 {
+            break;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(cache.get_float(3, 101), Some(2.0));
+    assert!(cache.get(3, 102).unwrap().time.is_some());
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+use tokio_iecp5::{
+    apci::{new_iframe, new_uframe, U_STARTDT_ACTIVE},
+    asdu::{Cause, CauseOfTransmission},
+    capture::{PcapWriter, LINKTYPE_RAW, PCAP_MAGIC},
+    mproc::{single, SinglePointInfo},
+    FrameObserver,
+};
+
+fn u32_le(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+#[test]
+fn pcap_writer_records_apdus() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("iec104-{}.pcap", std::process::id()));
+    let local = "192.168.1.10:50000".parse()?;
+    let remote = "192.168.1.20:2404".parse()?;
+    {
+        let writer = PcapWriter::create(&path, local, remote)?;
+        writer.on_tx(&new_uframe(U_STARTDT_ACTIVE), Instant::now());
+        let asdu = single(
+            false,
+            CauseOfTransmission::new(false, false, Cause::Spontaneous),
+            0x0001,
+            vec![SinglePointInfo::new_single(100, true)],
+        )?;
+        writer.on_rx(&new_iframe(asdu, 0, 0), Instant::now());
+    }
+    let data = std::fs::read(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(u32_le(&data[0..]), PCAP_MAGIC);
+    assert_eq!(u32_le(&data[20..]), LINKTYPE_RAW);
+
+    // 第一个报文: STARTDT, 192.168.1.10:50000 -> 192.168.1.20:2404
+    let rec = &data[24..];
+    let len = u32_le(&rec[8..]) as usize;
+    assert_eq!(len, 20 + 20 + 6);
+    let packet = &rec[16..16 + len];
+    assert_eq!(packet[0], 0x45);
+    assert_eq!(&packet[12..16], &[192, 168, 1, 10]);
+    assert_eq!(&packet[16..20], &[192, 168, 1, 20]);
+    assert_eq!(u16::from_be_bytes([packet[20], packet[21]]), 50000);
+    assert_eq!(u16::from_be_bytes([packet[22], packet[23]]), 2404);
+    assert_eq!(&packet[40..], &[0x68, 0x04, 0x07, 0x00, 0x00, 0x00]);
+
+    // 第二个报文: I 帧, 反方向, 确认号为已发送的 6 字节之后
+    let rec = &rec[16 + len..];
+    let len = u32_le(&rec[8..]) as usize;
+    let packet = &rec[16..16 + len];
+    assert_eq!(&packet[12..16], &[192, 168, 1, 20]);
+    assert_eq!(
+        u32::from_be_bytes([packet[28], packet[29], packet[30], packet[31]]),
+        1 + 6
+    );
+    assert_eq!(&packet[40..42], &[0x68, 0x0e]);
+    assert_eq!(rec.len(), 16 + len);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::{future, time::Duration};
+
+use tokio::time::timeout;
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    mproc::{single, SinglePointInfo},
+    test_util::{duplex_connector, serve_in_memory_with_handle, spontaneous, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, DataStore, Error,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 60 个信息对象的单点信息, 约 250 字节
+fn large_asdu() -> Asdu {
+    single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x0001,
+        (1..=60)
+            .map(|ioa| SinglePointInfo::new_single(ioa, true))
+            .collect(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn client_channel_backpressure() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false).with_channel_depth(4);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    // 被控站停止接收, 传输层缓冲区满后命令通道随之写满
+    let mut full = false;
+    for _ in 0..10_000 {
+        match client.try_send_asdu(large_asdu()) {
+            Ok(()) => tokio::task::yield_now().await,
+            Err(Error::ErrBufferFull) => {
+                full = true;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    assert!(full);
+    assert!(
+        timeout(Duration::from_millis(100), client.send_asdu(large_asdu()))
+            .await
+            .is_err()
+    );
+
+    // 恢复接收后可以继续发送
+    let drain = tokio::spawn(async move {
+        while let Some(apdu) = slave.recv().await {
+            drop(apdu);
+        }
+    });
+    timeout(Duration::from_secs(1), client.send_asdu(large_asdu())).await??;
+    drain.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_channel_backpressure() -> anyhow::Result<()> {
+    let (mut master, handle) = serve_in_memory_with_handle(DataStore::new());
+    master.start_dt().await?;
+
+    // 控制站停止接收
+    let mut full = false;
+    for _ in 0..10_000 {
+        match handle.broadcast_asdu(large_asdu()) {
+            Ok(()) => tokio::task::yield_now().await,
+            Err(Error::ErrBufferFull) => {
+                full = true;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    assert!(full);
+
+    // 恢复接收后积压的数据依次送达, 通道重新可用
+    let mut sent = false;
+    for _ in 0..2_000 {
+        let asdu = master
+            .expect_asdu_with(TypeID::M_SP_NA_1, Cause::Spontaneous)
+            .await;
+        assert_eq!(asdu.identifier.variable_struct.raw() & 0x7f, 60);
+        if handle.broadcast_asdu(large_asdu()).is_ok() {
+            sent = true;
+            break;
+        }
+    }
+    assert!(sent);
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_send_asdus_keeps_order() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false).with_channel_depth(4);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    // 超过通道深度的批次分段发送, 顺序不变
+    let script = async {
+        let mut ioas = Vec::new();
+        for _ in 0..10 {
+            let mut asdu = slave.expect_asdu().await;
+            ioas.push(asdu.get_single_point().unwrap()[0].ioa.addr().get());
+        }
+        ioas
+    };
+    let (sent, ioas) = tokio::join!(
+        client.send_asdus((1..=10).map(|ioa| spontaneous(1, ioa, true)).collect()),
+        script
+    );
+    sent?;
+    assert_eq!(ioas, (1..=10).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_broadcast_batch_keeps_order() -> anyhow::Result<()> {
+    let (mut master, handle) = serve_in_memory_with_handle(DataStore::new());
+    assert!(matches!(
+        handle.broadcast_batch(vec![spontaneous(1, 1, true)]),
+        Err(Error::ErrNotActive)
+    ));
+    master.start_dt().await?;
+
+    handle.broadcast_batch((1..=10).map(|ioa| spontaneous(1, ioa, true)).collect())?;
+    for ioa in 1..=10 {
+        let mut asdu = master
+            .expect_asdu_with(TypeID::M_SP_NA_1, Cause::Spontaneous)
+            .await;
+        assert_eq!(asdu.get_single_point()?[0].ioa.addr().get(), ioa);
+    }
+    Ok(())
+}
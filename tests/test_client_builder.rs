@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use tokio::{sync::mpsc, time::timeout};
+use tokio_iecp5::{
+    test_util::{duplex_connector, spontaneous, ScriptedPeer},
+    Client, ClientEvent, ClientOption,
+};
+
+#[tokio::test]
+async fn builder_default_handler_records_points() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::builder()
+        .option(ClientOption::new("127.0.0.1:2404".parse()?, false))
+        .connector(connector)
+        .build();
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    slave.send_asdu(spontaneous(1, 1, true)).await?;
+
+    let cache = client.point_cache().expect("point cache enabled");
+    timeout(Duration::from_secs(1), async {
+        while cache.get_single(0x0001, 1).is_none() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await?;
+    assert_eq!(cache.get_single(0x0001, 1), Some(true));
+    Ok(())
+}
+
+#[tokio::test]
+async fn builder_on_asdu_closure() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut received = 0;
+    let client = Client::builder()
+        .connector(connector)
+        .on_asdu(move |asdu| {
+            received += 1;
+            let _ = tx.send((received, asdu));
+        })
+        .build();
+    assert!(client.point_cache().is_none());
+    client.start().await?;
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    slave.send_asdu(spontaneous(1, 1, true)).await?;
+    slave.send_asdu(spontaneous(1, 2, true)).await?;
+
+    for n in 1..=2 {
+        let (count, asdu) = rx.recv().await.unwrap();
+        assert_eq!(count, n);
+        assert_eq!(asdu.raw, spontaneous(1, n as u16, true).raw);
+    }
+    Ok(())
+}
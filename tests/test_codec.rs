@@ -0,0 +1,97 @@
+use std::{future, time::Duration};
+
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio::{io::AsyncWriteExt, time::timeout};
+use tokio_iecp5::{
+    apci::{new_uframe, ApciKind, START_FRAME, U_TESTFR_ACTIVE, U_TESTFR_CONFIRM},
+    asdu::Asdu,
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Codec, Error, Resync,
+};
+use tokio_util::codec::Decoder;
+
+const TESTFR_ACT: [u8; 6] = [START_FRAME, 0x04, 0x43, 0x00, 0x00, 0x00];
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+fn decode_all(codec: &mut Codec, buf: &mut BytesMut) -> anyhow::Result<Vec<u8>> {
+    let mut functions = Vec::new();
+    while let Some(apdu) = codec.decode(buf)? {
+        if let ApciKind::U(u) = ApciKind::from(apdu.apci) {
+            functions.push(u.function);
+        }
+    }
+    Ok(functions)
+}
+
+#[test]
+fn strict_rejects_garbage() {
+    let mut buf = BytesMut::from(&[0x00, 0x12][..]);
+    buf.extend_from_slice(&TESTFR_ACT);
+    assert!(Codec::default().decode(&mut buf).is_err());
+
+    let mut buf = BytesMut::from(&[START_FRAME, 0xff, 0, 0, 0, 0][..]);
+    assert!(Codec::default().decode(&mut buf).is_err());
+}
+
+#[test]
+fn lenient_skips_to_next_start_byte() -> anyhow::Result<()> {
+    let mut codec = Codec::default().with_resync(Resync::Lenient);
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&[0x00, 0xff, 0x12]);
+    buf.extend_from_slice(&TESTFR_ACT);
+    // 长度错误的起始字符
+    buf.extend_from_slice(&[START_FRAME, 0xff, 0x01]);
+    buf.extend_from_slice(&TESTFR_ACT);
+    assert_eq!(
+        decode_all(&mut codec, &mut buf)?,
+        [U_TESTFR_ACTIVE, U_TESTFR_ACTIVE]
+    );
+    assert_eq!(codec.discarded(), 6);
+    assert!(buf.is_empty());
+
+    // 不完整的帧等待后续字节
+    buf.extend_from_slice(&[0x00, START_FRAME, 0x04]);
+    assert!(decode_all(&mut codec, &mut buf)?.is_empty());
+    assert_eq!(buf.len(), 2);
+    buf.extend_from_slice(&TESTFR_ACT[2..]);
+    assert_eq!(decode_all(&mut codec, &mut buf)?, [U_TESTFR_ACTIVE]);
+    assert_eq!(codec.discarded(), 7);
+    Ok(())
+}
+
+#[tokio::test]
+async fn lenient_client_survives_garbage() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false).with_resync(Resync::Lenient);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+
+    let mut framed = slave.into_inner();
+    framed.get_mut().write_all(&[0x00, 0x12, 0x34]).await?;
+    framed.send(new_uframe(U_TESTFR_ACTIVE)).await?;
+    let apdu = timeout(Duration::from_secs(1), framed.next())
+        .await?
+        .unwrap()?;
+    assert!(matches!(ApciKind::from(apdu.apci), ApciKind::U(u) if u.function == U_TESTFR_CONFIRM));
+    assert!(client.is_active());
+    assert_eq!(client.stats().discarded_bytes, 3);
+
+    drop((framed, streams));
+    client.stop().await;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use bytes::BytesMut;
+use proptest::prelude::*;
+use tokio_iecp5::{apci::START_FRAME, Codec, Resync};
+use tokio_util::codec::Decoder;
+
+const TESTFR_ACT: [u8; 6] = [START_FRAME, 0x04, 0x43, 0x00, 0x00, 0x00];
+
+// 解码 data 直到需要更多字节或出错, 返回解出的帧数
+fn decode_all(codec: &mut Codec, buf: &mut BytesMut) -> Result<usize, anyhow::Error> {
+    let mut n = 0;
+    while codec.decode(buf)?.is_some() {
+        n += 1;
+    }
+    Ok(n)
+}
+
+proptest! {
+    #[test]
+    fn strict_never_panics(data in proptest::collection::vec(any::<u8>(), 0..600)) {
+        let _ = decode_all(&mut Codec::default(), &mut BytesMut::from(&data[..]));
+    }
+
+    #[test]
+    fn lenient_never_fails(data in proptest::collection::vec(any::<u8>(), 0..600)) {
+        let mut codec = Codec::default().with_resync(Resync::Lenient);
+        let mut buf = BytesMut::from(&data[..]);
+        prop_assert!(decode_all(&mut codec, &mut buf).is_ok());
+        // 剩余的字节只能是一个不完整帧的开头
+        prop_assert!(buf.is_empty() || buf[0] == START_FRAME);
+        prop_assert!(codec.discarded() as usize + buf.len() <= data.len());
+    }
+
+    #[test]
+    fn lenient_recovers_frame_after_garbage(
+        garbage in proptest::collection::vec(any::<u8>().prop_filter("no start byte", |b| *b != START_FRAME), 0..300)
+    ) {
+        let mut codec = Codec::default().with_resync(Resync::Lenient);
+        let mut buf = BytesMut::from(&garbage[..]);
+        buf.extend_from_slice(&TESTFR_ACT);
+        prop_assert_eq!(decode_all(&mut codec, &mut buf).unwrap(), 1);
+        prop_assert_eq!(codec.discarded() as usize, garbage.len());
+        prop_assert!(buf.is_empty());
+    }
+}
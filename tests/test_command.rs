@@ -0,0 +1,359 @@
+use std::{future, time::Duration};
+
+use tokio_iecp5::{
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, DoubleCommandInfo, SingleCommandInfo},
+    mproc::{single, SinglePointInfo},
+    payload::AsduPayload,
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientHandler, ClientOption, Error,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+fn activation() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::Activation)
+}
+
+async fn start(
+    op: ClientOption,
+) -> anyhow::Result<(Client<NopHandler>, ScriptedPeer<tokio::io::DuplexStream>)> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    Ok((client, slave))
+}
+
+fn option() -> ClientOption {
+    ClientOption::default().with_command_timeout(Duration::from_millis(300))
+}
+
+#[tokio::test]
+async fn single_cmd_positive_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.single_cmd_confirmed(
+            TypeID::C_SC_NA_1,
+            activation(),
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    let result = result?;
+    assert!(result.is_positive());
+    assert_eq!(result.cause, Cause::ActivationCon);
+    assert!(!result.terminated);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn double_cmd_negative_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_DC_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(cmd.mirror_negative(Cause::ActivationCon))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.double_cmd_confirmed(
+            TypeID::C_DC_NA_1,
+            activation(),
+            1,
+            DoubleCommandInfo::new(100, 2, false),
+        ),
+        script
+    );
+    script?;
+    assert!(matches!(
+        result,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_DC_NA_1,
+            Cause::ActivationCon
+        ))
+    ));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn unrelated_asdu_is_not_a_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        // 信息对象地址, 公共地址或类型标识不同的确认都不匹配
+        let other_ioa = single_cmd(
+            TypeID::C_SC_NA_1,
+            activation(),
+            1,
+            SingleCommandInfo::new(101, true, false),
+        )?;
+        slave
+            .send_asdu(other_ioa.mirror_negative(Cause::ActivationCon))
+            .await?;
+        let mut other_ca = cmd.mirror_negative(Cause::ActivationCon);
+        other_ca.identifier.common_addr = 2;
+        slave.send_asdu(other_ca).await?;
+        let mut other_type = cmd.mirror_negative(Cause::ActivationCon);
+        other_type.identifier.type_id = TypeID::C_DC_NA_1;
+        slave.send_asdu(other_type).await?;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.single_cmd_confirmed(
+            TypeID::C_SC_NA_1,
+            activation(),
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    let result = result?;
+    assert!(result.is_positive());
+    assert_eq!(result.handle().ioa(), Some(100));
+    assert_eq!(result.handle().ca(), 1);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn command_confirm_timeout() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.single_cmd_confirmed(
+            TypeID::C_SC_NA_1,
+            activation(),
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    assert!(matches!(result, Err(Error::ErrTimeout)));
+    client.stop().await;
+    Ok(())
+}
+
+fn is_select(asdu: &mut Asdu) -> bool {
+    let mut sco = asdu.get_single_cmd().unwrap().sco;
+    sco.se().get()
+}
+
+#[tokio::test]
+async fn select_then_execute() -> anyhow::Result<()> {
+    // 等待激活终止时, 选择仍只等待激活确认
+    let (client, mut slave) = start(option().with_wait_termination(true)).await?;
+
+    let script = async {
+        let mut select = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        assert!(is_select(&mut select));
+        slave.send_asdu(select.mirror(Cause::ActivationCon)).await?;
+        let mut execute = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        assert!(!is_select(&mut execute));
+        slave
+            .send_asdu(execute.mirror(Cause::ActivationCon))
+            .await?;
+        slave
+            .send_asdu(execute.mirror(Cause::ActivationTerm))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.select_and_execute_single(
+            TypeID::C_SC_NA_1,
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    let result = result?;
+    assert!(result.is_positive());
+    assert!(result.terminated);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn select_negative_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        let select = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(select.mirror_negative(Cause::ActivationCon))
+            .await?;
+        // 选择被否定, 不发送执行命令
+        slave.expect_silence(Duration::from_millis(100)).await;
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.select_and_execute_single(
+            TypeID::C_SC_NA_1,
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    assert!(matches!(
+        result,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_SC_NA_1,
+            Cause::ActivationCon
+        ))
+    ));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn execute_timeout_cancels_select() -> anyhow::Result<()> {
+    let (client, mut slave) = start(option()).await?;
+
+    let script = async {
+        let select = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(select.mirror(Cause::ActivationCon)).await?;
+        slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        // 执行命令超时后发送选择命令的停止激活
+        let mut cancel = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Deactivation)
+            .await;
+        assert!(is_select(&mut cancel));
+        assert_eq!(cancel.get_single_cmd()?.ioa.addr().get(), 100);
+        anyhow::Ok(())
+    };
+    let (result, script) = tokio::join!(
+        client.select_and_execute_single(
+            TypeID::C_SC_NA_1,
+            1,
+            SingleCommandInfo::new(100, true, false),
+        ),
+        script
+    );
+    script?;
+    assert!(matches!(result, Err(Error::ErrTimeout)));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn commands_with_two_byte_ioa() -> anyhow::Result<()> {
+    let params = AsduParams::new(2, 2, 2)?;
+    let (connector, mut streams) = duplex_connector();
+    let op = option().with_asdu_params(params);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap()).with_asdu_params(params);
+    slave.accept_start_dt().await?;
+
+    let script = async {
+        let mut select = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        assert_eq!(select.get_single_cmd()?.ioa.addr().get(), 1000);
+        // 其他信息对象地址的确认不匹配
+        let other = single_cmd(
+            TypeID::C_SC_NA_1,
+            activation(),
+            1,
+            SingleCommandInfo::new(1001, true, true),
+        )?;
+        slave
+            .send_asdu(other.mirror_negative(Cause::ActivationCon))
+            .await?;
+        slave.send_asdu(select.mirror(Cause::ActivationCon)).await?;
+        let execute = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(execute.mirror(Cause::ActivationCon))
+            .await?;
+
+        slave
+            .expect_asdu_with(TypeID::C_RD_NA_1, Cause::Request)
+            .await;
+        let cot = CauseOfTransmission::new(false, false, Cause::Request);
+        for ioa in [1001, 1000] {
+            slave
+                .send_asdu(single(
+                    false,
+                    cot,
+                    1,
+                    vec![SinglePointInfo::new_single(ioa, true)],
+                )?)
+                .await?;
+        }
+        anyhow::Ok(())
+    };
+    let commands = async {
+        let result = client
+            .select_and_execute_single(
+                TypeID::C_SC_NA_1,
+                1,
+                SingleCommandInfo::new(1000, true, false),
+            )
+            .await?;
+        let payload = client.read(1, 1000).await?;
+        anyhow::Ok((result, payload))
+    };
+    let (commands, script) = tokio::join!(commands, script);
+    script?;
+    let (result, payload) = commands?;
+    assert!(result.is_positive());
+    assert_eq!(result.handle().ioa(), Some(1000));
+    match payload {
+        AsduPayload::SinglePoint(mut points) => assert_eq!(points[0].ioa.addr().get(), 1000),
+        payload => panic!("expect single point, got {payload:?}"),
+    }
+    client.stop().await;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::{future, time::Duration};
+
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientHandler, ClientOption, CommandHandle, Error,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+fn command(ioa: u16) -> Asdu {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    single_cmd(
+        TypeID::C_SC_NA_1,
+        cot,
+        1,
+        SingleCommandInfo::new(ioa, true, false),
+    )
+    .unwrap()
+}
+
+async fn start() -> anyhow::Result<(Client<NopHandler>, ScriptedPeer<tokio::io::DuplexStream>)> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::default()
+        .with_command_timeout(Duration::from_secs(1))
+        .with_single_outstanding_command(true);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    Ok((client, slave))
+}
+
+#[tokio::test]
+async fn commands_are_serialized_per_ca() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let first = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        let queued = client.queued_commands();
+        assert_eq!(queued.len(), 2);
+        assert!(queued[0].in_flight);
+        assert_eq!(queued[1].ioa, Some(2));
+        assert!(!queued[1].in_flight);
+
+        // 激活终止之前不发送下一个命令
+        slave.send_asdu(first.mirror(Cause::ActivationCon)).await?;
+        slave.expect_silence(Duration::from_millis(100)).await;
+        slave.send_asdu(first.mirror(Cause::ActivationTerm)).await?;
+
+        let mut second = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        assert_eq!(second.get_single_cmd()?.ioa.addr().get(), 2);
+        slave.send_asdu(second.mirror(Cause::ActivationCon)).await?;
+        slave
+            .send_asdu(second.mirror(Cause::ActivationTerm))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (first, second, script) = tokio::join!(
+        client.send_cmd_confirmed(command(1)),
+        client.send_cmd_confirmed(command(2)),
+        script
+    );
+    script?;
+    assert!(first?.terminated);
+    assert!(second?.terminated);
+    assert!(client.queued_commands().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn queued_command_can_be_cancelled() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let first = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        let queued = client.queued_commands();
+        // 已发送的命令不能取消
+        assert!(!client.cancel_queued_command(queued[0].id));
+        assert!(client.cancel_queued_command(queued[1].id));
+        slave.send_asdu(first.mirror(Cause::ActivationCon)).await?;
+        slave.send_asdu(first.mirror(Cause::ActivationTerm)).await?;
+        slave.expect_silence(Duration::from_millis(100)).await;
+        anyhow::Ok(())
+    };
+    let (first, second, script) = tokio::join!(
+        client.send_cmd_confirmed(command(1)),
+        client.send_cmd_confirmed(command(2)),
+        script
+    );
+    script?;
+    assert!(first.is_ok());
+    assert!(matches!(second, Err(Error::ErrCommandCancelled)));
+    assert_eq!(client.cancel_queued_commands(1), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn in_flight_command_can_be_deactivated() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+    let handle = CommandHandle::of(&command(1));
+    assert_eq!((handle.ca(), handle.ioa()), (1, Some(1)));
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        // 停止激活不排在执行中的命令之后
+        let reply = async {
+            let deact = slave
+                .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Deactivation)
+                .await;
+            slave.send_asdu(deact.mirror(Cause::DeactivationCon)).await
+        };
+        let (cancel, reply) = tokio::join!(client.cancel_command(&handle), reply);
+        reply?;
+        anyhow::Ok(cancel?)
+    };
+    let (result, cancel) = tokio::join!(client.send_cmd_confirmed(command(1)), script);
+    assert_eq!(cancel?.cause, Cause::DeactivationCon);
+    assert_eq!(result?.cause, Cause::DeactivationCon);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use tokio_iecp5::{
+    asdu::{Cause, TypeID},
+    config::{Signal, SignalList, SignalType},
+    AsduBuilder, DataStore, Point, PointCache, PointValue, Quality, ScaleTable,
+};
+
+const SIGNALS: &str = "\
+# 站 1 信号表
+name,ca,ioa,type,scale_min,scale_max,group
+feeder1.breaker,1,1001,double,,,1
+\"feeder1, current\",1,4001,normalized,0,600,2
+feeder1.energy,1,7001,counter,,,1
+";
+
+#[test]
+fn signal_list_from_csv() -> anyhow::Result<()> {
+    let list = SignalList::from_csv(SIGNALS)?;
+    assert_eq!(list.len(), 3);
+    let current = list.get(1, 4001).unwrap();
+    assert_eq!(current.name, "feeder1, current");
+    assert_eq!(current.kind, SignalType::Normalized);
+    assert_eq!(current.scale, Some(ScaleTable::new(0.0, 600.0)));
+    assert_eq!(current.group, 2);
+    assert_eq!(list.by_name("feeder1.energy").unwrap().ioa, 7001);
+    assert!(list.get(1, 1).is_none());
+    Ok(())
+}
+
+#[test]
+fn signal_list_validation() {
+    // 重复的地址与名称
+    assert!(SignalList::from_csv("a,1,1,single\nb,1,1,double").is_err());
+    assert!(SignalList::from_csv("a,1,1,single\na,1,2,double").is_err());
+    // 换算只用于规一化值, 召唤组超出类型的范围
+    assert!(SignalList::from_csv("a,1,1,float,0,100").is_err());
+    assert!(SignalList::from_csv("a,1,1,counter,,,5").is_err());
+    assert!(SignalList::from_csv("a,1,1,single,,,17").is_err());
+    // 格式错误
+    assert!(SignalList::from_csv("a,1,1,analog").is_err());
+    assert!(SignalList::from_csv("a,1,x,single").is_err());
+    assert!(SignalList::from_csv("a,1,1,normalized,0").is_err());
+
+    assert!(SignalList::new(vec![
+        Signal::new("a", 1, 1, SignalType::Normalized).with_scale(ScaleTable::new(0.0, 10.0)),
+        Signal::new("b", 2, 1, SignalType::Counter).with_group(4),
+    ])
+    .is_ok());
+}
+
+#[test]
+fn signal_list_apply_to_store() -> anyhow::Result<()> {
+    let list = SignalList::from_csv(SIGNALS)?;
+    let store = DataStore::new();
+    store.insert(1, 1001, Point::new(PointValue::Double(2)));
+    list.apply_to_store(&store)?;
+
+    assert_eq!(store.len(), 3);
+    // 已有的点保留当前值
+    assert_eq!(store.get(1, 1001).unwrap().value, PointValue::Double(2));
+    assert_eq!(store.get(1, 1001).unwrap().group, 1);
+    assert_eq!(store.get(1, 7001).unwrap().value, PointValue::Counter(0));
+    store.set_engineering(1, 4001, 300.0)?;
+    assert_eq!(store.get_engineering(1, 4001), Some(300.0));
+
+    // 已有点的类型不符
+    let store = DataStore::new();
+    store.insert(1, 4001, Point::new(PointValue::Float(0.0)));
+    assert!(list.apply_to_store(&store).is_err());
+    assert_eq!(store.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn signal_list_cache_and_check() -> anyhow::Result<()> {
+    let list = SignalList::from_csv(SIGNALS)?;
+    let cache = PointCache::new();
+    list.apply_to_cache(&cache);
+
+    let asdu = AsduBuilder::new(TypeID::M_ME_NA_1)
+        .cot(Cause::Spontaneous)
+        .ca(1)
+        .add(4001, PointValue::Normalized(0), Quality::default())
+        .build()?;
+    list.check(&asdu)?;
+    cache.apply(&asdu)?;
+    assert_eq!(cache.get_engineering(1, 4001), Some(300.0));
+
+    // 信号表中为双点信息
+    let asdu = AsduBuilder::new(TypeID::M_SP_NA_1)
+        .cot(Cause::Spontaneous)
+        .ca(1)
+        .add(1001, PointValue::Single(true), Quality::default())
+        .add(1002, PointValue::Single(true), Quality::default())
+        .build()?;
+    assert!(list.check(&asdu).is_err());
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::{future, io, net::SocketAddr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr, GLOBAL_COMMON_ADDR},
+    conformance::{Case, Conformance, Verdict},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    mproc::{single, SinglePointInfo},
+    Error, Server, ServerHandler, TcpConnector,
+};
+
+const STATION_CA: u16 = 1;
+
+// 总召唤返回 points 个单点信息, 每个信息对象一个 ASDU
+#[derive(Clone, Copy)]
+struct Station {
+    points: u16,
+}
+
+impl ServerHandler for Station {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, _qoi: ObjectQOI) -> Self::Future {
+        let mut con = asdu.mirror(Cause::ActivationCon);
+        if con.identifier.common_addr == GLOBAL_COMMON_ADDR {
+            con.identifier.common_addr = STATION_CA;
+        }
+        let mut asdus = vec![con];
+        for ioa in 0..self.points {
+            let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+            match single(
+                false,
+                cot,
+                STATION_CA,
+                vec![SinglePointInfo::new_single(100 + ioa, true)],
+            ) {
+                Ok(asdu) => asdus.push(asdu),
+                Err(e) => return future::ready(Err(e)),
+            }
+        }
+        future::ready(Ok(asdus))
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+async fn start_station(station: Station) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let server = Server::new(listener);
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((station, stream)))
+        };
+        let _ = server.serve(&on_connected, |_err| {}).await;
+    });
+    Ok(addr)
+}
+
+#[tokio::test]
+async fn conformance_server_passes() -> anyhow::Result<()> {
+    let addr = start_station(Station { points: 1 }).await?;
+    let report = Conformance::new(TcpConnector::new(addr))
+        .with_common_addr(STATION_CA)
+        .with_response_timeout(Duration::from_millis(300))
+        .with_cases(&[
+            Case::StartDtBeforeData,
+            Case::SequenceError,
+            Case::AckError,
+            Case::BroadcastCa,
+        ])
+        .run()
+        .await;
+
+    assert!(report.is_pass(), "{report}");
+    assert_eq!(report.cases.len(), 4);
+    assert!(report
+        .to_string()
+        .ends_with("4 passed, 0 failed, 0 inconclusive"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn conformance_max_unacknowledged_needs_data() -> anyhow::Result<()> {
+    let addr = start_station(Station { points: 3 }).await?;
+    let report = Conformance::new(TcpConnector::new(addr))
+        .with_k(12)
+        .with_response_timeout(Duration::from_millis(300))
+        .with_cases(&[Case::MaxUnacknowledged])
+        .run()
+        .await;
+
+    // 确认与 3 个数据, 少于 k 个时无法判断
+    assert!(matches!(
+        report.verdict(Case::MaxUnacknowledged),
+        Some(Verdict::Inconclusive(_))
+    ));
+    assert!(report.is_pass());
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use bit_struct::*;
+use bytes::Bytes;
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::cpara::*;
+
+#[test]
+fn encode_and_decode_parameter_normal() -> Result<()> {
+    let qpm = ObjectQPM::new(false, false, u6!(1));
+    let asdu = parameter_normal(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        ParameterNormalInfo::new(0x0102, 0x1234, qpm),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::P_ME_NA_1);
+
+    let raw: Bytes = asdu.clone().try_into()?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            u8::from(TypeID::P_ME_NA_1),
+            0x01,
+            0x06,
+            0x00,
+            0x01,
+            0x00,
+            0x02,
+            0x01,
+            0x00,
+            0x34,
+            0x12,
+            0x01,
+        ])
+    );
+
+    let mut asdu: Asdu = raw.try_into()?;
+    let p = asdu.get_parameter_normal()?;
+    assert_eq!(p, ParameterNormalInfo::new(0x0102, 0x1234, qpm));
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_parameter_scaled() -> Result<()> {
+    let qpm = ObjectQPM::new(false, true, u6!(2));
+    let mut asdu = parameter_scaled(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        ParameterScaledInfo::new(0x0003, -100, qpm),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::P_ME_NB_1);
+    assert_eq!(
+        asdu.get_parameter_scaled()?,
+        ParameterScaledInfo::new(0x0003, -100, qpm)
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_parameter_float() -> Result<()> {
+    let qpm = ObjectQPM::new(true, false, u6!(4));
+    let mut asdu = parameter_float(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        ParameterFloatInfo::new(0x0004, 12.5, qpm),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::P_ME_NC_1);
+    assert_eq!(
+        asdu.get_parameter_float()?,
+        ParameterFloatInfo::new(0x0004, 12.5, qpm)
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_parameter_activation() -> Result<()> {
+    let qpa = ObjectQPA::new(2);
+    let mut asdu = parameter_activation(
+        CauseOfTransmission::new(false, false, Cause::Deactivation),
+        0x0001,
+        ParameterActivationInfo::new(0x0005, qpa),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::P_AC_NA_1);
+    assert_eq!(
+        asdu.get_parameter_activation()?,
+        ParameterActivationInfo::new(0x0005, qpa)
+    );
+    Ok(())
+}
+
+#[test]
+fn parameter_invalid_cause() {
+    let r = parameter_normal(
+        CauseOfTransmission::new(false, false, Cause::Deactivation),
+        0x0001,
+        ParameterNormalInfo::new(0x0001, 0, ObjectQPM::new(false, false, u6!(1))),
+    );
+    assert!(r.is_err());
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use bit_struct::*;
+use bytes::Bytes;
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::cproc::*;
+use tokio_iecp5::Error;
+
+#[test]
+fn encode_single_cmd_select() -> Result<()> {
+    let asdu = single_cmd(
+        TypeID::C_SC_NA_1,
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        SingleCommandInfo::new(0x0001, true, true),
+    )?;
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            u8::from(TypeID::C_SC_NA_1),
+            0x01,
+            0x06,
+            0x00,
+            0x01,
+            0x00,
+            0x01,
+            0x00,
+            0x00,
+            0x81,
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_double_cmd() -> Result<()> {
+    let asdu = double_cmd(
+        TypeID::C_DC_NA_1,
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        DoubleCommandInfo::new(0x0002, 2, true),
+    )?;
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(raw[raw.len() - 1], 0x82);
+
+    let mut asdu: Asdu = raw.try_into()?;
+    let mut cmd = asdu.get_double_cmd()?;
+    assert!(cmd.dco.se().get());
+    assert_eq!(cmd.dco.dcs().get().value(), 2);
+    Ok(())
+}
+
+#[test]
+fn double_cmd_rejects_not_permitted_state() -> Result<()> {
+    assert!(matches!(
+        DoubleCommandInfo::try_new(0x0002, 0, false),
+        Err(Error::ErrInvalidDcs(0))
+    ));
+    assert!(matches!(
+        DoubleCommandInfo::try_new(0x0002, 3, false),
+        Err(Error::ErrInvalidDcs(3))
+    ));
+    assert!(DoubleCommandInfo::try_new(0x0002, 1, false).is_ok());
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let err = double_cmd(
+        TypeID::C_DC_NA_1,
+        cot,
+        0x0001,
+        DoubleCommandInfo::new(0x0002, 3, false),
+    );
+    assert!(matches!(err, Err(Error::ErrInvalidDcs(3))));
+    Ok(())
+}
+
+#[test]
+fn encode_setpoint_select() -> Result<()> {
+    let mut cmd = SetpointCommandNormalInfo::new(0x0003, 0x0100);
+    cmd.qos.se().set(u1!(1));
+    let asdu = set_point_cmd_normal(
+        TypeID::C_SE_NA_1,
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        cmd,
+    )?;
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(raw[raw.len() - 1], 0x80);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use chrono::{Duration, TimeZone, Utc};
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::csys::*;
+use tokio_iecp5::time::Cp56Time2a;
+
+#[test]
+fn encode_and_decode_clock_synchronization() -> Result<()> {
+    let time = Utc.with_ymd_and_hms(2024, 3, 4, 5, 6, 7).unwrap();
+    let mut asdu = clock_synchronization_cmd(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        time,
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_CS_NA_1);
+
+    let (mut ioa, t) = asdu.get_clock_synchronization_cmd()?;
+    assert_eq!(ioa.addr().get(), INFO_OBJ_ADDR_IRRELEVANT);
+    assert_eq!(t, Some(time));
+
+    let mut con = asdu.mirror(Cause::ActivationCon);
+    assert_eq!(con.identifier.cot.cause().get(), Cause::ActivationCon);
+    Ok(())
+}
+
+#[test]
+fn clock_synchronization_keeps_time_quality() -> Result<()> {
+    let time = Utc.with_ymd_and_hms(2024, 7, 4, 5, 6, 7).unwrap() + Duration::milliseconds(250);
+    let cp56 = Cp56Time2a::new(time)
+        .with_invalid(true)
+        .with_summer_time(true);
+    let encoded = cp56.encode();
+    // 毫秒(2字节), 分钟(IV), 小时(SU), 星期与日, 月, 年
+    assert_eq!(&encoded[..], &[0x52, 0x1c, 0x86, 0x85, 0x84, 0x07, 0x18]);
+
+    let mut asdu = clock_synchronization_cmd_cp56(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        cp56,
+    )?;
+    let (_, t) = asdu.get_clock_synchronization_time()?;
+    assert_eq!(t, Some(cp56));
+    // 时标无效时不返回时间
+    assert_eq!(asdu.get_clock_synchronization_cmd()?.1, None);
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_read_cmd() -> Result<()> {
+    let mut asdu = read_cmd(
+        CauseOfTransmission::new(false, false, Cause::Request),
+        0x0001,
+        InfoObjAddr::new(0, 0x0102),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_RD_NA_1);
+
+    let mut ioa = asdu.get_read_cmd()?;
+    assert_eq!(ioa.addr().get(), 0x0102);
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_delay_acquire() -> Result<()> {
+    let mut asdu = delay_acquire_command(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        1500,
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_CD_NA_1);
+
+    let (_, msec) = asdu.get_delay_acquire_cmd()?;
+    assert_eq!(msec, 1500);
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_test_command() -> Result<()> {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let mut asdu = test_command(cot, 0x0001)?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_TS_NA_1);
+    let (mut ioa, word) = asdu.get_test_cmd()?;
+    assert_eq!(ioa.addr().get(), INFO_OBJ_ADDR_IRRELEVANT);
+    assert_eq!(word, 0x55aa);
+
+    let time = Utc.with_ymd_and_hms(2024, 3, 4, 5, 6, 7).unwrap();
+    let mut asdu = test_command_cp56time2a(cot, 0x0001, time)?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_TS_TA_1);
+    let (_, word, t) = asdu.get_test_cmd_cp56time2a()?;
+    assert_eq!(word, 0x55aa);
+    assert_eq!(t, Some(Cp56Time2a::new(time)));
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_reset_process() -> Result<()> {
+    let mut asdu = reset_process_cmd(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        Qrp::ResetProcess,
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_RP_NA_1);
+
+    let (_, mut qrp) = asdu.get_reset_process_cmd()?;
+    assert_eq!(qrp.qrp().get(), 1);
+    assert_eq!(Qrp::from(qrp), Qrp::ResetProcess);
+    Ok(())
+}
+
+#[test]
+fn qualifier_enums_round_trip() {
+    assert_eq!(Qoi::from(20u8), Qoi::StationInterrogation);
+    assert_eq!(Qoi::from(21u8), Qoi::Group(1));
+    assert_eq!(Qoi::from(36u8), Qoi::Group(16));
+    assert_eq!(Qoi::from(37u8), Qoi::Reserved(37));
+    assert_eq!(u8::from(Qoi::Group(3)), 23);
+    let mut qoi = ObjectQOI::from(Qoi::StationInterrogation);
+    assert_eq!(qoi.range().get(), 20);
+
+    let qcc = Qcc::new(CounterGroup::Group(2), FreezeMode::FreezeWithReset);
+    assert_eq!(u8::from(qcc), 0x82);
+    assert_eq!(Qcc::from(0x82u8), qcc);
+    assert_eq!(Qcc::from(ObjectQCC::from(Qcc::general())), Qcc::general());
+    assert_eq!(u8::from(Qcc::general()), 5);
+    assert_eq!(Qcc::from(0xc0u8).request, CounterGroup::Reserved(0));
+    assert_eq!(Qcc::from(0xc0u8).freeze, FreezeMode::Reset);
+
+    assert_eq!(Qrp::from(2u8), Qrp::ResetEventBuffer);
+    assert_eq!(u8::from(Qrp::Reserved(7)), 7);
+}
+
+#[test]
+fn interrogation_cmd_with_typed_qualifier() -> Result<()> {
+    let act = CauseOfTransmission::new(false, false, Cause::Activation);
+    let mut asdu = interrogation_cmd(act, 0x0001, Qoi::Group(2))?;
+    let (_, qoi) = asdu.get_interrogation_cmd()?;
+    assert_eq!(Qoi::from(qoi), Qoi::Group(2));
+
+    let mut asdu = counter_interrogation_cmd(act, 0x0001, Qcc::general())?;
+    let (_, qcc) = asdu.get_counter_interrogation_cmd()?;
+    assert_eq!(Qcc::from(qcc), Qcc::general());
+    Ok(())
+}
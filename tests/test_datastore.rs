@@ -0,0 +1,276 @@
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    csys::{
+        counter_interrogation_cmd, interrogation_cmd, CounterGroup, FreezeMode, ObjectQCC,
+        ObjectQOI, Qcc,
+    },
+    mproc::ObjectQDS,
+    test_util::{assert_asdu, serve_in_memory},
+    DataStore, Point, PointValue,
+};
+
+fn store() -> DataStore {
+    let store = DataStore::new();
+    store.insert(1, 1, Point::new(PointValue::Single(true)).with_group(1));
+    store.insert(1, 2, Point::new(PointValue::Single(false)).with_group(2));
+    store.insert(1, 10, Point::new(PointValue::Float(1.5)).with_group(1));
+    store.insert(1, 20, Point::new(PointValue::Counter(42)).with_group(1));
+    store.insert(2, 1, Point::new(PointValue::Double(2)));
+    store
+}
+
+#[test]
+fn datastore_interrogation() -> anyhow::Result<()> {
+    let store = store();
+    assert_eq!(store.len(), 5);
+    assert_eq!(store.common_addrs(), vec![1, 2]);
+
+    let mut asdus = store.interrogation(1, ObjectQOI::new(20))?;
+    assert_eq!(asdus.len(), 2);
+    assert_asdu(&asdus[0], TypeID::M_SP_NA_1, Cause::InterrogatedByStation);
+    assert_eq!(asdus[0].get_single_point()?.len(), 2);
+    assert_asdu(&asdus[1], TypeID::M_ME_NC_1, Cause::InterrogatedByStation);
+    assert_eq!(asdus[1].get_measured_value_float()?[0].r, 1.5);
+
+    let mut asdus = store.interrogation(1, ObjectQOI::new(21))?;
+    assert_eq!(asdus.len(), 2);
+    assert_asdu(&asdus[0], TypeID::M_SP_NA_1, Cause::InterrogatedByGroup1);
+    let mut points = asdus[0].get_single_point()?;
+    assert_eq!(points.len(), 1);
+    assert!(points[0].siq.spi().get());
+
+    assert!(store.interrogation(1, ObjectQOI::new(36))?.is_empty());
+    assert!(store.interrogation(3, ObjectQOI::new(20))?.is_empty());
+
+    let mut asdus = store.counter_interrogation(1, ObjectQCC::new(5))?;
+    assert_eq!(asdus.len(), 1);
+    assert_asdu(&asdus[0], TypeID::M_IT_NA_1, Cause::RequestByGeneralCounter);
+    assert_eq!(asdus[0].get_integrated_totals()?[0].bcr.value, 42);
+    let asdus = store.counter_interrogation(1, ObjectQCC::new(1))?;
+    assert_asdu(&asdus[0], TypeID::M_IT_NA_1, Cause::RequestByGroup1Counter);
+    assert!(store
+        .counter_interrogation(1, ObjectQCC::new(2))?
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn datastore_change_detection() -> anyhow::Result<()> {
+    let store = store();
+    assert!(store
+        .update(
+            1,
+            1,
+            PointValue::Single(true),
+            ObjectQDS::of_defaults(),
+            None
+        )?
+        .is_empty());
+
+    let mut asdu = store.set(1, 1, PointValue::Single(false))?.remove(0);
+    assert_asdu(&asdu, TypeID::M_SP_TB_1, Cause::Spontaneous);
+    let mut points = asdu.get_single_point()?;
+    assert_eq!(points[0].ioa.addr().get(), 1);
+    assert!(!points[0].siq.spi().get());
+    assert!(points[0].time.is_some());
+    // 召唤组不变
+    assert_eq!(store.get(1, 1).unwrap().group, 1);
+
+    // 品质变化
+    let mut invalid = ObjectQDS::of_defaults();
+    invalid.invalid().set(true);
+    let mut asdu = store
+        .update(1, 10, PointValue::Float(1.5), invalid, None)?
+        .remove(0);
+    assert_asdu(&asdu, TypeID::M_ME_NC_1, Cause::Spontaneous);
+    assert!(asdu.get_measured_value_float()?[0].qds.invalid().get());
+
+    // 新的点
+    assert_eq!(store.set(1, 30, PointValue::Scaled(-7))?.len(), 1);
+    let mut asdu = store.read(1, 30)?.unwrap();
+    assert_asdu(&asdu, TypeID::M_ME_NB_1, Cause::Request);
+    assert_eq!(asdu.get_measured_value_scaled()?[0].sva, -7);
+    assert!(store.read(1, 31)?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn datastore_shared_by_sessions() -> anyhow::Result<()> {
+    let store = store();
+    let mut first = serve_in_memory(store.clone());
+    let mut second = serve_in_memory(store.clone());
+    store.set(1, 2, PointValue::Single(true))?;
+
+    for master in [&mut first, &mut second] {
+        master.start_dt().await?;
+        let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+        master
+            .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(22))?)
+            .await?;
+        master
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+            .await;
+        let mut data = master
+            .expect_asdu_with(TypeID::M_SP_NA_1, Cause::InterrogatedByGroup2)
+            .await;
+        assert!(data.get_single_point()?[0].siq.spi().get());
+        master
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationTerm)
+            .await;
+    }
+    Ok(())
+}
+
+#[test]
+fn datastore_set_group() -> anyhow::Result<()> {
+    let store = store();
+    store.set_group(1, 2, 16)?;
+    assert!(store.set_group(1, 2, 17).is_err());
+    store.set_group(1, 20, 4)?;
+    assert!(store.set_group(1, 20, 5).is_err());
+    assert!(store.set_group(1, 99, 1).is_err());
+
+    assert_eq!(store.group_members(1, 1), vec![1, 10]);
+    assert_eq!(store.group_members(1, 16), vec![2]);
+    assert_eq!(store.group_members(1, 4), vec![20]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn datastore_group_interrogation() -> anyhow::Result<()> {
+    let store = store();
+    store.set_group(1, 20, 2)?;
+    let mut master = serve_in_memory(store);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+
+    // 第1组召唤
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(21))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+    master
+        .expect_asdu_with(TypeID::M_SP_NA_1, Cause::InterrogatedByGroup1)
+        .await;
+    master
+        .expect_asdu_with(TypeID::M_ME_NC_1, Cause::InterrogatedByGroup1)
+        .await;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationTerm)
+        .await;
+
+    // 第2组计数量召唤
+    master
+        .send_asdu(counter_interrogation_cmd(cot, 1, ObjectQCC::new(2))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_CI_NA_1, Cause::ActivationCon)
+        .await;
+    let mut asdu = master
+        .expect_asdu_with(TypeID::M_IT_NA_1, Cause::RequestByGroup2Counter)
+        .await;
+    assert_eq!(asdu.get_integrated_totals()?[0].bcr.value, 42);
+    master
+        .expect_asdu_with(TypeID::C_CI_NA_1, Cause::ActivationTerm)
+        .await;
+
+    // 保留的 QOI
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(40))?)
+        .await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[test]
+fn datastore_double_transmission() -> anyhow::Result<()> {
+    let store = store().with_double_transmission(true);
+    let mut asdus = store.set(1, 1, PointValue::Single(false))?;
+    assert_eq!(asdus.len(), 2);
+    assert_asdu(&asdus[0], TypeID::M_SP_NA_1, Cause::Spontaneous);
+    assert_asdu(&asdus[1], TypeID::M_SP_TB_1, Cause::Spontaneous);
+    assert!(asdus[0].get_single_point()?[0].time.is_none());
+    assert!(asdus[1].get_single_point()?[0].time.is_some());
+
+    // 不带时标的变化只上送一次
+    let asdus = store.update(
+        1,
+        10,
+        PointValue::Float(2.5),
+        ObjectQDS::of_defaults(),
+        None,
+    )?;
+    assert_eq!(asdus.len(), 1);
+    assert_asdu(&asdus[0], TypeID::M_ME_NC_1, Cause::Spontaneous);
+    Ok(())
+}
+
+// 第一个累计量的值, 顺序号与 CA
+fn first_bcr(asdu: &mut Asdu) -> anyhow::Result<(i32, u8, bool)> {
+    let totals = asdu.get_integrated_totals()?;
+    let bcr = &totals[0].bcr;
+    Ok((bcr.value, bcr.seq, bcr.ca))
+}
+
+#[test]
+fn datastore_counter_freeze() -> anyhow::Result<()> {
+    let store = store();
+    let general = |freeze| Qcc::new(CounterGroup::General, freeze);
+
+    // 冻结不带复位: 冻结值以突发传送原因上送, 顺序号加 1
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::Freeze))?;
+    assert_asdu(&asdus[0], TypeID::M_IT_NA_1, Cause::Spontaneous);
+    assert_eq!(first_bcr(&mut asdus[0])?, (42, 1, false));
+
+    // 冻结之后的变化不影响读到的冻结值
+    store.set(1, 20, PointValue::Counter(50))?;
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::Read))?;
+    assert_asdu(&asdus[0], TypeID::M_IT_NA_1, Cause::RequestByGeneralCounter);
+    assert_eq!(first_bcr(&mut asdus[0])?, (42, 1, false));
+
+    // 冻结带复位: 冻结后当前值清零
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::FreezeWithReset))?;
+    assert_eq!(first_bcr(&mut asdus[0])?, (50, 2, false));
+    assert_eq!(store.get(1, 20).unwrap().value, PointValue::Counter(0));
+
+    // 复位: 只清零, 下一次冻结值带 CA
+    store.set(1, 20, PointValue::Counter(7))?;
+    assert!(store
+        .counter_interrogation(1, general(FreezeMode::Reset))?
+        .is_empty());
+    assert_eq!(store.get(1, 20).unwrap().value, PointValue::Counter(0));
+    store.set(1, 20, PointValue::Counter(3))?;
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::Freeze))?;
+    assert_eq!(first_bcr(&mut asdus[0])?, (3, 3, true));
+
+    // 顺序号 0~31 循环
+    for _ in 0..29 {
+        store.freeze_counters(1, Some(1), FreezeMode::Freeze);
+    }
+    let mut asdus =
+        store.counter_interrogation(1, Qcc::new(CounterGroup::Group(1), FreezeMode::Read))?;
+    assert_eq!(asdus[0].get_integrated_totals()?[0].bcr.seq, 0);
+    Ok(())
+}
+
+#[test]
+fn datastore_counter_reset_before_freeze() -> anyhow::Result<()> {
+    let store = store();
+    let general = |freeze| Qcc::new(CounterGroup::General, freeze);
+
+    // 未冻结过的累计量复位后仍以当前值响应, 不产生冻结值
+    store.counter_interrogation(1, general(FreezeMode::Reset))?;
+    store.set(1, 20, PointValue::Counter(5))?;
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::Read))?;
+    assert_eq!(first_bcr(&mut asdus[0])?, (5, 0, false));
+
+    // 第一次冻结值带 CA
+    let mut asdus = store.counter_interrogation(1, general(FreezeMode::Freeze))?;
+    assert_eq!(first_bcr(&mut asdus[0])?, (5, 1, true));
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use bytes::Bytes;
+use proptest::prelude::*;
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::Error;
+
+// 以给定类型标识与可变结构限定词构造 ASDU, 其余为信息对象
+fn asdu(type_id: u8, vsq: u8, raw: &[u8]) -> Option<Asdu> {
+    let mut buf = vec![type_id, vsq, 0x06, 0x00, 0x01, 0x00];
+    buf.extend_from_slice(raw);
+    Asdu::try_from(Bytes::from(buf)).ok()
+}
+
+#[test]
+fn get_with_mismatched_type_id_is_error() {
+    let mut a = asdu(1, 0x01, &[0x01, 0x00, 0x00, 0x01]).unwrap();
+    let err = a.get_single_cmd().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<Error>(),
+        Some(Error::ErrTypeIDNotMatch(TypeID::M_SP_NA_1))
+    ));
+
+    let mut a = asdu(45, 0x01, &[0x01, 0x00, 0x00, 0x01]).unwrap();
+    assert!(matches!(
+        a.get_single_point(),
+        Err(Error::ErrTypeIDNotMatch(TypeID::C_SC_NA_1))
+    ));
+}
+
+#[test]
+fn get_with_truncated_payload_is_error() {
+    let mut a = asdu(1, 0x02, &[0x01, 0x00, 0x00, 0x01, 0x02]).unwrap();
+    assert!(a.get_single_point().is_err());
+
+    let mut a = asdu(45, 0x01, &[0x01, 0x00]).unwrap();
+    assert!(a.get_single_cmd().is_err());
+}
+
+#[test]
+fn sequence_past_max_address_is_error() {
+    // SQ=1, 起始地址 65535, 第二个信息对象的地址溢出
+    let mut a = asdu(1, 0x82, &[0xff, 0xff, 0x00, 0x01, 0x00]).unwrap();
+    assert!(matches!(
+        a.get_single_point(),
+        Err(Error::ErrIoaNotSequential(0xffff))
+    ));
+}
+
+proptest! {
+    #[test]
+    fn decode_never_panics(data in proptest::collection::vec(any::<u8>(), 0..260)) {
+        if let Ok(mut a) = Asdu::try_from(Bytes::from(data)) {
+            let _ = a.decode_payload();
+            let _ = a.to_string();
+        }
+    }
+
+    #[test]
+    fn getters_never_panic(
+        type_id in 1..=127u8,
+        vsq in any::<u8>(),
+        raw in proptest::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let Some(mut a) = asdu(type_id, vsq, &raw) else {
+            return Ok(());
+        };
+        let _ = a.get_single_point();
+        let _ = a.get_double_point();
+        let _ = a.get_measured_value_normal();
+        let _ = a.get_measured_value_scaled();
+        let _ = a.get_measured_value_float();
+        let _ = a.get_integrated_totals();
+        let _ = a.get_packed_single_point();
+        let _ = a.get_single_cmd();
+        let _ = a.get_double_cmd();
+        let _ = a.get_setpoint_normal_cmd();
+        let _ = a.get_setpoint_scaled_cmd();
+        let _ = a.get_setpoint_float_cmd();
+        let _ = a.get_bits_string32_cmd();
+        let _ = a.get_interrogation_cmd();
+        let _ = a.get_counter_interrogation_cmd();
+        let _ = a.get_read_cmd();
+        let _ = a.get_clock_synchronization_cmd();
+        let _ = a.get_reset_process_cmd();
+        let _ = a.get_parameter_normal();
+        let _ = a.get_parameter_float();
+        let _ = a.get_file_ready();
+        let _ = a.get_segment();
+        let _ = a.get_directory();
+        let _ = a.get_authentication_challenge();
+        let _ = a.get_security_statistic();
+    }
+}
@@ -0,0 +1,102 @@
+use bit_struct::*;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr, TypeID, VariableStruct},
+    cproc::{single_cmd, ObjectSCO, SingleCommandInfo},
+    csys::{interrogation_cmd, ObjectQOI},
+    mproc::ObjectQDS,
+    AsduBuilder, PointValue, Quality,
+};
+
+#[test]
+fn describe_measured_float() -> anyhow::Result<()> {
+    let time = Utc
+        .with_ymd_and_hms(2024, 2, 1, 8, 0, 0)
+        .unwrap()
+        .checked_add_signed(chrono::Duration::milliseconds(250))
+        .unwrap();
+    let asdu = AsduBuilder::new(TypeID::M_ME_TF_1)
+        .cot(Cause::Spontaneous)
+        .ca(1)
+        .add_timestamped(
+            4005,
+            PointValue::Float(12.5),
+            ObjectQDS::of_defaults(),
+            time,
+        )
+        .build()?;
+    assert_eq!(
+        asdu.describe(),
+        "M_ME_TF_1 COT=spont CA=1 IOA=4005 val=12.5 QDS=GOOD t=2024-02-01T08:00:00.250Z"
+    );
+    // Display 输出同样的文本, {:#} 输出原始字节
+    assert_eq!(asdu.to_string(), asdu.describe());
+    assert!(format!("{asdu:#}").starts_with("[24][01][03][00][01][00]"));
+    Ok(())
+}
+
+#[test]
+fn describe_several_objects_and_quality() -> anyhow::Result<()> {
+    let invalid = Quality {
+        invalid: true,
+        nt: true,
+        ..Default::default()
+    };
+    let asdu = AsduBuilder::new(TypeID::M_SP_NA_1)
+        .cot(Cause::InterrogatedByStation)
+        .ca(2)
+        .add(1, PointValue::Single(true), Quality::default())
+        .add(2, PointValue::Single(false), invalid)
+        .build()?;
+    assert_eq!(
+        asdu.describe(),
+        "M_SP_NA_1 COT=inrogen CA=2 IOA=1 val=on SIQ=GOOD; IOA=2 val=off SIQ=IV|NT"
+    );
+    Ok(())
+}
+
+#[test]
+fn describe_commands() -> anyhow::Result<()> {
+    let mut cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let asdu = single_cmd(
+        TypeID::C_SC_NA_1,
+        cot,
+        1,
+        SingleCommandInfo {
+            ioa: InfoObjAddr::new(0, 6001),
+            sco: ObjectSCO::new(true, u5!(0), u1!(0), true),
+            time: None,
+        },
+    )?;
+    assert_eq!(
+        asdu.describe(),
+        "C_SC_NA_1 COT=act CA=1 IOA=6001 val=on select QU=0"
+    );
+
+    cot.cause().set(Cause::ActivationCon);
+    cot.set_negative(true);
+    let mut asdu = interrogation_cmd(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        1,
+        ObjectQOI::new(20),
+    )?;
+    asdu.identifier.cot = cot;
+    assert_eq!(asdu.describe(), "C_IC_NA_1 COT=actcon neg CA=1 QOI=20");
+    Ok(())
+}
+
+#[test]
+fn describe_undecodable_payload() {
+    let asdu = Asdu {
+        identifier: Identifier {
+            type_id: TypeID::M_ME_NC_1,
+            variable_struct: VariableStruct::new(u1!(0), u7!(1)),
+            cot: CauseOfTransmission::new(false, false, Cause::Spontaneous),
+            orig_addr: 0,
+            common_addr: 1,
+        },
+        raw: Bytes::from_static(&[0x01, 0x02]),
+    };
+    assert_eq!(asdu.describe(), "M_ME_NC_1 COT=spont CA=1 raw=0102");
+}
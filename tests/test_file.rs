@@ -0,0 +1,183 @@
+use anyhow::Result;
+use bit_struct::*;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::file::*;
+
+fn file_transfer_cot() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::FileTransfer)
+}
+
+#[test]
+fn file_transfer_cause_value() {
+    assert_eq!(file_transfer_cot().raw(), 13);
+}
+
+#[test]
+fn encode_and_decode_file_call() -> Result<()> {
+    let asdu = file_call(
+        file_transfer_cot(),
+        0x0001,
+        FileCallInfo::new(0x0102, 0x0003, 0x01, SCQ_REQUEST_SECTION),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_SC_NA_1);
+
+    let raw: Bytes = asdu.clone().try_into()?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            u8::from(TypeID::F_SC_NA_1),
+            0x01,
+            0x0d,
+            0x00,
+            0x01,
+            0x00,
+            0x02,
+            0x01,
+            0x00,
+            0x03,
+            0x00,
+            0x01,
+            0x06,
+        ])
+    );
+
+    let mut asdu: Asdu = raw.try_into()?;
+    let mut info = asdu.get_file_call()?;
+    assert_eq!(info.scq.scq().get().value(), SCQ_REQUEST_SECTION);
+    assert_eq!(
+        info,
+        FileCallInfo::new(0x0102, 0x0003, 0x01, SCQ_REQUEST_SECTION)
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_file_ready() -> Result<()> {
+    let mut asdu = file_ready(
+        file_transfer_cot(),
+        0x0001,
+        FileReadyInfo::new(0x0001, 0x0003, 1024, false),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_FR_NA_1);
+    assert_eq!(
+        asdu.get_file_ready()?,
+        FileReadyInfo::new(0x0001, 0x0003, 1024, false)
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_section_ready() -> Result<()> {
+    let mut asdu = section_ready(
+        file_transfer_cot(),
+        0x0001,
+        SectionReadyInfo::new(0x0001, 0x0003, 2, 512, true),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_SR_NA_1);
+    let mut info = asdu.get_section_ready()?;
+    assert!(info.srq.not_ready().get());
+    assert_eq!(info, SectionReadyInfo::new(0x0001, 0x0003, 2, 512, true));
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_segment_and_last_section() -> Result<()> {
+    let data = Bytes::from_static(b"hello iec104");
+    let mut asdu = segment(
+        file_transfer_cot(),
+        0x0001,
+        SegmentInfo::new(0x0001, 0x0003, 1, data.clone()),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_SG_NA_1);
+    assert_eq!(asdu.get_segment()?.segment, data);
+
+    let chs = checksum(&data);
+    let mut asdu = last_section(
+        file_transfer_cot(),
+        0x0001,
+        LastSectionInfo::new(0x0001, 0x0003, 1, LSQ_SECTION_TRANSFER_WITHOUT_DEACT, chs),
+    )?;
+    assert_eq!(
+        asdu.get_last_section()?,
+        LastSectionInfo::new(0x0001, 0x0003, 1, LSQ_SECTION_TRANSFER_WITHOUT_DEACT, chs)
+    );
+    Ok(())
+}
+
+#[test]
+fn segment_too_large() {
+    let r = segment(
+        file_transfer_cot(),
+        0x0001,
+        SegmentInfo::new(
+            0x0001,
+            0x0003,
+            1,
+            Bytes::from(vec![0; SEGMENT_SIZE_MAX + 1]),
+        ),
+    );
+    assert!(r.is_err());
+}
+
+#[test]
+fn encode_and_decode_file_ack() -> Result<()> {
+    let mut asdu = file_ack(
+        file_transfer_cot(),
+        0x0001,
+        FileAckInfo::new(0x0001, 0x0003, 0, AFQ_POSITIVE_FILE),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_AF_NA_1);
+    assert_eq!(
+        asdu.get_file_ack()?,
+        FileAckInfo::new(0x0001, 0x0003, 0, AFQ_POSITIVE_FILE)
+    );
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_directory() -> Result<()> {
+    let time = Utc.with_ymd_and_hms(2023, 5, 6, 7, 8, 9).unwrap();
+    let infos = vec![
+        DirectoryInfo {
+            ioa: InfoObjAddr::new(0, 0x0001),
+            nof: 1,
+            lof: 100,
+            sof: ObjectSOF::new(false, false, false, u5!(0)),
+            time: Some(time),
+        },
+        DirectoryInfo {
+            ioa: InfoObjAddr::new(0, 0x0002),
+            nof: 2,
+            lof: 200,
+            sof: ObjectSOF::new(false, false, true, u5!(0)),
+            time: Some(time),
+        },
+    ];
+    let mut asdu = directory(
+        true,
+        CauseOfTransmission::new(false, false, Cause::Request),
+        0x0001,
+        infos,
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::F_DR_TA_1);
+
+    let mut infos = asdu.get_directory()?;
+    assert_eq!(infos.len(), 2);
+    assert_eq!(infos[1].ioa.addr().get(), 0x0002);
+    assert_eq!(infos[1].lof, 200);
+    assert!(infos[1].sof.lfd().get());
+    assert_eq!(infos[0].time, Some(time));
+    Ok(())
+}
+
+#[test]
+fn file_invalid_cause() {
+    let r = file_ready(
+        CauseOfTransmission::new(false, false, Cause::Activation),
+        0x0001,
+        FileReadyInfo::new(0x0001, 0x0003, 1024, false),
+    );
+    assert!(r.is_err());
+}
@@ -0,0 +1,123 @@
+use std::{future, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    gateway::Gateway,
+    test_util::{duplex_connector, spontaneous, ScriptedPeer},
+    Client, ClientHandler, ClientOption, DataStore, Error, PointValue, Quality, Server,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+fn command(ca: u16, ioa: u16) -> Asdu {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    single_cmd(
+        TypeID::C_SC_NA_1,
+        cot,
+        ca,
+        SingleCommandInfo::new(ioa, true, false),
+    )
+    .unwrap()
+}
+
+// 等待 DataStore 中出现点
+async fn wait_point(store: &DataStore, ca: u16, ioa: u16) -> PointValue {
+    for _ in 0..100 {
+        if let Some(point) = store.get(ca, ioa) {
+            return point.value;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("point {ca}/{ioa} not forwarded");
+}
+
+#[tokio::test]
+async fn gateway_forwards_data_and_commands() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    let store = DataStore::new();
+    let gateway = Arc::new(
+        Gateway::new(client, store.clone())
+            .with_common_addr(1, 10)
+            .with_point((2, 100), (10, 500)),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = Arc::new(Server::new(listener));
+    let s = server.clone();
+    let handler = gateway.clone();
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| {
+            let handler = handler.clone();
+            async move { io::Result::Ok(Some((handler, stream))) }
+        };
+        let _ = s.serve(&on_connected, |_err| {}).await;
+    });
+    gateway.start(server.handle()).await?;
+
+    let mut rtu = ScriptedPeer::new(streams.recv().await.unwrap());
+    rtu.accept_start_dt().await?;
+    let mut scada = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    scada.start_dt().await?;
+
+    // 监视方向: 按映射表写入 DataStore 并突发上送
+    rtu.send_asdu(spontaneous(1, 5, true)).await?;
+    let mut asdu = scada
+        .expect_asdu_with(TypeID::M_SP_TB_1, Cause::Spontaneous)
+        .await;
+    assert_eq!(asdu.identifier.common_addr, 10);
+    assert_eq!(asdu.get_single_point()?[0].ioa.addr().get(), 5);
+    rtu.send_asdu(spontaneous(2, 100, true)).await?;
+    assert_eq!(wait_point(&store, 10, 500).await, PointValue::Single(true));
+    // 映射表中没有的地址不转发
+    rtu.send_asdu(spontaneous(3, 1, true)).await?;
+    scada
+        .expect_asdu_with(TypeID::M_SP_TB_1, Cause::Spontaneous)
+        .await;
+    scada.expect_silence(Duration::from_millis(200)).await;
+    assert!(store.get(3, 1).is_none());
+
+    // 控制方向: 改写地址后转发, 确认改回上级地址
+    scada.send_asdu(command(10, 500)).await?;
+    let mut cmd = rtu
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+        .await;
+    assert_eq!(cmd.identifier.common_addr, 2);
+    assert_eq!(cmd.get_single_cmd()?.ioa.addr().get(), 100);
+    rtu.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+    let mut con = scada
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::ActivationCon)
+        .await;
+    assert_eq!(con.identifier.common_addr, 10);
+    assert_eq!(con.get_single_cmd()?.ioa.addr().get(), 500);
+
+    scada.send_asdu(command(99, 1)).await?;
+    let con = scada
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::UnknownIOA)
+        .await;
+    assert!(con.identifier.cot.is_negative());
+
+    // 远方站断开后已上送的点置为无效
+    drop((rtu, streams));
+    for _ in 0..100 {
+        let quality: Quality = store.get(10, 5).unwrap().quality.into();
+        if quality.invalid {
+            gateway.stop().await;
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("points not invalidated");
+}
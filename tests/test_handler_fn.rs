@@ -0,0 +1,141 @@
+use std::{
+    future,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use tokio_iecp5::{
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, InfoObjAddr, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    csys::{clock_synchronization_cmd, interrogation_cmd, read_cmd, ObjectQOI},
+    mproc::{single, SinglePointInfo},
+    server_handler_fn,
+    test_util::{assert_asdu, serve_in_memory},
+    Role, SessionContext,
+};
+
+fn act() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::Activation)
+}
+
+#[tokio::test]
+async fn server_handler_fn_closures() -> anyhow::Result<()> {
+    let handler = server_handler_fn(
+        |asdu: Asdu| async move { Ok(vec![asdu.mirror(Cause::ActivationCon)]) },
+        |asdu: Asdu, _qoi| async move {
+            let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+            let data = single(false, cot, 1, vec![SinglePointInfo::new_single(100, true)])?;
+            Ok(vec![
+                asdu.mirror(Cause::ActivationCon),
+                data,
+                asdu.mirror(Cause::ActivationTerm),
+            ])
+        },
+        |_, _| future::ready(Ok(Vec::new())),
+    );
+    let mut master = serve_in_memory(handler);
+    master.start_dt().await?;
+
+    master
+        .send_asdu(interrogation_cmd(act(), 1, ObjectQOI::new(20))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+    master
+        .expect_asdu_with(TypeID::M_SP_NA_1, Cause::InterrogatedByStation)
+        .await;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationTerm)
+        .await;
+
+    let cmd = single_cmd(
+        TypeID::C_SC_NA_1,
+        act(),
+        1,
+        SingleCommandInfo::new(5, true, false),
+    )?;
+    master.send_asdu(cmd).await?;
+    master
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::ActivationCon)
+        .await;
+
+    // 未设置 with_read 时读命令回复未知的信息对象地址
+    let cot = CauseOfTransmission::new(false, false, Cause::Request);
+    master
+        .send_asdu(read_cmd(cot, 1, InfoObjAddr::new(0, 100))?)
+        .await?;
+    let asdu = master.expect_asdu().await;
+    assert_asdu(&asdu, TypeID::C_RD_NA_1, Cause::UnknownIOA);
+
+    master
+        .send_asdu(clock_synchronization_cmd(act(), 1, Utc::now())?)
+        .await?;
+    let asdu = master.expect_asdu().await;
+    assert_asdu(&asdu, TypeID::C_CS_NA_1, Cause::ActivationCon);
+    assert!(!asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_handler_fn_with_read() -> anyhow::Result<()> {
+    let handler = server_handler_fn(
+        |_| future::ready(Ok(Vec::new())),
+        |_, _| future::ready(Ok(Vec::new())),
+        |_, _| future::ready(Ok(Vec::new())),
+    )
+    .with_read(|_asdu, mut ioa: InfoObjAddr| async move {
+        let cot = CauseOfTransmission::new(false, false, Cause::Request);
+        let info = SinglePointInfo::new_single(ioa.addr().get(), true);
+        Ok(vec![single(false, cot, 1, vec![info])?])
+    });
+    let mut master = serve_in_memory(handler);
+    master.start_dt().await?;
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Request);
+    master
+        .send_asdu(read_cmd(cot, 1, InfoObjAddr::new(0, 100))?)
+        .await?;
+    let mut asdu = master.expect_asdu().await;
+    assert_asdu(&asdu, TypeID::M_SP_NA_1, Cause::Request);
+    let mut info = asdu.get_single_point()?;
+    assert_eq!(info[0].ioa.addr().get(), 100);
+    Ok(())
+}
+
+#[tokio::test]
+async fn session_context_in_handler() -> anyhow::Result<()> {
+    let seen = Arc::new(Mutex::new(None));
+    let handler_seen = seen.clone();
+    let handler = server_handler_fn(
+        move |asdu: Asdu| {
+            *handler_seen.lock().unwrap() = SessionContext::current();
+            future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)]))
+        },
+        |_, _| future::ready(Ok(Vec::new())),
+        |_, _| future::ready(Ok(Vec::new())),
+    );
+    let mut master = serve_in_memory(handler);
+    master.start_dt().await?;
+
+    let cmd = single_cmd(
+        TypeID::C_SC_NA_1,
+        act(),
+        1,
+        SingleCommandInfo::new(5, true, false),
+    )?;
+    master.send_asdu(cmd).await?;
+    master
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::ActivationCon)
+        .await;
+
+    let ctx = seen.lock().unwrap().take().expect("no session context");
+    assert_eq!(ctx.peer(), SocketAddr::from(([127, 0, 0, 1], 0)));
+    assert_eq!(ctx.params(), AsduParams::IEC104);
+    assert_eq!(ctx.role(), Role::Controlled);
+    assert!(ctx.is_active());
+    // 会话任务之外没有当前会话
+    assert!(SessionContext::current().is_none());
+    Ok(())
+}
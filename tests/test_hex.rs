@@ -0,0 +1,65 @@
+use tokio_iecp5::{
+    apci::ApciKind,
+    asdu::{AsduParams, TypeID},
+    Apdu, Codec,
+};
+
+#[test]
+fn apdu_from_hex() -> anyhow::Result<()> {
+    let apdu = Apdu::from_hex("680e0000000064010600010000000014")?;
+    assert!(matches!(ApciKind::from(apdu.apci), ApciKind::I(_)));
+    let asdu = apdu.asdu.as_ref().unwrap();
+    assert_eq!(asdu.identifier.type_id, TypeID::C_IC_NA_1);
+    assert_eq!(asdu.identifier.common_addr, 1);
+    assert_eq!(apdu.to_hex(), "680e0000000064010600010000000014");
+
+    // 分隔符与大小写
+    let apdu = Apdu::from_hex("68 04 0B 00 00 00")?;
+    assert!(matches!(ApciKind::from(apdu.apci), ApciKind::U(_)));
+    assert_eq!(apdu.to_hex(), "68040b000000");
+    assert!(Apdu::from_hex("68:04:07:00:00:00").is_ok());
+    Ok(())
+}
+
+#[test]
+fn apdu_from_hex_errors() {
+    // 奇数个十六进制数字, 非法字符
+    assert!(Apdu::from_hex("68040").is_err());
+    assert!(Apdu::from_hex("68 04 0G 00 00 00").is_err());
+    // 不完整, 多于一个 APDU
+    assert!(Apdu::from_hex("680e00000000640106").is_err());
+    assert!(Apdu::from_hex("680407000000 680401000000").is_err());
+    assert!(Apdu::from_hex("").is_err());
+}
+
+#[test]
+fn apdu_hex_with_params() -> anyhow::Result<()> {
+    // 1 字节传送原因与公共地址
+    let params = AsduParams {
+        cot_size: 1,
+        ca_size: 1,
+        ..AsduParams::IEC104
+    };
+    let apdu = Apdu::from_hex_with("680c000000006401060100000014", &params)?;
+    let asdu = apdu.asdu.as_ref().unwrap();
+    assert_eq!(asdu.identifier.type_id, TypeID::C_IC_NA_1);
+    assert_eq!(asdu.identifier.common_addr, 1);
+    assert_eq!(apdu.to_hex_with(&params)?, "680c000000006401060100000014");
+    Ok(())
+}
+
+#[test]
+fn codec_decode_slice() -> anyhow::Result<()> {
+    let data = [
+        0x68, 0x04, 0x07, 0x00, 0x00, 0x00, // STARTDT act
+        0x68, 0x04, 0x01, 0x00, 0x02, 0x00, // S 帧
+    ];
+    let apdus = Codec::default().decode_slice(&data)?;
+    assert_eq!(apdus.len(), 2);
+    assert!(matches!(ApciKind::from(apdus[0].apci), ApciKind::U(_)));
+    assert!(matches!(ApciKind::from(apdus[1].apci), ApciKind::S(_)));
+
+    assert!(Codec::default().decode_slice(&data[..8]).is_err());
+    assert!(Codec::default().decode_slice(&[]).unwrap().is_empty());
+    Ok(())
+}
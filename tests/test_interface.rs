@@ -0,0 +1,85 @@
+use std::{future, sync::Mutex};
+
+use tokio_iecp5::{
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    mproc::{single, SinglePointInfo},
+    server_handler_fn,
+    test_util::{assert_asdu, duplex_connector, serve_in_memory, ScriptedPeer},
+    Client, ClientHandler, ClientOption, Connect, Error, ServerSessionHandle, SessionContext,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 与方向无关的 ASDU 生成代码
+fn report<C: Connect>(conn: &C, ioa: u16) -> Result<(), Error> {
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    assert_eq!(conn.params(), AsduParams::IEC104);
+    conn.send(single(
+        false,
+        cot,
+        1,
+        vec![SinglePointInfo::new_single(ioa, true)],
+    )?)
+}
+
+#[tokio::test]
+async fn client_implements_connect() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    report(&client, 100)?;
+    let mut asdu = slave.expect_asdu().await;
+    assert_asdu(&asdu, TypeID::M_SP_NA_1, Cause::Spontaneous);
+    assert_eq!(asdu.get_single_point()?[0].ioa.addr().get(), 100);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_session_implements_connect() -> anyhow::Result<()> {
+    static SESSION: Mutex<Option<ServerSessionHandle>> = Mutex::new(None);
+    let handler = server_handler_fn(
+        |asdu: Asdu| async move {
+            *SESSION.lock().unwrap() = SessionContext::current().map(|ctx| ctx.handle());
+            Ok(vec![asdu.mirror(Cause::ActivationCon)])
+        },
+        |_, _| future::ready(Ok(Vec::new())),
+        |_, _| future::ready(Ok(Vec::new())),
+    );
+    let mut master = serve_in_memory(handler);
+    master.start_dt().await?;
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let cmd = single_cmd(
+        TypeID::C_SC_NA_1,
+        cot,
+        1,
+        SingleCommandInfo::new(1, true, false),
+    )?;
+    master.send_asdu(cmd).await?;
+    master
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::ActivationCon)
+        .await;
+
+    // 在会话任务之外经句柄上送
+    let session = SESSION.lock().unwrap().take().unwrap();
+    assert!(session.is_active());
+    report(&session, 200)?;
+    let mut asdu = master.expect_asdu().await;
+    assert_asdu(&asdu, TypeID::M_SP_NA_1, Cause::Spontaneous);
+    assert_eq!(asdu.get_single_point()?[0].ioa.addr().get(), 200);
+    Ok(())
+}
@@ -0,0 +1,477 @@
+use std::{future, io, net::SocketAddr, time::Duration};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, CommonAddr, InfoObjAddr, TypeID, GLOBAL_COMMON_ADDR},
+    csys::{CounterGroup, FreezeMode, Qcc, Qoi},
+    mproc::{integrated_totals, single, BinaryCounterReadingInfo, ObjectBCR, SinglePointInfo},
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, DataStore, Error, Point, PointValue, Server,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+async fn start() -> anyhow::Result<(Client<NopHandler>, ScriptedPeer<tokio::io::DuplexStream>)> {
+    start_with(ClientOption::default()).await
+}
+
+async fn start_with(
+    op: ClientOption,
+) -> anyhow::Result<(Client<NopHandler>, ScriptedPeer<tokio::io::DuplexStream>)> {
+    let (connector, mut streams) = duplex_connector();
+    let op = op.with_interrogation_timeout(Duration::from_millis(300));
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    Ok((client, slave))
+}
+
+fn point(cause: Cause, ca: CommonAddr, ioa: u16) -> Asdu {
+    single(
+        false,
+        CauseOfTransmission::new(false, false, cause),
+        ca,
+        vec![SinglePointInfo::new_single(ioa, true)],
+    )
+    .unwrap()
+}
+
+fn counter(ca: CommonAddr, ioa: u16, value: i32) -> Asdu {
+    integrated_totals(
+        CauseOfTransmission::new(false, false, Cause::RequestByGeneralCounter),
+        ca,
+        vec![BinaryCounterReadingInfo {
+            ioa: InfoObjAddr::new(0, ioa),
+            bcr: ObjectBCR {
+                invalid: false,
+                ca: false,
+                cy: false,
+                seq: 0,
+                value,
+            },
+            time: None,
+        }],
+    )
+    .unwrap()
+}
+
+fn read_general() -> Qcc {
+    Qcc::new(CounterGroup::General, FreezeMode::Read)
+}
+
+// 以 ca 为公共地址的镜像报文
+fn mirror_from(cmd: &Asdu, cause: Cause, ca: CommonAddr) -> Asdu {
+    let mut asdu = cmd.mirror(cause);
+    asdu.identifier.common_addr = ca;
+    asdu
+}
+
+// 以 DataStore 为处理函数的服务端, 本站公共地址为 1 和 2
+async fn serve_store(store: DataStore) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = Server::new(listener).with_common_addrs([1, 2]);
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| {
+            let store = store.clone();
+            async move { io::Result::Ok(Some((store, stream))) }
+        };
+        let _ = server.serve(&on_connected, |_err| {}).await;
+    });
+    Ok(addr)
+}
+
+async fn connect(addr: SocketAddr) -> anyhow::Result<Client<NopHandler>> {
+    let op = ClientOption::new(addr, false)
+        .with_common_addrs([1, 2])
+        .with_interrogation_timeout(Duration::from_secs(5));
+    let client = Client::new(NopHandler, op);
+    let mut events = client.events();
+    client.start().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+    Ok(client)
+}
+
+#[tokio::test]
+async fn general_interrogation_collects_until_term() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        slave
+            .send_asdu(point(Cause::InterrogatedByStation, 1, 100))
+            .await?;
+        // 其他站与突发数据不计入召唤结果
+        slave
+            .send_asdu(point(Cause::InterrogatedByStation, 2, 200))
+            .await?;
+        slave.send_asdu(point(Cause::Spontaneous, 1, 101)).await?;
+        slave
+            .send_asdu(point(Cause::InterrogatedByStation, 1, 102))
+            .await?;
+        slave.send_asdu(cmd.mirror(Cause::ActivationTerm)).await?;
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(1, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    let mut snapshot = snapshot?;
+    let ioas: Vec<u16> = snapshot
+        .single_points
+        .iter_mut()
+        .map(|p| p.ioa.addr().get())
+        .collect();
+    assert_eq!(ioas, vec![100, 102]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn general_interrogation_to_broadcast_address() -> anyhow::Result<()> {
+    let (client, mut slave) = start_with(ClientOption::default().with_common_addrs([1, 2])).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        assert_eq!(cmd.identifier.common_addr, GLOBAL_COMMON_ADDR);
+        // 各站依次以自己的公共地址确认, 上送与终止, 第一个站终止后召唤不结束
+        for ca in [1, 2] {
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationCon, ca))
+                .await?;
+            slave
+                .send_asdu(point(Cause::InterrogatedByStation, ca, 100 * ca))
+                .await?;
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationTerm, ca))
+                .await?;
+        }
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(GLOBAL_COMMON_ADDR, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    let snapshot = snapshot?;
+    assert_eq!(snapshot.single_points.len(), 2);
+    let cas: Vec<CommonAddr> = snapshot
+        .asdus
+        .iter()
+        .map(|asdu| asdu.identifier.common_addr)
+        .collect();
+    assert_eq!(cas, vec![1, 2]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_general_interrogation_without_common_addrs() -> anyhow::Result<()> {
+    // 未设置对端的各站时, 收集到超时为止
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        for ca in [1, 2] {
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationCon, ca))
+                .await?;
+            slave
+                .send_asdu(point(Cause::InterrogatedByStation, ca, 100 * ca))
+                .await?;
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationTerm, ca))
+                .await?;
+        }
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(GLOBAL_COMMON_ADDR, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    assert_eq!(snapshot?.single_points.len(), 2);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_general_interrogation_records_rejected_station() -> anyhow::Result<()> {
+    let (client, mut slave) = start_with(ClientOption::default().with_common_addrs([1, 2])).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        // 一个站否定确认不影响其他站的召唤
+        let mut rejected = cmd.mirror_negative(Cause::UnknownCA);
+        rejected.identifier.common_addr = 2;
+        slave.send_asdu(rejected).await?;
+        slave
+            .send_asdu(mirror_from(&cmd, Cause::ActivationCon, 1))
+            .await?;
+        slave
+            .send_asdu(point(Cause::InterrogatedByStation, 1, 100))
+            .await?;
+        slave
+            .send_asdu(mirror_from(&cmd, Cause::ActivationTerm, 1))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(GLOBAL_COMMON_ADDR, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    let snapshot = snapshot?;
+    assert_eq!(snapshot.single_points.len(), 1);
+    assert_eq!(snapshot.rejected, vec![(2, Cause::UnknownCA)]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_general_interrogation_from_datastore() -> anyhow::Result<()> {
+    let store = DataStore::new();
+    store.insert(1, 1, Point::new(PointValue::Single(true)));
+    store.insert(2, 1, Point::new(PointValue::Double(2)));
+    store.insert(2, 2, Point::new(PointValue::Single(false)));
+    let client = connect(serve_store(store).await?).await?;
+
+    // 服务端按公共地址依次回复确认, 数据与终止
+    let snapshot = client
+        .general_interrogation(GLOBAL_COMMON_ADDR, Qoi::StationInterrogation)
+        .await?;
+    assert_eq!(snapshot.single_points.len(), 2);
+    assert_eq!(snapshot.double_points.len(), 1);
+    assert!(snapshot.rejected.is_empty());
+    let mut cas: Vec<CommonAddr> = snapshot
+        .asdus
+        .iter()
+        .map(|asdu| asdu.identifier.common_addr)
+        .collect();
+    cas.dedup();
+    assert_eq!(cas, vec![1, 2]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn general_interrogation_negative_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(cmd.mirror_negative(Cause::ActivationCon))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(1, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    assert!(matches!(
+        snapshot,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_IC_NA_1,
+            Cause::ActivationCon
+        ))
+    ));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn general_interrogation_timeout() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+            .await;
+        // 只确认, 不终止
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        slave
+            .send_asdu(point(Cause::InterrogatedByStation, 1, 100))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (snapshot, script) = tokio::join!(
+        client.general_interrogation(1, Qoi::StationInterrogation),
+        script
+    );
+    script?;
+    assert!(matches!(snapshot, Err(Error::ErrTimeout)));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn counter_interrogation_collects_until_term() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_CI_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        slave.send_asdu(counter(1, 10, 42)).await?;
+        slave.send_asdu(counter(2, 20, 7)).await?;
+        slave.send_asdu(cmd.mirror(Cause::ActivationTerm)).await?;
+        anyhow::Ok(())
+    };
+    let (counters, script) = tokio::join!(client.counter_interrogation(1, read_general()), script);
+    script?;
+    let counters = counters?;
+    assert_eq!(counters.len(), 1);
+    assert_eq!(counters[0].bcr.value, 42);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn counter_interrogation_to_broadcast_address() -> anyhow::Result<()> {
+    let (client, mut slave) = start_with(ClientOption::default().with_common_addrs([1, 2])).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_CI_NA_1, Cause::Activation)
+            .await;
+        // 各站依次确认, 上送与终止, 第一个站终止后召唤不结束
+        for (ca, value) in [(1, 42), (2, 7)] {
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationCon, ca))
+                .await?;
+            slave.send_asdu(counter(ca, 10 * ca, value)).await?;
+            slave
+                .send_asdu(mirror_from(&cmd, Cause::ActivationTerm, ca))
+                .await?;
+        }
+        anyhow::Ok(())
+    };
+    let (counters, script) = tokio::join!(
+        client.counter_interrogation(GLOBAL_COMMON_ADDR, read_general()),
+        script
+    );
+    script?;
+    let values: Vec<i32> = counters?.iter().map(|c| c.bcr.value).collect();
+    assert_eq!(values, vec![42, 7]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_counter_interrogation_skips_rejected_station() -> anyhow::Result<()> {
+    let (client, mut slave) = start_with(ClientOption::default().with_common_addrs([1, 2])).await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_CI_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(mirror_from(&cmd, Cause::ActivationCon, 1))
+            .await?;
+        let mut rejected = cmd.mirror_negative(Cause::ActivationCon);
+        rejected.identifier.common_addr = 2;
+        slave.send_asdu(rejected).await?;
+        slave.send_asdu(counter(1, 10, 42)).await?;
+        slave
+            .send_asdu(mirror_from(&cmd, Cause::ActivationTerm, 1))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (counters, script) = tokio::join!(
+        client.counter_interrogation(GLOBAL_COMMON_ADDR, read_general()),
+        script
+    );
+    script?;
+    let values: Vec<i32> = counters?.iter().map(|c| c.bcr.value).collect();
+    assert_eq!(values, vec![42]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_counter_interrogation_from_datastore() -> anyhow::Result<()> {
+    let store = DataStore::new();
+    store.insert(1, 20, Point::new(PointValue::Counter(42)));
+    store.insert(2, 20, Point::new(PointValue::Counter(7)));
+    store.insert(2, 21, Point::new(PointValue::Counter(8)));
+    let client = connect(serve_store(store).await?).await?;
+
+    let values: Vec<i32> = client
+        .counter_interrogation(GLOBAL_COMMON_ADDR, read_general())
+        .await?
+        .iter()
+        .map(|c| c.bcr.value)
+        .collect();
+    assert_eq!(values, vec![42, 7, 8]);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn counter_interrogation_negative_confirm() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_CI_NA_1, Cause::Activation)
+            .await;
+        slave
+            .send_asdu(cmd.mirror_negative(Cause::ActivationCon))
+            .await?;
+        anyhow::Ok(())
+    };
+    let (counters, script) = tokio::join!(client.counter_interrogation(1, read_general()), script);
+    script?;
+    assert!(matches!(
+        counters,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_CI_NA_1,
+            Cause::ActivationCon
+        ))
+    ));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn counter_interrogation_timeout() -> anyhow::Result<()> {
+    let (client, mut slave) = start().await?;
+
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_CI_NA_1, Cause::Activation)
+            .await;
+        slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+        anyhow::Ok(())
+    };
+    let (counters, script) = tokio::join!(client.counter_interrogation(1, read_general()), script);
+    script?;
+    assert!(matches!(counters, Err(Error::ErrTimeout)));
+    client.stop().await;
+    Ok(())
+}
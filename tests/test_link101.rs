@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio::{io::duplex, sync::mpsc};
+use tokio_iecp5::{
+    asdu::{Asdu, AsduParams, TypeID},
+    link101::{
+        frame::{
+            checksum, PRM_REQUEST_CLASS1, PRM_REQUEST_CLASS2, PRM_REQUEST_LINK_STATUS,
+            PRM_RESET_LINK, PRM_USER_DATA_CONFIRMED, SEC_ACK, SEC_LINK_STATUS, SEC_NO_DATA,
+            SEC_USER_DATA,
+        },
+        ControlField, Ft12Codec, Ft12Frame, Link101Option, Serial101Client,
+    },
+    test_util::spontaneous,
+    ClientHandler, Error,
+};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+fn params() -> AsduParams {
+    AsduParams::new(1, 1, 2).unwrap()
+}
+
+#[test]
+fn encode_and_decode_ft12() -> anyhow::Result<()> {
+    let mut codec = Ft12Codec::new(1, params());
+
+    let mut buf = BytesMut::new();
+    let control = ControlField::primary(PRM_REQUEST_CLASS2, Some(true));
+    codec.encode(
+        Ft12Frame::Fixed {
+            control,
+            link_addr: 0x05,
+        },
+        &mut buf,
+    )?;
+    assert_eq!(&buf[..], &[0x10, 0x7b, 0x05, 0x80, 0x16]);
+
+    codec.encode(
+        Ft12Frame::Variable {
+            control: ControlField::secondary(SEC_USER_DATA, false),
+            link_addr: 0x05,
+            asdu: spontaneous(1, 0x0102, true),
+        },
+        &mut buf,
+    )?;
+    let user_data = [0x08, 0x05, 0x01, 0x01, 0x03, 0x01, 0x02, 0x01, 0x01];
+    let mut want = vec![0x68, 0x09, 0x09, 0x68];
+    want.extend_from_slice(&user_data);
+    want.extend_from_slice(&[checksum(&user_data), 0x16]);
+    assert_eq!(&buf[5..], &want[..]);
+    buf.extend_from_slice(&[0xe5]);
+
+    let Some(Ft12Frame::Fixed {
+        mut control,
+        link_addr,
+    }) = codec.decode(&mut buf)?
+    else {
+        panic!("expect fixed frame");
+    };
+    assert_eq!(control.function_code(), PRM_REQUEST_CLASS2);
+    assert!(control.fcb().get() && control.fcv().get() && control.prm().get());
+    assert_eq!(link_addr, 0x05);
+
+    let Some(Ft12Frame::Variable { mut asdu, .. }) = codec.decode(&mut buf)? else {
+        panic!("expect variable frame");
+    };
+    assert_eq!(asdu.identifier.type_id, TypeID::M_SP_NA_1);
+    let mut infos = asdu.get_single_point()?;
+    assert_eq!(infos[0].ioa.addr().get(), 0x0102);
+
+    assert!(matches!(
+        codec.decode(&mut buf)?,
+        Some(Ft12Frame::SingleChar)
+    ));
+    assert!(codec.decode(&mut buf)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn decode_ft12_resync() -> anyhow::Result<()> {
+    let mut codec = Ft12Codec::new(1, params());
+    // 干扰字节与校验和错误的帧被丢弃
+    let mut buf = BytesMut::from(&[0x00, 0x33, 0x10, 0x0b, 0x05, 0xff, 0x16][..]);
+    buf.extend_from_slice(&[0x10, 0x0b, 0x05, 0x10, 0x16]);
+    let Some(Ft12Frame::Fixed { mut control, .. }) = codec.decode(&mut buf)? else {
+        panic!("expect fixed frame");
+    };
+    assert_eq!(control.function_code(), SEC_LINK_STATUS);
+    assert!(buf.is_empty());
+
+    // 不完整的帧等待更多数据
+    let mut buf = BytesMut::from(&[0x68, 0x09, 0x09][..]);
+    assert!(codec.decode(&mut buf)?.is_none());
+    assert_eq!(buf.len(), 3);
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Forward(mpsc::UnboundedSender<Asdu>);
+
+impl ClientHandler for Forward {
+    type Future = std::future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        let _ = self.0.send(asdu);
+        std::future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn serial101_client_polls_slave() -> anyhow::Result<()> {
+    let (local, remote) = duplex(1024);
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let op = Link101Option::new(0x05).with_poll_interval(Duration::from_millis(20));
+    let client = Serial101Client::new(Forward(tx), op);
+    client.start(local).await?;
+
+    // 模拟从站
+    let (data_tx, mut data_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut slave = Framed::new(remote, Ft12Codec::new(1, params()));
+        let mut pending = vec![spontaneous(1, 0x10, true), spontaneous(1, 0x11, true)];
+        while let Some(Ok(frame)) = slave.next().await {
+            let mut control = frame.control().unwrap();
+            let resp = match (control.function_code(), frame) {
+                (PRM_REQUEST_LINK_STATUS, _) => Ft12Frame::Fixed {
+                    control: ControlField::secondary(SEC_LINK_STATUS, false),
+                    link_addr: 0x05,
+                },
+                (PRM_RESET_LINK, _) => Ft12Frame::SingleChar,
+                (PRM_USER_DATA_CONFIRMED, Ft12Frame::Variable { asdu, .. }) => {
+                    let _ = data_tx.send(asdu);
+                    Ft12Frame::Fixed {
+                        control: ControlField::secondary(SEC_ACK, false),
+                        link_addr: 0x05,
+                    }
+                }
+                (PRM_REQUEST_CLASS1 | PRM_REQUEST_CLASS2, _) => match pending.pop() {
+                    Some(asdu) => Ft12Frame::Variable {
+                        control: ControlField::secondary(SEC_USER_DATA, !pending.is_empty()),
+                        link_addr: 0x05,
+                        asdu,
+                    },
+                    None => Ft12Frame::Fixed {
+                        control: ControlField::secondary(SEC_NO_DATA, false),
+                        link_addr: 0x05,
+                    },
+                },
+                (f, _) => panic!("unexpected function code {f}"),
+            };
+            slave.send(resp).await.unwrap();
+        }
+    });
+
+    for expect in [0x11, 0x10] {
+        let mut asdu = received.recv().await.unwrap();
+        let mut infos = asdu.get_single_point()?;
+        assert_eq!(infos[0].ioa.addr().get(), expect);
+    }
+
+    client.send_asdu(spontaneous(1, 0x20, true)).await?;
+    let mut asdu = data_rx.recv().await.unwrap();
+    assert_eq!(asdu.identifier.common_addr, 0x01);
+    let mut infos = asdu.get_single_point()?;
+    assert_eq!(infos[0].ioa.addr().get(), 0x20);
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use std::{future, time::Duration};
+
+use tokio::sync::watch;
+use tokio_iecp5::{
+    apci::{U_STOPDT_ACTIVE, U_STOPDT_CONFIRM},
+    asdu::Asdu,
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientHandler, ClientOption, Error, LinkEvent, LinkState, Role,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+const ALL_STATES: [LinkState; 6] = [
+    LinkState::Connecting,
+    LinkState::Connected,
+    LinkState::Pending,
+    LinkState::Active,
+    LinkState::Stopping,
+    LinkState::Closed,
+];
+
+#[test]
+fn startdt_transitions() {
+    use LinkState::*;
+    assert_eq!(Closed.transition(LinkEvent::Connect), Some(Connecting));
+    assert_eq!(Connecting.transition(LinkEvent::Connect), Some(Connecting));
+    assert_eq!(Connecting.transition(LinkEvent::Connected), Some(Connected));
+    assert_eq!(Connected.transition(LinkEvent::StartDtSent), Some(Pending));
+    assert_eq!(
+        Pending.transition(LinkEvent::StartDtConfirmed),
+        Some(Active)
+    );
+    // 被控站收到对端的 STARTDT 后直接激活
+    assert_eq!(
+        Connected.transition(LinkEvent::StartDtConfirmed),
+        Some(Active)
+    );
+
+    assert_eq!(Active.transition(LinkEvent::StartDtSent), None);
+    assert_eq!(Active.transition(LinkEvent::StartDtConfirmed), None);
+    assert_eq!(Pending.transition(LinkEvent::StartDtSent), None);
+    assert_eq!(Closed.transition(LinkEvent::StartDtSent), None);
+    assert_eq!(Connecting.transition(LinkEvent::StartDtConfirmed), None);
+}
+
+#[test]
+fn stopdt_transitions() {
+    use LinkState::*;
+    assert_eq!(Active.transition(LinkEvent::StopDtSent), Some(Stopping));
+    assert_eq!(Connected.transition(LinkEvent::StopDtSent), Some(Stopping));
+    assert_eq!(
+        Stopping.transition(LinkEvent::StopDtConfirmed),
+        Some(Connected)
+    );
+    // 被控站收到对端的 STOPDT 后停止激活
+    assert_eq!(
+        Active.transition(LinkEvent::StopDtConfirmed),
+        Some(Connected)
+    );
+
+    assert_eq!(Pending.transition(LinkEvent::StopDtSent), None);
+    assert_eq!(Stopping.transition(LinkEvent::StopDtSent), None);
+    assert_eq!(Connected.transition(LinkEvent::StopDtConfirmed), None);
+    assert_eq!(Closed.transition(LinkEvent::StopDtConfirmed), None);
+}
+
+#[test]
+fn timeout_and_disconnect_transitions() {
+    use LinkState::*;
+    for state in [Connected, Pending, Active, Stopping] {
+        assert_eq!(
+            state.transition(LinkEvent::Timeout),
+            Some(Closed),
+            "{state:?}"
+        );
+    }
+    for state in [Connecting, Closed] {
+        assert_eq!(state.transition(LinkEvent::Timeout), None, "{state:?}");
+    }
+    for state in ALL_STATES {
+        assert_eq!(state.transition(LinkEvent::Disconnected), Some(Closed));
+    }
+    for state in [Connected, Pending, Active, Stopping] {
+        assert_eq!(state.transition(LinkEvent::Connect), None, "{state:?}");
+        assert_eq!(state.transition(LinkEvent::Connected), None, "{state:?}");
+    }
+}
+
+#[test]
+fn active_and_connected_states() {
+    for state in ALL_STATES {
+        let active = matches!(state, LinkState::Active | LinkState::Stopping);
+        assert_eq!(state.is_active(), active, "{state:?}");
+        let connected = !matches!(state, LinkState::Connecting | LinkState::Closed);
+        assert_eq!(state.is_connected(), connected, "{state:?}");
+    }
+    assert_eq!(LinkState::default(), LinkState::Closed);
+}
+
+async fn wait_state(rx: &mut watch::Receiver<LinkState>, state: LinkState) {
+    tokio::time::timeout(Duration::from_secs(1), rx.wait_for(|s| *s == state))
+        .await
+        .unwrap_or_else(|_| panic!("link state {state:?} not reached"))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn client_link_state_follows_startdt_stopdt() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    assert_eq!(client.link_state(), LinkState::Closed);
+    assert!(matches!(
+        client.send_start_dt().await,
+        Err(Error::ErrNotConnected)
+    ));
+    let mut states = client.watch_link_state();
+    client.start().await?;
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    wait_state(&mut states, LinkState::Pending).await;
+    slave.accept_start_dt().await?;
+    wait_state(&mut states, LinkState::Active).await;
+    assert!(client.is_active());
+
+    client.send_stop_dt().await?;
+    wait_state(&mut states, LinkState::Stopping).await;
+    slave.expect_u(U_STOPDT_ACTIVE).await;
+    slave.send_u(U_STOPDT_CONFIRM).await?;
+    wait_state(&mut states, LinkState::Connected).await;
+    assert!(!client.is_active());
+
+    client.stop().await;
+    assert_eq!(client.link_state(), LinkState::Closed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unconfirmed_startdt_closes_link() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::default().with_t1(Duration::from_millis(200));
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut states = client.watch_link_state();
+    client.start().await?;
+
+    let _slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    wait_state(&mut states, LinkState::Pending).await;
+    // t1 内不确认 STARTDT, 关闭连接后重连并再次发送 STARTDT
+    let reconnect = tokio::time::timeout(Duration::from_secs(1), streams.recv()).await?;
+    let mut slave = ScriptedPeer::new(reconnect.unwrap());
+    assert!(!client.is_active());
+    slave.accept_start_dt().await?;
+    wait_state(&mut states, LinkState::Active).await;
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn controlled_client_activates_on_peer_startdt() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::default().with_role(Role::Controlled);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut states = client.watch_link_state();
+    client.start().await?;
+
+    let mut master = ScriptedPeer::new(streams.recv().await.unwrap());
+    wait_state(&mut states, LinkState::Connected).await;
+    master.start_dt().await?;
+    wait_state(&mut states, LinkState::Active).await;
+    master.stop_dt().await?;
+    wait_state(&mut states, LinkState::Connected).await;
+    client.stop().await;
+    Ok(())
+}
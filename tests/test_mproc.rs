@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use chrono::{Datelike, TimeZone, Timelike, Utc};
+use chrono::{Datelike, Duration, TimeZone, Timelike, Utc};
 use tokio_test::{assert_err, assert_ok};
 use tokio_iecp5::asdu::*;
 use tokio_iecp5::mproc::*;
@@ -67,11 +67,17 @@ fn decode_singlepiont() -> Result<()> {
         ],
     });
 
+    // CP24Time2a 取距当前时间最近的 hh:03:00.513
     let now_utc = Utc::now();
-    let hour = now_utc.hour();
-    let day = now_utc.day();
-    let month = now_utc.month();
-    let year = now_utc.year();
+    let mut cp24 = Utc
+        .with_ymd_and_hms(now_utc.year(), now_utc.month(), now_utc.day(), now_utc.hour(), 3, 0)
+        .unwrap()
+        + Duration::milliseconds(513);
+    if cp24 - now_utc > Duration::minutes(30) {
+        cp24 -= Duration::hours(1);
+    } else if cp24 - now_utc < Duration::minutes(-30) {
+        cp24 += Duration::hours(1);
+    }
 
     tests.push(Test {
         name: "M_SP_NA_1 seq = true Number = 2".into(),
@@ -92,12 +98,12 @@ fn decode_singlepiont() -> Result<()> {
             SinglePointInfo::new(
                 InfoObjAddr::try_from(u24!(0x01)).unwrap(),
                 ObjectSIQ::try_from(0x11).unwrap(),
-                Some(Utc.with_ymd_and_hms(2019, 6, 5, 4, 3, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2019, 6, 5, 4, 3, 0).unwrap() + Duration::milliseconds(513)),
             ),
             SinglePointInfo::new(
                 InfoObjAddr::try_from(u24!(0x02)).unwrap(),
                 ObjectSIQ::try_from(0x10).unwrap(),
-                Some(Utc.with_ymd_and_hms(2019, 6, 5, 4, 3, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2019, 6, 5, 4, 3, 0).unwrap() + Duration::milliseconds(513)),
             ),
         ],
     });
@@ -121,12 +127,12 @@ fn decode_singlepiont() -> Result<()> {
             SinglePointInfo::new(
                 InfoObjAddr::try_from(u24!(0x01)).unwrap(),
                 ObjectSIQ::try_from(0x11).unwrap(),
-                Some(Utc.with_ymd_and_hms(year, month, day, hour, 3, 0).unwrap()),
+                Some(cp24),
             ),
             SinglePointInfo::new(
                 InfoObjAddr::try_from(u24!(0x02)).unwrap(),
                 ObjectSIQ::try_from(0x10).unwrap(),
-                Some(Utc.with_ymd_and_hms(year, month, day, hour, 3, 0).unwrap()),
+                Some(cp24),
             ),
         ],
     });
@@ -264,7 +270,7 @@ fn test_single() -> Result<()> {
                     ),
                 ],
                 want_bytes: Bytes::from_static(&[
-                    TypeID::M_SP_NA_1 as u8,
+                    u8::from(TypeID::M_SP_NA_1),
                     0x02,
                     0x02,
                     0x00,
@@ -301,7 +307,7 @@ fn test_single() -> Result<()> {
                     ),
                 ],
                 want_bytes: Bytes::from_static(&[
-                    TypeID::M_SP_NA_1 as u8,
+                    u8::from(TypeID::M_SP_NA_1),
                     0x82,
                     0x02,
                     0x00,
@@ -344,4 +350,144 @@ fn test_single() -> Result<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn encode_and_decode_packed_single_point() -> Result<()> {
+    let asdu = packed_single_point_with_scd(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x0001,
+        vec![PackedSinglePointInfo::new(0x0001, 0x1234, 0x0010)],
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::M_PS_NA_1);
+
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            u8::from(TypeID::M_PS_NA_1),
+            0x01,
+            0x03,
+            0x00,
+            0x01,
+            0x00,
+            0x01,
+            0x00,
+            0x00,
+            0x34,
+            0x12,
+            0x10,
+            0x00,
+            0x00,
+        ])
+    );
+
+    let mut asdu: Asdu = raw.try_into()?;
+    let mut infos = asdu.get_packed_single_point()?;
+    assert_eq!(infos[0].scd.spi().get(), 0x1234);
+    assert_eq!(infos[0].scd.vflag().get(), 0x0010);
+    assert_eq!(
+        infos,
+        vec![PackedSinglePointInfo::new(0x0001, 0x1234, 0x0010)]
+    );
+    Ok(())
+}
+
+#[test]
+fn sequence_requires_contiguous_addresses() -> Result<()> {
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    let infos = vec![
+        SinglePointInfo::new_single(0x01, true),
+        SinglePointInfo::new_single(0x03, false),
+    ];
+    assert!(matches!(
+        single(true, cot, 0x1234, infos),
+        Err(Error::ErrIoaNotSequential(0x03))
+    ));
+
+    let infos = vec![
+        SinglePointInfo::new_single(0x01, true),
+        SinglePointInfo::new_single(0x03, false),
+    ];
+    assert_ok!(single(false, cot, 0x1234, infos));
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_sequence() -> Result<()> {
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+
+    let mut asdu = single_sequence(cot, 0x1234, InfoObjAddr::new(0, 0x10), vec![true, false])?;
+    assert_eq!(asdu.identifier.variable_struct.raw(), 0x82);
+    let mut infos = asdu.get_single_point()?;
+    assert_eq!(infos[1].ioa.addr().get(), 0x11);
+    assert!(!infos[1].siq.spi().get());
+
+    let mut asdu = measured_value_float_sequence(
+        cot,
+        0x1234,
+        InfoObjAddr::new(0, 0x4001),
+        vec![1.0, 2.5, -3.0],
+    )?;
+    let mut infos = asdu.get_measured_value_float()?;
+    assert_eq!(infos.len(), 3);
+    assert_eq!(infos[2].ioa.addr().get(), 0x4003);
+    assert_eq!(infos[2].r, -3.0);
+
+    let mut asdu =
+        measured_value_scaled_sequence(cot, 0x1234, InfoObjAddr::new(0, 0x4001), vec![7, -7])?;
+    let mut infos = asdu.get_measured_value_scaled()?;
+    assert_eq!(infos[1].ioa.addr().get(), 0x4002);
+    assert_eq!(infos[1].sva, -7);
+
+    assert_err!(double_sequence(
+        cot,
+        0x1234,
+        InfoObjAddr::new(0, 0xffff),
+        vec![1, 2]
+    ));
+    Ok(())
+}
+
+#[test]
+fn split_into_asdus_respects_size_limit() -> Result<()> {
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+
+    let infos: Vec<SinglePointInfo> = (1..=200)
+        .map(|addr| SinglePointInfo::new_single(addr, addr % 2 == 0))
+        .collect();
+    let asdus = split_into_asdus(infos, |infos| single(false, cot, 0x1234, infos))?;
+    assert!(asdus.len() > 1);
+    let mut addrs = Vec::new();
+    for asdu in asdus {
+        let raw: Bytes = asdu.try_into()?;
+        assert!(raw.len() <= 249);
+        let mut asdu: Asdu = raw.try_into()?;
+        for mut info in asdu.get_single_point()? {
+            assert_eq!(info.siq.spi().get(), info.ioa.addr().get() % 2 == 0);
+            addrs.push(info.ioa.addr().get());
+        }
+    }
+    assert_eq!(addrs, (1..=200).collect::<Vec<u16>>());
+
+    // 顺序编码拆分后每个 ASDU 带各自的起始地址
+    let values: Vec<f32> = (0..100).map(|v| v as f32).collect();
+    let asdus = split_into_asdus(values, |values| {
+        measured_value_float_sequence(cot, 0x1234, InfoObjAddr::new(0, 0x4001), values)
+    })?;
+    let mut addr = 0x4001;
+    for asdu in asdus {
+        let raw: Bytes = asdu.try_into()?;
+        assert!(raw.len() <= 249);
+        let mut asdu: Asdu = raw.try_into()?;
+        assert_eq!(asdu.identifier.variable_struct.is_sequence().get().value(), 1);
+        for mut info in asdu.get_measured_value_float()? {
+            assert_eq!(info.ioa.addr().get(), addr);
+            assert_eq!(info.r, (addr - 0x4001) as f32);
+            addr += 1;
+        }
+    }
+    assert_eq!(addr, 0x4001 + 100);
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::{
+    future, io,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, TypeID},
+    test_util::{duplex_connector, spontaneous, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, DataStore, Error, Pacing, PointValue, Server,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[test]
+fn pacing_gap() {
+    assert!(!Pacing::default().is_limited());
+    assert!(!Pacing::max_rate(0).is_limited());
+    assert_eq!(Pacing::max_rate(4).gap(), Duration::from_millis(250));
+    assert_eq!(
+        Pacing::min_gap(Duration::from_millis(20)).gap(),
+        Duration::from_millis(20)
+    );
+    // 取较长的间隔
+    let pacing = Pacing::max_rate(10).with_min_gap(Duration::from_millis(50));
+    assert_eq!(pacing.gap(), Duration::from_millis(100));
+    let pacing = Pacing::max_rate(10).with_min_gap(Duration::from_millis(150));
+    assert_eq!(pacing.gap(), Duration::from_millis(150));
+}
+
+#[tokio::test]
+async fn client_paces_iframes() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default().with_pacing(Pacing::max_rate(20));
+    let client = Client::new_with_connector(NopHandler, option, connector);
+    let mut events = client.events();
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+
+    let start = Instant::now();
+    for ioa in 1..=4 {
+        client.send_asdu(spontaneous(1, ioa, true)).await?;
+    }
+    for _ in 1..=4 {
+        slave
+            .expect_asdu_with(TypeID::M_SP_NA_1, Cause::Spontaneous)
+            .await;
+    }
+    // 第一个 I 帧立即发送, 其后每隔 50ms 发送一个
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(150), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_paces_iframes() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server =
+        Arc::new(Server::new(listener).with_pacing(Pacing::min_gap(Duration::from_millis(40))));
+    let store = DataStore::new();
+    store.attach(server.handle());
+    let s = server.clone();
+    let handler = store.clone();
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| {
+            let handler = handler.clone();
+            async move { io::Result::Ok(Some((handler, stream))) }
+        };
+        let _ = s.serve(&on_connected, |_err| {}).await;
+    });
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    while server.sessions().iter().all(|(_, active)| !active) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let start = Instant::now();
+    for ioa in 1..=5 {
+        store.set(1, ioa, PointValue::Single(true))?;
+    }
+    for _ in 1..=5 {
+        master.expect_asdu().await;
+    }
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(160), "{elapsed:?}");
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use bit_struct::*;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::cproc::*;
+use tokio_iecp5::csys::*;
+use tokio_iecp5::mproc::*;
+use tokio_iecp5::payload::*;
+
+fn identifier(type_id: TypeID, cause: Cause) -> Identifier {
+    Identifier {
+        type_id,
+        variable_struct: VariableStruct::new(u1!(0), u7!(0)),
+        cot: CauseOfTransmission::new(false, false, cause),
+        orig_addr: 0,
+        common_addr: 0x0001,
+    }
+}
+
+#[test]
+fn payload_single_point_round_trip() -> Result<()> {
+    let infos = vec![
+        SinglePointInfo::new(
+            InfoObjAddr::new(0, 100),
+            ObjectSIQ::new_with_value(true),
+            None,
+        ),
+        SinglePointInfo::new(
+            InfoObjAddr::new(0, 101),
+            ObjectSIQ::new_with_value(false),
+            None,
+        ),
+    ];
+    let asdu = Asdu::from_payload(
+        identifier(TypeID::M_SP_NA_1, Cause::Spontaneous),
+        AsduPayload::SinglePoint(infos),
+    )?;
+    let expect = single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x0001,
+        vec![
+            SinglePointInfo::new(
+                InfoObjAddr::new(0, 100),
+                ObjectSIQ::new_with_value(true),
+                None,
+            ),
+            SinglePointInfo::new(
+                InfoObjAddr::new(0, 101),
+                ObjectSIQ::new_with_value(false),
+                None,
+            ),
+        ],
+    )?;
+    let raw: Bytes = asdu.try_into()?;
+    let expect: Bytes = expect.try_into()?;
+    assert_eq!(raw, expect);
+
+    let mut asdu: Asdu = raw.try_into()?;
+    match asdu.decode_payload()? {
+        AsduPayload::SinglePoint(mut infos) => {
+            assert_eq!(infos.len(), 2);
+            assert_eq!(infos[1].ioa.addr().get(), 101);
+        }
+        p => panic!("unexpected payload {:?}", p),
+    }
+    Ok(())
+}
+
+#[test]
+fn payload_measured_float_with_time() -> Result<()> {
+    let time = Utc.with_ymd_and_hms(2023, 5, 6, 7, 8, 9).unwrap();
+    let info = MeasuredValueFloatInfo {
+        ioa: InfoObjAddr::new(0, 0x4001),
+        r: 12.5,
+        qds: ObjectQDS::try_from(0).unwrap(),
+        time: Some(time),
+    };
+    let mut asdu = Asdu::from_payload(
+        identifier(TypeID::M_ME_TF_1, Cause::Spontaneous),
+        AsduPayload::MeasuredFloat(vec![info]),
+    )?;
+    assert_eq!(asdu.identifier.variable_struct.number().get().value(), 1);
+    assert_eq!(
+        asdu.decode_payload()?,
+        AsduPayload::MeasuredFloat(vec![MeasuredValueFloatInfo {
+            ioa: InfoObjAddr::new(0, 0x4001),
+            r: 12.5,
+            qds: ObjectQDS::try_from(0).unwrap(),
+            time: Some(time),
+        }])
+    );
+    Ok(())
+}
+
+#[test]
+fn payload_command_keeps_identifier() -> Result<()> {
+    let mut id = identifier(TypeID::C_SC_NA_1, Cause::ActivationCon);
+    id.orig_addr = 3;
+    let mut asdu = Asdu::from_payload(
+        id,
+        AsduPayload::SingleCommand(SingleCommandInfo::new(0x0102, true, false)),
+    )?;
+    assert_eq!(asdu.identifier.cot.cause().get(), Cause::ActivationCon);
+    assert_eq!(asdu.identifier.orig_addr, 3);
+    assert_eq!(
+        asdu.decode_payload()?,
+        AsduPayload::SingleCommand(SingleCommandInfo::new(0x0102, true, false))
+    );
+    Ok(())
+}
+
+#[test]
+fn payload_system_command() -> Result<()> {
+    let mut asdu = Asdu::from_payload(
+        identifier(TypeID::C_IC_NA_1, Cause::Activation),
+        AsduPayload::Interrogation(ObjectQOI::new(20)),
+    )?;
+    match asdu.decode_payload()? {
+        AsduPayload::Interrogation(mut qoi) => assert_eq!(qoi.range().get(), 20),
+        p => panic!("unexpected payload {:?}", p),
+    }
+
+    let mut asdu = Asdu::from_payload(
+        identifier(TypeID::C_CD_NA_1, Cause::Spontaneous),
+        AsduPayload::DelayAcquire(250),
+    )?;
+    assert_eq!(asdu.decode_payload()?, AsduPayload::DelayAcquire(250));
+    Ok(())
+}
+
+#[test]
+fn payload_type_id_mismatch() {
+    let r = Asdu::from_payload(
+        identifier(TypeID::M_SP_NA_1, Cause::Activation),
+        AsduPayload::Read(InfoObjAddr::new(0, 100)),
+    );
+    assert!(r.is_err());
+}
+
+#[test]
+fn payload_unknown_passthrough() -> Result<()> {
+    let raw = Bytes::from_static(&[0x01, 0x00, 0x00, 0x05]);
+    let mut asdu = Asdu::from_payload(
+        identifier(TypeID::M_ST_NA_1, Cause::Spontaneous),
+        AsduPayload::Unknown(raw.clone()),
+    )?;
+    assert_eq!(asdu.decode_payload()?, AsduPayload::Unknown(raw));
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::{future, time::Duration};
+
+use futures::StreamExt;
+use tokio::time::{timeout, Instant};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    csys::ObjectQOI,
+    mproc::{single, SinglePointInfo},
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, ClientPool, Error,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 作为被控站响应一次站召唤, 召唤过程中上送一个单点
+async fn answer_interrogation<T: tokio_iecp5::Transport>(
+    slave: &mut ScriptedPeer<T>,
+) -> anyhow::Result<()> {
+    let cmd = slave
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::Activation)
+        .await;
+    let ca = cmd.identifier.common_addr;
+    slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByStation);
+    slave
+        .send_asdu(single(
+            false,
+            cot,
+            ca,
+            vec![SinglePointInfo::new_single(1, true)],
+        )?)
+        .await?;
+    slave.send_asdu(cmd.mirror(Cause::ActivationTerm)).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_starts_and_interrogates_remotes() -> anyhow::Result<()> {
+    let (connector_a, mut streams_a) = duplex_connector();
+    let (connector_b, mut streams_b) = duplex_connector();
+    let stagger = Duration::from_millis(100);
+    let pool = ClientPool::new()
+        .with_remote(
+            "a",
+            [1],
+            Client::new_with_connector(NopHandler, ClientOption::default(), connector_a),
+        )
+        .with_remote(
+            "b",
+            [2],
+            Client::new_with_connector(NopHandler, ClientOption::default(), connector_b),
+        )
+        .with_interrogation(ObjectQOI::new(20), stagger);
+    assert_eq!(pool.names().collect::<Vec<_>>(), ["a", "b"]);
+
+    let mut events = pool.events();
+    let mut asdus = pool.asdu_stream().await;
+    pool.start().await?;
+
+    // 客户端连接后自动发送 STARTDT
+    let mut slave_a = ScriptedPeer::new(streams_a.recv().await.unwrap());
+    let mut slave_b = ScriptedPeer::new(streams_b.recv().await.unwrap());
+    slave_a.accept_start_dt().await?;
+    slave_b.accept_start_dt().await?;
+    let activated = Instant::now();
+
+    // 第二个远方站的总召唤错开 stagger
+    answer_interrogation(&mut slave_a).await?;
+    answer_interrogation(&mut slave_b).await?;
+    assert!(activated.elapsed() >= stagger);
+
+    let wait = Duration::from_secs(1);
+    let mut seen = Vec::new();
+    while seen.len() < 6 {
+        let (remote, asdu) = timeout(wait, asdus.next()).await?.unwrap();
+        seen.push((remote, asdu.identifier.type_id));
+    }
+    assert!(seen.contains(&("a".to_string(), TypeID::M_SP_NA_1)));
+    assert!(seen.contains(&("b".to_string(), TypeID::M_SP_NA_1)));
+
+    let mut activations = Vec::new();
+    while activations.len() < 2 {
+        let event = timeout(wait, events.recv()).await??;
+        if event.event == ClientEvent::Activated {
+            activations.push(event.remote);
+        }
+    }
+    activations.sort();
+    assert_eq!(activations, ["a", "b"]);
+
+    // 召唤完成后记录
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let health = pool.health("b").unwrap();
+    assert!(health.connected && health.active);
+    assert!(health.last_asdu.is_some());
+    assert!(health.last_interrogation.is_some());
+    assert_eq!(health.disconnects, 0);
+    assert!(pool.health("c").is_none());
+
+    // 连接断开后记录原因
+    drop(slave_a);
+    let event = loop {
+        let event = timeout(wait, events.recv()).await??;
+        if matches!(event.event, ClientEvent::Disconnected(_)) {
+            break event;
+        }
+    };
+    assert_eq!(event.remote, "a");
+    let health = pool.health_all();
+    assert_eq!(health[0].0, "a");
+    assert_eq!(health[0].1.disconnects, 1);
+    assert!(health[0].1.last_error.is_some());
+
+    // 重连后再次发送 STARTDT
+    let mut slave_a = ScriptedPeer::new(streams_a.recv().await.unwrap());
+    slave_a.accept_start_dt().await?;
+
+    // 被控站关闭连接, 不等待 STOPDT 确认
+    drop((slave_a, slave_b));
+    pool.stop().await;
+    assert!(!pool.client("b").unwrap().is_connected());
+    Ok(())
+}
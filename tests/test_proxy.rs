@@ -0,0 +1,96 @@
+#![cfg(feature = "proxy")]
+
+use std::future;
+
+use futures::StreamExt;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_iecp5::{
+    apci::{ApciKind, U_STARTDT_ACTIVE},
+    asdu::Asdu,
+    Client, ClientHandler, ClientOption, Codec, Error, ProxyConfig,
+};
+use tokio_util::codec::Framed;
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 握手完成后代理直接充当被控站, 隧道内应收到 STARTDT
+async fn expect_startdt(stream: TcpStream) -> anyhow::Result<()> {
+    let mut remote = Framed::new(stream, Codec::default());
+    let apdu = remote.next().await.unwrap()?;
+    match ApciKind::from(apdu.apci) {
+        ApciKind::U(u) => assert_eq!(u.function, U_STARTDT_ACTIVE),
+        _ => panic!("expect U-frame"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_connects_through_socks5() -> anyhow::Result<()> {
+    let proxy = TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_addr = proxy.local_addr()?;
+    let op = ClientOption::new_host("rtu1.substation:2404", false)
+        .with_proxy(ProxyConfig::socks5(proxy_addr).with_auth("user", "secret"));
+    let client = Client::new(NopHandler, op);
+    client.start().await?;
+
+    let (mut stream, _) = proxy.accept().await?;
+    let mut greeting = [0u8; 4];
+    stream.read_exact(&mut greeting).await?;
+    assert_eq!(greeting, [5, 2, 0, 2]);
+    stream.write_all(&[5, 2]).await?;
+
+    let mut auth = [0u8; 13];
+    stream.read_exact(&mut auth).await?;
+    assert_eq!(&auth, b"\x01\x04user\x06secret");
+    stream.write_all(&[1, 0]).await?;
+
+    // 主机名由代理解析
+    let host = b"rtu1.substation";
+    let mut req = vec![0u8; 5 + host.len() + 2];
+    stream.read_exact(&mut req).await?;
+    assert_eq!(&req[..5], &[5, 1, 0, 3, host.len() as u8]);
+    assert_eq!(&req[5..5 + host.len()], host);
+    assert_eq!(&req[5 + host.len()..], &2404u16.to_be_bytes());
+    stream.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?;
+
+    expect_startdt(stream).await?;
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_connects_through_http_connect() -> anyhow::Result<()> {
+    let proxy = TcpListener::bind("127.0.0.1:0").await?;
+    let op = ClientOption::new("192.0.2.10:2404".parse()?, false)
+        .with_proxy(ProxyConfig::http(proxy.local_addr()?).with_auth("user", "pass"));
+    let client = Client::new(NopHandler, op);
+    client.start().await?;
+
+    let (mut stream, _) = proxy.accept().await?;
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await?);
+    }
+    let head = String::from_utf8(head)?;
+    assert!(head.starts_with("CONNECT 192.0.2.10:2404 HTTP/1.1\r\n"));
+    assert!(head.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+    stream
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .await?;
+
+    expect_startdt(stream).await?;
+    client.stop().await;
+    Ok(())
+}
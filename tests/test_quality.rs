@@ -0,0 +1,69 @@
+use bit_struct::*;
+use tokio_iecp5::{
+    mproc::{ObjectBCR, ObjectDIQ, ObjectQDS, ObjectSIQ},
+    test_util::{spontaneous, spontaneous_siq},
+    PointCache, Quality, QualityFilter,
+};
+
+#[test]
+fn quality_from_descriptors() {
+    let siq = ObjectSIQ::new(true, false, true, false, u3!(0), true);
+    let q = Quality::from(siq);
+    assert!(q.is_invalid() && q.sb && !q.nt && !q.bl && !q.ov);
+
+    let diq = ObjectDIQ::new(false, true, false, true, u2!(0), u2!(2));
+    assert_eq!(
+        Quality::from(diq),
+        Quality {
+            nt: true,
+            bl: true,
+            ..Default::default()
+        }
+    );
+
+    let qds = ObjectQDS::new(false, false, false, false, u3!(0), true);
+    let q = Quality::from(qds);
+    assert!(q.ov && !q.is_good());
+    assert_eq!(ObjectQDS::from(q), qds);
+    assert!(Quality::from(ObjectQDS::of_defaults()).is_good());
+
+    let bcr = ObjectBCR {
+        invalid: true,
+        ca: false,
+        cy: false,
+        seq: 0,
+        value: 1,
+    };
+    assert!(Quality::from(&bcr).is_invalid());
+}
+
+#[test]
+fn point_cache_quality_filter() -> anyhow::Result<()> {
+    let good = spontaneous(1, 1, true);
+    let bad = spontaneous_siq(
+        1,
+        1,
+        ObjectSIQ::new(true, false, false, false, u3!(0), false),
+    );
+
+    let cache = PointCache::new();
+    cache.apply_with(&good, QualityFilter::Accept)?;
+    cache.apply_with(&bad, QualityFilter::Accept)?;
+    assert_eq!(cache.get_single(1, 1), Some(false));
+    assert!(cache.quality(1, 1).unwrap().is_invalid());
+
+    // 丢弃无效数据, 保留上一次的值与品质
+    let cache = PointCache::new();
+    cache.apply_with(&good, QualityFilter::Reject)?;
+    assert_eq!(cache.apply_with(&bad, QualityFilter::Reject)?, 0);
+    assert_eq!(cache.get_single(1, 1), Some(true));
+    assert!(cache.quality(1, 1).unwrap().is_good());
+
+    // 保留上一次的值, 品质置为无效
+    let cache = PointCache::new();
+    cache.apply_with(&good, QualityFilter::Flag)?;
+    assert_eq!(cache.apply_with(&bad, QualityFilter::Flag)?, 1);
+    assert_eq!(cache.get_single(1, 1), Some(true));
+    assert!(cache.quality(1, 1).unwrap().is_invalid());
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr},
+    mproc::{single, ObjectSIQ, SinglePointInfo},
+    Error, OverflowPolicy, Priority, SendQueue, SendQueueOption,
+};
+
+fn single_point(cause: Cause, addr: u16) -> Asdu {
+    let mut asdu = single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x0001,
+        vec![SinglePointInfo::new(
+            InfoObjAddr::new(0, addr),
+            ObjectSIQ::new_with_value(true),
+            None,
+        )],
+    )
+    .unwrap();
+    asdu.identifier.cot.cause().set(cause);
+    asdu
+}
+
+fn cause_of(asdu: &Asdu) -> Cause {
+    let mut cot = asdu.identifier.cot;
+    cot.cause().get()
+}
+
+#[test]
+fn send_queue_priority() {
+    let mut queue = SendQueue::new(SendQueueOption::default());
+    queue.push(single_point(Cause::Periodic, 1)).unwrap();
+    queue.push(single_point(Cause::Spontaneous, 2)).unwrap();
+    queue.push(single_point(Cause::ActivationCon, 3)).unwrap();
+    queue.push(single_point(Cause::Spontaneous, 4)).unwrap();
+    assert_eq!(queue.len(), 4);
+
+    let order: Vec<Cause> = std::iter::from_fn(|| queue.pop())
+        .map(|asdu| cause_of(&asdu))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            Cause::ActivationCon,
+            Cause::Spontaneous,
+            Cause::Spontaneous,
+            Cause::Periodic
+        ]
+    );
+    assert!(queue.is_empty());
+    assert_eq!(
+        Priority::of(&single_point(Cause::Background, 1)),
+        Priority::Background
+    );
+}
+
+#[test]
+fn send_queue_term_after_data() {
+    // 召唤与计数量召唤的响应数据与激活终止同一优先级
+    let term = Priority::of(&single_point(Cause::ActivationTerm, 1));
+    for cause in [
+        Cause::InterrogatedByStation,
+        Cause::InterrogatedByGroup1,
+        Cause::InterrogatedByGroup16,
+        Cause::RequestByGeneralCounter,
+        Cause::RequestByGroup1Counter,
+    ] {
+        assert_eq!(Priority::of(&single_point(cause, 1)), term, "{cause:?}");
+    }
+
+    let mut queue = SendQueue::new(SendQueueOption::default());
+    queue.push(single_point(Cause::ActivationCon, 1)).unwrap();
+    queue
+        .push(single_point(Cause::InterrogatedByStation, 2))
+        .unwrap();
+    assert_eq!(cause_of(&queue.pop().unwrap()), Cause::ActivationCon);
+    // 部分数据已发送后继续入队
+    queue
+        .push(single_point(Cause::InterrogatedByStation, 3))
+        .unwrap();
+    queue
+        .push(single_point(Cause::InterrogatedByGroup1, 4))
+        .unwrap();
+    queue.push(single_point(Cause::ActivationTerm, 5)).unwrap();
+    // 激活终止之后的命令确认仍可优先发送
+    queue.push(single_point(Cause::ActivationCon, 6)).unwrap();
+
+    let order: Vec<Cause> = std::iter::from_fn(|| queue.pop())
+        .map(|asdu| cause_of(&asdu))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            Cause::ActivationCon,
+            Cause::InterrogatedByStation,
+            Cause::InterrogatedByStation,
+            Cause::InterrogatedByGroup1,
+            Cause::ActivationTerm
+        ]
+    );
+}
+
+#[test]
+fn send_queue_overflow() {
+    let op = SendQueueOption::new(2);
+
+    let mut queue = SendQueue::new(op.with_overflow(OverflowPolicy::Reject));
+    queue.push(single_point(Cause::Spontaneous, 1)).unwrap();
+    queue.push(single_point(Cause::Spontaneous, 2)).unwrap();
+    assert!(matches!(
+        queue.push(single_point(Cause::Spontaneous, 3)),
+        Err(Error::ErrQueueFull)
+    ));
+
+    let mut queue = SendQueue::new(op.with_overflow(OverflowPolicy::DropNewest));
+    queue.push(single_point(Cause::Periodic, 1)).unwrap();
+    queue.push(single_point(Cause::Periodic, 2)).unwrap();
+    queue.push(single_point(Cause::Activation, 3)).unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!(cause_of(&queue.pop().unwrap()), Cause::Periodic);
+
+    // 队列满时丢弃优先级最低的 ASDU
+    let mut queue = SendQueue::new(op.with_overflow(OverflowPolicy::DropOldest));
+    queue.push(single_point(Cause::Periodic, 1)).unwrap();
+    queue.push(single_point(Cause::Spontaneous, 2)).unwrap();
+    queue.push(single_point(Cause::Activation, 3)).unwrap();
+    assert_eq!(cause_of(&queue.pop().unwrap()), Cause::Activation);
+    assert_eq!(cause_of(&queue.pop().unwrap()), Cause::Spontaneous);
+    assert!(queue.pop().is_none());
+
+    // 新 ASDU 的优先级更低时丢弃新 ASDU
+    queue.push(single_point(Cause::Activation, 1)).unwrap();
+    queue.push(single_point(Cause::Activation, 2)).unwrap();
+    queue.push(single_point(Cause::Periodic, 3)).unwrap();
+    assert_eq!(queue.len(), 2);
+    assert!(std::iter::from_fn(|| queue.pop()).all(|asdu| cause_of(&asdu) == Cause::Activation));
+}
+
+#[test]
+fn send_queue_offline() {
+    let mut queue = SendQueue::new(SendQueueOption::default());
+    queue.push(single_point(Cause::Spontaneous, 1)).unwrap();
+    assert!(queue.take_offline().is_empty());
+    assert!(queue.is_empty());
+
+    let mut queue = SendQueue::new(SendQueueOption::default().with_buffer_offline(true));
+    assert!(queue.is_buffered_offline(&single_point(Cause::Spontaneous, 1)));
+    assert!(!queue.is_buffered_offline(&single_point(Cause::Periodic, 1)));
+    queue.push(single_point(Cause::Periodic, 1)).unwrap();
+    queue.push(single_point(Cause::Spontaneous, 2)).unwrap();
+    queue.push(single_point(Cause::Activation, 3)).unwrap();
+    queue.retain_offline();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(cause_of(&queue.pop().unwrap()), Cause::Spontaneous);
+}
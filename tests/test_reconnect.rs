@@ -0,0 +1,84 @@
+use std::{future, io, time::Duration};
+
+use futures::future::BoxFuture;
+use tokio_iecp5::{
+    asdu::Asdu, Client, ClientEvent, ClientHandler, ClientOption, Connector, Error,
+    ReconnectPolicy, Transport,
+};
+
+#[test]
+fn reconnect_policy_delay() {
+    assert_eq!(ReconnectPolicy::Immediate.delay(3), Duration::ZERO);
+    assert_eq!(
+        ReconnectPolicy::Fixed(Duration::from_secs(5)).delay(3),
+        Duration::from_secs(5)
+    );
+
+    let backoff = ReconnectPolicy::ExponentialBackoff {
+        initial: Duration::from_secs(1),
+        max: Duration::from_secs(30),
+        jitter: false,
+    };
+    assert_eq!(backoff.delay(1), Duration::from_secs(1));
+    assert_eq!(backoff.delay(2), Duration::from_secs(2));
+    assert_eq!(backoff.delay(4), Duration::from_secs(8));
+    assert_eq!(backoff.delay(6), Duration::from_secs(30));
+    assert_eq!(backoff.delay(100), Duration::from_secs(30));
+}
+
+#[test]
+fn reconnect_policy_jitter() {
+    let backoff = ReconnectPolicy::ExponentialBackoff {
+        initial: Duration::from_secs(4),
+        max: Duration::from_secs(30),
+        jitter: true,
+    };
+    for attempt in 1..5 {
+        let delay = backoff.delay(attempt);
+        let full = Duration::from_secs(4 << (attempt - 1)).min(Duration::from_secs(30));
+        assert!(delay >= full / 2 && delay <= full);
+    }
+}
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+struct Unreachable;
+
+impl Connector for Unreachable {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>> {
+        Box::pin(async { Err(io::ErrorKind::ConnectionRefused.into()) })
+    }
+}
+
+#[tokio::test]
+async fn reconnect_attempts_are_reported() -> anyhow::Result<()> {
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, true)
+        .with_reconnect_policy(ReconnectPolicy::Immediate)
+        .with_max_retries(2);
+    let client = Client::new_with_connector(NopHandler, op, Unreachable);
+    let mut events = client.events();
+    client.start().await?;
+
+    for attempt in 1..=2 {
+        assert_eq!(
+            events.recv().await?,
+            ClientEvent::Reconnecting {
+                attempt,
+                delay: Duration::ZERO
+            }
+        );
+    }
+    // 超过最大重连次数后不再重连
+    let next = tokio::time::timeout(Duration::from_millis(200), events.recv()).await;
+    assert!(next.is_err());
+    Ok(())
+}
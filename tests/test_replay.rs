@@ -0,0 +1,170 @@
+use std::{future, io, net::SocketAddr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_iecp5::{
+    apci::{new_iframe, new_uframe, ApciKind, U_STARTDT_ACTIVE, U_STARTDT_CONFIRM},
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr, TypeID},
+    capture::PcapWriter,
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    mproc::{single, SinglePointInfo},
+    replay::{Origin, Replay},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, FrameObserver, Server, ServerHandler,
+};
+
+struct GiServer;
+
+impl ServerHandler for GiServer {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, _qoi: ObjectQOI) -> Self::Future {
+        future::ready(Ok(vec![
+            asdu.mirror(Cause::ActivationCon),
+            asdu.mirror(Cause::ActivationTerm),
+        ]))
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+const GI_SESSION: &str = "
+# 总召唤
+0 TX 68 04 07 00 00 00
+5 RX 68 04 0B 00 00 00
+10 TX 68 0E 00 00 00 00 64 01 06 00 01 00 00 00 00 14
+20 RX 68 0E 00 00 02 00 64 01 07 00 01 00 00 00 00 14
+25 RX 68 0E 02 00 02 00 64 01 0A 00 01 00 00 00 00 14
+";
+
+#[tokio::test]
+async fn replay_hex_as_master() -> anyhow::Result<()> {
+    let replay = Replay::from_hex(GI_SESSION)?.with_linger(Duration::from_millis(300));
+    let origins: Vec<_> = replay.records().iter().map(|r| r.origin).collect();
+    assert_eq!(
+        origins,
+        [
+            Origin::Master,
+            Origin::Slave,
+            Origin::Master,
+            Origin::Slave,
+            Origin::Slave
+        ]
+    );
+    assert_eq!(replay.records()[2].offset, Duration::from_millis(10));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((GiServer, stream)))
+        };
+        let _ = Server::new(listener).serve(&on_connected, |_err| {}).await;
+    });
+
+    let received = replay
+        .play_as_master(TcpStream::connect(addr).await?)
+        .await?;
+    assert!(matches!(
+        ApciKind::from(received[0].apci),
+        ApciKind::U(u) if u.function == U_STARTDT_CONFIRM
+    ));
+    let causes: Vec<_> = received
+        .iter()
+        .filter_map(|apdu| apdu.asdu.as_ref())
+        .map(|asdu| {
+            let mut cot = asdu.identifier.cot;
+            (asdu.identifier.type_id, cot.cause().get())
+        })
+        .collect();
+    assert_eq!(
+        causes,
+        [
+            (TypeID::C_IC_NA_1, Cause::ActivationCon),
+            (TypeID::C_IC_NA_1, Cause::ActivationTerm)
+        ]
+    );
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Forward(mpsc::UnboundedSender<Asdu>);
+
+impl ClientHandler for Forward {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        let _ = self.0.send(asdu);
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn replay_pcap_as_slave() -> anyhow::Result<()> {
+    // 以抓包写入器生成被控站一侧的记录
+    let path = std::env::temp_dir().join(format!("iec104-replay-{}.pcap", std::process::id()));
+    {
+        let writer =
+            PcapWriter::create(&path, "10.0.0.1:2404".parse()?, "10.0.0.2:40000".parse()?)?;
+        let now = std::time::Instant::now();
+        writer.on_rx(&new_uframe(U_STARTDT_ACTIVE), now);
+        writer.on_tx(&new_uframe(U_STARTDT_CONFIRM), now);
+        for (sn, ioa) in [(0, 100), (1, 101)] {
+            let asdu = single(
+                false,
+                CauseOfTransmission::new(false, false, Cause::Spontaneous),
+                0x0001,
+                vec![SinglePointInfo::new_single(ioa, true)],
+            )?;
+            writer.on_tx(&new_iframe(asdu, sn, 0), now);
+        }
+    }
+    let replay = Replay::from_pcap(std::fs::File::open(&path)?, 2404)?.with_speed(0.0);
+    std::fs::remove_file(&path)?;
+    assert_eq!(replay.records().len(), 4);
+    assert_eq!(replay.records()[0].origin, Origin::Master);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let slave = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        replay.play_as_slave(stream).await
+    });
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let client = Client::new(Forward(tx), ClientOption::new(addr, false));
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+    for expect in [100, 101] {
+        let mut asdu = rx.recv().await.unwrap();
+        let mut infos = asdu.get_single_point()?;
+        assert_eq!(infos[0].ioa.addr().get(), expect);
+    }
+
+    let received = slave.await??;
+    assert!(matches!(
+        ApciKind::from(received[0].apci),
+        ApciKind::U(u) if u.function == U_STARTDT_ACTIVE
+    ));
+    Ok(())
+}
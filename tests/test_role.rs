@@ -0,0 +1,205 @@
+use std::{
+    future, io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_iecp5::{
+    apci::U_STOPDT_ACTIVE,
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID},
+    cproc::{single_cmd, SingleCommandInfo},
+    csys::{clock_synchronization_cmd, test_command},
+    mproc::{single, SinglePointInfo},
+    server_handler_fn,
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, Role, Server,
+};
+
+// 作为被控站, 对收到的命令回复激活确认
+#[derive(Clone)]
+struct ConfirmHandler;
+
+impl ClientHandler for ConfirmHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)]))
+    }
+}
+
+// 拒绝测试命令, 记录对端的校时时间
+#[derive(Clone, Default)]
+struct ReverseHandler {
+    synced: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl ClientHandler for ReverseHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+
+    fn call_test(&self, _asdu: &Asdu) -> bool {
+        false
+    }
+
+    fn call_clock_sync(&self, _asdu: &Asdu, time: Option<DateTime<Utc>>) -> bool {
+        *self.synced.lock().unwrap() = time;
+        true
+    }
+}
+
+fn command() -> Asdu {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    single_cmd(
+        TypeID::C_SC_NA_1,
+        cot,
+        1,
+        SingleCommandInfo::new(100, true, false),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn client_as_controlled_station() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default().with_role(Role::Controlled);
+    let client = Client::new_with_connector(ConfirmHandler, option, connector);
+    let mut events = client.events();
+    client.start().await?;
+
+    // 被控站不发送 STARTDT, 等待控制站激活
+    let mut master = ScriptedPeer::new(streams.recv().await.unwrap());
+    master.expect_silence(Duration::from_millis(200)).await;
+    assert!(matches!(
+        client.send_start_dt().await,
+        Err(Error::ErrNotControlling("STARTDT"))
+    ));
+    master.start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+    assert!(client.is_active());
+
+    master.send_asdu(command()).await?;
+    master
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::ActivationCon)
+        .await;
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    client
+        .send_asdu(single(
+            false,
+            cot,
+            1,
+            vec![SinglePointInfo::new_single(1, true)],
+        )?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::M_SP_NA_1, Cause::Spontaneous)
+        .await;
+
+    master.stop_dt().await?;
+    while events.recv().await? != ClientEvent::Deactivated {}
+    drop(master);
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_as_controlling_station() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = Arc::new(Server::new(listener).with_role(Role::Controlling));
+    let (received_tx, mut received) = mpsc::unbounded_channel();
+    let handler = server_handler_fn(
+        move |asdu: Asdu| {
+            let _ = received_tx.send(asdu);
+            future::ready(Ok(Vec::new()))
+        },
+        |_, _| future::ready(Ok(Vec::new())),
+        |_, _| future::ready(Ok(Vec::new())),
+    );
+    let s = server.clone();
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| {
+            let handler = handler.clone();
+            async move { io::Result::Ok(Some((handler, stream))) }
+        };
+        let _ = s.serve(&on_connected, |_err| {}).await;
+    });
+
+    // 被控站发起连接, 由控制站发送 STARTDT
+    let mut slave = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    slave.accept_start_dt().await?;
+    while server.sessions().iter().all(|(_, active)| !active) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // 监视方向的数据交给 ServerHandler::call
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    slave
+        .send_asdu(single(
+            false,
+            cot,
+            1,
+            vec![SinglePointInfo::new_single(7, true)],
+        )?)
+        .await?;
+    let asdu = received.recv().await.unwrap();
+    assert_eq!(asdu.identifier.type_id, TypeID::M_SP_NA_1);
+
+    // 经 ServerHandle 发送命令
+    server.handle().broadcast_asdu(command())?;
+    let cmd = slave
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::Activation)
+        .await;
+    slave.send_asdu(cmd.mirror(Cause::ActivationCon)).await?;
+    let mut con = received.recv().await.unwrap();
+    assert_eq!(con.identifier.cot.cause().get(), Cause::ActivationCon);
+
+    // 控制站不响应对端的 STARTDT/STOPDT
+    slave.send_u(U_STOPDT_ACTIVE).await?;
+    slave.expect_silence(Duration::from_millis(200)).await;
+    server.shutdown();
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_confirms_reverse_commands() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let option = ClientOption::default().with_role(Role::Controlled);
+    let handler = ReverseHandler::default();
+    let client = Client::new_with_connector(handler.clone(), option, connector);
+    client.start().await?;
+    let mut master = ScriptedPeer::new(streams.recv().await.unwrap());
+    master.start_dt().await?;
+
+    let act = CauseOfTransmission::new(false, false, Cause::Activation);
+    let time = Utc.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap();
+    master
+        .send_asdu(clock_synchronization_cmd(act, 1, time)?)
+        .await?;
+    let con = master
+        .expect_asdu_with(TypeID::C_CS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(!con.identifier.cot.is_negative());
+    assert_eq!(*handler.synced.lock().unwrap(), Some(time));
+
+    // 钩子返回 false 时回复否定确认
+    master.send_asdu(test_command(act, 1)?).await?;
+    let con = master
+        .expect_asdu_with(TypeID::C_TS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(con.identifier.cot.is_negative());
+
+    drop(master);
+    client.stop().await;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, TypeID, GLOBAL_COMMON_ADDR},
+    csys::{interrogation_cmd, ObjectQOI},
+    test_util::{serve_in_memory, ScriptedPeer},
+    CaRouter, DataStore, Point, PointValue, Server,
+};
+
+fn station(ca: u16, ioa: u16) -> DataStore {
+    let store = DataStore::new();
+    store.insert(ca, ioa, Point::new(PointValue::Single(true)));
+    store
+}
+
+fn gi(ca: u16) -> anyhow::Result<Asdu> {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    Ok(interrogation_cmd(cot, ca, ObjectQOI::new(20))?)
+}
+
+#[tokio::test]
+async fn router_dispatches_by_common_addr() -> anyhow::Result<()> {
+    let router = CaRouter::new()
+        .with_route(1, station(1, 100))
+        .with_route(2, station(2, 200));
+    assert_eq!(router.common_addrs(), vec![1, 2]);
+    let mut master = serve_in_memory(router);
+    master.start_dt().await?;
+
+    for (ca, ioa) in [(1, 100), (2, 200)] {
+        master.send_asdu(gi(ca)?).await?;
+        master
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+            .await;
+        let mut data = master
+            .expect_asdu_with(TypeID::M_SP_NA_1, Cause::InterrogatedByStation)
+            .await;
+        assert_eq!(data.identifier.common_addr, ca);
+        assert_eq!(data.get_single_point()?[0].ioa.addr().get(), ioa);
+        master
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationTerm)
+            .await;
+    }
+
+    master.send_asdu(gi(3)?).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::UnknownCA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn datastore_rejects_unknown_common_addr() -> anyhow::Result<()> {
+    let mut master = serve_in_memory(station(1, 100));
+    master.start_dt().await?;
+    master.send_asdu(gi(2)?).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::UnknownCA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_routes_broadcast_and_rejects_unknown() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let router = CaRouter::new()
+        .with_route(1, station(1, 100))
+        .with_route(2, station(2, 200));
+    let server = Server::new(listener).with_common_addrs(router.common_addrs());
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _| {
+            let router = router.clone();
+            async move { std::io::Result::Ok(Some((router, stream))) }
+        };
+        let _ = server.serve(&on_connected, |_err| {}).await;
+    });
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+
+    // 广播的总召唤由各逻辑站分别响应, 确认优先发送, 同一逻辑站的响应保持顺序
+    master.send_asdu(gi(GLOBAL_COMMON_ADDR)?).await?;
+    let mut responses: [Vec<Cause>; 2] = Default::default();
+    for _ in 0..6 {
+        let asdu = master.expect_asdu().await;
+        let mut cot = asdu.identifier.cot;
+        let ca = asdu.identifier.common_addr;
+        assert!(ca == 1 || ca == 2);
+        responses[ca as usize - 1].push(cot.cause().get());
+    }
+    for causes in responses {
+        assert_eq!(
+            causes,
+            vec![
+                Cause::ActivationCon,
+                Cause::InterrogatedByStation,
+                Cause::ActivationTerm
+            ]
+        );
+    }
+
+    // 未配置的公共地址由服务端直接拒绝
+    master.send_asdu(gi(3)?).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::UnknownCA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
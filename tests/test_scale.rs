@@ -0,0 +1,73 @@
+use tokio_iecp5::{
+    asdu::{Cause, CauseOfTransmission, InfoObjAddr},
+    mproc::{measured_value_normal, MeasuredValueNormalInfo},
+    DataStore, Nva, PointCache, PointValue, ScaleTable,
+};
+
+#[test]
+fn nva_conversion() {
+    assert_eq!(Nva::from_f32(0.5), Nva(16384));
+    assert_eq!(Nva::from_f32(-1.0), Nva(i16::MIN));
+    // 超出范围取边界值
+    assert_eq!(Nva::from_f32(1.0), Nva(i16::MAX));
+    assert_eq!(Nva::from_f32(-2.0), Nva(i16::MIN));
+    assert_eq!(Nva(-16384).to_f32(), -0.5);
+    assert_eq!(i16::from(Nva::from(100)), 100);
+}
+
+#[test]
+fn scale_table_conversion() {
+    let table = ScaleTable::new(0.0, 200.0);
+    assert_eq!(table.to_nva(100.0), Nva(0));
+    assert_eq!(table.to_nva(0.0), Nva(i16::MIN));
+    assert_eq!(table.to_engineering(Nva(0)), 100.0);
+    assert_eq!(table.to_engineering(Nva(i16::MIN)), 0.0);
+    assert!((table.to_engineering(table.to_nva(150.0)) - 150.0).abs() < 0.01);
+    assert_eq!(ScaleTable::new(5.0, 5.0).to_nva(5.0), Nva(0));
+}
+
+#[test]
+fn point_cache_engineering_value() -> anyhow::Result<()> {
+    let cache = PointCache::new();
+    cache.set_scale(1, 10, ScaleTable::new(-50.0, 150.0));
+    let infos = [10, 11]
+        .into_iter()
+        .map(|addr| MeasuredValueNormalInfo {
+            ioa: InfoObjAddr::new(0, addr),
+            nva: Nva::from_f32(0.5).into(),
+            qds: None,
+            time: None,
+        })
+        .collect();
+    cache.apply(&measured_value_normal(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        1,
+        infos,
+    )?)?;
+    assert_eq!(cache.get_engineering(1, 10), Some(100.0));
+    // 未设置换算表时为规一化值
+    assert_eq!(cache.get_engineering(1, 11), Some(0.5));
+    assert_eq!(cache.get_engineering(1, 12), None);
+    Ok(())
+}
+
+#[test]
+fn datastore_engineering_value() -> anyhow::Result<()> {
+    let store = DataStore::new();
+    store.set_scale(1, 10, ScaleTable::new(0.0, 10.0));
+    let asdus = store.set_engineering(1, 10, 7.5)?;
+    assert_eq!(asdus.len(), 1);
+    assert_eq!(
+        store.get(1, 10).unwrap().value,
+        PointValue::Normalized(16384)
+    );
+    assert_eq!(store.get_engineering(1, 10), Some(7.5));
+
+    store.set_engineering(1, 11, -0.25)?;
+    assert_eq!(
+        store.get(1, 11).unwrap().value,
+        PointValue::Normalized(-8192)
+    );
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use tokio_iecp5::{
+    asdu::{Cause, TypeID},
+    test_util::{assert_asdu, serve_in_memory_with_handle},
+    DataStore, Point, PointValue, Scheduler,
+};
+
+fn store() -> DataStore {
+    let store = DataStore::new();
+    store.insert(1, 1, Point::new(PointValue::Single(true)).with_group(1));
+    store.insert(1, 10, Point::new(PointValue::Float(1.5)).with_group(1));
+    store.insert(1, 11, Point::new(PointValue::Scaled(7)).with_group(2));
+    store.insert(1, 20, Point::new(PointValue::Counter(42)).with_group(1));
+    store
+}
+
+#[test]
+fn datastore_cyclic_and_background() -> anyhow::Result<()> {
+    let store = store();
+    let mut asdus = store.cyclic(1, 0)?;
+    assert_eq!(asdus.len(), 2);
+    assert_asdu(&asdus[0], TypeID::M_ME_NB_1, Cause::Periodic);
+    assert_asdu(&asdus[1], TypeID::M_ME_NC_1, Cause::Periodic);
+    assert_eq!(asdus[1].get_measured_value_float()?[0].r, 1.5);
+    assert_eq!(store.cyclic(1, 2)?.len(), 1);
+
+    let asdus = store.background(1, 1)?;
+    assert_eq!(asdus.len(), 2);
+    assert_asdu(&asdus[0], TypeID::M_SP_NA_1, Cause::Background);
+    assert_asdu(&asdus[1], TypeID::M_ME_NC_1, Cause::Background);
+    assert!(store.background(2, 0)?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn scheduler_sends_to_active_session() -> anyhow::Result<()> {
+    let store = store();
+    let (mut master, handle) = serve_in_memory_with_handle(store.clone());
+    master.start_dt().await?;
+    let scheduler = Scheduler::new(store)
+        .with_cyclic(1, 2, Duration::from_millis(50))
+        .with_background(1, 1, Duration::from_secs(3600))
+        .spawn(handle);
+
+    for _ in 0..2 {
+        let mut asdu = master
+            .expect_asdu_with(TypeID::M_ME_NB_1, Cause::Periodic)
+            .await;
+        assert_eq!(asdu.get_measured_value_scaled()?[0].sva, 7);
+    }
+    scheduler.abort();
+    master.expect_silence(Duration::from_millis(150)).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn scheduler_background_scan() -> anyhow::Result<()> {
+    let store = store();
+    let (mut master, handle) = serve_in_memory_with_handle(store.clone());
+    master.start_dt().await?;
+    let scheduler = Scheduler::new(store)
+        .with_background(1, 0, Duration::from_millis(50))
+        .spawn(handle);
+
+    master
+        .expect_asdu_with(TypeID::M_SP_NA_1, Cause::Background)
+        .await;
+    master
+        .expect_asdu_with(TypeID::M_ME_NB_1, Cause::Background)
+        .await;
+    master
+        .expect_asdu_with(TypeID::M_ME_NC_1, Cause::Background)
+        .await;
+    scheduler.abort();
+    Ok(())
+}
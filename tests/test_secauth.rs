@@ -0,0 +1,245 @@
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use tokio_iecp5::asdu::*;
+use tokio_iecp5::mproc::ObjectBCR;
+use tokio_iecp5::secauth::*;
+
+fn authentication_cot() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::Authentication)
+}
+
+fn session_key_cot() -> CauseOfTransmission {
+    CauseOfTransmission::new(false, false, Cause::SessionKey)
+}
+
+// 测试用的 MAC: 数据的逐字节异或和按位置展开, 不具备安全性
+struct XorMac;
+
+impl MacCalculator for XorMac {
+    fn algorithm(&self) -> u8 {
+        MAL_HMAC_SHA256_8
+    }
+
+    fn calculate(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = vec![0u8; 32];
+        for (i, b) in data.iter().enumerate() {
+            mac[i % 32] ^= b.wrapping_add(i as u8);
+        }
+        mac
+    }
+}
+
+#[test]
+fn encode_and_decode_challenge() -> Result<()> {
+    let challenge = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+    let asdu = authentication_challenge(
+        authentication_cot(),
+        0x0001,
+        AuthenticationChallengeInfo::new(0, 7, 1, MAL_HMAC_SHA256_8, challenge.clone()),
+    )?;
+    assert_eq!(asdu.identifier.type_id, TypeID::S_CH_NA_1);
+
+    let raw: Bytes = asdu.try_into()?;
+    assert_eq!(
+        raw,
+        Bytes::from_static(&[
+            u8::from(TypeID::S_CH_NA_1),
+            0x01,
+            0x0e,
+            0x00,
+            0x01,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x07,
+            0x00,
+            0x00,
+            0x00,
+            0x01,
+            0x00,
+            MAL_HMAC_SHA256_8,
+            RSC_CRITICAL,
+            0x04,
+            0x00,
+            0xde,
+            0xad,
+            0xbe,
+            0xef,
+        ])
+    );
+
+    let mut asdu: Asdu = raw.try_into()?;
+    let info = asdu.get_authentication_challenge()?;
+    assert_eq!(
+        info,
+        AuthenticationChallengeInfo::new(0, 7, 1, MAL_HMAC_SHA256_8, challenge)
+    );
+    Ok(())
+}
+
+#[test]
+fn challenge_reply_mac() -> Result<()> {
+    let critical: Bytes =
+        Bytes::from_static(&[0x2d, 0x01, 0x06, 0x00, 0x01, 0x00, 0x05, 0x00, 0x00, 0x81]);
+    let challenge: Bytes = authentication_challenge(
+        authentication_cot(),
+        0x0001,
+        AuthenticationChallengeInfo::new(
+            0,
+            7,
+            1,
+            MAL_HMAC_SHA256_8,
+            Bytes::from_static(&[1, 2, 3, 4]),
+        ),
+    )?
+    .try_into()?;
+
+    let mac = calculate_mac(&XorMac, &[&challenge, &critical])?;
+    assert_eq!(mac.len(), mac_length(MAL_HMAC_SHA256_8).unwrap());
+    let reply: Bytes = authentication_reply(
+        authentication_cot(),
+        0x0001,
+        AuthenticationReplyInfo::new(0, 7, 1, mac),
+    )?
+    .try_into()?;
+
+    let mut reply: Asdu = reply.try_into()?;
+    assert_eq!(reply.identifier.type_id, TypeID::S_RP_NA_1);
+    let info = reply.get_authentication_reply()?;
+    assert_eq!(info.csq, 7);
+    assert!(verify_mac(&XorMac, &[&challenge, &critical], &info.mac));
+    assert!(!verify_mac(
+        &XorMac,
+        &[&challenge, &critical[1..]],
+        &info.mac
+    ));
+    assert!(!verify_mac(
+        &XorMac,
+        &[&challenge, &critical],
+        &info.mac[1..]
+    ));
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_aggressive_mode() -> Result<()> {
+    let critical =
+        Bytes::from_static(&[0x2d, 0x01, 0x06, 0x00, 0x01, 0x00, 0x05, 0x00, 0x00, 0x81]);
+    let mac = Bytes::from_static(&[9; 8]);
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let asdu = aggressive_mode_request(
+        cot,
+        0x0001,
+        AggressiveModeInfo::new(0, critical.clone(), 8, 1, mac.clone()),
+    )?;
+    let raw: Bytes = asdu.try_into()?;
+    let mut asdu: Asdu = raw.try_into()?;
+    assert_eq!(asdu.identifier.type_id, TypeID::S_AR_NA_1);
+    assert_eq!(
+        asdu.get_aggressive_mode_request()?,
+        AggressiveModeInfo::new(0, critical.clone(), 8, 1, mac)
+    );
+
+    let mut inner: Asdu = critical.try_into()?;
+    assert_eq!(inner.identifier.type_id, TypeID::C_SC_NA_1);
+    assert_eq!(inner.get_single_cmd()?.ioa.addr().get(), 5);
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_session_key() -> Result<()> {
+    let mut asdu = key_status_request(session_key_cot(), 0x0001, 0, 1)?;
+    assert_eq!(asdu.identifier.type_id, TypeID::S_KR_NA_1);
+    let (mut ioa, usr) = asdu.get_key_status_request()?;
+    assert_eq!((ioa.addr().get(), usr), (0, 1));
+
+    let status = KeyStatusInfo {
+        ioa: InfoObjAddr::new(0, 0),
+        ksq: 3,
+        usr: 1,
+        kwa: KWA_AES_128,
+        kst: KST_NOT_INIT,
+        mal: MAL_HMAC_SHA256_8,
+        challenge: Bytes::from_static(&[5; 4]),
+        mac: Bytes::new(),
+    };
+    let raw: Bytes = key_status(session_key_cot(), 0x0001, status)?.try_into()?;
+    let mut asdu: Asdu = raw.try_into()?;
+    let info = asdu.get_key_status()?;
+    assert_eq!(info.kst, KST_NOT_INIT);
+    assert_eq!(info.challenge, Bytes::from_static(&[5; 4]));
+    assert!(info.mac.is_empty());
+
+    let change = SessionKeyChangeInfo {
+        ioa: InfoObjAddr::new(0, 0),
+        ksq: 3,
+        usr: 1,
+        wrapped_key: Bytes::from_static(&[7; 40]),
+    };
+    let raw: Bytes = session_key_change(session_key_cot(), 0x0001, change)?.try_into()?;
+    let mut asdu: Asdu = raw.try_into()?;
+    assert_eq!(asdu.get_session_key_change()?.wrapped_key.len(), 40);
+    Ok(())
+}
+
+#[test]
+fn encode_and_decode_error_and_statistic() -> Result<()> {
+    let time = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 15).unwrap();
+    let error = AuthenticationErrorInfo {
+        ioa: InfoObjAddr::new(0, 0),
+        csq: 7,
+        usr: 1,
+        aid: 2,
+        err: ERR_AUTHENTICATION_FAILED,
+        time: Some(time),
+        text: Bytes::from_static(b"bad mac"),
+    };
+    let raw: Bytes = authentication_error(authentication_cot(), 0x0001, error)?.try_into()?;
+    let mut asdu: Asdu = raw.try_into()?;
+    let info = asdu.get_authentication_error()?;
+    assert_eq!(info.err, ERR_AUTHENTICATION_FAILED);
+    assert_eq!(info.time, Some(time));
+    assert_eq!(info.text, Bytes::from_static(b"bad mac"));
+
+    let statistic = SecurityStatisticInfo {
+        ioa: InfoObjAddr::new(0, 3),
+        aid: 2,
+        bcr: ObjectBCR {
+            invalid: false,
+            ca: false,
+            cy: false,
+            seq: 1,
+            value: 12,
+        },
+        time: Some(time),
+    };
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    let raw: Bytes = security_statistic(cot, 0x0001, statistic)?.try_into()?;
+    let mut asdu: Asdu = raw.try_into()?;
+    let info = asdu.get_security_statistic()?;
+    assert_eq!((info.aid, info.bcr.value, info.time), (2, 12, Some(time)));
+    Ok(())
+}
+
+#[test]
+fn secauth_invalid_cause_and_truncated() {
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    assert!(key_status_request(cot, 0x0001, 0, 1).is_err());
+    assert!(authentication_reply(
+        cot,
+        0x0001,
+        AuthenticationReplyInfo::new(0, 1, 1, Bytes::new())
+    )
+    .is_err());
+
+    let mut asdu = authentication_challenge(
+        authentication_cot(),
+        0x0001,
+        AuthenticationChallengeInfo::new(0, 7, 1, MAL_HMAC_SHA256_8, Bytes::from_static(&[1, 2])),
+    )
+    .unwrap();
+    asdu.raw = asdu.raw.slice(..asdu.raw.len() - 1);
+    assert!(asdu.get_authentication_challenge().is_err());
+}
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use chrono::Utc;
+use proptest::prelude::*;
+use tokio_iecp5::{
+    apci::{update_ack_no_out, SeqNum},
+    SeqPending,
+};
+
+// 从 start 开始已发送 sent 个未确认的 I 帧: (ack_sendsn, send_sn, pending)
+fn window(start: u16, sent: u16) -> (SeqNum, SeqNum, VecDeque<SeqPending>) {
+    let start = SeqNum::new(start);
+    let pending = (0..sent)
+        .map(|i| SeqPending {
+            seq: start.wrapping_add(i),
+            send_time: Utc::now(),
+        })
+        .collect();
+    (start, start.wrapping_add(sent), pending)
+}
+
+proptest! {
+    #[test]
+    fn new_keeps_low_15_bits(v in any::<u16>()) {
+        prop_assert_eq!(SeqNum::new(v).value(), v & 0x7fff);
+    }
+
+    #[test]
+    fn next_wraps_at_modulus(v in 0u16..SeqNum::MODULUS) {
+        prop_assert_eq!(SeqNum::new(v).next().value(), (v + 1) % SeqNum::MODULUS);
+    }
+
+    #[test]
+    fn distance_inverts_wrapping_add(v in 0u16..SeqNum::MODULUS, n in 0u16..SeqNum::MODULUS) {
+        let sn = SeqNum::new(v);
+        prop_assert_eq!(sn.distance(sn.wrapping_add(n)), n);
+        prop_assert_eq!(sn.wrapping_add(n).wrapping_add(SeqNum::MODULUS - n), sn);
+    }
+
+    #[test]
+    fn ack_inside_window_removes_acknowledged(
+        start in 0u16..SeqNum::MODULUS,
+        sent in 0u16..=12,
+        acked in 0u16..=12
+    ) {
+        prop_assume!(acked <= sent);
+        let (mut ack_sendsn, mut send_sn, mut pending) = window(start, sent);
+        let ack_no = ack_sendsn.wrapping_add(acked);
+        prop_assert!(update_ack_no_out(ack_no, &mut ack_sendsn, &mut send_sn, &mut pending));
+        prop_assert_eq!(ack_sendsn, ack_no);
+        prop_assert_eq!(pending.len(), usize::from(sent - acked));
+        prop_assert_eq!(pending.front().map(|p| p.seq), (acked < sent).then_some(ack_no));
+    }
+
+    #[test]
+    fn ack_outside_window_is_rejected(
+        start in 0u16..SeqNum::MODULUS,
+        sent in 0u16..=12,
+        beyond in 1u16..1000
+    ) {
+        let (mut ack_sendsn, mut send_sn, mut pending) = window(start, sent);
+        // 超过 send_sn 或早于 ack_sendsn
+        for ack_no in [
+            send_sn.wrapping_add(beyond),
+            ack_sendsn.wrapping_add(SeqNum::MODULUS - beyond),
+        ] {
+            prop_assert!(!update_ack_no_out(ack_no, &mut ack_sendsn, &mut send_sn, &mut pending));
+            prop_assert_eq!(ack_sendsn, SeqNum::new(start));
+            prop_assert_eq!(pending.len(), usize::from(sent));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "serde")]
+
+use serde::{
+    de::{
+        value::{
+            Error, StrDeserializer, U16Deserializer, U32Deserializer, U64Deserializer,
+            U8Deserializer,
+        },
+        DeserializeOwned, IntoDeserializer,
+    },
+    Deserialize, Serialize,
+};
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, Identifier, InfoObjAddr, Ioa, StationAddr, TypeID},
+    cproc::{ObjectSCO, SingleCommandInfo},
+    mproc::{MeasuredValueFloatInfo, ObjectBCR, ObjectQDS, ObjectSCD, ObjectSIQ, SinglePointInfo},
+    payload::AsduPayload,
+    time::Cp56Time2a,
+    Nva, Point, PointValue, Quality, ScaleTable,
+};
+
+fn assert_serde<T: Serialize + DeserializeOwned>() {}
+
+#[test]
+fn types_implement_serde() {
+    assert_serde::<Asdu>();
+    assert_serde::<Identifier>();
+    assert_serde::<TypeID>();
+    assert_serde::<Cause>();
+    assert_serde::<SinglePointInfo>();
+    assert_serde::<MeasuredValueFloatInfo>();
+    assert_serde::<SingleCommandInfo>();
+    assert_serde::<ObjectBCR>();
+    assert_serde::<AsduPayload>();
+    assert_serde::<Cp56Time2a>();
+    assert_serde::<Point>();
+    assert_serde::<PointValue>();
+    assert_serde::<Quality>();
+    assert_serde::<Nva>();
+    assert_serde::<ScaleTable>();
+}
+
+#[test]
+fn bit_structs_from_raw_value() {
+    let de: U8Deserializer<Error> = 0x81u8.into_deserializer();
+    let mut siq = ObjectSIQ::deserialize(de).unwrap();
+    assert!(siq.invalid().get());
+    assert!(siq.spi().get());
+
+    let de: U8Deserializer<Error> = 0x06u8.into_deserializer();
+    let mut cot = CauseOfTransmission::deserialize(de).unwrap();
+    assert_eq!(cot.cause().get(), Cause::Activation);
+
+    let de: U8Deserializer<Error> = 0x10u8.into_deserializer();
+    assert!(ObjectQDS::deserialize(de).is_ok());
+    let de: U8Deserializer<Error> = 0x81u8.into_deserializer();
+    assert!(ObjectSCO::deserialize(de).is_ok());
+
+    let de: U32Deserializer<Error> = 0x010203u32.into_deserializer();
+    let ioa = InfoObjAddr::deserialize(de).unwrap();
+    assert_eq!(ioa.raw().value(), 0x010203);
+    let de: U32Deserializer<Error> = 0x1000000u32.into_deserializer();
+    assert!(InfoObjAddr::deserialize(de).is_err());
+
+    let de: U64Deserializer<Error> = 0x00_ffff_0001u64.into_deserializer();
+    let mut scd = ObjectSCD::deserialize(de).unwrap();
+    assert_eq!(scd.vflag().get(), 0xffff);
+    assert_eq!(scd.spi().get(), 0x0001);
+}
+
+#[test]
+fn cause_by_number() {
+    let de: U8Deserializer<Error> = 20u8.into_deserializer();
+    assert_eq!(
+        Cause::deserialize(de).unwrap(),
+        Cause::InterrogatedByStation
+    );
+    let de: U8Deserializer<Error> = 0x46u8.into_deserializer();
+    assert!(Cause::deserialize(de).is_err());
+}
+
+#[test]
+fn validated_addresses() {
+    let de: U32Deserializer<Error> = 100u32.into_deserializer();
+    assert_eq!(Ioa::deserialize(de).unwrap(), Ioa::new(100).unwrap());
+    let de: U32Deserializer<Error> = 0u32.into_deserializer();
+    assert!(Ioa::deserialize(de).is_err());
+
+    let de: U16Deserializer<Error> = 1u16.into_deserializer();
+    assert_eq!(StationAddr::deserialize(de).unwrap().get(), 1);
+    let de: U16Deserializer<Error> = 0u16.into_deserializer();
+    assert!(StationAddr::deserialize(de).is_err());
+}
+
+#[test]
+fn type_id_by_name() {
+    let de: StrDeserializer<Error> = "M_ME_NC_1".into_deserializer();
+    assert_eq!(TypeID::deserialize(de).unwrap(), TypeID::M_ME_NC_1);
+    let de: StrDeserializer<Error> = "M_XX_NA_1".into_deserializer();
+    assert!(TypeID::deserialize(de).is_err());
+}
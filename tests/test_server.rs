@@ -0,0 +1,894 @@
+use std::{
+    future, io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_iecp5::{
+    apci::{new_iframe, new_uframe, ApciKind, U_STARTDT_ACTIVE, U_STARTDT_CONFIRM},
+    asdu::{Asdu, AsduParams, Cause, CauseOfTransmission, InfoObjAddr, TypeID, GLOBAL_COMMON_ADDR},
+    cproc::{double_cmd, single_cmd, DoubleCommandInfo, SingleCommandInfo},
+    csys::{
+        clock_synchronization_cmd, clock_synchronization_cmd_cp56, interrogation_cmd,
+        reset_process_cmd, test_command, ObjectQCC, ObjectQOI, ObjectQRP, Qrp,
+    },
+    mproc::{single, ObjectSIQ, SinglePointInfo},
+    msys::ObjectCOI,
+    test_util::{serve_in_memory, ScriptedPeer},
+    time::Cp56Time2a,
+    AccessControl, Apdu, Authorization, Cidr, Client, ClientEvent, ClientHandler, ClientOption,
+    ClockSyncMode, Codec, CommandAgePolicy, CommandRequest, Decision, Error, FrameObserver,
+    InactiveIFramePolicy, SendQueueOption, Server, ServerHandler, ServerOption,
+};
+use tokio_util::codec::Framed;
+
+struct NopServer;
+
+impl ServerHandler for NopServer {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, qoi: ObjectQOI) -> Self::Future {
+        // 以构造函数生成的确认, 源发站地址为 0
+        let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+        let con = interrogation_cmd(cot, asdu.identifier.common_addr, qoi).map(|mut con| {
+            con.identifier.cot.cause().set(Cause::ActivationCon);
+            vec![con]
+        });
+        future::ready(con)
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    // 不支持的单命令回复否定的激活确认
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        if asdu.identifier.type_id == TypeID::C_SC_NA_1 {
+            return future::ready(Ok(vec![asdu.mirror_negative(Cause::ActivationCon)]));
+        }
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+async fn start_server(server: Server) -> Arc<Server> {
+    let server = Arc::new(server);
+    let s = server.clone();
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((NopServer, stream)))
+        };
+        let _ = s.serve(&on_connected, |_err| {}).await;
+    });
+    server
+}
+
+async fn start_dt(addr: SocketAddr) -> anyhow::Result<Framed<TcpStream, Codec>> {
+    let mut master = Framed::new(TcpStream::connect(addr).await?, Codec::default());
+    master.send(new_uframe(U_STARTDT_ACTIVE)).await?;
+    let apdu = master.next().await.unwrap()?;
+    match ApciKind::from(apdu.apci) {
+        ApciKind::U(u) => assert_eq!(u.function, U_STARTDT_CONFIRM),
+        _ => panic!("expect U-frame"),
+    }
+    Ok(master)
+}
+
+// 跳过 S 帧与 U 帧
+async fn next_asdu(master: &mut Framed<TcpStream, Codec>) -> anyhow::Result<Asdu> {
+    loop {
+        if let Some(asdu) = master.next().await.unwrap()?.asdu {
+            return Ok(asdu);
+        }
+    }
+}
+
+async fn active_count(server: &Server, expect_sessions: usize) -> usize {
+    loop {
+        let sessions = server.sessions();
+        if sessions.len() == expect_sessions {
+            return sessions.iter().filter(|(_, active)| *active).count();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn server_single_active_session() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = start_server(Server::new(listener)).await;
+
+    let first = start_dt(addr).await?;
+    assert_eq!(active_count(&server, 1).await, 1);
+    let second = start_dt(addr).await?;
+    assert_eq!(active_count(&server, 2).await, 1);
+    let local = second.get_ref().local_addr()?;
+    assert!(server.sessions().contains(&(local, true)));
+
+    drop(first);
+    drop(second);
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_all_active_sessions() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = start_server(Server::new(listener).with_all_active(true)).await;
+
+    let first = start_dt(addr).await?;
+    let _second = start_dt(addr).await?;
+    assert_eq!(active_count(&server, 2).await, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_broadcast_spontaneous() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = start_server(Server::new(listener)).await;
+    let handle = server.handle();
+
+    let asdu = single(
+        false,
+        CauseOfTransmission::new(false, false, Cause::Spontaneous),
+        0x0001,
+        vec![SinglePointInfo::new(
+            InfoObjAddr::new(0, 100),
+            ObjectSIQ::new_with_value(true),
+            None,
+        )],
+    )?;
+    assert!(handle.broadcast_asdu(asdu.clone()).is_err());
+
+    let mut master = start_dt(addr).await?;
+    assert_eq!(active_count(&server, 1).await, 1);
+    handle.broadcast_asdu(asdu)?;
+
+    let apdu = master.next().await.unwrap()?;
+    assert!(matches!(ApciKind::from(apdu.apci), ApciKind::I(_)));
+    let mut asdu = apdu.asdu.unwrap();
+    assert_eq!(asdu.identifier.type_id, TypeID::M_SP_NA_1);
+    assert_eq!(asdu.identifier.cot.cause().get(), Cause::Spontaneous);
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_buffer_offline_spontaneous() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server =
+        Server::new(listener).with_send_queue(SendQueueOption::default().with_buffer_offline(true));
+    let server = start_server(server).await;
+    let handle = server.handle();
+
+    // 没有激活的连接时缓存突发数据
+    for addr in [100, 101] {
+        handle.broadcast_asdu(single(
+            false,
+            CauseOfTransmission::new(false, false, Cause::Spontaneous),
+            0x0001,
+            vec![SinglePointInfo::new(
+                InfoObjAddr::new(0, addr),
+                ObjectSIQ::new_with_value(true),
+                None,
+            )],
+        )?)?;
+    }
+
+    let mut master = start_dt(addr).await?;
+    for expect in [100, 101] {
+        let apdu = master.next().await.unwrap()?;
+        let mut asdu = apdu.asdu.unwrap();
+        assert_eq!(asdu.identifier.cot.cause().get(), Cause::Spontaneous);
+        let mut infos = asdu.get_single_point()?;
+        assert_eq!(infos[0].ioa.addr().get(), expect);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_response_keeps_orig_addr() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener)).await;
+    let mut master = start_dt(addr).await?;
+
+    // 处理函数返回的响应
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let req = interrogation_cmd(cot, 0x0001, ObjectQOI::new(20))?.with_orig_addr(7);
+    master.send(new_iframe(req, 0, 0)).await?;
+    let asdu = next_asdu(&mut master).await?;
+    assert_eq!(asdu.identifier.type_id, TypeID::C_IC_NA_1);
+    assert_eq!(asdu.identifier.orig_addr, 7);
+
+    // 镜像响应
+    let req = reset_process_cmd(cot, 0x0001, Qrp::ResetProcess)?.with_orig_addr(9);
+    master.send(new_iframe(req, 1, 1)).await?;
+    let mut asdu = next_asdu(&mut master).await?;
+    assert_eq!(asdu.identifier.cot.cause().get(), Cause::ActivationCon);
+    assert_eq!(asdu.identifier.orig_addr, 9);
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_answers_unknown_type_id() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener)).await;
+    let mut master = start_dt(addr).await?;
+
+    // 保留的类型标识 52 不中断连接, 以否定的镜像回复
+    let req = Asdu::try_from(bytes::Bytes::from_static(&[
+        52, 0x01, 0x06, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00,
+    ]))?;
+    assert_eq!(req.identifier.type_id, TypeID::Unknown(52));
+    master.send(new_iframe(req, 0, 0)).await?;
+    let mut asdu = next_asdu(&mut master).await?;
+    assert_eq!(asdu.identifier.type_id, TypeID::Unknown(52));
+    assert_eq!(asdu.identifier.cot.cause().get(), Cause::UnknownTypeID);
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_negative_confirm_is_error() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener)).await;
+
+    let op = ClientOption::new(addr, false).with_command_timeout(Duration::from_secs(1));
+    let client = Client::new(NopHandler, op);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    let result = client
+        .single_cmd_confirmed(
+            TypeID::C_SC_NA_1,
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            0x0001,
+            SingleCommandInfo::new(100, true, false),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_SC_NA_1,
+            Cause::ActivationCon
+        ))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn session_stats_count_frames() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = start_server(Server::new(listener)).await;
+
+    let client = Client::new(NopHandler, ClientOption::new(addr, false));
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+    let _ = client
+        .single_cmd_confirmed(
+            TypeID::C_SC_NA_1,
+            CauseOfTransmission::new(false, false, Cause::Activation),
+            0x0001,
+            SingleCommandInfo::new(100, true, false),
+        )
+        .await;
+
+    let stats = client.stats();
+    assert_eq!(stats.frames_tx.u, 1);
+    assert_eq!(stats.frames_tx.i, 1);
+    assert_eq!(stats.frames_rx.u, 1);
+    assert_eq!(stats.frames_rx.i, 1);
+    assert_eq!(stats.asdus_tx.get(&TypeID::C_SC_NA_1), Some(&1));
+    assert_eq!(stats.asdus_rx.get(&TypeID::C_SC_NA_1), Some(&1));
+    // STARTDT: 6 字节, 单命令 I 帧: 6 + 6 + 4 字节
+    assert_eq!(stats.bytes_tx, 6 + 16);
+    assert_eq!(stats.seq_errors, 0);
+    assert!(stats.last_rx.is_some());
+
+    let sessions = server.stats();
+    assert_eq!(sessions.len(), 1);
+    let (peer, stats) = &sessions[0];
+    assert_eq!(server.sessions()[0].0, *peer);
+    assert_eq!(stats.frames_rx.i, 1);
+    assert_eq!(stats.bytes_rx, 6 + 16);
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<(bool, u8)>>>);
+
+impl FrameObserver for Recorder {
+    fn on_rx(&self, apdu: &Apdu, _at: Instant) {
+        self.0.lock().unwrap().push((false, apdu.apci.ctrl1));
+    }
+
+    fn on_tx(&self, apdu: &Apdu, _at: Instant) {
+        self.0.lock().unwrap().push((true, apdu.apci.ctrl1));
+    }
+}
+
+#[tokio::test]
+async fn frame_observer_sees_raw_frames() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server_frames = Recorder::default();
+    let _server =
+        start_server(Server::new(listener).with_frame_observer(server_frames.clone())).await;
+
+    let client_frames = Recorder::default();
+    let op = ClientOption::new(addr, false).with_frame_observer(client_frames.clone());
+    let client = Client::new(NopHandler, op);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    let startdt = U_STARTDT_ACTIVE | 0x03;
+    let confirm = U_STARTDT_CONFIRM | 0x03;
+    assert_eq!(
+        *client_frames.0.lock().unwrap(),
+        vec![(true, startdt), (false, confirm)]
+    );
+    assert_eq!(
+        *server_frames.0.lock().unwrap(),
+        vec![(false, startdt), (true, confirm)]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn command_authorizer_rejects_commands() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    let server = Server::new(listener).with_command_authorizer(move |req: &CommandRequest| {
+        seen.lock().unwrap().push(*req);
+        match (req.orig_addr, req.ioa) {
+            (_, 100) => Authorization::UnknownIOA,
+            (1, _) => Authorization::Allow,
+            _ => Authorization::Deny,
+        }
+    });
+    let _server = start_server(server).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let cmd = |orig_addr, ioa| {
+        double_cmd(
+            TypeID::C_DC_NA_1,
+            cot,
+            1,
+            DoubleCommandInfo::new(ioa, 2, false),
+        )
+        .map(|asdu| asdu.with_orig_addr(orig_addr))
+    };
+
+    // 未授权的源发站地址
+    master.send_asdu(cmd(2, 5)?).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_DC_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    assert_eq!(asdu.identifier.orig_addr, 2);
+
+    // 隐藏的信息对象地址
+    master.send_asdu(cmd(1, 100)?).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_DC_NA_1, Cause::UnknownIOA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+
+    // 允许的命令交给处理函数, NopServer 对双命令不回复
+    master.send_asdu(cmd(1, 5)?).await?;
+    master.expect_silence(Duration::from_millis(200)).await;
+
+    // 召唤命令不经过授权
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(20))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].type_id, TypeID::C_DC_NA_1);
+    assert_eq!(requests[0].cause, Cause::Activation);
+    assert_eq!((requests[2].ca, requests[2].ioa), (1, 5));
+    assert_eq!(requests[2].peer.ip(), addr.ip());
+    Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_common_addr_uses_local_addrs() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener).with_common_addrs([1, 2])).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+
+    // 广播的总召唤以本站的各公共地址确认
+    master
+        .send_asdu(interrogation_cmd(
+            cot,
+            GLOBAL_COMMON_ADDR,
+            ObjectQOI::new(20),
+        )?)
+        .await?;
+    for ca in [1, 2] {
+        let asdu = master
+            .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+            .await;
+        assert_eq!(asdu.identifier.common_addr, ca);
+    }
+
+    // 控制命令不允许使用广播公共地址
+    master
+        .send_asdu(single_cmd(
+            TypeID::C_SC_NA_1,
+            cot,
+            GLOBAL_COMMON_ADDR,
+            SingleCommandInfo::new(1, true, false),
+        )?)
+        .await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_SC_NA_1, Cause::UnknownCA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn clock_sync_answers_with_local_clock() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server =
+        start_server(Server::new(listener).with_clock_sync(ClockSyncMode::LocalClock)).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let sent = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+    let before = Utc::now();
+    master
+        .send_asdu(clock_synchronization_cmd(cot, 1, sent)?)
+        .await?;
+    let mut asdu = master
+        .expect_asdu_with(TypeID::C_CS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(!asdu.identifier.cot.is_negative());
+    let (_, time) = asdu.get_clock_synchronization_time()?;
+    let time = time.expect("valid local time");
+    assert!(time.time >= before - chrono::Duration::milliseconds(1));
+    assert!(!time.invalid);
+    Ok(())
+}
+
+#[tokio::test]
+async fn clock_sync_applies_received_time() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let applied = Arc::new(Mutex::new(Vec::new()));
+    let record = applied.clone();
+    let apply = ClockSyncMode::Apply(Arc::new(move |time: Cp56Time2a| {
+        record.lock().unwrap().push(time);
+        // 拒绝标记为无效的时间
+        !time.invalid
+    }));
+    let _server = start_server(Server::new(listener).with_clock_sync(apply)).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let time =
+        Cp56Time2a::new(Utc.with_ymd_and_hms(2024, 7, 4, 5, 6, 7).unwrap()).with_summer_time(true);
+
+    master
+        .send_asdu(clock_synchronization_cmd_cp56(cot, 1, time)?)
+        .await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_CS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(!asdu.identifier.cot.is_negative());
+
+    master
+        .send_asdu(clock_synchronization_cmd_cp56(
+            cot,
+            1,
+            time.with_invalid(true),
+        )?)
+        .await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_CS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+
+    assert_eq!(
+        *applied.lock().unwrap(),
+        vec![time, time.with_invalid(true)]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn inactive_iframe_is_ignored() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(
+        Server::new(listener).with_inactive_iframe_policy(InactiveIFramePolicy::Ignore),
+    )
+    .await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(20))?)
+        .await?;
+    master.expect_silence(Duration::from_millis(300)).await;
+
+    // 激活后照常处理
+    master.start_dt().await?;
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(20))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn inactive_iframe_closes_connection() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(
+        Server::new(listener).with_inactive_iframe_policy(InactiveIFramePolicy::Close),
+    )
+    .await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    master
+        .send_asdu(interrogation_cmd(cot, 1, ObjectQOI::new(20))?)
+        .await?;
+    let mut framed = master.into_inner();
+    let closed = tokio::time::timeout(Duration::from_secs(1), framed.next()).await?;
+    assert!(closed.is_none());
+    Ok(())
+}
+
+// 运行服务端, 返回 on_process_error 收到的错误
+fn start_server_with_errors(server: Server) -> tokio::sync::mpsc::UnboundedReceiver<Error> {
+    let (err_tx, err_rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((NopServer, stream)))
+        };
+        let _ = server
+            .serve(&on_connected, move |err| {
+                let _ = err_tx.send(err);
+            })
+            .await;
+    });
+    err_rx
+}
+
+#[tokio::test]
+async fn session_limits_reject_connections() -> anyhow::Result<()> {
+    let option = ServerOption::new()
+        .with_max_sessions(2)
+        .with_max_sessions_per_ip(1)
+        .with_backlog(16);
+    let server = Server::bind("127.0.0.1:0".parse()?, option)?;
+    let addr = server.local_addr()?;
+    let mut errors = start_server_with_errors(server);
+
+    let first = start_dt(addr).await?;
+    // 同一 IP 的第二个连接被关闭
+    let mut second = Framed::new(TcpStream::connect(addr).await?, Codec::default());
+    let closed = tokio::time::timeout(Duration::from_secs(1), second.next()).await?;
+    assert!(closed.is_none());
+    assert!(matches!(
+        errors.recv().await,
+        Some(Error::ErrTooManySessionsPerIp(_))
+    ));
+
+    // 第一个连接关闭后可以重新连接
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let _third = start_dt(addr).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn idle_session_is_closed() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let option = ServerOption::new().with_idle_timeout(Duration::from_millis(300));
+    let mut errors = start_server_with_errors(Server::new(listener).with_option(option));
+
+    let mut master = start_dt(addr).await?;
+    let closed = tokio::time::timeout(Duration::from_secs(2), master.next()).await?;
+    assert!(closed.is_none());
+    assert!(matches!(errors.recv().await, Some(Error::ErrIdleTimeout)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn denied_peer_is_rejected() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let access = AccessControl::new()
+        .allow("127.0.0.0/8".parse::<Cidr>()?)
+        .deny("127.0.0.1".parse::<Cidr>()?);
+    let mut errors = start_server_with_errors(Server::new(listener).with_access_control(access));
+
+    let mut master = Framed::new(TcpStream::connect(addr).await?, Codec::default());
+    let closed = tokio::time::timeout(Duration::from_secs(1), master.next()).await?;
+    assert!(closed.is_none());
+    assert!(matches!(
+        errors.recv().await,
+        Some(Error::ErrAccessDenied(peer)) if peer.ip() == addr.ip()
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn connect_filter_decides_per_peer() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let filter_seen = seen.clone();
+    let server = Server::new(listener).with_connect_filter(move |peer: SocketAddr| {
+        let seen = filter_seen.clone();
+        async move {
+            let mut seen = seen.lock().unwrap();
+            seen.push(peer);
+            // 只接受第一个连接
+            if seen.len() == 1 {
+                Decision::Accept
+            } else {
+                Decision::Reject
+            }
+        }
+    });
+    let mut errors = start_server_with_errors(server);
+
+    let _first = start_dt(addr).await?;
+    let mut second = Framed::new(TcpStream::connect(addr).await?, Codec::default());
+    let closed = tokio::time::timeout(Duration::from_secs(1), second.next()).await?;
+    assert!(closed.is_none());
+    assert!(matches!(
+        errors.recv().await,
+        Some(Error::ErrAccessDenied(_))
+    ));
+    assert_eq!(seen.lock().unwrap().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn command_age_policy_window() {
+    let now = Utc::now();
+    let policy =
+        CommandAgePolicy::new(Duration::from_secs(5)).with_max_skew(Duration::from_secs(1));
+    let tag = |secs: i64| Cp56Time2a::new(now + chrono::Duration::seconds(secs));
+    assert!(policy.accepts(&tag(0), now));
+    assert!(policy.accepts(&tag(-6), now));
+    assert!(!policy.accepts(&tag(-7), now));
+    assert!(policy.accepts(&tag(1), now));
+    assert!(!policy.accepts(&tag(2), now));
+    assert!(!policy.accepts(&tag(0).with_invalid(true), now));
+    assert!(policy
+        .with_reject_invalid(false)
+        .accepts(&tag(0).with_invalid(true), now));
+}
+
+#[tokio::test]
+async fn stale_time_tagged_command_is_rejected() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server =
+        Server::new(listener).with_command_age(CommandAgePolicy::new(Duration::from_secs(10)));
+    let _server = start_server(server).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let cmd = |time| {
+        let mut info = SingleCommandInfo::new(5, true, false);
+        info.time = Some(time);
+        single_cmd(TypeID::C_SC_TA_1, cot, 1, info)
+    };
+
+    master
+        .send_asdu(cmd(Utc::now() - chrono::Duration::minutes(1))?)
+        .await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_SC_TA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+
+    // 时标有效的命令交给处理函数, NopServer 对 C_SC_TA_1 不回复
+    master.send_asdu(cmd(Utc::now())?).await?;
+    master.expect_silence(Duration::from_millis(200)).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn not_permitted_dcs_is_rejected() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener)).await;
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let mut cmd = double_cmd(
+        TypeID::C_DC_NA_1,
+        cot,
+        1,
+        DoubleCommandInfo::new(5, 2, false),
+    )?;
+    // double_cmd 不编码不允许的状态, 直接修改 DCO
+    let mut raw = cmd.raw.to_vec();
+    raw[3] |= 0x03;
+    cmd.raw = raw.into();
+
+    master.send_asdu(cmd).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_DC_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn end_of_initialization_after_startup() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    // 初始化原因 1: 手动复位
+    let coi = ObjectCOI::try_from(0x01).map_err(|_| anyhow::anyhow!("invalid COI"))?;
+    let server = Server::new(listener)
+        .with_common_addrs([1, 2])
+        .with_end_of_initialization(coi);
+    let _server = start_server(server).await;
+
+    let client = Client::new(NopHandler, ClientOption::new(addr, false));
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+    for ca in [1, 2] {
+        match events.recv().await? {
+            ClientEvent::EndOfInitialization { ca: got, mut coi } => {
+                assert_eq!(got, ca);
+                assert_eq!(coi.cause().get().value(), 1);
+            }
+            event => panic!("expect end of initialization, got {event:?}"),
+        }
+    }
+
+    // 只在启动后上送一次
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    master.expect_silence(Duration::from_millis(200)).await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_command_round_trip() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener)).await;
+
+    let op = ClientOption::new(addr, false).with_command_timeout(Duration::from_secs(1));
+    let client = Client::new(NopHandler, op);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+    let rtt = client.test_cmd(1).await?;
+    assert!(rtt < Duration::from_secs(1));
+
+    // 测试字错误时回复否定确认
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let mut cmd = test_command(CauseOfTransmission::new(false, false, Cause::Activation), 1)?;
+    let mut raw = cmd.raw.to_vec();
+    raw[3] = 0x00;
+    cmd.raw = raw.into();
+    master.send_asdu(cmd).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_TS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_command_is_rejected_without_closing() -> anyhow::Result<()> {
+    let mut master = serve_in_memory(NopServer);
+    master.start_dt().await?;
+
+    // 信息体截断的总召唤回复未知的信息对象地址, 会话保持
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let mut cmd = interrogation_cmd(cot, 1, ObjectQOI::new(20))?;
+    cmd.raw = cmd.raw.slice(..2);
+    master.send_asdu(cmd).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::UnknownIOA)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+
+    let rtt = test_command(cot, 1)?;
+    master.send_asdu(rtt).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_TS_NA_1, Cause::ActivationCon)
+        .await;
+    assert!(!asdu.identifier.cot.is_negative());
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_command_with_asdu_params() -> anyhow::Result<()> {
+    let params = AsduParams::new(2, 2, 2)?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let _server = start_server(Server::new(listener).with_asdu_params(params)).await;
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?).with_asdu_params(params);
+    master.start_dt().await?;
+
+    // 2 字节的信息对象地址完整, 缺少召唤限定词: 回复未知的传送原因, 会话保持
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    let mut cmd = interrogation_cmd(cot, 1, ObjectQOI::new(20))?;
+    cmd.raw = cmd.raw.slice(..3);
+    master.send_asdu(cmd).await?;
+    let asdu = master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::UnknownCOT)
+        .await;
+    assert!(asdu.identifier.cot.is_negative());
+
+    master.send_asdu(test_command(cot, 1)?).await?;
+    master
+        .expect_asdu_with(TypeID::C_TS_NA_1, Cause::ActivationCon)
+        .await;
+    Ok(())
+}
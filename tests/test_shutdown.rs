@@ -0,0 +1,143 @@
+use std::{future, io, net::SocketAddr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tokio_iecp5::{
+    apci::{U_STOPDT_ACTIVE, U_STOPDT_CONFIRM},
+    asdu::{Asdu, Cause, InfoObjAddr},
+    csys::{ObjectQCC, ObjectQOI, ObjectQRP},
+    test_util::{duplex_connector, spontaneous, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, Server, ServerHandler,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn client_stop_sends_stopdt() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, true);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    // 停止前已提交的 I 帧先发出, 然后停止数据传输, 关闭连接
+    client.send_asdu(spontaneous(1, 1, true)).await?;
+    let peer = async {
+        slave.expect_asdu().await;
+        slave.expect_u(U_STOPDT_ACTIVE).await;
+        slave.send_u(U_STOPDT_CONFIRM).await?;
+        assert!(slave.recv().await.is_none());
+        anyhow::Ok(())
+    };
+    let (_, peer) = tokio::join!(client.stop(), peer);
+    peer?;
+
+    assert_eq!(events.recv().await?, ClientEvent::Deactivated);
+    assert!(matches!(
+        events.recv().await?,
+        ClientEvent::Disconnected(reason) if matches!(*reason, Error::ErrClientStopped)
+    ));
+    assert!(!client.is_connected());
+    // 停止后不再重连
+    assert!(timeout(Duration::from_millis(200), streams.recv())
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_stop_without_stopdt_confirm() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let t1 = Duration::from_millis(300);
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, true).with_t1(t1);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while !client.is_active() {
+        tokio::task::yield_now().await;
+    }
+
+    // 被控站不确认 STOPDT, t1 后关闭连接
+    let peer = async {
+        slave.expect_u(U_STOPDT_ACTIVE).await;
+        assert!(slave.recv().await.is_none());
+    };
+    timeout(t1 * 5, async { tokio::join!(client.stop(), peer) }).await?;
+    assert!(!client.is_connected());
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Station;
+
+impl ServerHandler for Station {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, _qoi: ObjectQOI) -> Self::Future {
+        future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)]))
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn server_shutdown_closes_sessions() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server = Server::new(listener);
+    let handle = server.handle();
+    let serve = tokio::spawn(async move {
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((Station, stream)))
+        };
+        server.serve(&on_connected, |_err| {}).await
+    });
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+
+    // 停止前上送的数据发出并被确认后关闭连接, serve 随之返回
+    handle.broadcast_asdu(spontaneous(1, 1, true))?;
+    handle.shutdown();
+    master.expect_asdu().await;
+    assert!(master.recv().await.is_none());
+    timeout(Duration::from_secs(1), serve).await???;
+
+    assert!(TcpStream::connect(addr).await.is_err());
+    Ok(())
+}
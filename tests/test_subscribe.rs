@@ -0,0 +1,174 @@
+use std::{future, time::Duration};
+
+use futures::StreamExt;
+use tokio::time::timeout;
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr, TypeID},
+    mproc::{measured_value_float_sequence, single, SinglePointInfo},
+    payload::AsduPayload,
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientHandler, ClientOption, Error, PointValue,
+};
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn client_subscribe_points() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    let mut floats = client.subscribe(1, 101..=102).await;
+    let mut singles = client.subscribe(2, ..).await;
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    slave
+        .send_asdu(measured_value_float_sequence(
+            cot,
+            1,
+            InfoObjAddr::new(0, 100),
+            vec![1.0, 2.0, 3.0, 4.0],
+        )?)
+        .await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::InterrogatedByGroup1);
+    slave
+        .send_asdu(single(
+            false,
+            cot,
+            2,
+            vec![SinglePointInfo::new_single(7, true)],
+        )?)
+        .await?;
+
+    let wait = Duration::from_secs(1);
+    let first = timeout(wait, floats.recv()).await?.unwrap();
+    assert_eq!((first.ca, first.ioa), (1, 101));
+    assert_eq!(first.point.value, PointValue::Float(2.0));
+    assert_eq!(first.cause, Cause::Spontaneous);
+    let second = timeout(wait, floats.recv()).await?.unwrap();
+    assert_eq!(second.ioa, 102);
+
+    let update = timeout(wait, singles.recv()).await?.unwrap();
+    assert_eq!((update.ca, update.ioa), (2, 7));
+    assert_eq!(update.point.value, PointValue::Single(true));
+    assert_eq!(update.point.group, 1);
+
+    // 范围之外的点不推送
+    assert!(timeout(Duration::from_millis(50), floats.recv())
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_asdu_and_point_streams() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    let mut asdus = client.asdu_stream().await;
+    let mut points = client
+        .point_stream(1, ..)
+        .await
+        .filter(|u| future::ready(u.point.value != PointValue::Float(1.0)));
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    slave
+        .send_asdu(measured_value_float_sequence(
+            cot,
+            1,
+            InfoObjAddr::new(0, 100),
+            vec![1.0, 2.0],
+        )?)
+        .await?;
+    slave
+        .send_asdu(single(
+            false,
+            cot,
+            3,
+            vec![SinglePointInfo::new_single(7, true)],
+        )?)
+        .await?;
+
+    let wait = Duration::from_secs(1);
+    let first = timeout(wait, asdus.next()).await?.unwrap();
+    assert_eq!(first.identifier.type_id, TypeID::M_ME_NC_1);
+    let second = timeout(wait, asdus.next()).await?.unwrap();
+    assert_eq!(second.identifier.common_addr, 3);
+
+    let update = timeout(wait, points.next()).await?.unwrap();
+    assert_eq!((update.ca, update.ioa), (1, 101));
+    assert_eq!(update.point.value, PointValue::Float(2.0));
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_read_waits_for_response() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::default().with_command_timeout(Duration::from_secs(1));
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    let cot = CauseOfTransmission::new(false, false, Cause::Request);
+    let script = async {
+        slave
+            .expect_asdu_with(TypeID::C_RD_NA_1, Cause::Request)
+            .await;
+        // 其他信息对象的响应不结束等待
+        for ioa in [8, 7] {
+            slave
+                .send_asdu(single(
+                    false,
+                    cot,
+                    1,
+                    vec![SinglePointInfo::new_single(ioa, true)],
+                )?)
+                .await?;
+        }
+        anyhow::Ok(())
+    };
+    let (payload, script) = tokio::join!(client.read(1, 7), script);
+    script?;
+    match payload? {
+        AsduPayload::SinglePoint(mut points) => {
+            assert_eq!(points[0].ioa.addr().get(), 7);
+            assert!(points[0].siq.spi().get());
+        }
+        payload => panic!("expect single point, got {payload:?}"),
+    }
+
+    // 未知的信息对象地址
+    let script = async {
+        let cmd = slave
+            .expect_asdu_with(TypeID::C_RD_NA_1, Cause::Request)
+            .await;
+        slave
+            .send_asdu(cmd.mirror_negative(Cause::UnknownIOA))
+            .await
+    };
+    let (result, script) = tokio::join!(client.read(1, 9), script);
+    script?;
+    assert!(matches!(
+        result,
+        Err(Error::ErrNegativeConfirm(
+            TypeID::C_RD_NA_1,
+            Cause::UnknownIOA
+        ))
+    ));
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::{future, io, net::SocketAddr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::timeout,
+};
+use tokio_iecp5::{
+    apci::new_iframe,
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr},
+    csys::{interrogation_cmd, ObjectQCC, ObjectQOI, ObjectQRP},
+    test_util::{duplex_connector, spontaneous, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, LinkState, Server, ServerHandler,
+};
+
+const T1: Duration = Duration::from_millis(300);
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn client_closes_on_unacknowledged_iframe() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false).with_t1(T1);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    // 确认的 I 帧不会触发 t1
+    client.send_asdu(spontaneous(1, 1, true)).await?;
+    slave.expect_asdu().await;
+    slave.expect_silence(T1 * 2).await;
+    assert!(client.is_connected());
+
+    // 被控站收到 I 帧后不再确认
+    let mut framed = slave.into_inner();
+    client.send_asdu(spontaneous(1, 1, true)).await?;
+    assert!(framed.next().await.is_some());
+    assert!(matches!(
+        timeout(T1 * 5, events.recv()).await??,
+        ClientEvent::Disconnected(reason) if matches!(*reason, Error::ErrT1Timeout)
+    ));
+    // 与 STARTDT 超时一样, 断开之前链路状态已转为 Closed
+    assert_eq!(client.link_state(), LinkState::Closed);
+    assert_eq!(client.stats().timeouts, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_closes_on_sequence_error() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    // 发送序号跳过 0~4
+    slave
+        .send_apdu(new_iframe(spontaneous(1, 1, true), 5, 0))
+        .await?;
+    assert!(matches!(
+        timeout(Duration::from_secs(1), events.recv()).await??,
+        ClientEvent::Disconnected(reason) if matches!(*reason, Error::ErrSequence)
+    ));
+    assert_eq!(client.stats().seq_errors, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_asdu_timeout_waits_for_ack() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let client = Client::new_with_connector(NopHandler, ClientOption::default(), connector);
+    let mut events = client.events();
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    let wait = Duration::from_secs(1);
+    let (sent, _) = tokio::join!(
+        client.send_asdu_timeout(spontaneous(1, 1, true), wait, true),
+        slave.expect_asdu()
+    );
+    sent?;
+
+    // 对端不再确认: 写入传输层即返回, 等待确认则超时
+    let mut framed = slave.into_inner();
+    let wait = Duration::from_millis(100);
+    client
+        .send_asdu_timeout(spontaneous(1, 1, true), wait, false)
+        .await?;
+    assert!(matches!(
+        client
+            .send_asdu_timeout(spontaneous(1, 1, true), wait, true)
+            .await,
+        Err(Error::ErrTimeout)
+    ));
+    assert!(framed.next().await.is_some());
+    assert!(framed.next().await.is_some());
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Station;
+
+impl ServerHandler for Station {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, _qoi: ObjectQOI) -> Self::Future {
+        future::ready(Ok(vec![asdu.mirror(Cause::ActivationCon)]))
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn server_closes_on_unacknowledged_iframe() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let (err_tx, mut err_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let server = Server::new(listener).with_t1(T1);
+        let on_connected = |stream: TcpStream, _: SocketAddr| async move {
+            io::Result::Ok(Some((Station, stream)))
+        };
+        let _ = server
+            .serve(&on_connected, move |err| {
+                let _ = err_tx.send(err);
+            })
+            .await;
+    });
+
+    let mut master = ScriptedPeer::new(TcpStream::connect(addr).await?);
+    master.start_dt().await?;
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    master
+        .send_asdu(interrogation_cmd(cot, 0x0001, ObjectQOI::new(20))?)
+        .await?;
+
+    // 控制站收到激活确认后不再确认
+    let mut framed = master.into_inner();
+    assert!(framed.next().await.is_some());
+    let closed = timeout(T1 * 5, async {
+        while let Some(Ok(_)) = framed.next().await {}
+    })
+    .await;
+    assert!(closed.is_ok());
+    assert!(matches!(err_rx.recv().await, Some(Error::ErrT1Timeout)));
+    Ok(())
+}
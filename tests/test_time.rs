@@ -0,0 +1,75 @@
+use std::io::Cursor;
+
+use bytes::Bytes;
+use chrono::{Duration, FixedOffset, TimeZone, Utc};
+use tokio_iecp5::time::{
+    cp24time2a, cp56time2a, decode_cp24time2a_with, decode_cp56time2a, decode_cp56time2a_with,
+    Cp24Policy, Cp56Time2a, TimeOption,
+};
+
+#[test]
+fn cp56time2a_keeps_milliseconds() -> anyhow::Result<()> {
+    let time = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 15).unwrap() + Duration::milliseconds(123);
+    let raw = cp56time2a(time);
+    assert_eq!(decode_cp56time2a(&mut Cursor::new(&raw))?, Some(time));
+
+    // 日期非法时返回错误
+    let raw = Bytes::from_static(&[0, 0, 0, 0, 0x1f, 0x02, 0x18]);
+    assert!(decode_cp56time2a(&mut Cursor::new(&raw)).is_err());
+    Ok(())
+}
+
+#[test]
+fn cp56time2a_with_offset() -> anyhow::Result<()> {
+    let option = TimeOption::new(FixedOffset::east_opt(8 * 3600).unwrap());
+    let time = Utc.with_ymd_and_hms(2024, 3, 1, 20, 0, 0).unwrap();
+    let raw = Cp56Time2a::new(time).encode_with(&option);
+    // 字段为东八区的本地时间: 3月2日 04 时
+    assert_eq!(raw[3], 4);
+    assert_eq!(raw[4] & 0x1f, 2);
+    assert_eq!(
+        decode_cp56time2a_with(&mut Cursor::new(&raw), &option)?,
+        Some(time)
+    );
+    Ok(())
+}
+
+#[test]
+fn cp24time2a_reconstruct_around_hour_boundary() -> anyhow::Result<()> {
+    let event = Utc.with_ymd_and_hms(2024, 3, 1, 9, 59, 58).unwrap() + Duration::milliseconds(500);
+    let raw = cp24time2a(event);
+
+    // 整点后收到整点前的事件, 仍归到前一小时
+    let received = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 1).unwrap();
+    let option = TimeOption::default().with_cp24(Cp24Policy::Reference(received));
+    assert_eq!(
+        decode_cp24time2a_with(&mut Cursor::new(&raw), &option)?,
+        Some(event)
+    );
+
+    let earlier = Utc.with_ymd_and_hms(2024, 3, 1, 9, 40, 0).unwrap();
+    let option = TimeOption::default().with_cp24(Cp24Policy::Reference(earlier));
+    assert_eq!(
+        decode_cp24time2a_with(&mut Cursor::new(&raw), &option)?,
+        Some(event)
+    );
+
+    let option = TimeOption::default().with_cp24(Cp24Policy::Reject);
+    assert!(decode_cp24time2a_with(&mut Cursor::new(&raw), &option).is_err());
+    Ok(())
+}
+
+#[test]
+fn cp24time2a_reconstruct_with_offset() -> anyhow::Result<()> {
+    // 半小时时区: 本地 10:29 的事件, 参考时间为本地 10:31
+    let offset = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+    let event = Utc.with_ymd_and_hms(2024, 3, 1, 4, 59, 0).unwrap();
+    let received = Utc.with_ymd_and_hms(2024, 3, 1, 5, 1, 0).unwrap();
+    let raw = Bytes::from_static(&[0, 0, 29]);
+    let option = TimeOption::new(offset).with_cp24(Cp24Policy::Reference(received));
+    assert_eq!(
+        decode_cp24time2a_with(&mut Cursor::new(&raw), &option)?,
+        Some(event)
+    );
+    Ok(())
+}
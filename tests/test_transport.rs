@@ -0,0 +1,177 @@
+use std::{future, io, sync::Mutex, time::Duration};
+
+use futures::{future::BoxFuture, SinkExt, StreamExt};
+use tokio::{
+    io::{duplex, DuplexStream},
+    net::{TcpListener, TcpStream},
+    sync::broadcast,
+};
+use tokio_iecp5::{
+    apci::{new_uframe, ApciKind, U_STARTDT_ACTIVE, U_STARTDT_CONFIRM},
+    asdu::Asdu,
+    Client, ClientEvent, ClientHandler, ClientOption, Codec, Connector, Error, Keepalive,
+    RedundancyGroup, RemoteAddr, Server, ServerOption, SocketOption, Switchover, TcpConnector,
+    Transport,
+};
+use tokio_util::codec::Framed;
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+// 只能连接一次的内存连接器
+struct DuplexConnector(Mutex<Option<DuplexStream>>);
+
+impl Connector for DuplexConnector {
+    fn connect(&self) -> BoxFuture<'_, io::Result<Box<dyn Transport>>> {
+        let stream = self.0.lock().unwrap().take();
+        Box::pin(async move {
+            match stream {
+                Some(stream) => Ok(Box::new(stream) as Box<dyn Transport>),
+                None => Err(io::ErrorKind::NotConnected.into()),
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn client_over_duplex_stream() -> anyhow::Result<()> {
+    let (local, remote) = duplex(1024);
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false);
+    let client =
+        Client::new_with_connector(NopHandler, op, DuplexConnector(Mutex::new(Some(local))));
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut remote = Framed::new(remote, Codec::default());
+    while !client.is_connected() {
+        tokio::task::yield_now().await;
+    }
+
+    let apdu = remote.next().await.unwrap()?;
+    match ApciKind::from(apdu.apci) {
+        ApciKind::U(u) => assert_eq!(u.function, U_STARTDT_ACTIVE),
+        _ => panic!("expect U-frame"),
+    }
+    remote.send(new_uframe(U_STARTDT_CONFIRM)).await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+    assert!(client.is_active());
+
+    drop(remote);
+    assert!(matches!(events.recv().await?, ClientEvent::Disconnected(_)));
+    Ok(())
+}
+
+async fn expect_uframe(remote: &mut Framed<TcpStream, Codec>, function: u8) -> anyhow::Result<()> {
+    let apdu = remote.next().await.unwrap()?;
+    match ApciKind::from(apdu.apci) {
+        ApciKind::U(u) => assert_eq!(u.function, function),
+        _ => panic!("expect U-frame"),
+    }
+    Ok(())
+}
+
+async fn next_switchover(
+    events: &mut broadcast::Receiver<ClientEvent>,
+) -> anyhow::Result<Switchover> {
+    loop {
+        if let ClientEvent::Switchover(event) = events.recv().await? {
+            return Ok(event);
+        }
+    }
+}
+
+#[tokio::test]
+async fn redundancy_group_switch_over() -> anyhow::Result<()> {
+    let main = TcpListener::bind("127.0.0.1:0").await?;
+    let standby = TcpListener::bind("127.0.0.1:0").await?;
+    let group = RedundancyGroup::new(vec![main.local_addr()?, standby.local_addr()?]);
+    let op = ClientOption::new(main.local_addr()?, true).with_redundancy_group(group);
+    let client = Client::new(NopHandler, op);
+    let mut events = client.events();
+    client.start().await?;
+
+    // 主链路: 激活后断开
+    let (stream, _) = main.accept().await?;
+    let event = next_switchover(&mut events).await?;
+    assert_eq!(event.from, None);
+    assert_eq!(event.to, main.local_addr()?);
+    let mut remote = Framed::new(stream, Codec::default());
+    while !client.is_connected() {
+        tokio::task::yield_now().await;
+    }
+    expect_uframe(&mut remote, U_STARTDT_ACTIVE).await?;
+    remote.send(new_uframe(U_STARTDT_CONFIRM)).await?;
+    while !client.is_active() {
+        tokio::task::yield_now().await;
+    }
+    drop(remote);
+    drop(main);
+
+    // 备用链路: 自动发送 STARTDT
+    let (stream, _) = standby.accept().await?;
+    let event = next_switchover(&mut events).await?;
+    assert_eq!(event.to, standby.local_addr()?);
+    let mut remote = Framed::new(stream, Codec::default());
+    expect_uframe(&mut remote, U_STARTDT_ACTIVE).await?;
+    Ok(())
+}
+
+fn keepalive() -> Keepalive {
+    Keepalive::new()
+        .with_time(Duration::from_secs(30))
+        .with_interval(Duration::from_secs(5))
+        .with_retries(3)
+}
+
+#[tokio::test]
+async fn tcp_connector_applies_socket_option() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    // 取一个空闲端口作为连接的本地地址
+    let local = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+    let socket = SocketOption::new()
+        .with_local_addr(local)
+        .with_keepalive(keepalive());
+    let connector = TcpConnector::new(addr).with_socket(socket);
+
+    let (transport, accepted) = tokio::join!(connector.connect(), listener.accept());
+    let _transport = transport?;
+    let (_stream, peer) = accepted?;
+    assert_eq!(peer, local);
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_bind_with_keepalive() -> anyhow::Result<()> {
+    let option = ServerOption::new().with_keepalive_params(keepalive());
+    let server = Server::bind("127.0.0.1:0".parse()?, option)?;
+    let addr = server.local_addr()?;
+    let _stream = TcpStream::connect(addr).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_connects_by_hostname() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    // localhost 可能先解析为 ::1, 连接失败后尝试 127.0.0.1
+    let op = ClientOption::new_host(format!("localhost:{port}"), false);
+    assert_eq!(op.remote(), &RemoteAddr::Host(format!("localhost:{port}")));
+    let client = Client::new(NopHandler, op);
+    client.start().await?;
+
+    let (stream, _) = listener.accept().await?;
+    let mut remote = Framed::new(stream, Codec::default());
+    expect_uframe(&mut remote, U_STARTDT_ACTIVE).await?;
+    client.stop().await;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use std::{future, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc;
+use tokio_iecp5::{
+    asdu::{Asdu, Cause, CauseOfTransmission, InfoObjAddr, TypeID},
+    csys::{interrogation_cmd, ObjectQCC, ObjectQOI, ObjectQRP},
+    mproc::{single, SinglePointInfo},
+    test_util::{duplex_connector, serve_in_memory, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, ServerHandler,
+};
+
+struct GiServer;
+
+impl ServerHandler for GiServer {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call_interrogation(&self, asdu: Asdu, _qoi: ObjectQOI) -> Self::Future {
+        let data = single(
+            false,
+            CauseOfTransmission::new(false, false, Cause::InterrogatedByStation),
+            asdu.identifier.common_addr,
+            vec![SinglePointInfo::new_single(100, true)],
+        );
+        future::ready(data.map(|data| vec![asdu.mirror(Cause::ActivationCon), data]))
+    }
+    fn call_counter_interrogation(&self, _: Asdu, _qcc: ObjectQCC) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_read(&self, _: Asdu, _ioa: InfoObjAddr) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_clock_sync(&self, _: Asdu, _time: Option<DateTime<Utc>>) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_reset_process(&self, _: Asdu, _qrp: ObjectQRP) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call_delay_acquire(&self, _: Asdu, _msec: u16) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn server_handler_in_memory() -> anyhow::Result<()> {
+    let mut master = serve_in_memory(GiServer);
+    let cot = CauseOfTransmission::new(false, false, Cause::Activation);
+    master.start_dt().await?;
+
+    master
+        .send_asdu(interrogation_cmd(cot, 0x0001, ObjectQOI::new(20))?)
+        .await?;
+    master
+        .expect_asdu_with(TypeID::C_IC_NA_1, Cause::ActivationCon)
+        .await;
+    let mut data = master
+        .expect_asdu_with(TypeID::M_SP_NA_1, Cause::InterrogatedByStation)
+        .await;
+    assert_eq!(data.get_single_point()?[0].ioa.addr().get(), 100);
+    master.expect_silence(Duration::from_millis(50)).await;
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Forward(mpsc::UnboundedSender<Asdu>);
+
+impl ClientHandler for Forward {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, asdu: Asdu) -> Self::Future {
+        let _ = self.0.send(asdu);
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn client_handler_in_memory() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false);
+    let client = Client::new_with_connector(Forward(tx), op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Connected);
+
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    assert_eq!(events.recv().await?, ClientEvent::Activated);
+
+    slave
+        .send_asdu(single(
+            false,
+            CauseOfTransmission::new(false, false, Cause::Spontaneous),
+            0x0001,
+            vec![SinglePointInfo::new_single(7, true)],
+        )?)
+        .await?;
+    let mut asdu = rx.recv().await.unwrap();
+    assert_eq!(asdu.get_single_point()?[0].ioa.addr().get(), 7);
+
+    slave.expect_silence(Duration::from_millis(50)).await;
+    Ok(())
+}
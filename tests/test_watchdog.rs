@@ -0,0 +1,158 @@
+use std::{future, time::Duration};
+
+use futures::StreamExt;
+use tokio::time::{sleep, timeout};
+use tokio_iecp5::{
+    apci::{ApciKind, U_TESTFR_ACTIVE},
+    asdu::{Asdu, Cause, CauseOfTransmission},
+    mproc::{single, SinglePointInfo},
+    test_util::{duplex_connector, ScriptedPeer},
+    Client, ClientEvent, ClientHandler, ClientOption, Error, TestFrPolicy,
+};
+
+const T1: Duration = Duration::from_millis(200);
+
+#[derive(Clone)]
+struct NopHandler;
+
+impl ClientHandler for NopHandler {
+    type Future = future::Ready<Result<Vec<Asdu>, Error>>;
+
+    fn call(&self, _asdu: Asdu) -> Self::Future {
+        future::ready(Ok(Vec::new()))
+    }
+}
+
+#[tokio::test]
+async fn ping_measures_test_frame_round_trip() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false)
+        .with_t1(T1)
+        .with_testfr(TestFrPolicy::disabled().with_max_missed(10));
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    assert!(matches!(
+        client.ping().await,
+        Err(Error::ErrUseClosedConnection)
+    ));
+
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+
+    // 被控站确认测试帧
+    let (rtt, _) = tokio::join!(client.ping(), slave.expect_silence(T1));
+    assert!(rtt? < T1);
+
+    // 被控站不确认时 t1 后超时, 未达到丢失次数时连接保持
+    let mut framed = slave.into_inner();
+    let (rtt, _) = tokio::join!(client.ping(), framed.next());
+    assert!(matches!(rtt, Err(Error::ErrTimeout)));
+    assert!(client.is_connected());
+
+    drop((framed, streams));
+    client.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn link_closes_after_max_missed_test_frames() -> anyhow::Result<()> {
+    let (connector, mut streams) = duplex_connector();
+    let policy = TestFrPolicy::new()
+        .with_interval(Duration::from_millis(100))
+        .with_max_missed(3);
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false)
+        .with_t1(T1)
+        .with_testfr(policy);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+
+    // 不再确认测试帧, 连续丢失 3 次后关闭连接
+    let mut framed = slave.into_inner();
+    let mut testfr = 0;
+    timeout(T1 * 10, async {
+        while let Some(Ok(apdu)) = framed.next().await {
+            if matches!(ApciKind::from(apdu.apci), ApciKind::U(u) if u.function == U_TESTFR_ACTIVE)
+            {
+                testfr += 1;
+            }
+        }
+    })
+    .await?;
+    assert_eq!(testfr, 3);
+    loop {
+        if let ClientEvent::Disconnected(reason) = events.recv().await? {
+            assert!(matches!(*reason, Error::ErrT1Timeout));
+            break;
+        }
+    }
+    assert_eq!(client.stats().timeouts, 1);
+
+    drop(streams);
+    client.stop().await;
+    Ok(())
+}
+
+// 客户端每 20ms 发送一个 I 帧, 持续 10 个 t3, 对端不回复任何帧, 返回期间收到的测试帧个数
+async fn testfr_during_one_way_traffic(policy: TestFrPolicy) -> anyhow::Result<usize> {
+    let (connector, mut streams) = duplex_connector();
+    let op = ClientOption::new("127.0.0.1:2404".parse()?, false)
+        .with_t1(Duration::from_secs(5))
+        .with_testfr(policy);
+    let client = Client::new_with_connector(NopHandler, op, connector);
+    let mut events = client.events();
+    client.start().await?;
+    let mut slave = ScriptedPeer::new(streams.recv().await.unwrap());
+    slave.accept_start_dt().await?;
+    while events.recv().await? != ClientEvent::Activated {}
+
+    let mut framed = slave.into_inner();
+    let cot = CauseOfTransmission::new(false, false, Cause::Spontaneous);
+    let asdu = single(false, cot, 1, vec![SinglePointInfo::new_single(1, true)])?;
+    let mut testfr = 0;
+    let send = async {
+        for _ in 0..50 {
+            client.send_asdu(asdu.clone()).await?;
+            sleep(Duration::from_millis(20)).await;
+        }
+        anyhow::Ok(())
+    };
+    let recv = async {
+        while let Some(Ok(apdu)) = framed.next().await {
+            if matches!(ApciKind::from(apdu.apci), ApciKind::U(u) if u.function == U_TESTFR_ACTIVE)
+            {
+                testfr += 1;
+            }
+        }
+    };
+    tokio::select! {
+        sent = send => sent?,
+        _ = recv => anyhow::bail!("connection closed"),
+    }
+
+    drop((framed, streams));
+    client.stop().await;
+    Ok(testfr)
+}
+
+#[tokio::test]
+async fn one_way_traffic_triggers_testfr_by_default() -> anyhow::Result<()> {
+    let policy = TestFrPolicy::new().with_interval(Duration::from_millis(100));
+    assert!(!policy.suppress_on_traffic());
+    // 只以接收方向计空闲, 发送数据期间仍发送测试帧(未确认前不重复发送)
+    assert_eq!(testfr_during_one_way_traffic(policy).await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn one_way_traffic_suppresses_testfr() -> anyhow::Result<()> {
+    let policy = TestFrPolicy::new()
+        .with_interval(Duration::from_millis(100))
+        .with_suppress_on_traffic(true);
+    assert_eq!(testfr_during_one_way_traffic(policy).await?, 0);
+    Ok(())
+}